@@ -0,0 +1,280 @@
+//! Benchmarks of the builders and API wrappers of hooks-rs, run by hooks-rs-bench.
+//!
+//! Every `bench_<name>` function runs the code it measures once and returns 0, or the
+//! code of the error it ran into. `setup_<name>` sets the mock host up for it. Inputs go
+//! through [black_box], so that they are not folded into the benchmark at compile time
+//! when a real hook would get them from the host.
+
+#![no_std]
+
+use core::hint::black_box;
+use core::mem::MaybeUninit;
+
+use hooks_rs::*;
+
+const HOOK_ACCOUNT: AccountId = AccountId([1; ACC_ID_LEN]);
+const DESTINATION: AccountId = AccountId([2; ACC_ID_LEN]);
+const ISSUED_AMOUNT: [u8; ISSUED_AMOUNT_LEN] = [0xD4; ISSUED_AMOUNT_LEN];
+
+fn code<T>(result: Result<T>) -> i64 {
+    match result {
+        Ok(_) => 0,
+        Err(err) => err.into(),
+    }
+}
+
+fn set_up_host() -> i64 {
+    mock::reset();
+    mock::set_hook_account(HOOK_ACCOUNT);
+    mock::set_otxn_field(FieldId::Account, DESTINATION.as_bytes());
+    mock::set_otxn_field(FieldId::Destination, HOOK_ACCOUNT.as_bytes());
+    mock::set_otxn_field(FieldId::Amount, &ISSUED_AMOUNT);
+    mock::set_otxn_param(b"param", &1000u64.to_be_bytes());
+    mock::set_hook_param(b"param", &1000u64.to_be_bytes());
+    mock::set_state(b"key", &[7; 32]);
+    0
+}
+
+/// Sets the host up with one emitted transaction reserved, which the builders need for
+/// the emit details
+fn set_up_reserved_host() -> i64 {
+    set_up_host();
+    code(etxn_reserve(1))
+}
+
+macro_rules! setup {
+    ($set_up:ident: $($name:ident),* $(,)?) => {
+        $(
+            #[no_mangle]
+            extern "C" fn $name() -> i64 {
+                $set_up()
+            }
+        )*
+    };
+}
+
+setup!(
+    set_up_reserved_host: setup_xrp_payment_builder,
+    setup_trustline_payment_builder,
+    setup_etxn_details,
+    setup_etxn_fee_base,
+);
+setup!(
+    set_up_host: setup_emit_xrp_payment,
+    setup_emit_tracked,
+    setup_hook_account,
+    setup_otxn_field,
+    setup_otxn_fields,
+    setup_otxn_slot_fields,
+    setup_otxn_param,
+    setup_hook_param,
+    setup_hook_param_as,
+    setup_state,
+    setup_state_set,
+    setup_etxn_reserve,
+    setup_trace,
+);
+
+#[no_mangle]
+extern "C" fn bench_xrp_payment_builder() -> i64 {
+    let mut buffer = XrpPaymentBuilder::uninit_buffer();
+    code(XrpPaymentBuilder::new(black_box(Drops(1000)), black_box(&DESTINATION), None, None).build(&mut buffer))
+}
+
+#[no_mangle]
+extern "C" fn bench_trustline_payment_builder() -> i64 {
+    let mut buffer = TrustlinePaymentBuilder::uninit_buffer();
+    code(
+        TrustlinePaymentBuilder::new(black_box(&ISSUED_AMOUNT), black_box(&DESTINATION), None, None)
+            .build(&mut buffer),
+    )
+}
+
+#[no_mangle]
+extern "C" fn bench_emit_xrp_payment() -> i64 {
+    let mut reservation = match EmitReservation::new(1) {
+        Ok(reservation) => reservation,
+        Err(err) => return err.into(),
+    };
+    code(
+        XrpPaymentBuilder::new(black_box(Drops(1000)), black_box(&DESTINATION), None, None)
+            .build_and_emit(&mut reservation),
+    )
+}
+
+#[no_mangle]
+extern "C" fn bench_emit_tracked() -> i64 {
+    let mut reservation = match EmitReservation::new(1) {
+        Ok(reservation) => reservation,
+        Err(err) => return err.into(),
+    };
+    let mut buffer = XrpPaymentBuilder::uninit_buffer();
+    if let Err(err) = XrpPaymentBuilder::new(Drops(1000), black_box(&DESTINATION), None, None).build(&mut buffer)
+    {
+        return err.into();
+    }
+    code(emit_tracked(
+        &mut reservation,
+        &buffer,
+        black_box(b"payout"),
+    ))
+}
+
+#[no_mangle]
+extern "C" fn bench_encode_account() -> i64 {
+    let mut buf = [MaybeUninit::uninit(); 22];
+    TransactionBuffer::new(&mut buf)
+        .encode_account(black_box(&DESTINATION), AccountType::Destination);
+    black_box(buf);
+    0
+}
+
+#[no_mangle]
+extern "C" fn bench_encode_drops() -> i64 {
+    let mut buf = [MaybeUninit::uninit(); 9];
+    TransactionBuffer::new(&mut buf).encode_drops(black_box(1000), AmountType::Amount);
+    black_box(buf);
+    0
+}
+
+#[no_mangle]
+extern "C" fn bench_encode_issued_amount() -> i64 {
+    let mut buf = [MaybeUninit::uninit(); 49];
+    TransactionBuffer::new(&mut buf)
+        .encode_issued_amount(black_box(&ISSUED_AMOUNT), AmountType::Amount);
+    black_box(buf);
+    0
+}
+
+#[no_mangle]
+extern "C" fn bench_encode_u32() -> i64 {
+    let mut buf = [MaybeUninit::uninit(); 5];
+    TransactionBuffer::new(&mut buf).encode_u32(black_box(7), 4);
+    black_box(buf);
+    0
+}
+
+#[no_mangle]
+extern "C" fn bench_hook_account() -> i64 {
+    code(black_box(hook_account()))
+}
+
+#[no_mangle]
+extern "C" fn bench_otxn_field() -> i64 {
+    code(black_box(otxn_field::<ACC_ID_LEN>(black_box(
+        FieldId::Account,
+    ))))
+}
+
+#[no_mangle]
+extern "C" fn bench_otxn_fields() -> i64 {
+    let mut scratch = [MaybeUninit::uninit(); 2 * ACC_ID_LEN + AMOUNT_LEN];
+    let mut fields = OtxnFields::new(&mut scratch);
+    let account = fields.account(black_box(FieldId::Account));
+    let destination = fields.account(black_box(FieldId::Destination));
+    let amount = fields.amount(black_box(FieldId::Amount));
+
+    code(black_box(account))
+        | code(black_box(destination))
+        | code(black_box(amount))
+}
+
+#[no_mangle]
+extern "C" fn bench_otxn_slot_fields() -> i64 {
+    let mut otxn = match OtxnSlot::load() {
+        Ok(otxn) => otxn,
+        Err(err) => return err.into(),
+    };
+    let mut scratch = [MaybeUninit::uninit(); 2 * ACC_ID_LEN + AMOUNT_LEN];
+    let mut fields = OtxnFields::from_slot(&mut otxn, &mut scratch);
+    let account = fields.account(black_box(FieldId::Account));
+    let destination = fields.account(black_box(FieldId::Destination));
+    let amount = fields.amount(black_box(FieldId::Amount));
+
+    code(black_box(account))
+        | code(black_box(destination))
+        | code(black_box(amount))
+}
+
+#[no_mangle]
+extern "C" fn bench_otxn_param() -> i64 {
+    code(black_box(otxn_param::<8>(black_box(b"param"))))
+}
+
+#[no_mangle]
+extern "C" fn bench_hook_param() -> i64 {
+    code(black_box(hook_param::<8>(black_box(b"param"))))
+}
+
+#[no_mangle]
+extern "C" fn bench_hook_param_as() -> i64 {
+    code(black_box(hook_param_as::<u64>(black_box(b"param"))))
+}
+
+#[no_mangle]
+extern "C" fn bench_state() -> i64 {
+    code(black_box(state::<32>(black_box(b"key"))))
+}
+
+#[no_mangle]
+extern "C" fn bench_state_set() -> i64 {
+    code(state_set(black_box(&[8; 32]), black_box(b"key")))
+}
+
+#[no_mangle]
+extern "C" fn bench_etxn_reserve() -> i64 {
+    code(EmitReservation::new(black_box(1)))
+}
+
+#[no_mangle]
+extern "C" fn bench_etxn_details() -> i64 {
+    code(black_box(etxn_details::<138>()))
+}
+
+#[no_mangle]
+extern "C" fn bench_etxn_fee_base() -> i64 {
+    code(etxn_fee_base(black_box(&[0u8; 270])))
+}
+
+#[no_mangle]
+extern "C" fn bench_trace() -> i64 {
+    code(trace(
+        black_box(b"trace"),
+        black_box(b"data"),
+        DataRepr::AsUTF8,
+    ))
+}
+
+#[no_mangle]
+extern "C" fn bench_is_buffer_equal() -> i64 {
+    let equal = is_buffer_equal(black_box(&[7u8; 32]), black_box(&[7u8; 32]));
+    if equal {
+        0
+    } else {
+        -1
+    }
+}
+
+#[no_mangle]
+extern "C" fn bench_hex_encode() -> i64 {
+    let mut out = [0; 64];
+    let result = hex::encode_to(black_box(&[0xA5; 32]), &mut out);
+    black_box(out);
+    code(result)
+}
+
+#[no_mangle]
+extern "C" fn bench_util_raddr() -> i64 {
+    code(util_raddr(black_box(&DESTINATION)))
+}
+
+#[no_mangle]
+extern "C" fn bench_currency_code_from_iso() -> i64 {
+    match currency_code_from_iso(black_box(b"USD")) {
+        Some(currency) => {
+            black_box(currency);
+            0
+        }
+        None => -1,
+    }
+}
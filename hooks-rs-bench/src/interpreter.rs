@@ -0,0 +1,982 @@
+//! Interpreter for the subset of WebAssembly that rustc emits for hooks.
+//!
+//! It runs the integer instructions, memory, globals, tables and control flow of the MVP
+//! along with the sign extension and multi-value proposals, which is everything the
+//! benchmark hooks are built with. Floating point arithmetic isn't supported, since
+//! neither hooks-rs nor the mock host use it. Modules are not validated, so only run
+//! modules produced by rustc.
+
+use std::fmt;
+
+use wasmparser::{
+    BlockType, ConstExpr, DataKind, ElementItems, ElementKind, ExternalKind, FuncType, MemArg,
+    Operator, Parser, Payload, TableInit,
+};
+
+/// Size of a page of linear memory
+const PAGE_SIZE: usize = 65536;
+
+/// Frames on the call stack at most, so that unbounded recursion traps instead of
+/// running out of memory
+const MAX_CALL_DEPTH: usize = 10_000;
+
+/// Error of parsing or running a module
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The module is malformed or uses a feature the interpreter doesn't support
+    Unsupported(String),
+    /// The module has no exported function with this name
+    MissingExport(String),
+    /// Execution trapped, for example because the code panicked
+    Trap(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Unsupported(msg) => write!(f, "unsupported module: {msg}"),
+            Error::MissingExport(name) => write!(f, "no exported function {name}"),
+            Error::Trap(msg) => write!(f, "trap: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<wasmparser::BinaryReaderError> for Error {
+    fn from(err: wasmparser::BinaryReaderError) -> Self {
+        Error::Unsupported(err.to_string())
+    }
+}
+
+fn trap<T>(msg: impl Into<String>) -> Result<T, Error> {
+    Err(Error::Trap(msg.into()))
+}
+
+/// The result of calling an exported function with [Instance::call]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Call {
+    /// Values returned by the function. `i32` values are zero extended, and floats are
+    /// returned as their bits.
+    pub results: Vec<u64>,
+    /// Instructions executed by the call, not counting the instructions of uncounted
+    /// functions
+    pub instructions: u64,
+}
+
+struct Function<'a> {
+    ty: u32,
+    /// Byte length of the body in the module
+    size: usize,
+    locals: Vec<u64>,
+    ops: Vec<Operator<'a>>,
+    /// Index of the matching `end` of every `block`, `loop`, `if` and `else`
+    ends: Vec<usize>,
+    /// Index of the matching `else` of every `if` that has one
+    elses: Vec<Option<usize>>,
+}
+
+struct Label {
+    /// Height of the value stack below the parameters of the block
+    height: usize,
+    /// Number of values a branch to the label carries
+    arity: usize,
+    /// Where a branch to the label continues
+    target: usize,
+    is_loop: bool,
+}
+
+struct Frame {
+    func: usize,
+    pc: usize,
+    locals: usize,
+    labels: usize,
+    stack: usize,
+    counted: bool,
+}
+
+/// An instantiated module, which keeps its memory and globals across calls
+pub struct Instance<'a> {
+    types: Vec<FuncType>,
+    functions: Vec<Function<'a>>,
+    exports: Vec<(&'a str, usize)>,
+    counted: Vec<bool>,
+    table: Vec<Option<usize>>,
+    memory: Vec<u8>,
+    max_pages: usize,
+    globals: Vec<u64>,
+    stack: Vec<u64>,
+    locals: Vec<u64>,
+    labels: Vec<Label>,
+    frames: Vec<Frame>,
+    uncounted_frames: usize,
+    instructions: u64,
+}
+
+impl<'a> Instance<'a> {
+    /// Parses and instantiates a module, which must not have any imports
+    pub fn new(wasm: &'a [u8]) -> Result<Self, Error> {
+        let mut instance = Instance {
+            types: Vec::new(),
+            functions: Vec::new(),
+            exports: Vec::new(),
+            counted: Vec::new(),
+            table: Vec::new(),
+            memory: Vec::new(),
+            max_pages: PAGE_SIZE,
+            globals: Vec::new(),
+            stack: Vec::new(),
+            locals: Vec::new(),
+            labels: Vec::new(),
+            frames: Vec::new(),
+            uncounted_frames: 0,
+            instructions: 0,
+        };
+        let mut function_types = Vec::new();
+        let mut start = None;
+
+        for payload in Parser::new(0).parse_all(wasm) {
+            match payload? {
+                Payload::TypeSection(reader) => {
+                    for ty in reader.into_iter_err_on_gc_types() {
+                        instance.types.push(ty?);
+                    }
+                }
+                Payload::ImportSection(reader) => {
+                    if let Some(import) = reader.into_imports().next() {
+                        let import = import?;
+                        return Err(Error::Unsupported(format!(
+                            "imports {}::{}, build it with the mock feature of hooks-rs",
+                            import.module, import.name
+                        )));
+                    }
+                }
+                Payload::FunctionSection(reader) => {
+                    for ty in reader {
+                        function_types.push(ty?);
+                    }
+                }
+                Payload::TableSection(reader) => {
+                    for table in reader {
+                        let table = table?;
+                        if !instance.table.is_empty() || !matches!(table.init, TableInit::RefNull) {
+                            return Err(Error::Unsupported("more than one table".into()));
+                        }
+                        instance.table = vec![None; table.ty.initial as usize];
+                    }
+                }
+                Payload::MemorySection(reader) => {
+                    for memory in reader {
+                        let memory = memory?;
+                        instance.memory = vec![0; memory.initial as usize * PAGE_SIZE];
+                        if let Some(maximum) = memory.maximum {
+                            instance.max_pages = maximum as usize;
+                        }
+                    }
+                }
+                Payload::GlobalSection(reader) => {
+                    for global in reader {
+                        let value = instance.eval(&global?.init_expr)?;
+                        instance.globals.push(value);
+                    }
+                }
+                Payload::ExportSection(reader) => {
+                    for export in reader {
+                        let export = export?;
+                        if export.kind == ExternalKind::Func {
+                            instance.exports.push((export.name, export.index as usize));
+                        }
+                    }
+                }
+                Payload::StartSection { func, .. } => start = Some(func as usize),
+                Payload::ElementSection(reader) => {
+                    for element in reader {
+                        let element = element?;
+                        let ElementKind::Active {
+                            table_index: None | Some(0),
+                            offset_expr,
+                        } = element.kind
+                        else {
+                            continue;
+                        };
+                        let offset = instance.eval(&offset_expr)? as usize;
+                        let mut functions = Vec::new();
+                        match element.items {
+                            ElementItems::Functions(reader) => {
+                                for func in reader {
+                                    functions.push(Some(func? as usize));
+                                }
+                            }
+                            ElementItems::Expressions(_, reader) => {
+                                for expr in reader {
+                                    functions.push(instance.eval_ref(&expr?)?);
+                                }
+                            }
+                        }
+                        let slots = instance
+                            .table
+                            .get_mut(offset..offset + functions.len())
+                            .ok_or_else(|| {
+                                Error::Unsupported("element segment out of bounds".into())
+                            })?;
+                        slots.copy_from_slice(&functions);
+                    }
+                }
+                Payload::DataSection(reader) => {
+                    for data in reader {
+                        let data = data?;
+                        let DataKind::Active { offset_expr, .. } = data.kind else {
+                            continue;
+                        };
+                        let offset = instance.eval(&offset_expr)? as usize;
+                        instance
+                            .memory
+                            .get_mut(offset..offset + data.data.len())
+                            .ok_or_else(|| Error::Unsupported("data segment out of bounds".into()))?
+                            .copy_from_slice(data.data);
+                    }
+                }
+                Payload::CodeSectionEntry(body) => {
+                    let ty = *function_types
+                        .get(instance.functions.len())
+                        .ok_or_else(|| Error::Unsupported("body without a function".into()))?;
+                    instance.functions.push(Function::new(ty, &body)?);
+                }
+                _ => {}
+            }
+        }
+
+        instance.counted = vec![true; instance.functions.len()];
+        if let Some(start) = start {
+            instance.invoke(start)?;
+        }
+
+        Ok(instance)
+    }
+
+    /// Names of the exported functions, in the order of the export section
+    pub fn exports(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.exports.iter().map(|(name, _)| *name)
+    }
+
+    /// Stops counting the instructions of an exported function and of everything it calls
+    pub fn set_uncounted(&mut self, name: &str) -> Result<(), Error> {
+        let func = self.export(name)?;
+        self.counted[func] = false;
+        Ok(())
+    }
+
+    /// Bytes of code of an exported function and of every function it calls, directly or
+    /// not, counting every function once and leaving out uncounted functions. Functions
+    /// only called through a table are left out too.
+    pub fn code_size(&self, name: &str) -> Result<usize, Error> {
+        let mut reached = vec![false; self.functions.len()];
+        let mut pending = vec![self.export(name)?];
+        let mut size = 0;
+        while let Some(func) = pending.pop() {
+            if reached[func] || !self.counted[func] {
+                continue;
+            }
+            reached[func] = true;
+            size += self.functions[func].size;
+            for op in &self.functions[func].ops {
+                if let Operator::Call { function_index } | Operator::ReturnCall { function_index } =
+                    op
+                {
+                    pending.push(*function_index as usize);
+                }
+            }
+        }
+
+        Ok(size)
+    }
+
+    /// Calls an exported function that takes no parameters
+    pub fn call(&mut self, name: &str) -> Result<Call, Error> {
+        let func = self.export(name)?;
+        if !self.types[self.functions[func].ty as usize]
+            .params()
+            .is_empty()
+        {
+            return Err(Error::Unsupported(format!("{name} takes parameters")));
+        }
+        self.instructions = 0;
+        let results = self.invoke(func)?;
+
+        Ok(Call {
+            results,
+            instructions: self.instructions,
+        })
+    }
+
+    fn export(&self, name: &str) -> Result<usize, Error> {
+        self.exports
+            .iter()
+            .find(|(export, _)| *export == name)
+            .map(|(_, func)| *func)
+            .ok_or_else(|| Error::MissingExport(name.into()))
+    }
+
+    /// Evaluates a constant expression
+    fn eval(&self, expr: &ConstExpr<'_>) -> Result<u64, Error> {
+        let mut reader = expr.get_operators_reader();
+        let value = match reader.read()? {
+            Operator::I32Const { value } => value as u32 as u64,
+            Operator::I64Const { value } => value as u64,
+            Operator::F32Const { value } => value.bits() as u64,
+            Operator::F64Const { value } => value.bits(),
+            Operator::GlobalGet { global_index } => self.globals[global_index as usize],
+            op => return Err(Error::Unsupported(format!("{op:?} in constant expression"))),
+        };
+        Ok(value)
+    }
+
+    /// Evaluates a constant expression producing a function reference
+    fn eval_ref(&self, expr: &ConstExpr<'_>) -> Result<Option<usize>, Error> {
+        match expr.get_operators_reader().read()? {
+            Operator::RefFunc { function_index } => Ok(Some(function_index as usize)),
+            Operator::RefNull { .. } => Ok(None),
+            op => Err(Error::Unsupported(format!("{op:?} in element segment"))),
+        }
+    }
+
+    fn pop(&mut self) -> u64 {
+        self.stack.pop().expect("value stack underflow")
+    }
+
+    fn push(&mut self, value: u64) {
+        self.stack.push(value);
+    }
+
+    fn block_type(&self, ty: BlockType) -> (usize, usize) {
+        match ty {
+            BlockType::Empty => (0, 0),
+            BlockType::Type(_) => (0, 1),
+            BlockType::FuncType(index) => {
+                let ty = &self.types[index as usize];
+                (ty.params().len(), ty.results().len())
+            }
+        }
+    }
+
+    /// The address of an access of `len` bytes, popped from the stack
+    fn address(&mut self, memarg: &MemArg, len: usize) -> Result<usize, Error> {
+        let address = self.pop() as u32 as u64 + memarg.offset;
+        if address + len as u64 > self.memory.len() as u64 {
+            return trap(format!("out of bounds memory access at {address}"));
+        }
+        Ok(address as usize)
+    }
+
+    fn load<const LEN: usize>(&mut self, memarg: &MemArg) -> Result<[u8; LEN], Error> {
+        let address = self.address(memarg, LEN)?;
+        Ok(self.memory[address..address + LEN].try_into().unwrap())
+    }
+
+    fn store(&mut self, memarg: &MemArg, bytes: &[u8]) -> Result<(), Error> {
+        let address = self.address(memarg, bytes.len())?;
+        self.memory[address..address + bytes.len()].copy_from_slice(bytes);
+        Ok(())
+    }
+
+    fn push_frame(&mut self, func: usize) -> Result<(), Error> {
+        if self.frames.len() == MAX_CALL_DEPTH {
+            return trap("call stack exhausted");
+        }
+        let function = &self.functions[func];
+        let params = self.types[function.ty as usize].params().len();
+        let locals = self.locals.len();
+        self.locals
+            .extend_from_slice(&self.stack[self.stack.len() - params..]);
+        self.locals.extend_from_slice(&function.locals);
+        self.stack.truncate(self.stack.len() - params);
+
+        let counted = self.counted[func];
+        if !counted {
+            self.uncounted_frames += 1;
+        }
+        self.frames.push(Frame {
+            func,
+            pc: 0,
+            locals,
+            labels: self.labels.len(),
+            stack: self.stack.len(),
+            counted,
+        });
+        Ok(())
+    }
+
+    fn pop_frame(&mut self) {
+        let frame = self.frames.pop().unwrap();
+        let results = self.types[self.functions[frame.func].ty as usize]
+            .results()
+            .len();
+        let start = self.stack.len() - results;
+        self.stack.drain(frame.stack..start);
+        self.locals.truncate(frame.locals);
+        self.labels.truncate(frame.labels);
+        if !frame.counted {
+            self.uncounted_frames -= 1;
+        }
+    }
+
+    /// Branches to the label `depth` levels up, or returns if it is the function itself
+    fn branch(&mut self, depth: u32) {
+        let frame = self.frames.last().unwrap();
+        let depth = depth as usize;
+        if depth == self.labels.len() - frame.labels {
+            self.pop_frame();
+            return;
+        }
+        let index = self.labels.len() - 1 - depth;
+        let label = &self.labels[index];
+        let start = self.stack.len() - label.arity;
+        self.stack.drain(label.height..start);
+        let target = label.target;
+        if label.is_loop {
+            self.labels.truncate(index + 1);
+        } else {
+            self.labels.truncate(index);
+        }
+        self.frames.last_mut().unwrap().pc = target;
+    }
+
+    fn invoke(&mut self, func: usize) -> Result<Vec<u64>, Error> {
+        let depth = self.frames.len();
+        let results = self.types[self.functions[func].ty as usize].results().len();
+        let result = self.push_frame(func).and_then(|()| self.run(depth));
+        if result.is_err() {
+            // leave the instance usable for the next call
+            self.frames.truncate(depth);
+            self.uncounted_frames = self.frames.iter().filter(|frame| !frame.counted).count();
+            self.stack.clear();
+            self.locals.clear();
+            self.labels.clear();
+        }
+        result?;
+
+        Ok(self.stack.split_off(self.stack.len() - results))
+    }
+
+    /// Runs until the call stack is back at `depth` frames
+    fn run(&mut self, depth: usize) -> Result<(), Error> {
+        macro_rules! unop {
+            ($ty:ty, $f:expr) => {{
+                let a = self.pop() as $ty;
+                let f: fn($ty) -> $ty = $f;
+                self.push(f(a).into_value())
+            }};
+        }
+        macro_rules! binop {
+            ($ty:ty, $f:expr) => {{
+                let b = self.pop() as $ty;
+                let a = self.pop() as $ty;
+                let f: fn($ty, $ty) -> $ty = $f;
+                self.push(f(a, b).into_value())
+            }};
+        }
+        macro_rules! cmp {
+            ($ty:ty, $f:expr) => {{
+                let b = self.pop() as $ty;
+                let a = self.pop() as $ty;
+                let f: fn($ty, $ty) -> bool = $f;
+                self.push(f(a, b).into_value())
+            }};
+        }
+        macro_rules! div {
+            ($ty:ty, $f:ident) => {{
+                let b = self.pop() as $ty;
+                let a = self.pop() as $ty;
+                match a.$f(b) {
+                    Some(c) => self.push(c.into_value()),
+                    None if b == 0 => return trap("integer divide by zero"),
+                    None => return trap("integer overflow"),
+                }
+            }};
+        }
+        macro_rules! load {
+            ($memarg:expr, $ty:ty, $as:ty) => {{
+                let bytes = self.load::<{ core::mem::size_of::<$ty>() }>($memarg)?;
+                self.push((<$ty>::from_le_bytes(bytes) as $as).into_value())
+            }};
+        }
+        macro_rules! store {
+            ($memarg:expr, $ty:ty) => {{
+                let value = self.pop() as $ty;
+                self.store($memarg, &value.to_le_bytes())?
+            }};
+        }
+
+        while self.frames.len() > depth {
+            let frame = self.frames.last_mut().unwrap();
+            let func = frame.func;
+            let pc = frame.pc;
+            let locals = frame.locals;
+            frame.pc += 1;
+            if self.uncounted_frames == 0 {
+                self.instructions += 1;
+            }
+
+            let op = self.functions[func].ops[pc].clone();
+            match &op {
+                Operator::Unreachable => return trap("unreachable, the code probably panicked"),
+                Operator::Nop => {}
+                Operator::Block { blockty } | Operator::Loop { blockty } => {
+                    let is_loop = matches!(op, Operator::Loop { .. });
+                    let (params, results) = self.block_type(*blockty);
+                    self.labels.push(Label {
+                        height: self.stack.len() - params,
+                        arity: if is_loop { params } else { results },
+                        target: if is_loop {
+                            pc + 1
+                        } else {
+                            self.functions[func].ends[pc] + 1
+                        },
+                        is_loop,
+                    });
+                }
+                Operator::If { blockty } => {
+                    let (params, results) = self.block_type(*blockty);
+                    let end = self.functions[func].ends[pc];
+                    let else_pc = self.functions[func].elses[pc];
+                    let condition = self.pop() as u32;
+                    let label = Label {
+                        height: self.stack.len() - params,
+                        arity: results,
+                        target: end + 1,
+                        is_loop: false,
+                    };
+                    if condition != 0 {
+                        self.labels.push(label);
+                    } else if let Some(else_pc) = else_pc {
+                        self.labels.push(label);
+                        self.frames.last_mut().unwrap().pc = else_pc + 1;
+                    } else {
+                        self.frames.last_mut().unwrap().pc = end + 1;
+                    }
+                }
+                Operator::Else => {
+                    // the true branch is done, skip the false one
+                    self.labels.pop();
+                    self.frames.last_mut().unwrap().pc = self.functions[func].ends[pc] + 1;
+                }
+                Operator::End => {
+                    if self.labels.len() > self.frames.last().unwrap().labels {
+                        self.labels.pop();
+                    } else {
+                        self.pop_frame();
+                    }
+                }
+                Operator::Br { relative_depth } => self.branch(*relative_depth),
+                Operator::BrIf { relative_depth } => {
+                    if self.pop() as u32 != 0 {
+                        self.branch(*relative_depth);
+                    }
+                }
+                Operator::BrTable { targets } => {
+                    let index = self.pop() as u32;
+                    let depth = match targets.targets().nth(index as usize) {
+                        Some(depth) => depth?,
+                        None => targets.default(),
+                    };
+                    self.branch(depth);
+                }
+                Operator::Return => self.pop_frame(),
+                Operator::Call { function_index } => self.push_frame(*function_index as usize)?,
+                Operator::CallIndirect {
+                    type_index,
+                    table_index: 0,
+                } => {
+                    let index = self.pop() as u32 as usize;
+                    let Some(Some(callee)) = self.table.get(index).copied() else {
+                        return trap(format!("indirect call to missing element {index}"));
+                    };
+                    if self.types[*type_index as usize]
+                        != self.types[self.functions[callee].ty as usize]
+                    {
+                        return trap("indirect call type mismatch");
+                    }
+                    self.push_frame(callee)?;
+                }
+                Operator::Drop => {
+                    self.pop();
+                }
+                Operator::Select | Operator::TypedSelect { .. } => {
+                    let condition = self.pop() as u32;
+                    let b = self.pop();
+                    let a = self.pop();
+                    self.push(if condition != 0 { a } else { b });
+                }
+                Operator::LocalGet { local_index } => {
+                    let value = self.locals[locals + *local_index as usize];
+                    self.push(value);
+                }
+                Operator::LocalSet { local_index } => {
+                    let value = self.pop();
+                    self.locals[locals + *local_index as usize] = value;
+                }
+                Operator::LocalTee { local_index } => {
+                    let value = *self.stack.last().unwrap();
+                    self.locals[locals + *local_index as usize] = value;
+                }
+                Operator::GlobalGet { global_index } => {
+                    let value = self.globals[*global_index as usize];
+                    self.push(value);
+                }
+                Operator::GlobalSet { global_index } => {
+                    let value = self.pop();
+                    self.globals[*global_index as usize] = value;
+                }
+
+                Operator::I32Load { memarg } | Operator::F32Load { memarg } => {
+                    load!(memarg, u32, u32)
+                }
+                Operator::I64Load { memarg } | Operator::F64Load { memarg } => {
+                    load!(memarg, u64, u64)
+                }
+                Operator::I32Load8S { memarg } => load!(memarg, i8, i32),
+                Operator::I32Load8U { memarg } => load!(memarg, u8, u32),
+                Operator::I32Load16S { memarg } => load!(memarg, i16, i32),
+                Operator::I32Load16U { memarg } => load!(memarg, u16, u32),
+                Operator::I64Load8S { memarg } => load!(memarg, i8, i64),
+                Operator::I64Load8U { memarg } => load!(memarg, u8, u64),
+                Operator::I64Load16S { memarg } => load!(memarg, i16, i64),
+                Operator::I64Load16U { memarg } => load!(memarg, u16, u64),
+                Operator::I64Load32S { memarg } => load!(memarg, i32, i64),
+                Operator::I64Load32U { memarg } => load!(memarg, u32, u64),
+                Operator::I32Store { memarg } | Operator::F32Store { memarg } => {
+                    store!(memarg, u32)
+                }
+                Operator::I64Store { memarg } | Operator::F64Store { memarg } => {
+                    store!(memarg, u64)
+                }
+                Operator::I32Store8 { memarg } | Operator::I64Store8 { memarg } => {
+                    store!(memarg, u8)
+                }
+                Operator::I32Store16 { memarg } | Operator::I64Store16 { memarg } => {
+                    store!(memarg, u16)
+                }
+                Operator::I64Store32 { memarg } => store!(memarg, u32),
+                Operator::MemorySize { mem: 0 } => {
+                    self.push((self.memory.len() / PAGE_SIZE) as u64);
+                }
+                Operator::MemoryGrow { mem: 0 } => {
+                    let pages = self.memory.len() / PAGE_SIZE;
+                    let delta = self.pop() as u32 as usize;
+                    if pages + delta > self.max_pages {
+                        self.push(u32::MAX as u64);
+                    } else {
+                        self.memory.resize((pages + delta) * PAGE_SIZE, 0);
+                        self.push(pages as u64);
+                    }
+                }
+
+                Operator::I32Const { value } => self.push(*value as u32 as u64),
+                Operator::I64Const { value } => self.push(*value as u64),
+                Operator::F32Const { value } => self.push(value.bits() as u64),
+                Operator::F64Const { value } => self.push(value.bits()),
+
+                Operator::I32Eqz => unop!(u32, |a| (a == 0) as u32),
+                Operator::I32Eq => cmp!(u32, |a, b| a == b),
+                Operator::I32Ne => cmp!(u32, |a, b| a != b),
+                Operator::I32LtS => cmp!(i32, |a, b| a < b),
+                Operator::I32LtU => cmp!(u32, |a, b| a < b),
+                Operator::I32GtS => cmp!(i32, |a, b| a > b),
+                Operator::I32GtU => cmp!(u32, |a, b| a > b),
+                Operator::I32LeS => cmp!(i32, |a, b| a <= b),
+                Operator::I32LeU => cmp!(u32, |a, b| a <= b),
+                Operator::I32GeS => cmp!(i32, |a, b| a >= b),
+                Operator::I32GeU => cmp!(u32, |a, b| a >= b),
+                Operator::I64Eqz => unop!(u64, |a| (a == 0) as u64),
+                Operator::I64Eq => cmp!(u64, |a, b| a == b),
+                Operator::I64Ne => cmp!(u64, |a, b| a != b),
+                Operator::I64LtS => cmp!(i64, |a, b| a < b),
+                Operator::I64LtU => cmp!(u64, |a, b| a < b),
+                Operator::I64GtS => cmp!(i64, |a, b| a > b),
+                Operator::I64GtU => cmp!(u64, |a, b| a > b),
+                Operator::I64LeS => cmp!(i64, |a, b| a <= b),
+                Operator::I64LeU => cmp!(u64, |a, b| a <= b),
+                Operator::I64GeS => cmp!(i64, |a, b| a >= b),
+                Operator::I64GeU => cmp!(u64, |a, b| a >= b),
+
+                Operator::I32Clz => unop!(u32, |a| a.leading_zeros()),
+                Operator::I32Ctz => unop!(u32, |a| a.trailing_zeros()),
+                Operator::I32Popcnt => unop!(u32, |a| a.count_ones()),
+                Operator::I32Add => binop!(u32, u32::wrapping_add),
+                Operator::I32Sub => binop!(u32, u32::wrapping_sub),
+                Operator::I32Mul => binop!(u32, u32::wrapping_mul),
+                Operator::I32DivS => div!(i32, checked_div),
+                Operator::I32DivU => div!(u32, checked_div),
+                Operator::I32RemS => {
+                    let b = self.pop() as i32;
+                    let a = self.pop() as i32;
+                    if b == 0 {
+                        return trap("integer divide by zero");
+                    }
+                    self.push(a.wrapping_rem(b).into_value());
+                }
+                Operator::I32RemU => div!(u32, checked_rem),
+                Operator::I32And => binop!(u32, |a, b| a & b),
+                Operator::I32Or => binop!(u32, |a, b| a | b),
+                Operator::I32Xor => binop!(u32, |a, b| a ^ b),
+                Operator::I32Shl => binop!(u32, u32::wrapping_shl),
+                Operator::I32ShrS => binop!(i32, |a, b| a.wrapping_shr(b as u32)),
+                Operator::I32ShrU => binop!(u32, u32::wrapping_shr),
+                Operator::I32Rotl => binop!(u32, u32::rotate_left),
+                Operator::I32Rotr => binop!(u32, u32::rotate_right),
+
+                Operator::I64Clz => unop!(u64, |a| a.leading_zeros() as u64),
+                Operator::I64Ctz => unop!(u64, |a| a.trailing_zeros() as u64),
+                Operator::I64Popcnt => unop!(u64, |a| a.count_ones() as u64),
+                Operator::I64Add => binop!(u64, u64::wrapping_add),
+                Operator::I64Sub => binop!(u64, u64::wrapping_sub),
+                Operator::I64Mul => binop!(u64, u64::wrapping_mul),
+                Operator::I64DivS => div!(i64, checked_div),
+                Operator::I64DivU => div!(u64, checked_div),
+                Operator::I64RemS => {
+                    let b = self.pop() as i64;
+                    let a = self.pop() as i64;
+                    if b == 0 {
+                        return trap("integer divide by zero");
+                    }
+                    self.push(a.wrapping_rem(b).into_value());
+                }
+                Operator::I64RemU => div!(u64, checked_rem),
+                Operator::I64And => binop!(u64, |a, b| a & b),
+                Operator::I64Or => binop!(u64, |a, b| a | b),
+                Operator::I64Xor => binop!(u64, |a, b| a ^ b),
+                Operator::I64Shl => binop!(u64, |a, b| a.wrapping_shl(b as u32)),
+                Operator::I64ShrS => binop!(i64, |a, b| a.wrapping_shr(b as u32)),
+                Operator::I64ShrU => binop!(u64, |a, b| a.wrapping_shr(b as u32)),
+                Operator::I64Rotl => binop!(u64, |a, b| a.rotate_left(b as u32)),
+                Operator::I64Rotr => binop!(u64, |a, b| a.rotate_right(b as u32)),
+
+                Operator::I32WrapI64 => unop!(u64, |a| a as u32 as u64),
+                Operator::I64ExtendI32S => unop!(u64, |a| a as i32 as i64 as u64),
+                Operator::I64ExtendI32U => unop!(u64, |a| a as u32 as u64),
+                Operator::I32Extend8S => unop!(u64, |a| a as i8 as i32 as u32 as u64),
+                Operator::I32Extend16S => unop!(u64, |a| a as i16 as i32 as u32 as u64),
+                Operator::I64Extend8S => unop!(u64, |a| a as i8 as i64 as u64),
+                Operator::I64Extend16S => unop!(u64, |a| a as i16 as i64 as u64),
+                Operator::I64Extend32S => unop!(u64, |a| a as i32 as i64 as u64),
+                Operator::I32ReinterpretF32
+                | Operator::F32ReinterpretI32
+                | Operator::I64ReinterpretF64
+                | Operator::F64ReinterpretI64 => {}
+
+                op => return Err(Error::Unsupported(format!("{op:?}"))),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Conversion to the representation of values on the stack, where `i32` values are zero
+/// extended
+trait IntoValue {
+    fn into_value(self) -> u64;
+}
+
+impl IntoValue for bool {
+    fn into_value(self) -> u64 {
+        self as u64
+    }
+}
+
+impl IntoValue for u32 {
+    fn into_value(self) -> u64 {
+        self as u64
+    }
+}
+
+impl IntoValue for i32 {
+    fn into_value(self) -> u64 {
+        self as u32 as u64
+    }
+}
+
+impl IntoValue for u64 {
+    fn into_value(self) -> u64 {
+        self
+    }
+}
+
+impl IntoValue for i64 {
+    fn into_value(self) -> u64 {
+        self as u64
+    }
+}
+
+impl<'a> Function<'a> {
+    fn new(ty: u32, body: &wasmparser::FunctionBody<'a>) -> Result<Self, Error> {
+        let mut locals = Vec::new();
+        for local in body.get_locals_reader()? {
+            let (count, _) = local?;
+            locals.resize(locals.len() + count as usize, 0);
+        }
+
+        let mut ops = Vec::new();
+        let mut reader = body.get_operators_reader()?;
+        while !reader.eof() {
+            ops.push(reader.read()?);
+        }
+
+        let mut ends = vec![usize::MAX; ops.len()];
+        let mut elses = vec![None; ops.len()];
+        let mut open = Vec::new();
+        for (pc, op) in ops.iter().enumerate() {
+            match op {
+                Operator::Block { .. } | Operator::Loop { .. } | Operator::If { .. } => {
+                    open.push(pc)
+                }
+                Operator::Else => {
+                    let Some(&start) = open.last() else {
+                        return Err(Error::Unsupported("else without if".into()));
+                    };
+                    elses[start] = Some(pc);
+                }
+                Operator::End => {
+                    // the last end closes the function body
+                    if let Some(start) = open.pop() {
+                        ends[start] = pc;
+                        if let Some(else_pc) = elses[start] {
+                            ends[else_pc] = pc;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Function {
+            ty,
+            size: body.range().len(),
+            locals,
+            ops,
+            ends,
+            elses,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn section(id: u8, content: &[u8]) -> Vec<u8> {
+        [&[id, content.len() as u8][..], content].concat()
+    }
+
+    fn body(code: &[u8]) -> Vec<u8> {
+        [&[code.len() as u8][..], code].concat()
+    }
+
+    /// A module with `count`, which sums 1 to 10 in a loop, `double`, `calls_double`
+    /// and `traps`
+    fn module() -> Vec<u8> {
+        let count = body(&[
+            0x01, 0x02, 0x7E, // two i64 locals, the sum and the counter
+            0x03, 0x40, // loop
+            0x20, 0x01, 0x42, 0x01, 0x7C, 0x22, 0x01, // counter += 1
+            0x20, 0x00, 0x7C, 0x21, 0x00, // sum += counter
+            0x20, 0x01, 0x42, 0x0A, 0x54, 0x0D, 0x00, // continue while counter < 10
+            0x0B, // end
+            0x20, 0x00, 0x0B,
+        ]);
+        let double = body(&[0x00, 0x20, 0x00, 0x42, 0x02, 0x7E, 0x0B]);
+        let calls_double = body(&[0x00, 0x42, 0x15, 0x10, 0x01, 0x0B]);
+        let traps = body(&[0x00, 0x00, 0x0B]);
+
+        [
+            &b"\0asm\x01\0\0\0"[..],
+            &section(
+                1,
+                &[0x02, 0x60, 0x00, 0x01, 0x7E, 0x60, 0x01, 0x7E, 0x01, 0x7E],
+            ),
+            &section(3, &[0x04, 0x00, 0x01, 0x00, 0x00]),
+            &section(
+                7,
+                &[
+                    &[0x04, 0x05][..],
+                    b"count\x00\x00\x06double\x00\x01\x0Ccalls_double\x00\x02\x05traps\x00\x03",
+                ]
+                .concat(),
+            ),
+            &section(
+                10,
+                &[&[0x04][..], &count, &double, &calls_double, &traps].concat(),
+            ),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn counts_executed_instructions() {
+        let wasm = module();
+        let mut instance = Instance::new(&wasm).unwrap();
+
+        // loop, 11 instructions for each of the 10 iterations, end, local.get and end
+        assert_eq!(
+            instance.call("count"),
+            Ok(Call {
+                results: vec![55],
+                instructions: 114,
+            })
+        );
+    }
+
+    #[test]
+    fn skips_uncounted_functions() {
+        let wasm = module();
+        let mut instance = Instance::new(&wasm).unwrap();
+
+        assert_eq!(instance.call("calls_double").unwrap().instructions, 7);
+        instance.set_uncounted("double").unwrap();
+        assert_eq!(
+            instance.call("calls_double"),
+            Ok(Call {
+                results: vec![42],
+                instructions: 3,
+            })
+        );
+        assert_eq!(
+            instance.set_uncounted("triple"),
+            Err(Error::MissingExport("triple".into()))
+        );
+    }
+
+    #[test]
+    fn measures_code_size() {
+        let wasm = module();
+        let mut instance = Instance::new(&wasm).unwrap();
+
+        // the bodies of calls_double and double, without their length
+        assert_eq!(instance.code_size("calls_double"), Ok(13));
+        instance.set_uncounted("double").unwrap();
+        assert_eq!(instance.code_size("calls_double"), Ok(6));
+    }
+
+    #[test]
+    fn recovers_from_traps() {
+        let wasm = module();
+        let mut instance = Instance::new(&wasm).unwrap();
+
+        assert!(matches!(instance.call("traps"), Err(Error::Trap(_))));
+        assert_eq!(instance.call("count").unwrap().results, [55]);
+    }
+
+    #[test]
+    fn rejects_imports() {
+        let wasm = [
+            &b"\0asm\x01\0\0\0"[..],
+            &section(1, &[0x01, 0x60, 0x00, 0x00]),
+            &section(2, b"\x01\x03env\x06accept\x00\x00"),
+        ]
+        .concat();
+
+        assert!(
+            matches!(Instance::new(&wasm), Err(Error::Unsupported(msg)) if msg.contains("env::accept"))
+        );
+    }
+}
@@ -0,0 +1,150 @@
+//! Instruction count benchmarks for hooks-rs.
+//!
+//! The host charges for and limits what a hook executes by its instructions rather than by
+//! time, so the benchmarks count the wasm instructions each builder and API wrapper executes
+//! instead of timing them. The benchmark hooks in `hooks` are built with the `mock` feature
+//! of hooks-rs, so that the host functions are part of the module, and run in the
+//! [interpreter]. The instructions executed by the host functions themselves are not
+//! counted, since a real host runs them natively.
+//!
+//! Every exported function named `bench_<name>` is a benchmark. It takes no parameters
+//! and returns 0, or the code of the error it ran into. If the module also exports
+//! `setup_<name>`, it is called before the benchmark without counting its instructions.
+
+#![deny(missing_docs)]
+
+pub mod interpreter;
+
+use std::path::PathBuf;
+use std::process::Command;
+
+pub use interpreter::{Call, Error, Instance};
+
+/// Prefix of the exported functions that are benchmarks
+const BENCH_PREFIX: &str = "bench_";
+
+/// Prefix of the exported functions that set benchmarks up
+const SETUP_PREFIX: &str = "setup_";
+
+/// The number of instructions a hook can execute at most. The host rejects hooks that can
+/// execute more in their worst case, as estimated from their guards.
+pub const HOOK_INSTRUCTION_BUDGET: u64 = 0xFFFF;
+
+/// The instructions executed by one benchmark
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Measurement {
+    /// Name of the benchmark, without the `bench_` prefix
+    pub name: String,
+    /// Instructions executed by the benchmark, not counting its setup and the host functions
+    pub instructions: u64,
+}
+
+/// The code of one benchmark
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeSize {
+    /// Name of the benchmark, without the `bench_` prefix
+    pub name: String,
+    /// Bytes of code of the benchmark and of the functions it calls, not counting the host
+    /// functions, see [Instance::code_size]
+    pub bytes: usize,
+}
+
+/// Builds the benchmark hooks in release mode and returns the wasm module.
+///
+/// # Panics
+/// If cargo fails to build them.
+pub fn build_hooks() -> Vec<u8> {
+    let hooks_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("hooks");
+    // the hooks are built with their own toolchain, set in `hooks/rust-toolchain.toml`
+    let status = Command::new("cargo")
+        .args(["build", "--release"])
+        .current_dir(&hooks_dir)
+        .env_remove("RUSTUP_TOOLCHAIN")
+        .status()
+        .expect("failed to run cargo");
+    assert!(status.success(), "failed to build the benchmark hooks");
+
+    let wasm = hooks_dir.join("target/wasm32-unknown-unknown/release/hooks_rs_bench_hooks.wasm");
+    std::fs::read(&wasm).unwrap_or_else(|err| panic!("failed to read {}: {err}", wasm.display()))
+}
+
+/// Runs every benchmark of a module in the order they are exported, each on a fresh
+/// instance of the module.
+///
+/// Exported functions other than benchmarks and their setup, such as the host functions
+/// defined by the mock, are not counted. A benchmark that doesn't return 0 fails with
+/// [Error::Trap].
+pub fn run(wasm: &[u8]) -> Result<Vec<Measurement>, Error> {
+    let benchmarks: Vec<String> = Instance::new(wasm)?
+        .exports()
+        .filter(|name| name.starts_with(BENCH_PREFIX))
+        .map(String::from)
+        .collect();
+
+    benchmarks
+        .iter()
+        .map(|bench| {
+            let name = &bench[BENCH_PREFIX.len()..];
+            let mut instance = Instance::new(wasm)?;
+            let uncounted: Vec<String> = instance
+                .exports()
+                .filter(|export| {
+                    !export.starts_with(BENCH_PREFIX) && !export.starts_with(SETUP_PREFIX)
+                })
+                .map(String::from)
+                .collect();
+            for export in &uncounted {
+                instance.set_uncounted(export)?;
+            }
+
+            let setup = format!("{SETUP_PREFIX}{name}");
+            if instance.exports().any(|export| export == setup) {
+                check_success(&setup, instance.call(&setup)?)?;
+            }
+            let call = instance.call(bench)?;
+            let instructions = call.instructions;
+            check_success(bench, call)?;
+
+            Ok(Measurement {
+                name: name.into(),
+                instructions,
+            })
+        })
+        .collect()
+}
+
+/// Measures the code of every benchmark of a module in the order they are exported.
+///
+/// The fee of installing a hook grows with the size of its wasm, so this tracks how much
+/// code the builders and API wrappers add to a hook, the way [run] tracks how many
+/// instructions they execute.
+pub fn code_sizes(wasm: &[u8]) -> Result<Vec<CodeSize>, Error> {
+    let mut instance = Instance::new(wasm)?;
+    let exports: Vec<&str> = instance.exports().collect();
+    for export in &exports {
+        if !export.starts_with(BENCH_PREFIX) && !export.starts_with(SETUP_PREFIX) {
+            instance.set_uncounted(export)?;
+        }
+    }
+
+    exports
+        .iter()
+        .filter(|export| export.starts_with(BENCH_PREFIX))
+        .map(|bench| {
+            Ok(CodeSize {
+                name: bench[BENCH_PREFIX.len()..].into(),
+                bytes: instance.code_size(bench)?,
+            })
+        })
+        .collect()
+}
+
+fn check_success(name: &str, call: Call) -> Result<(), Error> {
+    match call.results[..] {
+        [0] => Ok(()),
+        [code] => Err(Error::Trap(format!("{name} returned {}", code as i64))),
+        _ => Err(Error::Unsupported(format!(
+            "{name} must return a single i64"
+        ))),
+    }
+}
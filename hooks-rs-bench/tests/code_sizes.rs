@@ -0,0 +1,62 @@
+use hooks_rs_bench::{build_hooks, code_sizes};
+
+// Upper bounds of the bytes of code of every benchmark. The fee of installing a hook grows
+// with the size of its wasm, so raise a bound only when the growth is worth paying for.
+// Benchmarks calling host functions include HookError::from_code, which a hook has only
+// once however many wrappers it calls, since it isn't inlined into them.
+const MAX_BYTES: &[(&str, usize)] = &[
+    ("xrp_payment_builder", 1000),
+    ("trustline_payment_builder", 950),
+    ("emit_xrp_payment", 1150),
+    ("emit_tracked", 1540),
+    ("encode_account", 100),
+    ("encode_drops", 140),
+    ("encode_issued_amount", 145),
+    ("encode_u32", 95),
+    ("hook_account", 420),
+    ("otxn_field", 450),
+    ("otxn_fields", 780),
+    ("otxn_slot_fields", 1000),
+    ("otxn_param", 340),
+    ("hook_param", 340),
+    ("hook_param_as", 460),
+    ("state", 500),
+    ("state_set", 280),
+    ("etxn_reserve", 220),
+    ("etxn_details", 2450),
+    ("etxn_fee_base", 240),
+    ("trace", 280),
+    ("is_buffer_equal", 240),
+    ("hex_encode", 1600),
+    ("util_raddr", 840),
+    ("currency_code_from_iso", 760),
+];
+
+#[test]
+fn benchmarks_stay_within_their_code_size() {
+    let sizes = code_sizes(&build_hooks()).unwrap_or_else(|err| panic!("{err}"));
+
+    let mut exceeded = Vec::new();
+    for size in &sizes {
+        let max = MAX_BYTES
+            .iter()
+            .find(|(name, _)| *name == size.name)
+            .map(|(_, max)| *max)
+            .unwrap_or_else(|| panic!("no bound for the benchmark {}", size.name));
+        println!("{:<32} {:>8} bytes (max {max})", size.name, size.bytes);
+        if size.bytes > max {
+            exceeded.push(&size.name);
+        }
+    }
+    assert!(
+        exceeded.is_empty(),
+        "benchmarks over their bound: {exceeded:?}"
+    );
+
+    for (name, _) in MAX_BYTES {
+        assert!(
+            sizes.iter().any(|size| size.name == *name),
+            "no benchmark {name}"
+        );
+    }
+}
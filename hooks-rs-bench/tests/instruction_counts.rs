@@ -0,0 +1,66 @@
+use hooks_rs_bench::{build_hooks, run, HOOK_INSTRUCTION_BUDGET};
+
+// Upper bounds of the instructions executed by every benchmark. The fee of running a hook
+// grows with the instructions it can execute, and the host rejects hooks that can execute
+// more than HOOK_INSTRUCTION_BUDGET, so raise a bound only when the growth is worth paying for.
+const MAX_INSTRUCTIONS: &[(&str, u64)] = &[
+    ("xrp_payment_builder", 550),
+    ("trustline_payment_builder", 550),
+    ("emit_xrp_payment", 900),
+    ("emit_tracked", 1050),
+    ("encode_account", 45),
+    ("encode_drops", 75),
+    ("encode_issued_amount", 65),
+    ("encode_u32", 50),
+    ("hook_account", 220),
+    ("otxn_field", 240),
+    ("otxn_fields", 240),
+    ("otxn_slot_fields", 300),
+    ("otxn_param", 150),
+    ("hook_param", 150),
+    ("hook_param_as", 140),
+    ("state", 320),
+    ("state_set", 60),
+    ("etxn_reserve", 35),
+    ("etxn_details", 1100),
+    ("etxn_fee_base", 45),
+    ("trace", 60),
+    ("is_buffer_equal", 1500),
+    ("hex_encode", 2300),
+    ("util_raddr", 300),
+    ("currency_code_from_iso", 200),
+];
+
+#[test]
+fn benchmarks_stay_within_their_bounds() {
+    let measurements = run(&build_hooks()).unwrap_or_else(|err| panic!("{err}"));
+
+    let mut exceeded = Vec::new();
+    for measurement in &measurements {
+        let max = MAX_INSTRUCTIONS
+            .iter()
+            .find(|(name, _)| *name == measurement.name)
+            .map(|(_, max)| *max)
+            .unwrap_or_else(|| panic!("no bound for the benchmark {}", measurement.name));
+        println!(
+            "{:<32} {:>8} instructions (max {max})",
+            measurement.name, measurement.instructions
+        );
+        if measurement.instructions > max.min(HOOK_INSTRUCTION_BUDGET) {
+            exceeded.push(&measurement.name);
+        }
+    }
+    assert!(
+        exceeded.is_empty(),
+        "benchmarks over their bound: {exceeded:?}"
+    );
+
+    for (name, _) in MAX_INSTRUCTIONS {
+        assert!(
+            measurements
+                .iter()
+                .any(|measurement| measurement.name == *name),
+            "no benchmark {name}"
+        );
+    }
+}
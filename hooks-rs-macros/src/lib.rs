@@ -0,0 +1,421 @@
+//! Procedural macros for hooks-rs. Use them through the `hooks-rs` crate,
+//! which re-exports them.
+
+#![deny(missing_docs, unreachable_pub)]
+
+use proc_macro::{Delimiter, Group, TokenStream, TokenTree};
+
+/// Turns a function returning an `Outcome` into the `hook` entry point.
+///
+/// The function can either take no arguments or take the `u32` argument that
+/// `hook` is called with. The generated `hook` calls the guard function once,
+/// calls the function, and then accepts or rollbacks according to the returned `Outcome`.
+///
+/// # Example
+/// ```ignore
+/// #[hook]
+/// fn handle() -> Outcome<'static> {
+///     Outcome::Accept { code: 0, msg: b"ok" }
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn hook(attr: TokenStream, item: TokenStream) -> TokenStream {
+    entry_point("hook", attr, item)
+}
+
+/// Turns a function returning an `Outcome` into the `cbak` entry point.
+///
+/// The function can either take no arguments or take the `u32` argument that
+/// `cbak` is called with, which is 0 if the emitted transaction was applied and
+/// 1 if it expired.
+///
+/// # Example
+/// ```ignore
+/// #[callback]
+/// fn on_callback(what: u32) -> Outcome<'static> {
+///     Outcome::Accept { code: what as i64, msg: b"" }
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn callback(attr: TokenStream, item: TokenStream) -> TokenStream {
+    entry_point("cbak", attr, item)
+}
+
+/// Implements `HookParams` for a struct with named fields, reading each field from
+/// the install-time hook parameter with the same name.
+///
+/// Every field type has to implement `FromHookParam`. A field can be configured with
+/// `#[param(...)]`:
+/// - `name = "..."` reads the field from a parameter with a different name
+/// - `default = <expr>` uses the expression if the parameter is not set
+///
+/// # Example
+/// ```ignore
+/// #[derive(HookParams)]
+/// struct Config {
+///     #[param(name = "min drops", default = 1000)]
+///     min_drops: u64,
+///     owner: AccountId,
+/// }
+/// ```
+#[proc_macro_derive(HookParams, attributes(param))]
+pub fn derive_hook_params(item: TokenStream) -> TokenStream {
+    let (struct_name, _, fields) = match parse_struct(item, "HookParams") {
+        Ok(parsed) => parsed,
+        Err(msg) => return compile_error(&msg),
+    };
+
+    let mut field_inits = String::new();
+    for field in fields {
+        let param_name = field
+            .param_name
+            .unwrap_or_else(|| format!("{:?}", field.name.trim_start_matches("r#")));
+        let read = format!("::hooks_rs::hook_param_as({param_name}.as_bytes())");
+        let init = match field.default {
+            Some(default) => format!(
+                "match {read} {{
+                    ::hooks_rs::Result::Ok(value) => value,
+                    ::hooks_rs::Result::Err(::hooks_rs::HookError::DoesntExist) => {default},
+                    ::hooks_rs::Result::Err(err) => return ::hooks_rs::Result::Err(err),
+                }}"
+            ),
+            None => format!("{read}?"),
+        };
+        field_inits.push_str(&format!("{}: {init},", field.name));
+    }
+
+    format!(
+        "impl ::hooks_rs::HookParams for {struct_name} {{
+            #[inline(always)]
+            fn load() -> ::hooks_rs::Result<Self> {{
+                ::hooks_rs::Result::Ok(Self {{ {field_inits} }})
+            }}
+        }}"
+    )
+    .parse()
+    .expect("generated HookParams implementation is valid Rust")
+}
+
+/// Implements `Record` for a struct with named fields, so that it can be kept in hook
+/// state and read by other hooks with `ForeignTable`.
+///
+/// The fields are laid out one after the other in the order they are declared, after
+/// a version byte set with `#[record(version = ...)]`, 1 if it is not given. Every field
+/// type has to implement `RecordField`.
+///
+/// # Example
+/// ```ignore
+/// #[derive(Record)]
+/// #[record(version = 2)]
+/// struct Balance {
+///     owner: AccountId,
+///     drops: Drops,
+/// }
+/// ```
+#[proc_macro_derive(Record, attributes(record))]
+pub fn derive_record(item: TokenStream) -> TokenStream {
+    let (struct_name, attrs, fields) = match parse_struct(item, "Record") {
+        Ok(parsed) => parsed,
+        Err(msg) => return compile_error(&msg),
+    };
+    let version = match parse_record_attrs(&attrs) {
+        Ok(version) => version,
+        Err(msg) => return compile_error(&msg),
+    };
+
+    let mut len = String::from("0");
+    let mut reads = String::new();
+    let mut writes = String::new();
+    for field in fields {
+        let field_len = format!("<{} as ::hooks_rs::RecordField>::LEN", field.ty);
+        len.push_str(&format!(" + {field_len}"));
+        reads.push_str(&format!(
+            "{}: {{
+                let field = ::hooks_rs::RecordField::read_from(&bytes[pos..pos + {field_len}]);
+                pos += {field_len};
+                field
+            }},",
+            field.name
+        ));
+        writes.push_str(&format!(
+            "::hooks_rs::RecordField::write_to(&self.{}, &mut bytes[pos..pos + {field_len}]);
+            pos += {field_len};",
+            field.name
+        ));
+    }
+
+    format!(
+        "impl ::hooks_rs::Record for {struct_name} {{
+            const VERSION: u8 = {version};
+            const LEN: usize = {{
+                let len = {len};
+                ::core::assert!(
+                    len <= ::hooks_rs::RECORD_MAX_LEN,
+                    \"record doesn't fit in a state entry\"
+                );
+                len
+            }};
+
+            #[inline(always)]
+            #[allow(unused_assignments)]
+            fn read_fields(bytes: &[u8]) -> Self {{
+                let mut pos = 0;
+                Self {{ {reads} }}
+            }}
+
+            #[inline(always)]
+            #[allow(unused_assignments)]
+            fn write_fields(&self, bytes: &mut [u8]) {{
+                let mut pos = 0;
+                {writes}
+            }}
+        }}"
+    )
+    .parse()
+    .expect("generated Record implementation is valid Rust")
+}
+
+fn entry_point(export_name: &str, attr: TokenStream, item: TokenStream) -> TokenStream {
+    if !attr.is_empty() {
+        return compile_error("this attribute does not take any arguments");
+    }
+
+    let (fn_name, takes_arg) = match parse_fn_signature(item.clone()) {
+        Some(signature) => signature,
+        None => return compile_error("this attribute can only be used on a function"),
+    };
+    if fn_name == "hook" || fn_name == "cbak" {
+        return compile_error("the function can't be called `hook` or `cbak`, these are generated");
+    }
+
+    let call_arg = if takes_arg { "reserved" } else { "" };
+    let entry_point: TokenStream = format!(
+        "#[no_mangle]
+        pub extern \"C\" fn {export_name}(reserved: u32) -> i64 {{
+            let _ = reserved;
+            // Every hook needs to import guard function
+            // and use it at least once
+            ::hooks_rs::max_iter(1);
+
+            ::hooks_rs::Outcome::exit({fn_name}({call_arg}))
+        }}"
+    )
+    .parse()
+    .expect("generated entry point is valid Rust");
+
+    let mut output = item;
+    output.extend(entry_point);
+    output
+}
+
+/// Returns the name of the function and whether it takes any arguments
+fn parse_fn_signature(item: TokenStream) -> Option<(String, bool)> {
+    let mut tokens = item.into_iter();
+
+    // skip attributes, visibility and qualifiers like `unsafe`
+    loop {
+        match tokens.next()? {
+            TokenTree::Ident(ident) if ident.to_string() == "fn" => break,
+            _ => {}
+        }
+    }
+    let fn_name = match tokens.next()? {
+        TokenTree::Ident(ident) => ident.to_string(),
+        _ => return None,
+    };
+    // generics are not supported, so the arguments follow right away
+    match tokens.next()? {
+        TokenTree::Group(args) if args.delimiter() == Delimiter::Parenthesis => {
+            Some((fn_name, !args.stream().is_empty()))
+        }
+        _ => None,
+    }
+}
+
+fn compile_error(msg: &str) -> TokenStream {
+    format!("::core::compile_error!({msg:?});")
+        .parse()
+        .expect("compile_error! invocation is valid Rust")
+}
+
+struct ParamField {
+    name: String,
+    ty: String,
+    param_name: Option<String>,
+    default: Option<String>,
+}
+
+/// Returns the name of the struct, its attributes and its fields
+fn parse_struct(
+    item: TokenStream,
+    derive_name: &str,
+) -> Result<(String, Vec<Group>, Vec<ParamField>), String> {
+    let not_supported = format!("{derive_name} can only be derived for structs with named fields");
+    let mut tokens = item.into_iter();
+    let mut attrs = Vec::new();
+
+    // keep attributes, skip visibility
+    loop {
+        match tokens.next() {
+            Some(TokenTree::Ident(ident)) if ident.to_string() == "struct" => break,
+            Some(TokenTree::Group(attr)) if attr.delimiter() == Delimiter::Bracket => {
+                attrs.push(attr)
+            }
+            Some(_) => {}
+            None => return Err(not_supported),
+        }
+    }
+    let struct_name = match tokens.next() {
+        Some(TokenTree::Ident(ident)) => ident.to_string(),
+        _ => return Err(not_supported),
+    };
+    let body = match tokens.next() {
+        Some(TokenTree::Group(body)) if body.delimiter() == Delimiter::Brace => body,
+        Some(TokenTree::Punct(punct)) if punct.as_char() == '<' => {
+            return Err(format!(
+                "{derive_name} can't be derived for generic structs"
+            ))
+        }
+        _ => return Err(not_supported),
+    };
+
+    let mut fields = Vec::new();
+    for field_tokens in split_top_level_commas(body.stream()) {
+        fields.push(parse_field(field_tokens, &not_supported)?);
+    }
+
+    Ok((struct_name, attrs, fields))
+}
+
+fn parse_field(tokens: Vec<TokenTree>, not_supported: &str) -> Result<ParamField, String> {
+    let mut field = ParamField {
+        name: String::new(),
+        ty: String::new(),
+        param_name: None,
+        default: None,
+    };
+    let mut tokens = tokens.into_iter().peekable();
+
+    // attributes
+    while matches!(tokens.peek(), Some(TokenTree::Punct(punct)) if punct.as_char() == '#') {
+        tokens.next();
+        match tokens.next() {
+            Some(TokenTree::Group(attr)) => parse_param_attr(&attr, &mut field)?,
+            _ => return Err("expected an attribute".into()),
+        }
+    }
+
+    // visibility
+    if matches!(tokens.peek(), Some(TokenTree::Ident(ident)) if ident.to_string() == "pub") {
+        tokens.next();
+        if matches!(tokens.peek(), Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Parenthesis)
+        {
+            tokens.next();
+        }
+    }
+
+    field.name = match tokens.next() {
+        Some(TokenTree::Ident(ident)) => ident.to_string(),
+        _ => return Err(not_supported.into()),
+    };
+    match tokens.next() {
+        Some(TokenTree::Punct(punct)) if punct.as_char() == ':' => {}
+        _ => return Err(not_supported.into()),
+    }
+    field.ty = tokens.collect::<TokenStream>().to_string();
+
+    Ok(field)
+}
+
+/// Reads `name` and `default` from `#[param(...)]`, ignoring any other attributes
+fn parse_param_attr(attr: &Group, field: &mut ParamField) -> Result<(), String> {
+    let mut tokens = attr.stream().into_iter();
+    match tokens.next() {
+        Some(TokenTree::Ident(ident)) if ident.to_string() == "param" => {}
+        _ => return Ok(()),
+    }
+    let args = match tokens.next() {
+        Some(TokenTree::Group(args)) if args.delimiter() == Delimiter::Parenthesis => args,
+        _ => return Err("expected #[param(...)]".into()),
+    };
+
+    for arg in split_top_level_commas(args.stream()) {
+        let mut arg = arg.into_iter();
+        let key = match arg.next() {
+            Some(TokenTree::Ident(key)) => key.to_string(),
+            _ => return Err("expected `name = \"...\"` or `default = ...`".into()),
+        };
+        match arg.next() {
+            Some(TokenTree::Punct(punct)) if punct.as_char() == '=' => {}
+            _ => return Err(format!("expected `=` after `{key}`")),
+        }
+        let value: TokenStream = arg.collect();
+        match key.as_str() {
+            "name" => match value.into_iter().next() {
+                Some(TokenTree::Literal(lit)) if lit.to_string().starts_with('"') => {
+                    field.param_name = Some(lit.to_string());
+                }
+                _ => return Err("`name` has to be a string literal".into()),
+            },
+            "default" => field.default = Some(format!("({value})")),
+            _ => return Err(format!("unknown param option `{key}`")),
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads the version from `#[record(version = ...)]`, 1 if there is none
+fn parse_record_attrs(attrs: &[Group]) -> Result<String, String> {
+    let mut version = String::from("1");
+    for attr in attrs {
+        let mut tokens = attr.stream().into_iter();
+        match tokens.next() {
+            Some(TokenTree::Ident(ident)) if ident.to_string() == "record" => {}
+            _ => continue,
+        }
+        let args = match tokens.next() {
+            Some(TokenTree::Group(args)) if args.delimiter() == Delimiter::Parenthesis => args,
+            _ => return Err("expected #[record(version = ...)]".into()),
+        };
+
+        let args: Vec<TokenTree> = args.stream().into_iter().collect();
+        match &args[..] {
+            [TokenTree::Ident(key), TokenTree::Punct(eq), TokenTree::Literal(value)]
+                if key.to_string() == "version" && eq.as_char() == '=' =>
+            {
+                version = value.to_string();
+            }
+            _ => return Err("expected #[record(version = <u8>)]".into()),
+        }
+    }
+
+    Ok(version)
+}
+
+/// Splits at commas that are not nested in a group or in angle brackets
+fn split_top_level_commas(stream: TokenStream) -> Vec<Vec<TokenTree>> {
+    let mut parts = Vec::new();
+    let mut current = Vec::new();
+    let mut angle_depth = 0usize;
+
+    for token in stream {
+        if let TokenTree::Punct(punct) = &token {
+            match punct.as_char() {
+                '<' => angle_depth += 1,
+                '>' => angle_depth = angle_depth.saturating_sub(1),
+                ',' if angle_depth == 0 => {
+                    parts.push(core::mem::take(&mut current));
+                    continue;
+                }
+                _ => {}
+            }
+        }
+        current.push(token);
+    }
+    if !current.is_empty() {
+        parts.push(current);
+    }
+
+    parts
+}
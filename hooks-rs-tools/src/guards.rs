@@ -0,0 +1,250 @@
+//! Static analysis of the guards of a hook wasm.
+//!
+//! A node only installs a hook if it can bound the instructions the hook executes, so every
+//! loop has to call the guard function `_g` before doing anything else that could branch
+//! or call, with the maximum number of iterations of the loop. [max_iter] calls it, but
+//! loops the compiler adds to a hook, such as the ones of `memcpy` or of `core::fmt`, or
+//! hand written loops that forget it, have no guard. The node then rejects the SetHook
+//! transaction without telling which loop is at fault, so [loops] finds them beforehand.
+//!
+//! The rules are a simpler take on the ones of the guard checker hooks-cli runs: from the
+//! start of a loop, `_g` has to be called before any branch, call to another function or
+//! nested loop. Blocks are looked into, since compilers often open one right at the start
+//! of a loop.
+//!
+//! [max_iter]: hooks_rs::max_iter
+
+use std::fmt;
+
+use wasmparser::{BinaryReaderError, Operator};
+
+use crate::module::{Location, Module};
+use crate::validate::GUARD_FUNCTION;
+
+/// A loop of a hook
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Loop {
+    /// Where the `loop` instruction is
+    pub location: Location,
+    /// The guard at its start, `None` if it has none and a node would reject the hook
+    pub guard: Option<Guard>,
+}
+
+/// A call to `_g` guarding a loop
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Guard {
+    /// Where the call is
+    pub location: Location,
+    /// The maximum number of iterations passed to `_g`, if passed as a constant
+    pub max_iter: Option<u32>,
+}
+
+impl fmt::Display for Loop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "loop in {}", self.location)?;
+        match &self.guard {
+            Some(Guard {
+                max_iter: Some(max_iter),
+                ..
+            }) => write!(f, " is guarded for {max_iter} iterations"),
+            Some(Guard { max_iter: None, .. }) => write!(f, " is guarded"),
+            None => write!(f, " has no guard"),
+        }
+    }
+}
+
+/// Find the loops of the hook compiled to `wasm`, along with their guards, or an error if
+/// `wasm` isn't a wasm module
+///
+/// # Example
+/// ```no_run
+/// let wasm = std::fs::read("target/wasm32-unknown-unknown/release/accept.wasm").unwrap();
+///
+/// for unguarded in hooks_rs_tools::loops(&wasm)
+///     .unwrap()
+///     .iter()
+///     .filter(|l| l.guard.is_none())
+/// {
+///     eprintln!("{unguarded}");
+/// }
+/// ```
+pub fn loops(wasm: &[u8]) -> Result<Vec<Loop>, BinaryReaderError> {
+    loops_of(&Module::parse(wasm)?)
+}
+
+/// [loops] of a parsed module
+pub(crate) fn loops_of(module: &Module<'_>) -> Result<Vec<Loop>, BinaryReaderError> {
+    let guard_function = module
+        .imports
+        .iter()
+        .filter(|import| import.ty.is_some())
+        .position(|import| import.name == GUARD_FUNCTION)
+        .map(|index| index as u32);
+    let imported_functions = module.imported_functions();
+
+    let mut loops = Vec::new();
+    for (i, body) in module.bodies.iter().enumerate() {
+        let function = imported_functions + i as u32;
+        let ops = body
+            .get_operators_reader()?
+            .into_iter_with_offsets()
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (start, (op, offset)) in ops.iter().enumerate() {
+            if !matches!(op, Operator::Loop { .. }) {
+                continue;
+            }
+            let guard = find_guard(&ops, start, guard_function).map(|call| Guard {
+                location: module.location(function, ops[call].1),
+                max_iter: constant_arg(&ops, call),
+            });
+            loops.push(Loop {
+                location: module.location(function, *offset),
+                guard,
+            });
+        }
+    }
+
+    Ok(loops)
+}
+
+/// Index of the call to `_g` guarding the loop starting at `start`
+fn find_guard(
+    ops: &[(Operator<'_>, usize)],
+    start: usize,
+    guard_function: Option<u32>,
+) -> Option<usize> {
+    for (i, (op, _)) in ops.iter().enumerate().skip(start + 1) {
+        match op {
+            Operator::Call { function_index } => {
+                return (Some(*function_index) == guard_function).then_some(i);
+            }
+            // the guard may be in a block opened at the start of the loop
+            Operator::Block { .. } => {}
+            Operator::Loop { .. }
+            | Operator::If { .. }
+            | Operator::Else
+            | Operator::End
+            | Operator::Br { .. }
+            | Operator::BrIf { .. }
+            | Operator::BrTable { .. }
+            | Operator::Return
+            | Operator::Unreachable
+            | Operator::CallIndirect { .. }
+            | Operator::ReturnCall { .. }
+            | Operator::ReturnCallIndirect { .. } => return None,
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// The second argument of the call at `call`, the maximum number of iterations of `_g`,
+/// if it's pushed by the instruction just before the call
+fn constant_arg(ops: &[(Operator<'_>, usize)], call: usize) -> Option<u32> {
+    match ops.get(call.checked_sub(1)?)?.0 {
+        Operator::I32Const { value } => Some(value as u32),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A module importing `_g` and `accept`, with a function of (i32) -> i64 made of
+    // `code` exported as `hook`
+    fn module(code: &[u8]) -> Vec<u8> {
+        let mut wasm = b"\0asm\x01\0\0\0".to_vec();
+        // (i32) -> i64, (i32, i32) -> i32
+        section(
+            &mut wasm,
+            1,
+            &[2, 0x60, 1, 0x7f, 1, 0x7e, 0x60, 2, 0x7f, 0x7f, 1, 0x7f],
+        );
+        section(
+            &mut wasm,
+            2,
+            &[
+                2, 3, b'e', b'n', b'v', 2, b'_', b'g', 0, 1, 3, b'e', b'n', b'v', 6, b'a', b'c',
+                b'c', b'e', b'p', b't', 0, 1,
+            ],
+        );
+        section(&mut wasm, 3, &[1, 0]);
+        section(&mut wasm, 7, &[1, 4, b'h', b'o', b'o', b'k', 0, 2]);
+
+        let mut body = vec![0];
+        body.extend_from_slice(code);
+        let mut code_section = vec![1, body.len() as u8];
+        code_section.extend_from_slice(&body);
+        section(&mut wasm, 10, &code_section);
+
+        wasm
+    }
+
+    fn section(wasm: &mut Vec<u8>, id: u8, contents: &[u8]) {
+        wasm.push(id);
+        wasm.push(contents.len() as u8);
+        wasm.extend_from_slice(contents);
+    }
+
+    const LOOP: u8 = 0x03;
+    const BLOCK: u8 = 0x02;
+    const EMPTY: u8 = 0x40;
+    const END: u8 = 0x0b;
+    const BR_IF: u8 = 0x0d;
+    const CALL: u8 = 0x10;
+    const DROP: u8 = 0x1a;
+    const I32_CONST: u8 = 0x41;
+    const LOCAL_GET: u8 = 0x20;
+    // i64.const 0, end
+    const RETURN_0: [u8; 3] = [0x42, 0, END];
+
+    #[test]
+    fn finds_guarded_loops() {
+        // loop, i32.const 1, i32.const 10, call _g, drop, local.get 0, br_if 0, end
+        let mut code = vec![LOOP, EMPTY, I32_CONST, 1, I32_CONST, 10, CALL, 0, DROP];
+        code.extend_from_slice(&[LOCAL_GET, 0, BR_IF, 0, END]);
+        code.extend_from_slice(&RETURN_0);
+
+        let loops = loops(&module(&code)).unwrap();
+
+        assert_eq!(loops.len(), 1);
+        assert_eq!(loops[0].location.function, 2);
+        let guard = loops[0].guard.as_ref().unwrap();
+        assert_eq!(guard.max_iter, Some(10));
+        assert_eq!(guard.location.offset, loops[0].location.offset + 6);
+    }
+
+    #[test]
+    fn looks_for_guards_in_blocks() {
+        // loop, block, local.get 0, local.get 0, call _g, drop, end, end
+        let mut code = vec![
+            LOOP, EMPTY, BLOCK, EMPTY, LOCAL_GET, 0, LOCAL_GET, 0, CALL, 0,
+        ];
+        code.extend_from_slice(&[DROP, END, END]);
+        code.extend_from_slice(&RETURN_0);
+
+        let loops = loops(&module(&code)).unwrap();
+
+        assert_eq!(loops[0].guard.as_ref().unwrap().max_iter, None);
+    }
+
+    #[test]
+    fn finds_unguarded_loops() {
+        // a call to another function before the guard: loop, i32.const 0, call accept,
+        // drop, i32.const 1, i32.const 10, call _g, drop, end
+        let mut code = vec![LOOP, EMPTY, I32_CONST, 0, CALL, 1, DROP];
+        code.extend_from_slice(&[I32_CONST, 1, I32_CONST, 10, CALL, 0, DROP, END]);
+        // a branch before the guard: loop, local.get 0, br_if 0, end
+        code.extend_from_slice(&[LOOP, EMPTY, LOCAL_GET, 0, BR_IF, 0, END]);
+        code.extend_from_slice(&RETURN_0);
+
+        let loops = loops(&module(&code)).unwrap();
+
+        assert_eq!(loops.len(), 2);
+        assert!(loops.iter().all(|l| l.guard.is_none()));
+        assert!(loops[1].to_string().ends_with("has no guard"));
+    }
+}
@@ -0,0 +1,46 @@
+//! Tools to deploy hooks built with hooks-rs.
+//!
+//! `cargo build --release` leaves a hook as a wasm module, while a node installs it from a
+//! SetHook transaction carrying the module as hex in `CreateCode`. [SetHook] builds that
+//! transaction as JSON, ready to be signed and submitted with any XRPL library, and gives
+//! the hash the hook will be known by on the ledger. The `HookOn` and namespace values are
+//! computed with [HookOn](hooks_rs::HookOn) and [Namespace](hooks_rs::Namespace), so they
+//! are the same values a hook computes with [hookon!](hooks_rs::hookon) and
+//! [Namespace::from_label](hooks_rs::Namespace::from_label).
+//!
+//! The `hooks-rs-tools` binary does the same from the command line:
+//!
+//! ```bash
+//! hooks-rs-tools target/wasm32-unknown-unknown/release/accept.wasm --hook-on PAYMENT INVOKE
+//! ```
+//!
+//! With the `testkit` feature, [testkit] installs hooks on a xahaud node and checks what
+//! they do to the ledger, for end-to-end tests.
+
+#![deny(missing_docs)]
+
+pub mod guards;
+mod module;
+pub mod set_hook;
+pub mod sha512;
+#[cfg(feature = "testkit")]
+pub mod testkit;
+pub mod validate;
+
+pub use guards::{loops, Guard, Loop};
+pub use module::Location;
+pub use set_hook::*;
+pub use sha512::{sha512, sha512_half};
+pub use validate::{validate, Violation};
+
+/// Encode bytes as uppercase hex, the way the XRPL shows blobs and hashes in JSON
+pub fn hex(bytes: &[u8]) -> String {
+    const DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+
+    let mut hex = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        hex.push(DIGITS[(byte >> 4) as usize] as char);
+        hex.push(DIGITS[(byte & 0x0F) as usize] as char);
+    }
+    hex
+}
@@ -0,0 +1,185 @@
+use std::path::Path;
+use std::process::ExitCode;
+
+use hooks_rs::{AccountId, Namespace};
+use hooks_rs_tools::{
+    default_namespace, hex, loops, sha512_half, txn_type_from_name, validate, HookGrant, SetHook,
+    MAX_HOOK_GRANTS,
+};
+
+const USAGE: &str = "\
+Prints the SetHook transaction installing a hook as JSON, after checking that a node
+would accept the hook
+
+Usage: hooks-rs-tools <HOOK.wasm> --hook-on <TXN_TYPE>... [OPTIONS]
+
+Options:
+  --hook-on <TXN_TYPE>...  Transaction types triggering the hook, in UPPERCASE
+                           (example: --hook-on PAYMENT TICKET_CREATE INVOKE)
+  --namespace <SEED>       Seed of the namespace of the hook, as hooks-cli takes it
+                           [default: <HOOK>namespace]
+  --namespace-label <LABEL>
+                           Label of the namespace of the hook, as hooks derive it with
+                           Namespace::from_label
+  --account <R_ADDRESS>    Account installing the hook
+  --flags <FLAGS>          Flags of the hook, such as 1 to override an installed hook
+  --grant <HOOK_HASH>[:<R_ADDRESS>]
+                           Lets the hook with the hash write to the state of the hook,
+                           when installed on the account if given (repeatable, up to 8)
+  --out <FILE>             Writes the JSON to a file instead of stdout
+  --guards                 Lists every loop of the hook and its guard";
+
+fn main() -> ExitCode {
+    match run(std::env::args().skip(1).collect()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}\n\n{USAGE}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn value(args: &mut impl Iterator<Item = String>, option: &str) -> Result<String, String> {
+    args.next().ok_or(format!("{option} needs a value"))
+}
+
+fn run(args: Vec<String>) -> Result<(), String> {
+    let mut wasm_path = None;
+    let mut txn_types = Vec::new();
+    let mut namespace_seed = None;
+    let mut namespace_label = None;
+    let mut account = None;
+    let mut flags = 0;
+    let mut grants = Vec::new();
+    let mut out = None;
+    let mut list_guards = false;
+
+    let mut args = args.into_iter().peekable();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--hook-on" => {
+                while let Some(name) = args.next_if(|arg| !arg.starts_with("--")) {
+                    let txn_type = txn_type_from_name(&name)
+                        .ok_or(format!("invalid transaction type for --hook-on: {name}"))?;
+                    txn_types.push(txn_type);
+                }
+            }
+            "--namespace" => namespace_seed = Some(value(&mut args, &arg)?),
+            "--namespace-label" => namespace_label = Some(value(&mut args, &arg)?),
+            "--account" => {
+                let r_address = value(&mut args, &arg)?;
+                account = Some(
+                    AccountId::from_raddress(r_address.as_bytes())
+                        .ok_or(format!("invalid r-address: {r_address}"))?,
+                );
+            }
+            "--flags" => {
+                let value = value(&mut args, &arg)?;
+                flags = value
+                    .parse()
+                    .map_err(|_| format!("invalid flags: {value}"))?;
+            }
+            "--grant" => {
+                let value = value(&mut args, &arg)?;
+                let (hook_hash, authorize) = match value.split_once(':') {
+                    Some((hook_hash, r_address)) => (hook_hash, Some(r_address)),
+                    None => (value.as_str(), None),
+                };
+                grants.push(HookGrant {
+                    hook_hash: hooks_rs::hex::decode_array(hook_hash.as_bytes())
+                        .ok_or(format!("invalid hook hash for --grant: {hook_hash}"))?,
+                    authorize: match authorize {
+                        Some(r_address) => Some(
+                            AccountId::from_raddress(r_address.as_bytes())
+                                .ok_or(format!("invalid r-address: {r_address}"))?,
+                        ),
+                        None => None,
+                    },
+                });
+            }
+            "--out" => out = Some(value(&mut args, &arg)?),
+            "--guards" => list_guards = true,
+            "--help" | "-h" => {
+                println!("{USAGE}");
+                return Ok(());
+            }
+            _ if arg.starts_with("--") => return Err(format!("unknown option {arg}")),
+            _ if wasm_path.is_none() => wasm_path = Some(arg),
+            _ => return Err(format!("unexpected argument {arg}")),
+        }
+    }
+
+    let wasm_path = wasm_path.ok_or("missing the path of the hook wasm")?;
+    // like hooks-cli, require it, since a hook triggered by nothing is a mistake
+    if txn_types.is_empty() {
+        return Err("--hook-on needs at least one transaction type".into());
+    }
+    if grants.len() > MAX_HOOK_GRANTS {
+        return Err(format!("a hook has at most {MAX_HOOK_GRANTS} --grant"));
+    }
+
+    let wasm = std::fs::read(&wasm_path).map_err(|err| format!("can't read {wasm_path}: {err}"))?;
+    if list_guards {
+        for l in loops(&wasm).map_err(|err| format!("{wasm_path} isn't wasm: {err}"))? {
+            eprintln!("{l}");
+        }
+    }
+    let violations = validate(&wasm).map_err(|err| format!("{wasm_path} isn't wasm: {err}"))?;
+    if !violations.is_empty() {
+        let violations: Vec<_> = violations.iter().map(|v| format!("  {v}")).collect();
+        return Err(format!(
+            "a node would reject {wasm_path}, since it\n{}",
+            violations.join("\n")
+        ));
+    }
+
+    let hook_name = Path::new(&wasm_path)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or_default();
+    let (namespace, derivation) = match (namespace_seed, namespace_label) {
+        (Some(_), Some(_)) => {
+            return Err("--namespace and --namespace-label can't be given together".into())
+        }
+        (Some(seed), None) => (
+            Namespace::from_seed(seed.as_bytes()),
+            format!("seed \"{seed}\""),
+        ),
+        (None, Some(label)) => (
+            Namespace::from_label(label.as_bytes()),
+            format!("label \"{label}\""),
+        ),
+        (None, None) => (
+            default_namespace(hook_name),
+            format!("seed \"{hook_name}namespace\""),
+        ),
+    };
+
+    let hook_hash = hex(&sha512_half(&wasm));
+    let mut set_hook = SetHook::new(wasm, namespace).flags(flags);
+    for txn_type in txn_types {
+        set_hook = set_hook.trigger_on(txn_type);
+    }
+    if let Some(account) = account {
+        set_hook = set_hook.account(account);
+    }
+    for grant in grants {
+        set_hook = set_hook.grant(grant);
+    }
+
+    // a hook created from its wasm has all of them
+    eprintln!("HookHash: {hook_hash}");
+    eprintln!("HookOn: {}", set_hook.hook_on_hex().unwrap_or_default());
+    eprintln!(
+        "HookNamespace: {} ({derivation})",
+        set_hook.namespace_hex().unwrap_or_default()
+    );
+
+    match out {
+        Some(out) => std::fs::write(&out, set_hook.to_json() + "\n")
+            .map_err(|err| format!("can't write {out}: {err}"))?,
+        None => println!("{}", set_hook.to_json()),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,145 @@
+//! The parts of a wasm module the checks of hooks look at.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use wasmparser::{
+    BinaryReaderError, ExternalKind, FuncType, FunctionBody, KnownCustom, Name, Parser, Payload,
+    TypeRef,
+};
+
+/// Where an instruction is in a module
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    /// Index of the function, counting imported functions first
+    pub function: u32,
+    /// Name of the function in the `name` section, which release builds leave out unless
+    /// built with debug info
+    pub name: Option<String>,
+    /// Byte offset of the instruction in the module
+    pub offset: usize,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "function {}", self.function)?;
+        if let Some(name) = &self.name {
+            write!(f, " ({name})")?;
+        }
+        write!(f, " at offset {:#x}", self.offset)
+    }
+}
+
+/// An imported function or other item
+pub(crate) struct Import<'a> {
+    pub(crate) module: &'a str,
+    pub(crate) name: &'a str,
+    /// Type index of the function, `None` for other kinds of imports
+    pub(crate) ty: Option<u32>,
+}
+
+/// A parsed module
+pub(crate) struct Module<'a> {
+    pub(crate) types: Vec<FuncType>,
+    pub(crate) imports: Vec<Import<'a>>,
+    /// Type index of every function, imported ones first
+    pub(crate) functions: Vec<u32>,
+    /// Exported functions with their index
+    pub(crate) exports: Vec<(&'a str, u32)>,
+    /// Bodies of the functions defined by the module, after the imported ones
+    pub(crate) bodies: Vec<FunctionBody<'a>>,
+    names: HashMap<u32, &'a str>,
+}
+
+impl<'a> Module<'a> {
+    pub(crate) fn parse(wasm: &'a [u8]) -> Result<Self, BinaryReaderError> {
+        let mut module = Module {
+            types: Vec::new(),
+            imports: Vec::new(),
+            functions: Vec::new(),
+            exports: Vec::new(),
+            bodies: Vec::new(),
+            names: HashMap::new(),
+        };
+
+        for payload in Parser::new(0).parse_all(wasm) {
+            match payload? {
+                Payload::TypeSection(reader) => {
+                    for ty in reader.into_iter_err_on_gc_types() {
+                        module.types.push(ty?);
+                    }
+                }
+                Payload::ImportSection(reader) => {
+                    for import in reader.into_imports() {
+                        let import = import?;
+                        let ty = match import.ty {
+                            TypeRef::Func(ty) | TypeRef::FuncExact(ty) => Some(ty),
+                            _ => None,
+                        };
+                        if let Some(ty) = ty {
+                            module.functions.push(ty);
+                        }
+                        module.imports.push(Import {
+                            module: import.module,
+                            name: import.name,
+                            ty,
+                        });
+                    }
+                }
+                Payload::FunctionSection(reader) => {
+                    for ty in reader {
+                        module.functions.push(ty?);
+                    }
+                }
+                Payload::ExportSection(reader) => {
+                    for export in reader {
+                        let export = export?;
+                        if export.kind == ExternalKind::Func {
+                            module.exports.push((export.name, export.index));
+                        }
+                    }
+                }
+                Payload::CodeSectionEntry(body) => module.bodies.push(body),
+                Payload::CustomSection(reader) => {
+                    let KnownCustom::Name(reader) = reader.as_known() else {
+                        continue;
+                    };
+                    // the name section is only for debugging, so a malformed one is ignored
+                    for name in reader.into_iter().flatten() {
+                        if let Name::Function(names) = name {
+                            for naming in names.into_iter().flatten() {
+                                module.names.insert(naming.index, naming.name);
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(module)
+    }
+
+    /// Number of imported functions, which come before the defined ones
+    pub(crate) fn imported_functions(&self) -> u32 {
+        self.imports
+            .iter()
+            .filter(|import| import.ty.is_some())
+            .count() as u32
+    }
+
+    /// Type of the function with the index
+    pub(crate) fn function_type(&self, function: u32) -> Option<&FuncType> {
+        let ty = *self.functions.get(function as usize)?;
+        self.types.get(ty as usize)
+    }
+
+    /// Location of an instruction in the function with the index
+    pub(crate) fn location(&self, function: u32, offset: usize) -> Location {
+        Location {
+            function,
+            name: self.names.get(&function).map(|name| name.to_string()),
+            offset,
+        }
+    }
+}
@@ -0,0 +1,609 @@
+//! SetHook transactions installing a hook from its wasm.
+
+use std::fmt::Write;
+
+use hooks_rs::{AccountId, Hash, HookOn, Namespace, TxnType};
+
+use crate::{hex, sha512_half};
+
+/// `Flags` of a hook: replace the hook already installed at its position
+pub const HSF_OVERRIDE: u32 = 1;
+
+/// `Flags` of a hook: delete the state of the namespace it replaces
+pub const HSF_NSDELETE: u32 = 2;
+
+/// `Flags` of a hook: allow other accounts to run its callback when it isn't set to
+pub const HSF_COLLECT: u32 = 4;
+
+/// Most [HookGrant]s a node accepts on one hook
+pub const MAX_HOOK_GRANTS: usize = 8;
+
+/// Transaction type names accepted by [txn_type_from_name], the same as the ones of
+/// `hooks-cli deploy --hook-on`
+const TXN_TYPE_NAMES: &[(&str, TxnType)] = &[
+    ("PAYMENT", TxnType::Payment),
+    ("ESCROW_CREATE", TxnType::EscrowCreate),
+    ("ESCROW_FINISH", TxnType::EscrowFinish),
+    ("ACCOUNT_SET", TxnType::AccountSet),
+    ("ESCROW_CANCEL", TxnType::EscrowCancel),
+    ("REGULAR_KEY_SET", TxnType::RegularKeySet),
+    ("OFFER_CREATE", TxnType::OfferCreate),
+    ("OFFER_CANCEL", TxnType::OfferCancel),
+    ("TICKET_CREATE", TxnType::TicketCreate),
+    ("TICKET_CANCEL", TxnType::TicketCancel),
+    ("SIGNER_LIST_SET", TxnType::SignerListSet),
+    ("PAYCHAN_CREATE", TxnType::PaychanCreate),
+    ("PAYCHAN_FUND", TxnType::PaychanFund),
+    ("PAYCHAN_CLAIM", TxnType::PaychanClaim),
+    ("CHECK_CREATE", TxnType::CheckCreate),
+    ("CHECK_CASH", TxnType::CheckCash),
+    ("CHECK_CANCEL", TxnType::CheckCancel),
+    ("DEPOSIT_PREAUTH", TxnType::DepositPreauth),
+    ("TRUST_SET", TxnType::TrustSet),
+    ("ACCOUNT_DELETE", TxnType::AccountDelete),
+    ("SET_HOOK", TxnType::HookSet),
+    ("INVOKE", TxnType::Invoke),
+    ("AMENDMENT", TxnType::Amendment),
+    ("FEE", TxnType::Fee),
+    ("UNL_MODIFY", TxnType::UnlModify),
+];
+
+/// Parse a transaction type from its name in uppercase, such as `PAYMENT` or
+/// `TICKET_CREATE`, as `hooks-cli` takes them
+pub fn txn_type_from_name(name: &str) -> Option<TxnType> {
+    TXN_TYPE_NAMES
+        .iter()
+        .find(|(txn_type_name, _)| *txn_type_name == name)
+        .map(|&(_, txn_type)| txn_type)
+}
+
+/// Entry of the `HookGrants` of a hook, letting another hook write to the state of the
+/// account it is installed on, in its namespace, with the `state_foreign_set` host function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HookGrant {
+    /// Hash of the hook allowed to write, see [SetHook::hook_hash]
+    pub hook_hash: Hash,
+    /// Account the hook must be installed on to write, `None` for any account
+    pub authorize: Option<AccountId>,
+}
+
+/// What a SetHook transaction does with the hook at its position.
+///
+/// A node tells the operations apart by which of `CreateCode` and `HookHash` are present,
+/// and whether `CreateCode` is empty, see [SetHook::to_json].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HookDefinition {
+    /// Install the hook compiled to the wasm, defining it on the ledger unless an account
+    /// installed the same wasm before
+    Code(Vec<u8>),
+    /// Install a hook already defined on the ledger, by its hash, without paying for its
+    /// code again
+    Hash(Hash),
+    /// Keep the hook installed at the position, changing only the fields that are set
+    Installed,
+    /// Delete the hook installed at the position
+    Deleted,
+}
+
+/// A SetHook transaction setting one hook: installing it from its wasm or hash, updating
+/// the installed one or deleting it.
+///
+/// It isn't signed and has no `Fee` or `Sequence`, which the library submitting it fills
+/// in.
+///
+/// # Example
+/// ```no_run
+/// use hooks_rs::{hookon, Namespace};
+/// use hooks_rs_tools::SetHook;
+///
+/// let wasm = std::fs::read("target/wasm32-unknown-unknown/release/accept.wasm").unwrap();
+/// let set_hook = SetHook::new(wasm, Namespace::from_label(b"accept")).hook_on(hookon!(Invoke));
+///
+/// println!("{}", set_hook.to_json());
+/// ```
+#[derive(Debug, Clone)]
+pub struct SetHook {
+    definition: HookDefinition,
+    namespace: Option<Namespace>,
+    hook_on: Option<HookOn>,
+    api_version: u16,
+    flags: u32,
+    account: Option<AccountId>,
+    parameters: Vec<(Vec<u8>, Option<Vec<u8>>)>,
+    grants: Vec<HookGrant>,
+}
+
+impl SetHook {
+    /// Create a SetHook transaction for the hook compiled to `wasm`, keeping its state in
+    /// `namespace`. The hook isn't triggered by any transaction until set with
+    /// [hook_on](Self::hook_on) or [trigger_on](Self::trigger_on).
+    pub fn new(wasm: Vec<u8>, namespace: Namespace) -> Self {
+        Self::with_definition(HookDefinition::Code(wasm)).namespace(namespace)
+    }
+
+    /// Create a SetHook transaction installing the hook already on the ledger with the
+    /// hash, as another account installed it. Unless set, `HookOn` is the one the hook was
+    /// first installed with.
+    pub fn install(hook_hash: Hash, namespace: Namespace) -> Self {
+        Self::with_definition(HookDefinition::Hash(hook_hash)).namespace(namespace)
+    }
+
+    /// Create a SetHook transaction changing the fields that are set of the hook installed
+    /// at the position, keeping its code. Parameters are added or replaced by
+    /// [parameter](Self::parameter) and removed by
+    /// [delete_parameter](Self::delete_parameter), while the grants, when any is set,
+    /// replace the installed ones. Add [HSF_NSDELETE] to the flags to delete the state of
+    /// the namespace replaced by [namespace](Self::namespace).
+    pub fn update() -> Self {
+        Self::with_definition(HookDefinition::Installed)
+    }
+
+    /// Create a SetHook transaction deleting the hook installed at the position. Add
+    /// [HSF_NSDELETE] to the flags to delete its state too.
+    pub fn delete() -> Self {
+        Self::with_definition(HookDefinition::Deleted).flags(HSF_OVERRIDE)
+    }
+
+    fn with_definition(definition: HookDefinition) -> Self {
+        SetHook {
+            definition,
+            namespace: None,
+            hook_on: None,
+            api_version: 0,
+            flags: 0,
+            account: None,
+            parameters: Vec::new(),
+            grants: Vec::new(),
+        }
+    }
+
+    /// What the transaction does with the hook at its position
+    pub fn definition(&self) -> &HookDefinition {
+        &self.definition
+    }
+
+    /// Set the namespace keeping the state of the hook
+    pub fn namespace(mut self, namespace: Namespace) -> Self {
+        self.namespace = Some(namespace);
+        self
+    }
+
+    /// Set the transaction types that trigger the hook, see [HookOn]
+    pub fn hook_on(mut self, hook_on: HookOn) -> Self {
+        self.hook_on = Some(hook_on);
+        self
+    }
+
+    /// Also trigger the hook on the transaction type, see [HookOn::trigger_on]
+    pub fn trigger_on(mut self, txn_type: TxnType) -> Self {
+        self.hook_on = Some(self.hook_on.unwrap_or(HookOn::NONE).trigger_on(txn_type));
+        self
+    }
+
+    /// Set the `Flags` of the hook, such as [HSF_OVERRIDE]
+    pub fn flags(mut self, flags: u32) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Set the account installing the hook
+    pub fn account(mut self, account: AccountId) -> Self {
+        self.account = Some(account);
+        self
+    }
+
+    /// Add a `HookParameter` to the hook, read by
+    /// [hook_param](hooks_rs::hook_param). A node rejects names longer than 32 bytes and
+    /// values longer than [HOOK_PARAM_VALUE_MAX_LEN](hooks_rs::HOOK_PARAM_VALUE_MAX_LEN).
+    pub fn parameter(mut self, name: &[u8], value: &[u8]) -> Self {
+        self.parameters.push((name.to_vec(), Some(value.to_vec())));
+        self
+    }
+
+    /// Remove a `HookParameter` from the installed hook, see [update](Self::update)
+    pub fn delete_parameter(mut self, name: &[u8]) -> Self {
+        self.parameters.push((name.to_vec(), None));
+        self
+    }
+
+    /// Add a `HookGrant` to the hook. A node rejects hooks with more than
+    /// [MAX_HOOK_GRANTS].
+    pub fn grant(mut self, grant: HookGrant) -> Self {
+        self.grants.push(grant);
+        self
+    }
+
+    /// Whether a node lets the hook with `hook_hash`, installed on `hook_account`, write
+    /// to the state in `namespace` of the account installing this hook with
+    /// `state_foreign_set`.
+    ///
+    /// Hooks always write the state of their own account. Writes to other accounts need a
+    /// grant for the hook, and for its account if the grant names one, on a hook of the
+    /// written namespace.
+    pub fn permits_state_foreign_set(
+        &self,
+        namespace: &Namespace,
+        hook_hash: &Hash,
+        hook_account: &AccountId,
+    ) -> bool {
+        if self.account.as_ref() == Some(hook_account) {
+            return true;
+        }
+
+        self.namespace.as_ref() == Some(namespace)
+            && self.grants.iter().any(|grant| {
+                grant.hook_hash == *hook_hash
+                    && grant
+                        .authorize
+                        .map_or(true, |authorize| authorize == *hook_account)
+            })
+    }
+
+    /// Hash of the hook on the ledger, as returned by
+    /// [hook_hash](hooks_rs::hook_hash) once installed. `None` when updating or deleting
+    /// the installed hook.
+    pub fn hook_hash(&self) -> Option<Hash> {
+        match &self.definition {
+            HookDefinition::Code(wasm) => Some(sha512_half(wasm)),
+            HookDefinition::Hash(hook_hash) => Some(*hook_hash),
+            HookDefinition::Installed | HookDefinition::Deleted => None,
+        }
+    }
+
+    /// `HookOn` field of the hook, if any
+    pub fn hook_on_hex(&self) -> Option<String> {
+        // the field is required when creating a hook
+        let hook_on = match self.definition {
+            HookDefinition::Code(_) => Some(self.hook_on.unwrap_or(HookOn::NONE)),
+            _ => self.hook_on,
+        };
+        hook_on.map(|hook_on| String::from_utf8(hook_on.to_hex().to_vec()).unwrap())
+    }
+
+    /// `HookNamespace` field of the hook, if any
+    pub fn namespace_hex(&self) -> Option<String> {
+        self.namespace.map(|namespace| hex(namespace.as_bytes()))
+    }
+
+    /// `CreateCode` field of the hook: the wasm when creating the hook, empty when
+    /// deleting it and left out otherwise
+    pub fn create_code_hex(&self) -> Option<String> {
+        match &self.definition {
+            HookDefinition::Code(wasm) => Some(hex(wasm)),
+            HookDefinition::Deleted => Some(String::new()),
+            HookDefinition::Hash(_) | HookDefinition::Installed => None,
+        }
+    }
+
+    /// The transaction as JSON, indented like `JSON.stringify(tx, null, 2)`
+    pub fn to_json(&self) -> String {
+        let mut fields = Vec::new();
+        if let Some(create_code) = self.create_code_hex() {
+            fields.push(format!("\"CreateCode\": \"{create_code}\""));
+        }
+        if let HookDefinition::Hash(hook_hash) = &self.definition {
+            fields.push(format!("\"HookHash\": \"{}\"", hex(hook_hash)));
+        }
+        if let Some(hook_on) = self.hook_on_hex() {
+            fields.push(format!("\"HookOn\": \"{hook_on}\""));
+        }
+        if let Some(namespace) = self.namespace_hex() {
+            fields.push(format!("\"HookNamespace\": \"{namespace}\""));
+        }
+        // only a new definition has an API version
+        if let HookDefinition::Code(_) = self.definition {
+            fields.push(format!("\"HookApiVersion\": {}", self.api_version));
+        }
+        // left out when 0, like hooks-cli does
+        if self.flags != 0 {
+            fields.push(format!("\"Flags\": {}", self.flags));
+        }
+        if !self.parameters.is_empty() {
+            let parameters = self.parameters.iter().map(|(name, value)| {
+                let mut fields = vec![format!("\"HookParameterName\": \"{}\"", hex(name))];
+                // a name without a value deletes the parameter
+                if let Some(value) = value {
+                    fields.push(format!("\"HookParameterValue\": \"{}\"", hex(value)));
+                }
+                fields
+            });
+            fields.push(array("HookParameters", "HookParameter", parameters));
+        }
+        if !self.grants.is_empty() {
+            let grants = self.grants.iter().map(|grant| {
+                let mut fields = vec![format!("\"HookHash\": \"{}\"", hex(&grant.hook_hash))];
+                if let Some(authorize) = &grant.authorize {
+                    fields.push(format!("\"Authorize\": \"{}\"", r_address(authorize)));
+                }
+                fields
+            });
+            fields.push(array("HookGrants", "HookGrant", grants));
+        }
+
+        let mut json = String::from("{\n  \"TransactionType\": \"SetHook\",\n");
+        if let Some(account) = &self.account {
+            writeln!(json, "  \"Account\": \"{}\",", r_address(account)).unwrap();
+        }
+        json.push_str("  \"Hooks\": [\n    {\n      \"Hook\": {");
+        for (i, field) in fields.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            write!(json, "\n        {field}").unwrap();
+        }
+        json.push_str("\n      }\n    }\n  ]\n}");
+        json
+    }
+}
+
+// A field of the hook holding an array of objects, with the fields of every object
+fn array(name: &str, element: &str, elements: impl Iterator<Item = Vec<String>>) -> String {
+    let mut json = format!("\"{name}\": [");
+    for (i, fields) in elements.enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        write!(json, "\n          {{\n            \"{element}\": {{").unwrap();
+        for (j, field) in fields.iter().enumerate() {
+            if j > 0 {
+                json.push(',');
+            }
+            write!(json, "\n              {field}").unwrap();
+        }
+        json.push_str("\n            }\n          }");
+    }
+    json.push_str("\n        ]");
+    json
+}
+
+fn r_address(account: &AccountId) -> String {
+    let r_address = account.to_raddress_const();
+    let len = r_address
+        .iter()
+        .position(|&byte| byte == 0)
+        .unwrap_or(r_address.len());
+    String::from_utf8(r_address[..len].to_vec()).unwrap()
+}
+
+/// Namespace hooks-cli installs the hook built to `<hook_name>.wasm` with, the SHA-256 of
+/// the seed `<hook_name>namespace`.
+///
+/// It isn't the namespace a hook derives with [Namespace::from_label], so when the hook
+/// reads its namespace or another hook reads its state, install it with the same
+/// `Namespace::from_label` instead.
+pub fn default_namespace(hook_name: &str) -> Namespace {
+    Namespace::from_seed(format!("{hook_name}namespace").as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use hooks_rs::fixtures::{DESTINATION, GENESIS_ACCOUNT};
+    use hooks_rs::hookon;
+
+    use super::*;
+
+    // (module (func (export "hook") (param i64) (result i64) i64.const 0))
+    const WASM: &[u8] = &[
+        0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x06, 0x01, 0x60, 0x01, 0x7e, 0x01,
+        0x7e, 0x03, 0x02, 0x01, 0x00, 0x07, 0x08, 0x01, 0x04, 0x68, 0x6f, 0x6f, 0x6b, 0x00, 0x00,
+        0x0a, 0x06, 0x01, 0x04, 0x00, 0x42, 0x00, 0x0b,
+    ];
+
+    #[test]
+    fn parses_txn_type_names() {
+        assert!(matches!(
+            txn_type_from_name("PAYMENT"),
+            Some(TxnType::Payment)
+        ));
+        assert!(matches!(
+            txn_type_from_name("SET_HOOK"),
+            Some(TxnType::HookSet)
+        ));
+        assert!(txn_type_from_name("Payment").is_none());
+        assert!(txn_type_from_name("URITOKEN_MINT").is_none());
+    }
+
+    #[test]
+    fn builds_set_hook_json() {
+        let set_hook = SetHook::new(WASM.to_vec(), default_namespace("accept"))
+            .hook_on(hookon!(Invoke))
+            .flags(HSF_OVERRIDE)
+            .account(GENESIS_ACCOUNT);
+
+        assert_eq!(
+            hex(&set_hook.hook_hash().unwrap()),
+            "10924B76FCE83ABE93DB636AE954EC987380F76E2AD6E9AAE8060DBC764AAE2F"
+        );
+        assert_eq!(
+            set_hook.to_json(),
+            format!(
+                r#"{{
+  "TransactionType": "SetHook",
+  "Account": "rHb9CJAWyB4rj91VRWn96DkukG4bwdtyTh",
+  "Hooks": [
+    {{
+      "Hook": {{
+        "CreateCode": "{}",
+        "HookOn": "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF7FFFFFFFFFFFFFFFFFFBFFFFF",
+        "HookNamespace": "0771A689DD2ADD41F20B0EDD9C13DFEAAAAFC862191466AFECFA6A3AA2386315",
+        "HookApiVersion": 0,
+        "Flags": 1
+      }}
+    }}
+  ]
+}}"#,
+                hex(WASM)
+            )
+        );
+    }
+
+    #[test]
+    fn triggers_on_transaction_types() {
+        let set_hook = SetHook::new(WASM.to_vec(), default_namespace("accept"))
+            .trigger_on(TxnType::HookSet)
+            .trigger_on(TxnType::Invoke);
+
+        assert_eq!(
+            set_hook.hook_on_hex().unwrap(),
+            "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF7FFFFFFFFFFFFFFFFFFFFFFFF"
+        );
+    }
+
+    #[test]
+    fn derives_namespaces_like_hooks() {
+        let set_hook = SetHook::new(WASM.to_vec(), Namespace::from_label(b"vault"));
+
+        assert_eq!(set_hook.namespace_hex(), Some(hex(&sha512_half(b"vault"))));
+    }
+
+    #[test]
+    fn leaves_out_unset_fields() {
+        let json = SetHook::new(WASM.to_vec(), default_namespace("accept")).to_json();
+
+        assert!(!json.contains("Account"));
+        assert!(!json.contains("Flags"));
+        assert!(json.contains(
+            "\"HookOn\": \"FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFBFFFFF\""
+        ));
+    }
+
+    #[test]
+    fn renders_hook_grants() {
+        let set_hook = SetHook::new(WASM.to_vec(), default_namespace("accept"))
+            .grant(HookGrant {
+                hook_hash: [0xAB; 32],
+                authorize: Some(GENESIS_ACCOUNT),
+            })
+            .grant(HookGrant {
+                hook_hash: [0xCD; 32],
+                authorize: None,
+            });
+
+        assert!(set_hook.to_json().ends_with(&format!(
+            r#""HookApiVersion": 0,
+        "HookGrants": [
+          {{
+            "HookGrant": {{
+              "HookHash": "{}",
+              "Authorize": "rHb9CJAWyB4rj91VRWn96DkukG4bwdtyTh"
+            }}
+          }},
+          {{
+            "HookGrant": {{
+              "HookHash": "{}"
+            }}
+          }}
+        ]
+      }}
+    }}
+  ]
+}}"#,
+            hex(&[0xAB; 32]),
+            hex(&[0xCD; 32])
+        )));
+    }
+
+    #[test]
+    fn checks_foreign_state_writes() {
+        let namespace = default_namespace("accept");
+        let set_hook = SetHook::new(WASM.to_vec(), namespace)
+            .account(GENESIS_ACCOUNT)
+            .grant(HookGrant {
+                hook_hash: [0xAB; 32],
+                authorize: Some(DESTINATION),
+            })
+            .grant(HookGrant {
+                hook_hash: [0xCD; 32],
+                authorize: None,
+            });
+
+        assert!(set_hook.permits_state_foreign_set(&namespace, &[0xAB; 32], &DESTINATION));
+        assert!(!set_hook.permits_state_foreign_set(&namespace, &[0xAB; 32], &AccountId([1; 20])));
+        assert!(set_hook.permits_state_foreign_set(&namespace, &[0xCD; 32], &AccountId([1; 20])));
+        assert!(!set_hook.permits_state_foreign_set(&namespace, &[0xEF; 32], &DESTINATION));
+        assert!(!set_hook.permits_state_foreign_set(
+            &Namespace([0; 32]),
+            &[0xCD; 32],
+            &DESTINATION
+        ));
+        // the account installing the hook writes its own state without grants
+        assert!(set_hook.permits_state_foreign_set(
+            &Namespace([0; 32]),
+            &[0xEF; 32],
+            &GENESIS_ACCOUNT
+        ));
+    }
+
+    #[test]
+    fn installs_defined_hooks_by_hash() {
+        let json = SetHook::install([0xAB; 32], default_namespace("accept"))
+            .flags(HSF_OVERRIDE)
+            .to_json();
+
+        assert_eq!(
+            json,
+            format!(
+                r#"{{
+  "TransactionType": "SetHook",
+  "Hooks": [
+    {{
+      "Hook": {{
+        "HookHash": "{}",
+        "HookNamespace": "0771A689DD2ADD41F20B0EDD9C13DFEAAAAFC862191466AFECFA6A3AA2386315",
+        "Flags": 1
+      }}
+    }}
+  ]
+}}"#,
+                hex(&[0xAB; 32])
+            )
+        );
+    }
+
+    #[test]
+    fn updates_only_the_fields_set() {
+        let set_hook = SetHook::update()
+            .parameter(b"limit", &[1, 0])
+            .delete_parameter(b"owner");
+
+        assert!(set_hook.hook_hash().is_none());
+        assert!(set_hook.create_code_hex().is_none());
+        assert_eq!(
+            set_hook.to_json(),
+            r#"{
+  "TransactionType": "SetHook",
+  "Hooks": [
+    {
+      "Hook": {
+        "HookParameters": [
+          {
+            "HookParameter": {
+              "HookParameterName": "6C696D6974",
+              "HookParameterValue": "0100"
+            }
+          },
+          {
+            "HookParameter": {
+              "HookParameterName": "6F776E6572"
+            }
+          }
+        ]
+      }
+    }
+  ]
+}"#
+        );
+    }
+
+    #[test]
+    fn deletes_with_empty_code_and_override() {
+        let json = SetHook::delete()
+            .flags(HSF_OVERRIDE | HSF_NSDELETE)
+            .to_json();
+
+        assert!(json.contains(
+            "      \"Hook\": {\n        \"CreateCode\": \"\",\n        \"Flags\": 3\n      }"
+        ));
+        assert!(SetHook::delete().to_json().contains("\"Flags\": 1\n"));
+    }
+}
@@ -0,0 +1,572 @@
+//! End-to-end tests of hooks against a xahaud node, with the `testkit` feature.
+//!
+//! Unit tests compare the bytes a builder such as
+//! [XrpPaymentBuilder](hooks_rs::XrpPaymentBuilder) writes with the expected ones, which
+//! doesn't tell whether a node applies them. [Node] installs a hook on a node with a
+//! [SetHook] transaction, submits the transactions triggering it and reads back what it
+//! left on the ledger: its executions, the transactions it emitted, balances and state.
+//!
+//! The node has to be one you run, such as `xahaud --standalone`, with an admin JSON-RPC
+//! port: it signs the transactions with the secrets of the accounts, creates the accounts
+//! and closes a ledger after every transaction. Accounts are funded from the genesis
+//! account, so never point a [Node] to a network holding real funds.
+//!
+//! ```no_run
+//! use hooks_rs::{hookon, Namespace};
+//! use hooks_rs_tools::testkit::{build_example, Node};
+//! use hooks_rs_tools::SetHook;
+//!
+//! let node = Node::from_env().unwrap();
+//! let alice = node.new_account().unwrap();
+//! let bob = node.new_account().unwrap();
+//!
+//! let wasm = build_example("accept").unwrap();
+//! let set_hook = SetHook::new(wasm, Namespace::from_label(b"accept")).hook_on(hookon!(Invoke));
+//! node.set_hook(&alice, &set_hook).unwrap();
+//!
+//! let invoke = node.invoke(&bob, &alice).unwrap();
+//! assert_eq!(invoke.hook_executions()[0].return_string, b"accept.rs: Finished.");
+//! ```
+
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::path::Path;
+use std::process::Command;
+
+use hooks_rs::{AccountId, Namespace};
+use serde_json::{json, Value};
+
+use crate::{hex, validate, SetHook};
+
+/// Node used by [Node::from_env] unless `XAHAU_RPC_URL` is set, the admin JSON-RPC port
+/// of a xahaud started with its example configuration
+pub const DEFAULT_NODE_URL: &str = "http://localhost:5005";
+
+/// Seed of the genesis account of a fresh ledger, which holds all XAH
+pub const GENESIS_SECRET: &str = "snoPBrXtMeMyMHUVTgbuqAfg1SUTb";
+
+/// Address of the genesis account
+pub const GENESIS_ADDRESS: &str = "rHb9CJAWyB4rj91VRWn96DkukG4bwdtyTh";
+
+/// Drops [Node::new_account] funds accounts with, enough for the reserves and fees of any
+/// test
+pub const FUNDING_DROPS: u64 = 10_000_000_000;
+
+// Networks with an ID above this one require the `NetworkID` field in transactions
+const LEGACY_NETWORK_ID_MAX: u64 = 1024;
+
+/// Errors of a [Node]
+#[derive(Debug)]
+pub enum Error {
+    /// Connecting to the node or running a build tool failed
+    Io(io::Error),
+    /// The node answered with something other than a JSON-RPC result
+    Http(String),
+    /// The node returned an error for a command
+    Rpc(String),
+    /// A transaction didn't succeed, with its engine result such as `tecHOOK_REJECTED`
+    Transaction {
+        /// Hash of the transaction, empty if it never got one
+        hash: String,
+        /// Engine result of the transaction
+        result: String,
+    },
+    /// Building or cleaning a hook failed, or the node would reject its wasm
+    Build(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "{err}"),
+            Error::Http(message) => write!(f, "invalid response: {message}"),
+            Error::Rpc(message) => write!(f, "node returned an error: {message}"),
+            Error::Transaction { hash, result } => write!(f, "transaction {hash} failed: {result}"),
+            Error::Build(message) => write!(f, "could not build the hook: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Http(err.to_string())
+    }
+}
+
+/// Result of the functions of this module
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// An account on the node, with the secret the node signs its transactions with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Account {
+    /// r-address of the account
+    pub address: String,
+    /// Seed of the account, as `s...`
+    pub secret: String,
+}
+
+impl Account {
+    /// The genesis account, see [GENESIS_SECRET]
+    pub fn genesis() -> Self {
+        Account {
+            address: GENESIS_ADDRESS.to_string(),
+            secret: GENESIS_SECRET.to_string(),
+        }
+    }
+
+    /// ID of the account, as hooks read it with
+    /// [hook_account](hooks_rs::hook_account) or [otxn_field](hooks_rs::otxn_field)
+    pub fn account_id(&self) -> AccountId {
+        AccountId::from_raddress(self.address.as_bytes())
+            .expect("the node returned an invalid r-address")
+    }
+}
+
+/// A transaction applied to a validated ledger, as returned by the `tx` command
+#[derive(Debug, Clone)]
+pub struct Transaction {
+    /// Hash of the transaction
+    pub hash: String,
+    /// The transaction and its metadata, in `meta`
+    pub json: Value,
+}
+
+impl Transaction {
+    /// Engine result of the transaction, such as `tesSUCCESS`
+    pub fn result(&self) -> &str {
+        self.json["meta"]["TransactionResult"]
+            .as_str()
+            .unwrap_or_default()
+    }
+
+    /// Fee paid by the transaction, in drops
+    pub fn fee(&self) -> u64 {
+        drops(&self.json["Fee"]).unwrap_or_default()
+    }
+
+    /// Executions of hooks triggered by the transaction, in the order they ran
+    pub fn hook_executions(&self) -> Vec<HookExecution> {
+        objects(&self.json["meta"]["HookExecutions"], "HookExecution")
+            .map(|execution| HookExecution {
+                account: execution["HookAccount"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                hook_hash: execution["HookHash"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                return_code: execution["HookReturnCode"]
+                    .as_str()
+                    .and_then(hook_return_code)
+                    .unwrap_or_default(),
+                return_string: execution["HookReturnString"]
+                    .as_str()
+                    .and_then(unhex)
+                    .unwrap_or_default(),
+                emit_count: execution["HookEmitCount"].as_u64().unwrap_or_default(),
+            })
+            .collect()
+    }
+
+    /// Hashes of the transactions emitted by hooks the transaction triggered
+    pub fn emitted_hashes(&self) -> Vec<String> {
+        objects(&self.json["meta"]["HookEmissions"], "HookEmission")
+            .filter_map(|emission| emission["EmittedTxnID"].as_str())
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+/// What a hook returned when a transaction triggered it, found in the `HookExecutions` of
+/// the metadata of the transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookExecution {
+    /// Account the hook is installed on
+    pub account: String,
+    /// Hash of the hook, as hex
+    pub hook_hash: String,
+    /// Code passed to [accept](hooks_rs::accept) or [rollback](hooks_rs::rollback)
+    pub return_code: i64,
+    /// Message passed to [accept](hooks_rs::accept) or [rollback](hooks_rs::rollback)
+    pub return_string: Vec<u8>,
+    /// Number of transactions the hook emitted
+    pub emit_count: u64,
+}
+
+/// A xahaud node taking admin commands over JSON-RPC, see the [module](self) docs
+#[derive(Debug, Clone)]
+pub struct Node {
+    // host and port of the node
+    address: String,
+    network_id: Option<u64>,
+}
+
+impl Node {
+    /// Connect to the node at `url`, such as [DEFAULT_NODE_URL], which is served over plain
+    /// HTTP
+    pub fn connect(url: &str) -> Result<Self> {
+        let address = url.strip_prefix("http://").unwrap_or(url);
+        let mut node = Node {
+            address: address.trim_end_matches('/').to_string(),
+            network_id: None,
+        };
+        let server_info = node.request("server_info", json!({}))?;
+        node.network_id = server_info["info"]["network_id"]
+            .as_u64()
+            .filter(|&network_id| network_id > LEGACY_NETWORK_ID_MAX);
+
+        Ok(node)
+    }
+
+    /// Connect to the node at `XAHAU_RPC_URL`, or [DEFAULT_NODE_URL] if it isn't set
+    pub fn from_env() -> Result<Self> {
+        match std::env::var("XAHAU_RPC_URL") {
+            Ok(url) => Node::connect(&url),
+            Err(_) => Node::connect(DEFAULT_NODE_URL),
+        }
+    }
+
+    /// Send a command to the node, returning its `result`
+    pub fn request(&self, command: &str, params: Value) -> Result<Value> {
+        let body = json!({ "method": command, "params": [params] }).to_string();
+        let mut stream = TcpStream::connect(&self.address)?;
+        write!(
+            stream,
+            "POST / HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\n\
+             Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            self.address,
+            body.len(),
+        )?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response)?;
+
+        let response: Value = serde_json::from_str(http_body(&response)?)?;
+        let result = response["result"].clone();
+        if result["status"] == "error" {
+            let message = result["error_message"]
+                .as_str()
+                .or(result["error"].as_str())
+                .unwrap_or("unknown error");
+            return Err(Error::Rpc(message.to_string()));
+        }
+
+        Ok(result)
+    }
+
+    /// Close the open ledger, validating the transactions submitted since the last one
+    pub fn close_ledger(&self) -> Result<()> {
+        self.request("ledger_accept", json!({}))?;
+        Ok(())
+    }
+
+    /// Create an account funded with [FUNDING_DROPS] from the genesis account
+    pub fn new_account(&self) -> Result<Account> {
+        let wallet = self.request("wallet_propose", json!({}))?;
+        let account = Account {
+            address: string(&wallet["account_id"])?,
+            secret: string(&wallet["master_seed"])?,
+        };
+        self.submit(
+            &Account::genesis(),
+            json!({
+                "TransactionType": "Payment",
+                "Destination": account.address,
+                "Amount": FUNDING_DROPS.to_string(),
+            }),
+        )?;
+
+        Ok(account)
+    }
+
+    /// Submit the transaction `tx` from `account` and close the ledger, returning the
+    /// validated transaction. `Account`, `Fee`, `Sequence` and `NetworkID` are filled in,
+    /// the fee being the one the node asks for the hooks the transaction triggers.
+    ///
+    /// Fails with [Error::Transaction] unless the transaction succeeds.
+    pub fn submit(&self, account: &Account, mut tx: Value) -> Result<Transaction> {
+        tx["Account"] = json!(account.address);
+        if let Some(network_id) = self.network_id {
+            tx["NetworkID"] = json!(network_id);
+        }
+
+        // autofilling the fee doesn't account for hooks, which the fee of a signed blob
+        // does
+        tx["Fee"] = json!("0");
+        let signed = self.request("sign", json!({ "secret": account.secret, "tx_json": tx }))?;
+        let fee = self.request("fee", json!({ "tx_blob": signed["tx_blob"] }))?;
+        tx["Fee"] = fee["drops"]["base_fee"].clone();
+
+        let submitted =
+            self.request("submit", json!({ "secret": account.secret, "tx_json": tx }))?;
+        let hash = string(&submitted["tx_json"]["hash"]).unwrap_or_default();
+        let engine_result = string(&submitted["engine_result"])?;
+        // only these are applied to a ledger
+        if !(engine_result.starts_with("tes") || engine_result.starts_with("tec")) {
+            return Err(Error::Transaction {
+                hash,
+                result: engine_result,
+            });
+        }
+        self.close_ledger()?;
+
+        let tx = self.tx(&hash)?;
+        if tx.result() != "tesSUCCESS" {
+            return Err(Error::Transaction {
+                hash,
+                result: tx.result().to_string(),
+            });
+        }
+
+        Ok(tx)
+    }
+
+    /// Look up the transaction with the hash in the validated ledgers
+    pub fn tx(&self, hash: &str) -> Result<Transaction> {
+        let json = self.request("tx", json!({ "transaction": hash }))?;
+        Ok(Transaction {
+            hash: hash.to_string(),
+            json,
+        })
+    }
+
+    /// The transactions emitted by hooks `tx` triggered, which are applied in the ledger
+    /// after the one of `tx`, so that ledger is closed first
+    pub fn emitted(&self, tx: &Transaction) -> Result<Vec<Transaction>> {
+        self.close_ledger()?;
+        tx.emitted_hashes()
+            .iter()
+            .map(|hash| self.tx(hash))
+            .collect()
+    }
+
+    /// Install the hook of `set_hook` on `account`, overriding the `Account` of `set_hook`
+    pub fn set_hook(&self, account: &Account, set_hook: &SetHook) -> Result<Transaction> {
+        self.submit(account, serde_json::from_str(&set_hook.to_json())?)
+    }
+
+    /// Submit an Invoke transaction from `from` to `to`, triggering the hooks of both
+    pub fn invoke(&self, from: &Account, to: &Account) -> Result<Transaction> {
+        self.submit(
+            from,
+            json!({ "TransactionType": "Invoke", "Destination": to.address }),
+        )
+    }
+
+    /// Balance of the account with `address` in drops, as of the last validated ledger
+    pub fn balance(&self, address: &str) -> Result<u64> {
+        let account_info = self.request(
+            "account_info",
+            json!({ "account": address, "ledger_index": "validated" }),
+        )?;
+        drops(&account_info["account_data"]["Balance"])
+            .ok_or(Error::Http("account without a balance".to_string()))
+    }
+
+    /// State of the account with `address` under `key` in `namespace`, as written by
+    /// [state_set](hooks_rs::state_set), or `None` if there is none. Build `namespace`
+    /// like the SetHook installing the hook did, such as with
+    /// [Namespace::from_label](hooks_rs::Namespace::from_label). Keys shorter than 32
+    /// bytes are padded with zeros in front, like hooks pad them.
+    pub fn hook_state(
+        &self,
+        address: &str,
+        namespace: &Namespace,
+        key: &[u8],
+    ) -> Result<Option<Vec<u8>>> {
+        let params = json!({
+            "hook_state": {
+                "account": address,
+                "key": format!("{:0>64}", hex(key)),
+                "namespace_id": hex(namespace.as_bytes()),
+            },
+            "ledger_index": "validated",
+        });
+        match self.request("ledger_entry", params) {
+            Ok(entry) => Ok(entry["node"]["HookStateData"].as_str().and_then(unhex)),
+            Err(Error::Rpc(message)) if message.contains("entryNotFound") => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// Build the example `name` of hooks-rs for release and prepare it for a SetHook
+/// transaction the way the TypeScript tests do, returning the wasm.
+///
+/// A node refuses blocks nested deeper than 16, so the wasm is flattened with `wasm-opt`
+/// from binaryen, then stripped of what a hook can't export with `hook-cleaner` from
+/// hook-cleaner-c, both of which have to be in the `PATH`. The result is checked with
+/// [validate].
+pub fn build_example(name: &str) -> Result<Vec<u8>> {
+    let hooks_rs = Path::new(env!("CARGO_MANIFEST_DIR")).join("../hooks-rs");
+    run(Command::new("cargo")
+        .args(["build", "--release", "--example", name])
+        .current_dir(&hooks_rs))?;
+
+    let examples = hooks_rs.join("target/wasm32-unknown-unknown/release/examples");
+    let built = examples.join(format!("{name}.wasm"));
+    let flattened = examples.join(format!("{name}-flattened.wasm"));
+    let cleaned = examples.join(format!("{name}-cleaned.wasm"));
+    run(Command::new("wasm-opt")
+        .arg(&built)
+        .args(["--flatten", "--rereloop", "-Oz", "-Oz", "-o"])
+        .arg(&flattened))?;
+    run(Command::new("hook-cleaner").arg(&flattened).arg(&cleaned))?;
+
+    let wasm = std::fs::read(&cleaned)?;
+    let violations = validate(&wasm).map_err(|err| Error::Build(err.to_string()))?;
+    if let Some(violation) = violations.first() {
+        return Err(Error::Build(violation.to_string()));
+    }
+
+    Ok(wasm)
+}
+
+fn run(command: &mut Command) -> Result<()> {
+    let output = command.output()?;
+    if !output.status.success() {
+        return Err(Error::Build(
+            String::from_utf8_lossy(&output.stderr).into_owned(),
+        ));
+    }
+    Ok(())
+}
+
+// The body of an HTTP response, if its status is 200
+fn http_body(response: &str) -> Result<&str> {
+    let (head, body) = response
+        .split_once("\r\n\r\n")
+        .ok_or(Error::Http("no end to the headers".to_string()))?;
+    let status = head.lines().next().unwrap_or_default();
+    match status.split(' ').nth(1) {
+        Some("200") => Ok(body),
+        _ => Err(Error::Http(status.to_string())),
+    }
+}
+
+// The objects named `name` wrapped in the elements of an array of the metadata
+fn objects<'a>(array: &'a Value, name: &'a str) -> impl Iterator<Item = &'a Value> {
+    array
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(move |element| &element[name])
+}
+
+fn string(value: &Value) -> Result<String> {
+    value
+        .as_str()
+        .map(str::to_string)
+        .ok_or(Error::Http(format!("expected a string, got {value}")))
+}
+
+// An amount of XRP, given as a string of drops
+fn drops(value: &Value) -> Option<u64> {
+    value.as_str()?.parse().ok()
+}
+
+// A return code of a hook executions, which the metadata holds as the hex of its sign
+// and magnitude
+fn hook_return_code(hex: &str) -> Option<i64> {
+    const SIGN: u64 = 1 << 63;
+
+    let code = u64::from_str_radix(hex, 16).ok()?;
+    if code & SIGN == 0 {
+        Some(code as i64)
+    } else {
+        Some(-((code ^ SIGN) as i64))
+    }
+}
+
+fn unhex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_hook_return_codes() {
+        assert_eq!(hook_return_code("0"), Some(0));
+        assert_eq!(hook_return_code("1C"), Some(28));
+        assert_eq!(hook_return_code("8000000000000001"), Some(-1));
+        assert_eq!(hook_return_code("800000000000001C"), Some(-28));
+        assert_eq!(hook_return_code("XYZ"), None);
+    }
+
+    #[test]
+    fn reads_hook_executions_and_emissions_from_meta() {
+        let tx = Transaction {
+            hash: String::new(),
+            json: json!({
+                "Fee": "1234",
+                "meta": {
+                    "TransactionResult": "tesSUCCESS",
+                    "HookExecutions": [{
+                        "HookExecution": {
+                            "HookAccount": GENESIS_ADDRESS,
+                            "HookEmitCount": 1,
+                            "HookHash": "ABCD",
+                            "HookReturnCode": "8000000000000002",
+                            "HookReturnString": "6F6B",
+                        }
+                    }],
+                    "HookEmissions": [{
+                        "HookEmission": { "EmittedTxnID": "EF01" }
+                    }],
+                },
+            }),
+        };
+
+        assert_eq!(tx.result(), "tesSUCCESS");
+        assert_eq!(tx.fee(), 1234);
+        assert_eq!(
+            tx.hook_executions(),
+            [HookExecution {
+                account: GENESIS_ADDRESS.to_string(),
+                hook_hash: "ABCD".to_string(),
+                return_code: -2,
+                return_string: b"ok".to_vec(),
+                emit_count: 1,
+            }]
+        );
+        assert_eq!(tx.emitted_hashes(), ["EF01"]);
+    }
+
+    #[test]
+    fn reads_bodies_of_http_responses() {
+        let ok = "HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n{}";
+        assert_eq!(http_body(ok).unwrap(), "{}");
+
+        let forbidden = "HTTP/1.1 403 Forbidden\r\n\r\n";
+        assert!(matches!(http_body(forbidden), Err(Error::Http(status)) if status.contains("403")));
+        assert!(matches!(
+            http_body("HTTP/1.1 200 OK\r\n"),
+            Err(Error::Http(_))
+        ));
+    }
+
+    #[test]
+    fn returns_the_genesis_account_id() {
+        assert_eq!(
+            Account::genesis().account_id(),
+            hooks_rs::fixtures::GENESIS_ACCOUNT
+        );
+    }
+}
@@ -0,0 +1,370 @@
+//! Checks that a node would accept a hook wasm in a SetHook transaction.
+//!
+//! A node validates the wasm of a hook before installing it, and a SetHook transaction
+//! with a wasm it rejects still costs its fee. [validate] runs the same structural checks
+//! beforehand:
+//!
+//! - only the host functions declared in `hooks-rs/c/extern.h` are imported, and `_g`
+//!   is among them
+//! - a `hook` function is exported, and if a `cbak` function is exported, it has the same
+//!   type of `(i32) -> i64`
+//! - no floating point instruction is used, since the host doesn't run any
+//! - memory is never grown with `memory.grow`
+//! - every loop starts with a guard, as found by [loops](crate::loops)
+
+use std::fmt;
+
+use wasmparser::{BinaryReaderError, Operator, ValType};
+
+use crate::guards::loops_of;
+use crate::module::{Location, Module};
+
+/// Module the host functions are imported from
+const HOST_MODULE: &str = "env";
+
+/// The host functions a hook may import, as declared in `hooks-rs/c/extern.h`
+pub const HOST_FUNCTIONS: &[&str] = &[
+    "_g",
+    "accept",
+    "emit",
+    "etxn_burden",
+    "etxn_details",
+    "etxn_fee_base",
+    "etxn_generation",
+    "etxn_nonce",
+    "etxn_reserve",
+    "fee_base",
+    "float_compare",
+    "float_divide",
+    "float_int",
+    "float_invert",
+    "float_log",
+    "float_mantissa",
+    "float_mantissa_set",
+    "float_mulratio",
+    "float_multiply",
+    "float_negate",
+    "float_one",
+    "float_root",
+    "float_set",
+    "float_sign",
+    "float_sign_set",
+    "float_sto",
+    "float_sto_set",
+    "float_sum",
+    "hook_account",
+    "hook_again",
+    "hook_hash",
+    "hook_param",
+    "otxn_param",
+    "hook_param_set",
+    "hook_pos",
+    "hook_skip",
+    "ledger_keylet",
+    "ledger_last_hash",
+    "ledger_last_time",
+    "ledger_nonce",
+    "ledger_seq",
+    "meta_slot",
+    "otxn_burden",
+    "otxn_field",
+    "otxn_field_txt",
+    "otxn_generation",
+    "otxn_id",
+    "otxn_slot",
+    "otxn_type",
+    "rollback",
+    "slot",
+    "slot_clear",
+    "slot_count",
+    "slot_float",
+    "slot_id",
+    "slot_set",
+    "slot_size",
+    "slot_subarray",
+    "slot_subfield",
+    "slot_type",
+    "state",
+    "state_foreign",
+    "state_foreign_set",
+    "state_set",
+    "sto_emplace",
+    "sto_erase",
+    "sto_subarray",
+    "sto_subfield",
+    "sto_validate",
+    "trace",
+    "trace_float",
+    "trace_num",
+    "trace_slot",
+    "util_accid",
+    "util_keylet",
+    "util_raddr",
+    "util_sha512h",
+    "util_verify",
+];
+
+/// Name of the guard function
+pub(crate) const GUARD_FUNCTION: &str = "_g";
+
+/// Exported functions a node calls, which take the reserved argument and return an
+/// `i64`
+const ENTRY_POINTS: &[&str] = &["hook", "cbak"];
+
+/// A reason a node would reject a hook
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    /// An import other than a host function, such as one left by a dependency that
+    /// assumes a JavaScript host or WASI
+    ForbiddenImport {
+        /// Module of the import
+        module: String,
+        /// Name of the import
+        name: String,
+    },
+    /// `_g` isn't imported, so the hook has no guards. Hooks built with hooks-rs always
+    /// import it, unless the optimizer removed every call to it.
+    MissingGuardImport,
+    /// No `hook` function is exported
+    MissingHookExport,
+    /// `hook` or `cbak` is exported with a type other than `(i32) -> i64`
+    InvalidEntryPointType(String),
+    /// A floating point instruction, which a dependency can bring in through `f64`
+    /// arithmetic or formatting
+    FloatInstruction {
+        /// Name of the instruction
+        instruction: String,
+        /// Where it is
+        location: Location,
+    },
+    /// A `memory.grow` instruction, which comes from allocating
+    MemoryGrow(Location),
+    /// A loop without a call to `_g` at its start
+    UnguardedLoop(Location),
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::ForbiddenImport { module, name } => {
+                write!(f, "imports {module}::{name}, which isn't a host function")
+            }
+            Violation::MissingGuardImport => write!(f, "doesn't import the guard function _g"),
+            Violation::MissingHookExport => write!(f, "doesn't export a hook function"),
+            Violation::InvalidEntryPointType(name) => {
+                write!(f, "exports {name} with a type other than (i32) -> i64")
+            }
+            Violation::FloatInstruction {
+                instruction,
+                location,
+            } => write!(
+                f,
+                "uses the floating point instruction {instruction} in {location}"
+            ),
+            Violation::MemoryGrow(location) => write!(f, "grows memory in {location}"),
+            Violation::UnguardedLoop(location) => {
+                write!(f, "has no guard in the loop in {location}")
+            }
+        }
+    }
+}
+
+/// Check that a node would accept the hook compiled to `wasm`, returning the reasons it
+/// would reject it, or an error if `wasm` isn't a wasm module at all
+///
+/// # Example
+/// ```no_run
+/// let wasm = std::fs::read("target/wasm32-unknown-unknown/release/accept.wasm").unwrap();
+///
+/// for violation in hooks_rs_tools::validate(&wasm).unwrap() {
+///     eprintln!("{violation}");
+/// }
+/// ```
+pub fn validate(wasm: &[u8]) -> Result<Vec<Violation>, BinaryReaderError> {
+    let module = Module::parse(wasm)?;
+    let mut violations = Vec::new();
+
+    for import in &module.imports {
+        let is_host_function = import.module == HOST_MODULE
+            && import.ty.is_some()
+            && HOST_FUNCTIONS.contains(&import.name);
+        if !is_host_function {
+            violations.push(Violation::ForbiddenImport {
+                module: import.module.to_string(),
+                name: import.name.to_string(),
+            });
+        }
+    }
+    let imports_guard = module
+        .imports
+        .iter()
+        .any(|import| import.module == HOST_MODULE && import.name == GUARD_FUNCTION);
+    if !imports_guard {
+        violations.push(Violation::MissingGuardImport);
+    }
+
+    if !module.exports.iter().any(|(name, _)| *name == "hook") {
+        violations.push(Violation::MissingHookExport);
+    }
+    for &(name, function) in &module.exports {
+        if !ENTRY_POINTS.contains(&name) {
+            continue;
+        }
+        let is_entry_point_type = module
+            .function_type(function)
+            .is_some_and(|ty| ty.params() == [ValType::I32] && ty.results() == [ValType::I64]);
+        if !is_entry_point_type {
+            violations.push(Violation::InvalidEntryPointType(name.to_string()));
+        }
+    }
+
+    let imported_functions = module.imported_functions();
+    for (i, body) in module.bodies.iter().enumerate() {
+        let function = imported_functions + i as u32;
+        for op in body.get_operators_reader()?.into_iter_with_offsets() {
+            let (op, offset) = op?;
+            if matches!(op, Operator::MemoryGrow { .. }) {
+                violations.push(Violation::MemoryGrow(module.location(function, offset)));
+            } else if let Some(instruction) = float_instruction(&op) {
+                violations.push(Violation::FloatInstruction {
+                    instruction,
+                    location: module.location(function, offset),
+                });
+            }
+        }
+    }
+
+    for unguarded in loops_of(&module)?.into_iter().filter(|l| l.guard.is_none()) {
+        violations.push(Violation::UnguardedLoop(unguarded.location));
+    }
+
+    Ok(violations)
+}
+
+/// Name of the instruction if it operates on floating point values. Every such
+/// instruction has `F32` or `F64` in its name, such as `F64Add` or `I32TruncF64S`, or
+/// in its block or select type.
+fn float_instruction(op: &Operator<'_>) -> Option<String> {
+    let debug = format!("{op:?}");
+    if !debug.contains("F32") && !debug.contains("F64") {
+        return None;
+    }
+
+    let name = debug.split([' ', '(', '{']).next().unwrap_or_default();
+    Some(name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A module with a type section of (i32) -> i64, an import section with `imports`,
+    // a function section with one function, an export section exporting it as
+    // `export`, and a code section with `code` as its body
+    fn module(imports: &[(&str, &str)], export: &str, code: &[u8]) -> Vec<u8> {
+        let mut wasm = b"\0asm\x01\0\0\0".to_vec();
+        section(&mut wasm, 1, &[1, 0x60, 1, 0x7f, 1, 0x7e]);
+
+        let mut import_section = vec![imports.len() as u8];
+        for (module, name) in imports {
+            import_section.push(module.len() as u8);
+            import_section.extend_from_slice(module.as_bytes());
+            import_section.push(name.len() as u8);
+            import_section.extend_from_slice(name.as_bytes());
+            import_section.extend_from_slice(&[0, 0]);
+        }
+        section(&mut wasm, 2, &import_section);
+        section(&mut wasm, 3, &[1, 0]);
+
+        let mut export_section = vec![1, export.len() as u8];
+        export_section.extend_from_slice(export.as_bytes());
+        export_section.extend_from_slice(&[0, imports.len() as u8]);
+        section(&mut wasm, 7, &export_section);
+
+        let mut body = vec![0];
+        body.extend_from_slice(code);
+        let mut code_section = vec![1, body.len() as u8];
+        code_section.extend_from_slice(&body);
+        section(&mut wasm, 10, &code_section);
+
+        wasm
+    }
+
+    fn section(wasm: &mut Vec<u8>, id: u8, contents: &[u8]) {
+        wasm.push(id);
+        wasm.push(contents.len() as u8);
+        wasm.extend_from_slice(contents);
+    }
+
+    // i64.const 0
+    const RETURN_0: &[u8] = &[0x42, 0x00, 0x0b];
+
+    #[test]
+    fn accepts_valid_hooks() {
+        let wasm = module(&[("env", "_g"), ("env", "accept")], "hook", RETURN_0);
+
+        assert_eq!(validate(&wasm).unwrap(), []);
+    }
+
+    #[test]
+    fn rejects_imports_other_than_host_functions() {
+        let wasm = module(
+            &[("env", "_g"), ("wbg", "__wbindgen_throw")],
+            "hook",
+            RETURN_0,
+        );
+
+        assert_eq!(
+            validate(&wasm).unwrap(),
+            [Violation::ForbiddenImport {
+                module: "wbg".into(),
+                name: "__wbindgen_throw".into()
+            }]
+        );
+    }
+
+    #[test]
+    fn rejects_hooks_without_guards_or_entry_point() {
+        let wasm = module(&[], "cbak", RETURN_0);
+
+        assert_eq!(
+            validate(&wasm).unwrap(),
+            [Violation::MissingGuardImport, Violation::MissingHookExport]
+        );
+    }
+
+    #[test]
+    fn rejects_floats_and_memory_growth() {
+        // f64.const 1, drop, i32.const 1, memory.grow 0, drop, i64.const 0
+        let mut code = vec![0x44, 0, 0, 0, 0, 0, 0, 0xf0, 0x3f, 0x1a];
+        code.extend_from_slice(&[0x41, 1, 0x40, 0, 0x1a]);
+        code.extend_from_slice(RETURN_0);
+        let wasm = module(&[("env", "_g")], "hook", &code);
+
+        let violations = validate(&wasm).unwrap();
+
+        assert_eq!(violations.len(), 2);
+        assert!(matches!(
+            &violations[0],
+            Violation::FloatInstruction { instruction, location }
+                if instruction == "F64Const" && location.function == 1
+        ));
+        assert!(
+            matches!(&violations[1], Violation::MemoryGrow(location) if location.function == 1)
+        );
+    }
+
+    #[test]
+    fn checks_the_type_of_entry_points() {
+        let mut wasm = module(&[("env", "_g")], "hook", RETURN_0);
+        // drop the parameter of the type, making it () -> i64
+        wasm[12] = 0;
+        wasm.remove(13);
+        wasm[9] -= 1;
+
+        assert_eq!(
+            validate(&wasm).unwrap(),
+            [Violation::InvalidEntryPointType("hook".into())]
+        );
+    }
+}
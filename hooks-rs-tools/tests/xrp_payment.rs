@@ -0,0 +1,65 @@
+//! Runs the `xrp_payment_txn` example on a xahaud node, checking that the payment built
+//! by `XrpPaymentBuilder` is applied and moves the balances it should.
+//!
+//! Needs a standalone node, see the `testkit` module:
+//!
+//! ```bash
+//! XAHAU_RPC_URL=http://localhost:5005 cargo test --features testkit -- --ignored
+//! ```
+
+#![cfg(feature = "testkit")]
+
+use hooks_rs::{hookon, Namespace};
+use hooks_rs_tools::testkit::{build_example, Node};
+use hooks_rs_tools::SetHook;
+
+const HOOK_NAME: &str = "xrp_payment_txn";
+
+const DROPS_SENT: u64 = 1000;
+
+#[test]
+#[ignore = "needs a standalone xahaud node, see XAHAU_RPC_URL"]
+fn emits_an_xrp_payment_the_node_applies() {
+    let node = Node::from_env().unwrap();
+    let alice = node.new_account().unwrap();
+    let bob = node.new_account().unwrap();
+
+    let set_hook = SetHook::new(
+        build_example(HOOK_NAME).unwrap(),
+        Namespace::from_label(HOOK_NAME.as_bytes()),
+    )
+    .hook_on(hookon!(Invoke));
+    node.set_hook(&alice, &set_hook).unwrap();
+
+    let alice_balance_before = node.balance(&alice.address).unwrap();
+    let bob_balance_before = node.balance(&bob.address).unwrap();
+
+    let invoke = node.invoke(&bob, &alice).unwrap();
+    let executions = invoke.hook_executions();
+    assert_eq!(executions.len(), 1);
+    assert_eq!(executions[0].return_code, 0);
+    assert_eq!(executions[0].emit_count, 1);
+
+    let emitted = node.emitted(&invoke).unwrap();
+    assert_eq!(emitted.len(), 1);
+    let payment = &emitted[0];
+    assert_eq!(payment.result(), "tesSUCCESS");
+    // the hook accepts with the hash of the payment it emitted
+    assert_eq!(
+        hooks_rs_tools::hex(&executions[0].return_string),
+        payment.hash
+    );
+    assert_eq!(payment.json["TransactionType"], "Payment");
+    assert_eq!(payment.json["Account"], alice.address.as_str());
+    assert_eq!(payment.json["Destination"], bob.address.as_str());
+    assert_eq!(payment.json["Amount"], DROPS_SENT.to_string());
+
+    assert_eq!(
+        node.balance(&alice.address).unwrap(),
+        alice_balance_before - DROPS_SENT - payment.fee()
+    );
+    assert_eq!(
+        node.balance(&bob.address).unwrap(),
+        bob_balance_before - invoke.fee() + DROPS_SENT
+    );
+}
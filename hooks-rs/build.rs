@@ -1,4 +1,4 @@
-use std::{env, path::PathBuf};
+use std::{env, fmt::Write, fs, path::PathBuf};
 
 fn main() {
     println!("cargo:rerun-if-changed=c/");
@@ -20,4 +20,49 @@ fn main() {
     bindings
         .write_to_file(out_path)
         .expect("Couldn't write bindings!");
+
+    let sfields_path = PathBuf::from(env::var("OUT_DIR").unwrap()).join("sfields.rs");
+    fs::write(
+        sfields_path,
+        sfields(&fs::read_to_string("c/sfcodes.h").unwrap()),
+    )
+    .expect("Couldn't write the field definitions!");
+}
+
+/// The `field_ids!` invocation defining `FieldId`, with a variant for every
+/// `#define sf<Name> ((<type> << 16U) + <code>)` of `sfcodes.h`, and the version of the
+/// definitions: an FNV-1a hash of the names and IDs, so it changes with every field added,
+/// removed or renumbered.
+fn sfields(sfcodes: &str) -> String {
+    let mut invocation = String::from("field_ids! {\n");
+    let mut version: u32 = 0x811C_9DC5;
+    for line in sfcodes.lines() {
+        let Some(definition) = line.strip_prefix("#define sf") else {
+            continue;
+        };
+        let (name, id) = definition.split_once(' ').expect("sfcodes.h: no field ID");
+        let numbers: Vec<u32> = id
+            .split(|c: char| !c.is_ascii_digit())
+            .filter(|number| !number.is_empty())
+            .map(|number| number.parse().unwrap())
+            .collect();
+        let [type_code, 16, field_code] = numbers[..] else {
+            panic!("sfcodes.h: unexpected field ID {id} of {name}");
+        };
+
+        let _ = writeln!(invocation, "    {name} = c::sf{name},");
+        let field_id = (type_code << 16) + field_code;
+        for byte in name.bytes().chain(field_id.to_be_bytes()) {
+            version = (version ^ byte as u32).wrapping_mul(0x0100_0193);
+        }
+    }
+    invocation.push_str("}\n\n");
+    let _ = writeln!(
+        invocation,
+        "/// Version of the field definitions of [FieldId], which changes with every field\n\
+         /// added, removed or renumbered\n\
+         pub const SFIELDS_VERSION: u32 = {version:#010X};"
+    );
+
+    invocation
 }
@@ -0,0 +1,41 @@
+//! This hook splits a payment of 1000 drops into two payments to the account that
+//! initiated the hook, and returns the number of emitted transactions.
+
+#![no_std]
+#![no_main]
+
+use hooks_rs::*;
+
+#[no_mangle]
+pub extern "C" fn cbak(_: u32) -> i64 {
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn hook(_: u32) -> i64 {
+    // Every hook needs to import guard function
+    // and use it at least once
+    max_iter(1);
+
+    let otxn_account = match otxn_field::<ACC_ID_LEN>(FieldId::Account) {
+        Ok(account) => AccountId(account),
+        Err(err) => {
+            rollback(b"could not get otxn account", err.into());
+        }
+    };
+    let builders = [
+        XrpPaymentBuilder::new(Drops(400), &otxn_account, None, None),
+        XrpPaymentBuilder::new(Drops(600), &otxn_account, None, None),
+    ];
+    let batch = match emit_all(&builders) {
+        Ok(batch) => batch,
+        Err(err) => {
+            rollback(b"could not reserve txns", err.into());
+        }
+    };
+    if let Some(err) = batch.first_error() {
+        rollback(b"could not emit xrp payment txn", err.into());
+    }
+
+    accept(b"emitted", batch.emitted_count() as i64);
+}
@@ -0,0 +1,23 @@
+//! A hook that returns the generation a transaction emitted by it would have,
+//! after making sure it is not part of a chain of emitted transactions.
+
+#![no_std]
+#![no_main]
+
+use hooks_rs::*;
+
+#[no_mangle]
+pub extern "C" fn cbak(_: u32) -> i64 {
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn hook(_: u32) -> i64 {
+    // Every hook needs to import guard function
+    // and use it at least once
+    max_iter(1);
+
+    guard_against_emit_loops(1);
+
+    accept(b"", etxn_generation());
+}
@@ -0,0 +1,25 @@
+//! Screens the originating transaction with `filter!` and accepts it if it is
+//! sent to the hook account.
+
+#![no_std]
+#![no_main]
+
+use hooks_rs::*;
+
+#[no_mangle]
+pub extern "C" fn cbak(_: u32) -> i64 {
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn hook(_: u32) -> i64 {
+    // Every hook needs to import guard function
+    // and use it at least once
+    max_iter(1);
+
+    filter! {
+        destination: hook_account,
+    }
+
+    accept(b"filter.rs: passed", 0);
+}
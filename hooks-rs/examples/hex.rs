@@ -0,0 +1,31 @@
+//! A hook that hex encodes a hash, decodes it back and returns the hex string.
+
+#![no_std]
+#![no_main]
+
+use hooks_rs::*;
+
+#[no_mangle]
+pub extern "C" fn cbak(_: u32) -> i64 {
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn hook(_: u32) -> i64 {
+    // Every hook needs to import guard function
+    // and use it at least once
+    max_iter(1);
+
+    let hash = sha512h(b"hooks-rs");
+
+    let mut hash_hex = [0; HASH_LEN * 2];
+    hex::encode_to(&hash, &mut hash_hex).unwrap_line_number();
+
+    let mut decoded = [0; HASH_LEN];
+    hex::decode_to(&hash_hex, &mut decoded).unwrap_line_number();
+    if !is_buffer_equal(&hash, &decoded) {
+        rollback(b"hex.rs: decoded hash is different", line!().into());
+    }
+
+    accept(&hash_hex, 0);
+}
@@ -19,5 +19,5 @@ pub extern "C" fn hook(_: u32) -> i64 {
         }
     };
 
-    accept(&hook_account, 0);
+    accept(hook_account.as_bytes(), 0);
 }
@@ -0,0 +1,27 @@
+//! Loads install-time hook parameters into a struct deriving `HookParams`, and
+//! accepts with `min drops` as the return code and `greeting` as the message.
+
+#![no_std]
+#![no_main]
+
+use hooks_rs::prelude::*;
+
+#[derive(HookParams)]
+struct Config {
+    #[param(name = "min drops")]
+    min_drops: u64,
+    #[param(default = *b"default greeting")]
+    greeting: [u8; 16],
+}
+
+#[callback]
+fn on_callback() -> Outcome<'static> {
+    Outcome::Accept { code: 0, msg: b"" }
+}
+
+#[hook]
+fn handle() -> Outcome<'static> {
+    let config = Config::load()?;
+
+    accept(&config.greeting, config.min_drops as i64)
+}
@@ -0,0 +1,31 @@
+//! A hook implemented as a type with the `Hook` trait and registered with
+//! `register_hook!`. It rejects payments and accepts everything else.
+
+#![no_std]
+#![no_main]
+
+use hooks_rs::*;
+
+struct PaymentBlocker {
+    accepted_msg: &'static [u8],
+}
+
+impl Hook for PaymentBlocker {
+    fn on_transaction(&self, ctx: HookCtx) -> Outcome<'_> {
+        if ctx.is_txn(TxnType::Payment) {
+            return Outcome::Rollback {
+                code: ctx.otxn_type,
+                msg: b"hook_trait.rs: payments are not accepted",
+            };
+        }
+
+        Outcome::Accept {
+            code: 0,
+            msg: self.accepted_msg,
+        }
+    }
+}
+
+register_hook!(PaymentBlocker {
+    accepted_msg: b"hook_trait.rs: accepted",
+});
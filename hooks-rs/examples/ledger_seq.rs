@@ -18,5 +18,5 @@ pub extern "C" fn hook(_: u32) -> i64 {
 
     let ledger_seq = ledger_seq();
 
-    accept(b"", ledger_seq);
+    accept(b"", ledger_seq.into());
 }
@@ -0,0 +1,26 @@
+//! Dispatches on the type of the originating transaction with `match_txn!`.
+
+#![no_std]
+#![no_main]
+
+use hooks_rs::*;
+
+#[no_mangle]
+pub extern "C" fn cbak(_: u32) -> i64 {
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn hook(_: u32) -> i64 {
+    // Every hook needs to import guard function
+    // and use it at least once
+    max_iter(1);
+
+    let msg: &[u8] = match_txn! {
+        Payment => { b"match_txn.rs: payment" },
+        Invoke | AccountSet => { b"match_txn.rs: invoke or account set" },
+        _ => { rollback(b"match_txn.rs: unsupported transaction type", otxn_type()) },
+    };
+
+    accept(msg, 0);
+}
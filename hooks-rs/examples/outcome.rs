@@ -0,0 +1,31 @@
+//! A hook whose logic returns an `Outcome`, with the entry points generated by
+//! `#[hook]` and `#[callback]`. It rejects payments and accepts everything
+//! else.
+
+#![no_std]
+#![no_main]
+
+use hooks_rs::prelude::*;
+
+#[callback]
+fn on_callback() -> Outcome<'static> {
+    Outcome::Accept { code: 0, msg: b"" }
+}
+
+#[hook]
+fn handle() -> Outcome<'static> {
+    // Errors returned with `?` rollback with their error code
+    let _hook_account = hook_account()?;
+
+    if otxn_type() == TxnType::Payment as i64 {
+        return Outcome::Rollback {
+            code: otxn_type(),
+            msg: b"outcome.rs: payments are not accepted",
+        };
+    }
+
+    Outcome::Accept {
+        code: 0,
+        msg: b"outcome.rs: accepted",
+    }
+}
@@ -0,0 +1,28 @@
+//! This hook sends a payment transaction to the same account that initiated the hook,
+//! built with `prepare_payment_simple!` like a C hook would with `PREPARE_PAYMENT_SIMPLE`.
+
+#![no_std]
+#![no_main]
+
+use hooks_rs::*;
+
+#[no_mangle]
+pub extern "C" fn cbak(_: u32) -> i64 {
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn hook(_: u32) -> i64 {
+    // Every hook needs to import guard function
+    // and use it at least once
+    max_iter(1);
+    let _ = etxn_reserve(1);
+
+    let otxn_account = AccountId(otxn_field::<ACC_ID_LEN>(FieldId::Account).unwrap_line_number());
+
+    let mut tx = XrpPaymentBuilder::uninit_buffer();
+    prepare_payment_simple!(tx, 1000, &otxn_account, 0, 0).unwrap_line_number();
+    let txn_hash = emit(&tx).unwrap_line_number();
+
+    accept(&txn_hash, 0);
+}
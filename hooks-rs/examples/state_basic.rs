@@ -33,10 +33,10 @@ pub extern "C" fn hook(_: u32) -> i64 {
         let _count_again = get_count(&otxn_account);
     }
     {
-        set_count(1, &hook_account);
-        let count = get_count(&hook_account);
-        set_count(count + 1, &hook_account);
-        let count_again = get_count(&hook_account);
+        set_count(1, hook_account.as_bytes());
+        let count = get_count(hook_account.as_bytes());
+        set_count(count + 1, hook_account.as_bytes());
+        let count_again = get_count(hook_account.as_bytes());
 
         accept(&count_again.to_be_bytes(), 0);
     }
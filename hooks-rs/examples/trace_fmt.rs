@@ -0,0 +1,35 @@
+//! A hook that formats a message with the account that initiated the hook,
+//! writes it to the trace log and returns it as the return string.
+
+#![no_std]
+#![no_main]
+
+use hooks_rs::*;
+
+#[no_mangle]
+pub extern "C" fn cbak(_: u32) -> i64 {
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn hook(_: u32) -> i64 {
+    // Every hook needs to import guard function
+    // and use it at least once
+    max_iter(1);
+
+    let otxn_account = match otxn_field::<ACC_ID_LEN>(FieldId::Account) {
+        Ok(account) => account,
+        Err(err) => {
+            rollback(b"could not get otxn account", err.into());
+        }
+    };
+
+    let _ = trace!("trace_fmt.rs: called by {}", otxn_account);
+
+    let mut writer = TraceWriter::<TRACE_MSG_MAX_LEN>::new("{} {} from {}");
+    writer.arg(&-42i64);
+    writer.arg("drops");
+    writer.arg(&otxn_account);
+
+    accept(writer.as_bytes(), 0);
+}
@@ -140,5 +140,5 @@ pub extern "C" fn hook(_: u32) -> i64 {
         }
     };
 
-    accept(&acc_id, 0);
+    accept(acc_id.as_bytes(), 0);
 }
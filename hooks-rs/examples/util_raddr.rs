@@ -41,7 +41,7 @@ pub extern "C" fn hook(_: u32) -> i64 {
     let account_id = unsafe {
         uninitialized_account_id
             .as_ptr()
-            .cast::<[u8; 20]>()
+            .cast::<AccountId>()
             .read_volatile()
     };
     let raddr = match util_raddr(&account_id) {
@@ -0,0 +1,22 @@
+//! A hook that hashes a fixed payload and returns the hash as the return string.
+
+#![no_std]
+#![no_main]
+
+use hooks_rs::*;
+
+#[no_mangle]
+pub extern "C" fn cbak(_: u32) -> i64 {
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn hook(_: u32) -> i64 {
+    // Every hook needs to import guard function
+    // and use it at least once
+    max_iter(1);
+
+    let hash = sha512h(b"hooks-rs");
+
+    accept(&hash, 0);
+}
@@ -0,0 +1,20 @@
+//! Decodes random bytes as r-addresses, checking that decoding doesn't panic and that
+//! every account ID that is decoded encodes back to the same r-address.
+
+#![no_main]
+
+use hooks_rs::AccountId;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|r_address: &[u8]| {
+    let Some(account_id) = AccountId::from_raddress(r_address) else {
+        return;
+    };
+
+    let encoded = account_id.to_raddress_const();
+    let encoded_len = encoded
+        .iter()
+        .position(|byte| *byte == 0)
+        .unwrap_or(encoded.len());
+    assert_eq!(&encoded[..encoded_len], r_address);
+});
@@ -0,0 +1,26 @@
+//! Decodes random bytes as hex into buffers of random length, checking that invalid
+//! input is rejected without panicking and that valid input encodes back to itself.
+
+#![no_main]
+
+use hooks_rs::*;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (Vec<u8>, u8)| {
+    let (hex, out_len) = input;
+    let mut out = vec![0; out_len as usize];
+
+    let decoded_len = match hex::decode_to(&hex, &mut out) {
+        Ok(decoded_len) => decoded_len,
+        Err(HookError::InvalidArgument) | Err(HookError::TooSmall) => return,
+        Err(_) => panic!("unexpected error"),
+    };
+    assert_eq!(decoded_len * 2, hex.len());
+
+    let mut encoded = vec![0; hex.len()];
+    assert!(matches!(
+        hex::encode_to(&out[..decoded_len], &mut encoded),
+        Ok(len) if len == hex.len()
+    ));
+    assert_eq!(encoded, hex.to_ascii_uppercase());
+});
@@ -0,0 +1,139 @@
+//! Sets random bytes as the fields of the originating transaction and reads them back
+//! with the parsers of the crate, `OtxnFields`, `AmountPolicy` and `SliceReader`,
+//! checking that they only hand out bytes the host wrote and never read past the end
+//! of the scratch buffer or of a field.
+
+#![no_main]
+
+use core::mem::MaybeUninit;
+use std::collections::HashMap;
+
+use arbitrary::Arbitrary;
+use hooks_rs::*;
+use libfuzzer_sys::fuzz_target;
+
+const SCRATCH_MAX_LEN: usize = 512;
+
+const FIELD_IDS: [FieldId; 6] = [
+    FieldId::Account,
+    FieldId::Destination,
+    FieldId::Amount,
+    FieldId::Fee,
+    FieldId::InvoiceID,
+    FieldId::Memos,
+];
+
+#[derive(Arbitrary, Debug)]
+enum Read {
+    Account(u8),
+    Amount(u8),
+    Bytes { field: u8, max_len: u16 },
+}
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    fields: Vec<(u8, Vec<u8>)>,
+    scratch_len: u16,
+    from_slot: bool,
+    reads: Vec<Read>,
+    max_drops: u64,
+}
+
+fn field_id(field: u8) -> FieldId {
+    FIELD_IDS[field as usize % FIELD_IDS.len()]
+}
+
+fn check_error(err: HookError) {
+    match err {
+        HookError::DoesntExist | HookError::TooSmall | HookError::InvalidArgument => {}
+        err => panic!("unexpected error {err:?}"),
+    }
+}
+
+// Reads `field_id` with `read` and checks that the bytes are the value of the field and
+// come out of the free part of the scratch buffer
+fn check_read<'a>(
+    fields: &mut OtxnFields<'a>,
+    values: &HashMap<u32, Vec<u8>>,
+    field_id: FieldId,
+    read: impl FnOnce(&mut OtxnFields<'a>) -> Result<&'a [u8]>,
+) {
+    let remaining = fields.remaining();
+    match read(fields) {
+        Ok(bytes) => {
+            assert_eq!(Some(bytes), values.get(&(field_id as u32)).map(|v| &v[..]));
+            assert_eq!(fields.remaining(), remaining - bytes.len());
+        }
+        Err(err) => {
+            check_error(err);
+            assert_eq!(fields.remaining(), remaining);
+        }
+    }
+}
+
+fuzz_target!(|input: Input| {
+    mock::reset();
+    let mut values = HashMap::new();
+    for (field, value) in &input.fields {
+        let field_id = field_id(*field);
+        mock::set_otxn_field(field_id, value);
+        values.insert(field_id as u32, value.clone());
+    }
+
+    let mut scratch = [MaybeUninit::new(0xAA); SCRATCH_MAX_LEN];
+    let scratch = &mut scratch[..input.scratch_len as usize % (SCRATCH_MAX_LEN + 1)];
+    let mut otxn = match OtxnSlot::load() {
+        Ok(otxn) => otxn,
+        Err(_) => panic!("could not slot the originating transaction"),
+    };
+    let mut fields = if input.from_slot {
+        OtxnFields::from_slot(&mut otxn, scratch)
+    } else {
+        OtxnFields::new(scratch)
+    };
+
+    for read in &input.reads {
+        match *read {
+            Read::Account(field) => check_read(&mut fields, &values, field_id(field), |fields| {
+                let account = fields.account(field_id(field))?;
+                Ok(&account.as_account_id().0[..])
+            }),
+            Read::Amount(field) => check_read(&mut fields, &values, field_id(field), |fields| {
+                let amount = fields.amount(field_id(field))?;
+                let bytes = amount.as_bytes();
+                if amount.is_xrp() {
+                    assert_eq!(bytes.len(), 8);
+                    assert!(amount.drops().is_some() && amount.currency().is_none());
+                } else {
+                    assert_eq!(bytes.len(), AMOUNT_LEN);
+                    assert_eq!(amount.currency().map(|c| &c[..]), Some(&bytes[8..28]));
+                    assert_eq!(amount.issuer().map(|i| &i.0[..]), Some(&bytes[28..]));
+                }
+                Ok(bytes)
+            }),
+            Read::Bytes { field, max_len } => {
+                check_read(&mut fields, &values, field_id(field), |fields| {
+                    let bytes = fields.bytes(field_id(field), max_len as usize)?;
+                    assert!(bytes.len() <= max_len as usize);
+                    Ok(bytes)
+                })
+            }
+        }
+    }
+
+    match AmountPolicy::xrp(None, Some(Drops(input.max_drops))).check_otxn() {
+        Ok(()) => {}
+        Err(HookError::DoesNotMatch | HookError::TooBig) => {}
+        Err(err) => check_error(err),
+    }
+
+    // memos are read with a reader, which has to stop at the end of the field
+    if let Some(memos) = values.get(&(FieldId::Memos as u32)) {
+        let mut reader = SliceReader::new(memos);
+        while let Ok(blob) = reader.read_vl() {
+            assert!(blob.len() <= memos.len());
+            assert_eq!(reader.pos() + reader.remaining(), memos.len());
+        }
+        assert!(reader.pos() <= memos.len());
+    }
+});
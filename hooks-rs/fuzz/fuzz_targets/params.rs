@@ -0,0 +1,34 @@
+//! Decodes random bytes as hook parameters and currency codes, which come from
+//! whoever installs or invokes the hook, checking that nothing panics.
+
+#![no_main]
+
+use hooks_rs::*;
+use libfuzzer_sys::fuzz_target;
+
+fn check<T: FromHookParam>(bytes: &[u8], len: usize) {
+    match T::from_param_bytes(bytes) {
+        Ok(_) => assert_eq!(bytes.len(), len),
+        Err(HookError::InvalidArgument) => assert_ne!(bytes.len(), len),
+        Err(_) => panic!("unexpected error"),
+    }
+}
+
+fuzz_target!(|bytes: &[u8]| {
+    check::<u8>(bytes, 1);
+    check::<u16>(bytes, 2);
+    check::<u32>(bytes, 4);
+    check::<u64>(bytes, 8);
+    check::<i64>(bytes, 8);
+    check::<XFL>(bytes, 8);
+    check::<AccountId>(bytes, ACC_ID_LEN);
+    check::<PublicKey>(bytes, PUBLIC_KEY_LEN);
+
+    if let Some(currency) = currency_code_from_iso(bytes) {
+        assert_eq!(currency[12..15], *bytes);
+    }
+    if let Some(currency) = currency_code_from_hex(bytes) {
+        assert_eq!(bytes.len(), CURRENCY_CODE_SIZE * 2);
+        assert!(currency_code_from_hex(&bytes.to_ascii_lowercase()) == Some(currency));
+    }
+});
@@ -0,0 +1,192 @@
+//! Encodes random sequences of fields with `TransactionBuffer` and checks that every
+//! encoder writes exactly the bytes of its field, no more and no less, and that the
+//! result decodes back into the same fields.
+
+#![no_main]
+
+use core::mem::MaybeUninit;
+
+use arbitrary::Arbitrary;
+use hooks_rs::decode::decode;
+use hooks_rs::*;
+use libfuzzer_sys::fuzz_target;
+
+const TXN_LEN: usize = 512;
+/// Bytes not written by any encoder keep this value
+const UNTOUCHED: u8 = 0xAA;
+
+const TXN_TYPES: [TxnType; 6] = [
+    TxnType::Payment,
+    TxnType::EscrowCreate,
+    TxnType::OfferCreate,
+    TxnType::TrustSet,
+    TxnType::HookSet,
+    TxnType::Invoke,
+];
+// Amount types with a field code of 16 or more need a two byte field header,
+// which the encoders don't support
+const AMOUNT_TYPES: [AmountType; 10] = [
+    AmountType::Amount,
+    AmountType::Balance,
+    AmountType::LimitAmount,
+    AmountType::TakerPays,
+    AmountType::TakerGets,
+    AmountType::LowLimit,
+    AmountType::HighLimit,
+    AmountType::Fee,
+    AmountType::SendMax,
+    AmountType::DeliverMin,
+];
+const ACCOUNT_TYPES: [AccountType; 9] = [
+    AccountType::Account,
+    AccountType::Owner,
+    AccountType::Destination,
+    AccountType::Issuer,
+    AccountType::Authorize,
+    AccountType::Unauthorize,
+    AccountType::Target,
+    AccountType::RegularKey,
+    AccountType::PseudoCallback,
+];
+
+#[derive(Arbitrary, Debug)]
+enum Field {
+    TxnType(u8),
+    U32 {
+        data: u32,
+        field: u8,
+    },
+    U32WithFieldId {
+        data: u32,
+        field: u8,
+    },
+    Drops {
+        drops: u64,
+        amount_type: u8,
+    },
+    IssuedAmount {
+        amount: [u8; ISSUED_AMOUNT_LEN],
+        amount_type: u8,
+    },
+    SigningPubKeyAsNull,
+    Account {
+        account_id: [u8; ACC_ID_LEN],
+        account_type: u8,
+    },
+}
+
+impl Field {
+    /// Encode the field with `txn_buffer` and return the bytes it should have written
+    fn encode(&self, txn_buffer: &mut TransactionBuffer<'_, TXN_LEN>) -> Vec<u8> {
+        match *self {
+            Field::TxnType(index) => {
+                let txn_type = TXN_TYPES[index as usize % TXN_TYPES.len()];
+                txn_buffer.encode_txn_type(txn_type);
+
+                [&[0x12][..], &(txn_type as u16).to_be_bytes()].concat()
+            }
+            Field::U32 { data, field } => {
+                // field codes that fit into the type byte
+                let field = 1 + field % 15;
+                txn_buffer.encode_u32(data, field);
+
+                [&[0x20 + field][..], &data.to_be_bytes()].concat()
+            }
+            Field::U32WithFieldId { data, field } => {
+                // field codes that need a byte of their own
+                let field = 16 + field % 240;
+                txn_buffer.encode_u32_with_field_id(data, field);
+
+                [&[0x20, field][..], &data.to_be_bytes()].concat()
+            }
+            Field::Drops { drops, amount_type } => {
+                let amount_type = AMOUNT_TYPES[amount_type as usize % AMOUNT_TYPES.len()];
+                // larger amounts don't fit into the 62 bits of an XRP amount
+                let drops = drops & 0x3FFF_FFFF_FFFF_FFFF;
+                txn_buffer.encode_drops(drops, amount_type);
+
+                let amount_type: u8 = amount_type.into();
+                [
+                    &[0x60 + amount_type][..],
+                    &(drops | 0x4000_0000_0000_0000).to_be_bytes(),
+                ]
+                .concat()
+            }
+            Field::IssuedAmount {
+                mut amount,
+                amount_type,
+            } => {
+                let amount_type = AMOUNT_TYPES[amount_type as usize % AMOUNT_TYPES.len()];
+                // issued amounts have the highest bit set
+                amount[0] |= 0x80;
+                txn_buffer.encode_issued_amount(&amount, amount_type);
+
+                let amount_type: u8 = amount_type.into();
+                [&[0x60 + amount_type][..], &amount].concat()
+            }
+            Field::SigningPubKeyAsNull => {
+                txn_buffer.encode_signing_pubkey_as_null();
+
+                [&[0x73, 0x21][..], &[0; 33]].concat()
+            }
+            Field::Account {
+                account_id,
+                account_type,
+            } => {
+                let account_type = ACCOUNT_TYPES[account_type as usize % ACCOUNT_TYPES.len()];
+                txn_buffer.encode_account(&AccountId(account_id), account_type);
+
+                let account_type: u8 = account_type.into();
+                [&[0x80 + account_type, 0x14][..], &account_id].concat()
+            }
+        }
+    }
+
+    /// Number of bytes the field takes
+    fn len(&self) -> usize {
+        match self {
+            Field::TxnType(_) => 3,
+            Field::U32 { .. } => 5,
+            Field::U32WithFieldId { .. } => 6,
+            Field::Drops { .. } => 9,
+            Field::IssuedAmount { .. } => 1 + ISSUED_AMOUNT_LEN,
+            Field::SigningPubKeyAsNull => 35,
+            Field::Account { .. } => 22,
+        }
+    }
+}
+
+fn bytes(buf: &[MaybeUninit<u8>; TXN_LEN]) -> [u8; TXN_LEN] {
+    // every byte is initialized with UNTOUCHED before encoding
+    buf.map(|byte| unsafe { byte.assume_init() })
+}
+
+fuzz_target!(|fields: Vec<Field>| {
+    let mut buf = [MaybeUninit::new(UNTOUCHED); TXN_LEN];
+    let mut expected = [UNTOUCHED; TXN_LEN];
+    let mut encoded_fields = 0;
+    let mut txn_buffer = TransactionBuffer::new(&mut buf);
+
+    for field in &fields {
+        let pos = txn_buffer.pos();
+        // the encoders don't check bounds, so it is up to the caller to stop
+        if pos + field.len() > TXN_LEN {
+            break;
+        }
+
+        let field_bytes = field.encode(&mut txn_buffer);
+        assert_eq!(field_bytes.len(), field.len());
+        assert_eq!(txn_buffer.pos(), pos + field.len(), "{field:?}");
+        expected[pos..pos + field.len()].copy_from_slice(&field_bytes);
+        encoded_fields += 1;
+    }
+
+    let pos = txn_buffer.pos();
+    let actual = bytes(&buf);
+    assert_eq!(actual[..pos], expected[..pos], "{fields:?}");
+    assert!(
+        actual[pos..].iter().all(|byte| *byte == UNTOUCHED),
+        "wrote past the last field: {fields:?}"
+    );
+    assert_eq!(decode(&actual[..pos]).fields.len(), encoded_fields);
+});
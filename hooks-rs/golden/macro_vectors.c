@@ -0,0 +1,116 @@
+/**
+ * Prints the buffers the PREPARE_* macros of macro.h build, for the golden vectors in
+ * src/macro_vectors.rs. To regenerate them, run this from hooks-rs:
+ *
+ *   gcc -std=gnu11 -I c golden/macro_vectors.c -o /tmp/macro_vectors && /tmp/macro_vectors
+ *
+ * The macros are meant to be compiled to wasm, where the host API takes pointers as
+ * 32 bit integers. To run them natively instead, the host functions they call are
+ * replaced with stubs that behave like the mock host of the crate and reach the
+ * buffers of the macro by name.
+ */
+
+#define HAS_CALLBACK 1
+
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+#include "hookapi.h"
+
+struct host {
+    uint8_t hook_account[20];
+    uint8_t otxn_id[32];
+    uint32_t ledger_seq;
+    int64_t fee_base;
+};
+
+static struct host host;
+
+#define EMIT_DETAILS_SIZE 138
+
+int32_t _g(uint32_t guard_id, uint32_t maxiter) {
+    return 1;
+}
+
+static void stub_hook_account(uint8_t *write) {
+    memcpy(write, host.hook_account, 20);
+}
+
+// emit details of a first generation emission without a hook hash, like the mock host
+static void stub_etxn_details(uint8_t *write) {
+    uint8_t *out = write;
+    *out++ = 0xED;
+    *out++ = 0x20; *out++ = 0x2E;
+    *out++ = 0; *out++ = 0; *out++ = 0; *out++ = 1;
+    *out++ = 0x3D;
+    memset(out, 0, 7); out += 7; *out++ = 1;
+    *out++ = 0x5B;
+    memcpy(out, host.otxn_id, 32); out += 32;
+    *out++ = 0x5C;
+    memset(out, 0, 32); out += 32;
+    *out++ = 0x8A; *out++ = 0x14;
+    memcpy(out, host.hook_account, 20); out += 20;
+    *out++ = 0x5D;
+    memset(out, 0, 32); out += 32;
+    *out++ = 0xE1;
+}
+
+#define ledger_seq() ((int64_t)host.ledger_seq)
+#define hook_account(...) stub_hook_account(acc)
+#define etxn_details(...) (stub_etxn_details(buf_out), EMIT_DETAILS_SIZE)
+#define etxn_fee_base(...) (host.fee_base)
+
+static void print(const char *name, const uint8_t *buf, size_t len) {
+    printf("%s (%zu bytes)\n", name, len);
+    for (size_t i = 0; i < len; ++i)
+        printf("%02X", buf[i]);
+    printf("\n\n");
+}
+
+static void set_host(uint8_t *buf, uint8_t hook_account, uint8_t otxn_id, uint32_t ledger_seq,
+                     int64_t fee_base) {
+    // ENCODE_SIGNING_PUBKEY_NULL leaves the last two bytes of the key as they are, which
+    // only works in fresh wasm memory that is still zero
+    memset(buf, 0, PREPARE_PAYMENT_SIMPLE_TRUSTLINE_SIZE + 1);
+    memset(host.hook_account, hook_account, 20);
+    memset(host.otxn_id, otxn_id, 32);
+    host.ledger_seq = ledger_seq;
+    host.fee_base = fee_base;
+}
+
+int main(void) {
+    // PREPARE_PAYMENT_SIMPLE_TRUSTLINE_SIZE is one byte too short for the emit details,
+    // so leave room for the end marker
+    uint8_t buf[PREPARE_PAYMENT_SIMPLE_TRUSTLINE_SIZE + 1];
+    uint8_t to[20];
+    uint8_t amount[48];
+
+    set_host(buf, 1, 3, 100, 10);
+    memset(to, 2, 20);
+    PREPARE_PAYMENT_SIMPLE(buf, 1000, to, 7, 8);
+    print("XRP_PAYMENT", buf, PREPARE_PAYMENT_SIMPLE_SIZE);
+
+    set_host(buf, 0xAB, 0x5E, 85000000, 12);
+    for (int i = 0; i < 20; ++i)
+        to[i] = i;
+    PREPARE_PAYMENT_SIMPLE(buf, 100000000000000000ULL, to, 0xFFFFFFFF, 0);
+    print("XRP_PAYMENT_MAX", buf, PREPARE_PAYMENT_SIMPLE_SIZE);
+
+    set_host(buf, 1, 3, 100, 10);
+    memset(to, 2, 20);
+    memset(amount, 4, 48);
+    amount[0] = 0xD4;
+    PREPARE_PAYMENT_SIMPLE_TRUSTLINE(buf, amount, to, 7, 8);
+    print("TRUSTLINE_PAYMENT", buf, sizeof(buf));
+
+    set_host(buf, 0xAB, 0x5E, 85000000, 12);
+    for (int i = 0; i < 20; ++i)
+        to[i] = i;
+    for (int i = 0; i < 48; ++i)
+        amount[i] = 0x80 + i;
+    PREPARE_PAYMENT_SIMPLE_TRUSTLINE(buf, amount, to, 0xFFFFFFFF, 0);
+    print("TRUSTLINE_PAYMENT_MAX", buf, sizeof(buf));
+
+    return 0;
+}
@@ -0,0 +1,292 @@
+//! Allowlists and blocklists of accounts kept in hook state.
+//!
+//! An [AccessList] keeps one state entry for every account on it, under a key made of
+//! the name of the list and the account, so that a hook can keep several lists, such as
+//! an allowlist and a blocklist, next to the rest of its state. Checking an account is a
+//! single [state] read, whatever the size of the list.
+//!
+//! The hook account manages the lists with Invoke transactions carrying the account to
+//! add or remove as the [AccessList::ADD_PARAM] or [AccessList::REMOVE_PARAM] parameter,
+//! which [AccessList::apply_invoke] applies.
+//!
+//! # Example
+//! ```
+//! const BLOCKED: AccessList = AccessList::new(b"blocked");
+//!
+//! #[hook]
+//! fn firewall() -> Outcome<'static> {
+//!     if BLOCKED.apply_invoke()?.is_some() {
+//!         return Outcome::Accept { code: 0, msg: b"blocklist updated" };
+//!     }
+//!
+//!     let sender = AccountId(otxn_field::<ACC_ID_LEN>(FieldId::Account)?);
+//!     if BLOCKED.contains(&sender)? {
+//!         return Outcome::Rollback { code: 1, msg: b"sender is blocked" };
+//!     }
+//!
+//!     Outcome::Accept { code: 0, msg: b"" }
+//! }
+//! ```
+
+use crate::api::*;
+use crate::params::otxn_param_as;
+
+/// Maximum length of the name of an [AccessList], the part of the state keys that is not
+/// the account
+pub const ACCESS_LIST_NAME_MAX_LEN: usize = STATE_KEY_LEN - ACC_ID_LEN;
+
+/// A set of accounts kept in hook state, such as the accounts a hook accepts payments
+/// from or the ones it rejects.
+#[derive(Clone, Copy)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct AccessList {
+    // the name, zero padded, which the state keys of the list start with
+    name: [u8; ACCESS_LIST_NAME_MAX_LEN],
+}
+
+/// A change made to an [AccessList] by [AccessList::apply_invoke]
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub enum AccessListChange {
+    /// The account was added to the list
+    Added(AccountId),
+    /// The account was removed from the list
+    Removed(AccountId),
+}
+
+impl AccessList {
+    /// Invoke parameter with the account to add to a list
+    pub const ADD_PARAM: &'static [u8] = b"ADD";
+
+    /// Invoke parameter with the account to remove from a list
+    pub const REMOVE_PARAM: &'static [u8] = b"REMOVE";
+
+    /// The list named `name`, which tells it apart from the other lists of the hook.
+    ///
+    /// The name is padded with a loop without guard, so the list must be a constant, which
+    /// also fails to compile if `name` is longer than [ACCESS_LIST_NAME_MAX_LEN].
+    #[inline(always)]
+    pub const fn new(name: &[u8]) -> Self {
+        Self {
+            name: padded_name(name),
+        }
+    }
+
+    /// The state key of `account` in this list: the name, then the account
+    #[inline(always)]
+    pub fn key(&self, account: &AccountId) -> [u8; STATE_KEY_LEN] {
+        state_key(&self.name, account.0)
+    }
+
+    /// Whether `account` is on the list
+    #[inline(always)]
+    pub fn contains(&self, account: &AccountId) -> Result<bool> {
+        match state::<1>(&self.key(account)) {
+            Ok(_) => Ok(true),
+            Err(HookError::DoesntExist) => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Add `account` to the list, which takes one state entry of the hook account's
+    /// reserve. Adding an account already on the list changes nothing.
+    #[inline(always)]
+    pub fn add(&self, account: &AccountId) -> Result<()> {
+        match state_set(&[1], &self.key(account)) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Remove `account` from the list, which frees its state entry
+    #[inline(always)]
+    pub fn remove(&self, account: &AccountId) -> Result<()> {
+        match state_set(&[], &self.key(account)) {
+            Ok(_) => Ok(()),
+            // it wasn't on the list
+            Err(HookError::DoesntExist) => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Apply the change requested by the originating transaction, if it is an Invoke with
+    /// an [ADD_PARAM](Self::ADD_PARAM) or [REMOVE_PARAM](Self::REMOVE_PARAM) parameter.
+    ///
+    /// Returns `Ok(None)` for other transactions. Only the hook account can change the
+    /// list: an Invoke from another account fails with [HookError::InvalidAccount], and one
+    /// with both parameters, or with a value that isn't an account ID, with
+    /// [HookError::InvalidArgument].
+    #[inline(always)]
+    pub fn apply_invoke(&self) -> Result<Option<AccessListChange>> {
+        if otxn_type() != TxnType::Invoke as i64 {
+            return Ok(None);
+        }
+
+        let added = optional_account_param(Self::ADD_PARAM)?;
+        let removed = optional_account_param(Self::REMOVE_PARAM)?;
+        let change = match (added, removed) {
+            (None, None) => return Ok(None),
+            (Some(account), None) => AccessListChange::Added(account),
+            (None, Some(account)) => AccessListChange::Removed(account),
+            (Some(_), Some(_)) => return Err(HookError::InvalidArgument),
+        };
+
+        let sender = AccountId(otxn_field::<ACC_ID_LEN>(FieldId::Account)?);
+        if sender != hook_account()? {
+            return Err(HookError::InvalidAccount);
+        }
+
+        match change {
+            AccessListChange::Added(account) => self.add(&account)?,
+            AccessListChange::Removed(account) => self.remove(&account)?,
+        }
+
+        Ok(Some(change))
+    }
+}
+
+// Length of what follows the name in a state key made with [state_key], such as the
+// account of an [AccessList]
+pub(crate) const STATE_KEY_SUFFIX_LEN: usize = STATE_KEY_LEN - ACCESS_LIST_NAME_MAX_LEN;
+
+// A state key under a name, such as the name of a list
+#[repr(C)]
+struct NamedKey {
+    name: [u8; ACCESS_LIST_NAME_MAX_LEN],
+    suffix: [u8; STATE_KEY_SUFFIX_LEN],
+}
+
+// `name` padded with zeros, for state keys made with [state_key]. Fails to compile in a
+// constant if `name` is too long.
+pub(crate) const fn padded_name(name: &[u8]) -> [u8; ACCESS_LIST_NAME_MAX_LEN] {
+    assert!(
+        name.len() <= ACCESS_LIST_NAME_MAX_LEN,
+        "state key name is too long"
+    );
+
+    let mut padded = [0; ACCESS_LIST_NAME_MAX_LEN];
+    let mut i = 0;
+    while i < name.len() {
+        padded[i] = name[i];
+        i += 1;
+    }
+
+    padded
+}
+
+// The state key of `suffix` under `name`: the name, then the suffix. The state helpers
+// keeping entries under a name of their own, such as access lists, event logs and nonce
+// stores, all lay out their keys with it.
+#[inline(always)]
+pub(crate) fn state_key(
+    name: &[u8; ACCESS_LIST_NAME_MAX_LEN],
+    suffix: [u8; STATE_KEY_SUFFIX_LEN],
+) -> [u8; STATE_KEY_LEN] {
+    // two array moves rather than copies into slices, which could become `memcpy`
+    let key = NamedKey {
+        name: *name,
+        suffix,
+    };
+
+    unsafe { core::mem::transmute::<NamedKey, [u8; STATE_KEY_LEN]>(key) }
+}
+
+// The account in the Invoke parameter `name`, `None` if it is not set
+#[inline(always)]
+fn optional_account_param(name: &[u8]) -> Result<Option<AccountId>> {
+    match otxn_param_as::<AccountId>(name) {
+        Ok(account) => Ok(Some(account)),
+        Err(HookError::DoesntExist) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::fixtures::{DESTINATION, HOOK_ACCOUNT, ISSUER};
+    use crate::mock;
+
+    const ALLOWED: AccessList = AccessList::new(b"allowed");
+    const BLOCKED: AccessList = AccessList::new(b"blocked");
+
+    fn invoke_from(sender: AccountId) {
+        mock::reset();
+        mock::set_hook_account(HOOK_ACCOUNT);
+        mock::set_otxn_type(TxnType::Invoke);
+        mock::set_otxn_field(FieldId::Account, sender.as_bytes());
+    }
+
+    #[wasm_bindgen_test]
+    fn keeps_lists_apart() {
+        invoke_from(HOOK_ACCOUNT);
+        assert!(matches!(ALLOWED.contains(&DESTINATION), Ok(false)));
+
+        assert!(ALLOWED.add(&DESTINATION).is_ok());
+        assert!(matches!(ALLOWED.contains(&DESTINATION), Ok(true)));
+        assert!(matches!(BLOCKED.contains(&DESTINATION), Ok(false)));
+        assert!(matches!(ALLOWED.contains(&ISSUER), Ok(false)));
+        assert_eq!(&ALLOWED.key(&DESTINATION)[..7], b"allowed");
+        assert_eq!(&ALLOWED.key(&DESTINATION)[12..], DESTINATION.as_bytes());
+
+        assert!(ALLOWED.remove(&DESTINATION).is_ok());
+        assert!(ALLOWED.remove(&DESTINATION).is_ok());
+        assert!(matches!(ALLOWED.contains(&DESTINATION), Ok(false)));
+    }
+
+    #[wasm_bindgen_test]
+    fn applies_changes_invoked_by_the_hook_account() {
+        invoke_from(HOOK_ACCOUNT);
+        assert!(matches!(BLOCKED.apply_invoke(), Ok(None)));
+
+        mock::set_otxn_param(AccessList::ADD_PARAM, DESTINATION.as_bytes());
+        assert!(matches!(
+            BLOCKED.apply_invoke(),
+            Ok(Some(AccessListChange::Added(account))) if account == DESTINATION
+        ));
+        assert!(matches!(BLOCKED.contains(&DESTINATION), Ok(true)));
+
+        invoke_from(HOOK_ACCOUNT);
+        mock::set_otxn_param(AccessList::REMOVE_PARAM, DESTINATION.as_bytes());
+        assert!(BLOCKED.add(&DESTINATION).is_ok());
+        assert!(matches!(
+            BLOCKED.apply_invoke(),
+            Ok(Some(AccessListChange::Removed(_)))
+        ));
+        assert!(matches!(BLOCKED.contains(&DESTINATION), Ok(false)));
+    }
+
+    #[wasm_bindgen_test]
+    fn rejects_changes_from_other_accounts() {
+        invoke_from(ISSUER);
+        mock::set_otxn_param(AccessList::ADD_PARAM, ISSUER.as_bytes());
+        assert!(matches!(
+            BLOCKED.apply_invoke(),
+            Err(HookError::InvalidAccount)
+        ));
+        assert!(matches!(BLOCKED.contains(&ISSUER), Ok(false)));
+
+        invoke_from(HOOK_ACCOUNT);
+        mock::set_otxn_param(AccessList::ADD_PARAM, ISSUER.as_bytes());
+        mock::set_otxn_param(AccessList::REMOVE_PARAM, ISSUER.as_bytes());
+        assert!(matches!(
+            BLOCKED.apply_invoke(),
+            Err(HookError::InvalidArgument)
+        ));
+
+        invoke_from(HOOK_ACCOUNT);
+        mock::set_otxn_param(AccessList::ADD_PARAM, &[2; 19]);
+        assert!(matches!(
+            BLOCKED.apply_invoke(),
+            Err(HookError::InvalidArgument)
+        ));
+
+        // payments don't change the list, even with the parameter
+        invoke_from(HOOK_ACCOUNT);
+        mock::set_otxn_type(TxnType::Payment);
+        mock::set_otxn_param(AccessList::ADD_PARAM, ISSUER.as_bytes());
+        assert!(matches!(BLOCKED.apply_invoke(), Ok(None)));
+    }
+}
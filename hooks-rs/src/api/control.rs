@@ -1,5 +1,7 @@
 use crate::c;
 
+use super::*;
+
 // Safe to be modified globally because wasm is guaranteed to run in a single thread
 static mut GUARD_ID: u32 = 0;
 
@@ -29,6 +31,253 @@ pub fn _g(id: u32, max_iter: u32) {
     }
 }
 
+/// Guard function, same as [_g].
+///
+/// `id` must be unique for every loop, and both `id` and `max_iter` must be
+/// constants, because the guard checker reads them from the wasm binary.
+/// [guarded_for](crate::guarded_for) takes care of both.
+///
+/// # Example
+///
+/// ```no_run
+/// let mut i = 0;
+/// while {
+///     guard(line!(), MAXITER + 1);
+///     i < MAXITER
+/// } {
+///     // your code
+///     i += 1;
+/// }
+/// ```
+#[cfg(not(doctest))]
+#[inline(always)]
+pub fn guard(id: u32, max_iter: u32) {
+    _g(id, max_iter)
+}
+
+/// Loop over a range with a [guard] call at the top of every iteration.
+///
+/// `max_iter` is the maximum number of iterations of the loop and must be a constant.
+/// The guard id is derived from the file, line and column of the macro invocation with
+/// [guard_id], so every loop has its own id.
+///
+/// # Example
+///
+/// ```no_run
+/// let mut sum = 0;
+/// guarded_for!(i in 0..accounts.len(), max_iter = 10, {
+///     sum += balance(&accounts[i]);
+/// });
+/// ```
+#[macro_export]
+macro_rules! guarded_for {
+    ($i:ident in $range:expr, max_iter = $max_iter:expr, $body:block) => {{
+        let range: ::core::ops::Range<_> = $range;
+        let mut next = range.start;
+        const GUARD_ID: u32 = $crate::guard_id(::core::file!(), ::core::line!(), ::core::column!());
+        while {
+            $crate::guard(GUARD_ID, ($max_iter) + 1);
+            next < range.end
+        } {
+            let $i = next;
+            next += 1;
+            $body
+        }
+    }};
+}
+
+/// The guard id of a loop at `line` and `column` of `file`, used by
+/// [guarded_for](crate::guarded_for).
+///
+/// Mixes the position into an FNV-1a hash of the file name, so that loops in different files
+/// don't share an id. The highest bit is set, which keeps the ids apart from the ones
+/// generated by [max_iter].
+///
+/// Contains unguarded loops, so it must only be evaluated at compile time in a hook.
+#[inline(always)]
+pub const fn guard_id(file: &str, line: u32, column: u32) -> u32 {
+    const FNV_OFFSET_BASIS: u32 = 0x811C_9DC5;
+    const FNV_PRIME: u32 = 0x0100_0193;
+
+    let mut hash = FNV_OFFSET_BASIS;
+    let file = file.as_bytes();
+    let mut i = 0;
+    while i < file.len() {
+        hash = (hash ^ file[i] as u32).wrapping_mul(FNV_PRIME);
+        i += 1;
+    }
+    let position = line.to_le_bytes();
+    let column = column.to_le_bytes();
+    let mut i = 0;
+    while i < 4 {
+        hash = (hash ^ position[i] as u32).wrapping_mul(FNV_PRIME);
+        hash = (hash ^ column[i] as u32).wrapping_mul(FNV_PRIME);
+        i += 1;
+    }
+
+    0x8000_0000 | hash
+}
+
+/// Rollback with `msg` if `cond` is false.
+///
+/// The line number of the assertion is used as the error code, like
+/// [unwrap_line_number](crate::Result::unwrap_line_number).
+///
+/// # Example
+///
+/// ```no_run
+/// hook_assert!(otxn_type() == TxnType::Payment as i64, "only payments are allowed");
+/// ```
+#[macro_export]
+macro_rules! hook_assert {
+    ($cond:expr, $msg:expr) => {
+        if !$cond {
+            $crate::rollback(
+                ::core::convert::AsRef::<[u8]>::as_ref($msg),
+                ::core::line!().into(),
+            );
+        }
+    };
+}
+
+/// Rollback with `error_code` if `cond` is false.
+///
+/// The stringified condition is used as the rollback message. `error_code` can be
+/// anything that converts into an `i64`, including [HookError](crate::HookError).
+///
+/// # Example
+///
+/// ```no_run
+/// hook_require!(drops >= MIN_DROPS, -1);
+/// hook_require!(is_buffer_equal(&otxn_account, &OWNER), HookError::NotAuthorized);
+/// ```
+#[macro_export]
+macro_rules! hook_require {
+    ($cond:expr, $error_code:expr) => {
+        if !$cond {
+            $crate::rollback(
+                ::core::concat!("requirement failed: ", ::core::stringify!($cond)).as_bytes(),
+                ::core::convert::Into::<i64>::into($error_code),
+            );
+        }
+    };
+}
+
+/// Rollback unless the originating transaction passes all the given checks.
+///
+/// Checks can be given in any order:
+/// - `txn_type: <TxnType variant>` requires the transaction type
+/// - `min_drops: <u64>` requires an amount in XRP of at least this many drops
+/// - `amount: <AmountPolicy>` requires an amount accepted by the
+///   [AmountPolicy](crate::AmountPolicy)
+/// - `account: <AccountId>` requires the sender
+/// - `destination: <AccountId>` requires the destination
+///
+/// `account` and `destination` also accept `hook_account`, which compares against
+/// the account the hook is installed on. The check that failed is used as the rollback
+/// message, and the line number of the macro invocation as the error code.
+///
+/// # Example
+///
+/// ```no_run
+/// filter! {
+///     txn_type: Payment,
+///     min_drops: 1000,
+///     destination: hook_account,
+/// }
+/// ```
+#[macro_export]
+macro_rules! filter {
+    () => {};
+    (txn_type: $txn_type:ident $(, $($rest:tt)*)?) => {
+        if $crate::otxn_type() != $crate::TxnType::$txn_type as i64 {
+            $crate::rollback(
+                ::core::concat!("filter: txn_type is not ", ::core::stringify!($txn_type))
+                    .as_bytes(),
+                ::core::line!().into(),
+            );
+        }
+        $crate::filter!($($($rest)*)?);
+    };
+    (min_drops: $min_drops:expr $(, $($rest:tt)*)?) => {
+        match $crate::otxn_drops() {
+            $crate::Result::Ok(drops) if drops >= $min_drops => {}
+            _ => $crate::rollback(
+                ::core::concat!("filter: drops below ", ::core::stringify!($min_drops))
+                    .as_bytes(),
+                ::core::line!().into(),
+            ),
+        }
+        $crate::filter!($($($rest)*)?);
+    };
+    (amount: $policy:expr $(, $($rest:tt)*)?) => {
+        match $crate::AmountPolicy::check_otxn(&$policy) {
+            $crate::Result::Ok(()) => {}
+            _ => $crate::rollback(
+                ::core::concat!("filter: amount not allowed by ", ::core::stringify!($policy))
+                    .as_bytes(),
+                ::core::line!().into(),
+            ),
+        }
+        $crate::filter!($($($rest)*)?);
+    };
+    (account: hook_account $(, $($rest:tt)*)?) => {
+        $crate::filter!(@account Account, "account", $crate::hook_account());
+        $crate::filter!($($($rest)*)?);
+    };
+    (account: $account:expr $(, $($rest:tt)*)?) => {
+        $crate::filter!(@account Account, "account", $crate::Result::Ok($account));
+        $crate::filter!($($($rest)*)?);
+    };
+    (destination: hook_account $(, $($rest:tt)*)?) => {
+        $crate::filter!(@account Destination, "destination", $crate::hook_account());
+        $crate::filter!($($($rest)*)?);
+    };
+    (destination: $destination:expr $(, $($rest:tt)*)?) => {
+        $crate::filter!(@account Destination, "destination", $crate::Result::Ok($destination));
+        $crate::filter!($($($rest)*)?);
+    };
+    (@account $field:ident, $name:literal, $expected:expr) => {
+        match ($crate::otxn_field::<{ $crate::ACC_ID_LEN }>($crate::FieldId::$field), $expected) {
+            ($crate::Result::Ok(actual), $crate::Result::Ok(expected))
+                if $crate::is_buffer_equal(&actual, $crate::AccountId::as_bytes(&expected)) => {}
+            _ => $crate::rollback(
+                ::core::concat!("filter: unexpected ", $name).as_bytes(),
+                ::core::line!().into(),
+            ),
+        }
+    };
+}
+
+/// Dispatch on the type of the originating transaction.
+///
+/// [otxn_type] is read once and compared against each arm in order, so the expansion
+/// has no loops that would need a guard. Arms can list several transaction types
+/// separated by `|`, and the `_` arm is required. The whole macro evaluates to the
+/// value of the arm that was taken.
+///
+/// # Example
+///
+/// ```no_run
+/// match_txn! {
+///     Payment => { handle_payment() },
+///     Invoke | AccountSet => { handle_config() },
+///     _ => { rollback(b"unsupported transaction type", otxn_type()) },
+/// }
+/// ```
+#[macro_export]
+macro_rules! match_txn {
+    ($($($txn_type:ident)|+ => $body:block $(,)?)* _ => $default:block $(,)?) => {{
+        let txn_type = $crate::otxn_type();
+        $(
+            if $(txn_type == $crate::TxnType::$txn_type as i64)||+
+                $body
+            else
+        )*
+        $default
+    }};
+}
+
 /// Instead of having to pass the `GUARD_ID` parameter to every call to `_g`,
 /// you can use this function to generate a unique `GUARD_ID` for each call
 /// automatically.
@@ -66,6 +315,9 @@ pub fn max_iter(max_iter: u32) {
 
 /// Accept the originating transaction and commit any changes the hook made
 ///
+/// `msg` and `code` show up as `HookReturnString` and `HookReturnCode` in the
+/// metadata of the originating transaction.
+///
 /// # Example
 ///
 /// You can use a byte string to return a message
@@ -84,23 +336,477 @@ pub fn max_iter(max_iter: u32) {
 /// accept(&txn_hash, 0);
 /// ```
 #[inline(always)]
-pub fn accept(msg: &[u8], error_code: i64) -> ! {
+pub fn accept(msg: &[u8], code: i64) -> ! {
     unsafe {
-        c::accept(msg.as_ptr() as u32, msg.len() as u32, error_code);
+        c::accept(host_ptr(msg.as_ptr()), msg.len() as u32, code);
         core::hint::unreachable_unchecked()
     }
 }
 
 /// Reject the originating transaction and discard any changes the hook made
 ///
+/// `msg` and `code` show up as `HookReturnString` and `HookReturnCode` in the
+/// metadata of the originating transaction.
+///
+/// With the `small` feature, `msg` is dropped and only `code` is returned, so that the
+/// messages don't take up space in the hook.
+///
 /// # Example
 /// ```
 /// rollback(b"encountered a problem", -1)
 /// ```
 #[inline(always)]
-pub fn rollback(msg: &[u8], error_code: i64) -> ! {
+pub fn rollback(msg: &[u8], code: i64) -> ! {
+    #[cfg(feature = "small")]
+    {
+        let _ = msg;
+        rollback_with_code(code)
+    }
+
+    #[cfg(not(feature = "small"))]
+    unsafe {
+        c::rollback(host_ptr(msg.as_ptr()), msg.len() as u32, code);
+        core::hint::unreachable_unchecked()
+    }
+}
+
+// Shared by every rollback of a hook built with the `small` feature, instead of being
+// inlined into each of them
+#[cfg(feature = "small")]
+#[cold]
+#[inline(never)]
+fn rollback_with_code(code: i64) -> ! {
+    let msg: &[u8] = &[];
     unsafe {
-        c::rollback(msg.as_ptr() as u32, msg.len() as u32, error_code);
+        c::rollback(host_ptr(msg.as_ptr()), 0, code);
         core::hint::unreachable_unchecked()
     }
 }
+
+/// What a hook decided to do with the originating transaction.
+///
+/// Hook logic can be written as a function returning an `Outcome` instead of calling
+/// [accept] or [rollback] in the middle of it, which makes it possible to unit test
+/// the logic without a host. The entry point then calls [Outcome::exit].
+///
+/// Using `?` on a [Result] in a function returning an `Outcome` turns the error into
+/// [Outcome::Rollback] with the error code.
+///
+/// # Example
+/// ```
+/// fn handle(drops: u64) -> Outcome<'static> {
+///     if drops < 1000 {
+///         return Outcome::Rollback { code: 1, msg: b"too small" };
+///     }
+///     let _ = state_set(&drops.to_be_bytes(), b"last")?;
+///
+///     Outcome::Accept { code: 0, msg: b"ok" }
+/// }
+///
+/// #[no_mangle]
+/// pub extern "C" fn hook(_: u32) -> i64 {
+///     max_iter(1);
+///
+///     handle(drops).exit()
+/// }
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub enum Outcome<'a> {
+    /// Accept the originating transaction, see [accept]
+    Accept {
+        /// Return code
+        code: i64,
+        /// Return message
+        msg: &'a [u8],
+    },
+    /// Reject the originating transaction, see [rollback]
+    Rollback {
+        /// Return code
+        code: i64,
+        /// Return message
+        msg: &'a [u8],
+    },
+}
+
+impl Outcome<'_> {
+    /// Ends the hook with [accept] or [rollback]
+    #[inline(always)]
+    pub fn exit(self) -> ! {
+        match self {
+            Outcome::Accept { code, msg } => accept(msg, code),
+            Outcome::Rollback { code, msg } => rollback(msg, code),
+        }
+    }
+}
+
+impl From<HookError> for Outcome<'_> {
+    #[inline(always)]
+    fn from(err: HookError) -> Self {
+        Outcome::Rollback {
+            code: err.into(),
+            msg: b"error",
+        }
+    }
+}
+
+impl core::ops::FromResidual<Result<core::convert::Infallible>> for Outcome<'_> {
+    #[inline(always)]
+    fn from_residual(residual: Result<core::convert::Infallible>) -> Self {
+        match residual {
+            Err(err) => err.into(),
+        }
+    }
+}
+
+/// How the hook is being executed.
+///
+/// Strong executions run before the originating transaction is applied and can reject it,
+/// weak ones run after it was applied, for transactions the hook account is only affected
+/// by, and can't. A strong execution can ask with [hook_again](crate::hook_again) to also
+/// be executed again as weak once the transaction is applied, see
+/// [Deferred](crate::Deferred).
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub enum ExecutionKind {
+    /// Before the originating transaction is applied, which the hook can reject
+    Strong,
+    /// After the originating transaction was applied
+    Weak,
+    /// After the originating transaction was applied, as requested by the strong execution
+    AgainAsWeak,
+    /// In `cbak`, for a transaction the hook emitted, see [CallbackCtx::execution]
+    Callback,
+}
+
+impl ExecutionKind {
+    /// Convert the argument `hook` is called with, which is 0 for a strong execution, 1
+    /// for a weak one and 2 for an again-as-weak one
+    #[inline(always)]
+    pub const fn from_hook_arg(reserved: u32) -> Self {
+        match reserved {
+            0 => ExecutionKind::Strong,
+            2 => ExecutionKind::AgainAsWeak,
+            _ => ExecutionKind::Weak,
+        }
+    }
+
+    /// Whether the hook can still reject the originating transaction
+    #[inline(always)]
+    pub const fn is_strong(self) -> bool {
+        matches!(self, ExecutionKind::Strong)
+    }
+}
+
+/// What the `hook` entry point was called with, and what the host tells about the
+/// execution that every hook asks for.
+///
+/// Read once with [HookCtx::load] and passed to the functions of the hook, so they take
+/// the context as a whole rather than asking the host themselves, and can be tested with
+/// a context made up in the test.
+///
+/// # Example
+/// ```
+/// fn handle(ctx: &HookCtx) -> Outcome<'static> {
+///     if !ctx.is_txn(TxnType::Payment) {
+///         return Outcome::Accept { code: 0, msg: b"not a payment" };
+///     }
+///     let _ = state_set(&ctx.ledger_seq.to_be_bytes(), b"last payment")?;
+///
+///     Outcome::Accept { code: 0, msg: b"recorded" }
+/// }
+///
+/// #[hook]
+/// fn hook(reserved: u32) -> Outcome<'static> {
+///     handle(&HookCtx::load(reserved)?)
+/// }
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct HookCtx {
+    /// Argument `hook` was called with, see [ExecutionKind::from_hook_arg]
+    pub reserved: u32,
+    /// Account the hook is installed on, see [hook_account](crate::hook_account)
+    pub hook_account: AccountId,
+    /// Sequence of the ledger the hook runs in, see [ledger_seq](crate::ledger_seq)
+    pub ledger_seq: LedgerSequence,
+    /// Close time of the last closed ledger, see [ledger_time](crate::ledger_time)
+    pub ledger_time: RippleTime,
+    /// Type of the originating transaction, see [otxn_type](crate::otxn_type)
+    pub otxn_type: i64,
+    /// Position of the hook in the hook chain, see [hook_pos](crate::hook_pos)
+    pub hook_pos: u32,
+}
+
+impl HookCtx {
+    /// Read the context from the host, for the `hook` entry point called with `reserved`
+    #[inline(always)]
+    pub fn load(reserved: u32) -> Result<Self> {
+        Ok(Self {
+            reserved,
+            hook_account: hook_account()?,
+            ledger_seq: ledger_seq(),
+            ledger_time: ledger_time(),
+            otxn_type: otxn_type(),
+            hook_pos: hook_pos() as u32,
+        })
+    }
+
+    /// Whether the originating transaction is of type `txn_type`
+    #[inline(always)]
+    pub const fn is_txn(&self, txn_type: TxnType) -> bool {
+        self.otxn_type == txn_type as i64
+    }
+
+    /// How the hook is being executed
+    #[inline(always)]
+    pub const fn execution(&self) -> ExecutionKind {
+        ExecutionKind::from_hook_arg(self.reserved)
+    }
+}
+
+/// What the `cbak` entry point was called with
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct CallbackCtx {
+    /// What happened to the emitted transaction the callback is for
+    pub outcome: crate::callback::EmitOutcome,
+}
+
+impl CallbackCtx {
+    /// How the hook is being executed, which is always [ExecutionKind::Callback]
+    #[inline(always)]
+    pub const fn execution(&self) -> ExecutionKind {
+        ExecutionKind::Callback
+    }
+}
+
+/// A hook written as a type, registered with [register_hook].
+///
+/// Larger hooks can keep shared helpers and configuration in the implementing type,
+/// and the execution context is passed in explicitly, so both methods can be called
+/// from unit tests with a context of your choice.
+///
+/// # Example
+/// ```
+/// struct Doorman {
+///     min_drops: u64,
+/// }
+///
+/// impl Hook for Doorman {
+///     fn on_transaction(&self, _ctx: HookCtx) -> Outcome<'_> {
+///         let amount = otxn_field::<8>(FieldId::Amount)?;
+///         if u64::from_be_bytes(amount) & 0x3FFF_FFFF_FFFF_FFFF < self.min_drops {
+///             return Outcome::Rollback { code: 1, msg: b"too small" };
+///         }
+///
+///         Outcome::Accept { code: 0, msg: b"welcome" }
+///     }
+/// }
+///
+/// register_hook!(Doorman { min_drops: 1000 });
+/// ```
+pub trait Hook {
+    /// Called from `hook` for the originating transaction
+    fn on_transaction(&self, ctx: HookCtx) -> Outcome<'_>;
+
+    /// Called from `cbak` once for every transaction the hook emitted.
+    ///
+    /// Accepts without doing anything by default.
+    #[inline(always)]
+    fn on_callback(&self, ctx: CallbackCtx) -> Outcome<'_> {
+        let _ = ctx;
+        Outcome::Accept { code: 0, msg: b"" }
+    }
+}
+
+/// Generate the `hook` and `cbak` entry points from a value implementing [Hook].
+///
+/// The expression is evaluated every time an entry point is called. `hook` reads the
+/// context with [HookCtx::load] and rolls back with the error if the host fails to
+/// provide it.
+///
+/// # Example
+/// ```
+/// struct MyHook;
+///
+/// impl Hook for MyHook {
+///     fn on_transaction(&self, _ctx: HookCtx) -> Outcome<'_> {
+///         Outcome::Accept { code: 0, msg: b"ok" }
+///     }
+/// }
+///
+/// register_hook!(MyHook);
+/// ```
+#[macro_export]
+macro_rules! register_hook {
+    ($hook:expr) => {
+        #[no_mangle]
+        pub extern "C" fn cbak(what: u32) -> i64 {
+            // Every hook needs to import guard function
+            // and use it at least once
+            $crate::max_iter(1);
+
+            let hook = $hook;
+            let ctx = $crate::CallbackCtx {
+                outcome: $crate::EmitOutcome::from_cbak_arg(what),
+            };
+            $crate::Outcome::exit($crate::Hook::on_callback(&hook, ctx))
+        }
+
+        #[no_mangle]
+        pub extern "C" fn hook(reserved: u32) -> i64 {
+            // Every hook needs to import guard function
+            // and use it at least once
+            $crate::max_iter(1);
+
+            let hook = $hook;
+            let outcome = match $crate::HookCtx::load(reserved) {
+                $crate::Result::Ok(ctx) => $crate::Hook::on_transaction(&hook, ctx),
+                $crate::Result::Err(err) => $crate::Outcome::from(err),
+            };
+            $crate::Outcome::exit(outcome)
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+
+    fn handle(drops: Result<u64>) -> Outcome<'static> {
+        let drops = drops?;
+        if drops < 1000 {
+            return Outcome::Rollback {
+                code: 1,
+                msg: b"too small",
+            };
+        }
+
+        Outcome::Accept {
+            code: 0,
+            msg: b"ok",
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn outcome_can_be_returned_from_hook_logic() {
+        assert!(
+            handle(Ok(1000))
+                == Outcome::Accept {
+                    code: 0,
+                    msg: b"ok"
+                }
+        );
+        assert!(
+            handle(Ok(1))
+                == Outcome::Rollback {
+                    code: 1,
+                    msg: b"too small"
+                }
+        );
+        assert!(
+            handle(Err(HookError::DoesntExist))
+                == Outcome::Rollback {
+                    code: -5,
+                    msg: b"error"
+                }
+        );
+    }
+
+    struct MinDrops {
+        min_drops: u64,
+    }
+
+    impl Hook for MinDrops {
+        fn on_transaction(&self, ctx: HookCtx) -> Outcome<'_> {
+            if (ctx.reserved as u64) < self.min_drops {
+                return Outcome::Rollback {
+                    code: 1,
+                    msg: b"too small",
+                };
+            }
+
+            Outcome::Accept {
+                code: 0,
+                msg: b"ok",
+            }
+        }
+    }
+
+    fn ctx(reserved: u32) -> HookCtx {
+        HookCtx {
+            reserved,
+            hook_account: AccountId::ZERO,
+            ledger_seq: LedgerSequence(1),
+            ledger_time: RippleTime(0),
+            otxn_type: TxnType::Payment as i64,
+            hook_pos: 0,
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn hook_can_be_called_with_a_mocked_context() {
+        let hook = MinDrops { min_drops: 1000 };
+
+        assert!(
+            hook.on_transaction(ctx(1000))
+                == Outcome::Accept {
+                    code: 0,
+                    msg: b"ok"
+                }
+        );
+        assert!(
+            hook.on_transaction(ctx(1))
+                == Outcome::Rollback {
+                    code: 1,
+                    msg: b"too small"
+                }
+        );
+        assert!(
+            hook.on_callback(CallbackCtx {
+                outcome: crate::callback::EmitOutcome::Expired
+            }) == Outcome::Accept { code: 0, msg: b"" }
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn loads_the_context_from_the_host() {
+        crate::mock::reset();
+        crate::mock::set_hook_account(AccountId([7; ACC_ID_LEN]));
+        crate::mock::set_ledger_seq(42);
+        crate::mock::set_ledger_last_time(1_000);
+        crate::mock::set_otxn_type(TxnType::Invoke);
+        crate::mock::set_hook_chain(&[None, Some([1; HASH_LEN])], 1);
+
+        let ctx = HookCtx::load(2).unwrap_line_number();
+        assert!(
+            ctx == HookCtx {
+                reserved: 2,
+                hook_account: AccountId([7; ACC_ID_LEN]),
+                ledger_seq: LedgerSequence(42),
+                ledger_time: RippleTime(1_000),
+                otxn_type: TxnType::Invoke as i64,
+                hook_pos: 1,
+            }
+        );
+        assert!(ctx.is_txn(TxnType::Invoke) && !ctx.is_txn(TxnType::Payment));
+        assert!(ctx.execution() == ExecutionKind::AgainAsWeak);
+    }
+
+    #[wasm_bindgen_test]
+    fn gives_every_loop_its_own_guard_id() {
+        let id = guard_id("src/lib.rs", 10, 5);
+        assert!(id & 0x8000_0000 != 0);
+        assert!(id != guard_id("src/lib.rs", 10, 6));
+        assert!(id != guard_id("src/lib.rs", 11, 5));
+        assert!(id != guard_id("src/main.rs", 10, 5));
+
+        let mut sum = 0;
+        guarded_for!(i in 0..3, max_iter = 3, { sum += i; });
+        guarded_for!(i in 0..2, max_iter = 2, { sum += i; });
+        assert_eq!(sum, 4);
+    }
+}
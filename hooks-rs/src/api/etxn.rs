@@ -19,7 +19,7 @@ pub fn etxn_burden() -> i64 {
 pub fn etxn_details<const EMIT_DETAILS_LEN: usize>() -> Result<[u8; EMIT_DETAILS_LEN]> {
     init_buffer_mut(|buffer_mut_ptr: *mut MaybeUninit<u8>| {
         let result: Result<u64> =
-            unsafe { c::etxn_details(buffer_mut_ptr as u32, EMIT_DETAILS_LEN as u32).into() };
+            unsafe { c::etxn_details(host_ptr(buffer_mut_ptr), EMIT_DETAILS_LEN as u32).into() };
 
         result
     })
@@ -32,11 +32,7 @@ pub fn etxn_details<const EMIT_DETAILS_LEN: usize>() -> Result<[u8; EMIT_DETAILS
 /// ```
 /// let buf_mut_ptr: *mut MaybeUninit<u8> = buf.as_mut_ptr();
 /// let pos = 45;
-/// let insert_etxn_details_from_ptr_result: Result<u64> = insert_etxn_details_from_ptr(unsafe { buf_mut_ptr.add(pos) as u32 }, 138);
-/// match insert_etxn_details_from_ptr_result {
-///     Err(e) => return Err(e),
-///     Ok(_) => {}
-/// }
+/// insert_etxn_details_from_ptr(host_ptr(unsafe { buf_mut_ptr.add(pos) }), 138)?;
 /// ```
 #[inline(always)]
 pub fn insert_etxn_details_from_ptr(txn_buffer_mut_ptr: u32, emit_details_len: u32) -> Result<u64> {
@@ -66,6 +62,7 @@ pub fn etxn_fee_base<T>(tx_blob: &[T]) -> Result<u64> {
 }
 
 /// Estimate the required fee for a txn to be emitted successfully from a pointer to the transaction buffer.
+///
 /// Does the same thing as [etxn_fee_base](etxn_fee_base) but takes a pointer to the transaction buffer instead of a slice.
 ///
 /// # Example
@@ -81,7 +78,105 @@ pub fn etxn_fee_base<T>(tx_blob: &[T]) -> Result<u64> {
 /// ```
 #[inline(always)]
 pub fn etxn_fee_base_from_ptr<T>(tx_blob_ptr: *const T, tx_blob_len: usize) -> Result<u64> {
-    unsafe { c::etxn_fee_base(tx_blob_ptr as u32, tx_blob_len as u32).into() }
+    unsafe { c::etxn_fee_base(host_ptr(tx_blob_ptr), tx_blob_len as u32).into() }
+}
+
+/// The fee in drops of emitting `emit_count` transactions like `tx_blob`, so a hook can
+/// decide up front whether it can afford them.
+///
+/// `tx_blob` is a built transaction, such as one returned by
+/// [TransactionBuilder::build](crate::TransactionBuilder::build). The fee can't be
+/// estimated from the length of a transaction alone: [etxn_fee_base] adds the execution
+/// fees of the hooks the transaction triggers on the accounts it touches, and scales the
+/// base fee by the burden of the emitting chain, which the host only knows from the
+/// transaction itself. Each transaction is counted at the fee of `tx_blob`, so call
+/// [etxn_fee_base] on transactions that differ in length or in the hooks they trigger.
+///
+/// [etxn_reserve] must be called before this function, otherwise the host returns
+/// [HookError::PrerequisiteNotMet]. Returns [HookError::TooBig] if the total overflows.
+///
+/// # Example
+/// ```
+/// etxn_reserve(3).unwrap_line_number();
+///
+/// let mut buffer = XrpPaymentBuilder::uninit_buffer();
+/// let tx = xrp_payment_txn_builder.build(&mut buffer).unwrap_line_number();
+/// let fee = emit_fee(tx, 3).unwrap_line_number();
+/// ```
+#[inline(always)]
+pub fn emit_fee(tx_blob: &[u8], emit_count: u32) -> Result<u64> {
+    let fee = etxn_fee_base(tx_blob)?;
+
+    match fee.checked_mul(emit_count as u64) {
+        Some(cost) => Ok(cost),
+        None => Err(HookError::TooBig),
+    }
+}
+
+/// Errors that can happen while reserving, estimating the fee of or emitting a transaction.
+///
+/// Use `EmitError::from(err)` on a [HookError] returned by [etxn_reserve], [etxn_fee_base] or
+/// [emit] to tell conditions that may go away on their own from bugs in the hook.
+#[derive(Clone, Copy)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub enum EmitError {
+    /// [etxn_reserve] was not called before emitting
+    PrerequisiteNotMet,
+    /// [etxn_reserve] was called more than once
+    AlreadySet,
+    /// More transactions were emitted than were reserved
+    TooManyEmittedTxn,
+    /// The fee of the transaction is larger than the host is willing to pay
+    FeeTooLarge,
+    /// The host rejected the emitted transaction.
+    /// Check the trace log of the node to which the originating transaction was submitted.
+    EmissionFailure,
+    /// Any other error, which usually means the transaction buffer is invalid
+    Other(HookError),
+}
+
+impl EmitError {
+    /// Returns `true` if the error may go away when the hook runs again later, for example
+    /// because fees have dropped. Other errors mean the hook itself needs to be fixed.
+    #[inline(always)]
+    pub const fn is_retryable(self) -> bool {
+        matches!(self, EmitError::FeeTooLarge | EmitError::EmissionFailure)
+    }
+}
+
+impl From<HookError> for EmitError {
+    #[inline(always)]
+    fn from(err: HookError) -> Self {
+        match err {
+            HookError::PrerequisiteNotMet => EmitError::PrerequisiteNotMet,
+            HookError::AlreadySet => EmitError::AlreadySet,
+            HookError::TooManyEmittedTxn => EmitError::TooManyEmittedTxn,
+            HookError::FeeTooLarge => EmitError::FeeTooLarge,
+            HookError::EmissionFailure => EmitError::EmissionFailure,
+            err => EmitError::Other(err),
+        }
+    }
+}
+
+impl From<EmitError> for HookError {
+    #[inline(always)]
+    fn from(err: EmitError) -> Self {
+        match err {
+            EmitError::PrerequisiteNotMet => HookError::PrerequisiteNotMet,
+            EmitError::AlreadySet => HookError::AlreadySet,
+            EmitError::TooManyEmittedTxn => HookError::TooManyEmittedTxn,
+            EmitError::FeeTooLarge => HookError::FeeTooLarge,
+            EmitError::EmissionFailure => HookError::EmissionFailure,
+            EmitError::Other(err) => err,
+        }
+    }
+}
+
+impl From<EmitError> for i64 {
+    #[inline(always)]
+    fn from(err: EmitError) -> Self {
+        HookError::from(err).into()
+    }
 }
 
 /// Generate a 32 byte nonce for use in an emitted transaction
@@ -89,44 +184,141 @@ pub fn etxn_fee_base_from_ptr<T>(tx_blob_ptr: *const T, tx_blob_len: usize) -> R
 pub fn etxn_nonce() -> Result<[u8; NONCE_LEN]> {
     init_buffer_mut(|buffer_mut_ptr: *mut MaybeUninit<u8>| {
         let result: Result<u64> =
-            unsafe { c::etxn_nonce(buffer_mut_ptr as u32, NONCE_LEN as u32).into() };
+            unsafe { c::etxn_nonce(host_ptr(buffer_mut_ptr), NONCE_LEN as u32).into() };
 
         result
     })
 }
 
-/// Estimate the required fee for a txn to be emitted successfully
+/// Reserve a number of transactions to be emitted by this hook execution.
+///
+/// This must be called once before [emit], and can only be called once per execution.
+/// Consider using [EmitReservation] instead, which also keeps track of how many of the
+/// reserved transactions have been emitted.
 #[inline(always)]
 pub fn etxn_reserve(count: u32) -> Result<u64> {
     unsafe { c::etxn_reserve(count).into() }
 }
 
+/// Transactions reserved for emission with [etxn_reserve].
+///
+/// Emitting through the reservation counts the emitted transactions and returns
+/// [HookError::TooManyEmittedTxn] without calling the host once all reserved transactions
+/// have been used, so that a hook fails fast instead of wasting fee on the attempt.
+///
+/// # Example
+/// ```
+/// let mut reservation = EmitReservation::new(1).unwrap_line_number();
+///
+/// let xrp_payment_txn_builder = XrpPaymentBuilder::new(Drops(1000), &otxn_account, None, None);
+/// let txn_hash = match xrp_payment_txn_builder.build_and_emit(&mut reservation) {
+///     Ok(hash) => hash,
+///     Err(err) => {
+///         rollback(b"could not emit xrp payment txn", err.into());
+///     }
+/// };
+/// ```
+// Not Copy on purpose: a copy would keep its own count of emitted transactions.
+#[allow(missing_copy_implementations)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct EmitReservation {
+    reserved: u32,
+    emitted: u32,
+}
+
+impl EmitReservation {
+    /// Reserve `count` transactions to be emitted by this hook execution
+    #[inline(always)]
+    pub fn new(count: u32) -> Result<Self> {
+        etxn_reserve(count)?;
+
+        Ok(Self {
+            reserved: count,
+            emitted: 0,
+        })
+    }
+
+    /// Number of reserved transactions
+    #[inline(always)]
+    pub const fn reserved(&self) -> u32 {
+        self.reserved
+    }
+
+    /// Number of transactions emitted through this reservation so far
+    #[inline(always)]
+    pub const fn emitted(&self) -> u32 {
+        self.emitted
+    }
+
+    /// Number of transactions that can still be emitted
+    #[inline(always)]
+    pub const fn remaining(&self) -> u32 {
+        self.reserved - self.emitted
+    }
+
+    /// Emit a built transaction, consuming one of the reserved transactions.
+    ///
+    /// Same as [emit], but returns [HookError::TooManyEmittedTxn] if no reserved
+    /// transactions are left.
+    #[inline(always)]
+    pub fn emit<T>(&mut self, tx: &[T]) -> Result<[u8; HASH_LEN]> {
+        if self.remaining() == 0 {
+            return Err(HookError::TooManyEmittedTxn);
+        }
+
+        let hash = emit(tx)?;
+        self.emitted += 1;
+
+        Ok(hash)
+    }
+}
+
 /// Get the generation of a hypothetically emitted transaction
 #[inline(always)]
 pub fn etxn_generation() -> i64 {
     unsafe { c::etxn_generation() }
 }
 
+/// Roll back if a transaction emitted now would be deeper than `max_generation` in a chain
+/// of emitted transactions.
+///
+/// Every transaction emitted in response to an emitted transaction is one generation
+/// deeper than it. A hook that emits to an account whose hook emits back, or to itself,
+/// would otherwise keep emitting until the host stops the chain, paying fees
+/// every time.
+///
+/// # Example
+/// ```
+/// // only react to transactions submitted by users or emitted directly in response to one
+/// guard_against_emit_loops(2);
+/// ```
+#[inline(always)]
+pub fn guard_against_emit_loops(max_generation: u32) {
+    let generation = etxn_generation();
+    if generation > max_generation as i64 {
+        rollback(b"emit generation limit exceeded", generation);
+    }
+}
+
 /// Emit a new transaction from the hook and return the 32-bytes long txn hash
 /// T should almost always be `MaybeUninit<u8>` or `u8` type depending on your use case.
 ///
 /// # Example
 /// ```
-/// let xrp_payment_txn_builder = XrpPaymentBuilder::new(1000, &otxn_account, 0, 0);
+/// let xrp_payment_txn_builder = XrpPaymentBuilder::new(Drops(1000), &otxn_account, None, None);
 /// let mut xrp_payment_txn_buffer = XrpPaymentBuilder::uninit_buffer();
-/// match xrp_payment_txn_builder.build(&mut xrp_payment_txn_buffer) {
-///     Ok(ptr) => ptr,
+/// let tx = match xrp_payment_txn_builder.build(&mut xrp_payment_txn_buffer) {
+///     Ok(tx) => tx,
 ///     Err(err) => {
 ///         rollback(b"could not build xrp payment txn", err.into());
 ///     }
 /// };
-/// let txn_hash = match emit(
-///     &xrp_payment_txn_buffer,
-/// ) {
+/// let txn_hash = match emit(tx) {
 ///     Ok(hash) => hash,
-///     Err(err) => {
-///         rollback(b"could not emit xrp payment txn", err.into());
-///     }
+///     Err(err) => match EmitError::from(err) {
+///         err if err.is_retryable() => rollback(b"try again later", err.into()),
+///         err => rollback(b"could not emit xrp payment txn", err.into()),
+///     },
 /// };
 /// ```
 #[inline(always)]
@@ -135,6 +327,7 @@ pub fn emit<T>(tx: &[T]) -> Result<[u8; HASH_LEN]> {
 }
 
 /// Emit a new transaction from the hook and return the 32-bytes long txn hash.
+///
 /// Same as [emit](emit) but takes a pointer to the transaction buffer instead of a slice.
 /// This might be useful for dealing with raw pointers.
 #[inline(always)]
@@ -142,9 +335,9 @@ pub fn emit_from_ptr(tx_ptr: *const u8, tx_len: u32) -> Result<[u8; HASH_LEN]> {
     let func = |buffer_mut_ptr: *mut MaybeUninit<u8>| {
         let result: Result<u64> = unsafe {
             c::emit(
-                buffer_mut_ptr as u32,
+                host_ptr(buffer_mut_ptr),
                 HASH_LEN as u32,
-                tx_ptr as u32,
+                host_ptr(tx_ptr),
                 tx_len,
             )
             .into()
@@ -155,3 +348,27 @@ pub fn emit_from_ptr(tx_ptr: *const u8, tx_len: u32) -> Result<[u8; HASH_LEN]> {
 
     init_buffer_mut(func)
 }
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::{mock, TransactionBuilder, XrpPaymentBuilder};
+
+    #[wasm_bindgen_test]
+    fn multiplies_the_fee_of_the_host_by_the_emit_count() {
+        mock::reset();
+        mock::set_ledger_seq(100);
+        mock::set_fee_base(12);
+        etxn_reserve(3).unwrap_line_number();
+        let builder = XrpPaymentBuilder::new(Drops(1000), &AccountId([1; ACC_ID_LEN]), None, None);
+        let mut buffer = XrpPaymentBuilder::uninit_buffer();
+        let tx = builder.build(&mut buffer).unwrap_line_number();
+
+        assert!(matches!(emit_fee(tx, 3), Ok(36)));
+
+        mock::set_fee_base(u64::MAX / 2);
+        assert!(matches!(emit_fee(tx, 3), Err(HookError::TooBig)));
+    }
+}
@@ -8,8 +8,10 @@ use crate::c;
 use super::*;
 
 /// Abstraction of [XFL floating point numbers](https://github.com/XRPLF/XRPL-Standards/discussions/39).
+///
 /// The struct is overloaded with basic numeric operations, such as addition, subtraction, multiplication, division, and negation. Comparison operators are also implemented.
 #[derive(Clone, Copy)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
 pub struct XFL(pub i64);
 
 /// Output an XFL as a serialized object
@@ -23,11 +25,11 @@ pub fn float_sto(
 ) -> Result<u64> {
     let res = unsafe {
         c::float_sto(
-            amount.as_mut_ptr() as _,
+            host_ptr(amount.as_mut_ptr()),
             amount.len() as _,
-            currency_code.as_ptr() as _,
+            host_ptr(currency_code.as_ptr()),
             currency_code.len() as _,
-            issuer_accid.as_ptr() as _,
+            host_ptr(issuer_accid.as_ptr()),
             issuer_accid.len() as _,
             float.0,
             field_code as _,
@@ -53,7 +55,7 @@ impl XFL {
     #[inline(always)]
     pub fn from_sto(serialized_xfl: &[u8; XFL_LEN]) -> Result<Self> {
         Self::from_verified_i64(unsafe {
-            c::float_sto_set(serialized_xfl.as_ptr() as _, XFL_LEN as _)
+            c::float_sto_set(host_ptr(serialized_xfl.as_ptr()), XFL_LEN as _)
         })
     }
 
@@ -90,7 +92,7 @@ impl XFL {
 
         match result {
             res if res >= 0 => Ok(res),
-            _ => Err(Error::from_code(result as _)),
+            _ => Err(HookError::from_code(result as _)),
         }
     }
 
@@ -155,7 +157,7 @@ impl XFL {
     pub(crate) fn from_verified_i64(source: i64) -> Result<Self> {
         match source {
             source if source >= 0 => Ok(XFL(source)),
-            _ => Err(Error::from_code(source as _)),
+            _ => Err(HookError::from_code(source as _)),
         }
     }
 }
@@ -175,10 +177,7 @@ impl Sub for XFL {
     #[inline(always)]
     fn sub(self, other: XFL) -> Self::Output {
         unsafe {
-            let rhs = match Self::from_verified_i64(c::float_negate(other.0)) {
-                Ok(rhs) => rhs,
-                Err(e) => return Err(e),
-            };
+            let rhs = Self::from_verified_i64(c::float_negate(other.0))?;
 
             Self::from_verified_i64(c::float_sum(self.0, rhs.0))
         }
@@ -245,3 +244,46 @@ impl PartialOrd for XFL {
         }
     }
 }
+
+/// The number in decimal notation, like `-0.0125` or `1000`
+#[cfg(any(test, feature = "std"))]
+impl core::fmt::Display for XFL {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut mantissa = (self.0 & ((1 << 54) - 1)) as u64;
+        if self.0 < 0 {
+            return write!(f, "invalid XFL ({})", self.0);
+        }
+        if mantissa == 0 {
+            return f.write_str("0");
+        }
+
+        let mut exponent = self.exponent() as i32;
+        while mantissa % 10 == 0 {
+            mantissa /= 10;
+            exponent += 1;
+        }
+        // the sign bit is set for positive numbers
+        if self.0 & (1 << 62) == 0 {
+            f.write_str("-")?;
+        }
+
+        let digits = mantissa.ilog10() as i32 + 1;
+        if exponent >= 0 {
+            write!(f, "{mantissa}")?;
+            (0..exponent).try_for_each(|_| f.write_str("0"))
+        } else if -exponent < digits {
+            let scale = 10u64.pow(-exponent as u32);
+            write!(
+                f,
+                "{}.{:0width$}",
+                mantissa / scale,
+                mantissa % scale,
+                width = -exponent as usize
+            )
+        } else {
+            f.write_str("0.")?;
+            (0..-exponent - digits).try_for_each(|_| f.write_str("0"))?;
+            write!(f, "{mantissa}")
+        }
+    }
+}
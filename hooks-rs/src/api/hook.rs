@@ -5,6 +5,7 @@ use crate::c;
 
 /// Meant to be used as an argument to `hook_hash` to specify the hook number.
 #[derive(Copy, Clone)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
 pub enum HookNumber {
     /// The currently executing hook
     CurrentHook,
@@ -12,6 +13,16 @@ pub enum HookNumber {
     Custom(i32),
 }
 
+/// Meant to be used as an argument to `hook_skip` to either skip or stop skipping a hook.
+#[derive(Copy, Clone)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub enum HookSkipFlag {
+    /// Skip the hook for the rest of the current hook chain execution
+    Skip = 0,
+    /// Cancel a skip that was previously requested for the hook
+    CancelSkip = 1,
+}
+
 /// Retreive the 20 byte Account ID the Hook is executing on
 ///
 /// # Example
@@ -23,18 +34,21 @@ pub enum HookNumber {
 ///     }
 /// };
 ///
-/// accept(&hook_account, 0);
+/// accept(hook_account.as_bytes(), 0);
 /// ```
 #[inline(always)]
-pub fn hook_account() -> Result<[u8; ACC_ID_LEN]> {
+pub fn hook_account() -> Result<AccountId> {
     let func = |buffer_mut_ptr: *mut MaybeUninit<u8>| {
         let result: Result<u64> =
-            unsafe { c::hook_account(buffer_mut_ptr as u32, ACC_ID_LEN as u32).into() };
+            unsafe { c::hook_account(host_ptr(buffer_mut_ptr), ACC_ID_LEN as u32).into() };
 
         result
     };
 
-    init_buffer_mut(func)
+    match init_buffer_mut(func) {
+        Ok(account_id) => Ok(AccountId(account_id)),
+        Err(err) => Err(err),
+    }
 }
 
 /// Retrieve the parameter value for a named hook parameter
@@ -53,9 +67,9 @@ pub fn hook_param<const HOOK_PARAM_LEN: usize>(
     let func = |buffer_mut_ptr: *mut MaybeUninit<u8>| {
         let result: Result<u64> = unsafe {
             c::hook_param(
-                buffer_mut_ptr as u32,
+                host_ptr(buffer_mut_ptr),
                 HOOK_PARAM_LEN as u32,
-                parameter_name.as_ptr() as u32,
+                host_ptr(parameter_name.as_ptr()),
                 parameter_name.len() as u32,
             )
             .into()
@@ -91,7 +105,7 @@ pub fn hook_hash(hook_number: HookNumber) -> Result<[u8; HOOK_HASH_LEN]> {
     let func = |buffer_mut_ptr: *mut MaybeUninit<u8>| {
         let result: Result<u64> = unsafe {
             c::hook_hash(
-                buffer_mut_ptr as u32,
+                host_ptr(buffer_mut_ptr),
                 HOOK_HASH_LEN as u32,
                 hook_number.into(),
             )
@@ -104,6 +118,156 @@ pub fn hook_hash(hook_number: HookNumber) -> Result<[u8; HOOK_HASH_LEN]> {
     init_buffer_mut(func)
 }
 
+/// Request that the currently executing hook be executed again, weakly, after the
+/// originating transaction has been applied.
+///
+/// Returns [HookError::AlreadySet] if `hook_again` was already called during this execution
+/// and [HookError::PrerequisiteNotMet] if the hook is not being executed strongly.
+///
+/// # Example
+/// ```
+/// // returns 1 on success
+/// let _ = hook_again();
+/// ```
+#[inline(always)]
+pub fn hook_again() -> Result<u64> {
+    unsafe { c::hook_again() }.into()
+}
+
+/// Request the weak re-execution of the currently executing hook, which is useful
+/// for postponing work until the originating transaction has been applied.
+///
+/// Unlike [hook_again], calling this more than once during the same execution is not
+/// an error.
+///
+/// # Example
+/// ```
+/// request_weak_reexecution().unwrap_line_number();
+/// ```
+#[inline(always)]
+pub fn request_weak_reexecution() -> Result<()> {
+    match hook_again() {
+        Ok(_) | Err(HookError::AlreadySet) => Ok(()),
+        Err(err) => Err(err),
+    }
+}
+
+/// Skip, or cancel a previous skip of, a hook with the given hook hash in the hook chain
+/// currently executing
+///
+/// # Example
+/// ```
+/// let other_hook_hash = hook_hash(HookNumber::Custom(1)).unwrap_line_number();
+///
+/// hook_skip(&other_hook_hash, HookSkipFlag::Skip).unwrap_line_number();
+/// ```
+#[inline(always)]
+pub fn hook_skip(hook_hash: &[u8; HOOK_HASH_LEN], flag: HookSkipFlag) -> Result<u64> {
+    unsafe {
+        c::hook_skip(
+            host_ptr(hook_hash.as_ptr()),
+            HOOK_HASH_LEN as u32,
+            flag.into(),
+        )
+    }
+    .into()
+}
+
+/// Most hooks the hook chain of an account has
+pub const HOOK_CHAIN_MAX_LEN: u32 = 10;
+
+/// Whether no hook comes before the executing one in the hook chain of its account, so no
+/// other hook of the chain ran for the originating transaction before it.
+///
+/// # Example
+/// ```
+/// if is_first_hook().unwrap_line_number() {
+///     // no other hook could have handled the transaction yet
+/// }
+/// ```
+#[inline(always)]
+pub fn is_first_hook() -> Result<bool> {
+    Ok(!any_hook_in(0, hook_pos() as u32)?)
+}
+
+/// Whether no hook comes after the executing one in the hook chain of its account, so no
+/// other hook of the chain runs for the originating transaction after it.
+#[inline(always)]
+pub fn is_last_hook() -> Result<bool> {
+    Ok(!any_hook_in(hook_pos() as u32 + 1, HOOK_CHAIN_MAX_LEN)?)
+}
+
+/// Skip every hook that comes after the executing one in the hook chain, for the rest of
+/// the execution of the chain.
+///
+/// A hook that handles the originating transaction for the whole chain calls this so the
+/// hooks after it don't handle it a second time, see [accept_handled].
+#[inline(always)]
+pub fn skip_later_hooks() -> Result<()> {
+    let mut hook_no = hook_pos() as u32 + 1;
+    // guarded loop
+    while {
+        max_iter(HOOK_CHAIN_MAX_LEN + 1);
+        hook_no < HOOK_CHAIN_MAX_LEN
+    } {
+        if let Some(hook_hash) = hook_at(hook_no)? {
+            hook_skip(&hook_hash, HookSkipFlag::Skip)?;
+        }
+        hook_no += 1;
+    }
+
+    Ok(())
+}
+
+/// Accept the originating transaction as handled, skipping the later hooks of the chain.
+///
+/// Hooks composed into a chain short-circuit this way once one of them handled the
+/// transaction. Rolls back if the later hooks can't be skipped.
+///
+/// # Example
+/// ```
+/// if otxn_type() == TxnType::Invoke as i64 {
+///     accept_handled(b"invoked", 0);
+/// }
+/// // leave other transactions to the hooks after this one
+/// accept(b"", 0);
+/// ```
+#[inline(always)]
+pub fn accept_handled(msg: &[u8], code: i64) -> ! {
+    match skip_later_hooks() {
+        Ok(()) => accept(msg, code),
+        Err(err) => rollback(b"could not skip the later hooks", err.into()),
+    }
+}
+
+// The hash of the hook at the position of the chain, `None` if the position is empty
+#[inline(always)]
+fn hook_at(hook_no: u32) -> Result<Option<[u8; HOOK_HASH_LEN]>> {
+    match hook_hash(HookNumber::Custom(hook_no as i32)) {
+        Ok(hook_hash) => Ok(Some(hook_hash)),
+        Err(HookError::DoesntExist) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+// Whether any position of the chain from `start` up to `end` has a hook
+#[inline(always)]
+fn any_hook_in(start: u32, end: u32) -> Result<bool> {
+    let mut hook_no = start;
+    // guarded loop
+    while {
+        max_iter(HOOK_CHAIN_MAX_LEN + 1);
+        hook_no < end
+    } {
+        if hook_at(hook_no)?.is_some() {
+            return Ok(true);
+        }
+        hook_no += 1;
+    }
+
+    Ok(false)
+}
+
 impl From<HookNumber> for i32 {
     fn from(hook_no: HookNumber) -> i32 {
         match hook_no {
@@ -112,3 +276,305 @@ impl From<HookNumber> for i32 {
         }
     }
 }
+
+impl From<HookSkipFlag> for u32 {
+    fn from(flag: HookSkipFlag) -> u32 {
+        flag as u32
+    }
+}
+
+/// Byte length of a `HookOn` bitmask
+pub const HOOK_ON_LEN: usize = 32;
+
+/// `HookOn` bitmask of a SetHook transaction, choosing the transaction types a hook
+/// is triggered by.
+///
+/// Bit `n`, counted from the least significant bit of the last byte, stands for
+/// transaction type `n`. A cleared bit triggers the hook and a set bit does not,
+/// except for [TxnType::HookSet], whose bit is inverted. [HookOn::trigger_on] and
+/// [HookOn::skip] take care of that, so the mask never needs to be written by hand.
+///
+/// # Example
+/// ```
+/// const HOOK_ON: HookOn = HookOn::NONE
+///     .trigger_on(TxnType::Payment)
+///     .trigger_on(TxnType::Invoke);
+///
+/// assert!(HOOK_ON.triggers_on(TxnType::Payment));
+/// assert!(!HOOK_ON.triggers_on(TxnType::HookSet));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[repr(transparent)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct HookOn(pub [u8; HOOK_ON_LEN]);
+
+impl HookOn {
+    /// Triggered by no transaction type
+    pub const NONE: HookOn = {
+        let mut mask = [0xFF; HOOK_ON_LEN];
+        let (byte, bit) = Self::position(TxnType::HookSet);
+        mask[byte] &= !bit;
+        HookOn(mask)
+    };
+
+    /// Byte and bit of the mask standing for the transaction type
+    const fn position(txn_type: TxnType) -> (usize, u8) {
+        let txn_type = txn_type as u32;
+        (HOOK_ON_LEN - 1 - txn_type as usize / 8, 1 << (txn_type % 8))
+    }
+
+    /// Also trigger the hook on the transaction type
+    pub const fn trigger_on(mut self, txn_type: TxnType) -> Self {
+        let (byte, bit) = Self::position(txn_type);
+        if matches!(txn_type, TxnType::HookSet) {
+            self.0[byte] |= bit;
+        } else {
+            self.0[byte] &= !bit;
+        }
+        self
+    }
+
+    /// Don't trigger the hook on the transaction type
+    pub const fn skip(mut self, txn_type: TxnType) -> Self {
+        let (byte, bit) = Self::position(txn_type);
+        if matches!(txn_type, TxnType::HookSet) {
+            self.0[byte] &= !bit;
+        } else {
+            self.0[byte] |= bit;
+        }
+        self
+    }
+
+    /// Whether the transaction type triggers the hook
+    pub const fn triggers_on(&self, txn_type: TxnType) -> bool {
+        let (byte, bit) = Self::position(txn_type);
+        (self.0[byte] & bit == 0) != matches!(txn_type, TxnType::HookSet)
+    }
+
+    /// The mask as the 64 uppercase hex characters of the `HookOn` field of SetHook
+    /// transactions
+    pub const fn to_hex(&self) -> [u8; HOOK_ON_LEN * 2] {
+        hook_on_hex(self)
+    }
+}
+
+/// Compute the `HookOn` bitmask that triggers a hook on the given transaction types
+/// only. Use [hookon!](crate::hookon!) to compute it at compile time.
+pub const fn hook_on(txn_types: &[TxnType]) -> HookOn {
+    let mut hook_on = HookOn::NONE;
+    let mut i = 0;
+    while i < txn_types.len() {
+        hook_on = hook_on.trigger_on(txn_types[i]);
+        i += 1;
+    }
+
+    hook_on
+}
+
+/// Encode a `HookOn` bitmask as the 64 uppercase hex characters used in the `HookOn`
+/// field of SetHook transactions
+pub const fn hook_on_hex(hook_on: &HookOn) -> [u8; HOOK_ON_LEN * 2] {
+    const DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+
+    let mut hex = [0; HOOK_ON_LEN * 2];
+    let mut i = 0;
+    while i < HOOK_ON_LEN {
+        hex[i * 2] = DIGITS[(hook_on.0[i] >> 4) as usize];
+        hex[i * 2 + 1] = DIGITS[(hook_on.0[i] & 0x0F) as usize];
+        i += 1;
+    }
+
+    hex
+}
+
+/// Compute the `HookOn` bitmask for the given [TxnType] variants at compile time.
+///
+/// # Example
+/// ```
+/// const HOOK_ON: HookOn = hookon!(Payment, Invoke);
+/// const HOOK_ON_HEX: [u8; 64] = hook_on_hex(&HOOK_ON);
+/// ```
+#[macro_export]
+macro_rules! hookon {
+    ($($txn_type:ident),* $(,)?) => {
+        const { $crate::hook_on(&[$($crate::TxnType::$txn_type),*]) }
+    };
+}
+
+/// Compute the `HookNamespace` of a SetHook transaction from a seed.
+///
+/// The namespace is the SHA-256 of the seed. hooks-cli installs every hook with the seed
+/// `<hook name>namespace`, and so do the `default_namespace` of `hooks-rs-tools` and its
+/// `--namespace <SEED>` option. Pass it to [state_foreign] to read the state of a hook
+/// installed that way, and use [Namespace::from_label] for namespaces of your own.
+///
+/// Contains an unguarded loop, so it must only be evaluated at compile time in a hook.
+///
+/// # Example
+/// ```
+/// const NAMESPACE: Hash = hook_namespace(b"acceptnamespace");
+///
+/// let mut data = [0; 8];
+/// state_foreign(&mut data, b"key", &NAMESPACE, &otxn_account).unwrap_line_number();
+/// ```
+pub const fn hook_namespace(seed: &[u8]) -> Hash {
+    crate::base58::sha256(seed)
+}
+
+/// Namespace of the state of a hook, the `HookNamespace` of the SetHook transaction
+/// installing it.
+///
+/// Derive it with [Namespace::from_label] both in the hook, to pass to [state_foreign],
+/// and in the SetHook transaction, so that the two can't disagree: `hooks-rs-tools`
+/// takes the same label in `SetHook::new(wasm, Namespace::from_label(b"vault"))` and in
+/// its `--namespace-label` option. hooks-cli doesn't take labels, it installs hooks with
+/// the SHA-256 of a seed, which is [Namespace::from_seed] and also the `default_namespace`
+/// of `hooks-rs-tools`.
+///
+/// # Example
+/// ```
+/// const VAULT: Namespace = Namespace::from_label(b"vault");
+///
+/// let mut data = [0; 8];
+/// state_foreign(&mut data, b"key", VAULT.as_bytes(), &otxn_account).unwrap_line_number();
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[repr(transparent)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct Namespace(pub Hash);
+
+impl Namespace {
+    /// The namespace of a label, its SHA-512Half as `util_sha512h` computes it.
+    ///
+    /// Contains unguarded loops, so it must only be evaluated at compile time in a hook.
+    pub const fn from_label(label: &[u8]) -> Self {
+        Namespace(crate::sha512::sha512_half(label))
+    }
+
+    /// The namespace hooks-cli installs a hook with for a seed, see [hook_namespace]
+    pub const fn from_seed(seed: &[u8]) -> Self {
+        Namespace(hook_namespace(seed))
+    }
+
+    /// The 32 bytes of the namespace
+    #[inline(always)]
+    pub const fn as_bytes(&self) -> &Hash {
+        &self.0
+    }
+}
+
+impl From<Hash> for Namespace {
+    fn from(namespace: Hash) -> Self {
+        Namespace(namespace)
+    }
+}
+
+impl From<Namespace> for Hash {
+    fn from(namespace: Namespace) -> Self {
+        namespace.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+
+    #[wasm_bindgen_test]
+    fn computes_hook_on_masks() {
+        assert_eq!(
+            &hook_on_hex(&hookon!(Payment)),
+            b"FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFBFFFFE"
+        );
+        assert_eq!(
+            &hook_on_hex(&hookon!(Payment, HookSet, Invoke)),
+            b"FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF7FFFFFFFFFFFFFFFFFFFFFFFE"
+        );
+        assert_eq!(
+            &hook_on_hex(&hookon!()),
+            b"FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFBFFFFF"
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn toggles_hook_on_transaction_types() {
+        const HOOK_ON: HookOn = HookOn::NONE
+            .trigger_on(TxnType::Payment)
+            .trigger_on(TxnType::HookSet)
+            .trigger_on(TxnType::Invoke)
+            .skip(TxnType::Payment);
+
+        assert!(HOOK_ON == hookon!(HookSet, Invoke));
+        assert!(HOOK_ON.triggers_on(TxnType::HookSet));
+        assert!(HOOK_ON.triggers_on(TxnType::Invoke));
+        assert!(!HOOK_ON.triggers_on(TxnType::Payment));
+        assert!(HOOK_ON.skip(TxnType::HookSet) == hookon!(Invoke));
+        assert!(!HookOn::NONE.triggers_on(TxnType::HookSet));
+        assert_eq!(
+            &HookOn::NONE.to_hex(),
+            b"FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFBFFFFF"
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn computes_hook_namespaces() {
+        const NAMESPACE: Hash = hook_namespace(b"acceptnamespace");
+
+        assert_eq!(
+            NAMESPACE,
+            crate::hex::decode_array::<32>(
+                b"0771A689DD2ADD41F20B0EDD9C13DFEAAAAFC862191466AFECFA6A3AA2386315"
+            )
+            .unwrap()
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn derives_namespaces_from_labels() {
+        const VAULT: Namespace = Namespace::from_label(b"vault");
+
+        assert_eq!(VAULT.0, crate::sha512::sha512_half(b"vault"));
+        assert!(VAULT != Namespace::from_label(b"vaults"));
+        assert!(
+            Namespace::from_seed(b"acceptnamespace") == hook_namespace(b"acceptnamespace").into()
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn finds_the_position_in_the_hook_chain() {
+        crate::mock::reset();
+        assert!(matches!(is_first_hook(), Ok(true)));
+        assert!(matches!(is_last_hook(), Ok(true)));
+
+        crate::mock::set_hook_chain(&[None, Some([1; 32]), None, Some([2; 32])], 1);
+        assert!(matches!(is_first_hook(), Ok(true)));
+        assert!(matches!(is_last_hook(), Ok(false)));
+
+        crate::mock::set_hook_chain(&[Some([0; 32]), Some([1; 32]), None], 1);
+        assert!(matches!(is_first_hook(), Ok(false)));
+        assert!(matches!(is_last_hook(), Ok(true)));
+    }
+
+    #[wasm_bindgen_test]
+    fn skips_the_later_hooks() {
+        crate::mock::reset();
+        crate::mock::set_hook_chain(
+            &[
+                Some([0; 32]),
+                Some([1; 32]),
+                None,
+                Some([2; 32]),
+                Some([3; 32]),
+            ],
+            1,
+        );
+
+        let invocation = crate::mock::invoke(|| {
+            skip_later_hooks().unwrap_line_number();
+            Outcome::Accept { code: 0, msg: b"" }
+        });
+        assert!(invocation.accepted);
+        assert_eq!(crate::mock::skipped_hooks(), [[2; 32], [3; 32]]);
+    }
+}
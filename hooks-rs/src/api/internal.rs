@@ -16,11 +16,31 @@ where
             }
         }
 
-        uninitialized_buffer
-            .as_ptr()
-            .cast::<[u8; BUFFER_LEN]>()
-            .read_volatile()
+        assume_init_array(&uninitialized_buffer)
     };
 
     Ok(buffer)
 }
+
+/// Buffers up to this length are moved out of their `MaybeUninit` array with a plain read
+const MAX_PLAIN_READ_LEN: usize = 32;
+
+/// Copies a buffer initialized by the host out of its `MaybeUninit` array.
+///
+/// Up to [MAX_PLAIN_READ_LEN] bytes, this is a plain read, which compiles to a few 8 byte
+/// loads. LLVM turns plain reads of larger arrays into a call to `memcpy`, at least with
+/// `opt-level = "z"`, which brings the unguarded loops of `memcpy` into the hook. Larger
+/// buffers are read with a volatile read instead, which copies them byte by byte but
+/// never calls `memcpy`. This is the only volatile access of hooks-rs, so that every other
+/// copy can be merged into wide loads and stores by the optimizer.
+///
+/// # Safety
+/// Every byte of `buffer` must be initialized.
+#[inline(always)]
+pub(crate) unsafe fn assume_init_array<const N: usize>(buffer: &[MaybeUninit<u8>; N]) -> [u8; N] {
+    if N <= MAX_PLAIN_READ_LEN {
+        MaybeUninit::array_assume_init(*buffer)
+    } else {
+        buffer.as_ptr().cast::<[u8; N]>().read_volatile()
+    }
+}
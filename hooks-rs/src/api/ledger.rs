@@ -9,10 +9,93 @@ pub fn fee_base() -> i64 {
     unsafe { c::fee_base() }
 }
 
-/// Fetch the current ledger sequence number
+/// Fetch the sequence of the ledger the hook is executed in
+///
+/// # Example
+/// ```
+/// // valid in the next ledger and the 9 ledgers after it
+/// let window = LedgerWindow::after(ledger_seq(), 10);
+/// ```
 #[inline(always)]
-pub fn ledger_seq() -> i64 {
-    unsafe { c::ledger_seq() }
+pub fn ledger_seq() -> LedgerSequence {
+    LedgerSequence(unsafe { c::ledger_seq() } as u32)
+}
+
+/// The sequence number of a ledger, as in `FirstLedgerSequence` and `LastLedgerSequence`.
+///
+/// Adding ledgers saturates at the maximum rather than wrapping around, and
+/// [LedgerSequence::next] is the ledger emitted transactions are applied in at the
+/// earliest.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct LedgerSequence(pub u32);
+
+impl LedgerSequence {
+    /// The ledger after this one
+    #[inline(always)]
+    pub const fn next(self) -> Self {
+        LedgerSequence(self.0.saturating_add(1))
+    }
+
+    /// Add ledgers, returning `None` on overflow
+    #[inline(always)]
+    pub const fn checked_add(self, ledgers: u32) -> Option<Self> {
+        match self.0.checked_add(ledgers) {
+            Some(sequence) => Some(LedgerSequence(sequence)),
+            None => None,
+        }
+    }
+
+    /// Subtract ledgers, returning `None` if the result is before the first ledger
+    #[inline(always)]
+    pub const fn checked_sub(self, ledgers: u32) -> Option<Self> {
+        match self.0.checked_sub(ledgers) {
+            Some(sequence) => Some(LedgerSequence(sequence)),
+            None => None,
+        }
+    }
+
+    /// Ledgers closed since `earlier`, or `None` if `earlier` is later than `self`
+    #[inline(always)]
+    pub const fn ledgers_since(self, earlier: LedgerSequence) -> Option<u32> {
+        self.0.checked_sub(earlier.0)
+    }
+
+    /// Read a sequence stored as 4 big endian bytes, such as in hook state
+    #[inline(always)]
+    pub const fn from_be_bytes(bytes: [u8; 4]) -> Self {
+        LedgerSequence(u32::from_be_bytes(bytes))
+    }
+
+    /// Convert into 4 big endian bytes, such as for storing in hook state
+    #[inline(always)]
+    pub const fn to_be_bytes(self) -> [u8; 4] {
+        self.0.to_be_bytes()
+    }
+}
+
+impl core::ops::Add<u32> for LedgerSequence {
+    type Output = LedgerSequence;
+
+    /// The sequence `ledgers` ledgers later, saturating at the maximum
+    #[inline(always)]
+    fn add(self, ledgers: u32) -> LedgerSequence {
+        LedgerSequence(self.0.saturating_add(ledgers))
+    }
+}
+
+impl From<LedgerSequence> for u32 {
+    #[inline(always)]
+    fn from(sequence: LedgerSequence) -> Self {
+        sequence.0
+    }
+}
+
+impl From<LedgerSequence> for i64 {
+    #[inline(always)]
+    fn from(sequence: LedgerSequence) -> Self {
+        sequence.0 as i64
+    }
 }
 
 /// Fetch the last time the ledger was closed
@@ -21,23 +104,223 @@ pub fn ledger_last_time() -> i64 {
     unsafe { c::ledger_last_time() }
 }
 
+/// Fetch the close time of the last closed ledger as a [RippleTime]
+///
+/// # Example
+/// ```
+/// const UNLOCK_TIME: RippleTime = RippleTime::from_unix_seconds(1_735_689_600);
+///
+/// if ledger_time() < UNLOCK_TIME {
+///     rollback(b"funds are still locked", line!().into());
+/// }
+/// ```
+#[inline(always)]
+pub fn ledger_time() -> RippleTime {
+    RippleTime(ledger_last_time() as u32)
+}
+
+/// A point in time, in seconds since the Ripple epoch (2000-01-01T00:00:00Z).
+///
+/// This is how the ledger represents time, for example in `CloseTime` or `Expiration`
+/// fields. Use [RippleTime::from_unix_seconds] to avoid getting the epoch offset wrong.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct RippleTime(pub u32);
+
+impl RippleTime {
+    /// Seconds between the Unix epoch and the Ripple epoch
+    pub const UNIX_EPOCH_OFFSET: u64 = 946_684_800;
+
+    /// Create a time from Unix seconds.
+    ///
+    /// Times before the Ripple epoch are clamped to the Ripple epoch, and times that do not
+    /// fit in 32 bits are clamped to the maximum.
+    #[inline(always)]
+    pub const fn from_unix_seconds(unix_seconds: u64) -> Self {
+        let ripple_seconds = unix_seconds.saturating_sub(Self::UNIX_EPOCH_OFFSET);
+
+        if ripple_seconds > u32::MAX as u64 {
+            RippleTime(u32::MAX)
+        } else {
+            RippleTime(ripple_seconds as u32)
+        }
+    }
+
+    /// Convert into Unix seconds
+    #[inline(always)]
+    pub const fn to_unix_seconds(self) -> u64 {
+        self.0 as u64 + Self::UNIX_EPOCH_OFFSET
+    }
+
+    /// Read a time stored as 4 big endian bytes, such as in hook state
+    #[inline(always)]
+    pub const fn from_be_bytes(bytes: [u8; 4]) -> Self {
+        RippleTime(u32::from_be_bytes(bytes))
+    }
+
+    /// Convert into 4 big endian bytes, such as for storing in hook state
+    #[inline(always)]
+    pub const fn to_be_bytes(self) -> [u8; 4] {
+        self.0.to_be_bytes()
+    }
+
+    /// Add seconds, returning `None` on overflow
+    #[inline(always)]
+    pub const fn checked_add_seconds(self, seconds: u32) -> Option<Self> {
+        match self.0.checked_add(seconds) {
+            Some(time) => Some(RippleTime(time)),
+            None => None,
+        }
+    }
+
+    /// Subtract seconds, returning `None` if the result is before the Ripple epoch
+    #[inline(always)]
+    pub const fn checked_sub_seconds(self, seconds: u32) -> Option<Self> {
+        match self.0.checked_sub(seconds) {
+            Some(time) => Some(RippleTime(time)),
+            None => None,
+        }
+    }
+
+    /// Seconds elapsed since `earlier`, or `None` if `earlier` is later than `self`
+    #[inline(always)]
+    pub const fn seconds_since(self, earlier: RippleTime) -> Option<u32> {
+        self.0.checked_sub(earlier.0)
+    }
+}
+
 /// Retreive the 32 byte namespace biased SHA512H of the last closed ledger
+///
+/// # Example
+/// ```
+/// let last_ledger_hash = ledger_last_hash().unwrap_line_number();
+/// ```
 #[inline(always)]
-pub fn ledger_last_hash() -> Result<[u8; 32]> {
+pub fn ledger_last_hash() -> Result<[u8; LEDGER_HASH_LEN]> {
     init_buffer_mut(|buffer_mut_ptr: *mut MaybeUninit<u8>| {
         let result: Result<u64> =
-            unsafe { c::ledger_last_hash(buffer_mut_ptr as u32, 32_u32).into() };
+            unsafe { c::ledger_last_hash(host_ptr(buffer_mut_ptr), LEDGER_HASH_LEN as u32).into() };
 
         result
     })
 }
 
-/// Generate a 32 byte nonce for use in an emitted transaction
+/// Generate a 32 byte nonce based on the last closed ledger
+///
+/// Every call returns a different value within the same hook execution, but
+/// the sequence of values is deterministic for all nodes validating it.
 #[inline(always)]
-pub fn ledger_nonce() -> Result<[u8; 32]> {
+pub fn ledger_nonce() -> Result<[u8; NONCE_LEN]> {
     init_buffer_mut(|buffer_mut_ptr: *mut MaybeUninit<u8>| {
-        let result: Result<u64> = unsafe { c::ledger_nonce(buffer_mut_ptr as u32, 32_u32).into() };
+        let result: Result<u64> =
+            unsafe { c::ledger_nonce(host_ptr(buffer_mut_ptr), NONCE_LEN as u32).into() };
 
         result
     })
 }
+
+/// Get 32 pseudo-random bytes, for example to pick a winner in a lottery or a raffle.
+///
+/// This is a shorthand for [ledger_nonce]. The bytes are only unpredictable until
+/// the ledger closes and are identical for every node executing the hook, so they
+/// must never be used as a secret.
+///
+/// # Example
+/// ```
+/// let random = random_bytes().unwrap_line_number();
+/// let winner_index = random[0] % number_of_participants;
+/// ```
+#[inline(always)]
+pub fn random_bytes() -> Result<[u8; NONCE_LEN]> {
+    ledger_nonce()
+}
+
+/// Search for the first ledger object whose keylet is within the range specified by
+/// `low` and `high` (both inclusive)
+///
+/// Returns `Ok(None)` if there is no object in the range, so that "not found" can be
+/// told apart from real errors, such as an invalid keylet.
+///
+/// # Example
+/// ```
+/// match ledger_keylet(&low, &high) {
+///     Ok(Some(keylet)) => {
+///         // found the first object in the range
+///     }
+///     Ok(None) => {
+///         // the range is empty
+///     }
+///     Err(err) => rollback(b"could not search the keylet range", err.into()),
+/// }
+/// ```
+#[inline(always)]
+pub fn ledger_keylet(low: &Keylet, high: &Keylet) -> Result<Option<Keylet>> {
+    let func = |buffer_mut_ptr: *mut MaybeUninit<u8>| {
+        let result: Result<u64> = unsafe {
+            c::ledger_keylet(
+                host_ptr(buffer_mut_ptr),
+                KEYLET_LEN as u32,
+                host_ptr(low.as_ptr()),
+                KEYLET_LEN as u32,
+                host_ptr(high.as_ptr()),
+                KEYLET_LEN as u32,
+            )
+            .into()
+        };
+
+        result
+    };
+
+    match init_buffer_mut(func) {
+        Ok(keylet) => Ok(Some(keylet)),
+        Err(HookError::DoesntExist) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+
+    #[wasm_bindgen_test]
+    fn can_convert_ripple_time_from_and_to_unix_seconds() {
+        // 2025-01-01T00:00:00Z
+        let time = RippleTime::from_unix_seconds(1_735_689_600);
+
+        assert_eq!(time.0, 789_004_800);
+        assert_eq!(time.to_unix_seconds(), 1_735_689_600);
+        assert_eq!(RippleTime::from_unix_seconds(0).0, 0);
+        assert_eq!(RippleTime::from_unix_seconds(u64::MAX).0, u32::MAX);
+    }
+
+    #[wasm_bindgen_test]
+    fn can_compare_and_offset_ripple_time() {
+        let start = RippleTime(1_000);
+        let end = start.checked_add_seconds(500).unwrap();
+
+        assert!(start < end);
+        assert_eq!(end.seconds_since(start), Some(500));
+        assert_eq!(start.seconds_since(end), None);
+        assert!(start.checked_sub_seconds(1_001).is_none());
+        assert!(RippleTime(u32::MAX).checked_add_seconds(1).is_none());
+        assert!(RippleTime::from_be_bytes(end.to_be_bytes()) == end);
+    }
+
+    #[wasm_bindgen_test]
+    fn can_compare_and_offset_ledger_sequences() {
+        let start = LedgerSequence(1_000);
+        let end = start + 10;
+
+        assert_eq!(end, LedgerSequence(1_010));
+        assert_eq!(start.next(), LedgerSequence(1_001));
+        assert_eq!(end.ledgers_since(start), Some(10));
+        assert_eq!(start.ledgers_since(end), None);
+        assert_eq!(start.checked_sub(1_000), Some(LedgerSequence(0)));
+        assert!(start.checked_sub(1_001).is_none());
+        assert!(LedgerSequence(u32::MAX).checked_add(1).is_none());
+        assert_eq!(LedgerSequence(u32::MAX) + 1, LedgerSequence(u32::MAX));
+        assert_eq!(LedgerSequence::from_be_bytes(end.to_be_bytes()), end);
+    }
+}
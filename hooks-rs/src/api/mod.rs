@@ -47,6 +47,8 @@ pub const HASH_LEN: usize = 32;
 pub const HOOK_HASH_LEN: usize = 32;
 /// Amount byte length
 pub const AMOUNT_LEN: usize = 48;
+/// Public key byte length, for secp256k1 and prefixed Ed25519 keys alike
+pub const PUBLIC_KEY_LEN: usize = 33;
 /// Payment simple transaction byte length
 pub const PREPARE_PAYMENT_SIMPLE_SIZE: usize = c::PREPARE_PAYMENT_SIMPLE_SIZE as _;
 /// Emit details byte length
@@ -57,8 +59,351 @@ pub const XFL_LEN: usize = 8;
 /// Buffer of the specified size
 pub type Buffer<const T: usize> = [u8; T];
 
-/// Account id buffer
-pub type AccountId = Buffer<ACC_ID_LEN>;
+/// 20 byte account id, which can't be mixed up with currency codes or other 20 byte
+/// values.
+///
+/// Comparisons go through [is_buffer_equal](crate::is_buffer_equal), so they are guarded
+/// like every other loop of a hook.
+///
+/// # Example
+/// ```
+/// const GENESIS: Option<AccountId> = AccountId::from_raddress(b"rHb9CJAWyB4rj91VRWn96DkukG4bwdtyTh");
+///
+/// let hook_account = hook_account().unwrap_line_number();
+/// if hook_account.is_zero() || Some(hook_account) == GENESIS {
+///     rollback(b"not on this account", 1);
+/// }
+/// ```
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct AccountId(pub Buffer<ACC_ID_LEN>);
+
+impl AccountId {
+    /// The account id of zeroes, which no one has the keys of
+    pub const ZERO: AccountId = AccountId([0; ACC_ID_LEN]);
+
+    /// The account id with the given bytes
+    #[inline(always)]
+    pub const fn from_bytes(bytes: [u8; ACC_ID_LEN]) -> Self {
+        AccountId(bytes)
+    }
+
+    /// The account id with the given bytes, borrowing them, such as from a buffer the host
+    /// wrote into
+    #[inline(always)]
+    pub const fn from_bytes_ref(bytes: &[u8; ACC_ID_LEN]) -> &Self {
+        // SAFETY: AccountId is a transparent wrapper of the bytes
+        unsafe { &*(bytes as *const [u8; ACC_ID_LEN] as *const AccountId) }
+    }
+
+    /// Decode an r-address, `None` if it contains a character outside of the base58
+    /// alphabet or has an invalid length, version or checksum.
+    ///
+    /// Unlike [util_accid](crate::util_accid), it doesn't call the host, so it also works
+    /// in constants, native tests and off-chain tooling. [accid!](crate::accid) decodes
+    /// r-addresses with it.
+    ///
+    /// Contains unguarded loops, so it must only be evaluated at compile time in a hook.
+    ///
+    /// # Example
+    /// ```
+    /// const GENESIS_ACCOUNT_ID: Option<AccountId> =
+    ///     AccountId::from_raddress(b"rHb9CJAWyB4rj91VRWn96DkukG4bwdtyTh");
+    /// ```
+    #[inline(always)]
+    pub const fn from_raddress(r_address: &[u8]) -> Option<Self> {
+        crate::base58::decode_account_id(r_address)
+    }
+
+    /// Encode the account id as an r-address without calling the host, like
+    /// [util_raddr](crate::util_raddr) but also in constants and native tests.
+    ///
+    /// The r-address is written at the beginning of the returned buffer and the rest of it
+    /// is filled with zeroes.
+    ///
+    /// Contains unguarded loops, so it must only be evaluated at compile time in a hook.
+    #[inline(always)]
+    pub const fn to_raddress_const(&self) -> [u8; crate::R_ADDRESS_MAX_LEN] {
+        crate::base58::encode_account_id(self)
+    }
+
+    /// The 20 bytes of the account id
+    #[inline(always)]
+    pub const fn as_bytes(&self) -> &[u8; ACC_ID_LEN] {
+        &self.0
+    }
+
+    /// Whether all bytes are zero, as they are for [AccountId::ZERO]
+    #[inline(always)]
+    pub fn is_zero(&self) -> bool {
+        crate::is_buffer_equal(&self.0, &Self::ZERO.0)
+    }
+}
+
+impl PartialEq for AccountId {
+    #[inline(always)]
+    fn eq(&self, other: &Self) -> bool {
+        crate::is_buffer_equal(&self.0, &other.0)
+    }
+}
+
+impl Eq for AccountId {}
+
+impl From<[u8; ACC_ID_LEN]> for AccountId {
+    #[inline(always)]
+    fn from(bytes: [u8; ACC_ID_LEN]) -> Self {
+        AccountId(bytes)
+    }
+}
+
+impl From<AccountId> for [u8; ACC_ID_LEN] {
+    #[inline(always)]
+    fn from(account_id: AccountId) -> Self {
+        account_id.0
+    }
+}
+
+impl AsRef<[u8]> for AccountId {
+    #[inline(always)]
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// 33 byte public key, either a compressed secp256k1 key or an Ed25519 key prefixed with
+/// `0xED`, as taken by [util_verify] and encoded as `SigningPubKey`.
+///
+/// Comparisons are guarded like those of [AccountId].
+///
+/// # Example
+/// ```
+/// const OWNER: PublicKey = PublicKey(hex!("ED...")); // the key of the owner
+///
+/// if !util_verify(&payload, &signature, &OWNER)? {
+///     rollback(b"not signed by the owner", 1);
+/// }
+/// ```
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct PublicKey(pub Buffer<PUBLIC_KEY_LEN>);
+
+impl PublicKey {
+    /// The key of zeroes, which is how emitted transactions leave `SigningPubKey` empty
+    pub const NULL: PublicKey = PublicKey([0; PUBLIC_KEY_LEN]);
+
+    /// The public key with the given bytes
+    #[inline(always)]
+    pub const fn from_bytes(bytes: [u8; PUBLIC_KEY_LEN]) -> Self {
+        PublicKey(bytes)
+    }
+
+    /// The 33 bytes of the key
+    #[inline(always)]
+    pub const fn as_bytes(&self) -> &[u8; PUBLIC_KEY_LEN] {
+        &self.0
+    }
+
+    /// Whether this is an Ed25519 key, which starts with `0xED`, rather than a secp256k1
+    /// key, which starts with `0x02` or `0x03`
+    #[inline(always)]
+    pub const fn is_ed25519(&self) -> bool {
+        self.0[0] == 0xED
+    }
+
+    /// Whether all bytes are zero, as they are for [PublicKey::NULL]
+    #[inline(always)]
+    pub fn is_null(&self) -> bool {
+        crate::is_buffer_equal(&self.0, &Self::NULL.0)
+    }
+}
+
+impl PartialEq for PublicKey {
+    #[inline(always)]
+    fn eq(&self, other: &Self) -> bool {
+        crate::is_buffer_equal(&self.0, &other.0)
+    }
+}
+
+impl Eq for PublicKey {}
+
+impl From<[u8; PUBLIC_KEY_LEN]> for PublicKey {
+    #[inline(always)]
+    fn from(bytes: [u8; PUBLIC_KEY_LEN]) -> Self {
+        PublicKey(bytes)
+    }
+}
+
+impl From<PublicKey> for [u8; PUBLIC_KEY_LEN] {
+    #[inline(always)]
+    fn from(public_key: PublicKey) -> Self {
+        public_key.0
+    }
+}
+
+impl AsRef<[u8]> for PublicKey {
+    #[inline(always)]
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// Drops in one XRP
+pub const DROPS_PER_XRP: u64 = 1_000_000;
+
+/// Basis points in a whole, for shares given in hundredths of a percent
+pub const BASIS_POINTS: u32 = 10_000;
+
+/// An amount of XRP in drops, the unit of XRP amounts in transactions, which can't be
+/// mixed up with whole XRP or other numbers.
+///
+/// The checked operations return `None` on overflow and for amounts above [Drops::MAX],
+/// which could not be encoded.
+///
+/// # Example
+/// ```
+/// const FEE: Option<Drops> = Drops::from_xrp(2);
+///
+/// let paid = Drops(otxn_drops()?);
+/// let payout = match FEE.and_then(|fee| paid.checked_sub(fee)) {
+///     Some(payout) => payout,
+///     None => rollback(b"not enough to pay the fee", 1),
+/// };
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(transparent)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct Drops(pub u64);
+
+impl Drops {
+    /// No drops
+    pub const ZERO: Drops = Drops(0);
+
+    /// All the XRP there is, 100 billion XRP
+    pub const MAX: Drops = Drops(100_000_000_000 * DROPS_PER_XRP);
+
+    /// The drops in `xrp` whole XRP, `None` if that is more than [Drops::MAX]
+    #[inline(always)]
+    pub const fn from_xrp(xrp: u64) -> Option<Self> {
+        match xrp.checked_mul(DROPS_PER_XRP) {
+            Some(drops) => Drops(drops).within_max(),
+            None => None,
+        }
+    }
+
+    /// The whole XRP, rounded down, and the drops left over
+    #[inline(always)]
+    pub const fn to_xrp(self) -> (u64, u64) {
+        (self.0 / DROPS_PER_XRP, self.0 % DROPS_PER_XRP)
+    }
+
+    /// `self + other`, `None` on overflow
+    #[inline(always)]
+    pub const fn checked_add(self, other: Drops) -> Option<Self> {
+        match self.0.checked_add(other.0) {
+            Some(drops) => Drops(drops).within_max(),
+            None => None,
+        }
+    }
+
+    /// `self - other`, `None` if `other` is more than `self`
+    #[inline(always)]
+    pub const fn checked_sub(self, other: Drops) -> Option<Self> {
+        match self.0.checked_sub(other.0) {
+            Some(drops) => Drops(drops).within_max(),
+            None => None,
+        }
+    }
+
+    /// `self * factor`, `None` on overflow
+    #[inline(always)]
+    pub const fn checked_mul(self, factor: u64) -> Option<Self> {
+        match self.0.checked_mul(factor) {
+            Some(drops) => Drops(drops).within_max(),
+            None => None,
+        }
+    }
+
+    /// `self / divisor`, rounded down, `None` if `divisor` is zero
+    #[inline(always)]
+    pub const fn checked_div(self, divisor: u64) -> Option<Self> {
+        match self.0.checked_div(divisor) {
+            Some(drops) => Drops(drops).within_max(),
+            None => None,
+        }
+    }
+
+    /// `basis_points` hundredths of a percent of `self`, rounded down, `None` if that is
+    /// more than all of it
+    #[inline(always)]
+    pub const fn share(self, basis_points: u32) -> Option<Self> {
+        if basis_points > BASIS_POINTS {
+            return None;
+        }
+
+        // split up so that the multiplications can't overflow
+        let whole = self.0 / BASIS_POINTS as u64;
+        let rest = self.0 % BASIS_POINTS as u64;
+        Some(Drops(
+            whole * basis_points as u64 + rest * basis_points as u64 / BASIS_POINTS as u64,
+        ))
+    }
+
+    #[inline(always)]
+    const fn within_max(self) -> Option<Self> {
+        if self.0 > Self::MAX.0 {
+            None
+        } else {
+            Some(self)
+        }
+    }
+}
+
+impl From<Drops> for u64 {
+    #[inline(always)]
+    fn from(drops: Drops) -> Self {
+        drops.0
+    }
+}
+
+/// The amount in XRP, like `1.5 XRP` or `0.000012 XRP`
+#[cfg(any(test, feature = "std", feature = "trace"))]
+impl core::fmt::Display for Drops {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let (xrp, mut fraction) = self.to_xrp();
+        if fraction == 0 {
+            return write!(f, "{xrp} XRP");
+        }
+
+        let mut digits = 6;
+        while {
+            max_iter(7);
+            fraction % 10 == 0
+        } {
+            fraction /= 10;
+            digits -= 1;
+        }
+        write!(f, "{xrp}.{fraction:0digits$} XRP")
+    }
+}
+
+/// The `DestinationTag` of a transaction, which tells the recipient, such as an exchange,
+/// whom the payment is for.
+///
+/// A tag of 0 is a tag like any other, so a transaction without a tag has `None` where an
+/// `Option<DestTag>` is taken or returned, never `DestTag(0)`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct DestTag(pub u32);
+
+/// The `SourceTag` of a transaction, which tells on whose behalf the sender sent it.
+///
+/// Like [DestTag], "no tag" is `None` rather than `SrcTag(0)`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct SrcTag(pub u32);
+
 /// Hash buffer
 pub type Hash = Buffer<HASH_LEN>;
 /// Keylet buffer
@@ -80,6 +425,7 @@ pub type CurrencyCode = Buffer<CURRENCY_CODE_SIZE>;
 #[allow(missing_docs)]
 #[derive(Clone, Copy)]
 #[repr(u32)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
 pub enum TxnType {
     Payment = c::ttPAYMENT,
     EscrowCreate = 1,
@@ -102,15 +448,125 @@ pub enum TxnType {
     TrustSet = 20,
     AccountDelete = 21,
     HookSet = 22,
+    Invoke = c::ttINVOKE,
     Amendment = 100,
     Fee = 101,
     UnlModify = 102,
 }
 
+impl TxnType {
+    /// The transaction type with the given code, as in the `TransactionType` field of a
+    /// serialized transaction or returned by [otxn_type](crate::otxn_type)
+    #[inline(always)]
+    pub const fn from_code(code: u16) -> Option<Self> {
+        Some(match code {
+            0 => TxnType::Payment,
+            1 => TxnType::EscrowCreate,
+            2 => TxnType::EscrowFinish,
+            3 => TxnType::AccountSet,
+            4 => TxnType::EscrowCancel,
+            5 => TxnType::RegularKeySet,
+            7 => TxnType::OfferCreate,
+            8 => TxnType::OfferCancel,
+            10 => TxnType::TicketCreate,
+            11 => TxnType::TicketCancel,
+            12 => TxnType::SignerListSet,
+            13 => TxnType::PaychanCreate,
+            14 => TxnType::PaychanFund,
+            15 => TxnType::PaychanClaim,
+            16 => TxnType::CheckCreate,
+            17 => TxnType::CheckCash,
+            18 => TxnType::CheckCancel,
+            19 => TxnType::DepositPreauth,
+            20 => TxnType::TrustSet,
+            21 => TxnType::AccountDelete,
+            22 => TxnType::HookSet,
+            99 => TxnType::Invoke,
+            100 => TxnType::Amendment,
+            101 => TxnType::Fee,
+            102 => TxnType::UnlModify,
+            _ => return None,
+        })
+    }
+}
+
+/// Names of the transaction types in the JSON of rippled and xahaud, such as the
+/// `TransactionType` of a `tx` RPC response or of the `xrpl` crate
+#[cfg(any(test, feature = "std"))]
+impl TxnType {
+    const ALL: [TxnType; 25] = [
+        TxnType::Payment,
+        TxnType::EscrowCreate,
+        TxnType::EscrowFinish,
+        TxnType::AccountSet,
+        TxnType::EscrowCancel,
+        TxnType::RegularKeySet,
+        TxnType::OfferCreate,
+        TxnType::OfferCancel,
+        TxnType::TicketCreate,
+        TxnType::TicketCancel,
+        TxnType::SignerListSet,
+        TxnType::PaychanCreate,
+        TxnType::PaychanFund,
+        TxnType::PaychanClaim,
+        TxnType::CheckCreate,
+        TxnType::CheckCash,
+        TxnType::CheckCancel,
+        TxnType::DepositPreauth,
+        TxnType::TrustSet,
+        TxnType::AccountDelete,
+        TxnType::HookSet,
+        TxnType::Invoke,
+        TxnType::Amendment,
+        TxnType::Fee,
+        TxnType::UnlModify,
+    ];
+
+    /// The name of the transaction type, like `PaymentChannelCreate` for
+    /// [TxnType::PaychanCreate]
+    pub const fn name(self) -> &'static str {
+        match self {
+            TxnType::Payment => "Payment",
+            TxnType::EscrowCreate => "EscrowCreate",
+            TxnType::EscrowFinish => "EscrowFinish",
+            TxnType::AccountSet => "AccountSet",
+            TxnType::EscrowCancel => "EscrowCancel",
+            TxnType::RegularKeySet => "SetRegularKey",
+            TxnType::OfferCreate => "OfferCreate",
+            TxnType::OfferCancel => "OfferCancel",
+            TxnType::TicketCreate => "TicketCreate",
+            TxnType::TicketCancel => "TicketCancel",
+            TxnType::SignerListSet => "SignerListSet",
+            TxnType::PaychanCreate => "PaymentChannelCreate",
+            TxnType::PaychanFund => "PaymentChannelFund",
+            TxnType::PaychanClaim => "PaymentChannelClaim",
+            TxnType::CheckCreate => "CheckCreate",
+            TxnType::CheckCash => "CheckCash",
+            TxnType::CheckCancel => "CheckCancel",
+            TxnType::DepositPreauth => "DepositPreauth",
+            TxnType::TrustSet => "TrustSet",
+            TxnType::AccountDelete => "AccountDelete",
+            TxnType::HookSet => "SetHook",
+            TxnType::Invoke => "Invoke",
+            TxnType::Amendment => "EnableAmendment",
+            TxnType::Fee => "SetFee",
+            TxnType::UnlModify => "UNLModify",
+        }
+    }
+
+    /// The transaction type with the given [name](TxnType::name)
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|txn_type| txn_type.name() == name)
+    }
+}
+
 /// Account type
 #[allow(missing_docs)]
 #[derive(Clone, Copy)]
 #[repr(u32)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
 pub enum AccountType {
     Account = c::atACCOUNT,
     Owner = c::atOWNER,
@@ -127,6 +583,7 @@ pub enum AccountType {
 #[allow(missing_docs)]
 #[derive(Clone, Copy)]
 #[repr(u32)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
 pub enum AmountType {
     Amount = c::amAMOUNT,
     Balance = c::amBALANCE,
@@ -146,6 +603,7 @@ pub enum AmountType {
 /// Keylet type
 #[allow(missing_docs)]
 #[derive(Clone, Copy)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
 pub enum KeyletType<'a> {
     Hook(&'a [u8]),
     HookState(&'a [u8], &'a [u8]),
@@ -174,6 +632,7 @@ pub enum KeyletType<'a> {
 ///
 /// Used as return of [slot_type] function
 #[derive(Clone, Copy)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
 pub enum FieldOrXrpAmount {
     /// Field ID
     Field(FieldId),
@@ -185,6 +644,7 @@ pub enum FieldOrXrpAmount {
 
 /// Flags for [slot_type]
 #[derive(Clone, Copy)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
 pub enum SlotTypeFlags {
     /// Field
     Field,
@@ -192,227 +652,126 @@ pub enum SlotTypeFlags {
     XrpAmount,
 }
 
-/// Field type
+// Defines FieldId from the fields of `c/sfcodes.h`, see `build.rs`
+macro_rules! field_ids {
+    ($($name:ident = $code:path,)*) => {
+        /// Field type
+        #[allow(missing_docs)]
+        #[derive(Clone, Copy)]
+        #[repr(u32)]
+        #[cfg_attr(any(test, feature = "std"), derive(Debug))]
+        pub enum FieldId {
+            $($name = $code,)*
+        }
+
+        impl FieldId {
+            /// Every field, in the order of `sfcodes.h`
+            pub const ALL: &'static [FieldId] = &[$(FieldId::$name,)*];
+
+            /// The name of the field, which is also its name in the JSON of rippled, like
+            /// `Destination`
+            pub const fn name(self) -> &'static str {
+                match self {
+                    $(FieldId::$name => stringify!($name),)*
+                }
+            }
+
+            /// The field with the given ID, in the encoding of [FieldId]
+            #[inline(always)]
+            pub const fn from_code(field_id: u32) -> Option<Self> {
+                match field_id {
+                    $($code => Some(FieldId::$name),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+include!(concat!(env!("OUT_DIR"), "/sfields.rs"));
+
+impl FieldId {
+    /// The field with the given [name](FieldId::name)
+    #[cfg(any(test, feature = "std"))]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|field| field.name() == name)
+    }
+
+    /// The serialized type of the field, `None` if unknown to [SerializedType]
+    #[inline(always)]
+    pub const fn serialized_type(self) -> Option<SerializedType> {
+        SerializedType::from_code(self as u32 >> 16)
+    }
+
+    /// The code of the field within its type, the lower 16 bits of its ID
+    #[inline(always)]
+    pub const fn field_code(self) -> u32 {
+        self as u32 & 0xFFFF
+    }
+}
+
+/// Serialized type of a field, the upper 16 bits of a [FieldId]
 #[allow(missing_docs)]
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 #[repr(u32)]
-pub enum FieldId {
-    CloseResolution = c::sfCloseResolution,
-    Method = c::sfMethod,
-    TransactionResult = c::sfTransactionResult,
-    TickSize = c::sfTickSize,
-    UNLModifyDisabling = c::sfUNLModifyDisabling,
-    HookResult = c::sfHookResult,
-    LedgerEntryType = c::sfLedgerEntryType,
-    TransactionType = c::sfTransactionType,
-    SignerWeight = c::sfSignerWeight,
-    TransferFee = c::sfTransferFee,
-    Version = c::sfVersion,
-    HookStateChangeCount = c::sfHookStateChangeCount,
-    HookEmitCount = c::sfHookEmitCount,
-    HookExecutionIndex = c::sfHookExecutionIndex,
-    HookApiVersion = c::sfHookApiVersion,
-    NetworkID = c::sfNetworkID,
-    Flags = c::sfFlags,
-    SourceTag = c::sfSourceTag,
-    Sequence = c::sfSequence,
-    PreviousTxnLgrSeq = c::sfPreviousTxnLgrSeq,
-    LedgerSequence = c::sfLedgerSequence,
-    CloseTime = c::sfCloseTime,
-    ParentCloseTime = c::sfParentCloseTime,
-    SigningTime = c::sfSigningTime,
-    Expiration = c::sfExpiration,
-    TransferRate = c::sfTransferRate,
-    WalletSize = c::sfWalletSize,
-    OwnerCount = c::sfOwnerCount,
-    DestinationTag = c::sfDestinationTag,
-    HighQualityIn = c::sfHighQualityIn,
-    HighQualityOut = c::sfHighQualityOut,
-    LowQualityIn = c::sfLowQualityIn,
-    LowQualityOut = c::sfLowQualityOut,
-    QualityIn = c::sfQualityIn,
-    QualityOut = c::sfQualityOut,
-    StampEscrow = c::sfStampEscrow,
-    BondAmount = c::sfBondAmount,
-    LoadFee = c::sfLoadFee,
-    OfferSequence = c::sfOfferSequence,
-    FirstLedgerSequence = c::sfFirstLedgerSequence,
-    LastLedgerSequence = c::sfLastLedgerSequence,
-    TransactionIndex = c::sfTransactionIndex,
-    OperationLimit = c::sfOperationLimit,
-    ReferenceFeeUnits = c::sfReferenceFeeUnits,
-    ReserveBase = c::sfReserveBase,
-    ReserveIncrement = c::sfReserveIncrement,
-    SetFlag = c::sfSetFlag,
-    ClearFlag = c::sfClearFlag,
-    SignerQuorum = c::sfSignerQuorum,
-    CancelAfter = c::sfCancelAfter,
-    FinishAfter = c::sfFinishAfter,
-    SignerListID = c::sfSignerListID,
-    SettleDelay = c::sfSettleDelay,
-    TicketCount = c::sfTicketCount,
-    TicketSequence = c::sfTicketSequence,
-    NFTokenTaxon = c::sfNFTokenTaxon,
-    MintedNFTokens = c::sfMintedNFTokens,
-    BurnedNFTokens = c::sfBurnedNFTokens,
-    HookStateCount = c::sfHookStateCount,
-    EmitGeneration = c::sfEmitGeneration,
-    LockCount = c::sfLockCount,
-    RewardTime = c::sfRewardTime,
-    RewardLgrFirst = c::sfRewardLgrFirst,
-    RewardLgrLast = c::sfRewardLgrLast,
-    IndexNext = c::sfIndexNext,
-    IndexPrevious = c::sfIndexPrevious,
-    BookNode = c::sfBookNode,
-    OwnerNode = c::sfOwnerNode,
-    BaseFee = c::sfBaseFee,
-    ExchangeRate = c::sfExchangeRate,
-    LowNode = c::sfLowNode,
-    HighNode = c::sfHighNode,
-    DestinationNode = c::sfDestinationNode,
-    Cookie = c::sfCookie,
-    ServerVersion = c::sfServerVersion,
-    NFTokenOfferNode = c::sfNFTokenOfferNode,
-    EmitBurden = c::sfEmitBurden,
-    HookInstructionCount = c::sfHookInstructionCount,
-    HookReturnCode = c::sfHookReturnCode,
-    ReferenceCount = c::sfReferenceCount,
-    RewardAccumulator = c::sfRewardAccumulator,
-    EmailHash = c::sfEmailHash,
-    TakerPaysCurrency = c::sfTakerPaysCurrency,
-    TakerPaysIssuer = c::sfTakerPaysIssuer,
-    TakerGetsCurrency = c::sfTakerGetsCurrency,
-    TakerGetsIssuer = c::sfTakerGetsIssuer,
-    LedgerHash = c::sfLedgerHash,
-    ParentHash = c::sfParentHash,
-    TransactionHash = c::sfTransactionHash,
-    AccountHash = c::sfAccountHash,
-    PreviousTxnID = c::sfPreviousTxnID,
-    LedgerIndex = c::sfLedgerIndex,
-    WalletLocator = c::sfWalletLocator,
-    RootIndex = c::sfRootIndex,
-    AccountTxnID = c::sfAccountTxnID,
-    NFTokenID = c::sfNFTokenID,
-    EmitParentTxnID = c::sfEmitParentTxnID,
-    EmitNonce = c::sfEmitNonce,
-    EmitHookHash = c::sfEmitHookHash,
-    BookDirectory = c::sfBookDirectory,
-    InvoiceID = c::sfInvoiceID,
-    Nickname = c::sfNickname,
-    Amendment = c::sfAmendment,
-    HookOn = c::sfHookOn,
-    Digest = c::sfDigest,
-    Channel = c::sfChannel,
-    ConsensusHash = c::sfConsensusHash,
-    CheckID = c::sfCheckID,
-    ValidatedHash = c::sfValidatedHash,
-    PreviousPageMin = c::sfPreviousPageMin,
-    NextPageMin = c::sfNextPageMin,
-    NFTokenBuyOffer = c::sfNFTokenBuyOffer,
-    NFTokenSellOffer = c::sfNFTokenSellOffer,
-    HookStateKey = c::sfHookStateKey,
-    HookHash = c::sfHookHash,
-    HookNamespace = c::sfHookNamespace,
-    HookSetTxnID = c::sfHookSetTxnID,
-    OfferID = c::sfOfferID,
-    EscrowID = c::sfEscrowID,
-    URITokenID = c::sfURITokenID,
-    Amount = c::sfAmount,
-    Balance = c::sfBalance,
-    LimitAmount = c::sfLimitAmount,
-    TakerPays = c::sfTakerPays,
-    TakerGets = c::sfTakerGets,
-    LowLimit = c::sfLowLimit,
-    HighLimit = c::sfHighLimit,
-    Fee = c::sfFee,
-    SendMax = c::sfSendMax,
-    DeliverMin = c::sfDeliverMin,
-    MinimumOffer = c::sfMinimumOffer,
-    RippleEscrow = c::sfRippleEscrow,
-    DeliveredAmount = c::sfDeliveredAmount,
-    NFTokenBrokerFee = c::sfNFTokenBrokerFee,
-    HookCallbackFee = c::sfHookCallbackFee,
-    LockedBalance = c::sfLockedBalance,
-    PublicKey = c::sfPublicKey,
-    MessageKey = c::sfMessageKey,
-    SigningPubKey = c::sfSigningPubKey,
-    TxnSignature = c::sfTxnSignature,
-    URI = c::sfURI,
-    Signature = c::sfSignature,
-    Domain = c::sfDomain,
-    FundCode = c::sfFundCode,
-    RemoveCode = c::sfRemoveCode,
-    ExpireCode = c::sfExpireCode,
-    CreateCode = c::sfCreateCode,
-    MemoType = c::sfMemoType,
-    MemoData = c::sfMemoData,
-    MemoFormat = c::sfMemoFormat,
-    Fulfillment = c::sfFulfillment,
-    Condition = c::sfCondition,
-    MasterSignature = c::sfMasterSignature,
-    UNLModifyValidator = c::sfUNLModifyValidator,
-    ValidatorToDisable = c::sfValidatorToDisable,
-    ValidatorToReEnable = c::sfValidatorToReEnable,
-    HookStateData = c::sfHookStateData,
-    HookReturnString = c::sfHookReturnString,
-    HookParameterName = c::sfHookParameterName,
-    HookParameterValue = c::sfHookParameterValue,
-    Blob = c::sfBlob,
-    Account = c::sfAccount,
-    Owner = c::sfOwner,
-    Destination = c::sfDestination,
-    Issuer = c::sfIssuer,
-    Authorize = c::sfAuthorize,
-    Unauthorize = c::sfUnauthorize,
-    RegularKey = c::sfRegularKey,
-    NFTokenMinter = c::sfNFTokenMinter,
-    EmitCallback = c::sfEmitCallback,
-    HookAccount = c::sfHookAccount,
-    Indexes = c::sfIndexes,
-    Hashes = c::sfHashes,
-    Amendments = c::sfAmendments,
-    NFTokenOffers = c::sfNFTokenOffers,
-    HookNamespaces = c::sfHookNamespaces,
-    Paths = c::sfPaths,
-    TransactionMetaData = c::sfTransactionMetaData,
-    CreatedNode = c::sfCreatedNode,
-    DeletedNode = c::sfDeletedNode,
-    ModifiedNode = c::sfModifiedNode,
-    PreviousFields = c::sfPreviousFields,
-    FinalFields = c::sfFinalFields,
-    NewFields = c::sfNewFields,
-    TemplateEntry = c::sfTemplateEntry,
-    Memo = c::sfMemo,
-    SignerEntry = c::sfSignerEntry,
-    NFToken = c::sfNFToken,
-    EmitDetails = c::sfEmitDetails,
-    Hook = c::sfHook,
-    Signer = c::sfSigner,
-    Majority = c::sfMajority,
-    DisabledValidator = c::sfDisabledValidator,
-    EmittedTxn = c::sfEmittedTxn,
-    HookExecution = c::sfHookExecution,
-    HookDefinition = c::sfHookDefinition,
-    HookParameter = c::sfHookParameter,
-    HookGrant = c::sfHookGrant,
-    Signers = c::sfSigners,
-    SignerEntries = c::sfSignerEntries,
-    Template = c::sfTemplate,
-    Necessary = c::sfNecessary,
-    Sufficient = c::sfSufficient,
-    AffectedNodes = c::sfAffectedNodes,
-    Memos = c::sfMemos,
-    NFTokens = c::sfNFTokens,
-    Hooks = c::sfHooks,
-    Majorities = c::sfMajorities,
-    DisabledValidators = c::sfDisabledValidators,
-    HookExecutions = c::sfHookExecutions,
-    HookParameters = c::sfHookParameters,
-    HookGrants = c::sfHookGrants,
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub enum SerializedType {
+    UInt16 = 1,
+    UInt32 = 2,
+    UInt64 = 3,
+    Hash128 = 4,
+    Hash256 = 5,
+    Amount = 6,
+    Blob = 7,
+    Account = 8,
+    Object = 14,
+    Array = 15,
+    UInt8 = 16,
+    Hash160 = 17,
+    PathSet = 18,
+    Vector256 = 19,
+}
+
+impl SerializedType {
+    /// The serialized type with the given type code
+    pub const fn from_code(type_code: u32) -> Option<Self> {
+        Some(match type_code {
+            1 => SerializedType::UInt16,
+            2 => SerializedType::UInt32,
+            3 => SerializedType::UInt64,
+            4 => SerializedType::Hash128,
+            5 => SerializedType::Hash256,
+            6 => SerializedType::Amount,
+            7 => SerializedType::Blob,
+            8 => SerializedType::Account,
+            14 => SerializedType::Object,
+            15 => SerializedType::Array,
+            16 => SerializedType::UInt8,
+            17 => SerializedType::Hash160,
+            18 => SerializedType::PathSet,
+            19 => SerializedType::Vector256,
+            _ => return None,
+        })
+    }
+
+    /// Byte length of values of the type, `None` for types of variable length
+    pub const fn fixed_len(self) -> Option<usize> {
+        match self {
+            SerializedType::UInt8 => Some(1),
+            SerializedType::UInt16 => Some(2),
+            SerializedType::UInt32 => Some(4),
+            SerializedType::UInt64 => Some(8),
+            SerializedType::Hash128 => Some(16),
+            SerializedType::Hash160 => Some(20),
+            SerializedType::Hash256 => Some(32),
+            _ => None,
+        }
+    }
 }
 
 /// Data representation
 #[derive(Clone, Copy)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
 pub enum DataRepr {
     /// As UTF-8
     AsUTF8 = 0,
@@ -425,11 +784,12 @@ pub enum DataRepr {
 /// This is simple version of Result type
 /// to comply XRPL Hooks Webassembly restrictions
 #[must_use]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
 pub enum Result<T> {
     /// Contains the success value
     Ok(T),
     /// Contains the error value
-    Err(Error),
+    Err(HookError),
 }
 
 pub use self::Result::*;
@@ -508,12 +868,164 @@ impl<T> Result<T> {
     }
 }
 
+/// Terminates the hook on errors.
+///
+/// # Example
+/// ```
+/// let otxn_account = otxn_field::<ACC_ID_LEN>(FieldId::Account)
+///     .or_rollback(b"could not get otxn account");
+/// let amount = otxn_param::<8>(b"amount").or_accept(b"no amount given, nothing to do");
+/// ```
+pub trait ResultExt<T> {
+    /// Returns the contained [`Ok`] value, or rollbacks with `msg` and the error code.
+    fn or_rollback(self, msg: &[u8]) -> T;
+
+    /// Returns the contained [`Ok`] value, or accepts with `msg` and the error code.
+    fn or_accept(self, msg: &[u8]) -> T;
+
+    /// Attaches a short description of what failed to the error, see [ErrorCtx].
+    fn context(self, context: &'static [u8]) -> CtxResult<T>;
+}
+
+impl<T> ResultExt<T> for Result<T> {
+    #[inline(always)]
+    fn or_rollback(self, msg: &[u8]) -> T {
+        match self {
+            Ok(val) => val,
+            Err(err) => rollback(msg, err.into()),
+        }
+    }
+
+    #[inline(always)]
+    fn or_accept(self, msg: &[u8]) -> T {
+        match self {
+            Ok(val) => val,
+            Err(err) => accept(msg, err.into()),
+        }
+    }
+
+    #[inline(always)]
+    fn context(self, context: &'static [u8]) -> CtxResult<T> {
+        match self {
+            Ok(val) => core::result::Result::Ok(val),
+            Err(error) => core::result::Result::Err(ErrorCtx { context, error }),
+        }
+    }
+}
+
+/// A [HookError] together with a short static description of what failed,
+/// such as `b"state read"` or `b"emit"`.
+///
+/// Helpers can return a [CtxResult] so that the rollback message of a failed hook says
+/// which part of it failed, while the rollback code is still the error code.
+///
+/// # Example
+/// ```
+/// fn pay_out(reservation: &mut EmitReservation) -> CtxResult<[u8; HASH_LEN]> {
+///     let count = state::<8>(b"count").context(b"state read")?;
+///     let otxn_account = otxn_field::<ACC_ID_LEN>(FieldId::Account).context(b"otxn account")?;
+///
+///     XrpPaymentBuilder::new(Drops(u64::from_be_bytes(count)), &otxn_account, None, None)
+///         .build_and_emit(reservation)
+///         .context(b"emit")
+/// }
+///
+/// let txn_hash = pay_out(&mut reservation).or_rollback();
+/// ```
+#[derive(Clone, Copy)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct ErrorCtx {
+    /// What failed
+    pub context: &'static [u8],
+    /// The error it failed with
+    pub error: HookError,
+}
+
+impl ErrorCtx {
+    /// Rollbacks with the context as the message and the error code
+    #[inline(always)]
+    pub fn rollback(self) -> ! {
+        rollback(self.context, self.error.into())
+    }
+}
+
+impl From<ErrorCtx> for HookError {
+    #[inline(always)]
+    fn from(err: ErrorCtx) -> Self {
+        err.error
+    }
+}
+
+/// The context as text, followed by the error
+#[cfg(any(test, feature = "std"))]
+impl core::fmt::Display for ErrorCtx {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for chunk in self.context.utf8_chunks() {
+            f.write_str(chunk.valid())?;
+            if !chunk.invalid().is_empty() {
+                f.write_str("\u{FFFD}")?;
+            }
+        }
+
+        write!(f, ": {}", self.error)
+    }
+}
+
+/// Result of helpers that attach an [ErrorCtx] to their errors
+pub type CtxResult<T> = core::result::Result<T, ErrorCtx>;
+
+/// Terminates the hook on errors with context.
+pub trait CtxResultExt<T> {
+    /// Returns the contained value, or rollbacks with the context as the
+    /// message and the error code.
+    fn or_rollback(self) -> T;
+}
+
+impl<T> CtxResultExt<T> for CtxResult<T> {
+    #[inline(always)]
+    fn or_rollback(self) -> T {
+        match self {
+            core::result::Result::Ok(val) => val,
+            core::result::Result::Err(err) => err.rollback(),
+        }
+    }
+}
+
+/// Allows using `?` on [Result] in functions returning [Result]
+impl<T> core::ops::Try for Result<T> {
+    type Output = T;
+    type Residual = Result<core::convert::Infallible>;
+
+    #[inline(always)]
+    fn from_output(output: Self::Output) -> Self {
+        Ok(output)
+    }
+
+    #[inline(always)]
+    fn branch(self) -> core::ops::ControlFlow<Self::Residual, Self::Output> {
+        match self {
+            Ok(val) => core::ops::ControlFlow::Continue(val),
+            Err(err) => core::ops::ControlFlow::Break(Err(err)),
+        }
+    }
+}
+
+impl<T> core::ops::FromResidual<Result<core::convert::Infallible>> for Result<T> {
+    #[inline(always)]
+    fn from_residual(residual: Result<core::convert::Infallible>) -> Self {
+        match residual {
+            Err(err) => Err(err),
+        }
+    }
+}
+
 /// Possible errors returned by Hook APIs.
 ///
 /// Errors are global across all Hook APIs.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 #[repr(i32)]
-pub enum Error {
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub enum HookError {
     /// A pointer or buffer length provided as a parameter described memory outside of the Hook's allowed memory region.
     OutOfBounds = c::OUT_OF_BOUNDS,
     /// Reserved for internal invariant trips, generally unrelated to inputs.
@@ -590,21 +1102,119 @@ pub enum Error {
     NotAnAmount = -32,
     /// An API would have returned a negative integer except that negative integers are reserved for error codes (i.e. what you are reading.)
     CantReturnNegative = -33,
+    /// Hook attempted to set foreign state, or perform some other action on another account,
+    /// without the required authorization.
+    NotAuthorized = c::NOT_AUTHORIZED,
+    /// A previous failure prevents the operation from being retried.
+    PreviousFailurePreventsRetry = c::PREVIOUS_FAILURE_PREVENTS_RETRY,
+    /// Too many hook parameters were provided.
+    TooManyParams = c::TOO_MANY_PARAMS,
+    /// The serialized transaction provided to an API was not valid.
+    InvalidTxn = c::INVALID_TXN,
+    /// The hook account does not hold enough reserve for the state it tried to create.
+    ReserveInsufficient = c::RESERVE_INSUFFICIENT,
+    /// The API does not support the requested operation on this kind of value.
+    ComplexNotSupported = c::COMPLEX_NOT_SUPPORTED,
+    /// Two values that were required to match did not.
+    DoesNotMatch = c::DOES_NOT_MATCH,
+    /// An error code that is not known to this version of the library.
+    /// The original code is preserved.
+    Unknown(i32) = 0,
 }
 
-impl Error {
-    #[inline(always)]
+/// Alias of [HookError], which is what the error type was called before.
+pub type Error = HookError;
+
+impl HookError {
+    // Host functions only fail on error paths, so the conversion is kept out of line: a match
+    // over every code compiles to a jump table, which would otherwise be inlined into
+    // every wrapper of a host function
+    #[cold]
+    #[inline(never)]
     fn from_code(code: i32) -> Self {
-        unsafe { core::mem::transmute(code) }
+        match code {
+            c::OUT_OF_BOUNDS => HookError::OutOfBounds,
+            c::INTERNAL_ERROR => HookError::InternalError,
+            c::TOO_BIG => HookError::TooBig,
+            c::TOO_SMALL => HookError::TooSmall,
+            c::DOESNT_EXIST => HookError::DoesntExist,
+            c::NO_FREE_SLOTS => HookError::NoFreeSlots,
+            c::INVALID_ARGUMENT => HookError::InvalidArgument,
+            c::ALREADY_SET => HookError::AlreadySet,
+            c::PREREQUISITE_NOT_MET => HookError::PrerequisiteNotMet,
+            c::FEE_TOO_LARGE => HookError::FeeTooLarge,
+            c::EMISSION_FAILURE => HookError::EmissionFailure,
+            c::TOO_MANY_NONCES => HookError::TooManyNonces,
+            c::TOO_MANY_EMITTED_TXN => HookError::TooManyEmittedTxn,
+            c::NOT_IMPLEMENTED => HookError::NotImplemented,
+            c::INVALID_ACCOUNT => HookError::InvalidAccount,
+            c::GUARD_VIOLATION => HookError::GuardViolation,
+            c::INVALID_FIELD => HookError::InvalidField,
+            c::PARSE_ERROR => HookError::ParseError,
+            c::RC_ROLLBACK => HookError::RcRollback,
+            c::RC_ACCEPT => HookError::RcAccept,
+            c::NO_SUCH_KEYLET => HookError::NoSuchKeylet,
+            -22 => HookError::NotAnArray,
+            -23 => HookError::NotAnObject,
+            c::INVALID_FLOAT => HookError::InvalidFloat,
+            -25 => HookError::DivisionByZero,
+            -26 => HookError::ManitssaOversized,
+            -27 => HookError::MantissaUndersized,
+            -28 => HookError::ExponentOversized,
+            -29 => HookError::ExponentUndersized,
+            -30 => HookError::Overflow,
+            -31 => HookError::NotIouAmount,
+            -32 => HookError::NotAnAmount,
+            -33 => HookError::CantReturnNegative,
+            c::NOT_AUTHORIZED => HookError::NotAuthorized,
+            c::PREVIOUS_FAILURE_PREVENTS_RETRY => HookError::PreviousFailurePreventsRetry,
+            c::TOO_MANY_PARAMS => HookError::TooManyParams,
+            c::INVALID_TXN => HookError::InvalidTxn,
+            c::RESERVE_INSUFFICIENT => HookError::ReserveInsufficient,
+            c::COMPLEX_NOT_SUPPORTED => HookError::ComplexNotSupported,
+            c::DOES_NOT_MATCH => HookError::DoesNotMatch,
+            code => HookError::Unknown(code),
+        }
     }
 
-    /// Error code
+    /// The code the host returned for the error, such as `-5` for [HookError::DoesntExist]
+    /// or the original code of [HookError::Unknown]
     #[inline(always)]
     pub fn code(self) -> i32 {
-        self as _
+        match self {
+            HookError::Unknown(code) => code,
+            // SAFETY: `HookError` is `repr(i32)`, so its discriminant is stored as
+            // an `i32` at the start of the value
+            err => unsafe { *(&err as *const Self as *const i32) },
+        }
+    }
+}
+
+/// The name of the error followed by its code, like `TooSmall (-4)`
+#[cfg(any(test, feature = "std"))]
+impl core::fmt::Display for HookError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            HookError::Unknown(code) => write!(f, "Unknown ({code})"),
+            err => write!(f, "{err:?} ({})", err.code()),
+        }
     }
 }
 
+/// Converts a pointer to the 32 bit integer the host functions in [c](crate::c) take.
+///
+/// On wasm32 this is just a cast. When the mock host runs natively on a 64 bit target,
+/// such as with the `std` feature, the full pointer is also handed to the mock so it
+/// can find the memory again. Use it instead of `as u32` when calling the host directly.
+#[inline(always)]
+pub fn host_ptr<T>(ptr: *const T) -> u32 {
+    #[cfg(all(any(test, feature = "mock"), target_pointer_width = "64"))]
+    return crate::mock::register_ptr(ptr as usize);
+
+    #[cfg(not(all(any(test, feature = "mock"), target_pointer_width = "64")))]
+    return ptr as u32;
+}
+
 type Api1ArgsU32 = unsafe extern "C" fn(u32) -> i64;
 type Api2ArgsU32 = unsafe extern "C" fn(u32, u32) -> i64;
 type Api3ArgsU32 = unsafe extern "C" fn(u32, u32, u32) -> i64;
@@ -628,14 +1238,20 @@ fn api_3arg_call(arg_1: u32, arg_2: u32, arg_3: u32, fun: Api3ArgsU32) -> Result
 
 #[inline(always)]
 fn buf_write_1arg(buf_write: &mut [u8], arg: u32, fun: BufWriter1Arg) -> Result<u64> {
-    let res = unsafe { fun(buf_write.as_mut_ptr() as u32, buf_write.len() as u32, arg) };
+    let res = unsafe {
+        fun(
+            host_ptr(buf_write.as_mut_ptr()),
+            buf_write.len() as u32,
+            arg,
+        )
+    };
 
     res.into()
 }
 
 #[inline(always)]
 fn buf_read(buf: &[u8], fun: BufReader) -> Result<u64> {
-    let res = unsafe { fun(buf.as_ptr() as u32, buf.len() as u32) };
+    let res = unsafe { fun(host_ptr(buf.as_ptr()), buf.len() as u32) };
 
     res.into()
 }
@@ -656,15 +1272,22 @@ impl From<i64> for Result<u64> {
     fn from(res: i64) -> Self {
         match res {
             res if res >= 0 => Ok(res as _),
-            _ => Err(Error::from_code(res as _)),
+            _ => Err(HookError::from_code(res as _)),
         }
     }
 }
 
-impl From<Error> for i64 {
+impl From<i64> for HookError {
+    #[inline(always)]
+    fn from(code: i64) -> Self {
+        HookError::from_code(code as _)
+    }
+}
+
+impl From<HookError> for i64 {
     #[inline(always)]
-    fn from(err: Error) -> Self {
-        err as _
+    fn from(err: HookError) -> Self {
+        err.code() as _
     }
 }
 
@@ -688,3 +1311,202 @@ impl From<TxnType> for u8 {
         transaction_type as _
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+
+    #[wasm_bindgen_test]
+    fn can_convert_hook_error_from_and_to_code() {
+        assert!(HookError::from(-5i64) == HookError::DoesntExist);
+        assert!(HookError::DoesntExist.code() == -5);
+        assert!(HookError::from(-10024i64) == HookError::InvalidFloat);
+        assert!(HookError::InvalidFloat.code() == -10024);
+        assert!(HookError::from(-40i64) == HookError::DoesNotMatch);
+        assert!(i64::from(HookError::DoesNotMatch) == -40);
+    }
+
+    #[wasm_bindgen_test]
+    fn question_mark_propagates_errors() {
+        fn add_one(res: Result<u64>) -> Result<u64> {
+            let val = res?;
+
+            Ok(val + 1)
+        }
+
+        assert!(matches!(add_one(Ok(1)), Ok(2)));
+        assert!(matches!(
+            add_one(Err(HookError::TooSmall)),
+            Err(HookError::TooSmall)
+        ));
+    }
+
+    #[wasm_bindgen_test]
+    fn converts_account_ids() {
+        const GENESIS: Option<AccountId> =
+            AccountId::from_raddress(b"rHb9CJAWyB4rj91VRWn96DkukG4bwdtyTh");
+
+        assert_eq!(GENESIS, Some(crate::fixtures::GENESIS_ACCOUNT));
+        assert!(AccountId::from_raddress(b"rHb9CJAWyB4rj91VRWn96DkukG4bwdtyTi").is_none());
+        assert!(AccountId::ZERO.is_zero() && !crate::fixtures::GENESIS_ACCOUNT.is_zero());
+        assert_eq!(
+            AccountId::from_bytes_ref(&[1; ACC_ID_LEN]),
+            &AccountId::from([1; ACC_ID_LEN])
+        );
+        assert_eq!(
+            <[u8; ACC_ID_LEN]>::from(AccountId([2; ACC_ID_LEN])),
+            [2; ACC_ID_LEN]
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn tells_public_keys_apart() {
+        let secp256k1 = PublicKey(crate::hex!(
+            "03EE83BB432547885C219634A1BC407A9DB0474145D69737D09CCDC63E1DEE7FE3"
+        ));
+        let mut ed25519 = PublicKey::NULL;
+        ed25519.0[0] = 0xED;
+
+        assert!(!secp256k1.is_ed25519() && !secp256k1.is_null());
+        assert!(ed25519.is_ed25519() && !ed25519.is_null());
+        assert!(PublicKey::NULL.is_null());
+        assert_ne!(secp256k1, ed25519);
+        assert_eq!(PublicKey::from(*secp256k1.as_bytes()), secp256k1);
+    }
+
+    #[wasm_bindgen_test]
+    fn does_checked_arithmetic_on_drops() {
+        assert_eq!(Drops::from_xrp(2), Some(Drops(2_000_000)));
+        assert_eq!(Drops::from_xrp(100_000_000_000), Some(Drops::MAX));
+        assert_eq!(Drops::from_xrp(100_000_000_001), None);
+        assert_eq!(Drops::from_xrp(u64::MAX), None);
+        assert_eq!(Drops(1_500_000).to_xrp(), (1, 500_000));
+
+        assert_eq!(Drops(10).checked_add(Drops(5)), Some(Drops(15)));
+        assert_eq!(Drops::MAX.checked_add(Drops(1)), None);
+        assert_eq!(Drops(10).checked_sub(Drops(10)), Some(Drops::ZERO));
+        assert_eq!(Drops(10).checked_sub(Drops(11)), None);
+        assert_eq!(Drops(10).checked_mul(3), Some(Drops(30)));
+        assert_eq!(Drops(u64::MAX / 2).checked_mul(2), None);
+        assert_eq!(Drops(10).checked_div(3), Some(Drops(3)));
+        assert_eq!(Drops(10).checked_div(0), None);
+        assert_eq!(Drops(10_000).share(250), Some(Drops(250)));
+        assert_eq!(Drops(999).share(5000), Some(Drops(499)));
+        assert_eq!(Drops(u64::MAX).share(BASIS_POINTS), Some(Drops(u64::MAX)));
+        assert_eq!(Drops(10).share(BASIS_POINTS + 1), None);
+    }
+
+    #[wasm_bindgen_test]
+    fn names_transaction_types_like_rippled() {
+        for txn_type in TxnType::ALL {
+            assert_eq!(
+                TxnType::from_name(txn_type.name()).map(|t| t as u32),
+                Some(txn_type as u32)
+            );
+            assert_eq!(
+                TxnType::from_code(txn_type as u16).map(TxnType::name),
+                Some(txn_type.name())
+            );
+        }
+        assert_eq!(TxnType::HookSet.name(), "SetHook");
+        assert_eq!(TxnType::Invoke as u16, 99);
+        assert!(TxnType::from_name("NFTokenMint").is_none());
+        assert!(TxnType::from_code(6).is_none());
+    }
+
+    #[wasm_bindgen_test]
+    fn defines_fields_like_sfcodes() {
+        assert_eq!(FieldId::ALL.len(), 212);
+        for &field in FieldId::ALL {
+            assert_eq!(
+                FieldId::from_name(field.name()).map(|f| f as u32),
+                Some(field as u32)
+            );
+            assert_eq!(
+                FieldId::from_code(field as u32).map(FieldId::name),
+                Some(field.name())
+            );
+            // sfcodes.h numbers the currencies and issuers of TakerPays and TakerGets with
+            // the type 10, which rippled doesn't have
+            assert!(
+                field.serialized_type().is_some() || field as u32 >> 16 == 10,
+                "{field:?}"
+            );
+        }
+
+        assert_eq!(FieldId::Destination as u32, (8 << 16) + 3);
+        assert_eq!(
+            FieldId::Destination.serialized_type(),
+            Some(SerializedType::Account)
+        );
+        assert_eq!(FieldId::Destination.field_code(), 3);
+        assert_eq!(
+            FieldId::Amount.serialized_type(),
+            Some(SerializedType::Amount)
+        );
+        assert_eq!(
+            FieldId::HookOn.serialized_type(),
+            Some(SerializedType::Hash256)
+        );
+        assert_eq!(
+            FieldId::Memos.serialized_type(),
+            Some(SerializedType::Array)
+        );
+        assert_eq!(SerializedType::Hash256.fixed_len(), Some(32));
+        assert_eq!(SerializedType::Blob.fixed_len(), None);
+        assert!(FieldId::from_name("Destinations").is_none());
+        assert!(FieldId::from_code(0).is_none());
+    }
+
+    #[wasm_bindgen_test]
+    fn converts_every_hook_error_code() {
+        for code in (-45..=0).chain([c::INVALID_FLOAT]) {
+            assert!(HookError::from_code(code).code() == code);
+        }
+        assert!(HookError::from_code(-1) == HookError::OutOfBounds);
+        assert!(HookError::from_code(-23) == HookError::NotAnObject);
+        assert!(HookError::from_code(-25) == HookError::DivisionByZero);
+        assert!(HookError::from_code(-24) == HookError::Unknown(-24));
+        assert!(HookError::from_code(-41) == HookError::Unknown(-41));
+        assert!(HookError::from_code(0) == HookError::Unknown(0));
+    }
+
+    #[wasm_bindgen_test]
+    fn preserves_unknown_hook_error_codes() {
+        assert!(HookError::from(-41i64) == HookError::Unknown(-41));
+        assert!(HookError::Unknown(-41).code() == -41);
+        assert!(Result::<u64>::from(-41i64).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn formats_errors_and_floats() {
+        extern crate std;
+        use std::format;
+
+        assert_eq!(format!("{}", HookError::TooSmall), "TooSmall (-4)");
+        assert_eq!(format!("{}", HookError::Unknown(-41)), "Unknown (-41)");
+        let err = ErrorCtx {
+            context: b"state read",
+            error: HookError::DoesntExist,
+        };
+        assert_eq!(format!("{err}"), "state read: DoesntExist (-5)");
+        assert_eq!(
+            format!("{:?}", Result::<u64>::Err(HookError::DoesntExist)),
+            "Err(DoesntExist)"
+        );
+
+        assert_eq!(format!("{}", XFL::one()), "1");
+        assert_eq!(format!("{}", XFL(0)), "0");
+        // -1.25, 1250 and 0.000125
+        assert_eq!(format!("{}", XFL(1478430677777522688)), "-1.25");
+        assert_eq!(format!("{}", XFL(6144159891733356544)), "1250");
+        assert_eq!(format!("{}", XFL(6018059102166982656)), "0.000125");
+
+        assert_eq!(format!("{}", Drops(100_000_000)), "100 XRP");
+        assert_eq!(format!("{}", Drops(1_500_000)), "1.5 XRP");
+        assert_eq!(format!("{}", Drops(12)), "0.000012 XRP");
+        assert_eq!(format!("{}", Drops::ZERO), "0 XRP");
+    }
+}
@@ -6,6 +6,7 @@ use super::*;
 
 /// Flags for the originating transaction ID
 #[derive(Copy, Clone)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
 pub enum OtxnIdFlag {
     /// If 0: Write the canonical hash of the originating transaction.
     Zero = 0,
@@ -26,7 +27,7 @@ pub fn otxn_burden() -> i64 {
 pub fn otxn_field<const BUFFER_LEN: usize>(field_id: FieldId) -> Result<[u8; BUFFER_LEN]> {
     let func = |buffer_mut_ptr: *mut MaybeUninit<u8>| {
         let result: Result<u64> = unsafe {
-            c::otxn_field(buffer_mut_ptr as u32, BUFFER_LEN as u32, field_id as u32).into()
+            c::otxn_field(host_ptr(buffer_mut_ptr), BUFFER_LEN as u32, field_id as u32).into()
         };
 
         result
@@ -35,6 +36,72 @@ pub fn otxn_field<const BUFFER_LEN: usize>(field_id: FieldId) -> Result<[u8; BUF
     init_buffer_mut(func)
 }
 
+/// Get the amount of the originating transaction in drops.
+///
+/// Returns [HookError::InvalidArgument] if the amount is not in XRP, and the error of
+/// [otxn_field] if the transaction has no amount.
+#[inline(always)]
+pub fn otxn_drops() -> Result<u64> {
+    let amount = match otxn_field::<8>(FieldId::Amount) {
+        Ok(amount) => u64::from_be_bytes(amount),
+        // issued amounts are 48 bytes long
+        Err(HookError::TooSmall) => return Err(HookError::InvalidArgument),
+        Err(err) => return Err(err),
+    };
+
+    // the highest bit is set for issued amounts, the second highest marks
+    // positive amounts in XRP
+    if amount & 0x8000_0000_0000_0000 != 0 {
+        return Err(HookError::InvalidArgument);
+    }
+
+    Ok(amount & 0x3FFF_FFFF_FFFF_FFFF)
+}
+
+/// Get the `Flags` of the originating transaction, 0 if it has none, to be read as one
+/// of the flag sets of [flags](crate::flags)
+///
+/// # Example
+/// ```
+/// let flags = TrustSetFlags::from_bits(otxn_flags()?);
+/// ```
+#[inline(always)]
+pub fn otxn_flags() -> Result<u32> {
+    match otxn_field::<4>(FieldId::Flags) {
+        Ok(flags) => Ok(u32::from_be_bytes(flags)),
+        Err(HookError::DoesntExist) => Ok(0),
+        Err(err) => Err(err),
+    }
+}
+
+/// Get the destination tag of the originating transaction, `None` if it has none
+///
+/// # Example
+/// ```
+/// match otxn_dest_tag()? {
+///     Some(DestTag(customer)) => credit(customer),
+///     None => rollback(b"a destination tag is required", 1),
+/// }
+/// ```
+#[inline(always)]
+pub fn otxn_dest_tag() -> Result<Option<DestTag>> {
+    match otxn_field::<4>(FieldId::DestinationTag) {
+        Ok(tag) => Ok(Some(DestTag(u32::from_be_bytes(tag)))),
+        Err(HookError::DoesntExist) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Get the source tag of the originating transaction, `None` if it has none
+#[inline(always)]
+pub fn otxn_src_tag() -> Result<Option<SrcTag>> {
+    match otxn_field::<4>(FieldId::SourceTag) {
+        Ok(tag) => Ok(Some(SrcTag(u32::from_be_bytes(tag)))),
+        Err(HookError::DoesntExist) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
 /// Output a field from the originating transaction as a human readable string
 #[inline(always)]
 pub fn otxn_field_txt(acctxt: &mut [u8], field_id: FieldId) -> Result<u64> {
@@ -52,7 +119,7 @@ pub fn otxn_generation() -> i64 {
 pub fn otxn_id(flags: OtxnIdFlag) -> Result<[u8; 32]> {
     let func = |buffer_mut_ptr: *mut MaybeUninit<u8>| {
         let result: Result<u64> =
-            unsafe { c::otxn_id(buffer_mut_ptr as u32, 32, flags.into()).into() };
+            unsafe { c::otxn_id(host_ptr(buffer_mut_ptr), 32, flags.into()).into() };
 
         result
     };
@@ -72,15 +139,65 @@ pub fn otxn_slot(slot_no: u32) -> Result<u64> {
     api_1arg_call(slot_no, c::otxn_slot)
 }
 
+/// Get the hash of the transaction that caused the originating transaction to be emitted,
+/// which is the `EmitParentTxnID` in its emit details.
+///
+/// This is useful in `cbak` to find out which transaction a callback belongs to when
+/// chaining emitted transactions. Returns [HookError::DoesntExist] if the originating
+/// transaction was not emitted by a hook.
+#[inline(always)]
+pub fn otxn_emit_parent_txn_id() -> Result<[u8; HASH_LEN]> {
+    let otxn_slot_no = match otxn_slot(0) {
+        Ok(slot_no) => slot_no as u32,
+        Err(err) => return Err(err),
+    };
+    let emit_details_slot_no = match slot_subfield(otxn_slot_no, FieldId::EmitDetails, 0) {
+        Ok(slot_no) => slot_no as u32,
+        Err(err) => {
+            let _ = slot_clear(otxn_slot_no);
+            return Err(err);
+        }
+    };
+    let parent_txn_id_slot_no =
+        match slot_subfield(emit_details_slot_no, FieldId::EmitParentTxnID, 0) {
+            Ok(slot_no) => slot_no as u32,
+            Err(err) => {
+                let _ = slot_clear(emit_details_slot_no);
+                let _ = slot_clear(otxn_slot_no);
+                return Err(err);
+            }
+        };
+
+    let func = |buffer_mut_ptr: *mut MaybeUninit<u8>| {
+        let result: Result<u64> = unsafe {
+            c::slot(
+                host_ptr(buffer_mut_ptr),
+                HASH_LEN as u32,
+                parent_txn_id_slot_no,
+            )
+            .into()
+        };
+
+        result
+    };
+    let parent_txn_id = init_buffer_mut(func);
+
+    let _ = slot_clear(parent_txn_id_slot_no);
+    let _ = slot_clear(emit_details_slot_no);
+    let _ = slot_clear(otxn_slot_no);
+
+    parent_txn_id
+}
+
 /// Retrieve the parameter value for a named Invoke transaction parameter
 #[inline(always)]
 pub fn otxn_param<const PARAM_LEN: usize>(parameter_name: &[u8]) -> Result<[u8; PARAM_LEN]> {
     let func = |buffer_mut_ptr: *mut MaybeUninit<u8>| {
         let result: Result<u64> = unsafe {
             c::otxn_param(
-                buffer_mut_ptr as u32,
+                host_ptr(buffer_mut_ptr),
                 PARAM_LEN as u32,
-                parameter_name.as_ptr() as u32,
+                host_ptr(parameter_name.as_ptr()),
                 parameter_name.len() as u32,
             )
             .into()
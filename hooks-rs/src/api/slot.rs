@@ -30,7 +30,7 @@ pub fn slot_id(buf: &mut [u8], slot_no: u32) -> Result<u64> {
 /// Locate an object based on its keylet and place it into a slot
 #[inline(always)]
 pub fn slot_set(keylet: &[u8], slot_no: u32) -> Result<u64> {
-    let res = unsafe { c::slot_set(keylet.as_ptr() as u32, keylet.len() as u32, slot_no) };
+    let res = unsafe { c::slot_set(host_ptr(keylet.as_ptr()), keylet.len() as u32, slot_no) };
 
     res.into()
 }
@@ -64,7 +64,7 @@ pub fn slot_type(slot_no: u32, flags: SlotTypeFlags) -> Result<FieldOrXrpAmount>
                 res if res >= 0 => Ok(FieldOrXrpAmount::Field(unsafe {
                     core::mem::transmute::<u32, api::FieldId>(res as u32)
                 })),
-                _ => Err(Error::from_code(res as _)),
+                _ => Err(HookError::from_code(res as _)),
             }
         }
 
@@ -74,7 +74,7 @@ pub fn slot_type(slot_no: u32, flags: SlotTypeFlags) -> Result<FieldOrXrpAmount>
             match res {
                 1 => Ok(FieldOrXrpAmount::XrpAmount),
                 res if res >= 0 => Ok(FieldOrXrpAmount::NonXrpAmount),
-                _ => Err(Error::from_code(res as _)),
+                _ => Err(HookError::from_code(res as _)),
             }
         }
     }
@@ -4,9 +4,11 @@ use crate::c;
 
 use super::*;
 
-/// Retrieve the data pointed to by a Hook State key and write it to an output buffer
+/// Retrieve the data pointed to by a Hook State key and write it to an output buffer.
+///
 /// The keys are always 32 bytes (unsigned 256 bit integer) and the values are variable
-/// length with a maximum size determined by validator voting, at time of writing 128 bytes.
+/// length with a maximum size determined by validator voting, at time of writing 256 bytes
+/// on Xahau.
 ///
 /// # Example
 /// ```
@@ -37,9 +39,9 @@ where
     let func = |buffer_mut_ptr: *mut MaybeUninit<u8>| {
         let result: Result<u64> = unsafe {
             c::state(
-                buffer_mut_ptr as u32,
+                host_ptr(buffer_mut_ptr),
                 STATE_VALUE_LEN as u32,
-                key.as_ptr() as u32,
+                host_ptr(key.as_ptr()),
                 key.len() as u32,
             )
             .into()
@@ -51,6 +53,31 @@ where
     init_buffer_mut(func)
 }
 
+/// Retrieve the data of a Hook State key that must be exactly `STATE_VALUE_LEN` bytes long,
+/// such as a value a hook always stores with the same length.
+///
+/// Unlike [state], which leaves the rest of its buffer uninitialized when the value is
+/// shorter, fails with [HookError::DoesNotMatch] if the value is shorter, and like [state]
+/// if there is none or it is longer.
+#[inline(always)]
+pub fn state_exact<const STATE_VALUE_LEN: usize>(key: &[u8]) -> Result<[u8; STATE_VALUE_LEN]> {
+    let mut data = [0; STATE_VALUE_LEN];
+    let result: Result<u64> = unsafe {
+        c::state(
+            host_ptr(data.as_mut_ptr()),
+            STATE_VALUE_LEN as u32,
+            host_ptr(key.as_ptr()),
+            key.len() as u32,
+        )
+        .into()
+    };
+    if result? as usize != STATE_VALUE_LEN {
+        return Err(HookError::DoesNotMatch);
+    }
+
+    Ok(data)
+}
+
 /// Set the Hook State for a given key and value
 ///
 /// # Example
@@ -79,9 +106,9 @@ where
 pub fn state_set(data: &[u8], key: &[u8]) -> Result<u64> {
     unsafe {
         c::state_set(
-            data.as_ptr() as u32,
+            host_ptr(data.as_ptr()),
             data.len() as u32,
-            key.as_ptr() as u32,
+            host_ptr(key.as_ptr()),
             key.len() as u32,
         )
     }
@@ -93,13 +120,13 @@ pub fn state_set(data: &[u8], key: &[u8]) -> Result<u64> {
 pub fn state_foreign(data: &mut [u8], key: &[u8], namespace: &[u8], accid: &[u8]) -> Result<u64> {
     let res = unsafe {
         c::state_foreign(
-            data.as_mut_ptr() as u32,
+            host_ptr(data.as_mut_ptr()),
             data.len() as u32,
-            key.as_ptr() as u32,
+            host_ptr(key.as_ptr()),
             key.len() as u32,
-            namespace.as_ptr() as u32,
+            host_ptr(namespace.as_ptr()),
             namespace.len() as u32,
-            accid.as_ptr() as u32,
+            host_ptr(accid.as_ptr()),
             accid.len() as u32,
         )
     };
@@ -5,11 +5,11 @@ use super::*;
 /// Index into a xrpld serialized object and return the location and length of a subfield
 #[inline(always)]
 pub fn sto_subfield(sto: &[u8], field_id: FieldId) -> Result<&[u8]> {
-    let res = unsafe { c::sto_subfield(sto.as_ptr() as u32, sto.len() as u32, field_id as _) };
+    let res = unsafe { c::sto_subfield(host_ptr(sto.as_ptr()), sto.len() as u32, field_id as _) };
 
     let location = match res {
         res if res >= 0 => res,
-        res => return Err(Error::from_code(res as _)),
+        res => return Err(HookError::from_code(res as _)),
     };
 
     Ok(&sto[range_from_location(location)])
@@ -18,11 +18,11 @@ pub fn sto_subfield(sto: &[u8], field_id: FieldId) -> Result<&[u8]> {
 /// Index into a xrpld serialized array and return the location and length of an index
 #[inline(always)]
 pub fn sto_subarray(sto: &[u8], array_id: u32) -> Result<&[u8]> {
-    let res = unsafe { c::sto_subarray(sto.as_ptr() as u32, sto.len() as u32, array_id) };
+    let res = unsafe { c::sto_subarray(host_ptr(sto.as_ptr()), sto.len() as u32, array_id) };
 
     let location = match res {
         res if res >= 0 => res,
-        res => return Err(Error::from_code(res as _)),
+        res => return Err(HookError::from_code(res as _)),
     };
 
     Ok(&sto[range_from_location(location)])
@@ -38,11 +38,11 @@ pub fn sto_emplace(
 ) -> Result<u64> {
     let res = unsafe {
         c::sto_emplace(
-            sto_out.as_mut_ptr() as u32,
+            host_ptr(sto_out.as_mut_ptr()),
             sto_out.len() as u32,
-            sto_src.as_ptr() as u32,
+            host_ptr(sto_src.as_ptr()),
             sto_src.len() as u32,
-            field.as_ptr() as u32,
+            host_ptr(field.as_ptr()),
             field.len() as u32,
             field_id as _,
         )
@@ -56,9 +56,9 @@ pub fn sto_emplace(
 pub fn sto_erase(sto_out: &mut [u8], sto_src: &[u8], field_id: FieldId) -> Result<u64> {
     let res = unsafe {
         c::sto_erase(
-            sto_out.as_mut_ptr() as u32,
+            host_ptr(sto_out.as_mut_ptr()),
             sto_out.len() as u32,
-            sto_src.as_ptr() as u32,
+            host_ptr(sto_src.as_ptr()),
             sto_src.len() as u32,
             field_id as _,
         )
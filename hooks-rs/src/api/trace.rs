@@ -4,12 +4,20 @@
 //! `https://xahau-test.net/debugstream/{r-address}/` to see the debug stream.
 //!
 //! Your trace logs should appear in the debug stream.
+//!
+//! All trace functions are gated behind the `trace` feature, which is enabled by default.
+//! Without it, they return `Ok(0)` without calling the host, so production builds can
+//! depend on hooks-rs with `default-features = false` to drop all tracing from the wasm
+//! binary while the call sites stay unchanged.
 
+#[cfg(feature = "trace")]
 use core::mem::size_of_val;
 
+#[cfg(feature = "trace")]
 use crate::c;
 
 use super::*;
+use crate::utils::FixedWriter;
 
 /// Write the contents of a buffer to the XRPLD trace log
 ///
@@ -25,11 +33,17 @@ use super::*;
 /// ```
 #[inline(always)]
 pub fn trace(msg: &[u8], data: &[u8], data_repr: DataRepr) -> Result<u64> {
+    #[cfg(not(feature = "trace"))]
+    let res = {
+        let _ = (msg, data, data_repr);
+        0
+    };
+    #[cfg(feature = "trace")]
     let res = unsafe {
         c::trace(
-            msg.as_ptr() as u32,
+            host_ptr(msg.as_ptr()),
             size_of_val(msg) as u32,
-            data.as_ptr() as u32,
+            host_ptr(data.as_ptr()),
             size_of_val(data) as u32,
             data_repr as _,
         )
@@ -41,7 +55,13 @@ pub fn trace(msg: &[u8], data: &[u8], data_repr: DataRepr) -> Result<u64> {
 /// Write the contents of a slot to the XRPLD trace log
 #[inline(always)]
 pub fn trace_slot(msg: &[u8], slot: u32) -> Result<u64> {
-    let res = unsafe { c::trace_slot(msg.as_ptr() as u32, size_of_val(msg) as u32, slot) };
+    #[cfg(not(feature = "trace"))]
+    let res = {
+        let _ = (msg, slot);
+        0
+    };
+    #[cfg(feature = "trace")]
+    let res = unsafe { c::trace_slot(host_ptr(msg.as_ptr()), size_of_val(msg) as u32, slot) };
 
     res.into()
 }
@@ -54,7 +74,13 @@ pub fn trace_slot(msg: &[u8], slot: u32) -> Result<u64> {
 /// ```
 #[inline(always)]
 pub fn trace_num(msg: &[u8], number: i64) -> Result<u64> {
-    let res = unsafe { c::trace_num(msg.as_ptr() as u32, size_of_val(msg) as u32, number) };
+    #[cfg(not(feature = "trace"))]
+    let res = {
+        let _ = (msg, number);
+        0
+    };
+    #[cfg(feature = "trace")]
+    let res = unsafe { c::trace_num(host_ptr(msg.as_ptr()), size_of_val(msg) as u32, number) };
 
     res.into()
 }
@@ -63,11 +89,222 @@ pub fn trace_num(msg: &[u8], number: i64) -> Result<u64> {
 ///
 /// # Example
 /// ```
-/// let _ = trace_float(b"my float", XFL(42.0));
+/// // 42.5
+/// let float = XFL::new(-1, 425).unwrap_line_number();
+/// let _ = trace_float(b"my float", float);
 /// ```
 #[inline(always)]
 pub fn trace_float(msg: &[u8], float: XFL) -> Result<u64> {
-    let res = unsafe { c::trace_float(msg.as_ptr() as u32, size_of_val(msg) as u32, float.0) };
+    #[cfg(not(feature = "trace"))]
+    let res = {
+        let _ = (msg, float);
+        0
+    };
+    #[cfg(feature = "trace")]
+    let res = unsafe { c::trace_float(host_ptr(msg.as_ptr()), size_of_val(msg) as u32, float.0) };
 
     res.into()
 }
+
+// Whether trace! formats its messages, which it doesn't if they aren't traced or
+// formatting is left out with the `small` feature
+#[doc(hidden)]
+pub const TRACE_FORMATS: bool = cfg!(all(feature = "trace", not(feature = "small")));
+
+/// Maximum length of a message formatted with [trace!](crate::trace!).
+/// Longer messages are truncated.
+pub const TRACE_MSG_MAX_LEN: usize = 256;
+
+/// Format a message and write it to the XRPLD trace log.
+///
+/// Does nothing but evaluate the arguments if the `trace` feature is disabled or the
+/// `small` feature is enabled.
+///
+/// Each `{}` in the format string is replaced with the next argument. Arguments can be
+/// anything that implements [TraceArg]: integers and strings are written as is, and byte
+/// buffers such as account ids or hashes are written as uppercase hex. Other format
+/// specifiers like `{:?}` are not supported.
+///
+/// Unlike `core::fmt`, formatting does not allocate and only uses guarded loops,
+/// so it can be used anywhere in a hook. The message is truncated to [TRACE_MSG_MAX_LEN] bytes.
+///
+/// # Example
+/// ```
+/// let _ = trace!("received {} drops from {}", drops, otxn_account);
+/// ```
+#[macro_export]
+macro_rules! trace {
+    ($fmt:literal $(, $arg:expr)* $(,)?) => {{
+        if $crate::TRACE_FORMATS {
+            let mut writer = $crate::TraceWriter::<{ $crate::TRACE_MSG_MAX_LEN }>::new($fmt);
+            $(writer.arg(&$arg);)*
+            writer.finish()
+        } else {
+            $(let _ = &$arg;)*
+            $crate::Result::Ok(0)
+        }
+    }};
+}
+
+/// A value that can be formatted with [trace!](crate::trace!)
+pub trait TraceArg {
+    /// Write the value into `writer`
+    fn write_to<const N: usize>(&self, writer: &mut FixedWriter<N>);
+}
+
+/// Formats a [trace!](crate::trace!) message into a [FixedWriter]
+///
+/// Unlike [trace!](crate::trace!), it formats with any features, so it can also build
+/// messages that are not traced, like the return string of [accept](crate::accept). Only
+/// [TraceWriter::finish] depends on the `trace` feature to write to the trace log.
+pub struct TraceWriter<'a, const N: usize> {
+    fmt: &'a [u8],
+    fmt_pos: usize,
+    writer: FixedWriter<N>,
+}
+
+impl<'a, const N: usize> TraceWriter<'a, N> {
+    /// Creates a writer for a format string
+    #[inline(always)]
+    pub fn new(fmt: &'a str) -> Self {
+        Self {
+            fmt: fmt.as_bytes(),
+            fmt_pos: 0,
+            writer: FixedWriter::new(),
+        }
+    }
+
+    /// Writes the format string up to the next `{}`, followed by `arg`
+    #[inline(always)]
+    pub fn arg<T: TraceArg + ?Sized>(&mut self, arg: &T) {
+        self.write_fmt_until_placeholder();
+        arg.write_to(&mut self.writer);
+    }
+
+    /// Writes the rest of the format string and writes the message to the trace log,
+    /// returning `Ok(0)` without calling the host if the `trace` feature is disabled
+    #[inline(always)]
+    pub fn finish(mut self) -> Result<u64> {
+        self.write_fmt_until_placeholder();
+
+        trace(self.as_bytes(), b"", DataRepr::AsUTF8)
+    }
+
+    /// The message formatted so far
+    #[inline(always)]
+    pub fn as_bytes(&self) -> &[u8] {
+        self.writer.as_bytes()
+    }
+
+    #[inline(always)]
+    fn write_fmt_until_placeholder(&mut self) {
+        let start = self.fmt_pos;
+        let fmt_len = self.fmt.len();
+
+        // guarded loop
+        while {
+            max_iter(fmt_len as u32 + 1);
+            self.fmt_pos < fmt_len
+        } {
+            if self.fmt[self.fmt_pos] == b'{'
+                && self.fmt_pos + 1 < fmt_len
+                && self.fmt[self.fmt_pos + 1] == b'}'
+            {
+                let end = self.fmt_pos;
+                self.fmt_pos += 2;
+                self.writer.write_bytes(&self.fmt[start..end]);
+                return;
+            }
+            self.fmt_pos += 1;
+        }
+
+        self.writer.write_bytes(&self.fmt[start..]);
+    }
+}
+
+impl<T: TraceArg + ?Sized> TraceArg for &T {
+    #[inline(always)]
+    fn write_to<const N: usize>(&self, writer: &mut FixedWriter<N>) {
+        (**self).write_to(writer)
+    }
+}
+
+impl TraceArg for str {
+    #[inline(always)]
+    fn write_to<const N: usize>(&self, writer: &mut FixedWriter<N>) {
+        writer.write_bytes(self.as_bytes())
+    }
+}
+
+impl TraceArg for [u8] {
+    #[inline(always)]
+    fn write_to<const N: usize>(&self, writer: &mut FixedWriter<N>) {
+        writer.write_hex(self)
+    }
+}
+
+impl<const LEN: usize> TraceArg for [u8; LEN] {
+    #[inline(always)]
+    fn write_to<const N: usize>(&self, writer: &mut FixedWriter<N>) {
+        writer.write_hex(self)
+    }
+}
+
+impl TraceArg for bool {
+    #[inline(always)]
+    fn write_to<const N: usize>(&self, writer: &mut FixedWriter<N>) {
+        writer.write_bytes(if *self { b"true" } else { b"false" })
+    }
+}
+
+macro_rules! impl_trace_arg_for_unsigned {
+    ($($ty:ty),*) => {
+        $(impl TraceArg for $ty {
+            #[inline(always)]
+            fn write_to<const N: usize>(&self, writer: &mut FixedWriter<N>) {
+                writer.write_u64(*self as u64)
+            }
+        })*
+    };
+}
+
+macro_rules! impl_trace_arg_for_signed {
+    ($($ty:ty),*) => {
+        $(impl TraceArg for $ty {
+            #[inline(always)]
+            fn write_to<const N: usize>(&self, writer: &mut FixedWriter<N>) {
+                writer.write_i64(*self as i64)
+            }
+        })*
+    };
+}
+
+impl_trace_arg_for_unsigned!(u8, u16, u32, u64, usize);
+impl_trace_arg_for_signed!(i8, i16, i32, i64, isize);
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::mock;
+
+    #[wasm_bindgen_test]
+    fn formats_messages_with_a_trace_writer() {
+        let mut writer = TraceWriter::<TRACE_MSG_MAX_LEN>::new("{} {} from {}, {}");
+        writer.arg(&-42i64);
+        writer.arg("drops");
+        writer.arg(&[0xAB, 0x01]);
+        writer.arg(&true);
+        assert_eq!(writer.as_bytes(), b"-42 drops from AB01, true");
+
+        mock::reset();
+        let mut evaluated = 0;
+        let _ = crate::trace!("{} of {}", 1u8, {
+            evaluated += 1;
+            2u8
+        });
+        assert_eq!(evaluated, 1);
+        assert_eq!(mock::traces(), [b"1 of 2".to_vec()]);
+    }
+}
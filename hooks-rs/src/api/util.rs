@@ -9,7 +9,7 @@ use super::*;
 /// The resulting buffer might not be fully populated because size of an r-address varies.
 /// The rest of the buffer will be filled with zeroes.
 #[inline(always)]
-pub fn util_raddr(accid: &[u8; 20]) -> Result<[u8; 35]> {
+pub fn util_raddr(accid: &AccountId) -> Result<[u8; 35]> {
     let mut uninit_r_address_buffer: [MaybeUninit<u8>; 35] = MaybeUninit::uninit_array();
 
     let u64_uninit_r_address_buffer_ptr = uninit_r_address_buffer.as_mut_ptr() as *mut u64;
@@ -25,18 +25,13 @@ pub fn util_raddr(accid: &[u8; 20]) -> Result<[u8; 35]> {
         r_address_last_3_bytes_ptr.add(1).write(0);
         r_address_last_3_bytes_ptr.add(2).write(0);
     };
-    let mut r_address_buffer = unsafe {
-        uninit_r_address_buffer
-            .as_ptr()
-            .cast::<[u8; 35]>()
-            .read_volatile()
-    };
+    let mut r_address_buffer = unsafe { assume_init_array(&uninit_r_address_buffer) };
 
     let result: Result<u64> = unsafe {
         c::util_raddr(
-            r_address_buffer.as_mut_ptr() as u32,
+            host_ptr(r_address_buffer.as_mut_ptr()),
             35,
-            accid.as_ptr() as u32,
+            host_ptr(accid.0.as_ptr()),
             ACC_ID_LEN as u32,
         )
         .into()
@@ -52,13 +47,13 @@ pub fn util_raddr(accid: &[u8; 20]) -> Result<[u8; 35]> {
 ///
 /// R_ADDRESS_LEN must be >= 25 && <= 35.
 #[inline(always)]
-pub fn util_accid<const R_ADDRESS_LEN: usize>(raddr_in: &[u8; R_ADDRESS_LEN]) -> Result<[u8; 20]> {
+pub fn util_accid<const R_ADDRESS_LEN: usize>(raddr_in: &[u8; R_ADDRESS_LEN]) -> Result<AccountId> {
     let func = |buffer_mut_ptr: *mut MaybeUninit<u8>| {
         let result: Result<u64> = unsafe {
-            c::util_raddr(
-                buffer_mut_ptr as u32,
+            c::util_accid(
+                host_ptr(buffer_mut_ptr),
                 ACC_ID_LEN as u32,
-                raddr_in.as_ptr() as u32,
+                host_ptr(raddr_in.as_ptr()),
                 R_ADDRESS_LEN as u32,
             )
             .into()
@@ -67,22 +62,25 @@ pub fn util_accid<const R_ADDRESS_LEN: usize>(raddr_in: &[u8; R_ADDRESS_LEN]) ->
         result
     };
 
-    init_buffer_mut::<ACC_ID_LEN, _>(func)
+    match init_buffer_mut::<ACC_ID_LEN, _>(func) {
+        Ok(account_id) => Ok(AccountId(account_id)),
+        Err(err) => Err(err),
+    }
 }
 
 /// Verify a cryptographic signature
 ///
 /// If the public key is prefixed with 0xED then use ED25519. Otherwise assume SECP256k1.
 #[inline(always)]
-pub fn util_verify(payload: &[u8], signature: &[u8], publickey: &[u8]) -> Result<bool> {
+pub fn util_verify(payload: &[u8], signature: &[u8], publickey: &PublicKey) -> Result<bool> {
     let result: Result<u64> = unsafe {
         c::util_verify(
-            payload.as_ptr() as _,
+            host_ptr(payload.as_ptr()),
             payload.len() as _,
-            signature.as_ptr() as _,
+            host_ptr(signature.as_ptr()),
             signature.len() as _,
-            publickey.as_ptr() as _,
-            publickey.len() as _,
+            host_ptr(publickey.0.as_ptr()),
+            PUBLIC_KEY_LEN as _,
         )
         .into()
     };
@@ -95,15 +93,130 @@ pub fn util_verify(payload: &[u8], signature: &[u8], publickey: &[u8]) -> Result
     }
 }
 
+/// Compute a serialized keylet of a given type
+///
+/// # Example
+/// ```
+/// let account_keylet = util_keylet(KeyletType::Account(&hook_account)).unwrap_line_number();
+/// ```
+#[inline(always)]
+pub fn util_keylet(keylet_type: KeyletType) -> Result<Keylet> {
+    let (keylet_type, a, b, c, d, e, f) = match keylet_type {
+        KeyletType::Hook(accid) => (c::KEYLET_HOOK, ptr(accid), len(accid), 0, 0, 0, 0),
+        KeyletType::HookState(accid, key) => (
+            c::KEYLET_HOOK_STATE,
+            ptr(accid),
+            len(accid),
+            ptr(key),
+            len(key),
+            0,
+            0,
+        ),
+        KeyletType::Account(accid) => (c::KEYLET_ACCOUNT, ptr(accid), len(accid), 0, 0, 0, 0),
+        KeyletType::Amendments => (c::KEYLET_AMENDMENTS, 0, 0, 0, 0, 0, 0),
+        KeyletType::Child(key) => (c::KEYLET_CHILD, ptr(key), len(key), 0, 0, 0, 0),
+        KeyletType::Skip(None) => (c::KEYLET_SKIP, 0, 0, 0, 0, 0, 0),
+        KeyletType::Skip(Some((ledger_index, num))) => {
+            (c::KEYLET_SKIP, ledger_index, num, 0, 0, 0, 0)
+        }
+        KeyletType::Fees => (c::KEYLET_FEES, 0, 0, 0, 0, 0, 0),
+        KeyletType::NegativeUnl => (c::KEYLET_NEGATIVE_UNL, 0, 0, 0, 0, 0, 0),
+        KeyletType::Line(accid_high, accid_low, currency_code) => (
+            c::KEYLET_LINE,
+            ptr(accid_high),
+            len(accid_high),
+            ptr(accid_low),
+            len(accid_low),
+            ptr(currency_code),
+            len(currency_code),
+        ),
+        KeyletType::Offer(accid, num) => (c::KEYLET_OFFER, ptr(accid), len(accid), num, 0, 0, 0),
+        KeyletType::Quality(keylet, bits_high, bits_low) => (
+            c::KEYLET_QUALITY,
+            ptr(keylet),
+            len(keylet),
+            bits_high,
+            bits_low,
+            0,
+            0,
+        ),
+        KeyletType::EmittedDir => (c::KEYLET_EMITTED_DIR, 0, 0, 0, 0, 0, 0),
+        KeyletType::Signers(accid) => (c::KEYLET_SIGNERS, ptr(accid), len(accid), 0, 0, 0, 0),
+        KeyletType::Check(accid, num) => (c::KEYLET_CHECK, ptr(accid), len(accid), num, 0, 0, 0),
+        KeyletType::DepositPreauth(accid_1, accid_2) => (
+            c::KEYLET_DEPOSIT_PREAUTH,
+            ptr(accid_1),
+            len(accid_1),
+            ptr(accid_2),
+            len(accid_2),
+            0,
+            0,
+        ),
+        KeyletType::Unchecked(key) => (c::KEYLET_UNCHECKED, ptr(key), len(key), 0, 0, 0, 0),
+        KeyletType::OwnerDir(accid) => (c::KEYLET_OWNER_DIR, ptr(accid), len(accid), 0, 0, 0, 0),
+        KeyletType::Page(key, bits_high, bits_low) => (
+            c::KEYLET_PAGE,
+            ptr(key),
+            len(key),
+            bits_high,
+            bits_low,
+            0,
+            0,
+        ),
+        KeyletType::Escrow(accid, num) => (c::KEYLET_ESCROW, ptr(accid), len(accid), num, 0, 0, 0),
+        KeyletType::Paychan(accid_1, accid_2, num) => (
+            c::KEYLET_PAYCHAN,
+            ptr(accid_1),
+            len(accid_1),
+            ptr(accid_2),
+            len(accid_2),
+            num,
+            0,
+        ),
+        KeyletType::Emitted(key) => (c::KEYLET_EMITTED, ptr(key), len(key), 0, 0, 0, 0),
+    };
+
+    let func = |buffer_mut_ptr: *mut MaybeUninit<u8>| {
+        let result: Result<u64> = unsafe {
+            c::util_keylet(
+                host_ptr(buffer_mut_ptr),
+                KEYLET_LEN as u32,
+                keylet_type,
+                a,
+                b,
+                c,
+                d,
+                e,
+                f,
+            )
+            .into()
+        };
+
+        result
+    };
+
+    init_buffer_mut(func)
+}
+
+#[inline(always)]
+fn ptr(buf: &[u8]) -> u32 {
+    host_ptr(buf.as_ptr())
+}
+
+#[inline(always)]
+fn len(buf: &[u8]) -> u32 {
+    buf.len() as u32
+}
+
 /// Compute an sha512-half over some data
 #[inline(always)]
 pub fn util_sha512h(data_in: &[u8]) -> Result<[u8; HASH_LEN]> {
     let func = |buffer_mut_ptr: *mut MaybeUninit<u8>| {
         let result: Result<u64> = unsafe {
             c::util_sha512h(
-                buffer_mut_ptr as u32,
+                host_ptr(buffer_mut_ptr),
                 HASH_LEN as u32,
-                data_in.as_ptr() as u32,
+                host_ptr(data_in.as_ptr()),
                 data_in.len() as u32,
             )
             .into()
@@ -114,3 +227,23 @@ pub fn util_sha512h(data_in: &[u8]) -> Result<[u8; HASH_LEN]> {
 
     init_buffer_mut(func)
 }
+
+/// Compute an sha512-half over some data and return the 32 byte hash directly
+///
+/// This is a shorthand for [util_sha512h] for the common case of hashing
+/// arbitrary payloads, such as commitments, state keys or condition preimages.
+/// The output buffer is always large enough, so the host call can only fail if
+/// `data_in` points outside of the hook's memory.
+///
+/// # Rollbacks
+///
+/// Rollbacks with the error code if the host call fails.
+///
+/// # Example
+/// ```
+/// let state_key = sha512h(b"balance");
+/// ```
+#[inline(always)]
+pub fn sha512h(data_in: &[u8]) -> [u8; HASH_LEN] {
+    util_sha512h(data_in).unwrap()
+}
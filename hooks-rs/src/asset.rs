@@ -0,0 +1,207 @@
+//! Issued (non-XRP) assets, identified by a currency code and an issuer.
+//!
+//! Issuer and distributor hooks usually need to know whether a destination can
+//! actually hold a token before emitting a payment of it, which comes down to
+//! checking whether the trust line between the holder and the issuer exists.
+
+use crate::api::*;
+use crate::utils::is_buffer_equal;
+
+/// An issued asset: a currency code together with the account that issues it
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct IssuedAsset {
+    /// 20 byte currency code
+    pub currency: CurrencyCode,
+    /// Account ID of the issuer
+    pub issuer: AccountId,
+}
+
+impl IssuedAsset {
+    /// Create an issued asset from a 20 byte currency code and an issuer
+    #[inline(always)]
+    pub const fn new(currency: CurrencyCode, issuer: AccountId) -> Self {
+        Self { currency, issuer }
+    }
+
+    /// Create an issued asset from a standard 3 character currency code, such as `b"USD"`.
+    ///
+    /// The code is placed at bytes 12 to 14 of an otherwise zeroed currency code,
+    /// which is how the ledger represents standard currency codes.
+    #[inline(always)]
+    pub const fn from_iso_code(iso_code: &[u8; 3], issuer: AccountId) -> Self {
+        let mut currency = [0; CURRENCY_CODE_SIZE];
+        currency[12] = iso_code[0];
+        currency[13] = iso_code[1];
+        currency[14] = iso_code[2];
+
+        Self { currency, issuer }
+    }
+
+    /// Compute the keylet of the trust line between `holder` and the issuer for this currency
+    #[inline(always)]
+    pub fn trustline_keylet(&self, holder: &AccountId) -> Result<Keylet> {
+        util_keylet(KeyletType::Line(&holder.0, &self.issuer.0, &self.currency))
+    }
+
+    /// Check whether `holder` has a trust line to the issuer for this currency.
+    ///
+    /// This temporarily occupies a slot, which is freed again before returning.
+    /// Returns `Ok(true)` for the issuer itself, since an issuer can always receive
+    /// its own currency.
+    ///
+    /// # Example
+    /// ```
+    /// const USD: IssuedAsset = IssuedAsset::from_iso_code(b"USD", ISSUER);
+    ///
+    /// if !USD.has_trustline(&destination).unwrap_line_number() {
+    ///     rollback(b"destination cannot hold USD", -1);
+    /// }
+    /// ```
+    #[inline(always)]
+    pub fn has_trustline(&self, holder: &AccountId) -> Result<bool> {
+        if is_buffer_equal(&holder.0, &self.issuer.0) {
+            return Ok(true);
+        }
+
+        let keylet = self.trustline_keylet(holder)?;
+
+        match slot_set(&keylet, 0) {
+            Ok(slot_no) => {
+                let _ = slot_clear(slot_no as u32);
+                Ok(true)
+            }
+            Err(HookError::DoesntExist) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Create a standard currency code from a 3 character ISO 4217 style code, such as
+/// `b"USD"`, or `None` if the code is not a valid standard currency code.
+///
+/// Standard codes are made of letters, digits and the symbols `?!@#$%^&*<>(){}[]|`,
+/// and can't be `XRP`.
+pub const fn currency_code_from_iso(iso_code: &[u8]) -> Option<CurrencyCode> {
+    if iso_code.len() != 3 {
+        return None;
+    }
+    if iso_code[0] == b'X' && iso_code[1] == b'R' && iso_code[2] == b'P' {
+        return None;
+    }
+
+    let mut currency = [0; CURRENCY_CODE_SIZE];
+    let mut i = 0;
+    while i < 3 {
+        let char = iso_code[i];
+        if !(char.is_ascii_alphanumeric() || is_currency_symbol(char)) {
+            return None;
+        }
+        currency[12 + i] = char;
+        i += 1;
+    }
+
+    Some(currency)
+}
+
+/// Create a currency code from 40 hex characters, or `None` if the input is not
+/// 40 hex characters
+pub const fn currency_code_from_hex(hex: &[u8]) -> Option<CurrencyCode> {
+    crate::hex::decode_array(hex)
+}
+
+const fn is_currency_symbol(char: u8) -> bool {
+    matches!(
+        char,
+        b'?' | b'!'
+            | b'@'
+            | b'#'
+            | b'$'
+            | b'%'
+            | b'^'
+            | b'&'
+            | b'*'
+            | b'<'
+            | b'>'
+            | b'('
+            | b')'
+            | b'{'
+            | b'}'
+            | b'['
+            | b']'
+            | b'|'
+    )
+}
+
+/// Create a [CurrencyCode] at compile time, either from a standard 3 character code
+/// or from 40 hex characters prefixed with `hex`.
+///
+/// Fails to compile if the currency code is invalid.
+///
+/// # Example
+/// ```
+/// const USD: CurrencyCode = currency!("USD");
+/// const DEMURRAGE: CurrencyCode = currency!(hex "0158415500000000C1F76FF6ECB0BAC600000000");
+/// ```
+#[macro_export]
+macro_rules! currency {
+    (hex $hex:expr) => {
+        const {
+            match $crate::asset::currency_code_from_hex($hex.as_bytes()) {
+                ::core::option::Option::Some(currency) => currency,
+                ::core::option::Option::None => ::core::panic!("invalid hex currency code"),
+            }
+        }
+    };
+    ($iso_code:expr) => {
+        const {
+            match $crate::asset::currency_code_from_iso($iso_code.as_bytes()) {
+                ::core::option::Option::Some(currency) => currency,
+                ::core::option::Option::None => ::core::panic!("invalid currency code"),
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+
+    #[wasm_bindgen_test]
+    fn creates_standard_currency_codes() {
+        const USD: CurrencyCode = currency!("USD");
+
+        let mut expected = [0; CURRENCY_CODE_SIZE];
+        expected[12..15].copy_from_slice(b"USD");
+        assert_eq!(USD, expected);
+        assert_eq!(
+            USD,
+            IssuedAsset::from_iso_code(b"USD", AccountId::ZERO).currency
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn rejects_invalid_standard_currency_codes() {
+        assert_eq!(currency_code_from_iso(b"XRP"), None);
+        assert_eq!(currency_code_from_iso(b"US"), None);
+        assert_eq!(currency_code_from_iso(b"US D"), None);
+        assert_eq!(currency_code_from_iso(b"U D"), None);
+    }
+
+    #[wasm_bindgen_test]
+    fn creates_hex_currency_codes() {
+        const DEMURRAGE: CurrencyCode = currency!(hex "0158415500000000c1f76ff6ecb0bac600000000");
+
+        assert_eq!(
+            DEMURRAGE,
+            [
+                0x01, 0x58, 0x41, 0x55, 0x00, 0x00, 0x00, 0x00, 0xc1, 0xf7, 0x6f, 0xf6, 0xec, 0xb0,
+                0xba, 0xc6, 0x00, 0x00, 0x00, 0x00
+            ]
+        );
+        assert_eq!(currency_code_from_hex(b"0158"), None);
+        assert_eq!(currency_code_from_hex(&[b'g'; 40]), None);
+    }
+}
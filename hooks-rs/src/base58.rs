@@ -0,0 +1,430 @@
+//! Base58check encoding and decoding of account ids (r-addresses) without host calls.
+//!
+//! Unlike [util_raddr](crate::util_raddr) and [util_accid](crate::util_accid), the
+//! functions in this module do not call the host, so they also work in native tests
+//! where host functions are not available.
+//!
+//! Note that these functions contain loops without guards. Inside a hook, they must
+//! only be evaluated at compile time, either in a `const` context or through the
+//! [accid!](crate::accid) macro, otherwise the hook will be rejected by the guard checker.
+
+use crate::{AccountId, ACC_ID_LEN};
+
+/// Base58 alphabet used by the XRPL
+pub(crate) const ALPHABET: &[u8; 58] =
+    b"rpshnaf39wBUDNEGHJKLM4PQRST7VWXYZ2bcdeCg65jkm8oFqi1tuvAxyz";
+
+/// Maximum byte length of an r-address
+pub const R_ADDRESS_MAX_LEN: usize = 35;
+
+/// Minimum byte length of an r-address
+pub const R_ADDRESS_MIN_LEN: usize = 25;
+
+const ACCOUNT_ID_VERSION: u8 = 0;
+const CHECKSUM_LEN: usize = 4;
+// version byte + account id
+const VERSIONED_LEN: usize = 1 + ACC_ID_LEN;
+// version byte + account id + checksum
+const PAYLOAD_LEN: usize = VERSIONED_LEN + CHECKSUM_LEN;
+
+const INVALID_DIGIT: u8 = 0xFF;
+
+const DECODE_TABLE: [u8; 128] = {
+    let mut table = [INVALID_DIGIT; 128];
+    let mut i = 0;
+    while i < ALPHABET.len() {
+        table[ALPHABET[i] as usize] = i as u8;
+        i += 1;
+    }
+    table
+};
+
+/// Encode an account id as an r-address.
+///
+/// The r-address is written at the beginning of the returned buffer and the rest of it
+/// is filled with zeroes, the same way as [util_raddr](crate::util_raddr) does.
+///
+/// # Example
+/// ```
+/// const GENESIS_R_ADDRESS: [u8; 35] = encode_account_id(&GENESIS_ACCOUNT_ID);
+/// ```
+pub(crate) const fn encode_account_id(account_id: &AccountId) -> [u8; R_ADDRESS_MAX_LEN] {
+    let mut versioned = [0; VERSIONED_LEN];
+    versioned[0] = ACCOUNT_ID_VERSION;
+    let mut i = 0;
+    while i < ACC_ID_LEN {
+        versioned[i + 1] = account_id.0[i];
+        i += 1;
+    }
+    let checksum = checksum(&versioned);
+
+    let mut payload = [0; PAYLOAD_LEN];
+    i = 0;
+    while i < PAYLOAD_LEN {
+        payload[i] = if i < VERSIONED_LEN {
+            versioned[i]
+        } else {
+            checksum[i - VERSIONED_LEN]
+        };
+        i += 1;
+    }
+
+    // base58 digits of the payload, least significant digit first
+    let mut digits = [0_u8; R_ADDRESS_MAX_LEN];
+    let mut digits_len = 0;
+    i = 0;
+    while i < PAYLOAD_LEN {
+        let mut carry = payload[i] as u32;
+        let mut j = 0;
+        while j < digits_len {
+            carry += (digits[j] as u32) << 8;
+            digits[j] = (carry % 58) as u8;
+            carry /= 58;
+            j += 1;
+        }
+        while carry > 0 {
+            digits[digits_len] = (carry % 58) as u8;
+            digits_len += 1;
+            carry /= 58;
+        }
+        i += 1;
+    }
+
+    let mut r_address = [0; R_ADDRESS_MAX_LEN];
+    let mut pos = 0;
+    // every leading zero byte is encoded as the first letter of the alphabet
+    while pos < PAYLOAD_LEN && payload[pos] == 0 {
+        r_address[pos] = ALPHABET[0];
+        pos += 1;
+    }
+    while digits_len > 0 {
+        digits_len -= 1;
+        r_address[pos] = ALPHABET[digits[digits_len] as usize];
+        pos += 1;
+    }
+
+    r_address
+}
+
+/// Decode an r-address into an account id.
+///
+/// Returns `None` if the r-address contains a character outside of the alphabet, has an
+/// invalid length, version or checksum.
+///
+/// # Example
+/// ```
+/// const GENESIS_ACCOUNT_ID: Option<AccountId> =
+///     decode_account_id(b"rHb9CJAWyB4rj91VRWn96DkukG4bwdtyTh");
+/// ```
+pub(crate) const fn decode_account_id(r_address: &[u8]) -> Option<AccountId> {
+    if r_address.len() < R_ADDRESS_MIN_LEN || r_address.len() > R_ADDRESS_MAX_LEN {
+        return None;
+    }
+
+    // big endian number made out of the base58 digits
+    let mut payload = [0_u8; PAYLOAD_LEN];
+    let mut i = 0;
+    while i < r_address.len() {
+        let letter = r_address[i];
+        if letter >= 128 || DECODE_TABLE[letter as usize] == INVALID_DIGIT {
+            return None;
+        }
+
+        let mut carry = DECODE_TABLE[letter as usize] as u32;
+        let mut j = PAYLOAD_LEN;
+        while j > 0 {
+            j -= 1;
+            carry += payload[j] as u32 * 58;
+            payload[j] = (carry & 0xFF) as u8;
+            carry >>= 8;
+        }
+        if carry != 0 {
+            return None;
+        }
+        i += 1;
+    }
+
+    // each leading zero byte must be represented by exactly one leading letter
+    let mut leading_letters = 0;
+    while leading_letters < r_address.len() && r_address[leading_letters] == ALPHABET[0] {
+        leading_letters += 1;
+    }
+    let mut leading_zeroes = 0;
+    while leading_zeroes < PAYLOAD_LEN && payload[leading_zeroes] == 0 {
+        leading_zeroes += 1;
+    }
+    if leading_letters != leading_zeroes || payload[0] != ACCOUNT_ID_VERSION {
+        return None;
+    }
+
+    let mut versioned = [0; VERSIONED_LEN];
+    i = 0;
+    while i < VERSIONED_LEN {
+        versioned[i] = payload[i];
+        i += 1;
+    }
+    let checksum = checksum(&versioned);
+    i = 0;
+    while i < CHECKSUM_LEN {
+        if checksum[i] != payload[VERSIONED_LEN + i] {
+            return None;
+        }
+        i += 1;
+    }
+
+    let mut account_id = [0; ACC_ID_LEN];
+    i = 0;
+    while i < ACC_ID_LEN {
+        account_id[i] = payload[i + 1];
+        i += 1;
+    }
+
+    Some(AccountId(account_id))
+}
+
+/// Convert an r-address into an [AccountId](crate::AccountId) at compile time.
+///
+/// Fails to compile if the r-address is invalid.
+///
+/// # Example
+/// ```
+/// const GENESIS_ACCOUNT_ID: AccountId = accid!("rHb9CJAWyB4rj91VRWn96DkukG4bwdtyTh");
+/// ```
+#[macro_export]
+macro_rules! accid {
+    ($r_address:expr) => {
+        const {
+            match $crate::AccountId::from_raddress($r_address.as_bytes()) {
+                ::core::option::Option::Some(account_id) => account_id,
+                ::core::option::Option::None => ::core::panic!("invalid r-address"),
+            }
+        }
+    };
+}
+
+// First 4 bytes of the double SHA-256 hash of the versioned account id
+const fn checksum(versioned: &[u8; VERSIONED_LEN]) -> [u8; CHECKSUM_LEN] {
+    let hash = sha256(&sha256(versioned));
+
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+const SHA256_K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+// Plain SHA-256. The host only offers sha512-half, which neither base58check nor hook
+// namespaces use.
+pub(crate) const fn sha256(data: &[u8]) -> [u8; 32] {
+    let mut state: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+        0x5be0cd19,
+    ];
+    // message + 0x80 + 64 bit length, padded to a multiple of 64 bytes
+    let padded_len = (data.len() + 9).div_ceil(64) * 64;
+    let bit_len = (data.len() as u64) * 8;
+
+    let mut block = 0;
+    while block < padded_len {
+        let mut w = [0_u32; 64];
+        let mut t = 0;
+        while t < 16 {
+            let mut b = 0;
+            while b < 4 {
+                let idx = block + t * 4 + b;
+                let byte = if idx < data.len() {
+                    data[idx]
+                } else if idx == data.len() {
+                    0x80
+                } else if idx >= padded_len - 8 {
+                    (bit_len >> (8 * (padded_len - 1 - idx))) as u8
+                } else {
+                    0
+                };
+                w[t] = (w[t] << 8) | byte as u32;
+                b += 1;
+            }
+            t += 1;
+        }
+        while t < 64 {
+            let s0 = w[t - 15].rotate_right(7) ^ w[t - 15].rotate_right(18) ^ (w[t - 15] >> 3);
+            let s1 = w[t - 2].rotate_right(17) ^ w[t - 2].rotate_right(19) ^ (w[t - 2] >> 10);
+            w[t] = w[t - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[t - 7])
+                .wrapping_add(s1);
+            t += 1;
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+        t = 0;
+        while t < 64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let temp1 = h
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(SHA256_K[t])
+                .wrapping_add(w[t]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let temp2 = s0.wrapping_add(maj);
+
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(temp1);
+            d = c;
+            c = b;
+            b = a;
+            a = temp1.wrapping_add(temp2);
+            t += 1;
+        }
+
+        state[0] = state[0].wrapping_add(a);
+        state[1] = state[1].wrapping_add(b);
+        state[2] = state[2].wrapping_add(c);
+        state[3] = state[3].wrapping_add(d);
+        state[4] = state[4].wrapping_add(e);
+        state[5] = state[5].wrapping_add(f);
+        state[6] = state[6].wrapping_add(g);
+        state[7] = state[7].wrapping_add(h);
+
+        block += 64;
+    }
+
+    let mut hash = [0; 32];
+    let mut i = 0;
+    while i < 8 {
+        let bytes = state[i].to_be_bytes();
+        hash[i * 4] = bytes[0];
+        hash[i * 4 + 1] = bytes[1];
+        hash[i * 4 + 2] = bytes[2];
+        hash[i * 4 + 3] = bytes[3];
+        i += 1;
+    }
+
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::fixtures::GENESIS_ACCOUNT;
+
+    fn padded(r_address: &[u8]) -> [u8; R_ADDRESS_MAX_LEN] {
+        let mut buf = [0; R_ADDRESS_MAX_LEN];
+        buf[..r_address.len()].copy_from_slice(r_address);
+        buf
+    }
+
+    #[wasm_bindgen_test]
+    fn can_hash_with_sha256() {
+        assert_eq!(
+            sha256(b"abc"),
+            [
+                0xba, 0x78, 0x16, 0xbf, 0x8f, 0x01, 0xcf, 0xea, 0x41, 0x41, 0x40, 0xde, 0x5d, 0xae,
+                0x22, 0x23, 0xb0, 0x03, 0x61, 0xa3, 0x96, 0x17, 0x7a, 0x9c, 0xb4, 0x10, 0xff, 0x61,
+                0xf2, 0x00, 0x15, 0xad
+            ]
+        );
+        // spans more than one block
+        assert_eq!(
+            sha256(&[b'a'; 100]),
+            [
+                0x28, 0x16, 0x59, 0x78, 0x88, 0xe4, 0xa0, 0xd3, 0xa3, 0x6b, 0x82, 0xb8, 0x33, 0x16,
+                0xab, 0x32, 0x68, 0x0e, 0xb8, 0xf0, 0x0f, 0x8c, 0xd3, 0xb9, 0x04, 0xd6, 0x81, 0x24,
+                0x6d, 0x28, 0x5a, 0x0e
+            ]
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn can_encode_account_id() {
+        assert_eq!(
+            encode_account_id(&GENESIS_ACCOUNT),
+            padded(b"rHb9CJAWyB4rj91VRWn96DkukG4bwdtyTh")
+        );
+        assert_eq!(
+            encode_account_id(&AccountId::ZERO),
+            padded(b"rrrrrrrrrrrrrrrrrrrrrhoLvTp")
+        );
+        let mut account_one = AccountId::ZERO;
+        account_one.0[19] = 1;
+        assert_eq!(
+            encode_account_id(&account_one),
+            padded(b"rrrrrrrrrrrrrrrrrrrrBZbvji")
+        );
+        assert_eq!(
+            encode_account_id(&AccountId([0xFF; 20])),
+            padded(b"rQLbzfJH5BT1FS9apRLKV3G8dWEA5njaQi")
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn can_decode_account_id() {
+        assert_eq!(
+            decode_account_id(b"rHb9CJAWyB4rj91VRWn96DkukG4bwdtyTh"),
+            Some(GENESIS_ACCOUNT)
+        );
+        assert_eq!(
+            decode_account_id(b"rrrrrrrrrrrrrrrrrrrrrhoLvTp"),
+            Some(AccountId::ZERO)
+        );
+        assert_eq!(
+            decode_account_id(b"rQLbzfJH5BT1FS9apRLKV3G8dWEA5njaQi"),
+            Some(AccountId([0xFF; 20]))
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn rejects_invalid_r_addresses() {
+        // checksum mismatch
+        assert_eq!(
+            decode_account_id(b"rHb9CJAWyB4rj91VRWn96DkukG4bwdtyTj"),
+            None
+        );
+        // '0' is not in the alphabet
+        assert_eq!(
+            decode_account_id(b"rHb9CJAWyB4rj91VRWn96DkukG4bwdty0h"),
+            None
+        );
+        // too short
+        assert_eq!(decode_account_id(b"rHb9CJAWyB4rj91VRW"), None);
+        // missing leading 'r'
+        assert_eq!(
+            decode_account_id(b"Hb9CJAWyB4rj91VRWn96DkukG4bwdtyTh"),
+            None
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn can_decode_at_compile_time() {
+        const GENESIS: AccountId = crate::accid!("rHb9CJAWyB4rj91VRWn96DkukG4bwdtyTh");
+
+        assert_eq!(GENESIS, GENESIS_ACCOUNT);
+    }
+
+    #[wasm_bindgen_test]
+    fn converts_account_ids_without_the_host() {
+        const GENESIS: Option<AccountId> =
+            AccountId::from_raddress(b"rHb9CJAWyB4rj91VRWn96DkukG4bwdtyTh");
+        const GENESIS_R_ADDRESS: [u8; R_ADDRESS_MAX_LEN] = GENESIS_ACCOUNT.to_raddress_const();
+
+        assert_eq!(GENESIS, Some(GENESIS_ACCOUNT));
+        assert_eq!(
+            GENESIS_R_ADDRESS,
+            padded(b"rHb9CJAWyB4rj91VRWn96DkukG4bwdtyTh")
+        );
+        assert_eq!(AccountId::from_raddress(b"rHb9CJAWyB4rj91VRW"), None);
+    }
+}
@@ -0,0 +1,263 @@
+//! Bookkeeping for emitted transactions and their callbacks.
+//!
+//! `cbak` is called once for every emitted transaction, either after it was applied
+//! to a ledger or after it expired. To retry failed emissions reliably, a hook needs to
+//! remember what it emitted: [emit_tracked] records the hash of an emitted transaction
+//! in hook state under a key of your choice, and [take_tracked_emit] looks it up again
+//! from `cbak` and removes it.
+//!
+//! # Example
+//! ```
+//! const PAYOUT_KEY: &[u8; 6] = b"payout";
+//!
+//! #[no_mangle]
+//! pub extern "C" fn cbak(what: u32) -> i64 {
+//!     match take_tracked_emit(PAYOUT_KEY, what) {
+//!         Ok(Some(EmitOutcome::Expired)) => {
+//!             // remember to retry the payout the next time the hook runs
+//!         }
+//!         _ => {}
+//!     }
+//!     0
+//! }
+//!
+//! #[no_mangle]
+//! pub extern "C" fn hook(_: u32) -> i64 {
+//!     max_iter(1);
+//!
+//!     let mut reservation = EmitReservation::new(1).unwrap_line_number();
+//!     let mut buffer = XrpPaymentBuilder::uninit_buffer();
+//!     let tx = XrpPaymentBuilder::new(Drops(1000), &otxn_account, None, None)
+//!         .build(&mut buffer)
+//!         .unwrap_line_number();
+//!     let txn_hash = emit_tracked(&mut reservation, tx, PAYOUT_KEY).unwrap_line_number();
+//!
+//!     accept(&txn_hash, 0);
+//! }
+//! ```
+//!
+//! # Chaining emitted transactions
+//! A transaction that depends on another one, such as "create trust line, then send
+//! tokens", can't be emitted together with it, since both would be applied in the same
+//! ledger at the earliest. Track the first transaction with [emit_tracked], and emit the
+//! next one from its callback with [emit_after_tracked]. `cbak` runs in the ledger the
+//! first transaction was applied in, so the next one is only valid from the ledger after
+//! it. The key then tracks the next transaction, so chains can go on for more steps.
+//!
+//! ```
+//! #[callback]
+//! fn on_trust_line(what: u32) -> Outcome<'static> {
+//!     let mut reservation = EmitReservation::new(1)?;
+//!     let tokens = TrustlinePaymentBuilder::new(&amount, &otxn_account, None, None);
+//!     match emit_after_tracked(&mut reservation, TRUST_LINE_KEY, what, &tokens)? {
+//!         ChainedEmit::Emitted(_) => Outcome::Accept { code: 0, msg: b"sending tokens" },
+//!         _ => Outcome::Accept { code: 1, msg: b"no tokens sent" },
+//!     }
+//! }
+//! ```
+
+use crate::api::*;
+use crate::transaction::TransactionBuilder;
+use crate::utils::is_buffer_equal;
+
+/// What happened to an emitted transaction, as reported to `cbak`
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub enum EmitOutcome {
+    /// The emitted transaction was applied to a ledger
+    Applied,
+    /// The emitted transaction expired before it could be applied
+    Expired,
+}
+
+impl EmitOutcome {
+    /// Convert the argument `cbak` is called with, which is 0 if the emitted transaction
+    /// was applied and 1 if it expired
+    #[inline(always)]
+    pub const fn from_cbak_arg(what: u32) -> Self {
+        match what {
+            0 => EmitOutcome::Applied,
+            _ => EmitOutcome::Expired,
+        }
+    }
+}
+
+/// Emit a transaction through `reservation` and record its hash in hook state under `key`.
+///
+/// Returns an error without emitting if `key` is already tracking a transaction that
+/// has not been taken with [take_tracked_emit] yet. If the state can't be written after
+/// emitting, the error is returned and the hook should roll back, which also
+/// discards the emitted transaction.
+#[inline(always)]
+pub fn emit_tracked<T>(
+    reservation: &mut EmitReservation,
+    tx: &[T],
+    key: &[u8],
+) -> Result<[u8; HASH_LEN]> {
+    match state::<HASH_LEN>(key) {
+        Ok(_) => return Err(HookError::AlreadySet),
+        Err(HookError::DoesntExist) => {}
+        Err(err) => return Err(err),
+    }
+
+    let txn_hash = reservation.emit(tx)?;
+
+    match state_set(&txn_hash, key) {
+        Ok(_) => Ok(txn_hash),
+        Err(err) => Err(err),
+    }
+}
+
+/// Look up the transaction tracked under `key` from `cbak` and stop tracking it.
+///
+/// `what` is the argument `cbak` was called with. Returns `Ok(None)` if nothing is
+/// tracked under `key`, or if the callback is for a different emitted transaction,
+/// in which case the tracked transaction is left in place.
+#[inline(always)]
+pub fn take_tracked_emit(key: &[u8], what: u32) -> Result<Option<EmitOutcome>> {
+    let tracked_txn_hash = match state::<HASH_LEN>(key) {
+        Ok(txn_hash) => txn_hash,
+        Err(HookError::DoesntExist) => return Ok(None),
+        Err(err) => return Err(err),
+    };
+    let emitted_txn_hash = otxn_id(OtxnIdFlag::Zero)?;
+
+    if !is_buffer_equal(&tracked_txn_hash, &emitted_txn_hash) {
+        return Ok(None);
+    }
+
+    match state_set(&[], key) {
+        Ok(_) => Ok(Some(EmitOutcome::from_cbak_arg(what))),
+        Err(err) => Err(err),
+    }
+}
+
+/// What [emit_after_tracked] did
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub enum ChainedEmit {
+    /// Nothing is tracked under the key, or the callback is for another transaction
+    NotTracked,
+    /// The tracked transaction expired, so the next one was not emitted
+    Expired,
+    /// The tracked transaction was applied, and the next one was emitted with this hash
+    /// and is now tracked under the key
+    Emitted([u8; HASH_LEN]),
+}
+
+/// Emit `next` from `cbak` once the transaction tracked under `key` was applied, see
+/// [chaining emitted transactions](self#chaining-emitted-transactions).
+///
+/// `what` is the argument `cbak` was called with. The tracked transaction is taken like
+/// with [take_tracked_emit], and `next` is emitted through `reservation` and tracked under
+/// `key` in its place. Nothing is emitted if the tracked transaction expired or the
+/// callback is for another transaction. `next` is valid from the ledger after the one the
+/// tracked transaction was applied in with the default [LedgerWindow] of the builders.
+///
+/// [LedgerWindow]: crate::LedgerWindow
+#[inline(always)]
+pub fn emit_after_tracked<const TXN_LEN: usize, B: TransactionBuilder<TXN_LEN>>(
+    reservation: &mut EmitReservation,
+    key: &[u8],
+    what: u32,
+    next: &B,
+) -> Result<ChainedEmit> {
+    match take_tracked_emit(key, what)? {
+        Some(EmitOutcome::Applied) => {}
+        Some(EmitOutcome::Expired) => return Ok(ChainedEmit::Expired),
+        None => return Ok(ChainedEmit::NotTracked),
+    }
+
+    let mut buffer = B::uninit_buffer();
+    let tx = next.build(&mut buffer)?;
+    match emit_tracked(reservation, tx, key) {
+        Ok(txn_hash) => Ok(ChainedEmit::Emitted(txn_hash)),
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::decode::decode;
+    use crate::fixtures::{DESTINATION, HOOK_ACCOUNT, ISSUER};
+    use crate::mock;
+    use crate::transaction::XrpPaymentBuilder;
+
+    const CHAIN_KEY: &[u8; 5] = b"chain";
+
+    fn emit_first() -> Outcome<'static> {
+        let mut reservation = EmitReservation::new(1)?;
+        let mut buffer = XrpPaymentBuilder::uninit_buffer();
+        let tx =
+            XrpPaymentBuilder::new(Drops(1000), &DESTINATION, None, None).build(&mut buffer)?;
+        emit_tracked(&mut reservation, tx, CHAIN_KEY)?;
+
+        Outcome::Accept {
+            code: 0,
+            msg: b"emitted first",
+        }
+    }
+
+    fn emit_next(what: u32) -> Outcome<'static> {
+        let mut reservation = EmitReservation::new(1)?;
+        let next = XrpPaymentBuilder::new(Drops(2000), &ISSUER, None, None);
+        let code = match emit_after_tracked(&mut reservation, CHAIN_KEY, what, &next)? {
+            ChainedEmit::Emitted(_) => 0,
+            ChainedEmit::Expired => 1,
+            ChainedEmit::NotTracked => 2,
+        };
+
+        Outcome::Accept { code, msg: b"" }
+    }
+
+    fn setup() {
+        mock::reset();
+        mock::set_hook_account(HOOK_ACCOUNT);
+        mock::set_ledger_seq(100);
+    }
+
+    #[wasm_bindgen_test]
+    fn emits_the_next_transaction_after_the_tracked_one() {
+        setup();
+        let first = mock::invoke(emit_first);
+        assert_eq!(first.emitted.len(), 1);
+
+        // the callback runs in a later ledger, which the next transaction has to follow
+        mock::set_ledger_seq(102);
+        let chained = mock::callback(EmitOutcome::Applied, emit_next).unwrap();
+        assert_eq!(chained.code, 0);
+        assert_eq!(chained.emitted.len(), 1);
+        let next = decode(&chained.emitted[0].tx);
+        assert_eq!(next.account(FieldId::Destination), Some(ISSUER));
+        assert_eq!(next.u32(FieldId::FirstLedgerSequence), Some(103));
+        assert_eq!(
+            mock::state(CHAIN_KEY).as_deref(),
+            Some(&chained.emitted[0].hash[..])
+        );
+
+        // the key tracks the next transaction now
+        assert_eq!(
+            mock::callback(EmitOutcome::Expired, emit_next)
+                .unwrap()
+                .code,
+            1
+        );
+        assert_eq!(mock::state(CHAIN_KEY), None);
+    }
+
+    #[wasm_bindgen_test]
+    fn emits_nothing_for_other_transactions() {
+        setup();
+        mock::invoke(emit_first);
+        assert!(mock::state(CHAIN_KEY).is_some());
+        mock::set_state(CHAIN_KEY, &[9; HASH_LEN]);
+
+        let chained = mock::callback(EmitOutcome::Applied, emit_next).unwrap();
+        assert_eq!(chained.code, 2);
+        assert!(chained.emitted.is_empty());
+        assert_eq!(mock::state(CHAIN_KEY).as_deref(), Some(&[9; HASH_LEN][..]));
+    }
+}
@@ -0,0 +1,277 @@
+//! Decoder for serialized transactions, for asserting on what a builder produced.
+//!
+//! [decode] parses a buffer, such as one filled by
+//! [TransactionBuilder::build](crate::TransactionBuilder::build) or one of
+//! [mock::emitted](crate::mock::emitted), back into its fields. Tests can then check
+//! single fields instead of comparing whole buffers byte by byte.
+//!
+//! Like the mock host, this is only available in tests and with the `mock` feature.
+//! It panics on malformed input, since it is only meant to be used in tests.
+//!
+//! # Example
+//! ```
+//! let tx = decode(&mock::emitted()[0]);
+//!
+//! assert_eq!(tx.txn_type(), Some(TxnType::Payment as u16));
+//! assert_eq!(tx.drops(FieldId::Amount), Some(1000));
+//! assert_eq!(tx.account(FieldId::Destination), Some(DESTINATION));
+//! ```
+
+extern crate std;
+
+use std::vec::Vec;
+
+use crate::api::*;
+use crate::reader::SliceReader;
+
+const OBJECT_END_MARKER: u8 = 0xE1;
+const ARRAY_END_MARKER: u8 = 0xF1;
+
+/// A field of a decoded transaction
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecodedField {
+    /// Field ID in the same encoding as [FieldId], that is `(type << 16) + field`
+    pub field_id: u32,
+    /// Value of the field, without the length prefix of variable length fields.
+    /// Objects and arrays don't include their end marker.
+    pub value: Vec<u8>,
+}
+
+/// A decoded transaction or object, as a list of fields in serialization order
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecodedTxn {
+    /// The fields in the order they were serialized
+    pub fields: Vec<DecodedField>,
+}
+
+impl DecodedTxn {
+    /// The value of the first field with this ID
+    pub fn field(&self, field_id: FieldId) -> Option<&[u8]> {
+        self.fields
+            .iter()
+            .find(|field| field.field_id == field_id as u32)
+            .map(|field| field.value.as_slice())
+    }
+
+    /// The transaction type
+    pub fn txn_type(&self) -> Option<u16> {
+        self.field(FieldId::TransactionType)
+            .map(|value| u16::from_be_bytes(value.try_into().unwrap()))
+    }
+
+    /// The value of a `UInt32` field
+    pub fn u32(&self, field_id: FieldId) -> Option<u32> {
+        self.field(field_id)
+            .map(|value| u32::from_be_bytes(value.try_into().unwrap()))
+    }
+
+    /// The drops of an XRP amount field, or `None` if the field is missing or an issued amount
+    pub fn drops(&self, field_id: FieldId) -> Option<u64> {
+        let value = self.field(field_id)?;
+        if value.len() != 8 {
+            return None;
+        }
+        let amount = u64::from_be_bytes(value.try_into().unwrap());
+        // an amount in XRP has the highest bit unset and the positive bit set
+        assert_eq!(
+            amount & 0xC000_0000_0000_0000,
+            0x4000_0000_0000_0000,
+            "not a positive XRP amount"
+        );
+
+        Some(amount & 0x3FFF_FFFF_FFFF_FFFF)
+    }
+
+    /// The value of an account field
+    pub fn account(&self, field_id: FieldId) -> Option<AccountId> {
+        self.field(field_id)
+            .map(|value| AccountId(value.try_into().unwrap()))
+    }
+
+    /// The fields of an object field, such as [FieldId::EmitDetails]
+    pub fn object(&self, field_id: FieldId) -> Option<DecodedTxn> {
+        self.field(field_id).map(decode)
+    }
+}
+
+/// Decode a serialized transaction or the contents of an object.
+///
+/// # Panics
+/// If the buffer isn't a sequence of well formed fields. Path sets are not supported.
+pub fn decode(tx: &[u8]) -> DecodedTxn {
+    let mut decoder = Decoder::new(tx);
+    let mut fields = Vec::new();
+    while !decoder.reader.is_empty() {
+        fields.push(decoder.field());
+    }
+
+    DecodedTxn { fields }
+}
+
+/// Split a serialized transaction or the contents of an object into the bytes of each
+/// field, including its field header.
+///
+/// # Panics
+/// Like [decode], if the buffer isn't a sequence of well formed fields.
+pub fn split_fields(tx: &[u8]) -> Vec<&[u8]> {
+    let mut decoder = Decoder::new(tx);
+    let mut fields = Vec::new();
+    while !decoder.reader.is_empty() {
+        let start = decoder.reader.pos();
+        decoder.field();
+        fields.push(&tx[start..decoder.reader.pos()]);
+    }
+
+    fields
+}
+
+struct Decoder<'a> {
+    tx: &'a [u8],
+    reader: SliceReader<'a>,
+}
+
+impl<'a> Decoder<'a> {
+    fn new(tx: &'a [u8]) -> Self {
+        Self {
+            tx,
+            reader: SliceReader::new(tx),
+        }
+    }
+
+    fn byte(&mut self) -> u8 {
+        let pos = self.reader.pos();
+        match self.reader.read_u8() {
+            Ok(byte) => byte,
+            Err(_) => panic!("unexpected end of transaction at byte {pos}"),
+        }
+    }
+
+    fn bytes(&mut self, len: usize) -> &'a [u8] {
+        let pos = self.reader.pos();
+        match self.reader.read_bytes(len) {
+            Ok(bytes) => bytes,
+            Err(_) => panic!("unexpected end of transaction reading {len} bytes at byte {pos}"),
+        }
+    }
+
+    fn field_header(&mut self) -> (u32, u32) {
+        let first = self.byte();
+        let mut type_code = (first >> 4) as u32;
+        let mut field_code = (first & 0x0F) as u32;
+        if type_code == 0 {
+            type_code = self.byte() as u32;
+        }
+        if field_code == 0 {
+            field_code = self.byte() as u32;
+        }
+
+        (type_code, field_code)
+    }
+
+    fn variable_length(&mut self) -> usize {
+        let pos = self.reader.pos();
+        match self.reader.read_vl_len() {
+            Ok(len) => len,
+            Err(HookError::ParseError) => panic!("invalid length prefix at byte {pos}"),
+            Err(_) => panic!("unexpected end of transaction in length prefix at byte {pos}"),
+        }
+    }
+
+    fn field(&mut self) -> DecodedField {
+        let start = self.reader.pos();
+        let (type_code, field_code) = self.field_header();
+        let serialized_type = SerializedType::from_code(type_code);
+        let value = match serialized_type {
+            Some(SerializedType::Amount) => {
+                // issued amounts have the highest bit set
+                match self.reader.peek_u8() {
+                    Ok(byte) if byte & 0x80 != 0 => self.bytes(48),
+                    _ => self.bytes(8),
+                }
+            }
+            Some(SerializedType::Blob | SerializedType::Account | SerializedType::Vector256) => {
+                let len = self.variable_length();
+                self.bytes(len)
+            }
+            Some(SerializedType::Object) => self.until_end_marker(OBJECT_END_MARKER),
+            Some(SerializedType::Array) => self.until_end_marker(ARRAY_END_MARKER),
+            Some(fixed) if fixed.fixed_len().is_some() => self.bytes(fixed.fixed_len().unwrap()),
+            _ => panic!("unsupported type {type_code} of field at byte {start}"),
+        };
+
+        DecodedField {
+            field_id: (type_code << 16) + field_code,
+            value: value.into(),
+        }
+    }
+
+    /// Skips nested fields until the end marker, returning them without the marker
+    fn until_end_marker(&mut self, end_marker: u8) -> &'a [u8] {
+        let start = self.reader.pos();
+        while !matches!(self.reader.peek_u8(), Ok(byte) if byte == end_marker) {
+            self.field();
+        }
+        let value = &self.tx[start..self.reader.pos()];
+        self.byte();
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+
+    #[wasm_bindgen_test]
+    fn decodes_fields_of_every_length() {
+        let tx = [
+            // TransactionType = Payment
+            0x12, 0x00, 0x00, //
+            // Flags
+            0x22, 0x80, 0x00, 0x00, 0x00, //
+            // Amount = 1000 drops
+            0x61, 0x40, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0xE8, //
+            // SigningPubKey, empty
+            0x73, 0x00, //
+            // Destination
+            0x83, 0x14, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
+            20, //
+            // EmitDetails with EmitGeneration = 2
+            0xED, 0x20, 0x2E, 0x00, 0x00, 0x00, 0x02, 0xE1,
+        ];
+
+        let decoded = decode(&tx);
+
+        assert_eq!(decoded.fields.len(), 6);
+        assert_eq!(decoded.txn_type(), Some(TxnType::Payment as u16));
+        assert_eq!(decoded.u32(FieldId::Flags), Some(0x8000_0000));
+        assert_eq!(decoded.drops(FieldId::Amount), Some(1000));
+        assert_eq!(decoded.field(FieldId::SigningPubKey), Some(&[][..]));
+        assert_eq!(
+            decoded.account(FieldId::Destination),
+            Some(AccountId([
+                1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20
+            ]))
+        );
+        assert_eq!(
+            decoded
+                .object(FieldId::EmitDetails)
+                .and_then(|details| details.u32(FieldId::EmitGeneration)),
+            Some(2)
+        );
+        assert_eq!(decoded.field(FieldId::Fee), None);
+    }
+
+    #[wasm_bindgen_test]
+    fn decodes_issued_amounts() {
+        let mut tx = [0; 49];
+        tx[0] = 0x61;
+        tx[1] = 0xD4;
+
+        let decoded = decode(&tx);
+
+        assert_eq!(decoded.field(FieldId::Amount), Some(&tx[1..]));
+        assert_eq!(decoded.drops(FieldId::Amount), None);
+    }
+}
@@ -0,0 +1,290 @@
+//! Work postponed from the strong execution of a hook to its again-as-weak execution.
+//!
+//! The strong execution of a hook runs before the originating transaction is applied, and
+//! anything it does counts against the fee of the transaction and delays whether it is
+//! accepted. Work that doesn't decide whether to accept, such as bookkeeping for payouts,
+//! can instead be left to the again-as-weak execution that [hook_again] requests, which
+//! runs once the transaction was applied.
+//!
+//! [Deferred::defer] keeps a [Record] describing the work in the state of the hook and
+//! requests the again-as-weak execution, and [Deferred::take] hands the record back in
+//! that execution, and only in it. The record is kept with the other state changes of the
+//! strong execution, so nothing is deferred if the hook rolls back, and it is only removed
+//! by the execution that takes it, so work is never lost or done twice. If the
+//! again-as-weak execution rolls back, the work stays pending, and the next strong
+//! execution that tries to defer work requests another again-as-weak execution to take
+//! it.
+//!
+//! # Example
+//! ```
+//! const PAYOUT: Deferred<Payout> = Deferred::new(b"payout");
+//!
+//! #[hook]
+//! fn handle(reserved: u32) -> Outcome<'static> {
+//!     let ctx = HookCtx::load(reserved)?;
+//!     match ctx.execution() {
+//!         ExecutionKind::Strong => PAYOUT.defer(&Payout { account, drops })?,
+//!         _ => {
+//!             if let Some(payout) = PAYOUT.take(ctx.execution())? {
+//!                 record_payout(payout)?;
+//!             }
+//!         }
+//!     }
+//!
+//!     Outcome::Accept { code: 0, msg: b"" }
+//! }
+//! ```
+
+use core::marker::PhantomData;
+
+use crate::access::{padded_name, state_key, STATE_KEY_SUFFIX_LEN};
+use crate::api::*;
+use crate::table::{load_record, store_record, Record};
+
+/// Maximum length of the name of a [Deferred], which is laid out in state keys like the
+/// name of an [AccessList](crate::AccessList)
+pub const DEFERRED_NAME_MAX_LEN: usize = crate::ACCESS_LIST_NAME_MAX_LEN;
+
+/// Work of type `R` that the strong execution of a hook leaves to its again-as-weak
+/// execution, kept in its state under a name of its own
+#[derive(Clone, Copy)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct Deferred<R> {
+    name: [u8; DEFERRED_NAME_MAX_LEN],
+    record: PhantomData<R>,
+}
+
+impl<R: Record> Deferred<R> {
+    /// Work named `name`.
+    ///
+    /// Declare it as a constant, so that the name is padded at compile time, and a name
+    /// longer than [DEFERRED_NAME_MAX_LEN] is rejected there.
+    #[inline(always)]
+    pub const fn new(name: &[u8]) -> Self {
+        Self {
+            name: padded_name(name),
+            record: PhantomData,
+        }
+    }
+
+    /// The state key of the work
+    #[inline(always)]
+    pub fn key(&self) -> [u8; STATE_KEY_LEN] {
+        // the name, then zeros
+        state_key(&self.name, [0; STATE_KEY_SUFFIX_LEN])
+    }
+
+    /// Keep `work` in state and request the again-as-weak execution of the hook.
+    ///
+    /// Fails with [HookError::PrerequisiteNotMet] outside the strong execution, and
+    /// nothing is kept if the execution can't be requested.
+    ///
+    /// Fails with [HookError::AlreadySet] without keeping `work` if work deferred before
+    /// wasn't taken yet, because the again-as-weak execution that should have taken it
+    /// rolled back. The again-as-weak execution is requested anyway, so that it takes the
+    /// pending work, which [pending](Self::pending) returns in the meantime.
+    #[inline(always)]
+    pub fn defer(&self, work: &R) -> Result<()> {
+        let pending = self.is_pending()?;
+        request_weak_reexecution()?;
+        if pending {
+            return Err(HookError::AlreadySet);
+        }
+
+        store_record(&self.key(), work)
+    }
+
+    /// The work deferred and not taken yet, without taking it
+    #[inline(always)]
+    pub fn pending(&self) -> Result<Option<R>> {
+        match load_record::<R>(&self.key()) {
+            Ok(work) => Ok(Some(work)),
+            Err(HookError::DoesntExist) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Take the deferred work in the again-as-weak execution, removing it from state.
+    ///
+    /// Returns `None` if nothing was deferred, and in any other kind of execution, so the
+    /// work is only done once the originating transaction was applied.
+    #[inline(always)]
+    pub fn take(&self, execution: ExecutionKind) -> Result<Option<R>> {
+        if execution != ExecutionKind::AgainAsWeak {
+            return Ok(None);
+        }
+
+        let key = self.key();
+        let work = match load_record::<R>(&key) {
+            Ok(work) => work,
+            Err(HookError::DoesntExist) => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        match state_set(&[], &key) {
+            Ok(_) => Ok(Some(work)),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Whether work was deferred and not taken yet
+    #[inline(always)]
+    pub fn is_pending(&self) -> Result<bool> {
+        match state::<1>(&self.key()) {
+            Ok(_) | Err(HookError::TooSmall) => Ok(true),
+            Err(HookError::DoesntExist) => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::fixtures::DESTINATION;
+    use crate::mock;
+
+    #[derive(crate::Record)]
+    struct Payout {
+        account: AccountId,
+        drops: Drops,
+    }
+
+    const PAYOUT: Deferred<Payout> = Deferred::new(b"payout");
+
+    fn handle(reserved: u32) -> Outcome<'static> {
+        let execution = ExecutionKind::from_hook_arg(reserved);
+        if execution.is_strong() {
+            PAYOUT.defer(&Payout {
+                account: DESTINATION,
+                drops: Drops(1_000),
+            })?;
+            return Outcome::Accept {
+                code: 0,
+                msg: b"deferred",
+            };
+        }
+
+        match PAYOUT.take(execution)? {
+            Some(payout) => Outcome::Accept {
+                code: payout.drops.0 as i64,
+                msg: b"paid",
+            },
+            None => Outcome::Accept {
+                code: 0,
+                msg: b"nothing to do",
+            },
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn leaves_work_to_the_again_as_weak_execution() {
+        mock::reset();
+        let strong = mock::invoke(|| handle(0));
+        assert_eq!(strong.msg, b"deferred");
+        assert!(mock::again_requested());
+        assert!(matches!(PAYOUT.is_pending(), Ok(true)));
+
+        mock::set_execution(ExecutionKind::Weak);
+        assert_eq!(mock::invoke(|| handle(1)).msg, b"nothing to do");
+        assert!(matches!(PAYOUT.is_pending(), Ok(true)));
+
+        mock::set_execution(ExecutionKind::AgainAsWeak);
+        let again = mock::invoke(|| handle(2));
+        assert_eq!((again.code, again.msg.as_slice()), (1_000, &b"paid"[..]));
+        assert!(!mock::again_requested());
+        assert!(mock::state(&PAYOUT.key()).is_none());
+        assert_eq!(mock::invoke(|| handle(2)).msg, b"nothing to do");
+    }
+
+    #[wasm_bindgen_test]
+    fn takes_work_again_after_a_rollback() {
+        mock::reset();
+        assert!(mock::invoke(|| handle(0)).accepted);
+
+        // the again-as-weak execution fails to do the work
+        mock::set_execution(ExecutionKind::AgainAsWeak);
+        let again = mock::invoke(|| {
+            assert!(matches!(
+                PAYOUT.take(ExecutionKind::AgainAsWeak),
+                Ok(Some(_))
+            ));
+            Outcome::Rollback {
+                code: 1,
+                msg: b"could not record the payout",
+            }
+        });
+        assert!(!again.accepted);
+        assert!(matches!(PAYOUT.is_pending(), Ok(true)));
+
+        // the next strong execution requests another one, which takes the work
+        mock::set_execution(ExecutionKind::Strong);
+        let strong = mock::invoke(|| {
+            assert!(matches!(
+                PAYOUT.pending(),
+                Ok(Some(payout)) if payout.drops == Drops(1_000)
+            ));
+            match PAYOUT.defer(&Payout {
+                account: DESTINATION,
+                drops: Drops(2_000),
+            }) {
+                Err(HookError::AlreadySet) => Outcome::Accept {
+                    code: 0,
+                    msg: b"still pending",
+                },
+                _ => Outcome::Rollback { code: 1, msg: b"" },
+            }
+        });
+        assert_eq!(strong.msg, b"still pending");
+        assert!(mock::again_requested());
+
+        mock::set_execution(ExecutionKind::AgainAsWeak);
+        let again = mock::invoke(|| handle(2));
+        assert_eq!((again.code, again.msg.as_slice()), (1_000, &b"paid"[..]));
+        assert!(matches!(PAYOUT.pending(), Ok(None)));
+    }
+
+    #[wasm_bindgen_test]
+    fn defers_only_from_the_strong_execution() {
+        mock::reset();
+        let payout = Payout {
+            account: DESTINATION,
+            drops: Drops(1),
+        };
+        assert!(
+            mock::invoke(|| {
+                assert!(PAYOUT.defer(&payout).is_ok());
+                Outcome::Accept { code: 0, msg: b"" }
+            })
+            .accepted
+        );
+        assert!(
+            mock::invoke(|| {
+                assert!(matches!(PAYOUT.defer(&payout), Err(HookError::AlreadySet)));
+                Outcome::Accept { code: 0, msg: b"" }
+            })
+            .accepted
+        );
+
+        mock::reset();
+        mock::set_execution(ExecutionKind::Weak);
+        assert!(matches!(
+            PAYOUT.defer(&payout),
+            Err(HookError::PrerequisiteNotMet)
+        ));
+        assert!(matches!(PAYOUT.is_pending(), Ok(false)));
+    }
+
+    #[wasm_bindgen_test]
+    fn converts_the_hook_argument() {
+        assert_eq!(ExecutionKind::from_hook_arg(0), ExecutionKind::Strong);
+        assert_eq!(ExecutionKind::from_hook_arg(1), ExecutionKind::Weak);
+        assert_eq!(ExecutionKind::from_hook_arg(2), ExecutionKind::AgainAsWeak);
+        let callback = CallbackCtx {
+            outcome: crate::EmitOutcome::from_cbak_arg(0),
+        };
+        assert_eq!(callback.execution(), ExecutionKind::Callback);
+        assert!(!callback.execution().is_strong());
+    }
+}
@@ -0,0 +1,313 @@
+//! Typed events traced and kept in a rolling log in hook state, for indexers off the ledger.
+//!
+//! An [Event] is a small binary record: a tag of up to [EVENT_TAG_LEN] bytes, zero padded,
+//! followed by its fields, each encoded as a [RecordField] like the fields of a
+//! [Record](crate::Record). The [event!](crate::event!) macro builds one and writes it to
+//! the trace log as [EVENT_TRACE_MSG] with the record in hex, and optionally appends it to
+//! an [EventLog] in the state of the hook, so indexers that can't follow the debug stream
+//! can read the latest events from the ledger.
+//!
+//! An event log keeps the last `capacity` events in a ring of state entries, each the
+//! sequence number of the event, 4 bytes big endian, the ledger it was logged in, and the
+//! record. The ring takes `capacity` state entries of the hook account's reserve, and one
+//! more for the next sequence number.
+//!
+//! # Example
+//! ```
+//! const PAYOUTS: EventLog = EventLog::new(b"payouts", 16);
+//!
+//! // traced only
+//! event!(b"received", sender, Drops(otxn_drops()?))?;
+//! // traced and logged
+//! event!(log: PAYOUTS, b"payout", destination, amount)?;
+//! ```
+
+use crate::access::{padded_name, state_key, STATE_KEY_SUFFIX_LEN};
+use crate::api::*;
+use crate::table::RecordField;
+
+/// Maximum length of the tag of an [Event]
+pub const EVENT_TAG_LEN: usize = 8;
+
+/// Maximum length of an [Event], with its tag
+pub const EVENT_MAX_LEN: usize = 128;
+
+/// Message of the trace lines written for events, followed by the record in hex
+pub const EVENT_TRACE_MSG: &[u8] = b"event";
+
+/// Maximum length of the name of an [EventLog], which is laid out in state keys like
+/// the name of an [AccessList](crate::AccessList)
+pub const EVENT_LOG_NAME_MAX_LEN: usize = crate::ACCESS_LIST_NAME_MAX_LEN;
+
+// What a logged event starts with: its sequence number and ledger
+const LOGGED_HEADER_LEN: usize = 8;
+
+/// Trace an event made of a tag and fields, and optionally append it to an [EventLog].
+///
+/// The tag is a byte string of up to [EVENT_TAG_LEN] bytes, checked at compile time, and
+/// the fields are anything that implements [RecordField]. Evaluates to the [Result] of
+/// [Event::trace], or of [EventLog::append] with the sequence number of the event in the
+/// log, which fail with [HookError::TooBig] if the fields don't fit in [EVENT_MAX_LEN]
+/// bytes.
+///
+/// # Example
+/// ```
+/// event!(b"payout", destination, amount)?;
+/// event!(log: PAYOUTS, b"payout", destination, amount)?;
+/// ```
+#[macro_export]
+macro_rules! event {
+    (log: $log:expr, $tag:expr $(, $field:expr)* $(,)?) => {{
+        const TAG: [u8; $crate::EVENT_TAG_LEN] = $crate::event_tag($tag);
+        let mut event = $crate::Event::new(TAG);
+        $(event.field(&$field);)*
+        match event.trace() {
+            $crate::Result::Ok(()) => $crate::EventLog::append(&$log, &event),
+            $crate::Result::Err(err) => $crate::Result::Err(err),
+        }
+    }};
+    ($tag:expr $(, $field:expr)* $(,)?) => {{
+        const TAG: [u8; $crate::EVENT_TAG_LEN] = $crate::event_tag($tag);
+        let mut event = $crate::Event::new(TAG);
+        $(event.field(&$field);)*
+        event.trace()
+    }};
+}
+
+/// `tag` padded with zeros, for [Event::new].
+///
+/// Use it for the tag constants of a hook: `tag` is copied with a loop the guard checker
+/// would reject at runtime, and a tag longer than [EVENT_TAG_LEN] is a compile error there.
+#[inline(always)]
+pub const fn event_tag(tag: &[u8]) -> [u8; EVENT_TAG_LEN] {
+    assert!(tag.len() <= EVENT_TAG_LEN, "event tag is too long");
+
+    let mut padded = [0; EVENT_TAG_LEN];
+    let mut i = 0;
+    while i < tag.len() {
+        padded[i] = tag[i];
+        i += 1;
+    }
+
+    padded
+}
+
+/// An event record being built, usually with [event!](crate::event!)
+#[derive(Clone, Copy)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct Event {
+    bytes: [u8; EVENT_MAX_LEN],
+    len: usize,
+    truncated: bool,
+}
+
+impl Event {
+    /// An event with a tag made with [event_tag] and no fields yet
+    #[inline(always)]
+    pub fn new(tag: [u8; EVENT_TAG_LEN]) -> Self {
+        let mut event = Self {
+            bytes: [0; EVENT_MAX_LEN],
+            len: EVENT_TAG_LEN,
+            truncated: false,
+        };
+        RecordField::write_to(&tag, &mut event.bytes[..EVENT_TAG_LEN]);
+
+        event
+    }
+
+    /// Append a field, which is dropped if it doesn't fit anymore
+    #[inline(always)]
+    pub fn field<F: RecordField>(&mut self, field: &F) {
+        if self.len + F::LEN > EVENT_MAX_LEN {
+            self.truncated = true;
+            return;
+        }
+
+        field.write_to(&mut self.bytes[self.len..self.len + F::LEN]);
+        self.len += F::LEN;
+    }
+
+    /// The record: the tag, then the fields
+    #[inline(always)]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+
+    /// Write the event to the trace log, as [EVENT_TRACE_MSG] and the record in hex.
+    ///
+    /// Fails with [HookError::TooBig] without tracing if a field was dropped. Does
+    /// nothing else if the `trace` feature is disabled.
+    #[inline(always)]
+    pub fn trace(&self) -> Result<()> {
+        if self.truncated {
+            return Err(HookError::TooBig);
+        }
+
+        match trace(EVENT_TRACE_MSG, self.as_bytes(), DataRepr::AsHex) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// The last events of a hook, kept in its state under a name of its own
+#[derive(Clone, Copy)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct EventLog {
+    name: [u8; EVENT_LOG_NAME_MAX_LEN],
+    capacity: u32,
+}
+
+// A logged event, of which the record takes up the first bytes of `record`
+#[repr(C)]
+struct LoggedEvent {
+    sequence: [u8; 4],
+    ledger_seq: [u8; 4],
+    record: [u8; EVENT_MAX_LEN],
+}
+
+impl EventLog {
+    /// A log named `name`, keeping the last `capacity` events.
+    ///
+    /// Logs are declared as constants, which fail to compile if `name` is longer than
+    /// [EVENT_LOG_NAME_MAX_LEN] or `capacity` is 0 or [u32::MAX], the index kept for the
+    /// next sequence number.
+    #[inline(always)]
+    pub const fn new(name: &[u8], capacity: u32) -> Self {
+        assert!(
+            capacity > 0 && capacity < u32::MAX,
+            "event log capacity must be 1 to u32::MAX - 1"
+        );
+
+        Self {
+            name: padded_name(name),
+            capacity,
+        }
+    }
+
+    /// The state key of the event with sequence number `sequence`, as long as it is one
+    /// of the last `capacity` events
+    #[inline(always)]
+    pub fn key(&self, sequence: u32) -> [u8; STATE_KEY_LEN] {
+        self.slot_key(sequence % self.capacity)
+    }
+
+    /// The sequence number the next event will get, 0 for a log without events
+    #[inline(always)]
+    pub fn next_sequence(&self) -> Result<u32> {
+        match state::<4>(&self.slot_key(u32::MAX)) {
+            Ok(sequence) => Ok(u32::from_be_bytes(sequence)),
+            Err(HookError::DoesntExist) => Ok(0),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Append `event`, replacing the oldest one if the log is full, and return its
+    /// sequence number.
+    ///
+    /// Fails with [HookError::TooBig] if a field of the event was dropped.
+    #[inline(always)]
+    pub fn append(&self, event: &Event) -> Result<u32> {
+        if event.truncated {
+            return Err(HookError::TooBig);
+        }
+
+        let sequence = self.next_sequence()?;
+        let logged = LoggedEvent {
+            sequence: sequence.to_be_bytes(),
+            ledger_seq: ledger_seq().0.to_be_bytes(),
+            record: event.bytes,
+        };
+        let bytes = unsafe {
+            core::mem::transmute::<LoggedEvent, [u8; LOGGED_HEADER_LEN + EVENT_MAX_LEN]>(logged)
+        };
+        state_set(&bytes[..LOGGED_HEADER_LEN + event.len], &self.key(sequence))?;
+
+        match state_set(
+            &sequence.wrapping_add(1).to_be_bytes(),
+            &self.slot_key(u32::MAX),
+        ) {
+            Ok(_) => Ok(sequence),
+            Err(err) => Err(err),
+        }
+    }
+
+    // The state key of the slot at `index` in the ring, or of the next sequence number
+    // for u32::MAX: the name, then the index, then zeros
+    #[inline(always)]
+    fn slot_key(&self, index: u32) -> [u8; STATE_KEY_LEN] {
+        let [i0, i1, i2, i3] = index.to_be_bytes();
+        let mut suffix = [0; STATE_KEY_SUFFIX_LEN];
+        suffix[0] = i0;
+        suffix[1] = i1;
+        suffix[2] = i2;
+        suffix[3] = i3;
+        state_key(&self.name, suffix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec::Vec;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::fixtures::DESTINATION;
+    use crate::mock;
+
+    const PAYOUTS: EventLog = EventLog::new(b"payouts", 2);
+
+    fn payout_record(drops: u64) -> Vec<u8> {
+        let mut record = b"payout\0\0".to_vec();
+        record.extend_from_slice(DESTINATION.as_bytes());
+        record.extend_from_slice(&drops.to_be_bytes());
+        record
+    }
+
+    #[wasm_bindgen_test]
+    fn traces_events() {
+        mock::reset();
+        assert!(crate::event!(b"payout", DESTINATION, Drops(1000)).is_ok());
+
+        let mut line = b"event ".to_vec();
+        line.extend_from_slice(&payout_record(1000));
+        assert_eq!(mock::traces(), [line]);
+        assert!(matches!(PAYOUTS.next_sequence(), Ok(0)));
+    }
+
+    #[wasm_bindgen_test]
+    fn keeps_the_last_events_in_state() {
+        mock::reset();
+        mock::set_ledger_seq(7);
+        for drops in [1, 2, 3] {
+            assert!(crate::event!(log: PAYOUTS, b"payout", DESTINATION, Drops(drops)).is_ok());
+        }
+
+        assert_eq!(mock::traces().len(), 3);
+        assert!(matches!(PAYOUTS.next_sequence(), Ok(3)));
+        // the first event was replaced by the third
+        let mut logged = [2u32.to_be_bytes(), 7u32.to_be_bytes()].concat();
+        logged.extend_from_slice(&payout_record(3));
+        assert_eq!(mock::state(&PAYOUTS.key(2)).unwrap(), logged);
+        assert_eq!(PAYOUTS.key(0), PAYOUTS.key(2));
+        assert_eq!(
+            &mock::state(&PAYOUTS.key(1)).unwrap()[..4],
+            &1u32.to_be_bytes()
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn rejects_events_that_dont_fit() {
+        mock::reset();
+        let mut event = Event::new(event_tag(b"big"));
+        for _ in 0..7 {
+            event.field(&DESTINATION);
+        }
+        assert_eq!(event.as_bytes().len(), EVENT_TAG_LEN + 6 * ACC_ID_LEN);
+        assert!(matches!(event.trace(), Err(HookError::TooBig)));
+        assert!(matches!(PAYOUTS.append(&event), Err(HookError::TooBig)));
+        assert!(mock::traces().is_empty());
+    }
+}
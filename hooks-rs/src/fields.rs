@@ -0,0 +1,369 @@
+//! Borrowed views of the fields of the originating transaction.
+//!
+//! [otxn_field] returns a new array for every field, which a hook reading many fields
+//! then moves around its stack. [OtxnFields] instead has the host write the fields one
+//! after the other into a single scratch buffer of the caller, and hands out
+//! [AccountView]s, [AmountView]s and byte slices borrowing from it. The views stay valid
+//! while more fields are read, since every field gets its own part of the buffer.
+//!
+//! # Example
+//! ```
+//! let mut scratch = [MaybeUninit::uninit(); 2 * ACC_ID_LEN + AMOUNT_LEN];
+//! let mut fields = OtxnFields::new(&mut scratch);
+//!
+//! let sender = fields.account(FieldId::Account)?;
+//! let destination = fields.account(FieldId::Destination)?;
+//! let amount = fields.amount(FieldId::Amount)?;
+//!
+//! if destination == hook_account()? && amount.drops().is_some_and(|drops| drops < 1000) {
+//!     rollback(b"payment too small", sender.as_account_id()[0] as _);
+//! }
+//! ```
+//!
+//! # Reading from a slot
+//! Every [otxn_field] call looks the field up in the originating transaction again.
+//! [OtxnSlot] puts the transaction into a slot once with [otxn_slot] and reads each field
+//! from that slot with [slot_subfield], which is cheaper for hooks inspecting many fields.
+//! [OtxnFields::from_slot] reads the fields for the views this way.
+//!
+//! ```
+//! let mut otxn = OtxnSlot::load()?;
+//! let invoice_id = otxn.field::<HASH_LEN>(FieldId::InvoiceID)?;
+//!
+//! let mut scratch = [MaybeUninit::uninit(); 2 * ACC_ID_LEN];
+//! let mut fields = OtxnFields::from_slot(&mut otxn, &mut scratch);
+//! let sender = fields.account(FieldId::Account)?;
+//! let destination = fields.account(FieldId::Destination)?;
+//! ```
+
+use core::mem::MaybeUninit;
+
+use crate::api::*;
+use crate::c;
+
+/// Byte length of an amount in XRP
+pub const XRP_AMOUNT_LEN: usize = 8;
+
+/// Reads fields of the originating transaction into a scratch buffer, see the
+/// [module](self) documentation
+pub struct OtxnFields<'a> {
+    free: &'a mut [MaybeUninit<u8>],
+    // slot the fields are read from, instead of with `otxn_field`
+    slot: Option<&'a mut OtxnSlot>,
+}
+
+impl<'a> OtxnFields<'a> {
+    /// Read fields into `scratch`, which has to be large enough for all of them
+    #[inline(always)]
+    pub fn new(scratch: &'a mut [MaybeUninit<u8>]) -> Self {
+        Self {
+            free: scratch,
+            slot: None,
+        }
+    }
+
+    /// Read fields from the originating transaction in `otxn` into `scratch`
+    #[inline(always)]
+    pub fn from_slot(otxn: &'a mut OtxnSlot, scratch: &'a mut [MaybeUninit<u8>]) -> Self {
+        Self {
+            free: scratch,
+            slot: Some(otxn),
+        }
+    }
+
+    /// Bytes of the scratch buffer left for more fields
+    #[inline(always)]
+    pub fn remaining(&self) -> usize {
+        self.free.len()
+    }
+
+    /// Read a field of at most `max_len` bytes, serialized the way [otxn_field] does.
+    ///
+    /// Returns [HookError::TooSmall] if the field is longer than `max_len` or than what is
+    /// left of the scratch buffer.
+    #[inline(always)]
+    pub fn bytes(&mut self, field_id: FieldId, max_len: usize) -> Result<&'a [u8]> {
+        self.read(field_id, max_len, |_| true)
+    }
+
+    /// Read an account ID field, such as [FieldId::Account] or [FieldId::Destination].
+    ///
+    /// Returns [HookError::InvalidArgument] if the field is not 20 bytes long.
+    #[inline(always)]
+    pub fn account(&mut self, field_id: FieldId) -> Result<AccountView<'a>> {
+        let bytes = self.read(field_id, ACC_ID_LEN, |bytes| bytes.len() == ACC_ID_LEN)?;
+
+        match bytes.try_into() {
+            core::result::Result::Ok(bytes) => Ok(AccountView(AccountId::from_bytes_ref(bytes))),
+            core::result::Result::Err(_) => Err(HookError::InvalidArgument),
+        }
+    }
+
+    /// Read an amount field, such as [FieldId::Amount] or [FieldId::Fee].
+    ///
+    /// Returns [HookError::InvalidArgument] if the field is neither an amount in XRP nor
+    /// an issued amount.
+    #[inline(always)]
+    pub fn amount(&mut self, field_id: FieldId) -> Result<AmountView<'a>> {
+        let bytes = self.read(field_id, AMOUNT_LEN, |bytes| match bytes.len() {
+            XRP_AMOUNT_LEN => bytes[0] & 0x80 == 0,
+            AMOUNT_LEN => bytes[0] & 0x80 != 0,
+            _ => false,
+        })?;
+
+        Ok(AmountView(bytes))
+    }
+
+    // Has the host write the field to the start of the free part of the buffer, and takes
+    // it out of the free part if `is_valid` accepts it
+    #[inline(always)]
+    fn read(
+        &mut self,
+        field_id: FieldId,
+        max_len: usize,
+        is_valid: impl FnOnce(&[u8]) -> bool,
+    ) -> Result<&'a [u8]> {
+        let free = core::mem::take(&mut self.free);
+        let write_len = max_len.min(free.len());
+        let result: Result<u64> = match &mut self.slot {
+            Some(otxn) => otxn.write_field(field_id, free.as_mut_ptr(), write_len),
+            None => unsafe {
+                c::otxn_field(
+                    host_ptr(free.as_mut_ptr()),
+                    write_len as u32,
+                    field_id as u32,
+                )
+                .into()
+            },
+        };
+        let len = match result {
+            Ok(len) => (len as usize).min(write_len),
+            Err(err) => {
+                self.free = free;
+                return Err(err);
+            }
+        };
+
+        // SAFETY: the host wrote the first `len` bytes, and `MaybeUninit<u8>` has the
+        // layout of `u8`
+        let written = unsafe { &*(&free[..len] as *const [MaybeUninit<u8>] as *const [u8]) };
+        if !is_valid(written) {
+            self.free = free;
+            return Err(HookError::InvalidArgument);
+        }
+
+        let (field, rest) = free.split_at_mut(len);
+        self.free = rest;
+        // SAFETY: as above
+        Ok(unsafe { &*(field as *const [MaybeUninit<u8>] as *const [u8]) })
+    }
+}
+
+/// The originating transaction in a slot, see [reading from a slot](self#reading-from-a-slot)
+// Not Copy on purpose: a copy could clear the slots while the original still reads from them.
+#[allow(missing_copy_implementations)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct OtxnSlot {
+    otxn_slot_no: u32,
+    // slot every field is put into in turn, 0 until the first field is read
+    field_slot_no: u32,
+}
+
+impl OtxnSlot {
+    /// Put the originating transaction into a free slot.
+    ///
+    /// The fields read take a second slot. Both stay occupied until [OtxnSlot::clear].
+    #[inline(always)]
+    pub fn load() -> Result<Self> {
+        let otxn_slot_no = otxn_slot(0)? as u32;
+
+        Ok(Self {
+            otxn_slot_no,
+            field_slot_no: 0,
+        })
+    }
+
+    /// Read a field of at most `LEN` bytes, serialized the way [otxn_field] does
+    #[inline(always)]
+    pub fn field<const LEN: usize>(&mut self, field_id: FieldId) -> Result<[u8; LEN]> {
+        init_buffer_mut(|buffer_mut_ptr| self.write_field(field_id, buffer_mut_ptr, LEN))
+    }
+
+    /// Free the slots of the transaction and of its fields
+    #[inline(always)]
+    pub fn clear(self) {
+        if self.field_slot_no != 0 {
+            let _ = slot_clear(self.field_slot_no);
+        }
+        let _ = slot_clear(self.otxn_slot_no);
+    }
+
+    // Puts the field into the field slot and has the host write it to `write_ptr`
+    #[inline(always)]
+    fn write_field(
+        &mut self,
+        field_id: FieldId,
+        write_ptr: *mut MaybeUninit<u8>,
+        write_len: usize,
+    ) -> Result<u64> {
+        self.field_slot_no = slot_subfield(self.otxn_slot_no, field_id, self.field_slot_no)? as u32;
+
+        unsafe { c::slot(host_ptr(write_ptr), write_len as u32, self.field_slot_no).into() }
+    }
+}
+
+/// An account ID read by [OtxnFields::account]
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct AccountView<'a>(&'a AccountId);
+
+impl<'a> AccountView<'a> {
+    /// The account ID, borrowed from the scratch buffer
+    #[inline(always)]
+    pub fn as_account_id(&self) -> &'a AccountId {
+        self.0
+    }
+}
+
+impl PartialEq<AccountId> for AccountView<'_> {
+    #[inline(always)]
+    fn eq(&self, other: &AccountId) -> bool {
+        crate::utils::is_buffer_equal(&self.0 .0, &other.0)
+    }
+}
+
+/// An amount read by [OtxnFields::amount], either 8 bytes in XRP or 48 bytes of an issued
+/// amount
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct AmountView<'a>(&'a [u8]);
+
+impl<'a> AmountView<'a> {
+    /// Whether the amount is in XRP
+    #[inline(always)]
+    pub fn is_xrp(&self) -> bool {
+        self.0.len() == XRP_AMOUNT_LEN
+    }
+
+    /// The amount in drops, `None` for issued amounts
+    #[inline(always)]
+    pub fn drops(&self) -> Option<u64> {
+        let amount: &[u8; XRP_AMOUNT_LEN] = self.0.try_into().ok()?;
+
+        // the second highest bit marks positive amounts
+        Some(u64::from_be_bytes(*amount) & 0x3FFF_FFFF_FFFF_FFFF)
+    }
+
+    /// The currency code of an issued amount, `None` for amounts in XRP
+    #[inline(always)]
+    pub fn currency(&self) -> Option<&'a CurrencyCode> {
+        self.0.get(8..28)?.try_into().ok()
+    }
+
+    /// The issuer of an issued amount, `None` for amounts in XRP
+    #[inline(always)]
+    pub fn issuer(&self) -> Option<&'a AccountId> {
+        let issuer = self.0.get(28..AMOUNT_LEN)?.try_into().ok()?;
+
+        Some(AccountId::from_bytes_ref(issuer))
+    }
+
+    /// The serialized amount, borrowed from the scratch buffer
+    #[inline(always)]
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::mock;
+
+    const SENDER: AccountId = AccountId([1; ACC_ID_LEN]);
+    const DESTINATION: AccountId = AccountId([2; ACC_ID_LEN]);
+
+    #[wasm_bindgen_test]
+    fn reads_fields_into_one_buffer() {
+        mock::reset();
+        mock::set_otxn_field(FieldId::Account, SENDER.as_bytes());
+        mock::set_otxn_field(FieldId::Destination, DESTINATION.as_bytes());
+        mock::set_otxn_field(
+            FieldId::Amount,
+            &(0x4000_0000_0000_0000u64 | 1000).to_be_bytes(),
+        );
+        let mut issued = [0xD4; AMOUNT_LEN];
+        issued[8..28].copy_from_slice(&[3; CURRENCY_CODE_SIZE]);
+        issued[28..].copy_from_slice(&[4; ACC_ID_LEN]);
+        mock::set_otxn_field(FieldId::SendMax, &issued);
+
+        let mut scratch = [MaybeUninit::uninit(); 2 * ACC_ID_LEN + 56];
+        let mut fields = OtxnFields::new(&mut scratch);
+        let sender = fields.account(FieldId::Account).unwrap();
+        let destination = fields.account(FieldId::Destination).unwrap();
+        let amount = fields.amount(FieldId::Amount).unwrap();
+        let send_max = fields.amount(FieldId::SendMax).unwrap();
+
+        assert_eq!(fields.remaining(), 0);
+        assert_eq!(sender, SENDER);
+        assert_eq!(destination.as_account_id(), &DESTINATION);
+        assert_eq!(amount.drops(), Some(1000));
+        assert!(amount.is_xrp() && amount.currency().is_none());
+        assert_eq!(send_max.drops(), None);
+        assert_eq!(send_max.currency(), Some(&[3; CURRENCY_CODE_SIZE]));
+        assert_eq!(send_max.issuer(), Some(&AccountId([4; ACC_ID_LEN])));
+    }
+
+    #[wasm_bindgen_test]
+    fn reads_fields_from_one_slot() {
+        mock::reset();
+        mock::set_otxn_field(FieldId::Account, SENDER.as_bytes());
+        mock::set_otxn_field(FieldId::Destination, DESTINATION.as_bytes());
+        mock::set_otxn_field(FieldId::InvoiceID, &[5; HASH_LEN]);
+
+        let mut otxn = OtxnSlot::load().unwrap();
+        assert!(
+            matches!(otxn.field::<HASH_LEN>(FieldId::InvoiceID), Ok(id) if id == [5; HASH_LEN])
+        );
+        assert!(matches!(
+            otxn.field::<ACC_ID_LEN>(FieldId::Amount),
+            Err(HookError::DoesntExist)
+        ));
+        let mut scratch = [MaybeUninit::uninit(); 2 * ACC_ID_LEN];
+        let mut fields = OtxnFields::from_slot(&mut otxn, &mut scratch);
+        assert_eq!(fields.account(FieldId::Account).unwrap(), SENDER);
+        assert_eq!(fields.account(FieldId::Destination).unwrap(), DESTINATION);
+
+        // the transaction and every field read took one slot each
+        assert!(matches!(slot_clear(3), Err(HookError::DoesntExist)));
+        otxn.clear();
+        assert!(matches!(otxn_slot(0), Ok(1)));
+    }
+
+    #[wasm_bindgen_test]
+    fn keeps_the_buffer_free_after_errors() {
+        mock::reset();
+        mock::set_otxn_field(FieldId::Account, SENDER.as_bytes());
+        mock::set_otxn_field(FieldId::Amount, &[0xD4; XRP_AMOUNT_LEN]);
+
+        let mut scratch = [MaybeUninit::uninit(); ACC_ID_LEN];
+        let mut fields = OtxnFields::new(&mut scratch);
+
+        assert!(matches!(
+            fields.account(FieldId::Destination),
+            Err(HookError::DoesntExist)
+        ));
+        assert!(matches!(
+            fields.amount(FieldId::Amount),
+            Err(HookError::InvalidArgument)
+        ));
+        assert!(matches!(
+            fields.bytes(FieldId::Account, 8),
+            Err(HookError::TooSmall)
+        ));
+        assert_eq!(fields.remaining(), ACC_ID_LEN);
+        assert_eq!(fields.account(FieldId::Account).unwrap(), SENDER);
+    }
+}
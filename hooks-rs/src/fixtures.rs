@@ -0,0 +1,142 @@
+//! Well-known accounts, currencies, keylets and transactions for examples and tests.
+//!
+//! Use these instead of writing out 20 byte literals, so that tests read as what the
+//! values stand for. [HOOK_ACCOUNT], [DESTINATION] and [ISSUER] are placeholders that
+//! don't belong to anyone, while the genesis account and the keylets are the same on
+//! every ledger.
+//!
+//! Only available in tests and with the `std` feature.
+//!
+//! # Example
+//! ```
+//! mock::reset();
+//! mock::set_hook_account(HOOK_ACCOUNT);
+//!
+//! let mut buffer = XrpPaymentBuilder::uninit_buffer();
+//! XrpPaymentBuilder::new(Drops(1000), &DESTINATION, None, None)
+//!     .build(&mut buffer)
+//!     .unwrap_line_number();
+//! ```
+
+use crate::api::*;
+use crate::asset::IssuedAsset;
+
+/// Placeholder account the hook is installed on
+pub const HOOK_ACCOUNT: AccountId = AccountId([1; ACC_ID_LEN]);
+
+/// Placeholder account that payments are sent to
+pub const DESTINATION: AccountId = AccountId([2; ACC_ID_LEN]);
+
+/// Placeholder account issuing [USD_ASSET]
+pub const ISSUER: AccountId = AccountId([3; ACC_ID_LEN]);
+
+/// rHb9CJAWyB4rj91VRWn96DkukG4bwdtyTh, the account holding all XAH in the genesis ledger
+pub const GENESIS_ACCOUNT: AccountId =
+    AccountId(crate::hex!("B5F762798A53D543A014CAF8B297CFF8F2F937E8"));
+
+/// rrrrrrrrrrrrrrrrrrrrrhoLvTp, the account ID zero, which is the issuer of XAH in
+/// amount fields and can't sign anything
+pub const ACCOUNT_ZERO: AccountId = AccountId([0; ACC_ID_LEN]);
+
+/// rrrrrrrrrrrrrrrrrrrrBZbvji, the account ID one, which can't sign anything either
+pub const ACCOUNT_ONE: AccountId =
+    AccountId(crate::hex!("0000000000000000000000000000000000000001"));
+
+/// The standard currency code of US dollars
+pub const USD: CurrencyCode = crate::currency!("USD");
+
+/// A non-standard currency code, the example for demurrage currencies in the XRPL docs
+pub const DEMURRAGE_XAU: CurrencyCode =
+    crate::currency!(hex "0158415500000000C1F76FF6ECB0BAC600000000");
+
+/// [USD] issued by [ISSUER]
+pub const USD_ASSET: IssuedAsset = IssuedAsset::new(USD, ISSUER);
+
+/// Keylet of the account root of [GENESIS_ACCOUNT]
+pub const GENESIS_ACCOUNT_KEYLET: Keylet = crate::hex!(concat!(
+    "0061",
+    "2B6AC232AA4C4BE41BF49D2459FA4A0347E1B543A4C92FCEE0821C0201E2E9A8",
+));
+
+/// Keylet of the fee settings of the ledger
+pub const FEES_KEYLET: Keylet = crate::hex!(concat!(
+    "0073",
+    "4BC50C9B0D8515D3EAAE1E74B29A95804346C491EE1A95BF25E4AAB854A6A651",
+));
+
+/// Keylet of the amendments enabled on the ledger
+pub const AMENDMENTS_KEYLET: Keylet = crate::hex!(concat!(
+    "0066",
+    "7DB0788C020F02780A673DC74757F23823FA3014C1866E72CC4CD8B226CD6EF4",
+));
+
+/// A signed `OfferCreate`, the example of the
+/// [serialization format](https://xrpl.org/serialization.html)
+pub const OFFER_CREATE: [u8; 220] = crate::hex!(concat!(
+    "120007",
+    "2200080000",
+    "24001ABED8",
+    "2A2380BF2C",
+    "2019001ABED7",
+    "64D55920AC9391400000000000000000000000000055534400000000000A20B3C85F482532A9578DBB3950B85CA06594D1",
+    "65400000037E11D600",
+    "68400000000000000A",
+    "732103EE83BB432547885C219634A1BC407A9DB0474145D69737D09CCDC63E1DEE7FE3",
+    "744630440220143759437C04F7B61F012563AFE90D8DAFC46E86035E1D965A9CED282C97D4CE02204CFD241E86F17E011298FC1A39B63386C74306A5DE047E213B0F29EFA4571C2C",
+    "8114DD76483FACDEE26E60D8A586BB58D09F27045C46",
+));
+
+/// Hash of [OFFER_CREATE]
+pub const OFFER_CREATE_HASH: Hash =
+    crate::hex!("73734B611DDA23D3F5F62E20A173B78AB8406AC5015094DA53F53D39B9EDB06C");
+
+/// rMBzp8CgpE441cp5PVyA9rpVV7oT8hP3ys, the account of [OFFER_CREATE]
+pub const OFFER_CREATE_ACCOUNT: AccountId =
+    AccountId(crate::hex!("DD76483FACDEE26E60D8A586BB58D09F27045C46"));
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::base58::{encode_account_id, R_ADDRESS_MAX_LEN};
+    use crate::decode::decode;
+
+    fn padded(r_address: &[u8]) -> [u8; R_ADDRESS_MAX_LEN] {
+        let mut buf = [0; R_ADDRESS_MAX_LEN];
+        buf[..r_address.len()].copy_from_slice(r_address);
+        buf
+    }
+
+    #[wasm_bindgen_test]
+    fn accounts_match_their_r_addresses() {
+        assert_eq!(
+            encode_account_id(&GENESIS_ACCOUNT),
+            padded(b"rHb9CJAWyB4rj91VRWn96DkukG4bwdtyTh")
+        );
+        assert_eq!(
+            encode_account_id(&ACCOUNT_ZERO),
+            padded(b"rrrrrrrrrrrrrrrrrrrrrhoLvTp")
+        );
+        assert_eq!(
+            encode_account_id(&ACCOUNT_ONE),
+            padded(b"rrrrrrrrrrrrrrrrrrrrBZbvji")
+        );
+        assert_eq!(
+            encode_account_id(&OFFER_CREATE_ACCOUNT),
+            padded(b"rMBzp8CgpE441cp5PVyA9rpVV7oT8hP3ys")
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn offer_create_is_from_its_account() {
+        let tx = decode(&OFFER_CREATE);
+
+        assert_eq!(tx.account(FieldId::Account), Some(OFFER_CREATE_ACCOUNT));
+        // TakerPays is in USD
+        assert_eq!(
+            tx.field(FieldId::TakerPays).map(|amount| &amount[8..28]),
+            Some(&USD[..])
+        );
+    }
+}
@@ -0,0 +1,514 @@
+//! Named flag sets for the `Flags` field of transactions.
+//!
+//! The flag sets are shared by the code that encodes the `Flags` of an emitted transaction
+//! and the code that reads them off the originating transaction, so both use the same
+//! named constants instead of bare `u32` masks.
+//!
+//! Every flag set is a newtype over the raw `u32`, combined with `|` and tested with
+//! [contains](TrustSetFlags::contains). Bits without a name are kept as they are, so
+//! reading the flags of a transaction and encoding them again loses nothing.
+//!
+//! # Example
+//! ```
+//! let flags = TrustSetFlags::from_bits(otxn_flags()?);
+//! if flags.contains(TrustSetFlags::SET_FREEZE) {
+//!     rollback(b"trust lines can't be frozen", 1);
+//! }
+//!
+//! txn_buffer.encode_flags(TrustSetFlags::FULLY_CANONICAL_SIG | TrustSetFlags::SET_NO_RIPPLE);
+//! ```
+
+use core::ops::{BitAnd, BitOr, BitOrAssign};
+
+use crate::api::*;
+use crate::c;
+
+// Defines a flag set named `$name` with a constant for every flag, together with the
+// universal `tfFullyCanonicalSig` for the flags of a transaction
+macro_rules! flags {
+    (
+        transaction
+        $(#[$meta:meta])*
+        $name:ident { $($flags:tt)* }
+    ) => {
+        flags! {
+            $(#[$meta])*
+            $name {
+                /// `tfFullyCanonicalSig`, which every transaction type accepts
+                FULLY_CANONICAL_SIG = c::tfCANONICAL;
+                $($flags)*
+            }
+        }
+    };
+    (
+        $(#[$meta:meta])*
+        $name:ident {
+            $(
+                $(#[$flag_meta:meta])*
+                $flag:ident = $bits:expr;
+            )*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, PartialEq, Eq)]
+        #[cfg_attr(any(test, feature = "std"), derive(Debug))]
+        #[repr(transparent)]
+        pub struct $name(pub u32);
+
+        impl $name {
+            /// No flags set
+            pub const EMPTY: Self = Self(0);
+
+            $(
+                $(#[$flag_meta])*
+                pub const $flag: Self = Self($bits);
+            )*
+
+            /// Every named flag of this set
+            pub const ALL: Self = Self(0 $(| $bits)*);
+
+            /// The flags of the raw `bits`, keeping the bits without a name
+            #[inline(always)]
+            pub const fn from_bits(bits: u32) -> Self {
+                Self(bits)
+            }
+
+            /// The raw bits, as encoded in the `Flags` field
+            #[inline(always)]
+            pub const fn bits(self) -> u32 {
+                self.0
+            }
+
+            /// Whether no flag is set
+            #[inline(always)]
+            pub const fn is_empty(self) -> bool {
+                self.0 == 0
+            }
+
+            /// Whether every flag of `other` is set
+            #[inline(always)]
+            pub const fn contains(self, other: Self) -> bool {
+                self.0 & other.0 == other.0
+            }
+
+            /// Whether any flag of `other` is set
+            #[inline(always)]
+            pub const fn intersects(self, other: Self) -> bool {
+                self.0 & other.0 != 0
+            }
+
+            /// The flags set in either
+            #[inline(always)]
+            pub const fn union(self, other: Self) -> Self {
+                Self(self.0 | other.0)
+            }
+
+            /// These flags without the flags of `other`
+            #[inline(always)]
+            pub const fn without(self, other: Self) -> Self {
+                Self(self.0 & !other.0)
+            }
+
+            /// The bits set that are not a flag of this set
+            #[inline(always)]
+            pub const fn unknown_bits(self) -> u32 {
+                self.0 & !Self::ALL.0
+            }
+        }
+
+        impl BitOr for $name {
+            type Output = Self;
+
+            #[inline(always)]
+            fn bitor(self, other: Self) -> Self {
+                self.union(other)
+            }
+        }
+
+        impl BitOrAssign for $name {
+            #[inline(always)]
+            fn bitor_assign(&mut self, other: Self) {
+                *self = self.union(other);
+            }
+        }
+
+        impl BitAnd for $name {
+            type Output = Self;
+
+            #[inline(always)]
+            fn bitand(self, other: Self) -> Self {
+                Self(self.0 & other.0)
+            }
+        }
+
+        impl From<$name> for u32 {
+            #[inline(always)]
+            fn from(flags: $name) -> u32 {
+                flags.0
+            }
+        }
+    };
+}
+
+flags! {
+    transaction
+    /// Flags of a `Payment` transaction.
+    PaymentFlags {
+        /// `tfNoRippleDirect`: don't use the default path, only the paths of the payment
+        NO_RIPPLE_DIRECT = 0x0001_0000;
+        /// `tfPartialPayment`: deliver less than `Amount` rather than fail. The amount
+        /// delivered is in the `DeliveredAmount` of the metadata, never trust `Amount`.
+        PARTIAL_PAYMENT = 0x0002_0000;
+        /// `tfLimitQuality`: only take paths at least as good as `Amount` for `SendMax`
+        LIMIT_QUALITY = 0x0004_0000;
+    }
+}
+
+flags! {
+    transaction
+    /// Flags of a `TrustSet` transaction, which creates or changes a trust line.
+    TrustSetFlags {
+        /// `tfSetfAuth`: authorize the other party to hold currency issued by this account
+        SET_AUTH = 0x0001_0000;
+        /// `tfSetNoRipple`: block rippling between this trust line and the account's others
+        SET_NO_RIPPLE = 0x0002_0000;
+        /// `tfClearNoRipple`: allow rippling again
+        CLEAR_NO_RIPPLE = 0x0004_0000;
+        /// `tfSetFreeze`: freeze the trust line
+        SET_FREEZE = 0x0010_0000;
+        /// `tfClearFreeze`: unfreeze the trust line
+        CLEAR_FREEZE = 0x0020_0000;
+    }
+}
+
+flags! {
+    transaction
+    /// Flags of an `AccountSet` transaction, the older way of setting some of the
+    /// [AccountRootFlags]. Newer settings only have an [AccountSetFlag].
+    AccountSetFlags {
+        /// `tfRequireDestTag`: require a destination tag on payments to the account
+        REQUIRE_DEST_TAG = 0x0001_0000;
+        /// `tfOptionalDestTag`: stop requiring a destination tag
+        OPTIONAL_DEST_TAG = 0x0002_0000;
+        /// `tfRequireAuth`: require authorization to hold currency issued by the account
+        REQUIRE_AUTH = 0x0004_0000;
+        /// `tfOptionalAuth`: stop requiring authorization
+        OPTIONAL_AUTH = 0x0008_0000;
+        /// `tfDisallowXRP`: ask senders not to send XRP to the account
+        DISALLOW_XRP = 0x0010_0000;
+        /// `tfAllowXRP`: allow XRP again
+        ALLOW_XRP = 0x0020_0000;
+    }
+}
+
+flags! {
+    /// `Flags` of an `AccountRoot` ledger object, the settings of an account.
+    ///
+    /// Read them with [account_root_flags] or, for an account root already in a slot, with
+    /// [AccountRootFlags::from_slot].
+    AccountRootFlags {
+        /// `lsfPasswordSpent`: the free key reset has been used
+        PASSWORD_SPENT = 0x0001_0000;
+        /// `lsfRequireDestTag`: payments to the account need a destination tag
+        REQUIRE_DEST_TAG = 0x0002_0000;
+        /// `lsfRequireAuth`: holding currency issued by the account needs authorization
+        REQUIRE_AUTH = 0x0004_0000;
+        /// `lsfDisallowXRP`: the account asks not to be sent XRP
+        DISALLOW_XRP = 0x0008_0000;
+        /// `lsfDisableMaster`: the master key can't sign for the account
+        DISABLE_MASTER = 0x0010_0000;
+        /// `lsfNoFreeze`: the account gave up freezing its trust lines
+        NO_FREEZE = 0x0020_0000;
+        /// `lsfGlobalFreeze`: every trust line of the account is frozen
+        GLOBAL_FREEZE = 0x0040_0000;
+        /// `lsfDefaultRipple`: rippling is enabled by default on the trust lines of the account
+        DEFAULT_RIPPLE = 0x0080_0000;
+        /// `lsfDepositAuth`: only preauthorized accounts can send funds to the account
+        DEPOSIT_AUTH = 0x0100_0000;
+        /// `lsfTshCollect`: the account collects hook fees as a weak transactional stakeholder
+        TSH_COLLECT = 0x0200_0000;
+        /// `lsfDisallowIncomingNFTokenOffer`: the account refuses incoming NFT offers
+        DISALLOW_INCOMING_NFTOKEN_OFFER = 0x0400_0000;
+        /// `lsfDisallowIncomingCheck`: the account refuses incoming checks
+        DISALLOW_INCOMING_CHECK = 0x0800_0000;
+        /// `lsfDisallowIncomingPayChan`: the account refuses incoming payment channels
+        DISALLOW_INCOMING_PAYCHAN = 0x1000_0000;
+        /// `lsfDisallowIncomingTrustline`: the account refuses incoming trust lines
+        DISALLOW_INCOMING_TRUSTLINE = 0x2000_0000;
+        /// `lsfURITokenIssuer`: the account has issued URI tokens
+        URITOKEN_ISSUER = 0x4000_0000;
+        /// `lsfDisallowIncomingRemit`: the account refuses incoming remits
+        DISALLOW_INCOMING_REMIT = 0x8000_0000;
+    }
+}
+
+flags! {
+    transaction
+    /// Flags of an `OfferCreate` transaction, which places an offer on the DEX.
+    ///
+    /// An offer can't be both [IMMEDIATE_OR_CANCEL](Self::IMMEDIATE_OR_CANCEL) and
+    /// [FILL_OR_KILL](Self::FILL_OR_KILL).
+    OfferCreateFlags {
+        /// `tfPassive`: don't consume offers that match this one exactly
+        PASSIVE = 0x0001_0000;
+        /// `tfImmediateOrCancel`: take what can be filled now and never rest on the book
+        IMMEDIATE_OR_CANCEL = 0x0002_0000;
+        /// `tfFillOrKill`: fill the whole offer now or not at all
+        FILL_OR_KILL = 0x0004_0000;
+        /// `tfSell`: exchange all of `TakerGets`, even for more than `TakerPays`
+        SELL = 0x0008_0000;
+    }
+}
+
+flags! {
+    transaction
+    /// Flags of a `PaymentChannelClaim` transaction.
+    PaymentChannelClaimFlags {
+        /// `tfRenew`: clear the expiration of the channel, only for its source
+        RENEW = 0x0001_0000;
+        /// `tfClose`: close the channel, right away if it is empty or claimed by its destination
+        CLOSE = 0x0002_0000;
+    }
+}
+
+impl AccountRootFlags {
+    /// The flags of the `AccountRoot` in `slot_no`, such as one put there by [slot_set].
+    ///
+    /// Takes a slot to read the `Flags` from, which is freed again.
+    #[inline(always)]
+    pub fn from_slot(slot_no: u32) -> Result<Self> {
+        let flags_slot_no = match slot_subfield(slot_no, FieldId::Flags, 0) {
+            Ok(flags_slot_no) => flags_slot_no as u32,
+            // `Flags` is left out of an object when it is 0
+            Err(HookError::DoesntExist) => return Ok(Self::EMPTY),
+            Err(err) => return Err(err),
+        };
+        let mut flags = [0; 4];
+        let result = slot(&mut flags, flags_slot_no);
+        let _ = slot_clear(flags_slot_no);
+
+        match result {
+            Ok(_) => Ok(Self(u32::from_be_bytes(flags))),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// The flags of the `AccountRoot` of `account`, such as the hook account or the
+/// destination of a payment about to be emitted.
+///
+/// Returns [HookError::DoesntExist] if the account doesn't exist.
+///
+/// # Example
+/// ```
+/// let flags = account_root_flags(&destination)?;
+/// if flags.contains(AccountRootFlags::REQUIRE_DEST_TAG) && dest_tag.is_none() {
+///     rollback(b"destination needs a tag", 1);
+/// }
+/// ```
+#[inline(always)]
+pub fn account_root_flags(account: &AccountId) -> Result<AccountRootFlags> {
+    let keylet = util_keylet(KeyletType::Account(&account.0))?;
+    let slot_no = slot_set(&keylet, 0)? as u32;
+    let flags = AccountRootFlags::from_slot(slot_no);
+    let _ = slot_clear(slot_no);
+
+    flags
+}
+
+/// A setting of an account that an `AccountSet` transaction turns on with its `SetFlag`
+/// or off with its `ClearFlag`, the `asf` flags.
+#[allow(missing_docs)]
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub enum AccountSetFlag {
+    RequireDest = 1,
+    RequireAuth = 2,
+    DisallowXrp = 3,
+    DisableMaster = 4,
+    AccountTxnId = 5,
+    NoFreeze = 6,
+    GlobalFreeze = 7,
+    DefaultRipple = 8,
+    DepositAuth = 9,
+    AuthorizedNFTokenMinter = 10,
+    TshCollect = 11,
+    DisallowIncomingNFTokenOffer = 12,
+    DisallowIncomingCheck = 13,
+    DisallowIncomingPayChan = 14,
+    DisallowIncomingTrustline = 15,
+    DisallowIncomingRemit = 16,
+}
+
+impl AccountSetFlag {
+    /// Every flag, in the order of their codes
+    pub const ALL: [AccountSetFlag; 16] = [
+        AccountSetFlag::RequireDest,
+        AccountSetFlag::RequireAuth,
+        AccountSetFlag::DisallowXrp,
+        AccountSetFlag::DisableMaster,
+        AccountSetFlag::AccountTxnId,
+        AccountSetFlag::NoFreeze,
+        AccountSetFlag::GlobalFreeze,
+        AccountSetFlag::DefaultRipple,
+        AccountSetFlag::DepositAuth,
+        AccountSetFlag::AuthorizedNFTokenMinter,
+        AccountSetFlag::TshCollect,
+        AccountSetFlag::DisallowIncomingNFTokenOffer,
+        AccountSetFlag::DisallowIncomingCheck,
+        AccountSetFlag::DisallowIncomingPayChan,
+        AccountSetFlag::DisallowIncomingTrustline,
+        AccountSetFlag::DisallowIncomingRemit,
+    ];
+
+    /// The flag with the given code, as in the `SetFlag` or `ClearFlag` field of a
+    /// serialized transaction
+    #[inline(always)]
+    pub const fn from_code(code: u32) -> Option<Self> {
+        // the codes are contiguous from 1
+        if code >= 1 && code <= Self::ALL.len() as u32 {
+            Some(Self::ALL[code as usize - 1])
+        } else {
+            None
+        }
+    }
+
+    /// The flag of the `AccountRoot` this setting turns on or off, `None` for the settings
+    /// kept in a field of their own, like the `AccountTxnID` or the `NFTokenMinter`
+    pub const fn account_root_flag(self) -> Option<AccountRootFlags> {
+        let flags = match self {
+            AccountSetFlag::RequireDest => AccountRootFlags::REQUIRE_DEST_TAG,
+            AccountSetFlag::RequireAuth => AccountRootFlags::REQUIRE_AUTH,
+            AccountSetFlag::DisallowXrp => AccountRootFlags::DISALLOW_XRP,
+            AccountSetFlag::DisableMaster => AccountRootFlags::DISABLE_MASTER,
+            AccountSetFlag::AccountTxnId | AccountSetFlag::AuthorizedNFTokenMinter => return None,
+            AccountSetFlag::NoFreeze => AccountRootFlags::NO_FREEZE,
+            AccountSetFlag::GlobalFreeze => AccountRootFlags::GLOBAL_FREEZE,
+            AccountSetFlag::DefaultRipple => AccountRootFlags::DEFAULT_RIPPLE,
+            AccountSetFlag::DepositAuth => AccountRootFlags::DEPOSIT_AUTH,
+            AccountSetFlag::TshCollect => AccountRootFlags::TSH_COLLECT,
+            AccountSetFlag::DisallowIncomingNFTokenOffer => {
+                AccountRootFlags::DISALLOW_INCOMING_NFTOKEN_OFFER
+            }
+            AccountSetFlag::DisallowIncomingCheck => AccountRootFlags::DISALLOW_INCOMING_CHECK,
+            AccountSetFlag::DisallowIncomingPayChan => AccountRootFlags::DISALLOW_INCOMING_PAYCHAN,
+            AccountSetFlag::DisallowIncomingTrustline => {
+                AccountRootFlags::DISALLOW_INCOMING_TRUSTLINE
+            }
+            AccountSetFlag::DisallowIncomingRemit => AccountRootFlags::DISALLOW_INCOMING_REMIT,
+        };
+
+        Some(flags)
+    }
+}
+
+impl From<AccountSetFlag> for u32 {
+    #[inline(always)]
+    fn from(flag: AccountSetFlag) -> u32 {
+        flag as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+
+    #[wasm_bindgen_test]
+    fn combines_and_tests_flags() {
+        let mut flags = TrustSetFlags::SET_NO_RIPPLE | TrustSetFlags::SET_FREEZE;
+        assert_eq!(flags.bits(), 0x0012_0000);
+        assert!(flags.contains(TrustSetFlags::SET_FREEZE));
+        assert!(!flags.contains(TrustSetFlags::SET_FREEZE | TrustSetFlags::SET_AUTH));
+        assert!(flags.intersects(TrustSetFlags::SET_FREEZE | TrustSetFlags::SET_AUTH));
+        assert_eq!(
+            flags.without(TrustSetFlags::SET_FREEZE),
+            TrustSetFlags::SET_NO_RIPPLE
+        );
+        assert!(TrustSetFlags::EMPTY.is_empty());
+
+        flags |= TrustSetFlags::FULLY_CANONICAL_SIG;
+        assert_eq!(u32::from(flags), 0x8012_0000);
+        assert_eq!(flags.unknown_bits(), 0);
+        assert_eq!(TrustSetFlags::from_bits(0x8000_0001).unknown_bits(), 1);
+    }
+
+    #[wasm_bindgen_test]
+    fn reads_the_flags_of_the_originating_transaction() {
+        crate::mock::reset();
+        assert!(matches!(otxn_flags(), Ok(0)));
+
+        crate::mock::set_otxn_field(FieldId::Flags, &0x8004_0000u32.to_be_bytes());
+        let flags = match otxn_flags() {
+            Ok(bits) => TrustSetFlags::from_bits(bits),
+            Err(_) => panic!("no flags"),
+        };
+        assert!(flags.contains(TrustSetFlags::CLEAR_NO_RIPPLE));
+        assert!(flags.contains(TrustSetFlags::FULLY_CANONICAL_SIG));
+        assert!(!flags.contains(TrustSetFlags::SET_NO_RIPPLE));
+    }
+
+    #[wasm_bindgen_test]
+    fn reads_offer_flags_off_a_transaction() {
+        let offer = crate::decode::decode(&crate::fixtures::OFFER_CREATE);
+        let flags = OfferCreateFlags::from_bits(offer.u32(FieldId::Flags).unwrap());
+        assert_eq!(flags, OfferCreateFlags::SELL);
+        assert!(!flags
+            .intersects(OfferCreateFlags::IMMEDIATE_OR_CANCEL | OfferCreateFlags::FILL_OR_KILL));
+
+        // the same bits mean different things for different transaction types
+        assert_eq!(
+            PaymentChannelClaimFlags::CLOSE.bits(),
+            TrustSetFlags::SET_NO_RIPPLE.bits()
+        );
+        assert_eq!(PaymentChannelClaimFlags::ALL.bits(), 0x8003_0000);
+    }
+
+    #[wasm_bindgen_test]
+    fn maps_account_set_flags_to_account_root_flags() {
+        for flag in AccountSetFlag::ALL {
+            assert_eq!(AccountSetFlag::from_code(flag.into()), Some(flag));
+        }
+        assert_eq!(AccountSetFlag::from_code(0), None);
+        assert_eq!(AccountSetFlag::from_code(17), None);
+
+        assert_eq!(
+            AccountSetFlag::RequireDest.account_root_flag(),
+            Some(AccountRootFlags::REQUIRE_DEST_TAG)
+        );
+        assert_eq!(
+            AccountSetFlag::DisallowIncomingRemit.account_root_flag(),
+            Some(AccountRootFlags::DISALLOW_INCOMING_REMIT)
+        );
+        assert_eq!(AccountSetFlag::AccountTxnId.account_root_flag(), None);
+        // every account root flag can be set or cleared, but the ones the ledger sets
+        let settable = AccountSetFlag::ALL
+            .iter()
+            .filter_map(|flag| flag.account_root_flag())
+            .fold(AccountRootFlags::EMPTY, |all, flag| all | flag);
+        assert_eq!(
+            settable,
+            AccountRootFlags::ALL
+                .without(AccountRootFlags::PASSWORD_SPENT)
+                .without(AccountRootFlags::URITOKEN_ISSUER)
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn reads_account_root_flags_from_a_slot() {
+        // the mock host slots the fields of the originating transaction only, which
+        // read the same as an account root with these flags
+        crate::mock::reset();
+        let slot_no = otxn_slot(0).unwrap_line_number() as u32;
+        assert!(matches!(
+            AccountRootFlags::from_slot(slot_no),
+            Ok(AccountRootFlags::EMPTY)
+        ));
+
+        let flags = AccountRootFlags::REQUIRE_DEST_TAG | AccountRootFlags::DISALLOW_INCOMING_REMIT;
+        crate::mock::set_otxn_field(FieldId::Flags, &flags.bits().to_be_bytes());
+        assert!(matches!(AccountRootFlags::from_slot(slot_no), Ok(read) if read == flags));
+    }
+}
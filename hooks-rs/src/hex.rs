@@ -0,0 +1,160 @@
+//! Hex encoding and decoding into caller provided buffers.
+//!
+//! These are useful for logging hashes with [trace](crate::trace) or parsing hex
+//! parameters passed in Invoke transactions. Loops are guarded, so they can be used
+//! anywhere in a hook.
+
+use crate::api::*;
+
+const UPPERCASE_DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+
+/// Encode bytes as uppercase hex into `out`, returning the number of bytes written.
+///
+/// Returns [HookError::TooSmall] if `out` is shorter than twice the length of `bytes`.
+///
+/// # Example
+/// ```
+/// let hash = sha512h(b"hooks-rs");
+/// let mut hash_hex = [0; 64];
+/// hex::encode_to(&hash, &mut hash_hex).unwrap_line_number();
+///
+/// let _ = trace(b"hash", &hash_hex, DataRepr::AsUTF8);
+/// ```
+#[inline(always)]
+pub fn encode_to(bytes: &[u8], out: &mut [u8]) -> Result<usize> {
+    let bytes_len = bytes.len();
+    if out.len() < bytes_len * 2 {
+        return Err(HookError::TooSmall);
+    }
+
+    // guarded loop
+    let mut i = 0;
+    while {
+        max_iter(bytes_len as u32 + 1);
+        i < bytes_len
+    } {
+        let byte = bytes[i];
+        out[i * 2] = UPPERCASE_DIGITS[(byte >> 4) as usize];
+        out[i * 2 + 1] = UPPERCASE_DIGITS[(byte & 0x0F) as usize];
+        i += 1;
+    }
+
+    Ok(bytes_len * 2)
+}
+
+/// Decode hex, either uppercase or lowercase, into `out`, returning the number of
+/// bytes written.
+///
+/// Returns [HookError::InvalidArgument] if the input has an odd length or contains a non-hex
+/// character, and [HookError::TooSmall] if `out` is shorter than half the length of the input.
+///
+/// # Example
+/// ```
+/// let amount_param = otxn_param::<16>(b"amount").unwrap_line_number();
+/// let mut amount = [0; 8];
+/// hex::decode_to(&amount_param, &mut amount).unwrap_line_number();
+///
+/// let drops = u64::from_be_bytes(amount);
+/// ```
+#[inline(always)]
+pub fn decode_to<T: AsRef<[u8]> + ?Sized>(hex: &T, out: &mut [u8]) -> Result<usize> {
+    let hex = hex.as_ref();
+    let hex_len = hex.len();
+    if hex_len % 2 != 0 {
+        return Err(HookError::InvalidArgument);
+    }
+    let decoded_len = hex_len / 2;
+    if out.len() < decoded_len {
+        return Err(HookError::TooSmall);
+    }
+
+    // guarded loop
+    let mut i = 0;
+    while {
+        max_iter(decoded_len as u32 + 1);
+        i < decoded_len
+    } {
+        let high = match nibble(hex[i * 2]) {
+            Some(nibble) => nibble,
+            None => return Err(HookError::InvalidArgument),
+        };
+        let low = match nibble(hex[i * 2 + 1]) {
+            Some(nibble) => nibble,
+            None => return Err(HookError::InvalidArgument),
+        };
+        out[i] = (high << 4) | low;
+        i += 1;
+    }
+
+    Ok(decoded_len)
+}
+
+/// Decode hex, either uppercase or lowercase, into an array at compile time, or `None`
+/// if the input is not exactly `2 * N` hex characters. Used by [hex!](crate::hex!).
+///
+/// Unlike [decode_to], the loop is not guarded, so use it in const contexts only.
+pub const fn decode_array<const N: usize>(hex: &[u8]) -> Option<[u8; N]> {
+    if hex.len() != N * 2 {
+        return None;
+    }
+
+    let mut bytes = [0; N];
+    let mut i = 0;
+    while i < N {
+        let (high, low) = match (nibble(hex[i * 2]), nibble(hex[i * 2 + 1])) {
+            (Some(high), Some(low)) => (high, low),
+            _ => return None,
+        };
+        bytes[i] = (high << 4) | low;
+        i += 1;
+    }
+
+    Some(bytes)
+}
+
+/// Decode a hex string literal into a byte array at compile time.
+///
+/// Fails to compile if the input has an odd length or contains a non-hex character.
+///
+/// # Example
+/// ```
+/// const NAMESPACE: [u8; 32] =
+///     hex!("CAFECAFECAFECAFECAFECAFECAFECAFECAFECAFECAFECAFECAFECAFECAFECAFE");
+/// ```
+#[macro_export]
+macro_rules! hex {
+    ($hex:expr) => {
+        const {
+            match $crate::hex::decode_array::<{ $hex.len() / 2 }>($hex.as_bytes()) {
+                ::core::option::Option::Some(bytes) => bytes,
+                ::core::option::Option::None => ::core::panic!("invalid hex"),
+            }
+        }
+    };
+}
+
+#[inline(always)]
+const fn nibble(digit: u8) -> Option<u8> {
+    match digit {
+        b'0'..=b'9' => Some(digit - b'0'),
+        b'a'..=b'f' => Some(digit - b'a' + 10),
+        b'A'..=b'F' => Some(digit - b'A' + 10),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+
+    #[wasm_bindgen_test]
+    fn decodes_hex_at_compile_time() {
+        const BYTES: [u8; 4] = hex!("DEADbeef");
+
+        assert_eq!(BYTES, [0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(decode_array::<2>(b"DEADBEEF"), None);
+        assert_eq!(decode_array::<2>(b"DEAG"), None);
+    }
+}
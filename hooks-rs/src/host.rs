@@ -0,0 +1,101 @@
+//! The host calls used by the transaction builders, behind a trait.
+//!
+//! [XrpPaymentBuilder::build](crate::TransactionBuilder::build) and the other builders ask
+//! the host for the ledger sequence, the hook account, the emit details and the fee.
+//! Their `build_with` methods take those from a [HostEnv] instead, so that a test or
+//! another runtime can drive the builders with its own values. Every method of [HostEnv]
+//! defaults to the real host function, which is all [ExternHost] does.
+//!
+//! A hook building several transactions can pass them its [HookCtx], which already holds
+//! the hook account and the ledger sequence, instead of every `build` asking the host again.
+//!
+//! # Example
+//! ```
+//! struct FixedLedger;
+//!
+//! impl HostEnv for FixedLedger {
+//!     fn ledger_seq(&self) -> LedgerSequence {
+//!         LedgerSequence(1000)
+//!     }
+//! }
+//!
+//! let mut buffer = XrpPaymentBuilder::uninit_buffer();
+//! XrpPaymentBuilder::new(Drops(1000), &otxn_account, None, None)
+//!     .build_with(&FixedLedger, &mut buffer)
+//!     .unwrap_line_number();
+//! ```
+
+use core::mem::MaybeUninit;
+
+use crate::api::*;
+
+/// Host calls needed to build a transaction
+pub trait HostEnv {
+    /// Sequence of the current ledger, see [ledger_seq]
+    #[inline(always)]
+    fn ledger_seq(&self) -> LedgerSequence {
+        ledger_seq()
+    }
+
+    /// Account the hook is installed on, see [hook_account]
+    #[inline(always)]
+    fn hook_account(&self) -> Result<AccountId> {
+        hook_account()
+    }
+
+    /// Fee in drops needed to emit the fully built `tx_blob`, see [etxn_fee_base]
+    #[inline(always)]
+    fn etxn_fee_base(&self, tx_blob: &[MaybeUninit<u8>]) -> Result<u64> {
+        etxn_fee_base_from_ptr(tx_blob.as_ptr(), tx_blob.len())
+    }
+
+    /// Writes the emit details of the transaction being built into `emit_details`,
+    /// returning the number of bytes written, see [insert_etxn_details_from_ptr]
+    #[inline(always)]
+    fn insert_etxn_details(&self, emit_details: &mut [MaybeUninit<u8>]) -> Result<u64> {
+        insert_etxn_details_from_ptr(
+            host_ptr(emit_details.as_mut_ptr()),
+            emit_details.len() as u32,
+        )
+    }
+}
+
+/// The real host, calling the host functions imported by the hook
+#[derive(Clone, Copy)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct ExternHost;
+
+impl HostEnv for ExternHost {}
+
+/// The hook account and ledger sequence read by [HookCtx::load], to build several
+/// transactions with `build_with` without calling [hook_account] and [ledger_seq] for each
+/// of them.
+///
+/// Every host call costs execution budget, and both values are the same for the whole
+/// execution of a hook. The emit details and the fee still come from the host, since they
+/// differ between transactions.
+///
+/// # Example
+/// ```
+/// let ctx = HookCtx::load(reserved).unwrap_line_number();
+///
+/// let mut first = XrpPaymentBuilder::uninit_buffer();
+/// XrpPaymentBuilder::new(Drops(half), &first_recipient, None, None)
+///     .build_with(&ctx, &mut first)
+///     .unwrap_line_number();
+/// let mut second = XrpPaymentBuilder::uninit_buffer();
+/// XrpPaymentBuilder::new(Drops(drops - half), &second_recipient, None, None)
+///     .build_with(&ctx, &mut second)
+///     .unwrap_line_number();
+/// ```
+impl HostEnv for HookCtx {
+    #[inline(always)]
+    fn ledger_seq(&self) -> LedgerSequence {
+        self.ledger_seq
+    }
+
+    #[inline(always)]
+    fn hook_account(&self) -> Result<AccountId> {
+        Ok(self.hook_account)
+    }
+}
@@ -0,0 +1,413 @@
+//! Conversions between the types of hooks-rs and those of the
+//! [xrpl-rust](https://docs.rs/xrpl-rust) crate.
+//!
+//! Off-chain tooling written with xrpl-rust, like scripts that fund the accounts of a hook
+//! or check what it emitted, can share the accounts, assets and amounts of a hook and of
+//! its tests instead of writing them out twice:
+//!
+//! - [AccountId] to and from the `AccountId` of the binary codec
+//! - [IssuedAsset] to and from `IssuedCurrency` and `Currency`, with the currency code
+//!   like rippled renders it and the issuer as an r-address
+//! - [Drops] to and from `XRPAmount`, [AmountView] to `Amount`, and the value and the
+//!   asset of an `IssuedCurrencyAmount` to [XFL] and [IssuedAsset]
+//! - [TxnType] to and from `TransactionType`
+//!
+//! Conversions that can fail return [HookError::InvalidArgument]: XRP isn't an issued
+//! asset, and Xahau and the XRPL each have transaction types that the other doesn't have.
+//!
+//! Only available with the `xrpl` feature, which builds for normal binaries like `std`.
+//!
+//! # Example
+//! ```
+//! let usd: xrpl::models::IssuedCurrency = fixtures::USD_ASSET.into();
+//! assert_eq!(usd.currency, "USD");
+//!
+//! let payment = xrpl::models::transactions::TransactionType::Payment;
+//! assert!(matches!(TxnType::try_from(payment), Ok(TxnType::Payment)));
+//! ```
+
+extern crate std;
+
+use std::string::ToString;
+
+use xrpl::core::binarycodec::types::{self, XRPLType};
+use xrpl::models::transactions::TransactionType;
+use xrpl::models::{self, IssuedCurrency, IssuedCurrencyAmount, XRPAmount};
+
+use crate::api::{AccountId, Drops, HookError, TxnType, XFL};
+use crate::asset::{currency_code_from_hex, currency_code_from_iso, IssuedAsset};
+use crate::fields::AmountView;
+use crate::json::{currency_name, issued_value, r_address};
+
+impl From<AccountId> for types::AccountId {
+    fn from(account_id: AccountId) -> Self {
+        // the codec only refuses buffers that aren't 20 bytes long
+        XRPLType::new(Some(&account_id.0)).unwrap()
+    }
+}
+
+impl From<&types::AccountId> for AccountId {
+    fn from(account_id: &types::AccountId) -> Self {
+        AccountId(account_id.as_ref().try_into().unwrap())
+    }
+}
+
+impl From<IssuedAsset> for IssuedCurrency<'static> {
+    fn from(asset: IssuedAsset) -> Self {
+        IssuedCurrency::new(
+            currency_name(&asset.currency).into(),
+            r_address(&asset.issuer).into(),
+        )
+    }
+}
+
+impl From<IssuedAsset> for models::Currency<'static> {
+    fn from(asset: IssuedAsset) -> Self {
+        models::Currency::IssuedCurrency(asset.into())
+    }
+}
+
+impl TryFrom<&IssuedCurrency<'_>> for IssuedAsset {
+    type Error = HookError;
+
+    fn try_from(currency: &IssuedCurrency<'_>) -> Result<Self, HookError> {
+        issued_asset(&currency.currency, &currency.issuer)
+    }
+}
+
+impl TryFrom<&models::Currency<'_>> for IssuedAsset {
+    type Error = HookError;
+
+    fn try_from(currency: &models::Currency<'_>) -> Result<Self, HookError> {
+        match currency {
+            models::Currency::IssuedCurrency(currency) => currency.try_into(),
+            models::Currency::XRP(_) => Err(HookError::InvalidArgument),
+        }
+    }
+}
+
+impl TryFrom<&IssuedCurrencyAmount<'_>> for IssuedAsset {
+    type Error = HookError;
+
+    fn try_from(amount: &IssuedCurrencyAmount<'_>) -> Result<Self, HookError> {
+        issued_asset(&amount.currency, &amount.issuer)
+    }
+}
+
+impl TryFrom<&IssuedCurrencyAmount<'_>> for XFL {
+    type Error = HookError;
+
+    /// The value of the amount, with the digits after the 16th dropped like the ledger
+    /// drops them
+    fn try_from(amount: &IssuedCurrencyAmount<'_>) -> Result<Self, HookError> {
+        parse_value(&amount.value).ok_or(HookError::InvalidArgument)
+    }
+}
+
+impl From<Drops> for XRPAmount<'static> {
+    fn from(drops: Drops) -> Self {
+        XRPAmount(drops.0.to_string().into())
+    }
+}
+
+impl TryFrom<&XRPAmount<'_>> for Drops {
+    type Error = HookError;
+
+    fn try_from(amount: &XRPAmount<'_>) -> Result<Self, HookError> {
+        amount
+            .0
+            .parse()
+            .map(Drops)
+            .map_err(|_| HookError::InvalidArgument)
+    }
+}
+
+impl From<AmountView<'_>> for models::Amount<'static> {
+    fn from(amount: AmountView<'_>) -> Self {
+        let (Some(currency), Some(issuer)) = (amount.currency(), amount.issuer()) else {
+            return models::Amount::XRPAmount(Drops(amount.drops().unwrap()).into());
+        };
+
+        models::Amount::IssuedCurrencyAmount(IssuedCurrencyAmount::new(
+            currency_name(currency).into(),
+            r_address(issuer).into(),
+            issued_value(amount.as_bytes()[..8].try_into().unwrap()).into(),
+        ))
+    }
+}
+
+impl TryFrom<TxnType> for TransactionType {
+    type Error = HookError;
+
+    /// Fails for the transaction types of Xahau that the XRPL doesn't have, such as
+    /// [TxnType::HookSet] and [TxnType::Invoke]
+    fn try_from(txn_type: TxnType) -> Result<Self, HookError> {
+        Ok(match txn_type {
+            TxnType::Payment => TransactionType::Payment,
+            TxnType::EscrowCreate => TransactionType::EscrowCreate,
+            TxnType::EscrowFinish => TransactionType::EscrowFinish,
+            TxnType::AccountSet => TransactionType::AccountSet,
+            TxnType::EscrowCancel => TransactionType::EscrowCancel,
+            TxnType::RegularKeySet => TransactionType::SetRegularKey,
+            TxnType::OfferCreate => TransactionType::OfferCreate,
+            TxnType::OfferCancel => TransactionType::OfferCancel,
+            TxnType::TicketCreate => TransactionType::TicketCreate,
+            TxnType::SignerListSet => TransactionType::SignerListSet,
+            TxnType::PaychanCreate => TransactionType::PaymentChannelCreate,
+            TxnType::PaychanFund => TransactionType::PaymentChannelFund,
+            TxnType::PaychanClaim => TransactionType::PaymentChannelClaim,
+            TxnType::CheckCreate => TransactionType::CheckCreate,
+            TxnType::CheckCash => TransactionType::CheckCash,
+            TxnType::CheckCancel => TransactionType::CheckCancel,
+            TxnType::DepositPreauth => TransactionType::DepositPreauth,
+            TxnType::TrustSet => TransactionType::TrustSet,
+            TxnType::AccountDelete => TransactionType::AccountDelete,
+            TxnType::Amendment => TransactionType::EnableAmendment,
+            TxnType::Fee => TransactionType::SetFee,
+            TxnType::UnlModify => TransactionType::UNLModify,
+            TxnType::TicketCancel | TxnType::HookSet | TxnType::Invoke => {
+                return Err(HookError::InvalidArgument)
+            }
+        })
+    }
+}
+
+impl TryFrom<TransactionType> for TxnType {
+    type Error = HookError;
+
+    /// Fails for the transaction types of the XRPL that Xahau doesn't have, such as the
+    /// AMM, NFToken and cross-chain bridge transactions
+    fn try_from(transaction_type: TransactionType) -> Result<Self, HookError> {
+        Ok(match transaction_type {
+            TransactionType::Payment => TxnType::Payment,
+            TransactionType::EscrowCreate => TxnType::EscrowCreate,
+            TransactionType::EscrowFinish => TxnType::EscrowFinish,
+            TransactionType::AccountSet => TxnType::AccountSet,
+            TransactionType::EscrowCancel => TxnType::EscrowCancel,
+            TransactionType::SetRegularKey => TxnType::RegularKeySet,
+            TransactionType::OfferCreate => TxnType::OfferCreate,
+            TransactionType::OfferCancel => TxnType::OfferCancel,
+            TransactionType::TicketCreate => TxnType::TicketCreate,
+            TransactionType::SignerListSet => TxnType::SignerListSet,
+            TransactionType::PaymentChannelCreate => TxnType::PaychanCreate,
+            TransactionType::PaymentChannelFund => TxnType::PaychanFund,
+            TransactionType::PaymentChannelClaim => TxnType::PaychanClaim,
+            TransactionType::CheckCreate => TxnType::CheckCreate,
+            TransactionType::CheckCash => TxnType::CheckCash,
+            TransactionType::CheckCancel => TxnType::CheckCancel,
+            TransactionType::DepositPreauth => TxnType::DepositPreauth,
+            TransactionType::TrustSet => TxnType::TrustSet,
+            TransactionType::AccountDelete => TxnType::AccountDelete,
+            TransactionType::EnableAmendment => TxnType::Amendment,
+            TransactionType::SetFee => TxnType::Fee,
+            TransactionType::UNLModify => TxnType::UnlModify,
+            _ => return Err(HookError::InvalidArgument),
+        })
+    }
+}
+
+// An issued asset from a currency code like rippled renders it and the r-address of
+// the issuer
+fn issued_asset(currency: &str, issuer: &str) -> Result<IssuedAsset, HookError> {
+    let currency = match currency.len() {
+        3 => currency_code_from_iso(currency.as_bytes()),
+        _ => currency_code_from_hex(currency.as_bytes()),
+    };
+
+    match (currency, AccountId::from_raddress(issuer.as_bytes())) {
+        (Some(currency), Some(issuer)) => Ok(IssuedAsset::new(currency, issuer)),
+        _ => Err(HookError::InvalidArgument),
+    }
+}
+
+// A decimal number such as `-1.5`, `1000` or `1e-20` as a normalized XFL, `None` if it
+// isn't a number or too big for an XFL. Digits after the 16th are dropped, and numbers
+// too small for an XFL are zero.
+fn parse_value(value: &str) -> Option<XFL> {
+    const MIN_MANTISSA: u64 = 1_000_000_000_000_000;
+
+    let (is_negative, value) = match value.strip_prefix('-') {
+        Some(value) => (true, value),
+        None => (false, value),
+    };
+    let (number, mut exponent) = match value.split_once(['e', 'E']) {
+        Some((number, exponent)) => (number, exponent.parse::<i32>().ok()?),
+        None => (value, 0),
+    };
+    let (integer, fraction) = number.split_once('.').unwrap_or((number, ""));
+    if integer.is_empty() && fraction.is_empty() {
+        return None;
+    }
+
+    let mut mantissa = 0u64;
+    for (i, char) in integer.chars().chain(fraction.chars()).enumerate() {
+        let digit = char.to_digit(10)? as u64;
+        let is_fraction = i >= integer.len();
+        if mantissa < MIN_MANTISSA {
+            mantissa = mantissa * 10 + digit;
+            exponent -= is_fraction as i32;
+        } else {
+            exponent += !is_fraction as i32;
+        }
+    }
+    if mantissa == 0 {
+        return Some(XFL(0));
+    }
+    while mantissa < MIN_MANTISSA {
+        mantissa *= 10;
+        exponent -= 1;
+    }
+    if exponent < -96 {
+        return Some(XFL(0));
+    }
+    if exponent > 80 {
+        return None;
+    }
+
+    // the sign bit is set for positive numbers
+    let sign = if is_negative { 0 } else { 1 << 62 };
+    Some(XFL(sign | ((exponent as i64 + 97) << 54) | mantissa as i64))
+}
+
+#[cfg(test)]
+mod tests {
+    use core::mem::MaybeUninit;
+
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::api::{FieldId, AMOUNT_LEN};
+    use crate::fields::{OtxnFields, XRP_AMOUNT_LEN};
+    use crate::fixtures::*;
+    use crate::mock;
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn converts_account_ids() {
+        let genesis = types::AccountId::from(GENESIS_ACCOUNT);
+
+        assert_eq!(genesis.to_string(), "rHb9CJAWyB4rj91VRWn96DkukG4bwdtyTh");
+        assert_eq!(AccountId::from(&genesis), GENESIS_ACCOUNT);
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn converts_issued_assets() {
+        let usd = IssuedCurrency::from(IssuedAsset::new(USD, GENESIS_ACCOUNT));
+        let xau = IssuedCurrency::from(IssuedAsset::new(DEMURRAGE_XAU, GENESIS_ACCOUNT));
+
+        assert_eq!(usd.currency, "USD");
+        assert_eq!(usd.issuer, "rHb9CJAWyB4rj91VRWn96DkukG4bwdtyTh");
+        assert_eq!(xau.currency, "0158415500000000C1F76FF6ECB0BAC600000000");
+        assert_eq!(
+            IssuedAsset::try_from(&usd),
+            Ok(IssuedAsset::new(USD, GENESIS_ACCOUNT))
+        );
+        assert_eq!(
+            IssuedAsset::try_from(&models::Currency::from(xau)),
+            Ok(IssuedAsset::new(DEMURRAGE_XAU, GENESIS_ACCOUNT))
+        );
+        assert_eq!(
+            IssuedAsset::try_from(&models::Currency::XRP(models::XRP::new())),
+            Err(HookError::InvalidArgument)
+        );
+        assert_eq!(
+            IssuedAsset::try_from(&IssuedCurrency::new("XRP".into(), usd.issuer)),
+            Err(HookError::InvalidArgument)
+        );
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn converts_amounts_read_from_the_transaction() {
+        mock::reset();
+        mock::set_otxn_field(
+            FieldId::Amount,
+            &(0x4000_0000_0000_0000u64 | 1000).to_be_bytes(),
+        );
+        // 1.5 USD issued by the genesis account
+        let mut issued = [0; AMOUNT_LEN];
+        issued[..8].copy_from_slice(&0xD485_543D_F729_C000u64.to_be_bytes());
+        issued[8..28].copy_from_slice(&USD);
+        issued[28..].copy_from_slice(GENESIS_ACCOUNT.as_bytes());
+        mock::set_otxn_field(FieldId::SendMax, &issued);
+
+        let mut scratch = [MaybeUninit::uninit(); XRP_AMOUNT_LEN + AMOUNT_LEN];
+        let mut fields = OtxnFields::new(&mut scratch);
+        let amount = models::Amount::from(fields.amount(FieldId::Amount).unwrap());
+        let send_max = models::Amount::from(fields.amount(FieldId::SendMax).unwrap());
+
+        assert_eq!(amount, models::Amount::XRPAmount("1000".into()));
+        let models::Amount::IssuedCurrencyAmount(send_max) = send_max else {
+            panic!("{send_max:?} isn't issued");
+        };
+        assert_eq!(send_max.value, "1.5");
+        assert_eq!(
+            XFL::try_from(&send_max).map(|xfl| xfl.0),
+            Ok(0x5485_543D_F729_C000)
+        );
+        assert_eq!(
+            IssuedAsset::try_from(&send_max),
+            Ok(IssuedAsset::new(USD, GENESIS_ACCOUNT))
+        );
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn converts_drops() {
+        let amount = XRPAmount::from(Drops(1_000_000));
+
+        assert_eq!(amount.0, "1000000");
+        assert_eq!(Drops::try_from(&amount).map(|drops| drops.0), Ok(1_000_000));
+        assert_eq!(
+            Drops::try_from(&XRPAmount::from("1.5")).map(|drops| drops.0),
+            Err(HookError::InvalidArgument)
+        );
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn parses_issued_values_into_normalized_xfls() {
+        let parse = |value: &str| parse_value(value).map(|xfl| xfl.to_string());
+
+        assert_eq!(parse("1.5").as_deref(), Some("1.5"));
+        assert_eq!(parse("-123000000").as_deref(), Some("-123000000"));
+        assert_eq!(
+            parse("0.00000000000000000001").as_deref(),
+            Some("0.00000000000000000001")
+        );
+        assert_eq!(parse("25e-3").as_deref(), Some("0.025"));
+        assert_eq!(parse(".5").as_deref(), Some("0.5"));
+        assert_eq!(parse("0").as_deref(), Some("0"));
+        // a 17th digit is dropped
+        assert_eq!(parse("1.0000000000000009").as_deref(), Some("1"));
+        assert_eq!(parse("1e-100").as_deref(), Some("0"));
+        assert_eq!(parse("1e96"), None);
+        assert_eq!(parse("1.5 USD"), None);
+        assert_eq!(parse("-"), None);
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn maps_transaction_types_by_their_rippled_names() {
+        for code in 0..=u16::from(u8::MAX) {
+            let Some(txn_type) = TxnType::from_code(code) else {
+                continue;
+            };
+            match TransactionType::try_from(txn_type) {
+                Ok(transaction_type) => {
+                    assert_eq!(transaction_type.to_string(), txn_type.name());
+                    assert_eq!(
+                        TxnType::try_from(transaction_type).map(TxnType::name),
+                        Ok(txn_type.name())
+                    );
+                }
+                Err(err) => {
+                    assert_eq!(err, HookError::InvalidArgument);
+                    assert!(matches!(
+                        txn_type,
+                        TxnType::TicketCancel | TxnType::HookSet | TxnType::Invoke
+                    ));
+                }
+            }
+        }
+        assert_eq!(
+            TxnType::try_from(TransactionType::NFTokenMint).map(TxnType::name),
+            Err(HookError::InvalidArgument)
+        );
+    }
+}
@@ -0,0 +1,310 @@
+//! JSON rendering of built transactions.
+//!
+//! [to_json] renders a serialized transaction, such as one built by a
+//! [TransactionBuilder](crate::TransactionBuilder), the way rippled and xahaud render
+//! transactions in the JSON of their RPC responses: fields by name in serialization order,
+//! the transaction type by name, XRP amounts as strings of drops, issued amounts as
+//! `currency`, `issuer` and `value`, accounts as r-addresses and other binary fields as
+//! uppercase hex. The output can be pasted into explorers or compared with what a `tx`
+//! RPC call returns for an emitted transaction.
+//!
+//! Only available in tests and with the `std` feature.
+//!
+//! # Example
+//! ```
+//! let mut buffer = XrpPaymentBuilder::uninit_buffer();
+//! let tx = XrpPaymentBuilder::new(Drops(1000), &DESTINATION, None, None)
+//!     .build(&mut buffer)
+//!     .unwrap_line_number();
+//!
+//! // {"TransactionType":"Payment","Flags":2147483648,...,"Amount":"1000",...}
+//! std::println!("{}", json::to_json(tx));
+//! ```
+
+extern crate std;
+
+use std::fmt::Write;
+use std::string::{String, ToString};
+
+use crate::api::*;
+use crate::asset::currency_code_from_iso;
+use crate::base58::encode_account_id;
+use crate::decode::{decode, DecodedField, DecodedTxn};
+
+/// Render a serialized transaction or the contents of an object as JSON.
+///
+/// # Panics
+/// Like [decode], if the buffer isn't a sequence of well formed fields.
+pub fn to_json(tx: &[u8]) -> String {
+    decode(tx).to_json()
+}
+
+impl DecodedTxn {
+    /// Render the fields as a JSON object, see [to_json]
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+        write_object(&mut json, self);
+
+        json
+    }
+}
+
+fn write_object(json: &mut String, object: &DecodedTxn) {
+    json.push('{');
+    for (i, field) in object.fields.iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        write_field(json, field);
+    }
+    json.push('}');
+}
+
+fn write_field(json: &mut String, field: &DecodedField) {
+    let name = match FieldId::from_code(field.field_id) {
+        Some(field_id) => field_id.name().to_string(),
+        // not a field of this crate, so named by its ID like `Field(1048592)`
+        None => std::format!("Field({})", field.field_id),
+    };
+    let _ = write!(json, "\"{name}\":");
+
+    let value = &field.value[..];
+    match SerializedType::from_code(field.field_id >> 16) {
+        Some(SerializedType::UInt16) if field.field_id == FieldId::TransactionType as u32 => {
+            let code = u16::from_be_bytes(value.try_into().unwrap());
+            match TxnType::from_code(code) {
+                Some(txn_type) => {
+                    let _ = write!(json, "\"{}\"", txn_type.name());
+                }
+                None => {
+                    let _ = write!(json, "{code}");
+                }
+            }
+        }
+        Some(SerializedType::UInt8 | SerializedType::UInt16 | SerializedType::UInt32) => {
+            let number = value
+                .iter()
+                .fold(0u32, |number, &byte| number << 8 | byte as u32);
+            let _ = write!(json, "{number}");
+        }
+        Some(SerializedType::UInt64) => {
+            let number = u64::from_be_bytes(value.try_into().unwrap());
+            let _ = write!(json, "\"{number:X}\"");
+        }
+        Some(SerializedType::Amount) => write_amount(json, value),
+        Some(SerializedType::Account) => {
+            write_account(json, AccountId::from_bytes_ref(value.try_into().unwrap()))
+        }
+        Some(SerializedType::Object) => write_object(json, &decode(value)),
+        Some(SerializedType::Array) => {
+            // every element is an object field, rendered as an object with that field only
+            json.push('[');
+            for (i, element) in decode(value).fields.iter().enumerate() {
+                if i > 0 {
+                    json.push(',');
+                }
+                json.push('{');
+                write_field(json, element);
+                json.push('}');
+            }
+            json.push(']');
+        }
+        Some(SerializedType::Vector256) => {
+            json.push('[');
+            for (i, hash) in value.chunks(HASH_LEN).enumerate() {
+                if i > 0 {
+                    json.push(',');
+                }
+                write_hex(json, hash);
+            }
+            json.push(']');
+        }
+        // hashes and blobs
+        _ => write_hex(json, value),
+    }
+}
+
+fn write_amount(json: &mut String, amount: &[u8]) {
+    if amount.len() == 8 {
+        let drops = u64::from_be_bytes(amount.try_into().unwrap());
+        // the second highest bit marks positive amounts
+        let sign = if drops & 0x4000_0000_0000_0000 == 0 {
+            "-"
+        } else {
+            ""
+        };
+        let _ = write!(json, "\"{sign}{}\"", drops & 0x3FFF_FFFF_FFFF_FFFF);
+        return;
+    }
+
+    json.push_str("{\"currency\":");
+    write_currency(json, amount[8..28].try_into().unwrap());
+    json.push_str(",\"issuer\":");
+    write_account(
+        json,
+        AccountId::from_bytes_ref(amount[28..48].try_into().unwrap()),
+    );
+    let _ = write!(
+        json,
+        ",\"value\":\"{}\"}}",
+        issued_value(amount[..8].try_into().unwrap())
+    );
+}
+
+fn write_currency(json: &mut String, currency: &CurrencyCode) {
+    let _ = write!(json, "\"{}\"", currency_name(currency));
+}
+
+// A currency code like rippled renders it: the 3 characters of a standard code, or 40
+// uppercase hex characters for the others
+pub(crate) fn currency_name(currency: &CurrencyCode) -> String {
+    match currency_code_from_iso(&currency[12..15]) {
+        Some(standard) if standard == *currency => {
+            core::str::from_utf8(&currency[12..15]).unwrap().into()
+        }
+        _ => {
+            let mut hex = String::new();
+            for byte in currency {
+                let _ = write!(hex, "{byte:02X}");
+            }
+            hex
+        }
+    }
+}
+
+fn write_account(json: &mut String, account_id: &AccountId) {
+    let _ = write!(json, "\"{}\"", r_address(account_id));
+}
+
+// The r-address of an account ID, without the zeroes padding it to R_ADDRESS_MAX_LEN
+pub(crate) fn r_address(account_id: &AccountId) -> String {
+    let r_address = encode_account_id(account_id);
+    let len = r_address
+        .iter()
+        .position(|&byte| byte == 0)
+        .unwrap_or(r_address.len());
+
+    core::str::from_utf8(&r_address[..len]).unwrap().into()
+}
+
+fn write_hex(json: &mut String, bytes: &[u8]) {
+    json.push('"');
+    for byte in bytes {
+        let _ = write!(json, "{byte:02X}");
+    }
+    json.push('"');
+}
+
+// The decimal value of an issued amount: a sign bit, 8 bits of exponent offset by 97 and
+// 54 bits of mantissa, after the bit marking issued amounts
+pub(crate) fn issued_value(value: [u8; 8]) -> String {
+    let value = u64::from_be_bytes(value);
+    let mut mantissa = value & 0x003F_FFFF_FFFF_FFFF;
+    if mantissa == 0 {
+        return "0".into();
+    }
+    let mut exponent = ((value >> 54) & 0xFF) as i32 - 97;
+    while mantissa % 10 == 0 {
+        mantissa /= 10;
+        exponent += 1;
+    }
+
+    let digits = mantissa.to_string();
+    let mut text = String::new();
+    if value & 0x4000_0000_0000_0000 == 0 {
+        text.push('-');
+    }
+    let integer_digits = digits.len() as i32 + exponent;
+    if exponent >= 0 {
+        text.push_str(&digits);
+        text.extend(core::iter::repeat('0').take(exponent as usize));
+    } else if integer_digits > 0 {
+        let (integer, fraction) = digits.split_at(integer_digits as usize);
+        let _ = write!(text, "{integer}.{fraction}");
+    } else {
+        text.push_str("0.");
+        text.extend(core::iter::repeat('0').take(-integer_digits as usize));
+        text.push_str(&digits);
+    }
+
+    text
+}
+
+#[cfg(test)]
+mod tests {
+
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::fixtures::{DESTINATION, HOOK_ACCOUNT, OFFER_CREATE};
+    use crate::transaction::*;
+
+    #[wasm_bindgen_test]
+    fn renders_transactions_like_rippled() {
+        assert_eq!(
+            to_json(&OFFER_CREATE),
+            concat!(
+                "{\"TransactionType\":\"OfferCreate\",",
+                "\"Flags\":524288,",
+                "\"Sequence\":1752792,",
+                "\"Expiration\":595640108,",
+                "\"OfferSequence\":1752791,",
+                "\"TakerPays\":{\"currency\":\"USD\",",
+                "\"issuer\":\"rvYAfWj5gh67oV6fW32ZzP3Aw4Eubs59B\",\"value\":\"7072.8\"},",
+                "\"TakerGets\":\"15000000000\",",
+                "\"Fee\":\"10\",",
+                "\"SigningPubKey\":",
+                "\"03EE83BB432547885C219634A1BC407A9DB0474145D69737D09CCDC63E1DEE7FE3\",",
+                "\"TxnSignature\":\"30440220143759437C04F7B61F012563AFE90D8DAFC46E86035E1D965A",
+                "9CED282C97D4CE02204CFD241E86F17E011298FC1A39B63386C74306A5DE047E213B0F29EFA4",
+                "571C2C\",",
+                "\"Account\":\"rMBzp8CgpE441cp5PVyA9rpVV7oT8hP3ys\"}",
+            )
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn renders_built_transactions() {
+        crate::mock::reset();
+        crate::mock::set_hook_account(HOOK_ACCOUNT);
+        let _ = EmitReservation::new(1).unwrap_line_number();
+
+        let mut buffer = XrpPaymentBuilder::uninit_buffer();
+        let tx = XrpPaymentBuilder::new(Drops(1000), &DESTINATION, None, None)
+            .build(&mut buffer)
+            .unwrap_line_number();
+        let json = to_json(tx);
+
+        assert!(json.starts_with("{\"TransactionType\":\"Payment\",\"Flags\":2147483648,"));
+        assert!(json.contains("\"Amount\":\"1000\","));
+        assert!(json.contains("\"EmitDetails\":{\"EmitGeneration\":1,"));
+        let destination = encode_account_id(&DESTINATION);
+        let destination = core::str::from_utf8(&destination)
+            .unwrap()
+            .trim_end_matches('\0');
+        assert!(json.contains(&std::format!("\"Destination\":\"{destination}\"")));
+    }
+
+    #[wasm_bindgen_test]
+    fn renders_issued_values() {
+        // mantissa and exponent of 1.5, 1e-20 and -123 million
+        let value = |negative: bool, mantissa: u64, exponent: i32| {
+            let sign = if negative { 0 } else { 0x4000_0000_0000_0000 };
+            (0x8000_0000_0000_0000 | sign | ((exponent + 97) as u64) << 54 | mantissa).to_be_bytes()
+        };
+
+        assert_eq!(
+            issued_value(value(false, 1_500_000_000_000_000, -15)),
+            "1.5"
+        );
+        assert_eq!(
+            issued_value(value(false, 1_000_000_000_000_000, -35)),
+            "0.00000000000000000001"
+        );
+        assert_eq!(
+            issued_value(value(true, 1_230_000_000_000_000, -7)),
+            "-123000000"
+        );
+        assert_eq!(issued_value(0x8000_0000_0000_0000u64.to_be_bytes()), "0");
+    }
+}
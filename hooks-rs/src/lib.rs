@@ -20,7 +20,11 @@
     unreachable_pub
 )]
 #![doc(test(attr(deny(warnings))))]
-#![feature(maybe_uninit_uninit_array, maybe_uninit_array_assume_init)]
+#![feature(
+    maybe_uninit_uninit_array,
+    maybe_uninit_array_assume_init,
+    try_trait_v2
+)]
 /// Internal C bindings. Unless if you are creating something very low-level,
 /// you should not need to use this module directly.
 #[allow(missing_docs)]
@@ -37,18 +41,133 @@ pub mod api;
 /// Utility methods to make working with hooks easier
 pub mod utils;
 
-/// Transaction builders. It is a lot of manual work to build an XRPL transaction.
+/// Bounds-checked reading of serialized bytes, such as objects, blobs and memos
+pub mod reader;
+
+// Base58check encoding and decoding of r-addresses that does not need the host, behind
+// AccountId::from_raddress and AccountId::to_raddress_const
+pub(crate) mod base58;
+
+/// Hex encoding and decoding into caller provided buffers
+pub mod hex;
+
+mod sha512;
+
+/// Issued assets and trust line helpers
+pub mod asset;
+
+/// Bookkeeping for emitted transactions and their callbacks
+pub mod callback;
+
+/// Typed install-time hook parameters
+pub mod params;
+
+/// Allowlists and blocklists of accounts in hook state
+pub mod access;
+
+/// Token bucket rate limiting of accounts in hook state
+pub mod rate_limit;
+
+/// Vesting schedules paid out from hook state as they vest
+pub mod vesting;
+
+/// Values published by oracles, in a state layout shared by hooks
+pub mod oracle;
+
+/// Typed records in hook state that other hooks can read
+pub mod table;
+
+/// Splitting amounts between accounts by shares in basis points
+pub mod split;
+
+/// Limits on the amounts of payments, checked the same way by every hook
+pub mod policy;
+
+/// M-of-n authorization of administrative Invokes, by public keys kept in hook state
+pub mod multisig;
+
+/// Protection against replayed instructions, by the nonces they used kept in hook state
+pub mod replay;
+
+/// Work postponed from the strong execution of a hook to its again-as-weak execution
+pub mod deferred;
+
+/// Typed events traced and kept in a rolling log in hook state, for indexers off the ledger
+pub mod event;
+
+/// Ready-made hooks configured with install-time parameters
+#[cfg(any(test, feature = "prefab"))]
+pub mod prefab;
+
+/// Borrowed views of the fields of the originating transaction
+pub mod fields;
+
+/// Typed flags of transactions
+pub mod flags;
+
+/// The host calls used by the transaction builders, behind a trait to inject them
+pub mod host;
+
+/// Transaction builders.
+///
+/// It is a lot of manual work to build an XRPL transaction.
 /// This module provides a few pre-built transaction builders as well as a generic
 /// buffer and builder that can be used to build any transaction.
 pub mod transaction;
 
+/// Everything needed to write a hook, in one import
+pub mod prelude;
+
+/// In-memory fake of the hooks host for unit tests
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+
+/// Decoder for serialized transactions, for asserting on builder output in unit tests
+#[cfg(any(test, feature = "mock"))]
+pub mod decode;
+
+/// Snapshot tests for built transactions
+#[cfg(any(test, feature = "mock"))]
+pub mod snapshot;
+
+/// Well-known accounts, currencies, keylets and transactions for examples and tests
+#[cfg(any(test, feature = "std"))]
+pub mod fixtures;
+
+/// JSON rendering of built transactions, like rippled renders them
+#[cfg(any(test, feature = "std"))]
+pub mod json;
+
+/// Conversions to and from the types of the `xrpl` crate for off-chain tooling
+#[cfg(feature = "xrpl")]
+pub mod interop;
+
+#[cfg(test)]
+mod codec_vectors;
+
+#[cfg(test)]
+mod macro_vectors;
+
 // Prelude
-pub use {api::*, transaction::*, utils::*};
+pub use base58::{R_ADDRESS_MAX_LEN, R_ADDRESS_MIN_LEN};
+pub use {
+    access::*, api::*, asset::*, callback::*, deferred::*, event::*, fields::*, flags::*, host::*,
+    multisig::*, oracle::*, params::*, policy::*, rate_limit::*, reader::*, replay::*, split::*,
+    table::*, transaction::*, utils::*, vesting::*,
+};
+
+/// Attribute macros generating the `hook` and `cbak` entry points from functions
+/// returning an [Outcome], and derive macros for [HookParams] and [Record].
+pub use hooks_rs_macros::{callback, hook, HookParams, Record};
+
+// lets the derive macros, which refer to `::hooks_rs`, be tested in this crate
+#[cfg(test)]
+extern crate self as hooks_rs;
 
-#[cfg(not(test))]
+#[cfg(not(any(test, feature = "mock")))]
 use core::panic::PanicInfo;
 /// You should use rollback() instead of native panic!() macro
-#[cfg(not(test))]
+#[cfg(not(any(test, feature = "mock")))]
 #[inline(always)]
 #[panic_handler]
 fn panic(_: &PanicInfo<'_>) -> ! {
@@ -0,0 +1,175 @@
+//! Golden vectors built by the `PREPARE_*` macros of `macro.h` from the same inputs as
+//! the builders, to make sure the builders are drop-in replacements for them.
+//!
+//! The vectors are printed by `golden/macro_vectors.c`, which runs the macros natively
+//! against stubs that mirror the mock host. Regenerate them with the command at the top
+//! of that file after changing the inputs or updating `macro.h`.
+//!
+//! Two quirks of the macros are worked around by the generator: the buffers of
+//! `PREPARE_PAYMENT_SIMPLE_TRUSTLINE` need one more byte than
+//! `PREPARE_PAYMENT_SIMPLE_TRUSTLINE_SIZE`, and `ENCODE_SIGNING_PUBKEY_NULL` relies on
+//! the buffer being zeroed, since it doesn't write the last two bytes of the key.
+
+use core::mem::MaybeUninit;
+
+use wasm_bindgen_test::wasm_bindgen_test;
+
+use crate::api::*;
+use crate::fixtures::DESTINATION;
+use crate::mock;
+use crate::snapshot::{assert_snapshot, snapshot};
+use crate::transaction::*;
+
+/// `PREPARE_PAYMENT_SIMPLE(buf, 1000, to, 7, 8)` with `to` set to all 2s, on the host
+/// set up by [set_up_host] with `(1, 3, 100, 10)`
+const XRP_PAYMENT: [u8; 270] = crate::hex!(concat!(
+    "120000",
+    "2280000000",
+    "2300000008",
+    "2400000000",
+    "2E00000007",
+    "201A00000065",
+    "201B00000069",
+    "6140000000000003E8",
+    "68400000000000000A",
+    "7321000000000000000000000000000000000000000000000000000000000000000000",
+    "81140101010101010101010101010101010101010101",
+    "83140202020202020202020202020202020202020202",
+    "ED202E000000013D00000000000000015B03030303030303030303030303030303030303030303030303030303030303035C00000000000000000000000000000000000000000000000000000000000000008A1401010101010101010101010101010101010101015D0000000000000000000000000000000000000000000000000000000000000000E1",
+));
+
+/// `PREPARE_PAYMENT_SIMPLE(buf, 100000000000000000ULL, to, 0xFFFFFFFF, 0)` with `to`
+/// set to 0 to 19, on the host set up by [set_up_host] with `(0xAB, 0x5E, 85000000, 12)`
+const XRP_PAYMENT_MAX: [u8; 270] = crate::hex!(concat!(
+    "120000",
+    "2280000000",
+    "2300000000",
+    "2400000000",
+    "2EFFFFFFFF",
+    "201A0510FF41",
+    "201B0510FF45",
+    "61416345785D8A0000",
+    "68400000000000000C",
+    "7321000000000000000000000000000000000000000000000000000000000000000000",
+    "8114ABABABABABABABABABABABABABABABABABABABAB",
+    "8314000102030405060708090A0B0C0D0E0F10111213",
+    "ED202E000000013D00000000000000015B5E5E5E5E5E5E5E5E5E5E5E5E5E5E5E5E5E5E5E5E5E5E5E5E5E5E5E5E5E5E5E5E5C00000000000000000000000000000000000000000000000000000000000000008A14ABABABABABABABABABABABABABABABABABABABAB5D0000000000000000000000000000000000000000000000000000000000000000E1",
+));
+
+/// `PREPARE_PAYMENT_SIMPLE_TRUSTLINE(buf, amount, to, 7, 8)` with `amount` set to 0xD4
+/// followed by 4s and `to` set to all 2s, on the host set up by [set_up_host] with
+/// `(1, 3, 100, 10)`
+const TRUSTLINE_PAYMENT: [u8; 310] = crate::hex!(concat!(
+    "120000",
+    "2280000000",
+    "2300000008",
+    "2400000000",
+    "2E00000007",
+    "201A00000065",
+    "201B00000069",
+    "61D40404040404040404040404040404040404040404040404040404040404040404040404040404040404040404040404",
+    "68400000000000000A",
+    "7321000000000000000000000000000000000000000000000000000000000000000000",
+    "81140101010101010101010101010101010101010101",
+    "83140202020202020202020202020202020202020202",
+    "ED202E000000013D00000000000000015B03030303030303030303030303030303030303030303030303030303030303035C00000000000000000000000000000000000000000000000000000000000000008A1401010101010101010101010101010101010101015D0000000000000000000000000000000000000000000000000000000000000000E1",
+));
+
+/// `PREPARE_PAYMENT_SIMPLE_TRUSTLINE(buf, amount, to, 0xFFFFFFFF, 0)` with `amount` set
+/// to 0x80 to 0xAF and `to` set to 0 to 19, on the host set up by [set_up_host] with
+/// `(0xAB, 0x5E, 85000000, 12)`
+const TRUSTLINE_PAYMENT_MAX: [u8; 310] = crate::hex!(concat!(
+    "120000",
+    "2280000000",
+    "2300000000",
+    "2400000000",
+    "2EFFFFFFFF",
+    "201A0510FF41",
+    "201B0510FF45",
+    "61808182838485868788898A8B8C8D8E8F909192939495969798999A9B9C9D9E9FA0A1A2A3A4A5A6A7A8A9AAABACADAEAF",
+    "68400000000000000C",
+    "7321000000000000000000000000000000000000000000000000000000000000000000",
+    "8114ABABABABABABABABABABABABABABABABABABABAB",
+    "8314000102030405060708090A0B0C0D0E0F10111213",
+    "ED202E000000013D00000000000000015B5E5E5E5E5E5E5E5E5E5E5E5E5E5E5E5E5E5E5E5E5E5E5E5E5E5E5E5E5E5E5E5E5C00000000000000000000000000000000000000000000000000000000000000008A14ABABABABABABABABABABABABABABABABABABABAB5D0000000000000000000000000000000000000000000000000000000000000000E1",
+));
+
+/// Sets up the mock host like `set_host` of the generator and reserves one emission
+fn set_up_host(hook_account: u8, otxn_id: u8, ledger_seq: u32, fee_base: u64) {
+    mock::reset();
+    mock::set_hook_account(AccountId([hook_account; ACC_ID_LEN]));
+    mock::set_otxn_id([otxn_id; HASH_LEN]);
+    mock::set_ledger_seq(ledger_seq);
+    mock::set_fee_base(fee_base);
+    let _ = EmitReservation::new(1).unwrap_line_number();
+}
+
+fn counting_account() -> AccountId {
+    AccountId(core::array::from_fn(|i| i as u8))
+}
+
+fn xrp_payment(drops: u64, to: &AccountId, dest_tag: u32, src_tag: u32) -> [u8; 270] {
+    let mut buffer = XrpPaymentBuilder::uninit_buffer();
+    assert!(XrpPaymentBuilder::new(
+        Drops(drops),
+        to,
+        Some(DestTag(dest_tag)),
+        Some(SrcTag(src_tag))
+    )
+    .build(&mut buffer)
+    .is_ok());
+
+    unsafe { MaybeUninit::array_assume_init(buffer) }
+}
+
+fn trustline_payment(
+    amount: &[u8; ISSUED_AMOUNT_LEN],
+    to: &AccountId,
+    dest_tag: u32,
+    src_tag: u32,
+) -> [u8; 310] {
+    let mut buffer = TrustlinePaymentBuilder::uninit_buffer();
+    assert!(TrustlinePaymentBuilder::new(
+        amount,
+        to,
+        Some(DestTag(dest_tag)),
+        Some(SrcTag(src_tag))
+    )
+    .build(&mut buffer)
+    .is_ok());
+
+    unsafe { MaybeUninit::array_assume_init(buffer) }
+}
+
+#[wasm_bindgen_test]
+fn xrp_payment_matches_prepare_payment_simple() {
+    set_up_host(1, 3, 100, 10);
+    assert_snapshot(
+        &xrp_payment(1000, &DESTINATION, 7, 8),
+        &snapshot(&XRP_PAYMENT),
+    );
+
+    set_up_host(0xAB, 0x5E, 85_000_000, 12);
+    assert_snapshot(
+        &xrp_payment(100_000_000_000_000_000, &counting_account(), u32::MAX, 0),
+        &snapshot(&XRP_PAYMENT_MAX),
+    );
+}
+
+#[wasm_bindgen_test]
+fn trustline_payment_matches_prepare_payment_simple_trustline() {
+    let mut amount = [4; ISSUED_AMOUNT_LEN];
+    amount[0] = 0xD4;
+    set_up_host(1, 3, 100, 10);
+    assert_snapshot(
+        &trustline_payment(&amount, &DESTINATION, 7, 8),
+        &snapshot(&TRUSTLINE_PAYMENT),
+    );
+
+    let amount = core::array::from_fn(|i| 0x80 + i as u8);
+    set_up_host(0xAB, 0x5E, 85_000_000, 12);
+    assert_snapshot(
+        &trustline_payment(&amount, &counting_account(), u32::MAX, 0),
+        &snapshot(&TRUSTLINE_PAYMENT_MAX),
+    );
+}
@@ -0,0 +1,293 @@
+//! Runs hook logic as invocations on a simulated ledger, see
+//! [the module docs](super#simulated-ledger).
+
+extern crate std;
+
+use std::vec::Vec;
+
+use super::{emitted_txn_hash, with_host};
+use crate::api::*;
+use crate::callback::EmitOutcome;
+use crate::decode::decode;
+
+/// A transaction emitted by an accepted invocation
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EmittedTxn {
+    /// Hash returned by `emit`, which `cbak` sees as the hash of the originating transaction
+    pub hash: Hash,
+    /// The serialized transaction
+    pub tx: Vec<u8>,
+}
+
+/// What an invocation run with [invoke] or [callback] did
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Invocation {
+    /// `true` if the hook accepted, `false` if it rolled back
+    pub accepted: bool,
+    /// Return code
+    pub code: i64,
+    /// Return message
+    pub msg: Vec<u8>,
+    /// Transactions emitted by the invocation. They are only queued for [callback] if the
+    /// hook accepted.
+    pub emitted: Vec<EmittedTxn>,
+}
+
+/// Sets the balance of the hook account in drops, 1000 XAH by default
+pub fn set_balance(drops: u64) {
+    with_host(|host| host.balance = drops);
+}
+
+/// Sets the reserve of an account and the reserve of every object it owns in drops,
+/// 1 XAH and 0.2 XAH by default
+pub fn set_reserves(base: u64, increment: u64) {
+    with_host(|host| {
+        host.reserve_base = base;
+        host.reserve_increment = increment;
+    });
+}
+
+/// Sets the number of objects owned by the hook account other than its state entries,
+/// such as trust lines or the hooks themselves. 0 by default.
+pub fn set_owner_count(owner_count: u32) {
+    with_host(|host| host.owner_count = owner_count);
+}
+
+/// The number of objects owned by the hook account, including its state entries
+pub fn owner_count() -> u32 {
+    with_host(|host| host.owner_count + host.state.len() as u32)
+}
+
+/// Runs `hook` as one invocation of the hook, with the originating transaction set up so far.
+///
+/// The reservation of emitted transactions, the slots, the skipped hooks, the request to be
+/// executed again and the traces of previous invocations are cleared first. If the hook rolls back, its state changes and emitted
+/// transactions are discarded, otherwise the emitted transactions are queued for
+/// [callback].
+pub fn invoke<'a>(hook: impl FnOnce() -> Outcome<'a>) -> Invocation {
+    let state_before = with_host(|host| {
+        host.reserved = None;
+        host.emitted.clear();
+        host.traces.clear();
+        host.slots.clear();
+        host.skipped_hooks.clear();
+        host.again_requested = false;
+        host.state.clone()
+    });
+    let first_emit = with_host(|host| host.emit_count) + 1;
+
+    let (accepted, code, msg) = match hook() {
+        Outcome::Accept { code, msg } => (true, code, msg.into()),
+        Outcome::Rollback { code, msg } => (false, code, msg.into()),
+    };
+
+    with_host(|host| {
+        let emitted: Vec<EmittedTxn> = host
+            .emitted
+            .iter()
+            .enumerate()
+            .map(|(i, tx)| EmittedTxn {
+                hash: emitted_txn_hash(first_emit + i),
+                tx: tx.clone(),
+            })
+            .collect();
+        if accepted {
+            host.pending_emitted.extend(emitted.iter().cloned());
+        } else {
+            host.state = state_before;
+        }
+
+        Invocation {
+            accepted,
+            code,
+            msg,
+            emitted,
+        }
+    })
+}
+
+/// Emitted transactions that are waiting for their callback, oldest first
+pub fn pending_emitted() -> Vec<EmittedTxn> {
+    with_host(|host| host.pending_emitted.iter().cloned().collect())
+}
+
+/// Runs `cbak` for the oldest emitted transaction that is waiting for its callback, or
+/// returns `None` if there is none.
+///
+/// The emitted transaction becomes the originating transaction, replacing the one set
+/// up before, and `cbak` is called with 0 if `outcome` is [EmitOutcome::Applied] and 1
+/// if it is [EmitOutcome::Expired]. Otherwise it runs like [invoke].
+pub fn callback<'a>(
+    outcome: EmitOutcome,
+    cbak: impl FnOnce(u32) -> Outcome<'a>,
+) -> Option<Invocation> {
+    let emitted = with_host(|host| host.pending_emitted.pop_front())?;
+
+    let tx = decode(&emitted.tx);
+    with_host(|host| {
+        host.otxn_id = emitted.hash;
+        host.otxn_type = tx.txn_type().map_or(-1, |txn_type| txn_type as i64);
+        host.otxn_params.clear();
+        host.otxn_fields = tx
+            .fields
+            .into_iter()
+            .map(|field| (field.field_id, field.value))
+            .collect();
+    });
+    let what = match outcome {
+        EmitOutcome::Applied => 0,
+        EmitOutcome::Expired => 1,
+    };
+
+    Some(invoke(|| cbak(what)))
+}
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::callback::*;
+    use crate::fixtures::{DESTINATION, HOOK_ACCOUNT};
+    use crate::mock::{self, reset, set_hook_account, set_otxn_param, state};
+    use crate::transaction::*;
+
+    const PAYOUT_KEY: &[u8; 6] = b"payout";
+
+    // Counts invocations, rolls back if asked to and pays out every second invocation
+    fn count_and_pay_out() -> Outcome<'static> {
+        let count = match crate::state::<8>(b"count") {
+            Ok(count) => u64::from_be_bytes(count) + 1,
+            Err(HookError::DoesntExist) => 1,
+            Err(err) => return err.into(),
+        };
+        if let Err(err) = state_set(&count.to_be_bytes(), b"count") {
+            return err.into();
+        }
+
+        if count % 2 == 0 {
+            let mut reservation = match EmitReservation::new(1) {
+                Ok(reservation) => reservation,
+                Err(err) => return err.into(),
+            };
+            let mut buffer = XrpPaymentBuilder::uninit_buffer();
+            let tx = match XrpPaymentBuilder::new(Drops(1000), &DESTINATION, None, None)
+                .build(&mut buffer)
+            {
+                Ok(tx) => tx,
+                Err(err) => return err.into(),
+            };
+            if let Err(err) = emit_tracked(&mut reservation, tx, PAYOUT_KEY) {
+                return err.into();
+            }
+        }
+
+        if otxn_param::<1>(b"fail").is_ok() {
+            return Outcome::Rollback {
+                code: count as i64,
+                msg: b"asked to fail",
+            };
+        }
+        Outcome::Accept {
+            code: count as i64,
+            msg: b"counted",
+        }
+    }
+
+    fn on_payout(what: u32) -> Outcome<'static> {
+        match take_tracked_emit(PAYOUT_KEY, what) {
+            Ok(Some(EmitOutcome::Applied)) => Outcome::Accept {
+                code: 0,
+                msg: b"paid out",
+            },
+            Ok(Some(EmitOutcome::Expired)) => Outcome::Accept {
+                code: 1,
+                msg: b"payout expired",
+            },
+            Ok(None) => Outcome::Accept {
+                code: 2,
+                msg: b"not a payout",
+            },
+            Err(err) => err.into(),
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn keeps_state_across_invocations() {
+        reset();
+
+        let first = invoke(count_and_pay_out);
+        assert!(first.accepted);
+        assert_eq!((first.code, &first.msg[..]), (1, &b"counted"[..]));
+        assert!(first.emitted.is_empty());
+        assert_eq!(invoke(count_and_pay_out).code, 2);
+        assert_eq!(invoke(count_and_pay_out).code, 3);
+        assert_eq!(state(b"count").as_deref(), Some(&3u64.to_be_bytes()[..]));
+    }
+
+    #[wasm_bindgen_test]
+    fn discards_rolled_back_invocations() {
+        reset();
+        assert!(invoke(count_and_pay_out).accepted);
+
+        set_otxn_param(b"fail", b"1");
+        let rolled_back = invoke(count_and_pay_out);
+        assert!(!rolled_back.accepted);
+        assert_eq!(rolled_back.emitted.len(), 1);
+
+        assert_eq!(state(b"count").as_deref(), Some(&1u64.to_be_bytes()[..]));
+        assert_eq!(state(PAYOUT_KEY), None);
+        assert!(pending_emitted().is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    fn runs_callbacks_of_emitted_transactions() {
+        reset();
+        set_hook_account(HOOK_ACCOUNT);
+        assert!(callback(EmitOutcome::Applied, on_payout).is_none());
+
+        invoke(count_and_pay_out);
+        let emitting = invoke(count_and_pay_out);
+        assert_eq!(pending_emitted(), emitting.emitted);
+        assert_eq!(
+            decode(&emitting.emitted[0].tx).drops(FieldId::Amount),
+            Some(1000)
+        );
+
+        let payout = callback(EmitOutcome::Applied, on_payout).unwrap();
+        assert_eq!((payout.code, &payout.msg[..]), (0, &b"paid out"[..]));
+        assert_eq!(state(PAYOUT_KEY), None);
+        assert!(pending_emitted().is_empty());
+
+        // the emitted payment is the originating transaction of the callback
+        assert!(matches!(
+            otxn_field::<ACC_ID_LEN>(FieldId::Destination),
+            Ok(destination) if destination == DESTINATION.0
+        ));
+    }
+
+    #[wasm_bindgen_test]
+    fn requires_the_reserve_for_new_state_entries() {
+        reset();
+        // enough for the account, a hook and one more object
+        mock::set_balance(1_400_000);
+        set_owner_count(1);
+
+        assert!(matches!(state_set(b"1", b"first"), Ok(1)));
+        assert_eq!(owner_count(), 2);
+        // updating an existing entry doesn't need more reserve
+        assert!(matches!(state_set(b"2", b"first"), Ok(1)));
+        assert!(matches!(
+            state_set(b"1", b"second"),
+            Err(HookError::ReserveInsufficient)
+        ));
+
+        // the reserve is freed again when an entry is deleted
+        assert!(matches!(state_set(&[], b"first"), Ok(0)));
+        assert!(matches!(state_set(b"1", b"second"), Ok(1)));
+
+        set_reserves(1_000_000, 100_000);
+        assert!(matches!(state_set(b"1", b"third"), Ok(1)));
+        assert_eq!(owner_count(), 3);
+    }
+}
@@ -0,0 +1,1219 @@
+//! In-memory fake of the hooks host for unit tests.
+//!
+//! With the `mock` feature, hooks-rs defines the host functions itself instead of importing
+//! them, so hook logic and transaction builders can be tested with `wasm_bindgen_test`
+//! without a xahaud node. The functions in this module set up what the host returns and
+//! inspect what the hook did.
+//!
+//! The host functions take pointers as `u32`. On wasm32 they are the address itself, and
+//! on 64 bit targets the mock looks up the pointers that were last passed through
+//! [host_ptr](crate::host_ptr), so hook logic can also run in a normal binary with the
+//! `std` feature. Every test starts from a clean host after calling [reset].
+//!
+//! Only a subset of the host is implemented: the hook account, ledger sequence and
+//! time, the originating transaction, hook and Invoke parameters, the hook chain,
+//! `hook_again`, state, foreign state, emitting, the guard function, tracing, the
+//! r-addresses of `util_raddr`, and the sums, negations and ratios of XFLs. Signatures
+//! aren't checked cryptographically: `util_verify` accepts the signatures made by
+//! [fake_signature]. The originating transaction can be slotted, but only its fields can
+//! be read from the slot, through `slot_subfield`. To test a hook across several
+//! invocations, run each of them with [invoke] and the callbacks of emitted transactions
+//! with [callback], see [the simulated ledger](#simulated-ledger). [accept] and
+//! [rollback](crate::rollback) can't return, so they panic with their message and code
+//! instead. Test logic that returns an [Outcome](crate::Outcome) rather than the entry
+//! points themselves.
+//!
+//! # Example
+//! ```
+//! #[wasm_bindgen_test]
+//! fn pays_the_sender() {
+//!     mock::reset();
+//!     mock::set_hook_account(HOOK_ACCOUNT);
+//!     mock::set_otxn_field(FieldId::Account, &SENDER);
+//!
+//!     assert!(handle() == Outcome::Accept { code: 0, msg: b"paid" });
+//!     assert_eq!(mock::emitted().len(), 1);
+//! }
+//! ```
+//!
+//! # Simulated ledger
+//! [invoke] runs the hook logic like one invocation of the hook: state changes and
+//! emitted transactions are kept if it accepts and discarded if it rolls back. Emitted
+//! transactions of accepted invocations are queued until [callback] runs `cbak` for
+//! them. Creating a state entry needs the reserve for one more owned object, see
+//! [set_balance] and [set_reserves].
+//!
+//! ```
+//! #[wasm_bindgen_test]
+//! fn counts_invocations() {
+//!     mock::reset();
+//!
+//!     assert!(mock::invoke(count).accepted);
+//!     assert!(mock::invoke(count).accepted);
+//!     assert_eq!(mock::state(b"count").as_deref(), Some(&2u64.to_be_bytes()[..]));
+//! }
+//! ```
+//!
+//! # Recording and replaying
+//! With the `record` feature, [record] logs every host call made by hook logic with the
+//! answer of the host, and [replay] answers the same calls from such a recording instead
+//! of the mock host, failing as soon as the hook makes a different call. A recording can
+//! be written out with [calls_to_text] and kept with the regression test that replays
+//! it, also when it was put together from what a hook did on a ledger.
+//!
+//! ```
+//! #[wasm_bindgen_test]
+//! fn pays_like_before() {
+//!     let calls = mock::calls_from_text(include_str!("pays_the_sender.calls"));
+//!
+//!     let invocation = mock::replay(&calls, || mock::invoke(handle));
+//!     assert_eq!(invocation.msg, b"paid");
+//! }
+//! ```
+
+extern crate std;
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, VecDeque};
+use std::vec::Vec;
+
+use crate::api::*;
+
+mod ledger;
+#[cfg(any(test, feature = "record"))]
+mod record;
+
+pub use ledger::*;
+#[cfg(any(test, feature = "record"))]
+pub use record::*;
+
+/// Length of the emit details written by `etxn_details`, as for hooks with a `cbak` function
+const EMIT_DETAILS_LEN: usize = 138;
+
+/// Number of slots of the host, which are numbered from 1
+const SLOTS: u32 = 255;
+
+// What a slot holds
+#[derive(Clone)]
+enum Slotted {
+    Otxn,
+    // a field of the originating transaction, serialized as `otxn_field` returns it
+    Field(Vec<u8>),
+}
+
+struct Host {
+    hook_account: AccountId,
+    ledger_seq: i64,
+    ledger_last_time: i64,
+    fee_base: i64,
+    otxn_type: i64,
+    otxn_id: Hash,
+    otxn_fields: BTreeMap<u32, Vec<u8>>,
+    otxn_params: BTreeMap<Vec<u8>, Vec<u8>>,
+    hook_params: BTreeMap<Vec<u8>, Vec<u8>>,
+    state: BTreeMap<[u8; STATE_KEY_LEN], Vec<u8>>,
+    // the state of other hooks, by account, namespace and key
+    foreign_state: BTreeMap<(Buffer<ACC_ID_LEN>, Hash, [u8; STATE_KEY_LEN]), Vec<u8>>,
+    reserved: Option<u32>,
+    execution: ExecutionKind,
+    again_requested: bool,
+    emitted: Vec<Vec<u8>>,
+    emit_count: usize,
+    traces: Vec<Vec<u8>>,
+    balance: u64,
+    reserve_base: u64,
+    reserve_increment: u64,
+    owner_count: u32,
+    pending_emitted: VecDeque<EmittedTxn>,
+    slots: BTreeMap<u32, Slotted>,
+    // the hashes of the hooks at every position of the chain, `None` for empty positions
+    hook_chain: Vec<Option<Hash>>,
+    hook_pos: u32,
+    skipped_hooks: Vec<Hash>,
+}
+
+impl Host {
+    fn new() -> Self {
+        Self {
+            hook_account: AccountId::ZERO,
+            ledger_seq: 1,
+            ledger_last_time: 0,
+            fee_base: 10,
+            otxn_type: TxnType::Payment as i64,
+            otxn_id: [0; HASH_LEN],
+            otxn_fields: BTreeMap::new(),
+            otxn_params: BTreeMap::new(),
+            hook_params: BTreeMap::new(),
+            state: BTreeMap::new(),
+            foreign_state: BTreeMap::new(),
+            reserved: None,
+            execution: ExecutionKind::Strong,
+            again_requested: false,
+            emitted: Vec::new(),
+            emit_count: 0,
+            traces: Vec::new(),
+            balance: 1_000_000_000,
+            reserve_base: 1_000_000,
+            reserve_increment: 200_000,
+            owner_count: 0,
+            pending_emitted: VecDeque::new(),
+            slots: BTreeMap::new(),
+            hook_chain: std::vec![Some([0; HASH_LEN])],
+            hook_pos: 0,
+            skipped_hooks: Vec::new(),
+        }
+    }
+
+    /// Whether the hook account has the reserve for `count` more owned objects
+    fn can_afford_objects(&self, count: u32) -> bool {
+        let owned_objects = (self.owner_count + self.state.len() as u32 + count) as u64;
+        self.balance >= self.reserve_base + owned_objects * self.reserve_increment
+    }
+
+    /// Puts `slotted` into `slot_no`, or into the first free slot if it is 0, returning the
+    /// slot number or the error code of the host
+    fn set_slot(&mut self, slot_no: u32, slotted: Slotted) -> i64 {
+        let slot_no = match slot_no {
+            0 => match (1..=SLOTS).find(|slot_no| !self.slots.contains_key(slot_no)) {
+                Some(slot_no) => slot_no,
+                None => return error(HookError::NoFreeSlots),
+            },
+            slot_no if slot_no > SLOTS => return error(HookError::InvalidArgument),
+            slot_no => slot_no,
+        };
+        self.slots.insert(slot_no, slotted);
+
+        slot_no as i64
+    }
+}
+
+std::thread_local! {
+    static HOST: RefCell<Host> = RefCell::new(Host::new());
+}
+
+fn with_host<T>(f: impl FnOnce(&mut Host) -> T) -> T {
+    HOST.with(|host| f(&mut host.borrow_mut()))
+}
+
+/// Resets the host, clearing everything set up or recorded by previous tests
+pub fn reset() {
+    with_host(|host| *host = Host::new());
+    #[cfg(any(test, feature = "record"))]
+    record::stop();
+}
+
+/// Sets the account the hook is installed on
+pub fn set_hook_account(account: AccountId) {
+    with_host(|host| host.hook_account = account);
+}
+
+/// Sets the sequence of the ledger the hook is running in, 1 by default
+pub fn set_ledger_seq(ledger_seq: u32) {
+    with_host(|host| host.ledger_seq = ledger_seq as i64);
+}
+
+/// Sets the close time of the last ledger in seconds since the ripple epoch
+pub fn set_ledger_last_time(ledger_last_time: u32) {
+    with_host(|host| host.ledger_last_time = ledger_last_time as i64);
+}
+
+/// Sets the base fee returned for emitted transactions, 10 drops by default
+pub fn set_fee_base(drops: u64) {
+    with_host(|host| host.fee_base = drops as i64);
+}
+
+/// Sets the type of the originating transaction, [TxnType::Payment] by default
+pub fn set_otxn_type(txn_type: TxnType) {
+    with_host(|host| host.otxn_type = txn_type as i64);
+}
+
+/// Sets the hash of the originating transaction
+pub fn set_otxn_id(txn_id: Hash) {
+    with_host(|host| host.otxn_id = txn_id);
+}
+
+/// Sets a field of the originating transaction, serialized as [otxn_field] returns it
+pub fn set_otxn_field(field_id: FieldId, value: &[u8]) {
+    with_host(|host| host.otxn_fields.insert(field_id as u32, value.into()));
+}
+
+/// Sets a parameter of the originating Invoke transaction
+pub fn set_otxn_param(name: &[u8], value: &[u8]) {
+    with_host(|host| host.otxn_params.insert(name.into(), value.into()));
+}
+
+/// Sets an install-time hook parameter
+pub fn set_hook_param(name: &[u8], value: &[u8]) {
+    with_host(|host| host.hook_params.insert(name.into(), value.into()));
+}
+
+/// Sets how the hook is being executed, which decides whether [hook_again] is allowed.
+/// [ExecutionKind::Strong] by default.
+pub fn set_execution(execution: ExecutionKind) {
+    with_host(|host| host.execution = execution);
+}
+
+/// Whether the hook requested with [hook_again] to be executed again as weak
+pub fn again_requested() -> bool {
+    with_host(|host| host.again_requested)
+}
+
+/// Sets the hook chain of the hook account and the position of the executing hook in it.
+///
+/// The chain is the hash of the hook at every position, or `None` for an empty position.
+/// By default it only has the executing hook, with a hash of zeroes.
+pub fn set_hook_chain(hooks: &[Option<Hash>], hook_pos: u32) {
+    assert!(
+        matches!(hooks.get(hook_pos as usize), Some(Some(_))),
+        "no hook at the position of the executing hook"
+    );
+    with_host(|host| {
+        host.hook_chain = hooks.into();
+        host.hook_pos = hook_pos;
+    });
+}
+
+/// The hashes of the hooks the hook skipped with [hook_skip] and didn't cancel the skip of
+pub fn skipped_hooks() -> Vec<Hash> {
+    with_host(|host| host.skipped_hooks.clone())
+}
+
+/// Sets a state entry of the hook
+pub fn set_state(key: &[u8], value: &[u8]) {
+    let key = state_key(key).expect("state key is at most 32 bytes");
+    with_host(|host| host.state.insert(key, value.into()));
+}
+
+/// The value of a state entry of the hook, if it is set
+pub fn state(key: &[u8]) -> Option<Vec<u8>> {
+    let key = state_key(key)?;
+    with_host(|host| host.state.get(&key).cloned())
+}
+
+/// Sets a state entry of another hook, which [state_foreign] reads from the namespace
+/// `namespace` of `account`
+pub fn set_foreign_state(account: AccountId, namespace: Hash, key: &[u8], value: &[u8]) {
+    let key = state_key(key).expect("state key is at most 32 bytes");
+    with_host(|host| {
+        host.foreign_state
+            .insert((account.0, namespace, key), value.into())
+    });
+}
+
+/// The signature of `payload` by `public_key` that the mock `util_verify` accepts, which
+/// is the SHA-512Half of the key followed by the payload
+pub fn fake_signature(public_key: &PublicKey, payload: &[u8]) -> Hash {
+    let mut signed: Vec<u8> = public_key.as_bytes().into();
+    signed.extend_from_slice(payload);
+
+    crate::sha512::sha512_half(&signed)
+}
+
+/// The transactions the hook emitted, in order
+pub fn emitted() -> Vec<Vec<u8>> {
+    with_host(|host| host.emitted.clone())
+}
+
+/// The messages the hook traced, in order. The message and data of each trace call are
+/// joined with a space.
+pub fn traces() -> Vec<Vec<u8>> {
+    with_host(|host| host.traces.clone())
+}
+
+// State keys shorter than 32 bytes are padded with leading zeroes, like the host does
+fn state_key(key: &[u8]) -> Option<[u8; STATE_KEY_LEN]> {
+    if key.is_empty() || key.len() > STATE_KEY_LEN {
+        return None;
+    }
+    let mut padded = [0; STATE_KEY_LEN];
+    padded[STATE_KEY_LEN - key.len()..].copy_from_slice(key);
+
+    Some(padded)
+}
+
+// Not a real transaction hash, but unique within a test for the first 255 emitted transactions
+fn emitted_txn_hash(emit_count: usize) -> Hash {
+    [emit_count as u8; HASH_LEN]
+}
+
+fn error(err: HookError) -> i64 {
+    err.code() as i64
+}
+
+// An XFL as its signed mantissa and exponent
+fn xfl_parts(xfl: i64) -> (i128, i32) {
+    if xfl == 0 {
+        return (0, 0);
+    }
+    let mantissa = (xfl & ((1 << 54) - 1)) as i128;
+    let exponent = ((xfl >> 54) & 0xFF) as i32 - 97;
+    if xfl & (1 << 62) == 0 {
+        (-mantissa, exponent)
+    } else {
+        (mantissa, exponent)
+    }
+}
+
+// `mantissa * 10^exponent` as a normalized XFL, dropping the digits that don't fit into
+// the 16 of its mantissa
+fn xfl_from_parts(mantissa: i128, mut exponent: i32) -> i64 {
+    if mantissa == 0 {
+        return 0;
+    }
+    let mut digits = mantissa.unsigned_abs();
+    while digits >= 10_000_000_000_000_000 {
+        digits /= 10;
+        exponent += 1;
+    }
+    while digits < 1_000_000_000_000_000 {
+        digits *= 10;
+        exponent -= 1;
+    }
+    if exponent < -96 {
+        return 0;
+    }
+    if exponent > 80 {
+        return error(HookError::Overflow);
+    }
+
+    (((mantissa > 0) as i64) << 62) | (((exponent + 97) as i64) << 54) | digits as i64
+}
+
+// A host call as the hook makes it, before the host answers it
+#[cfg_attr(not(any(test, feature = "record")), allow(dead_code))]
+struct Call<'a> {
+    function: &'static str,
+    // arguments other than buffers
+    args: &'a [i64],
+    // buffers passed to the host, in the order of the arguments
+    input: &'a [&'a [u8]],
+    // the buffer the host writes into, as its pointer and length
+    write_to: Option<(u32, u32)>,
+}
+
+impl<'a> Call<'a> {
+    fn new(function: &'static str, args: &'a [i64], input: &'a [&'a [u8]]) -> Self {
+        Self {
+            function,
+            args,
+            input,
+            write_to: None,
+        }
+    }
+
+    fn writing(
+        function: &'static str,
+        args: &'a [i64],
+        input: &'a [&'a [u8]],
+        write_ptr: u32,
+        write_len: u32,
+    ) -> Self {
+        Self {
+            write_to: Some((write_ptr, write_len)),
+            ..Self::new(function, args, input)
+        }
+    }
+}
+
+unsafe fn read<'a>(ptr: u32, len: u32) -> &'a [u8] {
+    core::slice::from_raw_parts(resolve_ptr(ptr) as *const u8, len as usize)
+}
+
+// The pointers last passed through `host_ptr`, so the host functions can find the memory
+// they point to when pointers don't fit into 32 bits. No host function takes more than
+// four pointers, so the last few are enough.
+#[cfg(target_pointer_width = "64")]
+std::thread_local! {
+    static POINTERS: RefCell<VecDeque<usize>> = const { RefCell::new(VecDeque::new()) };
+}
+
+#[cfg(target_pointer_width = "64")]
+const POINTERS_KEPT: usize = 16;
+
+#[cfg(target_pointer_width = "64")]
+pub(crate) fn register_ptr(ptr: usize) -> u32 {
+    POINTERS.with(|pointers| {
+        let mut pointers = pointers.borrow_mut();
+        if pointers.len() == POINTERS_KEPT {
+            pointers.pop_back();
+        }
+        pointers.push_front(ptr);
+    });
+
+    ptr as u32
+}
+
+// The latest pointer passed through `host_ptr` that `ptr` is the lower half of
+#[cfg(target_pointer_width = "64")]
+fn resolve_ptr(ptr: u32) -> usize {
+    POINTERS.with(|pointers| {
+        pointers
+            .borrow()
+            .iter()
+            .copied()
+            .find(|&registered| registered as u32 == ptr)
+            .unwrap_or_else(|| panic!("pointer {ptr:#x} wasn't passed through host_ptr"))
+    })
+}
+
+#[cfg(target_pointer_width = "32")]
+fn resolve_ptr(ptr: u32) -> usize {
+    ptr as usize
+}
+
+// Writes `data` to the buffer, failing like the host if it does not fit
+unsafe fn write(write_ptr: u32, write_len: u32, data: &[u8]) -> i64 {
+    if (write_len as usize) < data.len() {
+        return error(HookError::TooSmall);
+    }
+    core::slice::from_raw_parts_mut(resolve_ptr(write_ptr) as *mut u8, data.len())
+        .copy_from_slice(data);
+
+    data.len() as i64
+}
+
+// Runs the host function `body` for `call`, recording or replaying it with the `record`
+// feature
+#[inline(always)]
+unsafe fn host_call(call: Call<'_>, body: impl FnOnce() -> i64) -> i64 {
+    #[cfg(any(test, feature = "record"))]
+    return record::host_call(call, body);
+
+    #[cfg(not(any(test, feature = "record")))]
+    {
+        let _ = call;
+        body()
+    }
+}
+
+// Records or checks `call` of a host function that doesn't return, before running it
+#[inline(always)]
+fn control_call(call: Call<'_>) {
+    #[cfg(any(test, feature = "record"))]
+    record::control_call(call);
+
+    #[cfg(not(any(test, feature = "record")))]
+    let _ = call;
+}
+
+// The host functions, linked instead of the imports declared in `c`
+mod host {
+    use super::*;
+
+    #[no_mangle]
+    extern "C" fn _g(_guard_id: u32, _maxiter: u32) -> i32 {
+        1
+    }
+
+    #[no_mangle]
+    unsafe extern "C" fn accept(read_ptr: u32, read_len: u32, error_code: i64) -> i64 {
+        let msg = read(read_ptr, read_len);
+        control_call(Call {
+            function: "accept",
+            args: &[error_code],
+            input: &[msg],
+            write_to: None,
+        });
+        panic!(
+            "hook accepted with code {error_code}: {}",
+            std::string::String::from_utf8_lossy(msg)
+        );
+    }
+
+    #[no_mangle]
+    unsafe extern "C" fn rollback(read_ptr: u32, read_len: u32, error_code: i64) -> i64 {
+        let msg = read(read_ptr, read_len);
+        control_call(Call {
+            function: "rollback",
+            args: &[error_code],
+            input: &[msg],
+            write_to: None,
+        });
+        panic!(
+            "hook rolled back with code {error_code}: {}",
+            std::string::String::from_utf8_lossy(msg)
+        );
+    }
+
+    #[no_mangle]
+    unsafe extern "C" fn hook_account(write_ptr: u32, write_len: u32) -> i64 {
+        host_call(
+            Call::writing("hook_account", &[], &[], write_ptr, write_len),
+            || {
+                let account = with_host(|host| host.hook_account);
+                write(write_ptr, write_len, &account.0)
+            },
+        )
+    }
+
+    #[no_mangle]
+    extern "C" fn ledger_seq() -> i64 {
+        unsafe {
+            host_call(Call::new("ledger_seq", &[], &[]), || {
+                with_host(|host| host.ledger_seq)
+            })
+        }
+    }
+
+    #[no_mangle]
+    extern "C" fn ledger_last_time() -> i64 {
+        unsafe {
+            host_call(Call::new("ledger_last_time", &[], &[]), || {
+                with_host(|host| host.ledger_last_time)
+            })
+        }
+    }
+
+    #[no_mangle]
+    extern "C" fn otxn_type() -> i64 {
+        unsafe {
+            host_call(Call::new("otxn_type", &[], &[]), || {
+                with_host(|host| host.otxn_type)
+            })
+        }
+    }
+
+    #[no_mangle]
+    unsafe extern "C" fn otxn_id(write_ptr: u32, write_len: u32, flags: u32) -> i64 {
+        host_call(
+            Call::writing("otxn_id", &[flags as i64], &[], write_ptr, write_len),
+            || {
+                let txn_id = with_host(|host| host.otxn_id);
+                write(write_ptr, write_len, &txn_id)
+            },
+        )
+    }
+
+    #[no_mangle]
+    unsafe extern "C" fn otxn_field(write_ptr: u32, write_len: u32, field_id: u32) -> i64 {
+        host_call(
+            Call::writing("otxn_field", &[field_id as i64], &[], write_ptr, write_len),
+            || match with_host(|host| host.otxn_fields.get(&field_id).cloned()) {
+                Some(value) => write(write_ptr, write_len, &value),
+                None => error(HookError::DoesntExist),
+            },
+        )
+    }
+
+    #[no_mangle]
+    extern "C" fn otxn_slot(slot_no: u32) -> i64 {
+        unsafe {
+            host_call(Call::new("otxn_slot", &[slot_no as i64], &[]), || {
+                with_host(|host| host.set_slot(slot_no, Slotted::Otxn))
+            })
+        }
+    }
+
+    #[no_mangle]
+    extern "C" fn slot_subfield(parent_slot: u32, field_id: u32, new_slot: u32) -> i64 {
+        let args = [parent_slot as i64, field_id as i64, new_slot as i64];
+        unsafe {
+            host_call(Call::new("slot_subfield", &args, &[]), || {
+                with_host(|host| {
+                    let field = match host.slots.get(&parent_slot) {
+                        Some(Slotted::Otxn) => host.otxn_fields.get(&field_id).cloned(),
+                        Some(Slotted::Field(_)) => return error(HookError::NotAnObject),
+                        None => return error(HookError::DoesntExist),
+                    };
+                    match field {
+                        Some(field) => host.set_slot(new_slot, Slotted::Field(field)),
+                        None => error(HookError::DoesntExist),
+                    }
+                })
+            })
+        }
+    }
+
+    #[no_mangle]
+    unsafe extern "C" fn slot(write_ptr: u32, write_len: u32, slot_no: u32) -> i64 {
+        host_call(
+            Call::writing("slot", &[slot_no as i64], &[], write_ptr, write_len),
+            || match with_host(|host| host.slots.get(&slot_no).cloned()) {
+                Some(Slotted::Field(field)) => write(write_ptr, write_len, &field),
+                Some(Slotted::Otxn) => error(HookError::NotImplemented),
+                None => error(HookError::DoesntExist),
+            },
+        )
+    }
+
+    #[no_mangle]
+    extern "C" fn slot_clear(slot_no: u32) -> i64 {
+        unsafe {
+            host_call(
+                Call::new("slot_clear", &[slot_no as i64], &[]),
+                || match with_host(|host| host.slots.remove(&slot_no)) {
+                    Some(_) => 1,
+                    None => error(HookError::DoesntExist),
+                },
+            )
+        }
+    }
+
+    #[no_mangle]
+    unsafe extern "C" fn otxn_param(
+        write_ptr: u32,
+        write_len: u32,
+        read_ptr: u32,
+        read_len: u32,
+    ) -> i64 {
+        let name = read(read_ptr, read_len);
+        host_call(
+            Call::writing("otxn_param", &[], &[name], write_ptr, write_len),
+            || match with_host(|host| host.otxn_params.get(name).cloned()) {
+                Some(value) => write(write_ptr, write_len, &value),
+                None => error(HookError::DoesntExist),
+            },
+        )
+    }
+
+    #[no_mangle]
+    unsafe extern "C" fn hook_param(
+        write_ptr: u32,
+        write_len: u32,
+        read_ptr: u32,
+        read_len: u32,
+    ) -> i64 {
+        let name = read(read_ptr, read_len);
+        host_call(
+            Call::writing("hook_param", &[], &[name], write_ptr, write_len),
+            || match with_host(|host| host.hook_params.get(name).cloned()) {
+                Some(value) => write(write_ptr, write_len, &value),
+                None => error(HookError::DoesntExist),
+            },
+        )
+    }
+
+    #[no_mangle]
+    extern "C" fn hook_again() -> i64 {
+        unsafe {
+            host_call(Call::new("hook_again", &[], &[]), || {
+                with_host(|host| {
+                    if !host.execution.is_strong() {
+                        return error(HookError::PrerequisiteNotMet);
+                    }
+                    if host.again_requested {
+                        return error(HookError::AlreadySet);
+                    }
+                    host.again_requested = true;
+                    1
+                })
+            })
+        }
+    }
+
+    #[no_mangle]
+    extern "C" fn hook_pos() -> i64 {
+        unsafe {
+            host_call(Call::new("hook_pos", &[], &[]), || {
+                with_host(|host| host.hook_pos as i64)
+            })
+        }
+    }
+
+    #[no_mangle]
+    unsafe extern "C" fn hook_hash(write_ptr: u32, write_len: u32, hook_no: i32) -> i64 {
+        host_call(
+            Call::writing("hook_hash", &[hook_no as i64], &[], write_ptr, write_len),
+            || {
+                let hook_hash = with_host(|host| {
+                    let hook_pos = match hook_no {
+                        -1 => host.hook_pos as usize,
+                        hook_no => hook_no as usize,
+                    };
+                    host.hook_chain.get(hook_pos).copied().flatten()
+                });
+                match hook_hash {
+                    Some(hook_hash) => write(write_ptr, write_len, &hook_hash),
+                    None => error(HookError::DoesntExist),
+                }
+            },
+        )
+    }
+
+    #[no_mangle]
+    unsafe extern "C" fn hook_skip(read_ptr: u32, read_len: u32, flags: u32) -> i64 {
+        let hook_hash = read(read_ptr, read_len);
+        host_call(
+            Call::new("hook_skip", &[flags as i64], &[hook_hash]),
+            || {
+                let Some(hook_hash) = Hash::try_from(hook_hash).ok() else {
+                    return error(HookError::InvalidArgument);
+                };
+                with_host(|host| {
+                    if !host.hook_chain.contains(&Some(hook_hash)) {
+                        return error(HookError::DoesntExist);
+                    }
+                    host.skipped_hooks.retain(|&skipped| skipped != hook_hash);
+                    if flags == HookSkipFlag::Skip as u32 {
+                        host.skipped_hooks.push(hook_hash);
+                    }
+                    1
+                })
+            },
+        )
+    }
+
+    #[no_mangle]
+    unsafe extern "C" fn state(
+        write_ptr: u32,
+        write_len: u32,
+        kread_ptr: u32,
+        kread_len: u32,
+    ) -> i64 {
+        let key = read(kread_ptr, kread_len);
+        host_call(
+            Call::writing("state", &[], &[key], write_ptr, write_len),
+            || {
+                let key = match state_key(key) {
+                    Some(key) => key,
+                    None => return error(HookError::TooBig),
+                };
+                match with_host(|host| host.state.get(&key).cloned()) {
+                    Some(value) => write(write_ptr, write_len, &value),
+                    None => error(HookError::DoesntExist),
+                }
+            },
+        )
+    }
+
+    #[no_mangle]
+    unsafe extern "C" fn state_set(
+        read_ptr: u32,
+        read_len: u32,
+        kread_ptr: u32,
+        kread_len: u32,
+    ) -> i64 {
+        let key = read(kread_ptr, kread_len);
+        let value = read(read_ptr, read_len);
+        host_call(Call::new("state_set", &[], &[value, key]), || {
+            let key = match state_key(key) {
+                Some(key) => key,
+                None => return error(HookError::TooBig),
+            };
+            let result = with_host(|host| {
+                if value.is_empty() {
+                    host.state.remove(&key);
+                } else {
+                    if !host.state.contains_key(&key) && !host.can_afford_objects(1) {
+                        return Err(HookError::ReserveInsufficient);
+                    }
+                    host.state.insert(key, value.into());
+                }
+                Ok(())
+            });
+
+            match result {
+                Ok(()) => read_len as i64,
+                Err(err) => error(err),
+            }
+        })
+    }
+
+    #[no_mangle]
+    #[allow(clippy::too_many_arguments)]
+    unsafe extern "C" fn state_foreign(
+        write_ptr: u32,
+        write_len: u32,
+        kread_ptr: u32,
+        kread_len: u32,
+        nread_ptr: u32,
+        nread_len: u32,
+        aread_ptr: u32,
+        aread_len: u32,
+    ) -> i64 {
+        let key = read(kread_ptr, kread_len);
+        let namespace = read(nread_ptr, nread_len);
+        let account = read(aread_ptr, aread_len);
+        host_call(
+            Call::writing(
+                "state_foreign",
+                &[],
+                &[key, namespace, account],
+                write_ptr,
+                write_len,
+            ),
+            || {
+                let (Some(key), Some(namespace), Some(account)) = (
+                    state_key(key),
+                    Hash::try_from(namespace).ok(),
+                    Buffer::<ACC_ID_LEN>::try_from(account).ok(),
+                ) else {
+                    return error(HookError::InvalidArgument);
+                };
+                match with_host(|host| host.foreign_state.get(&(account, namespace, key)).cloned())
+                {
+                    Some(value) => write(write_ptr, write_len, &value),
+                    None => error(HookError::DoesntExist),
+                }
+            },
+        )
+    }
+
+    #[no_mangle]
+    unsafe extern "C" fn util_raddr(
+        write_ptr: u32,
+        write_len: u32,
+        read_ptr: u32,
+        read_len: u32,
+    ) -> i64 {
+        let account_id = read(read_ptr, read_len);
+        host_call(
+            Call::writing("util_raddr", &[], &[account_id], write_ptr, write_len),
+            || {
+                let Some(account_id) = Buffer::<ACC_ID_LEN>::try_from(account_id).ok() else {
+                    return error(HookError::InvalidArgument);
+                };
+                let r_address = crate::base58::encode_account_id(&AccountId(account_id));
+                let len = r_address
+                    .iter()
+                    .position(|&byte| byte == 0)
+                    .unwrap_or(r_address.len());
+                write(write_ptr, write_len, &r_address[..len])
+            },
+        )
+    }
+
+    #[no_mangle]
+    unsafe extern "C" fn util_verify(
+        dread_ptr: u32,
+        dread_len: u32,
+        sread_ptr: u32,
+        sread_len: u32,
+        kread_ptr: u32,
+        kread_len: u32,
+    ) -> i64 {
+        let payload = read(dread_ptr, dread_len);
+        let signature = read(sread_ptr, sread_len);
+        let public_key = read(kread_ptr, kread_len);
+        host_call(
+            Call::new("util_verify", &[], &[payload, signature, public_key]),
+            || {
+                let Some(public_key) = Buffer::<PUBLIC_KEY_LEN>::try_from(public_key).ok() else {
+                    return error(HookError::InvalidArgument);
+                };
+                (signature == super::fake_signature(&PublicKey(public_key), payload)) as i64
+            },
+        )
+    }
+
+    #[no_mangle]
+    extern "C" fn etxn_reserve(count: u32) -> i64 {
+        unsafe {
+            host_call(Call::new("etxn_reserve", &[count as i64], &[]), || {
+                with_host(|host| {
+                    if host.reserved.is_some() {
+                        return error(HookError::AlreadySet);
+                    }
+                    host.reserved = Some(count);
+
+                    count as i64
+                })
+            })
+        }
+    }
+
+    #[no_mangle]
+    unsafe extern "C" fn etxn_details(write_ptr: u32, write_len: u32) -> i64 {
+        host_call(
+            Call::writing("etxn_details", &[], &[], write_ptr, write_len),
+            || {
+                if with_host(|host| host.reserved.is_none()) {
+                    return error(HookError::PrerequisiteNotMet);
+                }
+
+                // emit details of a first generation emission without a hook hash
+                let (otxn_id, hook_account) = with_host(|host| (host.otxn_id, host.hook_account));
+                let mut details = Vec::with_capacity(EMIT_DETAILS_LEN);
+                // EmitDetails
+                details.push(0xED);
+                // EmitGeneration
+                details.extend_from_slice(&[0x20, 0x2E]);
+                details.extend_from_slice(&1u32.to_be_bytes());
+                // EmitBurden
+                details.push(0x3D);
+                details.extend_from_slice(&1u64.to_be_bytes());
+                // EmitParentTxnID
+                details.push(0x5B);
+                details.extend_from_slice(&otxn_id);
+                // EmitNonce
+                details.push(0x5C);
+                details.extend_from_slice(&[0; HASH_LEN]);
+                // EmitCallback
+                details.extend_from_slice(&[0x8A, ACC_ID_LEN as u8]);
+                details.extend_from_slice(&hook_account.0);
+                // EmitHookHash
+                details.push(0x5D);
+                details.extend_from_slice(&[0; HASH_LEN]);
+                details.push(0xE1);
+
+                write(write_ptr, write_len, &details)
+            },
+        )
+    }
+
+    #[no_mangle]
+    unsafe extern "C" fn etxn_fee_base(read_ptr: u32, read_len: u32) -> i64 {
+        let tx = read(read_ptr, read_len);
+        host_call(Call::new("etxn_fee_base", &[], &[tx]), || {
+            with_host(|host| host.fee_base)
+        })
+    }
+
+    #[no_mangle]
+    unsafe extern "C" fn emit(write_ptr: u32, write_len: u32, read_ptr: u32, read_len: u32) -> i64 {
+        let tx = read(read_ptr, read_len);
+        host_call(
+            Call::writing("emit", &[], &[tx], write_ptr, write_len),
+            || {
+                let emitted_count = with_host(|host| match host.reserved {
+                    None => Err(HookError::PrerequisiteNotMet),
+                    Some(reserved) if host.emitted.len() as u32 >= reserved => {
+                        Err(HookError::TooManyEmittedTxn)
+                    }
+                    Some(_) => {
+                        host.emitted.push(tx.into());
+                        host.emit_count += 1;
+                        Ok(host.emit_count)
+                    }
+                });
+
+                match emitted_count {
+                    // not a real transaction hash, but unique within a test
+                    Ok(emit_count) => write(write_ptr, write_len, &emitted_txn_hash(emit_count)),
+                    Err(err) => error(err),
+                }
+            },
+        )
+    }
+
+    #[no_mangle]
+    unsafe extern "C" fn trace(
+        mread_ptr: u32,
+        mread_len: u32,
+        dread_ptr: u32,
+        dread_len: u32,
+        as_hex: u32,
+    ) -> i64 {
+        let msg = read(mread_ptr, mread_len);
+        let data = read(dread_ptr, dread_len);
+        host_call(Call::new("trace", &[as_hex as i64], &[msg, data]), || {
+            let mut line: Vec<u8> = msg.into();
+            if !data.is_empty() {
+                line.push(b' ');
+                line.extend_from_slice(data);
+            }
+            with_host(|host| host.traces.push(line));
+
+            0
+        })
+    }
+
+    #[no_mangle]
+    unsafe extern "C" fn trace_num(read_ptr: u32, read_len: u32, number: i64) -> i64 {
+        let msg = read(read_ptr, read_len);
+        host_call(Call::new("trace_num", &[number], &[msg]), || {
+            let mut line: Vec<u8> = msg.into();
+            line.extend_from_slice(std::format!(" {number}").as_bytes());
+            with_host(|host| host.traces.push(line));
+
+            0
+        })
+    }
+
+    #[no_mangle]
+    extern "C" fn float_sum(float1: i64, float2: i64) -> i64 {
+        unsafe {
+            host_call(Call::new("float_sum", &[float1, float2], &[]), || {
+                let ((m1, e1), (m2, e2)) = (xfl_parts(float1), xfl_parts(float2));
+                if m1 == 0 || m2 == 0 || e1.abs_diff(e2) > 20 {
+                    // the smaller one doesn't change the 16 digits of the larger one
+                    return if m2 == 0 || (m1 != 0 && e1 > e2) {
+                        float1
+                    } else {
+                        float2
+                    };
+                }
+                let exponent = e1.min(e2);
+                xfl_from_parts(
+                    m1 * 10i128.pow((e1 - exponent) as u32)
+                        + m2 * 10i128.pow((e2 - exponent) as u32),
+                    exponent,
+                )
+            })
+        }
+    }
+
+    #[no_mangle]
+    extern "C" fn float_negate(float1: i64) -> i64 {
+        unsafe {
+            host_call(Call::new("float_negate", &[float1], &[]), || {
+                if float1 == 0 {
+                    0
+                } else {
+                    float1 ^ (1 << 62)
+                }
+            })
+        }
+    }
+
+    #[no_mangle]
+    extern "C" fn float_mulratio(
+        float1: i64,
+        round_up: u32,
+        numerator: u32,
+        denominator: u32,
+    ) -> i64 {
+        unsafe {
+            host_call(
+                Call::new(
+                    "float_mulratio",
+                    &[
+                        float1,
+                        round_up as i64,
+                        numerator as i64,
+                        denominator as i64,
+                    ],
+                    &[],
+                ),
+                || {
+                    if denominator == 0 {
+                        return error(HookError::DivisionByZero);
+                    }
+                    let (mantissa, exponent) = xfl_parts(float1);
+                    // 8 more digits, so the quotient keeps the 16 of the mantissa
+                    let scaled = mantissa * numerator as i128 * 100_000_000;
+                    let mut quotient = scaled / denominator as i128;
+                    if round_up != 0 && scaled % denominator as i128 != 0 {
+                        quotient += scaled.signum();
+                    }
+                    xfl_from_parts(quotient, exponent - 8)
+                },
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::decode::decode;
+    use crate::fixtures::{DESTINATION, HOOK_ACCOUNT};
+    use crate::transaction::*;
+
+    #[wasm_bindgen_test]
+    fn state_round_trips_through_the_mock_host() {
+        reset();
+
+        assert!(matches!(state_set(b"value", b"counter"), Ok(5)));
+        assert!(matches!(crate::state::<5>(b"counter"), Ok(value) if &value == b"value"));
+        assert_eq!(super::state(b"counter").as_deref(), Some(&b"value"[..]));
+
+        assert!(matches!(state_set(&[], b"counter"), Ok(0)));
+        assert!(matches!(
+            crate::state::<5>(b"counter"),
+            Err(HookError::DoesntExist)
+        ));
+    }
+
+    #[wasm_bindgen_test]
+    fn emits_xrp_payments_through_the_mock_host() {
+        reset();
+        set_hook_account(HOOK_ACCOUNT);
+        set_ledger_seq(100);
+        set_fee_base(12);
+
+        let mut reservation = EmitReservation::new(1).unwrap_line_number();
+        let builder = XrpPaymentBuilder::new(Drops(1000), &DESTINATION, None, None);
+        assert!(builder.build_and_emit(&mut reservation).is_ok());
+        assert!(matches!(
+            builder.build_and_emit(&mut reservation),
+            Err(HookError::TooManyEmittedTxn)
+        ));
+
+        let emitted = emitted();
+        assert_eq!(emitted.len(), 1);
+        // without the 5 bytes of each tag
+        assert_eq!(emitted[0].len(), 260);
+        let tx = decode(&emitted[0]);
+        assert_eq!(tx.txn_type(), Some(TxnType::Payment as u16));
+        assert_eq!(tx.u32(FieldId::SourceTag), None);
+        assert_eq!(tx.u32(FieldId::DestinationTag), None);
+        assert_eq!(tx.u32(FieldId::FirstLedgerSequence), Some(101));
+        assert_eq!(tx.u32(FieldId::LastLedgerSequence), Some(105));
+        assert_eq!(tx.drops(FieldId::Amount), Some(1000));
+        assert_eq!(tx.drops(FieldId::Fee), Some(12));
+        assert_eq!(tx.account(FieldId::Account), Some(HOOK_ACCOUNT));
+        assert_eq!(tx.account(FieldId::Destination), Some(DESTINATION));
+        assert_eq!(
+            tx.object(FieldId::EmitDetails)
+                .and_then(|details| details.account(FieldId::EmitCallback)),
+            Some(HOOK_ACCOUNT)
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn builds_trustline_payments_with_the_mock_host() {
+        reset();
+        set_hook_account(HOOK_ACCOUNT);
+
+        let _ = EmitReservation::new(1).unwrap_line_number();
+        let mut amount = [3; ISSUED_AMOUNT_LEN];
+        amount[0] = 0xD4;
+        let mut buffer = TrustlinePaymentBuilder::uninit_buffer();
+        let tx = decode(
+            TrustlinePaymentBuilder::new(&amount, &DESTINATION, Some(DestTag(7)), None)
+                .build(&mut buffer)
+                .unwrap_line_number(),
+        );
+        assert_eq!(tx.field(FieldId::Amount), Some(&amount[..]));
+        assert_eq!(tx.u32(FieldId::DestinationTag), Some(7));
+        assert_eq!(tx.u32(FieldId::SourceTag), None);
+        assert_eq!(tx.account(FieldId::Destination), Some(DESTINATION));
+        // the emit details fit, including their end marker
+        assert!(tx.object(FieldId::EmitDetails).is_some());
+    }
+
+    #[wasm_bindgen_test]
+    fn reads_the_originating_transaction_from_the_mock_host() {
+        reset();
+        set_otxn_type(TxnType::Invoke);
+        set_otxn_param(b"drops", &1000u64.to_be_bytes());
+
+        assert_eq!(crate::otxn_type(), TxnType::Invoke as i64);
+        assert!(matches!(
+            otxn_param::<8>(b"drops"),
+            Ok(drops) if u64::from_be_bytes(drops) == 1000
+        ));
+        assert!(matches!(
+            otxn_field::<ACC_ID_LEN>(FieldId::Account),
+            Err(HookError::DoesntExist)
+        ));
+        let _ = crate::trace!("{} drops", 1000u64);
+        assert_eq!(traces(), [b"1000 drops".to_vec()]);
+    }
+
+    #[wasm_bindgen_test]
+    fn tells_missing_tags_from_zero_tags() {
+        reset();
+        assert!(matches!(otxn_dest_tag(), Ok(None)));
+        assert!(matches!(otxn_src_tag(), Ok(None)));
+
+        set_otxn_field(FieldId::DestinationTag, &0u32.to_be_bytes());
+        set_otxn_field(FieldId::SourceTag, &8u32.to_be_bytes());
+        assert!(matches!(otxn_dest_tag(), Ok(Some(DestTag(0)))));
+        assert!(matches!(otxn_src_tag(), Ok(Some(SrcTag(8)))));
+    }
+
+    #[wasm_bindgen_test]
+    fn encodes_r_addresses_like_the_host() {
+        let mut genesis = [0; crate::R_ADDRESS_MAX_LEN];
+        genesis[..34].copy_from_slice(b"rHb9CJAWyB4rj91VRWn96DkukG4bwdtyTh");
+
+        assert!(matches!(
+            crate::util_raddr(&crate::fixtures::GENESIS_ACCOUNT),
+            Ok(r_address) if r_address == genesis
+        ));
+    }
+}
@@ -0,0 +1,429 @@
+//! Records the host calls of hook logic and replays them, see
+//! [the module docs](super#recording-and-replaying).
+
+extern crate std;
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fmt;
+use std::fmt::Write;
+use std::string::{String, ToString};
+use std::vec::Vec;
+
+use super::{read, write, Call};
+
+/// One call of a host function and what the host answered
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HostCall {
+    /// Name of the host function
+    pub function: String,
+    /// Arguments other than buffers, such as the field ID of `otxn_field`
+    pub args: Vec<i64>,
+    /// Buffers passed to the host, in the order of the arguments
+    pub input: Vec<Vec<u8>>,
+    /// Bytes the host wrote into the buffer of the hook
+    pub output: Vec<u8>,
+    /// Value returned by the host
+    pub result: i64,
+}
+
+impl Call<'_> {
+    fn matches(&self, call: &HostCall) -> bool {
+        call.function == self.function
+            && call.args == self.args
+            && call.input.len() == self.input.len()
+            && call.input.iter().zip(self.input).all(|(a, b)| a == b)
+    }
+
+    fn answered(&self, output: Vec<u8>, result: i64) -> HostCall {
+        HostCall {
+            function: self.function.into(),
+            args: self.args.into(),
+            input: self.input.iter().map(|input| input.to_vec()).collect(),
+            output,
+            result,
+        }
+    }
+}
+
+enum Mode {
+    Off,
+    Recording(Vec<HostCall>),
+    Replaying(VecDeque<HostCall>),
+}
+
+std::thread_local! {
+    static MODE: RefCell<Mode> = const { RefCell::new(Mode::Off) };
+}
+
+// Stops recording or replaying when dropped, also if the hook logic panics and unwinds
+struct Stop;
+
+impl Drop for Stop {
+    fn drop(&mut self) {
+        stop();
+    }
+}
+
+fn start(new_mode: Mode) -> Stop {
+    let was_off = MODE.with(|mode| {
+        let mut mode = mode.borrow_mut();
+        let was_off = matches!(*mode, Mode::Off);
+        if was_off {
+            *mode = new_mode;
+        }
+        was_off
+    });
+    assert!(was_off, "already recording or replaying host calls");
+
+    Stop
+}
+
+/// Runs `f` and records the host calls it makes, except for the guard function `_g`.
+///
+/// # Example
+/// ```
+/// let (invocation, calls) = mock::record(|| mock::invoke(handle));
+/// assert!(invocation.accepted);
+/// println!("{}", mock::calls_to_text(&calls));
+/// ```
+pub fn record<T>(f: impl FnOnce() -> T) -> (T, Vec<HostCall>) {
+    let _stop = start(Mode::Recording(Vec::new()));
+    let value = f();
+    let calls = MODE.with(|mode| match &mut *mode.borrow_mut() {
+        Mode::Recording(calls) => core::mem::take(calls),
+        _ => unreachable!("recording stopped early"),
+    });
+
+    (value, calls)
+}
+
+/// Runs `f` with its host calls answered from `calls` rather than by the mock host.
+///
+/// Every call has to be the next one of `calls`, with the same arguments and input, and
+/// is answered with its recorded output and result. [accept] and [rollback](crate::rollback)
+/// are checked the same way and then end the hook like on the mock host. The setup of
+/// the mock host is not used, and no state is written nor are transactions emitted.
+///
+/// # Panics
+/// If `f` makes a call that differs from the recording, or fewer calls than recorded.
+#[track_caller]
+pub fn replay<T>(calls: &[HostCall], f: impl FnOnce() -> T) -> T {
+    let _stop = start(Mode::Replaying(calls.iter().cloned().collect()));
+    let value = f();
+    let remaining = MODE.with(|mode| match &mut *mode.borrow_mut() {
+        Mode::Replaying(remaining) => core::mem::take(remaining),
+        _ => unreachable!("replay stopped early"),
+    });
+    if let Some(next) = remaining.front() {
+        panic!(
+            "{} of {} recorded host calls were not made, the next one is {next}",
+            remaining.len(),
+            calls.len()
+        );
+    }
+
+    value
+}
+
+// Takes the next recorded call while replaying. The host is not borrowed while checking
+// it, since a panic in a wasm test doesn't unwind and would leave the host borrowed for
+// the next tests.
+fn next_replayed() -> Option<Option<HostCall>> {
+    MODE.with(|mode| match &mut *mode.borrow_mut() {
+        Mode::Replaying(remaining) => Some(remaining.pop_front()),
+        _ => None,
+    })
+}
+
+// The recorded call answering `call`, or a panic if it is not the next one
+fn check_replayed(recorded: Option<HostCall>, call: &Call<'_>) -> HostCall {
+    match recorded {
+        Some(recorded) if call.matches(&recorded) => recorded,
+        Some(recorded) => panic!(
+            "host calls diverged from the recording:\n  expected: {recorded}\n  actual:   {}",
+            call.answered(Vec::new(), 0)
+        ),
+        None => panic!(
+            "host call made after the recording ended: {}",
+            call.answered(Vec::new(), 0)
+        ),
+    }
+}
+
+/// Runs the host function `body` for `call`, recording it or answering it from the replay
+pub(super) unsafe fn host_call(call: Call<'_>, body: impl FnOnce() -> i64) -> i64 {
+    if let Some(recorded) = next_replayed() {
+        let recorded = check_replayed(recorded, &call);
+        if let (Some((write_ptr, write_len)), false) = (call.write_to, recorded.output.is_empty()) {
+            let written = write(write_ptr, write_len, &recorded.output);
+            if written < 0 {
+                return written;
+            }
+        }
+        return recorded.result;
+    }
+
+    let result = body();
+    MODE.with(|mode| {
+        if let Mode::Recording(calls) = &mut *mode.borrow_mut() {
+            let output = match call.write_to {
+                Some((write_ptr, write_len)) if result > 0 => {
+                    read(write_ptr, write_len.min(result as u32)).to_vec()
+                }
+                _ => Vec::new(),
+            };
+            calls.push(call.answered(output, result));
+        }
+    });
+
+    result
+}
+
+/// Records or checks `call` of a host function that doesn't return, before it is run
+pub(super) fn control_call(call: Call<'_>) {
+    if let Some(recorded) = next_replayed() {
+        check_replayed(recorded, &call);
+        return;
+    }
+    MODE.with(|mode| {
+        if let Mode::Recording(calls) = &mut *mode.borrow_mut() {
+            calls.push(call.answered(Vec::new(), 0));
+        }
+    });
+}
+
+/// Stops recording or replaying, for [reset](super::reset)
+pub(super) fn stop() {
+    MODE.with(|mode| *mode.borrow_mut() = Mode::Off);
+}
+
+/// Writes a call as `function args=1,2 in=6B6579 -> result out=0101`, leaving out empty
+/// parts. Every buffer is uppercase hex.
+impl fmt::Display for HostCall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.function)?;
+        if !self.args.is_empty() {
+            let args: Vec<String> = self.args.iter().map(i64::to_string).collect();
+            write!(f, " args={}", args.join(","))?;
+        }
+        if !self.input.is_empty() {
+            let input: Vec<String> = self.input.iter().map(|input| to_hex(input)).collect();
+            write!(f, " in={}", input.join(","))?;
+        }
+        write!(f, " -> {}", self.result)?;
+        if !self.output.is_empty() {
+            write!(f, " out={}", to_hex(&self.output))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Host calls written one per line like [HostCall] displays them, to keep a recording
+/// next to the test that replays it
+pub fn calls_to_text(calls: &[HostCall]) -> String {
+    let mut text = String::new();
+    for call in calls {
+        let _ = writeln!(text, "{call}");
+    }
+
+    text
+}
+
+/// Host calls written by [calls_to_text].
+///
+/// Leading and trailing whitespace of the lines and empty lines are ignored, like in
+/// [snapshots](crate::snapshot::assert_snapshot), and hex can be in either case.
+///
+/// # Panics
+/// If a line is not a host call.
+#[track_caller]
+pub fn calls_from_text(text: &str) -> Vec<HostCall> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| parse_call(line).unwrap_or_else(|| panic!("not a host call: {line}")))
+        .collect()
+}
+
+fn parse_call(line: &str) -> Option<HostCall> {
+    let (call, answer) = line.split_once(" -> ")?;
+    let mut parts = call.split(' ');
+    let mut host_call = HostCall {
+        function: parts.next()?.into(),
+        args: Vec::new(),
+        input: Vec::new(),
+        output: Vec::new(),
+        result: 0,
+    };
+    for part in parts {
+        if let Some(args) = part.strip_prefix("args=") {
+            host_call.args = args
+                .split(',')
+                .map(|arg| arg.parse().ok())
+                .collect::<Option<_>>()?;
+        } else if let Some(input) = part.strip_prefix("in=") {
+            host_call.input = input.split(',').map(from_hex).collect::<Option<_>>()?;
+        } else {
+            return None;
+        }
+    }
+
+    let mut answer = answer.split(' ');
+    host_call.result = answer.next()?.parse().ok()?;
+    if let Some(output) = answer.next() {
+        host_call.output = from_hex(output.strip_prefix("out=")?)?;
+    }
+    if answer.next().is_some() {
+        return None;
+    }
+
+    Some(host_call)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    let mut hex = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        let _ = write!(hex, "{byte:02X}");
+    }
+
+    hex
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::api::*;
+    use crate::fixtures::{DESTINATION, HOOK_ACCOUNT};
+    use crate::mock::{self, invoke, reset, set_hook_account, set_otxn_field, set_state};
+    use crate::transaction::*;
+
+    // Pays back the sender the amount in its state
+    fn pay_back() -> Outcome<'static> {
+        let sender = match otxn_field::<ACC_ID_LEN>(FieldId::Account) {
+            Ok(sender) => sender,
+            Err(_) => {
+                return Outcome::Rollback {
+                    code: 1,
+                    msg: b"no sender",
+                }
+            }
+        };
+        let drops = match state::<8>(b"drops") {
+            Ok(drops) => u64::from_be_bytes(drops),
+            Err(_) => {
+                return Outcome::Rollback {
+                    code: 2,
+                    msg: b"no drops",
+                }
+            }
+        };
+        match XrpPaymentBuilder::new(Drops(drops), &AccountId(sender), None, None)
+            .build_and_emit(&mut EmitReservation::new(1).unwrap_line_number())
+        {
+            Ok(_) => Outcome::Accept {
+                code: 0,
+                msg: b"paid",
+            },
+            Err(_) => Outcome::Rollback {
+                code: 3,
+                msg: b"not paid",
+            },
+        }
+    }
+
+    fn set_up() {
+        reset();
+        set_hook_account(HOOK_ACCOUNT);
+        set_otxn_field(FieldId::Account, DESTINATION.as_bytes());
+        set_state(b"drops", &1000u64.to_be_bytes());
+    }
+
+    #[wasm_bindgen_test]
+    fn records_host_calls() {
+        set_up();
+
+        let (invocation, calls) = record(|| invoke(pay_back));
+
+        assert!(invocation.accepted);
+        let functions: Vec<&str> = calls.iter().map(|call| call.function.as_str()).collect();
+        assert_eq!(
+            functions,
+            [
+                "otxn_field",
+                "state",
+                "etxn_reserve",
+                "ledger_seq",
+                "hook_account",
+                "etxn_details",
+                "etxn_fee_base",
+                "emit"
+            ]
+        );
+        assert_eq!(calls[0].args, [FieldId::Account as i64]);
+        assert_eq!(calls[0].output, DESTINATION.0);
+        assert_eq!(calls[7].input, [invocation.emitted[0].tx.clone()]);
+    }
+
+    #[wasm_bindgen_test]
+    fn replays_without_the_mock_host() {
+        set_up();
+        let (recorded, calls) = record(|| invoke(pay_back));
+
+        reset();
+        let replayed = replay(&calls, || invoke(pay_back));
+
+        assert_eq!(replayed.code, recorded.code);
+        assert_eq!(replayed.msg, recorded.msg);
+        assert!(mock::emitted().is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    fn round_trips_through_text() {
+        set_up();
+        let (_, calls) = record(|| invoke(pay_back));
+
+        assert_eq!(calls_from_text(&calls_to_text(&calls)), calls);
+        assert_eq!(
+            calls_from_text("  trace args=0 in=6869, -> 0\n\n  ledger_seq -> 7  "),
+            [
+                HostCall {
+                    function: "trace".into(),
+                    args: [0].into(),
+                    input: [b"hi".to_vec(), Vec::new()].into(),
+                    output: Vec::new(),
+                    result: 0,
+                },
+                HostCall {
+                    function: "ledger_seq".into(),
+                    args: Vec::new(),
+                    input: Vec::new(),
+                    output: Vec::new(),
+                    result: 7,
+                },
+            ]
+        );
+    }
+
+    #[wasm_bindgen_test]
+    #[should_panic(expected = "host calls diverged from the recording")]
+    fn fails_on_diverging_calls() {
+        set_up();
+        let (_, calls) = record(|| invoke(pay_back));
+
+        let _ = replay(&calls, || state::<8>(b"other"));
+    }
+}
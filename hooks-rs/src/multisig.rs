@@ -0,0 +1,556 @@
+//! M-of-n authorization of administrative Invokes, by public keys kept in hook state.
+//!
+//! A [Quorum] keeps a [SignerSet] in the state of the hook: up to [MULTISIG_MAX_SIGNERS]
+//! public keys, of which `threshold` have to sign an action before the hook carries it
+//! out, such as changing its parameters or the signers themselves. This works like a
+//! SignerList on the hook account, but is checked by the hook, so anyone can relay the
+//! signed action and the hook account itself doesn't need to be governed.
+//!
+//! An action is any bytes the hook knows how to apply. The signers sign the
+//! [signing payload](Quorum::signing_payload) of the action off the ledger, and an Invoke
+//! carries the action in [ACTION_PARAM] and the signature of the signer at index `i` of
+//! the set in the parameter named [signature_param]`(i)`. [Quorum::authorize_invoke]
+//! checks the signatures and returns the action once enough of them are valid.
+//!
+//! The payload includes the state key of the quorum, the hook account and a sequence
+//! kept with the signers, which grows with every authorized action, so that signatures
+//! can't be replayed on another hook or for a second time.
+//!
+//! # Example
+//! ```
+//! const ADMINS: Quorum = Quorum::new(b"admins");
+//!
+//! #[hook]
+//! fn governed() -> Outcome<'static> {
+//!     let mut action = [MaybeUninit::uninit(); HOOK_PARAM_VALUE_MAX_LEN];
+//!     if let Some(action) = ADMINS.authorize_invoke(&mut action)? {
+//!         apply(action)?;
+//!         return Outcome::Accept { code: 0, msg: b"action applied" };
+//!     }
+//!
+//!     Outcome::Accept { code: 0, msg: b"" }
+//! }
+//! ```
+
+use core::mem::MaybeUninit;
+
+use crate::api::*;
+use crate::c;
+use crate::oracle::SIGNATURE_MAX_LEN;
+use crate::params::{otxn_param_into, HOOK_PARAM_VALUE_MAX_LEN};
+use crate::utils::is_buffer_equal;
+
+/// Maximum number of public keys in a [SignerSet], which fits in a state entry with the
+/// threshold and the sequence
+pub const MULTISIG_MAX_SIGNERS: usize = 7;
+
+/// Maximum length of the name of a [Quorum], the part of its state key after
+/// [MULTISIG_KEY_PREFIX]
+pub const QUORUM_NAME_MAX_LEN: usize = STATE_KEY_LEN - MULTISIG_KEY_PREFIX.len();
+
+/// Bytes the state keys of quorums start with, which also start every signing payload,
+/// so that signatures of actions can't be mistaken for signatures of anything else
+pub const MULTISIG_KEY_PREFIX: [u8; 4] = *b"MSIG";
+
+/// Length of the signing payload of an action without the action: the state key of the
+/// quorum, the hook account and the sequence
+pub const MULTISIG_PAYLOAD_HEADER_LEN: usize = STATE_KEY_LEN + ACC_ID_LEN + 4;
+
+/// Invoke parameter with the action to authorize
+pub const ACTION_PARAM: &[u8] = b"MSIG_ACTION";
+
+/// Length of the names of the signature parameters, see [signature_param]
+pub const SIGNATURE_PARAM_LEN: usize = 9;
+
+/// The Invoke parameter with the signature of the signer at `index` in the [SignerSet]:
+/// `MSIG_SIG` followed by the index as a digit
+#[inline(always)]
+pub const fn signature_param(index: usize) -> [u8; SIGNATURE_PARAM_LEN] {
+    assert!(index < MULTISIG_MAX_SIGNERS, "no such signer");
+
+    let mut name = *b"MSIG_SIG0";
+    name[SIGNATURE_PARAM_LEN - 1] += index as u8;
+    name
+}
+
+/// The public keys allowed to sign the actions of a [Quorum], and how many of them have
+/// to
+#[derive(Clone, Copy)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct SignerSet {
+    threshold: u8,
+    sequence: u32,
+    keys: [PublicKey; MULTISIG_MAX_SIGNERS],
+    len: usize,
+}
+
+// The layout of a signer set in state
+#[repr(C)]
+struct StoredSignerSet {
+    threshold: u8,
+    sequence: [u8; 4],
+    keys: [[u8; PUBLIC_KEY_LEN]; MULTISIG_MAX_SIGNERS],
+}
+
+const STORED_SIGNER_SET_LEN: usize = 5 + MULTISIG_MAX_SIGNERS * PUBLIC_KEY_LEN;
+
+// The layout of a signing payload, of which the action takes up the first bytes of
+// `action`
+#[repr(C)]
+struct StoredPayload {
+    key: [u8; STATE_KEY_LEN],
+    account: AccountId,
+    sequence: [u8; 4],
+    action: [u8; HOOK_PARAM_VALUE_MAX_LEN],
+}
+
+/// What the signers of an action sign, see [Quorum::signing_payload]
+#[derive(Clone, Copy)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct SigningPayload {
+    bytes: [u8; MULTISIG_PAYLOAD_HEADER_LEN + HOOK_PARAM_VALUE_MAX_LEN],
+    len: usize,
+}
+
+impl SigningPayload {
+    /// The bytes to sign
+    #[inline(always)]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+impl SignerSet {
+    /// A set of `keys` of which `threshold` have to sign every action.
+    ///
+    /// Returns [HookError::InvalidArgument] if there are no keys or more than
+    /// [MULTISIG_MAX_SIGNERS], if a key is there twice, or if `threshold` is 0 or more
+    /// than the number of keys.
+    #[inline(always)]
+    pub fn new(threshold: u8, keys: &[PublicKey]) -> Result<Self> {
+        if keys.is_empty()
+            || keys.len() > MULTISIG_MAX_SIGNERS
+            || threshold == 0
+            || threshold as usize > keys.len()
+            || has_duplicate_keys(keys)
+        {
+            return Err(HookError::InvalidArgument);
+        }
+
+        let mut set = Self {
+            threshold,
+            sequence: 0,
+            keys: [PublicKey([0; PUBLIC_KEY_LEN]); MULTISIG_MAX_SIGNERS],
+            len: keys.len(),
+        };
+        // guarded loop
+        let mut i = 0;
+        while {
+            max_iter(MULTISIG_MAX_SIGNERS as u32 + 1);
+            i < keys.len()
+        } {
+            set.keys[i] = keys[i];
+            i += 1;
+        }
+
+        Ok(set)
+    }
+
+    /// Number of valid signatures an action needs
+    #[inline(always)]
+    pub const fn threshold(&self) -> u8 {
+        self.threshold
+    }
+
+    /// The public keys of the signers, in the order of their [signature_param]s
+    #[inline(always)]
+    pub fn keys(&self) -> &[PublicKey] {
+        &self.keys[..self.len]
+    }
+
+    /// Number of actions authorized so far, which the next signing payload includes
+    #[inline(always)]
+    pub const fn sequence(&self) -> u32 {
+        self.sequence
+    }
+}
+
+// Whether a key is in `keys` twice, which would let its signer approve an action once
+// for every index of the key
+#[inline(always)]
+fn has_duplicate_keys(keys: &[PublicKey]) -> bool {
+    // guarded loop
+    let mut i = 0;
+    while {
+        max_iter(MULTISIG_MAX_SIGNERS as u32 + 1);
+        i < keys.len()
+    } {
+        // guarded loop
+        let mut j = i + 1;
+        while {
+            max_iter(MULTISIG_MAX_SIGNERS as u32 + 1);
+            j < keys.len()
+        } {
+            if is_buffer_equal(&keys[i].0, &keys[j].0) {
+                return true;
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+
+    false
+}
+
+/// A signer set kept in the state of the hook, under a name of its own
+#[derive(Clone, Copy)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct Quorum {
+    key: [u8; STATE_KEY_LEN],
+}
+
+impl Quorum {
+    /// The quorum named `name`, which tells it apart from the other quorums of the hook.
+    ///
+    /// The prefix and the name are copied into the state key with loops without guard, so
+    /// declare quorums as constants, where a `name` longer than [QUORUM_NAME_MAX_LEN] fails
+    /// to compile.
+    #[inline(always)]
+    pub const fn new(name: &[u8]) -> Self {
+        assert!(name.len() <= QUORUM_NAME_MAX_LEN, "quorum name is too long");
+
+        let mut key = [0; STATE_KEY_LEN];
+        let mut i = 0;
+        while i < MULTISIG_KEY_PREFIX.len() {
+            key[i] = MULTISIG_KEY_PREFIX[i];
+            i += 1;
+        }
+        let mut i = 0;
+        while i < name.len() {
+            key[MULTISIG_KEY_PREFIX.len() + i] = name[i];
+            i += 1;
+        }
+
+        Self { key }
+    }
+
+    /// The state key of the signer set: [MULTISIG_KEY_PREFIX], then the name padded with
+    /// zeros
+    #[inline(always)]
+    pub const fn key(&self) -> [u8; STATE_KEY_LEN] {
+        self.key
+    }
+
+    /// Read the signer set, [HookError::DoesntExist] if it was never stored.
+    ///
+    /// Fails with [HookError::DoesNotMatch] if the state entry isn't a valid signer set,
+    /// such as one with a key there twice, rather than authorizing actions with whatever
+    /// it holds.
+    #[inline(always)]
+    pub fn load(&self) -> Result<SignerSet> {
+        let mut bytes = [0; STORED_SIGNER_SET_LEN];
+        let result: Result<u64> = unsafe {
+            c::state(
+                host_ptr(bytes.as_mut_ptr()),
+                STORED_SIGNER_SET_LEN as u32,
+                host_ptr(self.key.as_ptr()),
+                STATE_KEY_LEN as u32,
+            )
+            .into()
+        };
+        let len = result? as usize;
+        if len < 5 + PUBLIC_KEY_LEN || (len - 5) % PUBLIC_KEY_LEN != 0 {
+            return Err(HookError::DoesNotMatch);
+        }
+
+        let stored =
+            unsafe { core::mem::transmute::<[u8; STORED_SIGNER_SET_LEN], StoredSignerSet>(bytes) };
+        let mut set = SignerSet {
+            threshold: stored.threshold,
+            sequence: u32::from_be_bytes(stored.sequence),
+            keys: [PublicKey([0; PUBLIC_KEY_LEN]); MULTISIG_MAX_SIGNERS],
+            len: (len - 5) / PUBLIC_KEY_LEN,
+        };
+        if set.threshold == 0 || set.threshold as usize > set.len {
+            return Err(HookError::DoesNotMatch);
+        }
+        // guarded loop
+        let mut i = 0;
+        while {
+            max_iter(MULTISIG_MAX_SIGNERS as u32 + 1);
+            i < set.len
+        } {
+            set.keys[i] = PublicKey(stored.keys[i]);
+            i += 1;
+        }
+        if has_duplicate_keys(set.keys()) {
+            return Err(HookError::DoesNotMatch);
+        }
+
+        Ok(set)
+    }
+
+    /// Write the signer set, with its sequence, replacing the one stored before.
+    ///
+    /// The hook stores the first set when it is set up, and later ones should only come
+    /// from actions authorized by the set before them.
+    #[inline(always)]
+    pub fn store(&self, set: &SignerSet) -> Result<()> {
+        let mut stored = StoredSignerSet {
+            threshold: set.threshold,
+            sequence: set.sequence.to_be_bytes(),
+            keys: [[0; PUBLIC_KEY_LEN]; MULTISIG_MAX_SIGNERS],
+        };
+        // guarded loop
+        let mut i = 0;
+        while {
+            max_iter(MULTISIG_MAX_SIGNERS as u32 + 1);
+            i < set.len
+        } {
+            stored.keys[i] = set.keys[i].0;
+            i += 1;
+        }
+        let bytes =
+            unsafe { core::mem::transmute::<StoredSignerSet, [u8; STORED_SIGNER_SET_LEN]>(stored) };
+
+        match state_set(&bytes[..5 + set.len * PUBLIC_KEY_LEN], &self.key) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// What the signers sign to authorize `action` at `sequence`: the state key of the
+    /// quorum, the hook account, the sequence, 4 bytes big endian, and the action.
+    ///
+    /// Returns [HookError::TooBig] if `action` is longer than an Invoke parameter.
+    #[inline(always)]
+    pub fn signing_payload(&self, sequence: u32, action: &[u8]) -> Result<SigningPayload> {
+        if action.len() > HOOK_PARAM_VALUE_MAX_LEN {
+            return Err(HookError::TooBig);
+        }
+
+        let mut stored = StoredPayload {
+            key: self.key,
+            account: hook_account()?,
+            sequence: sequence.to_be_bytes(),
+            action: [0; HOOK_PARAM_VALUE_MAX_LEN],
+        };
+        // guarded loop
+        let mut i = 0;
+        while {
+            max_iter(HOOK_PARAM_VALUE_MAX_LEN as u32 + 1);
+            i < action.len()
+        } {
+            stored.action[i] = action[i];
+            i += 1;
+        }
+
+        Ok(SigningPayload {
+            bytes: unsafe {
+                core::mem::transmute::<
+                    StoredPayload,
+                    [u8; MULTISIG_PAYLOAD_HEADER_LEN + HOOK_PARAM_VALUE_MAX_LEN],
+                >(stored)
+            },
+            len: MULTISIG_PAYLOAD_HEADER_LEN + action.len(),
+        })
+    }
+
+    /// Check the signatures of the action carried by the originating transaction, if it is
+    /// an Invoke with an [ACTION_PARAM], and return the action read into `buffer` once
+    /// enough of them are valid.
+    ///
+    /// Returns `Ok(None)` for other transactions, and fails with
+    /// [HookError::NotAuthorized] if a signature is invalid or there are fewer than the
+    /// threshold. Authorizing an action moves the sequence on, so the hook should roll
+    /// back if it fails to apply it, which also keeps the signatures valid.
+    #[inline(always)]
+    pub fn authorize_invoke<'a>(
+        &self,
+        buffer: &'a mut [MaybeUninit<u8>; HOOK_PARAM_VALUE_MAX_LEN],
+    ) -> Result<Option<&'a [u8]>> {
+        if otxn_type() != TxnType::Invoke as i64 {
+            return Ok(None);
+        }
+
+        let action = match otxn_param_into(ACTION_PARAM, buffer) {
+            Ok(action) => action,
+            Err(HookError::DoesntExist) => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        let mut set = self.load()?;
+        let payload = self.signing_payload(set.sequence, action)?;
+
+        let mut approvals = 0;
+        // guarded loop
+        let mut i = 0;
+        while {
+            max_iter(MULTISIG_MAX_SIGNERS as u32 + 1);
+            i < set.len
+        } {
+            let mut signature = [MaybeUninit::uninit(); SIGNATURE_MAX_LEN];
+            match otxn_param_into(&signature_param(i), &mut signature) {
+                Ok(signature) => {
+                    if !util_verify(payload.as_bytes(), signature, &set.keys[i])? {
+                        return Err(HookError::NotAuthorized);
+                    }
+                    approvals += 1;
+                }
+                // this signer didn't sign
+                Err(HookError::DoesntExist) => {}
+                Err(err) => return Err(err),
+            }
+            i += 1;
+        }
+        if approvals < set.threshold {
+            return Err(HookError::NotAuthorized);
+        }
+
+        set.sequence = set.sequence.wrapping_add(1);
+        self.store(&set)?;
+
+        Ok(Some(action))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::fixtures::HOOK_ACCOUNT;
+    use crate::mock;
+
+    const ADMINS: Quorum = Quorum::new(b"admins");
+    const KEYS: [PublicKey; 3] = [
+        PublicKey([0xED; PUBLIC_KEY_LEN]),
+        PublicKey([0x02; PUBLIC_KEY_LEN]),
+        PublicKey([0x03; PUBLIC_KEY_LEN]),
+    ];
+    const ACTION: &[u8] = b"set fee to 10";
+
+    fn set_up() {
+        mock::reset();
+        mock::set_hook_account(HOOK_ACCOUNT);
+        assert!(ADMINS
+            .store(&SignerSet::new(2, &KEYS).unwrap_line_number())
+            .is_ok());
+    }
+
+    fn invoke_signed_by(signers: &[usize], sequence: u32) {
+        mock::set_otxn_type(TxnType::Invoke);
+        mock::set_otxn_param(ACTION_PARAM, ACTION);
+        let payload = ADMINS
+            .signing_payload(sequence, ACTION)
+            .unwrap_line_number();
+        for &signer in signers {
+            let signature = mock::fake_signature(&KEYS[signer], payload.as_bytes());
+            mock::set_otxn_param(&signature_param(signer), &signature);
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn keeps_signer_sets_in_state() {
+        set_up();
+        assert_eq!(&signature_param(3), b"MSIG_SIG3");
+        assert_eq!(&ADMINS.key()[..10], b"MSIGadmins");
+
+        let set = ADMINS.load().unwrap_line_number();
+        assert_eq!(set.threshold(), 2);
+        assert_eq!(set.sequence(), 0);
+        assert!(set.keys() == KEYS);
+        assert_eq!(
+            mock::state(&ADMINS.key()).unwrap().len(),
+            5 + 3 * PUBLIC_KEY_LEN
+        );
+
+        assert!(matches!(
+            SignerSet::new(4, &KEYS),
+            Err(HookError::InvalidArgument)
+        ));
+        assert!(matches!(
+            SignerSet::new(0, &KEYS),
+            Err(HookError::InvalidArgument)
+        ));
+        assert!(matches!(
+            Quorum::new(b"others").load(),
+            Err(HookError::DoesntExist)
+        ));
+
+        // a threshold of 0 would authorize anything
+        let mut stored = mock::state(&ADMINS.key()).unwrap();
+        stored[0] = 0;
+        mock::set_state(&ADMINS.key(), &stored);
+        assert!(matches!(ADMINS.load(), Err(HookError::DoesNotMatch)));
+    }
+
+    #[wasm_bindgen_test]
+    fn authorizes_actions_signed_by_enough_signers() {
+        set_up();
+        let mut buffer = [MaybeUninit::uninit(); HOOK_PARAM_VALUE_MAX_LEN];
+        assert!(matches!(ADMINS.authorize_invoke(&mut buffer), Ok(None)));
+
+        invoke_signed_by(&[0, 2], 0);
+        assert!(matches!(
+            ADMINS.authorize_invoke(&mut buffer),
+            Ok(Some(action)) if action == ACTION
+        ));
+        assert_eq!(ADMINS.load().unwrap_line_number().sequence(), 1);
+
+        // the same signatures can't be used again
+        assert!(matches!(
+            ADMINS.authorize_invoke(&mut buffer),
+            Err(HookError::NotAuthorized)
+        ));
+    }
+
+    #[wasm_bindgen_test]
+    fn rejects_too_few_or_invalid_signatures() {
+        set_up();
+        let mut buffer = [MaybeUninit::uninit(); HOOK_PARAM_VALUE_MAX_LEN];
+        invoke_signed_by(&[1], 0);
+        assert!(matches!(
+            ADMINS.authorize_invoke(&mut buffer),
+            Err(HookError::NotAuthorized)
+        ));
+
+        // signed for another action
+        set_up();
+        invoke_signed_by(&[0, 1], 0);
+        mock::set_otxn_param(ACTION_PARAM, b"set fee to 0");
+        assert!(matches!(
+            ADMINS.authorize_invoke(&mut buffer),
+            Err(HookError::NotAuthorized)
+        ));
+        assert_eq!(ADMINS.load().unwrap_line_number().sequence(), 0);
+    }
+
+    #[wasm_bindgen_test]
+    fn refuses_sets_with_a_key_there_twice() {
+        set_up();
+        let repeated = [KEYS[0], KEYS[1], KEYS[0]];
+        assert!(matches!(
+            SignerSet::new(2, &repeated),
+            Err(HookError::InvalidArgument)
+        ));
+
+        // a set stored with the key at indices 0 and 2, signed by its signer alone under
+        // both indices
+        let mut stored = mock::state(&ADMINS.key()).unwrap();
+        stored[5 + 2 * PUBLIC_KEY_LEN..].copy_from_slice(&KEYS[0].0);
+        mock::set_state(&ADMINS.key(), &stored);
+        mock::set_otxn_type(TxnType::Invoke);
+        mock::set_otxn_param(ACTION_PARAM, ACTION);
+        let payload = ADMINS.signing_payload(0, ACTION).unwrap_line_number();
+        let signature = mock::fake_signature(&KEYS[0], payload.as_bytes());
+        mock::set_otxn_param(&signature_param(0), &signature);
+        mock::set_otxn_param(&signature_param(2), &signature);
+
+        let mut buffer = [MaybeUninit::uninit(); HOOK_PARAM_VALUE_MAX_LEN];
+        assert!(matches!(
+            ADMINS.authorize_invoke(&mut buffer),
+            Err(HookError::DoesNotMatch)
+        ));
+        assert_eq!(mock::state(&ADMINS.key()).unwrap(), stored);
+    }
+}
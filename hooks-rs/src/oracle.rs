@@ -0,0 +1,320 @@
+//! Values published by oracles, in a state layout shared by the hooks of this crate.
+//!
+//! An oracle hook keeps one [OracleEntry] for every feed, such as a price, in its state
+//! under the [oracle_key] of the feed: the value, when it was published and the public
+//! key of the publisher that signed it. Publishers are services off the ledger that sign
+//! the [signing payload](OracleEntry::signing_payload) of every value, so anyone can
+//! relay a value to the oracle hook with an Invoke, which [publish_invoke] checks
+//! against the key of the publisher before storing it.
+//!
+//! Consumer hooks on other accounts read the entries with [OracleEntry::load_foreign],
+//! and check for themselves that the publisher is one they trust and that the value is
+//! recent enough.
+//!
+//! # Example
+//! ```
+//! // the oracle hook, with the key of its publisher in a hook parameter
+//! let publisher: PublicKey = hook_param_as(b"publisher")?;
+//! publish_invoke(&publisher)?;
+//!
+//! // a consumer hook
+//! const XAH_USD: [u8; STATE_KEY_LEN] = oracle_key(b"XAH/USD");
+//! let price = OracleEntry::load_foreign(&XAH_USD, &ORACLE_NAMESPACE, &ORACLE_ACCOUNT)?;
+//! if price.publisher != PUBLISHER || !price.is_fresh(ledger_time(), 600) {
+//!     rollback(b"no recent price", 1);
+//! }
+//! ```
+
+use core::mem::MaybeUninit;
+
+use crate::api::*;
+use crate::params::{otxn_param_as, otxn_param_into};
+
+/// Length of an [OracleEntry] in state
+pub const ORACLE_ENTRY_LEN: usize = XFL_LEN + 4 + PUBLIC_KEY_LEN;
+
+/// Bytes the signing payload of every value starts with, so that signatures of oracle
+/// values can't be mistaken for signatures of anything else
+pub const ORACLE_PAYLOAD_PREFIX: [u8; 4] = *b"ORCL";
+
+/// Length of the [signing payload](OracleEntry::signing_payload) of a value
+pub const ORACLE_PAYLOAD_LEN: usize = 4 + STATE_KEY_LEN + XFL_LEN + 4;
+
+/// Maximum length of a signature, which is a DER encoded secp256k1 signature
+pub const SIGNATURE_MAX_LEN: usize = 72;
+
+/// Invoke parameter with the [oracle_key] of the feed, all 32 bytes
+pub const KEY_PARAM: &[u8] = b"ORACLE_KEY";
+
+/// Invoke parameter with the value, as an 8 byte little endian XFL like hook parameters
+pub const VALUE_PARAM: &[u8] = b"ORACLE_VALUE";
+
+/// Invoke parameter with the time the value was published, 4 bytes big endian
+pub const TIME_PARAM: &[u8] = b"ORACLE_TIME";
+
+/// Invoke parameter with the signature of the publisher over the signing payload
+pub const SIGNATURE_PARAM: &[u8] = b"ORACLE_SIG";
+
+/// The state key of the feed named `name`, which is the name padded with zeros.
+///
+/// Keys are shared by the oracle hook and the hooks reading it, as constants: the name is
+/// copied with a loop without guard, and one longer than 32 bytes fails to compile.
+pub const fn oracle_key(name: &[u8]) -> [u8; STATE_KEY_LEN] {
+    assert!(name.len() <= STATE_KEY_LEN, "oracle key name is too long");
+
+    let mut key = [0; STATE_KEY_LEN];
+    let mut i = 0;
+    while i < name.len() {
+        key[i] = name[i];
+        i += 1;
+    }
+
+    key
+}
+
+/// The latest value of a feed
+#[derive(Clone, Copy)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct OracleEntry {
+    /// The value, such as a price
+    pub value: XFL,
+    /// When the publisher published the value
+    pub timestamp: RippleTime,
+    /// The key of the publisher that signed the value
+    pub publisher: PublicKey,
+}
+
+// The layout of an entry in state
+#[repr(C)]
+struct StoredEntry {
+    value: [u8; XFL_LEN],
+    timestamp: [u8; 4],
+    publisher: [u8; PUBLIC_KEY_LEN],
+}
+
+#[repr(C)]
+struct Payload {
+    prefix: [u8; 4],
+    key: [u8; STATE_KEY_LEN],
+    value: [u8; XFL_LEN],
+    timestamp: [u8; 4],
+}
+
+impl OracleEntry {
+    /// What the publisher signs to publish the value under `key`: the
+    /// [prefix](ORACLE_PAYLOAD_PREFIX), the key, the value as 8 little endian bytes and the
+    /// timestamp as 4 big endian bytes
+    #[inline(always)]
+    pub fn signing_payload(&self, key: &[u8; STATE_KEY_LEN]) -> [u8; ORACLE_PAYLOAD_LEN] {
+        let payload = Payload {
+            prefix: ORACLE_PAYLOAD_PREFIX,
+            key: *key,
+            value: self.value.0.to_le_bytes(),
+            timestamp: self.timestamp.to_be_bytes(),
+        };
+
+        unsafe { core::mem::transmute::<Payload, [u8; ORACLE_PAYLOAD_LEN]>(payload) }
+    }
+
+    /// Whether the value was published at most `max_age` seconds before `now`. A value
+    /// published after `now` isn't, since the publisher's clock can't be trusted.
+    #[inline(always)]
+    pub const fn is_fresh(&self, now: RippleTime, max_age: u32) -> bool {
+        self.timestamp.0 <= now.0 && now.0 - self.timestamp.0 <= max_age
+    }
+
+    /// Read the entry under `key` from the state of this hook, failing with
+    /// [HookError::DoesNotMatch] if it isn't [ORACLE_ENTRY_LEN] bytes long
+    #[inline(always)]
+    pub fn load(key: &[u8; STATE_KEY_LEN]) -> Result<Self> {
+        Ok(Self::from_bytes(state_exact::<ORACLE_ENTRY_LEN>(key)?))
+    }
+
+    /// Read the entry under `key` from the state of the oracle hook of `account`, which
+    /// keeps its state in `namespace`, failing like [load](Self::load)
+    #[inline(always)]
+    pub fn load_foreign(
+        key: &[u8; STATE_KEY_LEN],
+        namespace: &Hash,
+        account: &AccountId,
+    ) -> Result<Self> {
+        let mut bytes = [0; ORACLE_ENTRY_LEN];
+        match state_foreign(&mut bytes, key, namespace, account.as_bytes()) {
+            Ok(len) if len as usize == ORACLE_ENTRY_LEN => Ok(Self::from_bytes(bytes)),
+            Ok(_) => Err(HookError::DoesNotMatch),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Write the entry to the state of this hook under `key`, without checking it
+    #[inline(always)]
+    pub fn store(&self, key: &[u8; STATE_KEY_LEN]) -> Result<()> {
+        let stored = StoredEntry {
+            value: self.value.0.to_le_bytes(),
+            timestamp: self.timestamp.to_be_bytes(),
+            publisher: self.publisher.0,
+        };
+        let bytes = unsafe { core::mem::transmute::<StoredEntry, [u8; ORACLE_ENTRY_LEN]>(stored) };
+
+        match state_set(&bytes, key) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    #[inline(always)]
+    fn from_bytes(bytes: [u8; ORACLE_ENTRY_LEN]) -> Self {
+        let stored = unsafe { core::mem::transmute::<[u8; ORACLE_ENTRY_LEN], StoredEntry>(bytes) };
+
+        Self {
+            value: XFL(i64::from_le_bytes(stored.value)),
+            timestamp: RippleTime::from_be_bytes(stored.timestamp),
+            publisher: PublicKey(stored.publisher),
+        }
+    }
+}
+
+/// Store `entry` under `key` if `signature` is the signature of its publisher over its
+/// [signing payload](OracleEntry::signing_payload).
+///
+/// Fails with [HookError::NotAuthorized] if the signature doesn't match, and with
+/// [HookError::AlreadySet] if the entry stored under `key` is at least as recent, so
+/// that old values can't be published again.
+#[inline(always)]
+pub fn publish(key: &[u8; STATE_KEY_LEN], entry: &OracleEntry, signature: &[u8]) -> Result<()> {
+    if !util_verify(&entry.signing_payload(key), signature, &entry.publisher)? {
+        return Err(HookError::NotAuthorized);
+    }
+
+    match OracleEntry::load(key) {
+        Ok(stored) if stored.timestamp >= entry.timestamp => return Err(HookError::AlreadySet),
+        Ok(_) | Err(HookError::DoesntExist) => {}
+        Err(err) => return Err(err),
+    }
+
+    entry.store(key)
+}
+
+/// [publish] the value carried by the originating transaction, if it is an Invoke with a
+/// [KEY_PARAM], signed by `publisher`. Returns the key of the feed.
+///
+/// Returns `Ok(None)` for other transactions, and the error of reading the parameter for
+/// an Invoke missing one of the other parameters.
+#[inline(always)]
+pub fn publish_invoke(publisher: &PublicKey) -> Result<Option<[u8; STATE_KEY_LEN]>> {
+    if otxn_type() != TxnType::Invoke as i64 {
+        return Ok(None);
+    }
+
+    let key = match otxn_param_as::<[u8; STATE_KEY_LEN]>(KEY_PARAM) {
+        Ok(key) => key,
+        Err(HookError::DoesntExist) => return Ok(None),
+        Err(err) => return Err(err),
+    };
+    let entry = OracleEntry {
+        value: otxn_param_as(VALUE_PARAM)?,
+        timestamp: RippleTime(otxn_param_as(TIME_PARAM)?),
+        publisher: *publisher,
+    };
+    let mut signature = [MaybeUninit::uninit(); SIGNATURE_MAX_LEN];
+    let signature = otxn_param_into(SIGNATURE_PARAM, &mut signature)?;
+
+    publish(&key, &entry, signature)?;
+
+    Ok(Some(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::fixtures::{HOOK_ACCOUNT, ISSUER};
+    use crate::mock;
+
+    const XAH_USD: [u8; STATE_KEY_LEN] = oracle_key(b"XAH/USD");
+    const PUBLISHER: PublicKey = PublicKey([0xED; PUBLIC_KEY_LEN]);
+
+    fn entry_at(timestamp: u32) -> OracleEntry {
+        OracleEntry {
+            value: XFL(6089866696204910592),
+            timestamp: RippleTime(timestamp),
+            publisher: PUBLISHER,
+        }
+    }
+
+    fn invoke_publishing(entry: &OracleEntry, signature: &[u8]) {
+        mock::set_otxn_type(TxnType::Invoke);
+        mock::set_otxn_param(KEY_PARAM, &XAH_USD);
+        mock::set_otxn_param(VALUE_PARAM, &entry.value.0.to_le_bytes());
+        mock::set_otxn_param(TIME_PARAM, &entry.timestamp.to_be_bytes());
+        mock::set_otxn_param(SIGNATURE_PARAM, signature);
+    }
+
+    #[wasm_bindgen_test]
+    fn publishes_values_signed_by_the_publisher() {
+        mock::reset();
+        assert!(matches!(publish_invoke(&PUBLISHER), Ok(None)));
+
+        let entry = entry_at(1000);
+        let signature = mock::fake_signature(&PUBLISHER, &entry.signing_payload(&XAH_USD));
+        invoke_publishing(&entry, &signature);
+        assert!(matches!(publish_invoke(&PUBLISHER), Ok(Some(key)) if key == XAH_USD));
+        assert!(matches!(
+            OracleEntry::load(&XAH_USD),
+            Ok(stored) if stored.value.0 == entry.value.0
+                && stored.timestamp == RippleTime(1000)
+                && stored.publisher == PUBLISHER
+        ));
+
+        // the same value again
+        assert!(matches!(
+            publish_invoke(&PUBLISHER),
+            Err(HookError::AlreadySet)
+        ));
+        // signed by someone else
+        let other = PublicKey([0x02; PUBLIC_KEY_LEN]);
+        assert!(matches!(
+            publish_invoke(&other),
+            Err(HookError::NotAuthorized)
+        ));
+        // or for another time
+        invoke_publishing(&entry_at(2000), &signature);
+        assert!(matches!(
+            publish_invoke(&PUBLISHER),
+            Err(HookError::NotAuthorized)
+        ));
+    }
+
+    #[wasm_bindgen_test]
+    fn reads_entries_of_other_hooks() {
+        const NAMESPACE: Hash = [7; HASH_LEN];
+
+        mock::reset();
+        mock::set_hook_account(HOOK_ACCOUNT);
+        assert!(entry_at(1000).store(&XAH_USD).is_ok());
+        let stored = mock::state(&XAH_USD).unwrap();
+        mock::set_foreign_state(ISSUER, NAMESPACE, &XAH_USD, &stored);
+
+        let entry = OracleEntry::load_foreign(&XAH_USD, &NAMESPACE, &ISSUER);
+        assert!(matches!(entry, Ok(entry) if entry.publisher == PUBLISHER
+            && entry.is_fresh(RippleTime(1600), 600)
+            && !entry.is_fresh(RippleTime(1601), 600)
+            && !entry.is_fresh(RippleTime(999), 600)));
+        assert!(matches!(
+            OracleEntry::load_foreign(&XAH_USD, &[8; HASH_LEN], &ISSUER),
+            Err(HookError::DoesntExist)
+        ));
+
+        mock::set_foreign_state(ISSUER, NAMESPACE, &XAH_USD, &stored[1..]);
+        assert!(matches!(
+            OracleEntry::load_foreign(&XAH_USD, &NAMESPACE, &ISSUER),
+            Err(HookError::DoesNotMatch)
+        ));
+        mock::set_state(&XAH_USD, &stored[1..]);
+        assert!(matches!(
+            OracleEntry::load(&XAH_USD),
+            Err(HookError::DoesNotMatch)
+        ));
+    }
+}
@@ -0,0 +1,622 @@
+//! Typed install-time hook parameters.
+//!
+//! Hook parameters are set when the hook is installed and are the usual way to configure
+//! a hook without recompiling it. Instead of calling [hook_param] for every parameter and
+//! decoding the bytes by hand, the configuration can be declared as a struct deriving
+//! [HookParams](macro@HookParams) and loaded at once with [HookParams::load].
+//!
+//! Each field is read from the parameter named like the field, or the name given with
+//! `#[param(name = "...")]`. Fields with `#[param(default = ...)]` fall back to the
+//! default if the parameter is not set, while missing parameters without a default make
+//! `load` return [HookError::DoesntExist].
+//!
+//! # Example
+//! ```
+//! #[derive(HookParams)]
+//! struct Config {
+//!     #[param(name = "min drops")]
+//!     min_drops: u64,
+//!     owner: AccountId,
+//!     #[param(default = 10)]
+//!     max_payouts: u32,
+//! }
+//!
+//! let config = Config::load()?;
+//! ```
+//!
+//! A [ParamSchema] declares the names, types and lengths of the hook and Invoke parameters
+//! a hook expects, and which of them are required. Enforced at the start of the hook with
+//! [ParamSchema::enforce], it rolls back with a message naming the first parameter that
+//! doesn't match, so a hook installed with a wrong or missing parameter fails loudly the
+//! first time it runs instead of acting on bad values.
+//!
+//! ```
+//! const SCHEMA: ParamSchema = ParamSchema::new(&[
+//!     ParamSpec::hook(b"owner", ParamType::AccountId),
+//!     ParamSpec::hook(b"min drops", ParamType::Drops).optional(),
+//!     ParamSpec::otxn(b"memo", ParamType::Bytes { min: 1, max: 64 }).optional(),
+//! ]);
+//!
+//! SCHEMA.enforce();
+//! ```
+
+use core::mem::MaybeUninit;
+
+use crate::api::*;
+use crate::c;
+use crate::utils::FixedWriter;
+
+/// Maximum length of a hook parameter value
+pub const HOOK_PARAM_VALUE_MAX_LEN: usize = 256;
+
+/// Maximum length of a hook parameter name
+pub const HOOK_PARAM_NAME_MAX_LEN: usize = 32;
+
+/// Maximum number of parameters in a [ParamSchema]
+pub const PARAM_SCHEMA_MAX_LEN: usize = 32;
+
+/// Maximum length of the message of a [ParamViolation]
+pub const PARAM_VIOLATION_MSG_MAX_LEN: usize = 96;
+
+/// A set of hook parameters that can be loaded at once, usually derived with
+/// [HookParams](macro@HookParams)
+pub trait HookParams: Sized {
+    /// Read and decode all parameters
+    fn load() -> Result<Self>;
+}
+
+/// A type that can be decoded from the value of a hook parameter.
+///
+/// Integers and [Drops] are big endian, XFLs are 8 byte little endian, which is what
+/// `floatToLEXfl` of hooks-toolkit produces, and byte arrays, including [AccountId] and
+/// [PublicKey], are taken as is.
+/// Values of the wrong length are rejected with [HookError::InvalidArgument].
+pub trait FromHookParam: Sized {
+    /// Decode the parameter value
+    fn from_param_bytes(bytes: &[u8]) -> Result<Self>;
+}
+
+impl<const N: usize> FromHookParam for [u8; N] {
+    #[inline(always)]
+    fn from_param_bytes(bytes: &[u8]) -> Result<Self> {
+        match bytes.try_into() {
+            core::result::Result::Ok(array) => Ok(array),
+            core::result::Result::Err(_) => Err(HookError::InvalidArgument),
+        }
+    }
+}
+
+macro_rules! impl_from_hook_param_for_int {
+    ($($int:ty),*) => {
+        $(
+            impl FromHookParam for $int {
+                #[inline(always)]
+                fn from_param_bytes(bytes: &[u8]) -> Result<Self> {
+                    Ok(<$int>::from_be_bytes(FromHookParam::from_param_bytes(bytes)?))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_hook_param_for_int!(u8, u16, u32, u64, i64);
+
+impl FromHookParam for XFL {
+    #[inline(always)]
+    fn from_param_bytes(bytes: &[u8]) -> Result<Self> {
+        Ok(XFL(i64::from_le_bytes(FromHookParam::from_param_bytes(
+            bytes,
+        )?)))
+    }
+}
+
+impl FromHookParam for AccountId {
+    #[inline(always)]
+    fn from_param_bytes(bytes: &[u8]) -> Result<Self> {
+        Ok(AccountId(FromHookParam::from_param_bytes(bytes)?))
+    }
+}
+
+impl FromHookParam for Drops {
+    #[inline(always)]
+    fn from_param_bytes(bytes: &[u8]) -> Result<Self> {
+        Ok(Drops(FromHookParam::from_param_bytes(bytes)?))
+    }
+}
+
+impl FromHookParam for PublicKey {
+    #[inline(always)]
+    fn from_param_bytes(bytes: &[u8]) -> Result<Self> {
+        Ok(PublicKey(FromHookParam::from_param_bytes(bytes)?))
+    }
+}
+
+/// Read the hook parameter named `parameter_name` and decode it as `T`
+///
+/// # Example
+/// ```
+/// let min_drops: u64 = hook_param_as(b"min drops")?;
+/// ```
+#[inline(always)]
+pub fn hook_param_as<T: FromHookParam>(parameter_name: &[u8]) -> Result<T> {
+    let mut buffer: [MaybeUninit<u8>; HOOK_PARAM_VALUE_MAX_LEN] = MaybeUninit::uninit_array();
+
+    T::from_param_bytes(hook_param_into(parameter_name, &mut buffer)?)
+}
+
+/// Read the hook parameter named `parameter_name` into `buffer`, returning the part of
+/// `buffer` the value was written to.
+///
+/// For values of varying length, such as a list of accounts, which [hook_param_as] can't
+/// decode without copying them. Returns [HookError::TooSmall] if the value doesn't fit.
+///
+/// # Example
+/// ```
+/// let mut buffer = [MaybeUninit::uninit(); HOOK_PARAM_VALUE_MAX_LEN];
+/// let accounts = hook_param_into(b"accounts", &mut buffer)?;
+/// ```
+#[inline(always)]
+pub fn hook_param_into<'a>(
+    parameter_name: &[u8],
+    buffer: &'a mut [MaybeUninit<u8>],
+) -> Result<&'a [u8]> {
+    let result: Result<u64> = unsafe {
+        c::hook_param(
+            host_ptr(buffer.as_mut_ptr()),
+            buffer.len() as u32,
+            host_ptr(parameter_name.as_ptr()),
+            parameter_name.len() as u32,
+        )
+        .into()
+    };
+    let len = result? as usize;
+
+    // the host initialized the first `len` bytes
+    Ok(unsafe { core::slice::from_raw_parts(buffer.as_ptr() as *const u8, len) })
+}
+
+/// Read the Invoke parameter named `parameter_name` of the originating transaction and
+/// decode it as `T`, like [hook_param_as]
+///
+/// # Example
+/// ```
+/// let recipient: AccountId = otxn_param_as(b"recipient")?;
+/// ```
+#[inline(always)]
+pub fn otxn_param_as<T: FromHookParam>(parameter_name: &[u8]) -> Result<T> {
+    let mut buffer: [MaybeUninit<u8>; HOOK_PARAM_VALUE_MAX_LEN] = MaybeUninit::uninit_array();
+
+    T::from_param_bytes(otxn_param_into(parameter_name, &mut buffer)?)
+}
+
+/// Read the Invoke parameter named `parameter_name` of the originating transaction into
+/// `buffer`, like [hook_param_into]
+#[inline(always)]
+pub fn otxn_param_into<'a>(
+    parameter_name: &[u8],
+    buffer: &'a mut [MaybeUninit<u8>],
+) -> Result<&'a [u8]> {
+    let result: Result<u64> = unsafe {
+        c::otxn_param(
+            host_ptr(buffer.as_mut_ptr()),
+            buffer.len() as u32,
+            host_ptr(parameter_name.as_ptr()),
+            parameter_name.len() as u32,
+        )
+        .into()
+    };
+    let len = result? as usize;
+
+    // the host initialized the first `len` bytes
+    Ok(unsafe { core::slice::from_raw_parts(buffer.as_ptr() as *const u8, len) })
+}
+
+/// Where a parameter of a [ParamSchema] is read from
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub enum ParamSource {
+    /// An install-time hook parameter, see [hook_param]
+    Hook,
+    /// An Invoke parameter of the originating transaction, see [otxn_param]
+    Otxn,
+}
+
+/// What the value of a parameter of a [ParamSchema] has to be, encoded like
+/// [FromHookParam] decodes it
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub enum ParamType {
+    /// `min` to `max` bytes of anything
+    Bytes {
+        /// Smallest accepted length
+        min: usize,
+        /// Largest accepted length
+        max: usize,
+    },
+    /// A big endian [u8]
+    U8,
+    /// A big endian [u16]
+    U16,
+    /// A big endian [u32]
+    U32,
+    /// A big endian [u64]
+    U64,
+    /// A big endian [i64]
+    I64,
+    /// A normalized little endian [XFL]
+    XFL,
+    /// Big endian [Drops], at most [Drops::MAX]
+    Drops,
+    /// An [AccountId]
+    AccountId,
+    /// A [PublicKey]
+    PublicKey,
+}
+
+impl ParamType {
+    /// Smallest and largest accepted length of the value
+    #[inline(always)]
+    pub const fn len_range(self) -> (usize, usize) {
+        let len = match self {
+            ParamType::Bytes { min, max } => return (min, max),
+            ParamType::U8 => 1,
+            ParamType::U16 => 2,
+            ParamType::U32 => 4,
+            ParamType::U64 | ParamType::I64 | ParamType::XFL | ParamType::Drops => 8,
+            ParamType::AccountId => ACC_ID_LEN,
+            ParamType::PublicKey => PUBLIC_KEY_LEN,
+        };
+
+        (len, len)
+    }
+
+    // Whether a value of an accepted length is valid
+    #[inline(always)]
+    fn is_valid(self, value: &[u8]) -> bool {
+        match self {
+            ParamType::XFL => match XFL::from_param_bytes(value) {
+                Ok(xfl) => is_normalized(xfl),
+                Err(_) => false,
+            },
+            ParamType::Drops => match Drops::from_param_bytes(value) {
+                Ok(drops) => drops.0 <= Drops::MAX.0,
+                Err(_) => false,
+            },
+            _ => true,
+        }
+    }
+}
+
+/// A parameter of a [ParamSchema]
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct ParamSpec {
+    /// Name of the parameter
+    pub name: &'static [u8],
+    /// Where the parameter is read from
+    pub source: ParamSource,
+    /// What its value has to be
+    pub kind: ParamType,
+    /// Whether the parameter has to be set
+    pub required: bool,
+}
+
+impl ParamSpec {
+    /// A required hook parameter.
+    ///
+    /// Fails to compile in constants if `name` is longer than [HOOK_PARAM_NAME_MAX_LEN].
+    #[inline(always)]
+    pub const fn hook(name: &'static [u8], kind: ParamType) -> Self {
+        Self::new(name, ParamSource::Hook, kind)
+    }
+
+    /// A required Invoke parameter of the originating transaction, like [hook](Self::hook)
+    #[inline(always)]
+    pub const fn otxn(name: &'static [u8], kind: ParamType) -> Self {
+        Self::new(name, ParamSource::Otxn, kind)
+    }
+
+    /// The same parameter, but only checked if it is set
+    #[inline(always)]
+    pub const fn optional(self) -> Self {
+        Self {
+            required: false,
+            ..self
+        }
+    }
+
+    #[inline(always)]
+    const fn new(name: &'static [u8], source: ParamSource, kind: ParamType) -> Self {
+        assert!(
+            !name.is_empty() && name.len() <= HOOK_PARAM_NAME_MAX_LEN,
+            "parameter name must be 1 to 32 bytes"
+        );
+
+        Self {
+            name,
+            source,
+            kind,
+            required: true,
+        }
+    }
+}
+
+/// What is wrong with a parameter, see [ParamViolation]
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub enum ParamProblem {
+    /// A required parameter is not set
+    Missing,
+    /// The value has this length, which the type of the parameter doesn't accept
+    WrongLength(usize),
+    /// The value has the right length, but is not valid for the type of the parameter
+    InvalidValue,
+    /// The host failed to read the parameter
+    Unreadable(HookError),
+}
+
+/// The first parameter that doesn't match a [ParamSchema]
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct ParamViolation {
+    /// The parameter, as declared in the schema
+    pub spec: ParamSpec,
+    /// What is wrong with it
+    pub problem: ParamProblem,
+}
+
+impl ParamViolation {
+    /// The error the violation stands for: [HookError::DoesntExist] for a missing
+    /// parameter, [HookError::InvalidArgument] for a wrong value, or the error of the host
+    #[inline(always)]
+    pub fn error(&self) -> HookError {
+        match self.problem {
+            ParamProblem::Missing => HookError::DoesntExist,
+            ParamProblem::WrongLength(_) | ParamProblem::InvalidValue => HookError::InvalidArgument,
+            ParamProblem::Unreadable(err) => err,
+        }
+    }
+
+    /// A message naming the parameter and the problem, like
+    /// `hook param owner: 32 bytes, expected 20`
+    #[inline(always)]
+    pub fn message(&self) -> FixedWriter<PARAM_VIOLATION_MSG_MAX_LEN> {
+        let mut msg = FixedWriter::new();
+        msg.write_bytes(match self.spec.source {
+            ParamSource::Hook => b"hook param ",
+            ParamSource::Otxn => b"otxn param ",
+        });
+        msg.write_bytes(self.spec.name);
+        msg.write_bytes(b": ");
+
+        match self.problem {
+            ParamProblem::Missing => msg.write_bytes(b"missing"),
+            ParamProblem::WrongLength(len) => {
+                let (min, max) = self.spec.kind.len_range();
+                msg.write_u64(len as u64);
+                msg.write_bytes(b" bytes, expected ");
+                msg.write_u64(min as u64);
+                if max != min {
+                    msg.write_bytes(b" to ");
+                    msg.write_u64(max as u64);
+                }
+            }
+            ParamProblem::InvalidValue => msg.write_bytes(match self.spec.kind {
+                ParamType::XFL => b"not a normalized XFL",
+                ParamType::Drops => b"more drops than there are",
+                _ => b"invalid value",
+            }),
+            ParamProblem::Unreadable(err) => {
+                msg.write_bytes(b"unreadable, error ");
+                msg.write_i64(err.code() as i64);
+            }
+        }
+
+        msg
+    }
+}
+
+/// The parameters a hook expects, checked at once when it starts
+#[derive(Clone, Copy)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct ParamSchema<'a> {
+    params: &'a [ParamSpec],
+}
+
+impl<'a> ParamSchema<'a> {
+    /// A schema of `params`, checked in order.
+    ///
+    /// A schema is a constant, so that a schema with more parameters than the
+    /// [PARAM_SCHEMA_MAX_LEN] iterations [violation](Self::violation) is guarded for fails
+    /// to compile.
+    #[inline(always)]
+    pub const fn new(params: &'a [ParamSpec]) -> Self {
+        assert!(
+            params.len() <= PARAM_SCHEMA_MAX_LEN,
+            "too many parameters in schema"
+        );
+
+        Self { params }
+    }
+
+    /// The parameters of the schema
+    #[inline(always)]
+    pub fn params(&self) -> &'a [ParamSpec] {
+        self.params
+    }
+
+    /// The first parameter that doesn't match the schema, if any
+    #[inline(always)]
+    pub fn violation(&self) -> Option<ParamViolation> {
+        // guarded loop
+        let mut i = 0;
+        while {
+            max_iter(PARAM_SCHEMA_MAX_LEN as u32 + 1);
+            i < self.params.len()
+        } {
+            let spec = self.params[i];
+            i += 1;
+
+            let mut buffer = [MaybeUninit::uninit(); HOOK_PARAM_VALUE_MAX_LEN];
+            let value = match spec.source {
+                ParamSource::Hook => hook_param_into(spec.name, &mut buffer),
+                ParamSource::Otxn => otxn_param_into(spec.name, &mut buffer),
+            };
+            let problem = match value {
+                Ok(value) => {
+                    let (min, max) = spec.kind.len_range();
+                    if value.len() < min || value.len() > max {
+                        ParamProblem::WrongLength(value.len())
+                    } else if !spec.kind.is_valid(value) {
+                        ParamProblem::InvalidValue
+                    } else {
+                        continue;
+                    }
+                }
+                Err(HookError::DoesntExist) if !spec.required => continue,
+                Err(HookError::DoesntExist) => ParamProblem::Missing,
+                Err(err) => ParamProblem::Unreadable(err),
+            };
+
+            return Some(ParamViolation { spec, problem });
+        }
+
+        None
+    }
+
+    /// Check the parameters, failing with the [error](ParamViolation::error) of the
+    /// first one that doesn't match the schema
+    #[inline(always)]
+    pub fn check(&self) -> Result<()> {
+        match self.violation() {
+            None => Ok(()),
+            Some(violation) => Err(violation.error()),
+        }
+    }
+
+    /// Rollback with the [message](ParamViolation::message) and the error code of the
+    /// first parameter that doesn't match the schema, if any
+    #[inline(always)]
+    pub fn enforce(&self) {
+        if let Some(violation) = self.violation() {
+            rollback(violation.message().as_bytes(), violation.error().into());
+        }
+    }
+}
+
+// Whether `xfl` is 0 or has a mantissa of 16 digits and an exponent of -96 to 80, as the
+// XFLs made by the host do
+#[inline(always)]
+fn is_normalized(xfl: XFL) -> bool {
+    const MIN_MANTISSA: i64 = 1_000_000_000_000_000;
+
+    let mantissa = xfl.0 & ((1 << 54) - 1);
+    let exponent = (xfl.0 >> 54) & 0xFF;
+    xfl.0 == 0
+        || (xfl.0 >= 0
+            && (MIN_MANTISSA..10 * MIN_MANTISSA).contains(&mantissa)
+            && (1..=177).contains(&exponent))
+}
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::fixtures::DESTINATION;
+    use crate::mock;
+
+    const SCHEMA: ParamSchema = ParamSchema::new(&[
+        ParamSpec::hook(b"owner", ParamType::AccountId),
+        ParamSpec::hook(b"rate", ParamType::XFL).optional(),
+        ParamSpec::otxn(b"memo", ParamType::Bytes { min: 1, max: 16 }).optional(),
+    ]);
+
+    #[wasm_bindgen_test]
+    fn decodes_hook_param_values() {
+        assert!(matches!(
+            u64::from_param_bytes(&1000u64.to_be_bytes()),
+            Ok(1000)
+        ));
+        assert!(matches!(u32::from_param_bytes(&[0, 0, 1, 0]), Ok(256)));
+        assert!(matches!(<[u8; 3]>::from_param_bytes(b"abc"), Ok(bytes) if &bytes == b"abc"));
+        assert!(matches!(
+            XFL::from_param_bytes(&42i64.to_le_bytes()),
+            Ok(XFL(42))
+        ));
+        assert!(matches!(
+            AccountId::from_param_bytes(&[1; ACC_ID_LEN]),
+            Ok(account_id) if account_id == AccountId([1; ACC_ID_LEN])
+        ));
+    }
+
+    #[wasm_bindgen_test]
+    fn rejects_hook_param_values_of_the_wrong_length() {
+        assert!(matches!(
+            u64::from_param_bytes(&[0; 4]),
+            Err(HookError::InvalidArgument)
+        ));
+        assert!(matches!(
+            <[u8; ACC_ID_LEN]>::from_param_bytes(&[0; 32]),
+            Err(HookError::InvalidArgument)
+        ));
+        assert!(matches!(
+            AccountId::from_param_bytes(&[0; 32]),
+            Err(HookError::InvalidArgument)
+        ));
+    }
+
+    #[wasm_bindgen_test]
+    fn accepts_parameters_matching_the_schema() {
+        mock::reset();
+        mock::set_hook_param(b"owner", DESTINATION.as_bytes());
+        assert!(SCHEMA.violation().is_none());
+        assert!(SCHEMA.check().is_ok());
+        SCHEMA.enforce();
+
+        // XFL::one()
+        mock::set_hook_param(b"rate", &6089866696204910592i64.to_le_bytes());
+        mock::set_otxn_param(b"memo", b"hello");
+        assert!(SCHEMA.violation().is_none());
+    }
+
+    #[wasm_bindgen_test]
+    fn names_the_first_parameter_not_matching_the_schema() {
+        mock::reset();
+        let violation = SCHEMA.violation().unwrap();
+        assert_eq!(violation.problem, ParamProblem::Missing);
+        assert_eq!(violation.message().as_bytes(), b"hook param owner: missing");
+        assert!(matches!(SCHEMA.check(), Err(HookError::DoesntExist)));
+
+        mock::set_hook_param(b"owner", &[1; 32]);
+        let violation = SCHEMA.violation().unwrap();
+        assert_eq!(violation.problem, ParamProblem::WrongLength(32));
+        assert_eq!(
+            violation.message().as_bytes(),
+            b"hook param owner: 32 bytes, expected 20"
+        );
+        assert_eq!(violation.error(), HookError::InvalidArgument);
+
+        mock::set_hook_param(b"owner", DESTINATION.as_bytes());
+        mock::set_hook_param(b"rate", &1i64.to_le_bytes());
+        assert_eq!(
+            SCHEMA.violation().unwrap().message().as_bytes(),
+            b"hook param rate: not a normalized XFL"
+        );
+
+        mock::set_hook_param(b"rate", &0i64.to_le_bytes());
+        mock::set_otxn_param(b"memo", &[b'a'; 17]);
+        assert_eq!(
+            SCHEMA.violation().unwrap().message().as_bytes(),
+            b"otxn param memo: 17 bytes, expected 1 to 16"
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn validates_drops() {
+        let drops = ParamType::Drops;
+        assert!(drops.is_valid(&Drops::MAX.0.to_be_bytes()));
+        assert!(!drops.is_valid(&(Drops::MAX.0 + 1).to_be_bytes()));
+        assert!(!ParamType::XFL.is_valid(&(-1i64).to_le_bytes()));
+    }
+}
@@ -0,0 +1,287 @@
+//! Limits on the amounts of payments, checked the same way by every hook.
+//!
+//! An [AmountPolicy] names the currency amounts have to be in and the smallest and largest
+//! accepted values, so that a hook enforcing payment sizes only states its limits. It can
+//! check any serialized [Amount] with [AmountPolicy::check], or the amount of the
+//! originating transaction with [AmountPolicy::check_otxn], which is what the `amount`
+//! check of [filter!](crate::filter) does.
+//!
+//! Limits are XFLs, in drops for XRP and in units of the currency for issued amounts.
+//! They are compared by their bits rather than with [float_compare](crate::c::float_compare),
+//! which orders normalized XFLs the same way without calling the host, so they have to
+//! be normalized, as the XFLs made by the host are.
+//!
+//! # Example
+//! ```
+//! const POLICY: AmountPolicy = AmountPolicy::xrp(Some(Drops(1_000)), Some(Drops(1_000_000)));
+//!
+//! #[hook]
+//! fn bounded() -> Outcome<'static> {
+//!     filter! {
+//!         txn_type: Payment,
+//!         amount: POLICY,
+//!     }
+//!
+//!     Outcome::Accept { code: 0, msg: b"" }
+//! }
+//! ```
+
+use crate::api::*;
+use crate::asset::IssuedAsset;
+use crate::reader::SliceReader;
+use crate::utils::is_buffer_equal;
+
+/// Which amounts an [AmountPolicy] accepts
+#[derive(Clone, Copy)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct AmountPolicy {
+    /// Smallest accepted value, if any
+    pub min: Option<XFL>,
+    /// Largest accepted value, if any
+    pub max: Option<XFL>,
+    /// Currency of the accepted amounts, `None` for XRP
+    pub currency: Option<IssuedAsset>,
+}
+
+impl AmountPolicy {
+    /// Amounts in XRP of `min` to `max` drops
+    #[inline(always)]
+    pub const fn xrp(min: Option<Drops>, max: Option<Drops>) -> Self {
+        Self {
+            min: match min {
+                Some(drops) => Some(drops_to_xfl(drops)),
+                None => None,
+            },
+            max: match max {
+                Some(drops) => Some(drops_to_xfl(drops)),
+                None => None,
+            },
+            currency: None,
+        }
+    }
+
+    /// Amounts of `currency` of `min` to `max` units
+    #[inline(always)]
+    pub const fn issued(currency: IssuedAsset, min: Option<XFL>, max: Option<XFL>) -> Self {
+        Self {
+            min,
+            max,
+            currency: Some(currency),
+        }
+    }
+
+    /// Check `amount`, a serialized amount in XRP or in an issued currency.
+    ///
+    /// Fails with [HookError::DoesNotMatch] if the amount is not in the currency of the
+    /// policy, [HookError::TooSmall] if it is below the minimum, and
+    /// [HookError::TooBig] if it is above the maximum.
+    #[inline(always)]
+    pub fn check(&self, amount: &Amount) -> Result<()> {
+        let mut reader = SliceReader::new(amount);
+        let head = reader.read_u64_be()?;
+        // the highest bit is set for issued amounts
+        let is_xrp = head & 0x8000_0000_0000_0000 == 0;
+        let value = match self.currency {
+            None if is_xrp => drops_to_xfl(Drops(head & 0x3FFF_FFFF_FFFF_FFFF)),
+            Some(asset)
+                if !is_xrp
+                    && is_buffer_equal(reader.read_bytes(CURRENCY_CODE_SIZE)?, &asset.currency)
+                    && is_buffer_equal(reader.read_bytes(ACC_ID_LEN)?, &asset.issuer.0) =>
+            {
+                // the value of an issued amount is an XFL, but for the highest bit
+                XFL((head & 0x7FFF_FFFF_FFFF_FFFF) as i64)
+            }
+            _ => return Err(HookError::DoesNotMatch),
+        };
+
+        match (self.min, self.max) {
+            (Some(min), _) if xfl_order(value) < xfl_order(min) => Err(HookError::TooSmall),
+            (_, Some(max)) if xfl_order(value) > xfl_order(max) => Err(HookError::TooBig),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check the `Amount` of the originating transaction, see [check](Self::check)
+    #[inline(always)]
+    pub fn check_otxn(&self) -> Result<()> {
+        let mut amount = [0; AMOUNT_LEN];
+        match otxn_field_into(&mut amount, FieldId::Amount) {
+            Ok(_) => self.check(&amount),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+// Reads the field into `buffer`, which can be longer than it
+#[inline(always)]
+fn otxn_field_into(buffer: &mut [u8], field_id: FieldId) -> Result<u64> {
+    unsafe {
+        crate::c::otxn_field(
+            host_ptr(buffer.as_mut_ptr()),
+            buffer.len() as u32,
+            field_id as u32,
+        )
+        .into()
+    }
+}
+
+// `drops` as a normalized XFL, with a mantissa of 16 digits, without loops or host calls
+#[inline(always)]
+const fn drops_to_xfl(drops: Drops) -> XFL {
+    const MIN_MANTISSA: u64 = 1_000_000_000_000_000;
+    const MAX_MANTISSA: u64 = 10 * MIN_MANTISSA - 1;
+
+    let mut mantissa = drops.0;
+    if mantissa == 0 {
+        return XFL(0);
+    }
+
+    let mut exponent: i64 = 0;
+    if mantissa > 100 * MAX_MANTISSA {
+        mantissa /= 100;
+        exponent += 2;
+    }
+    if mantissa > MAX_MANTISSA {
+        mantissa /= 10;
+        exponent += 1;
+    }
+    if mantissa > MAX_MANTISSA {
+        mantissa /= 10;
+        exponent += 1;
+    }
+    if mantissa < MIN_MANTISSA / 10_000_000 {
+        mantissa *= 100_000_000;
+        exponent -= 8;
+    }
+    if mantissa < MIN_MANTISSA / 1_000 {
+        mantissa *= 10_000;
+        exponent -= 4;
+    }
+    if mantissa < MIN_MANTISSA / 10 {
+        mantissa *= 100;
+        exponent -= 2;
+    }
+    if mantissa < MIN_MANTISSA {
+        mantissa *= 10;
+        exponent -= 1;
+    }
+
+    // positive, then the exponent offset by 97, then the mantissa
+    XFL((1 << 62) | ((exponent + 97) << 54) | mantissa as i64)
+}
+
+// A key ordering normalized XFLs like the numbers they stand for: positive XFLs grow
+// with their bits, and negative ones, which don't have the sign bit, shrink with them
+#[inline(always)]
+const fn xfl_order(xfl: XFL) -> i64 {
+    if xfl.0 & (1 << 62) != 0 {
+        xfl.0
+    } else {
+        -xfl.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::fixtures::ISSUER;
+    use crate::mock;
+
+    const USD: IssuedAsset = IssuedAsset::from_iso_code(b"USD", ISSUER);
+
+    fn xrp(drops: u64) -> Amount {
+        let mut amount = [0; AMOUNT_LEN];
+        amount[..8].copy_from_slice(&(drops | 0x4000_0000_0000_0000).to_be_bytes());
+        amount
+    }
+
+    fn issued(asset: &IssuedAsset, value: XFL) -> Amount {
+        let mut amount = [0; AMOUNT_LEN];
+        amount[..8].copy_from_slice(&(value.0 as u64 | 0x8000_0000_0000_0000).to_be_bytes());
+        amount[8..28].copy_from_slice(&asset.currency);
+        amount[28..].copy_from_slice(asset.issuer.as_bytes());
+        amount
+    }
+
+    #[wasm_bindgen_test]
+    fn normalizes_drops() {
+        // XFL::one()
+        assert_eq!(drops_to_xfl(Drops(1)).0, 6089866696204910592);
+        assert_eq!(drops_to_xfl(Drops::ZERO).0, 0);
+        for drops in [9, 10, 99_999_999, 100_000_000, 123_456_789_012] {
+            let xfl = drops_to_xfl(Drops(drops)).0;
+            let exponent = ((xfl >> 54) & 0xFF) - 97;
+            let mantissa = xfl & ((1 << 54) - 1);
+            assert_eq!(mantissa, drops as i64 * 10i64.pow(-exponent as u32));
+        }
+        for drops in [Drops::MAX.0, u64::MAX] {
+            let mantissa = drops_to_xfl(Drops(drops)).0 & ((1 << 54) - 1);
+            assert!((1_000_000_000_000_000..10_000_000_000_000_000).contains(&mantissa));
+        }
+        assert!(xfl_order(drops_to_xfl(Drops(99))) < xfl_order(drops_to_xfl(Drops(100))));
+        assert!(xfl_order(drops_to_xfl(Drops(1))) > xfl_order(XFL(0)));
+    }
+
+    #[wasm_bindgen_test]
+    fn checks_amounts_in_xrp() {
+        const POLICY: AmountPolicy = AmountPolicy::xrp(Some(Drops(1_000)), Some(Drops(50_000)));
+
+        assert!(POLICY.check(&xrp(1_000)).is_ok());
+        assert!(POLICY.check(&xrp(50_000)).is_ok());
+        assert!(matches!(POLICY.check(&xrp(999)), Err(HookError::TooSmall)));
+        assert!(matches!(POLICY.check(&xrp(50_001)), Err(HookError::TooBig)));
+        assert!(matches!(
+            POLICY.check(&issued(&USD, drops_to_xfl(Drops(2_000)))),
+            Err(HookError::DoesNotMatch)
+        ));
+        assert!(AmountPolicy::xrp(None, None).check(&xrp(0)).is_ok());
+    }
+
+    #[wasm_bindgen_test]
+    fn checks_issued_amounts() {
+        let ten = drops_to_xfl(Drops(10));
+        let policy = AmountPolicy::issued(USD, Some(ten), None);
+
+        assert!(policy.check(&issued(&USD, ten)).is_ok());
+        assert!(policy
+            .check(&issued(&USD, drops_to_xfl(Drops(1_000_000))))
+            .is_ok());
+        assert!(matches!(
+            policy.check(&issued(&USD, drops_to_xfl(Drops(9)))),
+            Err(HookError::TooSmall)
+        ));
+        assert!(matches!(
+            policy.check(&issued(&USD, XFL(0))),
+            Err(HookError::TooSmall)
+        ));
+
+        let other_issuer = IssuedAsset::from_iso_code(b"USD", AccountId([9; ACC_ID_LEN]));
+        assert!(matches!(
+            policy.check(&issued(&other_issuer, ten)),
+            Err(HookError::DoesNotMatch)
+        ));
+        assert!(matches!(
+            policy.check(&xrp(10)),
+            Err(HookError::DoesNotMatch)
+        ));
+    }
+
+    #[wasm_bindgen_test]
+    fn checks_the_originating_transaction_in_filters() {
+        const POLICY: AmountPolicy = AmountPolicy::xrp(Some(Drops(1_000)), None);
+
+        mock::reset();
+        mock::set_otxn_type(TxnType::Payment);
+        mock::set_otxn_field(FieldId::Amount, &xrp(2_000)[..8]);
+        assert!(POLICY.check_otxn().is_ok());
+        crate::filter! {
+            txn_type: Payment,
+            amount: POLICY,
+        }
+
+        mock::set_otxn_field(FieldId::Amount, &xrp(999)[..8]);
+        assert!(matches!(POLICY.check_otxn(), Err(HookError::TooSmall)));
+    }
+}
@@ -0,0 +1,14 @@
+//! Ready-made hook logic, configured with install-time parameters.
+//!
+//! Each module here is a whole hook built from the rest of the crate, which can be
+//! installed as is by calling it from the `hook` entry point, or taken apart for the
+//! checks it is made of. They read their configuration from hook parameters, so the
+//! same hook can be installed with different settings without recompiling it.
+//!
+//! Only available with the `prefab` feature.
+
+/// Forwarding a share of every incoming payment to another account
+pub mod carbon_copy;
+
+/// Screening of incoming transactions
+pub mod firewall;
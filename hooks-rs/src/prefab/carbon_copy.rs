@@ -0,0 +1,218 @@
+//! Forwarding a share of every incoming payment to another account.
+//!
+//! Like the carbon copy example hook of the Hooks documentation: every payment in XRP to
+//! the hook account is copied to the account in [ACCOUNT_PARAM], scaled down to the share
+//! in [SHARE_PARAM]. Both parameters are required:
+//!
+//! | Parameter       | Value                                    |
+//! |-----------------|------------------------------------------|
+//! | [ACCOUNT_PARAM] | account ID the copies are sent to        |
+//! | [SHARE_PARAM]   | basis points, 4 bytes big endian, 0 to [BASIS_POINTS] |
+//!
+//! Payments from the copy account itself aren't copied back, so two carbon copy hooks
+//! pointing at each other don't send payments back and forth. Partial payments aren't
+//! copied either, since their `Amount` isn't what they deliver.
+//!
+//! # Example
+//! ```
+//! #[hook]
+//! fn carbon_copy() -> Outcome<'static> {
+//!     prefab::carbon_copy::run()
+//! }
+//! ```
+
+use crate::api::*;
+use crate::flags::PaymentFlags;
+use crate::params::hook_param_as;
+use crate::transaction::{TransactionBuilder, XrpPaymentBuilder};
+
+/// Hook parameter with the account the copies are sent to
+pub const ACCOUNT_PARAM: &[u8] = b"CC_ACCOUNT";
+
+/// Hook parameter with the share of each payment that is copied, in basis points
+pub const SHARE_PARAM: &[u8] = b"CC_SHARE";
+
+/// Where incoming payments are copied to and how much of them
+#[derive(Clone, Copy)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct CarbonCopy {
+    /// Account the copies are sent to
+    pub account: AccountId,
+    /// Share of each payment that is copied, in basis points
+    pub share: u32,
+}
+
+/// A copy sent by [CarbonCopy::forward]
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct Forwarded {
+    /// Drops sent to the copy account
+    pub amount: Drops,
+    /// Hash of the emitted payment
+    pub txn_hash: [u8; HASH_LEN],
+}
+
+impl CarbonCopy {
+    /// Copy `share` basis points of every payment to `account`
+    #[inline(always)]
+    pub const fn new(account: AccountId, share: u32) -> Self {
+        Self { account, share }
+    }
+
+    /// Read the copy account and share from the hook parameters.
+    ///
+    /// Returns [HookError::DoesntExist] if one of them is not set, and
+    /// [HookError::InvalidArgument] if the share is more than [BASIS_POINTS].
+    #[inline(always)]
+    pub fn from_params() -> Result<Self> {
+        let account = hook_param_as::<AccountId>(ACCOUNT_PARAM)?;
+        let share = hook_param_as::<u32>(SHARE_PARAM)?;
+        if share > BASIS_POINTS {
+            return Err(HookError::InvalidArgument);
+        }
+
+        Ok(Self::new(account, share))
+    }
+
+    /// The drops to copy for the originating transaction, `None` if it is not a payment
+    /// in XRP to the hook account or its share rounds down to nothing
+    #[inline(always)]
+    pub fn amount_to_copy(&self) -> Result<Option<Drops>> {
+        if otxn_type() != TxnType::Payment as i64 {
+            return Ok(None);
+        }
+
+        let destination = AccountId(otxn_field::<ACC_ID_LEN>(FieldId::Destination)?);
+        if destination != hook_account()? {
+            return Ok(None);
+        }
+        let sender = AccountId(otxn_field::<ACC_ID_LEN>(FieldId::Account)?);
+        if sender == self.account {
+            return Ok(None);
+        }
+        if PaymentFlags::from_bits(otxn_flags()?).contains(PaymentFlags::PARTIAL_PAYMENT) {
+            return Ok(None);
+        }
+
+        let drops = match otxn_drops() {
+            Ok(drops) => Drops(drops),
+            // issued currencies
+            Err(HookError::InvalidArgument) => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        match drops.share(self.share) {
+            Some(Drops::ZERO) => Ok(None),
+            Some(copy) => Ok(Some(copy)),
+            None => Err(HookError::InvalidArgument),
+        }
+    }
+
+    /// Send the copy of the originating transaction through `reservation`, if it gets
+    /// one, see [amount_to_copy](Self::amount_to_copy)
+    #[inline(always)]
+    pub fn forward(&self, reservation: &mut EmitReservation) -> Result<Option<Forwarded>> {
+        let amount = match self.amount_to_copy()? {
+            Some(amount) => amount,
+            None => return Ok(None),
+        };
+
+        let txn_hash = XrpPaymentBuilder::new(amount, &self.account, None, None)
+            .build_and_emit(reservation)?;
+
+        Ok(Some(Forwarded { amount, txn_hash }))
+    }
+}
+
+/// The whole carbon copy hook: reads the parameters and copies the originating
+/// transaction if it is an incoming payment
+#[inline(always)]
+pub fn run() -> Outcome<'static> {
+    let carbon_copy = CarbonCopy::from_params()?;
+    let mut reservation = EmitReservation::new(1)?;
+
+    match carbon_copy.forward(&mut reservation)? {
+        Some(_) => Outcome::Accept {
+            code: 0,
+            msg: b"carbon copy: forwarded",
+        },
+        None => Outcome::Accept {
+            code: 0,
+            msg: b"carbon copy: nothing to forward",
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::decode::decode;
+    use crate::fixtures::{DESTINATION, HOOK_ACCOUNT, ISSUER};
+    use crate::mock;
+
+    const SENDER: AccountId = AccountId([4; ACC_ID_LEN]);
+
+    fn incoming_payment(drops: u64) {
+        mock::reset();
+        mock::set_hook_account(HOOK_ACCOUNT);
+        mock::set_hook_param(ACCOUNT_PARAM, DESTINATION.as_bytes());
+        mock::set_hook_param(SHARE_PARAM, &2500u32.to_be_bytes());
+        mock::set_otxn_type(TxnType::Payment);
+        mock::set_otxn_field(FieldId::Account, SENDER.as_bytes());
+        mock::set_otxn_field(FieldId::Destination, HOOK_ACCOUNT.as_bytes());
+        mock::set_otxn_field(
+            FieldId::Amount,
+            &(drops | 0x4000_0000_0000_0000).to_be_bytes(),
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn forwards_a_share_of_incoming_payments() {
+        incoming_payment(10_001);
+
+        let invocation = mock::invoke(run);
+        assert_eq!(invocation.msg, b"carbon copy: forwarded");
+        assert_eq!(invocation.emitted.len(), 1);
+        let copy = decode(&invocation.emitted[0].tx);
+        assert_eq!(copy.account(FieldId::Destination), Some(DESTINATION));
+        assert_eq!(copy.drops(FieldId::Amount), Some(2500));
+
+        // too small to copy anything
+        incoming_payment(3);
+        assert_eq!(mock::invoke(run).msg, b"carbon copy: nothing to forward");
+    }
+
+    #[wasm_bindgen_test]
+    fn leaves_other_transactions_alone() {
+        let copy = CarbonCopy::new(DESTINATION, 2500);
+
+        incoming_payment(10_000);
+        mock::set_otxn_field(FieldId::Account, HOOK_ACCOUNT.as_bytes());
+        mock::set_otxn_field(FieldId::Destination, ISSUER.as_bytes());
+        assert!(matches!(copy.amount_to_copy(), Ok(None)));
+
+        incoming_payment(10_000);
+        mock::set_otxn_field(FieldId::Account, DESTINATION.as_bytes());
+        assert!(matches!(copy.amount_to_copy(), Ok(None)));
+
+        incoming_payment(10_000);
+        mock::set_otxn_field(
+            FieldId::Flags,
+            &PaymentFlags::PARTIAL_PAYMENT.bits().to_be_bytes(),
+        );
+        assert!(matches!(copy.amount_to_copy(), Ok(None)));
+
+        incoming_payment(10_000);
+        mock::set_otxn_field(FieldId::Amount, &[0x80; 48]);
+        assert!(matches!(copy.amount_to_copy(), Ok(None)));
+
+        incoming_payment(10_000);
+        mock::set_hook_param(SHARE_PARAM, &10_001u32.to_be_bytes());
+        assert!(matches!(
+            CarbonCopy::from_params(),
+            Err(HookError::InvalidArgument)
+        ));
+        assert!(!mock::invoke(run).accepted);
+    }
+}
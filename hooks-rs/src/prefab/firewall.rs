@@ -0,0 +1,383 @@
+//! Screening of incoming transactions.
+//!
+//! The firewall rejects incoming transactions that don't meet the rules set in its hook
+//! parameters, and lets everything else through. Rules whose parameter isn't set don't
+//! apply:
+//!
+//! | Parameter           | Value                                     | Rejects                          |
+//! |---------------------|-------------------------------------------|----------------------------------|
+//! | [MIN_DROPS_PARAM]   | drops, 8 bytes big endian                 | payments of fewer drops          |
+//! | [MAX_DROPS_PARAM]   | drops, 8 bytes big endian                 | payments of more drops           |
+//! | [TXN_TYPES_PARAM]   | 32 byte mask, see [txn_type_mask]         | transactions of other types      |
+//! | [BLOCKED_PARAM]     | up to 12 account IDs, one after the other | transactions from these accounts |
+//! | [MEMO_PARAM]        | 1 byte, not 0                             | transactions without memos       |
+//!
+//! The amount limits only apply to amounts in XRP. Transactions sent by the hook account
+//! itself are never rejected, so that it can't lock itself out.
+//!
+//! # Example
+//! ```
+//! #[hook]
+//! fn firewall() -> Outcome<'static> {
+//!     prefab::firewall::run()
+//! }
+//! ```
+
+use core::mem::MaybeUninit;
+
+use crate::api::*;
+use crate::params::{hook_param_as, hook_param_into};
+
+/// Hook parameter with the minimum drops of incoming payments
+pub const MIN_DROPS_PARAM: &[u8] = b"MIN_DROPS";
+
+/// Hook parameter with the maximum drops of incoming payments
+pub const MAX_DROPS_PARAM: &[u8] = b"MAX_DROPS";
+
+/// Hook parameter with the mask of the transaction types let through
+pub const TXN_TYPES_PARAM: &[u8] = b"TXN_TYPES";
+
+/// Hook parameter with the accounts whose transactions are rejected
+pub const BLOCKED_PARAM: &[u8] = b"BLOCKED";
+
+/// Hook parameter requiring incoming transactions to have memos
+pub const MEMO_PARAM: &[u8] = b"MEMO_REQUIRED";
+
+/// Length of the mask of [TXN_TYPES_PARAM], one bit for each of the 256 transaction types
+pub const TXN_TYPE_MASK_LEN: usize = 32;
+
+/// Maximum number of accounts in [BLOCKED_PARAM], as many as fit in a parameter value
+pub const MAX_BLOCKED_ACCOUNTS: usize = crate::HOOK_PARAM_VALUE_MAX_LEN / ACC_ID_LEN;
+
+const BLOCKED_LEN: usize = MAX_BLOCKED_ACCOUNTS * ACC_ID_LEN;
+
+/// The value of [TXN_TYPES_PARAM] letting `txn_types` through: bit `t % 8` of byte
+/// `t / 8` is set for every type `t`. Meant for constants and for computing the parameter
+/// when installing the hook.
+pub const fn txn_type_mask(txn_types: &[TxnType]) -> [u8; TXN_TYPE_MASK_LEN] {
+    let mut mask = [0; TXN_TYPE_MASK_LEN];
+    let mut i = 0;
+    while i < txn_types.len() {
+        let txn_type = txn_types[i] as usize;
+        mask[txn_type / 8] |= 1 << (txn_type % 8);
+        i += 1;
+    }
+
+    mask
+}
+
+/// Why the firewall rejected a transaction
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub enum Rejection {
+    /// The type of the transaction isn't in [TXN_TYPES_PARAM]
+    TxnType,
+    /// The sender is in [BLOCKED_PARAM]
+    BlockedSender,
+    /// The transaction has no memos, but [MEMO_PARAM] requires them
+    MissingMemo,
+    /// The payment is below [MIN_DROPS_PARAM]
+    BelowMinimum,
+    /// The payment is above [MAX_DROPS_PARAM]
+    AboveMaximum,
+}
+
+impl Rejection {
+    /// Rollback code, from 1 in the order of the variants
+    #[inline(always)]
+    pub const fn code(self) -> i64 {
+        self as i64 + 1
+    }
+
+    /// Rollback message
+    #[inline(always)]
+    pub const fn msg(self) -> &'static [u8] {
+        match self {
+            Rejection::TxnType => b"firewall: transaction type not allowed",
+            Rejection::BlockedSender => b"firewall: sender is blocked",
+            Rejection::MissingMemo => b"firewall: memo required",
+            Rejection::BelowMinimum => b"firewall: amount below minimum",
+            Rejection::AboveMaximum => b"firewall: amount above maximum",
+        }
+    }
+}
+
+impl From<Rejection> for Outcome<'_> {
+    #[inline(always)]
+    fn from(rejection: Rejection) -> Self {
+        Outcome::Rollback {
+            code: rejection.code(),
+            msg: rejection.msg(),
+        }
+    }
+}
+
+/// The rules of the firewall, usually read from the hook parameters with
+/// [Firewall::from_params]
+#[derive(Clone, Copy)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct Firewall {
+    /// Minimum drops of incoming payments
+    pub min_drops: Option<Drops>,
+    /// Maximum drops of incoming payments
+    pub max_drops: Option<Drops>,
+    /// Transaction types let through, see [txn_type_mask]
+    pub txn_types: Option<[u8; TXN_TYPE_MASK_LEN]>,
+    /// Whether incoming transactions need memos
+    pub require_memo: bool,
+    // the account IDs of BLOCKED_PARAM, of which the first `blocked_len` bytes are set
+    blocked: [MaybeUninit<u8>; BLOCKED_LEN],
+    blocked_len: usize,
+}
+
+impl Firewall {
+    /// A firewall letting everything through
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            min_drops: None,
+            max_drops: None,
+            txn_types: None,
+            require_memo: false,
+            blocked: [MaybeUninit::uninit(); BLOCKED_LEN],
+            blocked_len: 0,
+        }
+    }
+
+    /// Read the rules from the hook parameters.
+    ///
+    /// Returns [HookError::InvalidArgument] if a parameter has the wrong length, and
+    /// [HookError::TooSmall] if [BLOCKED_PARAM] has more than [MAX_BLOCKED_ACCOUNTS].
+    #[inline(always)]
+    pub fn from_params() -> Result<Self> {
+        let mut firewall = Self::new();
+        firewall.min_drops = optional_param(MIN_DROPS_PARAM)?;
+        firewall.max_drops = optional_param(MAX_DROPS_PARAM)?;
+        firewall.txn_types = optional_param(TXN_TYPES_PARAM)?;
+        firewall.require_memo = match optional_param::<u8>(MEMO_PARAM)? {
+            Some(required) => required != 0,
+            None => false,
+        };
+
+        firewall.blocked_len = match hook_param_into(BLOCKED_PARAM, &mut firewall.blocked) {
+            Ok(blocked) if blocked.len() % ACC_ID_LEN == 0 => blocked.len(),
+            Ok(_) => return Err(HookError::InvalidArgument),
+            Err(HookError::DoesntExist) => 0,
+            Err(err) => return Err(err),
+        };
+
+        Ok(firewall)
+    }
+
+    /// Whether `account` is in [BLOCKED_PARAM]
+    #[inline(always)]
+    pub fn is_blocked(&self, account: &AccountId) -> bool {
+        // a single guarded loop over all the blocked bytes, comparing them with the
+        // account one account ID at a time
+        let mut matching = true;
+        let mut i = 0;
+        while {
+            max_iter(BLOCKED_LEN as u32 + 1);
+            i < self.blocked_len
+        } {
+            // the first `blocked_len` bytes were written by the host
+            let byte = unsafe { self.blocked[i].assume_init() };
+            matching &= byte == account.0[i % ACC_ID_LEN];
+            if i % ACC_ID_LEN == ACC_ID_LEN - 1 {
+                if matching {
+                    return true;
+                }
+                matching = true;
+            }
+            i += 1;
+        }
+
+        false
+    }
+
+    /// Whether transactions of type `txn_type` are let through
+    #[inline(always)]
+    pub fn allows_txn_type(&self, txn_type: i64) -> bool {
+        match self.txn_types {
+            Some(mask) => {
+                (0..256).contains(&txn_type)
+                    && mask[txn_type as usize / 8] & (1 << (txn_type % 8)) != 0
+            }
+            None => true,
+        }
+    }
+
+    /// Check the originating transaction against the rules, returning why it is rejected
+    /// if it is
+    #[inline(always)]
+    pub fn screen(&self) -> Result<Option<Rejection>> {
+        let sender = AccountId(otxn_field::<ACC_ID_LEN>(FieldId::Account)?);
+        if sender == hook_account()? {
+            return Ok(None);
+        }
+
+        if !self.allows_txn_type(otxn_type()) {
+            return Ok(Some(Rejection::TxnType));
+        }
+        if self.is_blocked(&sender) {
+            return Ok(Some(Rejection::BlockedSender));
+        }
+        if self.require_memo && !has_memos()? {
+            return Ok(Some(Rejection::MissingMemo));
+        }
+
+        if self.min_drops.is_none() && self.max_drops.is_none() {
+            return Ok(None);
+        }
+        let drops = match otxn_drops() {
+            Ok(drops) => Drops(drops),
+            // issued amounts and transactions without an amount
+            Err(HookError::InvalidArgument) | Err(HookError::DoesntExist) => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        match (self.min_drops, self.max_drops) {
+            (Some(min), _) if drops < min => Ok(Some(Rejection::BelowMinimum)),
+            (_, Some(max)) if drops > max => Ok(Some(Rejection::AboveMaximum)),
+            _ => Ok(None),
+        }
+    }
+}
+
+impl Default for Firewall {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The whole firewall hook: reads the rules from the hook parameters and accepts or
+/// rejects the originating transaction
+#[inline(always)]
+pub fn run() -> Outcome<'static> {
+    match Firewall::from_params()?.screen()? {
+        Some(rejection) => rejection.into(),
+        None => Outcome::Accept {
+            code: 0,
+            msg: b"firewall: passed",
+        },
+    }
+}
+
+// The hook parameter `name` decoded as `T`, `None` if it is not set
+#[inline(always)]
+fn optional_param<T: crate::FromHookParam>(name: &[u8]) -> Result<Option<T>> {
+    match hook_param_as::<T>(name) {
+        Ok(value) => Ok(Some(value)),
+        Err(HookError::DoesntExist) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+// Whether the originating transaction has memos, without reading them
+#[inline(always)]
+fn has_memos() -> Result<bool> {
+    match otxn_field::<1>(FieldId::Memos) {
+        // the memos don't fit in a byte, but they are there
+        Ok(_) | Err(HookError::TooSmall) => Ok(true),
+        Err(HookError::DoesntExist) => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::fixtures::{DESTINATION, HOOK_ACCOUNT, ISSUER};
+    use crate::mock;
+
+    fn payment_from(sender: AccountId, drops: u64) {
+        mock::reset();
+        mock::set_hook_account(HOOK_ACCOUNT);
+        mock::set_otxn_type(TxnType::Payment);
+        mock::set_otxn_field(FieldId::Account, sender.as_bytes());
+        mock::set_otxn_field(
+            FieldId::Amount,
+            &(drops | 0x4000_0000_0000_0000).to_be_bytes(),
+        );
+    }
+
+    fn screen() -> Option<Rejection> {
+        Firewall::from_params()
+            .unwrap_line_number()
+            .screen()
+            .unwrap_line_number()
+    }
+
+    #[wasm_bindgen_test]
+    fn lets_everything_through_without_rules() {
+        payment_from(DESTINATION, 1);
+        assert_eq!(screen(), None);
+        assert!(matches!(run(), Outcome::Accept { code: 0, .. }));
+    }
+
+    #[wasm_bindgen_test]
+    fn limits_amounts_in_xrp() {
+        payment_from(DESTINATION, 999);
+        mock::set_hook_param(MIN_DROPS_PARAM, &1000u64.to_be_bytes());
+        mock::set_hook_param(MAX_DROPS_PARAM, &5000u64.to_be_bytes());
+        assert_eq!(screen(), Some(Rejection::BelowMinimum));
+        assert!(matches!(run(), Outcome::Rollback { code: 4, .. }));
+
+        mock::set_otxn_field(
+            FieldId::Amount,
+            &(5001u64 | 0x4000_0000_0000_0000).to_be_bytes(),
+        );
+        assert_eq!(screen(), Some(Rejection::AboveMaximum));
+        mock::set_otxn_field(
+            FieldId::Amount,
+            &(5000u64 | 0x4000_0000_0000_0000).to_be_bytes(),
+        );
+        assert_eq!(screen(), None);
+
+        // issued amounts
+        mock::set_otxn_field(FieldId::Amount, &[0x80; 48]);
+        assert_eq!(screen(), None);
+
+        // the hook account's own payments
+        mock::set_otxn_field(FieldId::Account, HOOK_ACCOUNT.as_bytes());
+        mock::set_otxn_field(
+            FieldId::Amount,
+            &(1u64 | 0x4000_0000_0000_0000).to_be_bytes(),
+        );
+        assert_eq!(screen(), None);
+    }
+
+    #[wasm_bindgen_test]
+    fn screens_txn_types_senders_and_memos() {
+        payment_from(DESTINATION, 1000);
+        mock::set_hook_param(
+            TXN_TYPES_PARAM,
+            &txn_type_mask(&[TxnType::Payment, TxnType::Invoke]),
+        );
+        assert_eq!(screen(), None);
+        mock::set_otxn_type(TxnType::TrustSet);
+        assert_eq!(screen(), Some(Rejection::TxnType));
+
+        payment_from(DESTINATION, 1000);
+        let mut blocked = [0; 3 * ACC_ID_LEN];
+        blocked[..ACC_ID_LEN].copy_from_slice(ISSUER.as_bytes());
+        blocked[2 * ACC_ID_LEN..].copy_from_slice(DESTINATION.as_bytes());
+        mock::set_hook_param(BLOCKED_PARAM, &blocked);
+        assert_eq!(screen(), Some(Rejection::BlockedSender));
+        mock::set_otxn_field(FieldId::Account, &[9; ACC_ID_LEN]);
+        assert_eq!(screen(), None);
+        mock::set_hook_param(BLOCKED_PARAM, &[9; ACC_ID_LEN + 1]);
+        assert!(matches!(
+            Firewall::from_params(),
+            Err(HookError::InvalidArgument)
+        ));
+
+        payment_from(DESTINATION, 1000);
+        mock::set_hook_param(MEMO_PARAM, &[1]);
+        assert_eq!(screen(), Some(Rejection::MissingMemo));
+        mock::set_otxn_field(FieldId::Memos, &[0xEA, 0x7D, 0x01, 0x61, 0xE1, 0xF1]);
+        assert_eq!(screen(), None);
+    }
+}
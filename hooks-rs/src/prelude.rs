@@ -0,0 +1,35 @@
+//! Everything needed to write a hook, in one import.
+//!
+//! This includes the hook entry macros, [Result] and [HookError], the host API for
+//! the originating transaction, state, XFL floats and tracing, the transaction builders
+//! and all macros of the crate.
+//!
+//! # Example
+//! ```
+//! use hooks_rs::prelude::*;
+//!
+//! #[callback]
+//! fn on_callback() -> Outcome<'static> {
+//!     Outcome::Accept { code: 0, msg: b"" }
+//! }
+//!
+//! #[hook]
+//! fn handle() -> Outcome<'static> {
+//!     let _ = trace!("triggered by {}", otxn_field::<ACC_ID_LEN>(FieldId::Account)?);
+//!
+//!     Outcome::Accept { code: 0, msg: b"ok" }
+//! }
+//! ```
+
+pub use crate::{
+    access::*, api::*, asset::*, callback::*, deferred::*, event::*, fields::*, flags::*, host::*,
+    multisig::*, oracle::*, params::*, policy::*, rate_limit::*, reader::*, replay::*, split::*,
+    table::*, transaction::*, utils::*, vesting::*,
+};
+
+pub use crate::{callback, hook, HookParams, Record};
+
+pub use crate::{
+    accid, currency, event, filter, guarded_for, hex, hook_assert, hook_require, hookon, match_txn,
+    prepare_payment_simple, prepare_payment_simple_trustline, register_hook, trace,
+};
@@ -0,0 +1,265 @@
+//! Token bucket rate limiting of accounts, kept in hook state.
+//!
+//! A [RateLimiter] gives every account a bucket of up to `capacity` tokens, refilled with
+//! one token every `interval` seconds of [ledger_time] or every `interval` ledgers of
+//! [ledger_seq]. Each transaction that is let through takes a token, so an account can
+//! send bursts of up to `capacity` transactions and then one every `interval`.
+//!
+//! The bucket of an account takes one state entry of 8 bytes: the tokens left and when
+//! they were last refilled. Accounts that never sent anything have a full bucket and no
+//! state.
+//!
+//! # Example
+//! ```
+//! // at most 3 claims in a row, then one an hour
+//! const FAUCET: RateLimiter = RateLimiter::per_seconds(b"faucet", 3, 3600);
+//!
+//! let sender = AccountId(otxn_field::<ACC_ID_LEN>(FieldId::Account)?);
+//! if let RateLimit::Limited { retry_after } = FAUCET.check(&sender)? {
+//!     rollback(b"too many claims, try again later", retry_after.into());
+//! }
+//! ```
+
+use crate::access::{padded_name, state_key};
+use crate::api::*;
+
+/// Maximum length of the name of a [RateLimiter], which is laid out in state keys like
+/// the name of an [AccessList](crate::AccessList)
+pub const RATE_LIMITER_NAME_MAX_LEN: usize = crate::ACCESS_LIST_NAME_MAX_LEN;
+
+/// Limits how often each account can do something, such as claiming from a faucet.
+///
+/// The name of the limiter tells its buckets apart from the rest of the hook state, so
+/// it must differ from the names of the other limiters and access lists of the hook.
+#[derive(Clone, Copy)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct RateLimiter {
+    name: [u8; RATE_LIMITER_NAME_MAX_LEN],
+    capacity: u32,
+    interval: u32,
+    clock: Clock,
+}
+
+/// What [RateLimiter::check] decided
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub enum RateLimit {
+    /// Let through, with `remaining` tokens left
+    Allowed {
+        /// Tokens left after this one
+        remaining: u32,
+    },
+    /// Over the limit, until the next token in `retry_after` seconds or ledgers
+    Limited {
+        /// Seconds or ledgers, depending on the limiter, until the next token
+        retry_after: u32,
+    },
+}
+
+// What the tokens of a limiter are refilled with
+#[derive(Clone, Copy)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+enum Clock {
+    LedgerTime,
+    LedgerSeq,
+}
+
+impl RateLimiter {
+    /// A limiter refilling one token every `interval` seconds of ledger close time.
+    ///
+    /// As a constant, the limiter fails to compile with a name longer than
+    /// [RATE_LIMITER_NAME_MAX_LEN], or with a `capacity` or `interval` of 0, which would
+    /// let nothing through or divide by zero when refilling.
+    #[inline(always)]
+    pub const fn per_seconds(name: &[u8], capacity: u32, interval: u32) -> Self {
+        Self::new(name, capacity, interval, Clock::LedgerTime)
+    }
+
+    /// A limiter refilling one token every `interval` ledgers
+    #[inline(always)]
+    pub const fn per_ledgers(name: &[u8], capacity: u32, interval: u32) -> Self {
+        Self::new(name, capacity, interval, Clock::LedgerSeq)
+    }
+
+    #[inline(always)]
+    const fn new(name: &[u8], capacity: u32, interval: u32, clock: Clock) -> Self {
+        assert!(capacity > 0, "rate limiter capacity must not be 0");
+        assert!(interval > 0, "rate limiter interval must not be 0");
+
+        Self {
+            name: padded_name(name),
+            capacity,
+            interval,
+            clock,
+        }
+    }
+
+    /// The state key of the bucket of `account`
+    #[inline(always)]
+    pub fn key(&self, account: &AccountId) -> [u8; STATE_KEY_LEN] {
+        state_key(&self.name, account.0)
+    }
+
+    /// Take a token from the bucket of `account` if it has one left.
+    ///
+    /// Fails with [HookError::DoesNotMatch] if the bucket in state isn't 8 bytes long. Only
+    /// writes the state when the token is taken, so a limited transaction costs a
+    /// single [state] read. The token is only spent if the hook accepts the transaction,
+    /// since a rollback discards the state change.
+    #[inline(always)]
+    pub fn check(&self, account: &AccountId) -> Result<RateLimit> {
+        let key = self.key(account);
+        let now = self.now();
+        let (tokens, refilled_at) = self.refill(state_exact::<8>(&key), now)?;
+
+        if tokens == 0 {
+            let elapsed = now.saturating_sub(refilled_at);
+            return Ok(RateLimit::Limited {
+                retry_after: self.interval - elapsed,
+            });
+        }
+
+        let remaining = tokens - 1;
+        let [t0, t1, t2, t3] = remaining.to_be_bytes();
+        let [r0, r1, r2, r3] = refilled_at.to_be_bytes();
+        let bucket = [t0, t1, t2, t3, r0, r1, r2, r3];
+        match state_set(&bucket, &key) {
+            Ok(_) => Ok(RateLimit::Allowed { remaining }),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// The tokens left in the bucket of `account`, without taking one
+    #[inline(always)]
+    pub fn remaining(&self, account: &AccountId) -> Result<u32> {
+        match self.refill(state_exact::<8>(&self.key(account)), self.now()) {
+            Ok((tokens, _)) => Ok(tokens),
+            Err(err) => Err(err),
+        }
+    }
+
+    #[inline(always)]
+    fn now(&self) -> u32 {
+        match self.clock {
+            Clock::LedgerTime => ledger_time().0,
+            Clock::LedgerSeq => ledger_seq().0,
+        }
+    }
+
+    // The tokens in a bucket read from state once refilled up to `now`, and when the
+    // last of them was refilled. Time left over from the last interval is kept, so that
+    // a bucket checked often still refills at the same pace.
+    #[inline(always)]
+    fn refill(&self, bucket: Result<[u8; 8]>, now: u32) -> Result<(u32, u32)> {
+        let bucket = match bucket {
+            Ok(bucket) => bucket,
+            Err(HookError::DoesntExist) => return Ok((self.capacity, now)),
+            Err(err) => return Err(err),
+        };
+        let tokens = u32::from_be_bytes([bucket[0], bucket[1], bucket[2], bucket[3]]);
+        let refilled_at = u32::from_be_bytes([bucket[4], bucket[5], bucket[6], bucket[7]]);
+
+        let intervals = now.saturating_sub(refilled_at) / self.interval;
+        let tokens = tokens.saturating_add(intervals);
+        if tokens >= self.capacity {
+            // a full bucket doesn't keep filling
+            return Ok((self.capacity, now));
+        }
+
+        Ok((tokens, refilled_at + intervals * self.interval))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::fixtures::{DESTINATION, ISSUER};
+    use crate::mock;
+
+    const CLAIMS: RateLimiter = RateLimiter::per_seconds(b"claims", 2, 60);
+
+    #[wasm_bindgen_test]
+    fn lets_bursts_through_then_refills() {
+        mock::reset();
+        mock::set_ledger_last_time(1000);
+
+        assert!(matches!(CLAIMS.remaining(&DESTINATION), Ok(2)));
+        assert!(matches!(
+            CLAIMS.check(&DESTINATION),
+            Ok(RateLimit::Allowed { remaining: 1 })
+        ));
+        assert!(matches!(
+            CLAIMS.check(&DESTINATION),
+            Ok(RateLimit::Allowed { remaining: 0 })
+        ));
+        assert!(matches!(
+            CLAIMS.check(&DESTINATION),
+            Ok(RateLimit::Limited { retry_after: 60 })
+        ));
+        // other accounts have their own bucket
+        assert!(matches!(
+            CLAIMS.check(&ISSUER),
+            Ok(RateLimit::Allowed { remaining: 1 })
+        ));
+
+        mock::set_ledger_last_time(1050);
+        assert!(matches!(
+            CLAIMS.check(&DESTINATION),
+            Ok(RateLimit::Limited { retry_after: 10 })
+        ));
+        // a token every 60 seconds, keeping the 30 seconds left over
+        mock::set_ledger_last_time(1090);
+        assert!(matches!(
+            CLAIMS.check(&DESTINATION),
+            Ok(RateLimit::Allowed { remaining: 0 })
+        ));
+        mock::set_ledger_last_time(1120);
+        assert!(matches!(
+            CLAIMS.check(&DESTINATION),
+            Ok(RateLimit::Allowed { remaining: 0 })
+        ));
+
+        // and never more than the capacity
+        mock::set_ledger_last_time(100_000);
+        assert!(matches!(CLAIMS.remaining(&DESTINATION), Ok(2)));
+    }
+
+    #[wasm_bindgen_test]
+    fn refills_per_ledger() {
+        const VOTES: RateLimiter = RateLimiter::per_ledgers(b"votes", 1, 10);
+
+        mock::reset();
+        mock::set_ledger_seq(500);
+        assert!(matches!(
+            VOTES.check(&DESTINATION),
+            Ok(RateLimit::Allowed { remaining: 0 })
+        ));
+        mock::set_ledger_seq(509);
+        assert!(matches!(
+            VOTES.check(&DESTINATION),
+            Ok(RateLimit::Limited { retry_after: 1 })
+        ));
+        mock::set_ledger_seq(510);
+        assert!(matches!(
+            VOTES.check(&DESTINATION),
+            Ok(RateLimit::Allowed { remaining: 0 })
+        ));
+        assert_ne!(VOTES.key(&DESTINATION), CLAIMS.key(&DESTINATION));
+    }
+
+    #[wasm_bindgen_test]
+    fn rejects_buckets_of_the_wrong_length() {
+        mock::reset();
+        mock::set_state(&CLAIMS.key(&DESTINATION), &[0; 4]);
+        assert!(matches!(
+            CLAIMS.check(&DESTINATION),
+            Err(HookError::DoesNotMatch)
+        ));
+        assert!(matches!(
+            CLAIMS.remaining(&DESTINATION),
+            Err(HookError::DoesNotMatch)
+        ));
+    }
+}
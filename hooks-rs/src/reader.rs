@@ -0,0 +1,289 @@
+//! Bounds-checked reading of serialized bytes, such as objects, blobs and memos.
+//!
+//! A [SliceReader] is a cursor over a byte slice that reads integers, hashes, accounts
+//! and variable length fields one after the other, failing with
+//! [HookError::OutOfBounds] instead of panicking when the slice ends too early. Parsers
+//! keep the position in the reader rather than doing index arithmetic of their own.
+//!
+//! Integers are big endian, as in serialized objects, and variable length fields have
+//! the 1 to 3 byte length prefix of blobs and accounts.
+//!
+//! # Example
+//! ```
+//! let mut reader = SliceReader::new(memo_data);
+//! let version = reader.read_u8()?;
+//! let recipient = reader.read_accid()?;
+//! let drops = Drops(reader.read_u64_be()?);
+//! let note = reader.read_vl()?;
+//! ```
+
+use crate::api::*;
+
+/// Largest length a variable length prefix can encode
+pub const VL_MAX_LEN: usize = 918_744;
+
+/// A cursor reading serialized bytes from the start of a slice
+#[derive(Clone, Copy)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct SliceReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> SliceReader<'a> {
+    /// A reader at the start of `bytes`
+    #[inline(always)]
+    pub const fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    /// Number of bytes read so far
+    #[inline(always)]
+    pub const fn pos(&self) -> usize {
+        self.pos
+    }
+
+    /// Number of bytes left to read
+    #[inline(always)]
+    pub const fn remaining(&self) -> usize {
+        self.bytes.len() - self.pos
+    }
+
+    /// Whether everything was read
+    #[inline(always)]
+    pub const fn is_empty(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    /// The bytes left to read, without reading them
+    #[inline(always)]
+    pub fn rest(&self) -> &'a [u8] {
+        &self.bytes[self.pos..]
+    }
+
+    /// The next byte, without reading it
+    #[inline(always)]
+    pub fn peek_u8(&self) -> Result<u8> {
+        match self.bytes.get(self.pos) {
+            Some(&byte) => Ok(byte),
+            None => Err(HookError::OutOfBounds),
+        }
+    }
+
+    /// Read the next `len` bytes
+    #[inline(always)]
+    pub fn read_bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        if len > self.remaining() {
+            return Err(HookError::OutOfBounds);
+        }
+
+        let bytes = &self.bytes[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    /// Skip the next `len` bytes
+    #[inline(always)]
+    pub fn skip(&mut self, len: usize) -> Result<()> {
+        match self.read_bytes(len) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Read the next `N` bytes into an array
+    #[inline(always)]
+    pub fn read_array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let bytes = self.read_bytes(N)?;
+
+        let mut array = [0; N];
+        // guarded loop rather than a slice copy, which could become `memcpy`
+        let mut i = 0;
+        while {
+            max_iter(N as u32 + 1);
+            i < N
+        } {
+            array[i] = bytes[i];
+            i += 1;
+        }
+
+        Ok(array)
+    }
+
+    /// Read a byte
+    #[inline(always)]
+    pub fn read_u8(&mut self) -> Result<u8> {
+        let byte = self.peek_u8()?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    /// Read a big endian [u16]
+    #[inline(always)]
+    pub fn read_u16_be(&mut self) -> Result<u16> {
+        let bytes = self.read_bytes(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    /// Read a big endian [u32]
+    #[inline(always)]
+    pub fn read_u32_be(&mut self) -> Result<u32> {
+        let bytes = self.read_bytes(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    /// Read a big endian [u64]
+    #[inline(always)]
+    pub fn read_u64_be(&mut self) -> Result<u64> {
+        let bytes = self.read_bytes(8)?;
+        Ok(u64::from_be_bytes([
+            bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7],
+        ]))
+    }
+
+    /// Read a 32 byte hash, such as a transaction ID
+    #[inline(always)]
+    pub fn read_hash256(&mut self) -> Result<Hash> {
+        self.read_array()
+    }
+
+    /// Read the 20 bytes of an account ID, without a length prefix. Account fields of
+    /// serialized objects are variable length, see [read_vl](Self::read_vl).
+    #[inline(always)]
+    pub fn read_accid(&mut self) -> Result<AccountId> {
+        Ok(AccountId(self.read_array()?))
+    }
+
+    /// Read a variable length prefix, 1 to 3 bytes, and return the length it encodes.
+    ///
+    /// Fails with [HookError::ParseError] if the bytes aren't a valid prefix, in which
+    /// case nothing is read.
+    #[inline(always)]
+    pub fn read_vl_len(&mut self) -> Result<usize> {
+        let first = self.peek_u8()? as usize;
+        let len = match first {
+            0..=192 => {
+                self.pos += 1;
+                return Ok(first);
+            }
+            193..=240 => {
+                let bytes = self.read_bytes(2)?;
+                193 + (first - 193) * 256 + bytes[1] as usize
+            }
+            241..=254 => {
+                let bytes = self.read_bytes(3)?;
+                let len =
+                    12481 + (first - 241) * 65536 + bytes[1] as usize * 256 + bytes[2] as usize;
+                if len > VL_MAX_LEN {
+                    self.pos -= 3;
+                    return Err(HookError::ParseError);
+                }
+                len
+            }
+            _ => return Err(HookError::ParseError),
+        };
+
+        Ok(len)
+    }
+
+    /// Read a variable length field: its length prefix, then as many bytes.
+    ///
+    /// Nothing is read if it fails.
+    #[inline(always)]
+    pub fn read_vl(&mut self) -> Result<&'a [u8]> {
+        let start = self.pos;
+        let len = self.read_vl_len()?;
+        match self.read_bytes(len) {
+            Ok(bytes) => Ok(bytes),
+            Err(err) => {
+                self.pos = start;
+                Err(err)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::fixtures::DESTINATION;
+
+    #[wasm_bindgen_test]
+    fn reads_fields_one_after_the_other() {
+        let mut bytes = [0; 1 + 2 + 4 + 8 + HASH_LEN + ACC_ID_LEN];
+        bytes[0] = 7;
+        bytes[1..3].copy_from_slice(&0x0102u16.to_be_bytes());
+        bytes[3..7].copy_from_slice(&0x0304_0506u32.to_be_bytes());
+        bytes[7..15].copy_from_slice(&1_000u64.to_be_bytes());
+        bytes[15..47].copy_from_slice(&[9; HASH_LEN]);
+        bytes[47..].copy_from_slice(DESTINATION.as_bytes());
+
+        let mut reader = SliceReader::new(&bytes);
+        assert!(matches!(reader.peek_u8(), Ok(7)));
+        assert!(matches!(reader.read_u8(), Ok(7)));
+        assert!(matches!(reader.read_u16_be(), Ok(0x0102)));
+        assert!(matches!(reader.read_u32_be(), Ok(0x0304_0506)));
+        assert!(matches!(reader.read_u64_be(), Ok(1_000)));
+        assert!(matches!(reader.read_hash256(), Ok(hash) if hash == [9; HASH_LEN]));
+        assert_eq!(reader.remaining(), ACC_ID_LEN);
+        assert!(matches!(reader.read_accid(), Ok(account) if account == DESTINATION));
+        assert!(reader.is_empty());
+        assert_eq!(reader.pos(), bytes.len());
+    }
+
+    #[wasm_bindgen_test]
+    fn reads_variable_length_fields() {
+        let mut bytes = [0; 3 + 2 + 193];
+        bytes[0] = 2;
+        bytes[1..3].copy_from_slice(b"hi");
+        bytes[3..5].copy_from_slice(&[193, 0]);
+        assert!(matches!(
+            SliceReader::new(&bytes[3..]).read_vl_len(),
+            Ok(193)
+        ));
+
+        let mut reader = SliceReader::new(&bytes);
+        assert!(matches!(reader.read_vl(), Ok(b"hi")));
+        assert!(matches!(reader.read_vl(), Ok(value) if value.len() == 193));
+        assert!(matches!(
+            SliceReader::new(&[241, 0, 0]).read_vl_len(),
+            Ok(12481)
+        ));
+        assert!(matches!(
+            SliceReader::new(&[240, 255]).read_vl_len(),
+            Ok(12480)
+        ));
+        assert!(matches!(
+            SliceReader::new(&[254, 212, 23]).read_vl_len(),
+            Ok(VL_MAX_LEN)
+        ));
+    }
+
+    #[wasm_bindgen_test]
+    fn fails_without_reading_past_the_end() {
+        let mut reader = SliceReader::new(&[1, 2, 3]);
+        assert!(matches!(reader.read_u32_be(), Err(HookError::OutOfBounds)));
+        assert_eq!(reader.pos(), 0);
+        assert!(matches!(reader.read_u16_be(), Ok(0x0102)));
+        assert!(matches!(reader.skip(2), Err(HookError::OutOfBounds)));
+        assert_eq!(reader.rest(), [3]);
+
+        let mut truncated = SliceReader::new(&[5, b'a', b'b']);
+        assert!(matches!(truncated.read_vl(), Err(HookError::OutOfBounds)));
+        assert_eq!(truncated.pos(), 0);
+        assert!(matches!(
+            SliceReader::new(&[255]).read_vl_len(),
+            Err(HookError::ParseError)
+        ));
+        assert!(matches!(
+            SliceReader::new(&[254, 212, 24]).read_vl_len(),
+            Err(HookError::ParseError)
+        ));
+        assert!(matches!(
+            SliceReader::new(&[200]).read_vl_len(),
+            Err(HookError::OutOfBounds)
+        ));
+    }
+}
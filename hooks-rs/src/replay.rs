@@ -0,0 +1,262 @@
+//! Protection against replayed instructions, by the nonces they used kept in hook state.
+//!
+//! Hooks accepting instructions signed off the ledger, such as vouchers or oracle updates
+//! relayed by anyone, have to make sure each instruction is only carried out once. A
+//! [NonceStore] records the nonces of the instructions it let through, usually a hash of
+//! the instruction or the ID of the originating transaction, and [NonceStore::consume]
+//! rejects a nonce it saw before.
+//!
+//! The store keeps at most `capacity` nonces, in a ring of state entries that remembers
+//! in which order they were consumed. Once it is full, consuming a nonce prunes the
+//! oldest one, but only if it was consumed at least `retention` ledgers ago, so every
+//! nonce is remembered for at least that long. Instructions must therefore expire within
+//! `retention` ledgers, for example with a `LastLedgerSequence` of their own checked by
+//! the hook, or a pruned nonce could be replayed.
+//!
+//! Each nonce takes two state entries of the hook account's reserve, and the store one
+//! more for the position in the ring, so a store takes up to `2 * capacity + 1` entries.
+//!
+//! # Example
+//! ```
+//! // instructions are valid for at most 100 ledgers
+//! const VOUCHERS: NonceStore = NonceStore::new(b"vouchers", 64, 100);
+//!
+//! let voucher_hash = util_sha512h(voucher)?;
+//! match VOUCHERS.consume(&voucher_hash) {
+//!     Ok(()) => redeem(voucher)?,
+//!     Err(HookError::AlreadySet) => rollback(b"voucher already redeemed", 1),
+//!     Err(err) => return Err(err.into()),
+//! }
+//! ```
+
+use crate::access::{padded_name, state_key, STATE_KEY_SUFFIX_LEN};
+use crate::api::*;
+
+/// Maximum length of the name of a [NonceStore], which is laid out in state keys like
+/// the name of an [AccessList](crate::AccessList)
+pub const NONCE_STORE_NAME_MAX_LEN: usize = crate::ACCESS_LIST_NAME_MAX_LEN;
+
+// The bytes of a nonce kept in its state key, plenty to tell hashes apart
+const NONCE_KEY_LEN: usize = STATE_KEY_LEN - NONCE_STORE_NAME_MAX_LEN - 1;
+
+// What a state key of a store is for, after its name
+const NONCE_KIND: u8 = 0;
+const SLOT_KIND: u8 = 1;
+const HEAD_KIND: u8 = 2;
+
+/// Nonces consumed by a hook, remembered for a bounded number of ledgers
+#[derive(Clone, Copy)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct NonceStore {
+    name: [u8; NONCE_STORE_NAME_MAX_LEN],
+    capacity: u32,
+    retention: u32,
+}
+
+// What follows the name in a state key of a store: what the entry is for, and the first
+// bytes of the nonce or the index of the slot in the ring
+#[repr(C)]
+struct NonceKeySuffix {
+    kind: u8,
+    rest: [u8; NONCE_KEY_LEN],
+}
+
+// A slot of the ring: when the nonce was consumed, and the bytes of it in its state key
+#[repr(C)]
+struct Slot {
+    consumed_at: [u8; 4],
+    nonce: [u8; NONCE_KEY_LEN],
+}
+
+const SLOT_LEN: usize = 4 + NONCE_KEY_LEN;
+
+impl NonceStore {
+    /// A store named `name`, keeping up to `capacity` nonces for at least `retention`
+    /// ledgers each.
+    ///
+    /// Stores are constants, which fail to compile if `name` is longer than
+    /// [NONCE_STORE_NAME_MAX_LEN] or `capacity` is 0 and would leave no slot in the ring.
+    #[inline(always)]
+    pub const fn new(name: &[u8], capacity: u32, retention: u32) -> Self {
+        assert!(capacity > 0, "nonce store capacity must not be 0");
+
+        Self {
+            name: padded_name(name),
+            capacity,
+            retention,
+        }
+    }
+
+    /// Record `nonce` as consumed, failing with [HookError::AlreadySet] if it was before.
+    ///
+    /// Fails with [HookError::TooManyNonces] if the store is full of nonces consumed in
+    /// the last `retention` ledgers. Like any state change, the nonce is only consumed if
+    /// the hook accepts the transaction.
+    #[inline(always)]
+    pub fn consume(&self, nonce: &[u8; HASH_LEN]) -> Result<()> {
+        let nonce = nonce_prefix(nonce);
+        let nonce_key = self.key(NONCE_KIND, nonce);
+        match state::<4>(&nonce_key) {
+            Ok(_) => return Err(HookError::AlreadySet),
+            Err(HookError::DoesntExist) => {}
+            Err(err) => return Err(err),
+        }
+
+        let head_key = self.key(HEAD_KIND, [0; NONCE_KEY_LEN]);
+        let head = match state::<4>(&head_key) {
+            Ok(head) => u32::from_be_bytes(head),
+            Err(HookError::DoesntExist) => 0,
+            Err(err) => return Err(err),
+        };
+        let slot_key = self.slot_key(head);
+        let now = ledger_seq().0;
+        match state::<SLOT_LEN>(&slot_key) {
+            Ok(slot) => {
+                let slot = unsafe { core::mem::transmute::<[u8; SLOT_LEN], Slot>(slot) };
+                let consumed_at = u32::from_be_bytes(slot.consumed_at);
+                if now.saturating_sub(consumed_at) < self.retention {
+                    return Err(HookError::TooManyNonces);
+                }
+                // prune the oldest nonce, which the slot held
+                match state_set(&[], &self.key(NONCE_KIND, slot.nonce)) {
+                    Ok(_) | Err(HookError::DoesntExist) => {}
+                    Err(err) => return Err(err),
+                }
+            }
+            Err(HookError::DoesntExist) => {}
+            Err(err) => return Err(err),
+        }
+
+        let slot = Slot {
+            consumed_at: now.to_be_bytes(),
+            nonce,
+        };
+        state_set(&now.to_be_bytes(), &nonce_key)?;
+        state_set(
+            &unsafe { core::mem::transmute::<Slot, [u8; SLOT_LEN]>(slot) },
+            &slot_key,
+        )?;
+        match state_set(&((head + 1) % self.capacity).to_be_bytes(), &head_key) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// [consume](Self::consume) the ID of the originating transaction, for hooks that
+    /// take each transaction into account once, such as when it is replayed on another
+    /// network
+    #[inline(always)]
+    pub fn consume_otxn_id(&self) -> Result<()> {
+        self.consume(&otxn_id(OtxnIdFlag::Zero)?)
+    }
+
+    /// Whether `nonce` was consumed and not pruned yet
+    #[inline(always)]
+    pub fn is_consumed(&self, nonce: &[u8; HASH_LEN]) -> Result<bool> {
+        match state::<4>(&self.key(NONCE_KIND, nonce_prefix(nonce))) {
+            Ok(_) => Ok(true),
+            Err(HookError::DoesntExist) => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    #[inline(always)]
+    fn slot_key(&self, index: u32) -> [u8; STATE_KEY_LEN] {
+        let [i0, i1, i2, i3] = index.to_be_bytes();
+        let mut rest = [0; NONCE_KEY_LEN];
+        rest[0] = i0;
+        rest[1] = i1;
+        rest[2] = i2;
+        rest[3] = i3;
+        self.key(SLOT_KIND, rest)
+    }
+
+    #[inline(always)]
+    fn key(&self, kind: u8, rest: [u8; NONCE_KEY_LEN]) -> [u8; STATE_KEY_LEN] {
+        let suffix = NonceKeySuffix { kind, rest };
+
+        state_key(&self.name, unsafe {
+            core::mem::transmute::<NonceKeySuffix, [u8; STATE_KEY_SUFFIX_LEN]>(suffix)
+        })
+    }
+}
+
+// The first bytes of `nonce`, which go in its state key
+#[inline(always)]
+fn nonce_prefix(nonce: &[u8; HASH_LEN]) -> [u8; NONCE_KEY_LEN] {
+    #[repr(C)]
+    struct Split {
+        prefix: [u8; NONCE_KEY_LEN],
+        rest: [u8; HASH_LEN - NONCE_KEY_LEN],
+    }
+
+    unsafe { core::mem::transmute::<[u8; HASH_LEN], Split>(*nonce) }.prefix
+}
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::mock;
+
+    const VOUCHERS: NonceStore = NonceStore::new(b"vouchers", 2, 10);
+
+    #[wasm_bindgen_test]
+    fn rejects_nonces_consumed_before() {
+        mock::reset();
+        mock::set_ledger_seq(100);
+
+        assert!(matches!(VOUCHERS.is_consumed(&[1; HASH_LEN]), Ok(false)));
+        assert!(VOUCHERS.consume(&[1; HASH_LEN]).is_ok());
+        assert!(matches!(VOUCHERS.is_consumed(&[1; HASH_LEN]), Ok(true)));
+        assert!(matches!(
+            VOUCHERS.consume(&[1; HASH_LEN]),
+            Err(HookError::AlreadySet)
+        ));
+        assert!(VOUCHERS.consume(&[2; HASH_LEN]).is_ok());
+
+        let mut key = [0; STATE_KEY_LEN];
+        key[..8].copy_from_slice(b"vouchers");
+        key[13..].copy_from_slice(&[1; NONCE_KEY_LEN]);
+        assert_eq!(mock::state(&key).unwrap(), 100u32.to_be_bytes());
+    }
+
+    #[wasm_bindgen_test]
+    fn prunes_the_oldest_nonces_after_the_retention() {
+        mock::reset();
+        mock::set_ledger_seq(100);
+        assert!(VOUCHERS.consume(&[1; HASH_LEN]).is_ok());
+        mock::set_ledger_seq(105);
+        assert!(VOUCHERS.consume(&[2; HASH_LEN]).is_ok());
+
+        // full of nonces that are still retained
+        mock::set_ledger_seq(109);
+        assert!(matches!(
+            VOUCHERS.consume(&[3; HASH_LEN]),
+            Err(HookError::TooManyNonces)
+        ));
+
+        mock::set_ledger_seq(110);
+        assert!(VOUCHERS.consume(&[3; HASH_LEN]).is_ok());
+        assert!(matches!(VOUCHERS.is_consumed(&[1; HASH_LEN]), Ok(false)));
+        assert!(matches!(VOUCHERS.is_consumed(&[2; HASH_LEN]), Ok(true)));
+        assert!(matches!(
+            VOUCHERS.consume(&[4; HASH_LEN]),
+            Err(HookError::TooManyNonces)
+        ));
+        mock::set_ledger_seq(115);
+        assert!(VOUCHERS.consume(&[4; HASH_LEN]).is_ok());
+        assert!(matches!(VOUCHERS.is_consumed(&[2; HASH_LEN]), Ok(false)));
+    }
+
+    #[wasm_bindgen_test]
+    fn consumes_transaction_ids() {
+        mock::reset();
+        assert!(VOUCHERS.consume_otxn_id().is_ok());
+        assert!(matches!(
+            VOUCHERS.consume_otxn_id(),
+            Err(HookError::AlreadySet)
+        ));
+    }
+}
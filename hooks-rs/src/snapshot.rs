@@ -0,0 +1,121 @@
+//! Snapshot tests for built transactions.
+//!
+//! A snapshot is the hex dump of a built transaction with one serialized field per line,
+//! kept next to the test as a string. [assert_snapshot] fails when the transaction is
+//! encoded differently, and prints the new snapshot, so a change in the encoding is
+//! either caught or has to be accepted by replacing the snapshot in the test.
+//!
+//! Like the mock host, this is only available in tests and with the `mock` feature.
+//!
+//! # Example
+//! ```
+//! let mut buffer = XrpPaymentBuilder::uninit_buffer();
+//! let tx = XrpPaymentBuilder::new(Drops(1000), &DESTINATION, None, None)
+//!     .build(&mut buffer)
+//!     .unwrap_line_number();
+//!
+//! snapshot::assert_snapshot(
+//!     tx,
+//!     "
+//!     120000
+//!     2280000000
+//!     ...
+//!     ",
+//! );
+//! ```
+
+extern crate std;
+
+use std::fmt::Write;
+use std::string::String;
+use std::vec::Vec;
+
+use crate::decode::split_fields;
+
+/// The snapshot of a transaction: each field as uppercase hex on its own line
+pub fn snapshot(tx: &[u8]) -> String {
+    let mut snapshot = String::new();
+    for field in split_fields(tx) {
+        for byte in field {
+            let _ = write!(snapshot, "{byte:02X}");
+        }
+        snapshot.push('\n');
+    }
+
+    snapshot
+}
+
+/// Assert that a transaction matches `expected`, a snapshot as returned by [snapshot].
+///
+/// Leading and trailing whitespace of the lines of `expected` and empty lines are
+/// ignored, and hex can be in either case, so the snapshot can be indented like the
+/// code around it.
+///
+/// # Panics
+/// If the transaction doesn't match, with the first field that differs and the new
+/// snapshot to replace `expected` with if the change is intended.
+#[track_caller]
+pub fn assert_snapshot(tx: &[u8], expected: &str) {
+    let actual = snapshot(tx);
+    let actual_lines: Vec<&str> = actual.lines().collect();
+    let expected_lines: Vec<String> = expected
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_ascii_uppercase)
+        .collect();
+    if actual_lines == expected_lines {
+        return;
+    }
+
+    let first_difference = actual_lines
+        .iter()
+        .zip(&expected_lines)
+        .position(|(actual, expected)| actual != expected)
+        .unwrap_or(actual_lines.len().min(expected_lines.len()));
+    panic!(
+        "transaction does not match the snapshot, first difference in field {}:\n  \
+         expected: {}\n  actual:   {}\n\nnew snapshot:\n{actual}",
+        first_difference + 1,
+        expected_lines
+            .get(first_difference)
+            .map_or("(none)", String::as_str),
+        actual_lines.get(first_difference).unwrap_or(&"(none)"),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+
+    const TX: [u8; 14] = [
+        0x12, 0x00, 0x00, // TransactionType
+        0x22, 0x80, 0x00, 0x00, 0x00, // Flags
+        0x81, 0x04, 0xDE, 0xAD, 0xBE, 0xEF, // Account, too short but well formed
+    ];
+
+    #[wasm_bindgen_test]
+    fn writes_one_field_per_line() {
+        assert_eq!(snapshot(&TX), "120000\n2280000000\n8104DEADBEEF\n");
+    }
+
+    #[wasm_bindgen_test]
+    fn ignores_indentation_and_case() {
+        assert_snapshot(
+            &TX,
+            "
+            120000
+            2280000000
+            8104deadbeef
+            ",
+        );
+    }
+
+    #[wasm_bindgen_test]
+    #[should_panic(expected = "first difference in field 2")]
+    fn fails_on_changed_fields() {
+        assert_snapshot(&TX, "120000\n2280000001\n8104DEADBEEF");
+    }
+}
@@ -0,0 +1,749 @@
+//! Splitting amounts between accounts by shares in basis points.
+//!
+//! A [Splitter] is a table of up to [SPLIT_MAX_SHARES] accounts, each with a share in
+//! basis points, as used by royalty and revenue sharing hooks. It can be a constant of
+//! the hook, or be read from a hook parameter or a state entry so that it can be changed
+//! without reinstalling the hook. In both, the table is encoded as the shares one after
+//! the other, each the account ID followed by the basis points, 4 bytes big endian.
+//!
+//! [Splitter::distribute] pays every account its share of an amount with one emitted
+//! payment each. The shares are rounded down, and what rounding leaves over goes to the
+//! first account, so that shares adding up to [BASIS_POINTS] distribute all of the
+//! amount. What shares adding up to less leave over stays with the hook account.
+//!
+//! Issued amounts are split the same way by [Splitter::distribute_issued], with one
+//! payment over a trust line each. Their values are XFLs, which keep 16 significant
+//! digits, so the shares are rounded down to those digits rather than to whole units.
+//!
+//! # Example
+//! ```
+//! #[hook]
+//! fn royalties() -> Outcome<'static> {
+//!     let splitter = Splitter::from_param(b"SPLIT")?;
+//!     let mut reservation = EmitReservation::new(splitter.len() as u32)?;
+//!     splitter.distribute(Drops(otxn_drops()?), &mut reservation)?;
+//!
+//!     Outcome::Accept { code: 0, msg: b"royalties paid" }
+//! }
+//! ```
+
+use crate::api::*;
+use crate::c;
+use crate::transaction::{
+    TransactionBuilder, TrustlinePaymentBuilder, XrpPaymentBuilder, ISSUED_AMOUNT_LEN,
+};
+
+/// Maximum number of shares in a [Splitter]
+pub const SPLIT_MAX_SHARES: usize = 10;
+
+/// Length of an encoded [SplitShare]: the account ID, then the basis points
+pub const SPLIT_SHARE_LEN: usize = ACC_ID_LEN + 4;
+
+/// Maximum length of an encoded [Splitter]
+///
+/// The 240 bytes of [SPLIT_MAX_SHARES] shares fit in a hook parameter value of
+/// [HOOK_PARAM_VALUE_MAX_LEN](crate::HOOK_PARAM_VALUE_MAX_LEN) bytes and in a state entry of
+/// 256 bytes.
+pub const SPLIT_TABLE_MAX_LEN: usize = SPLIT_MAX_SHARES * SPLIT_SHARE_LEN;
+
+/// An account and its share of the amounts split by a [Splitter]
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct SplitShare {
+    /// Account the share is paid to
+    pub account: AccountId,
+    /// Share in basis points, out of [BASIS_POINTS]
+    pub basis_points: u32,
+}
+
+/// A payment made by [Splitter::distribute], or of the value `A` of an issued amount by
+/// [Splitter::distribute_issued]
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct SplitPayout<A = Drops> {
+    /// Account paid
+    pub account: AccountId,
+    /// Amount paid to the account
+    pub amount: A,
+    /// Hash of the emitted payment
+    pub txn_hash: [u8; HASH_LEN],
+}
+
+/// The payments made by [Splitter::distribute] or [Splitter::distribute_issued]
+#[derive(Clone, Copy)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct Distribution<A = Drops> {
+    payouts: [SplitPayout<A>; SPLIT_MAX_SHARES],
+    len: usize,
+}
+
+impl<A> Distribution<A> {
+    /// The payments, in the order of the shares. Shares rounding down to nothing get no
+    /// payment.
+    #[inline(always)]
+    pub fn payouts(&self) -> &[SplitPayout<A>] {
+        &self.payouts[..self.len]
+    }
+}
+
+impl Distribution {
+    /// Drops paid in all
+    #[inline(always)]
+    pub fn total(&self) -> Drops {
+        let mut total = Drops::ZERO;
+        // guarded loop
+        let mut i = 0;
+        while {
+            max_iter(SPLIT_MAX_SHARES as u32 + 1);
+            i < self.len
+        } {
+            total = Drops(total.0 + self.payouts[i].amount.0);
+            i += 1;
+        }
+
+        total
+    }
+}
+
+impl Distribution<XFL> {
+    /// Value paid in all
+    #[inline(always)]
+    pub fn total(&self) -> Result<XFL> {
+        let mut total = XFL(0);
+        // guarded loop
+        let mut i = 0;
+        while {
+            max_iter(SPLIT_MAX_SHARES as u32 + 1);
+            i < self.len
+        } {
+            total = (total + self.payouts[i].amount)?;
+            i += 1;
+        }
+
+        Ok(total)
+    }
+}
+
+/// A table of accounts sharing the amounts split between them
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct Splitter {
+    shares: [SplitShare; SPLIT_MAX_SHARES],
+    len: usize,
+}
+
+// An issued amount: the value, then the currency and the issuer
+#[repr(C)]
+struct IssuedAmount {
+    value: [u8; 8],
+    asset: [u8; ISSUED_AMOUNT_LEN - 8],
+}
+
+// The layout of a share in hook parameters and state
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct StoredShare {
+    account: [u8; ACC_ID_LEN],
+    basis_points: [u8; 4],
+}
+
+const NO_SHARE: SplitShare = SplitShare {
+    account: AccountId([0; ACC_ID_LEN]),
+    basis_points: 0,
+};
+
+impl Splitter {
+    /// A splitter with the given shares.
+    ///
+    /// Meant for constants, so that the shares are checked at compile time rather than by
+    /// a loop without guard in the hook. Fails to compile if there are none, more than
+    /// [SPLIT_MAX_SHARES], or if they add up to more than [BASIS_POINTS].
+    #[inline(always)]
+    pub const fn new(shares: &[SplitShare]) -> Self {
+        assert!(
+            !shares.is_empty() && shares.len() <= SPLIT_MAX_SHARES,
+            "a splitter has 1 to SPLIT_MAX_SHARES shares"
+        );
+
+        let mut splitter = Self {
+            shares: [NO_SHARE; SPLIT_MAX_SHARES],
+            len: shares.len(),
+        };
+        let mut basis_points = 0;
+        let mut i = 0;
+        while i < shares.len() {
+            splitter.shares[i] = shares[i];
+            basis_points += shares[i].basis_points;
+            i += 1;
+        }
+        assert!(
+            basis_points <= BASIS_POINTS,
+            "the shares add up to more than BASIS_POINTS"
+        );
+
+        splitter
+    }
+
+    /// Decode a splitter from `bytes`, the shares one after the other.
+    ///
+    /// Returns [HookError::InvalidArgument] if `bytes` isn't 1 to [SPLIT_MAX_SHARES]
+    /// encoded shares, or if they add up to more than [BASIS_POINTS].
+    #[inline(always)]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() > SPLIT_TABLE_MAX_LEN {
+            return Err(HookError::InvalidArgument);
+        }
+
+        let mut table = [0; SPLIT_TABLE_MAX_LEN];
+        // guarded loop
+        let mut i = 0;
+        while {
+            max_iter(SPLIT_TABLE_MAX_LEN as u32 + 1);
+            i < bytes.len()
+        } {
+            table[i] = bytes[i];
+            i += 1;
+        }
+
+        Self::from_table(table, bytes.len())
+    }
+
+    /// Read the splitter from the hook parameter `name`.
+    ///
+    /// Returns [HookError::DoesntExist] if it is not set, and the errors of
+    /// [from_bytes](Self::from_bytes) if it isn't a valid table.
+    #[inline(always)]
+    pub fn from_param(name: &[u8]) -> Result<Self> {
+        let mut table = [0; SPLIT_TABLE_MAX_LEN];
+        let result: Result<u64> = unsafe {
+            c::hook_param(
+                host_ptr(table.as_mut_ptr()),
+                SPLIT_TABLE_MAX_LEN as u32,
+                host_ptr(name.as_ptr()),
+                name.len() as u32,
+            )
+            .into()
+        };
+        let len = match result {
+            Ok(len) => len as usize,
+            // more shares than fit
+            Err(HookError::TooSmall) => return Err(HookError::InvalidArgument),
+            Err(err) => return Err(err),
+        };
+
+        Self::from_table(table, len)
+    }
+
+    /// Read the splitter from the state entry under `key`, written with
+    /// [store](Self::store).
+    ///
+    /// Returns [HookError::DoesntExist] if there is none, and the errors of
+    /// [from_bytes](Self::from_bytes) if it isn't a valid table.
+    #[inline(always)]
+    pub fn load(key: &[u8]) -> Result<Self> {
+        let mut table = [0; SPLIT_TABLE_MAX_LEN];
+        let result: Result<u64> = unsafe {
+            c::state(
+                host_ptr(table.as_mut_ptr()),
+                SPLIT_TABLE_MAX_LEN as u32,
+                host_ptr(key.as_ptr()),
+                key.len() as u32,
+            )
+            .into()
+        };
+        let len = match result {
+            Ok(len) => len as usize,
+            Err(HookError::TooSmall) => return Err(HookError::InvalidArgument),
+            Err(err) => return Err(err),
+        };
+
+        Self::from_table(table, len)
+    }
+
+    /// Write the splitter to the state of this hook under `key`
+    #[inline(always)]
+    pub fn store(&self, key: &[u8]) -> Result<()> {
+        let mut stored = [StoredShare {
+            account: [0; ACC_ID_LEN],
+            basis_points: [0; 4],
+        }; SPLIT_MAX_SHARES];
+        // guarded loop
+        let mut i = 0;
+        while {
+            max_iter(SPLIT_MAX_SHARES as u32 + 1);
+            i < self.len
+        } {
+            stored[i] = StoredShare {
+                account: self.shares[i].account.0,
+                basis_points: self.shares[i].basis_points.to_be_bytes(),
+            };
+            i += 1;
+        }
+        let table = unsafe {
+            core::mem::transmute::<[StoredShare; SPLIT_MAX_SHARES], [u8; SPLIT_TABLE_MAX_LEN]>(
+                stored,
+            )
+        };
+
+        match state_set(&table[..self.len * SPLIT_SHARE_LEN], key) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Number of shares
+    #[inline(always)]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether there are no shares, which the constructors rule out
+    #[inline(always)]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The shares, in the order they are paid
+    #[inline(always)]
+    pub fn shares(&self) -> &[SplitShare] {
+        &self.shares[..self.len]
+    }
+
+    /// The drops each share gets of `amount`, in the order of the shares, of which the
+    /// first [len](Self::len) are set. See the [module docs](self) for the rounding.
+    #[inline(always)]
+    pub fn amounts(&self, amount: Drops) -> [Drops; SPLIT_MAX_SHARES] {
+        let mut amounts = [Drops::ZERO; SPLIT_MAX_SHARES];
+        let mut basis_points = 0;
+        let mut paid = 0;
+        // guarded loop
+        let mut i = 0;
+        while {
+            max_iter(SPLIT_MAX_SHARES as u32 + 1);
+            i < self.len
+        } {
+            amounts[i] = share_of(amount, self.shares[i].basis_points);
+            basis_points += self.shares[i].basis_points;
+            paid += amounts[i].0;
+            i += 1;
+        }
+
+        // what rounding each share down left over
+        amounts[0] = Drops(amounts[0].0 + (share_of(amount, basis_points).0 - paid));
+
+        amounts
+    }
+
+    /// Pay every account its share of `amount` through `reservation`, which needs room
+    /// for [len](Self::len) payments.
+    ///
+    /// Shares rounding down to nothing are skipped. If emitting a payment fails, the error
+    /// is returned and the hook should roll back, which also discards the payments
+    /// emitted before it.
+    #[inline(always)]
+    pub fn distribute(
+        &self,
+        amount: Drops,
+        reservation: &mut EmitReservation,
+    ) -> Result<Distribution> {
+        let amounts = self.amounts(amount);
+        let mut distribution = Distribution {
+            payouts: [SplitPayout {
+                account: NO_SHARE.account,
+                amount: Drops::ZERO,
+                txn_hash: [0; HASH_LEN],
+            }; SPLIT_MAX_SHARES],
+            len: 0,
+        };
+
+        // guarded loop
+        let mut i = 0;
+        while {
+            max_iter(SPLIT_MAX_SHARES as u32 + 1);
+            i < self.len
+        } {
+            if amounts[i].0 != 0 {
+                let account = self.shares[i].account;
+                let txn_hash = XrpPaymentBuilder::new(amounts[i], &account, None, None)
+                    .build_and_emit(reservation)?;
+                distribution.payouts[distribution.len] = SplitPayout {
+                    account,
+                    amount: amounts[i],
+                    txn_hash,
+                };
+                distribution.len += 1;
+            }
+            i += 1;
+        }
+
+        Ok(distribution)
+    }
+
+    /// The value each share gets of the issued value `value`, like [amounts](Self::amounts).
+    ///
+    /// Each share is rounded down to the 16 digits of an XFL, and what rounding leaves
+    /// over goes to the first share. Fails with the error of the host if an XFL operation
+    /// fails.
+    #[inline(always)]
+    pub fn issued_amounts(&self, value: XFL) -> Result<[XFL; SPLIT_MAX_SHARES]> {
+        let mut amounts = [XFL(0); SPLIT_MAX_SHARES];
+        let mut basis_points = 0;
+        let mut paid = XFL(0);
+        // guarded loop
+        let mut i = 0;
+        while {
+            max_iter(SPLIT_MAX_SHARES as u32 + 1);
+            i < self.len
+        } {
+            amounts[i] = value.mulratio(false, self.shares[i].basis_points, BASIS_POINTS)?;
+            basis_points += self.shares[i].basis_points;
+            paid = (paid + amounts[i])?;
+            i += 1;
+        }
+
+        // what rounding each share down left over
+        let left_over = (value.mulratio(false, basis_points, BASIS_POINTS)? - paid)?;
+        amounts[0] = (amounts[0] + left_over)?;
+
+        Ok(amounts)
+    }
+
+    /// Pay every account its share of the issued `amount`, the 48 bytes of a serialized
+    /// amount such as the `Amount` of an originating payment, through `reservation`.
+    ///
+    /// Works like [distribute](Self::distribute), with payments of the currency of
+    /// `amount` over trust lines. Returns [HookError::InvalidArgument] if `amount` is XRP
+    /// or negative.
+    #[inline(always)]
+    pub fn distribute_issued(
+        &self,
+        amount: &[u8; ISSUED_AMOUNT_LEN],
+        reservation: &mut EmitReservation,
+    ) -> Result<Distribution<XFL>> {
+        let amount =
+            unsafe { core::mem::transmute::<[u8; ISSUED_AMOUNT_LEN], IssuedAmount>(*amount) };
+        let head = u64::from_be_bytes(amount.value);
+        // XRP, or a negative value
+        if head & 0x8000_0000_0000_0000 == 0
+            || (head & 0x4000_0000_0000_0000 == 0 && head != 0x8000_0000_0000_0000)
+        {
+            return Err(HookError::InvalidArgument);
+        }
+
+        // the value of an issued amount is an XFL, but for the highest bit
+        let amounts = self.issued_amounts(XFL((head & 0x7FFF_FFFF_FFFF_FFFF) as i64))?;
+        let mut distribution = Distribution {
+            payouts: [SplitPayout {
+                account: NO_SHARE.account,
+                amount: XFL(0),
+                txn_hash: [0; HASH_LEN],
+            }; SPLIT_MAX_SHARES],
+            len: 0,
+        };
+
+        // guarded loop
+        let mut i = 0;
+        while {
+            max_iter(SPLIT_MAX_SHARES as u32 + 1);
+            i < self.len
+        } {
+            if amounts[i].0 != 0 {
+                let account = self.shares[i].account;
+                let share = IssuedAmount {
+                    value: (amounts[i].0 as u64 | 0x8000_0000_0000_0000).to_be_bytes(),
+                    asset: amount.asset,
+                };
+                let share =
+                    unsafe { core::mem::transmute::<IssuedAmount, [u8; ISSUED_AMOUNT_LEN]>(share) };
+                let txn_hash = TrustlinePaymentBuilder::new(&share, &account, None, None)
+                    .build_and_emit(reservation)?;
+                distribution.payouts[distribution.len] = SplitPayout {
+                    account,
+                    amount: amounts[i],
+                    txn_hash,
+                };
+                distribution.len += 1;
+            }
+            i += 1;
+        }
+
+        Ok(distribution)
+    }
+
+    // The splitter in the first `len` bytes of `table`
+    #[inline(always)]
+    fn from_table(table: [u8; SPLIT_TABLE_MAX_LEN], len: usize) -> Result<Self> {
+        if len == 0 || len % SPLIT_SHARE_LEN != 0 {
+            return Err(HookError::InvalidArgument);
+        }
+
+        let stored = unsafe {
+            core::mem::transmute::<[u8; SPLIT_TABLE_MAX_LEN], [StoredShare; SPLIT_MAX_SHARES]>(
+                table,
+            )
+        };
+        let mut splitter = Self {
+            shares: [NO_SHARE; SPLIT_MAX_SHARES],
+            len: len / SPLIT_SHARE_LEN,
+        };
+        let mut basis_points = 0;
+        // guarded loop
+        let mut i = 0;
+        while {
+            max_iter(SPLIT_MAX_SHARES as u32 + 1);
+            i < splitter.len
+        } {
+            splitter.shares[i] = SplitShare {
+                account: AccountId(stored[i].account),
+                basis_points: u32::from_be_bytes(stored[i].basis_points),
+            };
+            basis_points += splitter.shares[i].basis_points as u64;
+            i += 1;
+        }
+        if basis_points > BASIS_POINTS as u64 {
+            return Err(HookError::InvalidArgument);
+        }
+
+        Ok(splitter)
+    }
+}
+
+// `basis_points` of `amount`, which are at most BASIS_POINTS
+#[inline(always)]
+fn share_of(amount: Drops, basis_points: u32) -> Drops {
+    match amount.share(basis_points) {
+        Some(share) => share,
+        None => Drops::ZERO,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec::Vec;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::decode::decode;
+    use crate::fixtures::{DESTINATION, HOOK_ACCOUNT, ISSUER, USD};
+    use crate::mock;
+
+    const ARTIST: AccountId = AccountId([4; ACC_ID_LEN]);
+
+    const ROYALTIES: Splitter = Splitter::new(&[
+        SplitShare {
+            account: ARTIST,
+            basis_points: 5000,
+        },
+        SplitShare {
+            account: DESTINATION,
+            basis_points: 3333,
+        },
+        SplitShare {
+            account: ISSUER,
+            basis_points: 1667,
+        },
+    ]);
+
+    // A positive XFL
+    const fn xfl(mantissa: i64, exponent: i64) -> XFL {
+        XFL((1 << 62) | ((exponent + 97) << 54) | mantissa)
+    }
+
+    fn issued(value: XFL) -> [u8; ISSUED_AMOUNT_LEN] {
+        let mut amount = [0; ISSUED_AMOUNT_LEN];
+        amount[..8].copy_from_slice(&(value.0 as u64 | 0x8000_0000_0000_0000).to_be_bytes());
+        amount[8..28].copy_from_slice(&USD);
+        amount[28..].copy_from_slice(ISSUER.as_bytes());
+        amount
+    }
+
+    fn encoded(shares: &[(AccountId, u32)]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for (account, basis_points) in shares {
+            bytes.extend_from_slice(account.as_bytes());
+            bytes.extend_from_slice(&basis_points.to_be_bytes());
+        }
+        bytes
+    }
+
+    #[wasm_bindgen_test]
+    fn gives_what_rounding_leaves_over_to_the_first_share() {
+        let amounts = ROYALTIES.amounts(Drops(1001));
+        assert_eq!(&amounts[..3], &[Drops(502), Drops(333), Drops(166)]);
+        assert_eq!(amounts[3], Drops::ZERO);
+
+        // shares adding up to less than everything keep the rest
+        let half = Splitter::new(&[
+            SplitShare {
+                account: ARTIST,
+                basis_points: 2500,
+            },
+            SplitShare {
+                account: DESTINATION,
+                basis_points: 2500,
+            },
+        ]);
+        assert_eq!(&half.amounts(Drops(1003))[..2], &[Drops(251), Drops(250)]);
+        assert_eq!(&half.amounts(Drops(1))[..2], &[Drops::ZERO, Drops::ZERO]);
+    }
+
+    #[wasm_bindgen_test]
+    fn decodes_tables_from_params_and_state() {
+        mock::reset();
+        let table = encoded(&[(ARTIST, 5000), (DESTINATION, 3333), (ISSUER, 1667)]);
+        mock::set_hook_param(b"SPLIT", &table);
+        assert!(matches!(Splitter::from_param(b"SPLIT"), Ok(splitter) if splitter == ROYALTIES));
+        assert!(matches!(
+            Splitter::from_param(b"OTHER"),
+            Err(HookError::DoesntExist)
+        ));
+
+        assert!(ROYALTIES.store(b"split").is_ok());
+        assert_eq!(mock::state(b"split").unwrap(), table);
+        assert!(matches!(Splitter::load(b"split"), Ok(splitter) if splitter == ROYALTIES));
+        assert!(
+            matches!(Splitter::from_bytes(&table), Ok(splitter) if splitter.shares() == ROYALTIES.shares())
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn rejects_invalid_tables() {
+        for table in [
+            Vec::new(),
+            encoded(&[(ARTIST, 5000), (DESTINATION, 5001)]),
+            encoded(&[(ARTIST, 100); SPLIT_MAX_SHARES + 1]),
+            encoded(&[(ARTIST, 5000)])[..SPLIT_SHARE_LEN - 1].to_vec(),
+        ] {
+            assert!(matches!(
+                Splitter::from_bytes(&table),
+                Err(HookError::InvalidArgument)
+            ));
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn emits_a_payment_for_every_share() {
+        mock::reset();
+        mock::set_hook_account(HOOK_ACCOUNT);
+
+        let invocation = mock::invoke(|| {
+            let mut reservation = EmitReservation::new(ROYALTIES.len() as u32)?;
+            let distribution = ROYALTIES.distribute(Drops(1001), &mut reservation)?;
+            assert_eq!(distribution.payouts().len(), 3);
+            assert_eq!(distribution.total(), Drops(1001));
+            assert_eq!(distribution.payouts()[1].account, DESTINATION);
+
+            Outcome::Accept {
+                code: 0,
+                msg: b"split",
+            }
+        });
+        assert_eq!(invocation.emitted.len(), 3);
+        let paid: Vec<_> = invocation
+            .emitted
+            .iter()
+            .map(|emitted| {
+                let payment = decode(&emitted.tx);
+                (
+                    payment.account(FieldId::Destination).unwrap(),
+                    payment.drops(FieldId::Amount).unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(paid, [(ARTIST, 502), (DESTINATION, 333), (ISSUER, 166)]);
+
+        // shares too small to get anything aren't paid
+        let invocation = mock::invoke(|| {
+            let mut reservation = EmitReservation::new(ROYALTIES.len() as u32)?;
+            let distribution = ROYALTIES.distribute(Drops(2), &mut reservation)?;
+            assert_eq!(distribution.payouts().len(), 1);
+
+            Outcome::Accept {
+                code: 0,
+                msg: b"split",
+            }
+        });
+        assert_eq!(invocation.emitted.len(), 1);
+    }
+
+    #[wasm_bindgen_test]
+    fn splits_issued_values_to_16_digits() {
+        mock::reset();
+        // 1001 units
+        let amounts = ROYALTIES
+            .issued_amounts(xfl(1_001_000_000_000_000, -12))
+            .unwrap();
+        let bits: Vec<_> = amounts[..3].iter().map(|amount| amount.0).collect();
+        assert_eq!(
+            bits,
+            [
+                xfl(5_005_000_000_000_000, -13).0,
+                xfl(3_336_333_000_000_000, -13).0,
+                xfl(1_668_667_000_000_000, -13).0,
+            ]
+        );
+        assert_eq!(amounts[3].0, 0);
+
+        // a third of a unit each, with what rounding leaves over going to the first share
+        let thirds = Splitter::new(&[
+            SplitShare {
+                account: ARTIST,
+                basis_points: 3334,
+            },
+            SplitShare {
+                account: DESTINATION,
+                basis_points: 3333,
+            },
+            SplitShare {
+                account: ISSUER,
+                basis_points: 3333,
+            },
+        ]);
+        let value = xfl(1_000_000_000_000_001, -15);
+        let amounts = thirds.issued_amounts(value).unwrap();
+        assert_eq!(amounts[1].0, xfl(3_333_000_000_000_003, -16).0);
+        let total = ((amounts[0] + amounts[1]).unwrap() + amounts[2]).unwrap();
+        assert_eq!(total.0, value.0);
+    }
+
+    #[wasm_bindgen_test]
+    fn emits_a_trust_line_payment_for_every_share() {
+        mock::reset();
+        mock::set_hook_account(HOOK_ACCOUNT);
+
+        let amount = issued(xfl(1_001_000_000_000_000, -12));
+        let invocation = mock::invoke(|| {
+            let mut reservation = EmitReservation::new(ROYALTIES.len() as u32)?;
+            let distribution = ROYALTIES.distribute_issued(&amount, &mut reservation)?;
+            assert_eq!(distribution.payouts().len(), 3);
+            assert_eq!(distribution.total()?.0, xfl(1_001_000_000_000_000, -12).0);
+
+            Outcome::Accept {
+                code: 0,
+                msg: b"split",
+            }
+        });
+        assert_eq!(invocation.emitted.len(), 3);
+        let payment = decode(&invocation.emitted[1].tx);
+        assert_eq!(payment.account(FieldId::Destination), Some(DESTINATION));
+        assert_eq!(
+            payment.field(FieldId::Amount),
+            Some(&issued(xfl(3_336_333_000_000_000, -13))[..])
+        );
+
+        mock::reset();
+        let mut reservation = EmitReservation::new(1).unwrap();
+        let mut xrp = [0; ISSUED_AMOUNT_LEN];
+        xrp[..8].copy_from_slice(&(1000u64 | 0x4000_0000_0000_0000).to_be_bytes());
+        let mut negative = amount;
+        negative[0] &= !0x40;
+        for amount in [xrp, negative] {
+            assert!(matches!(
+                ROYALTIES.distribute_issued(&amount, &mut reservation),
+                Err(HookError::InvalidArgument)
+            ));
+        }
+    }
+}
@@ -0,0 +1,278 @@
+//! Typed records in hook state that other hooks can read.
+//!
+//! Hooks can read each other's state with [state_foreign], but only as bytes, so the
+//! hook writing a state entry and the hooks reading it have to agree on what the bytes
+//! mean. A [Record] is a struct laid out in a state entry the same way by every hook
+//! built with this crate: a version byte, then the fields one after the other, encoded
+//! with [RecordField]. Derive it with [Record](macro@crate::Record), and bump the
+//! version whenever the fields change, so that readers built for the old layout get
+//! [HookError::DoesNotMatch] instead of garbage.
+//!
+//! The writing hook stores its records with [store_record], and other hooks read them
+//! with a [ForeignTable] pointing at its account and namespace.
+//!
+//! # Example
+//! ```
+//! #[derive(Record)]
+//! #[record(version = 1)]
+//! struct Membership {
+//!     member: AccountId,
+//!     since: RippleTime,
+//! }
+//!
+//! // in the hook keeping the members
+//! store_record(b"member", &Membership { member, since: ledger_time() })?;
+//!
+//! // in another hook
+//! const MEMBERS: ForeignTable<Membership> = ForeignTable::new(REGISTRY, REGISTRY_NAMESPACE);
+//! let membership = MEMBERS.get(b"member")?;
+//! ```
+
+use core::marker::PhantomData;
+
+use crate::api::*;
+use crate::c;
+
+/// Maximum length of the fields of a [Record], which fit in a state entry of 256 bytes
+/// with the version byte
+pub const RECORD_MAX_LEN: usize = 255;
+
+/// A struct kept in a state entry, usually derived with [Record](macro@crate::Record)
+pub trait Record: Sized {
+    /// Version of the layout, the first byte of the state entry
+    const VERSION: u8;
+
+    /// Length of the fields, without the version byte
+    const LEN: usize;
+
+    /// Decode the fields from `bytes`, which is [LEN](Self::LEN) bytes long
+    fn read_fields(bytes: &[u8]) -> Self;
+
+    /// Encode the fields into `bytes`, which is [LEN](Self::LEN) bytes long
+    fn write_fields(&self, bytes: &mut [u8]);
+}
+
+/// A field of a [Record], encoded into a fixed number of bytes.
+///
+/// Integers and [Drops] are big endian, XFLs are 8 byte little endian like hook
+/// parameters, see [FromHookParam](crate::FromHookParam), and byte arrays, including
+/// [AccountId] and [PublicKey], are taken as is.
+pub trait RecordField: Sized {
+    /// Length of the encoded field
+    const LEN: usize;
+
+    /// Decode the field from `bytes`, which is [LEN](Self::LEN) bytes long
+    fn read_from(bytes: &[u8]) -> Self;
+
+    /// Encode the field into `bytes`, which is [LEN](Self::LEN) bytes long
+    fn write_to(&self, bytes: &mut [u8]);
+}
+
+impl<const N: usize> RecordField for [u8; N] {
+    const LEN: usize = N;
+
+    #[inline(always)]
+    fn read_from(bytes: &[u8]) -> Self {
+        let mut array = [0; N];
+        // guarded loop rather than a slice copy, which could become `memcpy`
+        let mut i = 0;
+        while {
+            max_iter(N as u32 + 1);
+            i < N
+        } {
+            array[i] = bytes[i];
+            i += 1;
+        }
+
+        array
+    }
+
+    #[inline(always)]
+    fn write_to(&self, bytes: &mut [u8]) {
+        // guarded loop
+        let mut i = 0;
+        while {
+            max_iter(N as u32 + 1);
+            i < N
+        } {
+            bytes[i] = self[i];
+            i += 1;
+        }
+    }
+}
+
+macro_rules! impl_record_field {
+    ($($ty:ty: $bytes:ty => $from_bytes:expr, $to_bytes:expr;)*) => {
+        $(
+            impl RecordField for $ty {
+                const LEN: usize = <$bytes as RecordField>::LEN;
+
+                #[inline(always)]
+                fn read_from(bytes: &[u8]) -> Self {
+                    $from_bytes(<$bytes as RecordField>::read_from(bytes))
+                }
+
+                #[inline(always)]
+                fn write_to(&self, bytes: &mut [u8]) {
+                    RecordField::write_to(&$to_bytes(self), bytes)
+                }
+            }
+        )*
+    };
+}
+
+impl_record_field! {
+    u8: [u8; 1] => |[byte]: [u8; 1]| byte, |byte: &u8| [*byte];
+    u16: [u8; 2] => u16::from_be_bytes, |int: &u16| int.to_be_bytes();
+    u32: [u8; 4] => u32::from_be_bytes, |int: &u32| int.to_be_bytes();
+    u64: [u8; 8] => u64::from_be_bytes, |int: &u64| int.to_be_bytes();
+    i64: [u8; 8] => i64::from_be_bytes, |int: &i64| int.to_be_bytes();
+    bool: [u8; 1] => |[byte]: [u8; 1]| byte != 0, |flag: &bool| [*flag as u8];
+    Drops: [u8; 8] => |bytes| Drops(u64::from_be_bytes(bytes)), |drops: &Drops| drops.0.to_be_bytes();
+    RippleTime: [u8; 4] => RippleTime::from_be_bytes, |time: &RippleTime| time.to_be_bytes();
+    LedgerSequence: [u8; 4] => |bytes| LedgerSequence(u32::from_be_bytes(bytes)), |seq: &LedgerSequence| seq.0.to_be_bytes();
+    XFL: [u8; XFL_LEN] => |bytes| XFL(i64::from_le_bytes(bytes)), |xfl: &XFL| xfl.0.to_le_bytes();
+    AccountId: [u8; ACC_ID_LEN] => AccountId, |account: &AccountId| account.0;
+    PublicKey: [u8; PUBLIC_KEY_LEN] => PublicKey, |key: &PublicKey| key.0;
+}
+
+/// Write `record` to the state of this hook under `key`, after its version byte
+#[inline(always)]
+pub fn store_record<R: Record>(key: &[u8], record: &R) -> Result<()> {
+    let mut bytes = [0; RECORD_MAX_LEN + 1];
+    bytes[0] = R::VERSION;
+    record.write_fields(&mut bytes[1..1 + R::LEN]);
+
+    match state_set(&bytes[..1 + R::LEN], key) {
+        Ok(_) => Ok(()),
+        Err(err) => Err(err),
+    }
+}
+
+/// Read the record under `key` from the state of this hook.
+///
+/// Fails with [HookError::DoesNotMatch] if the entry has another version or length.
+#[inline(always)]
+pub fn load_record<R: Record>(key: &[u8]) -> Result<R> {
+    let mut bytes = [0; RECORD_MAX_LEN + 1];
+    let result: Result<u64> = unsafe {
+        c::state(
+            host_ptr(bytes.as_mut_ptr()),
+            bytes.len() as u32,
+            host_ptr(key.as_ptr()),
+            key.len() as u32,
+        )
+        .into()
+    };
+
+    decode_record(&bytes[..result? as usize])
+}
+
+/// The records another hook stored with [store_record], read from the state of `account`
+/// in `namespace`, the namespace of the hook that wrote them
+#[derive(Clone, Copy)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct ForeignTable<R> {
+    account: AccountId,
+    namespace: Hash,
+    record: PhantomData<R>,
+}
+
+impl<R: Record> ForeignTable<R> {
+    /// The records in the state of `account` in `namespace`
+    #[inline(always)]
+    pub const fn new(account: AccountId, namespace: Hash) -> Self {
+        Self {
+            account,
+            namespace,
+            record: PhantomData,
+        }
+    }
+
+    /// Read the record under `key`.
+    ///
+    /// Fails with [HookError::DoesntExist] if there is none, and with
+    /// [HookError::DoesNotMatch] if the entry has another version or length.
+    #[inline(always)]
+    pub fn get(&self, key: &[u8]) -> Result<R> {
+        let mut bytes = [0; RECORD_MAX_LEN + 1];
+        let len = state_foreign(&mut bytes, key, &self.namespace, self.account.as_bytes())?;
+
+        decode_record(&bytes[..len as usize])
+    }
+}
+
+#[inline(always)]
+fn decode_record<R: Record>(bytes: &[u8]) -> Result<R> {
+    if bytes.len() != 1 + R::LEN || bytes[0] != R::VERSION {
+        return Err(HookError::DoesNotMatch);
+    }
+
+    Ok(R::read_fields(&bytes[1..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::fixtures::{DESTINATION, ISSUER};
+    use crate::mock;
+
+    #[derive(crate::Record, Clone, Copy, PartialEq, Eq, Debug)]
+    #[record(version = 2)]
+    struct Membership {
+        member: AccountId,
+        since: RippleTime,
+        fee: Drops,
+        active: bool,
+    }
+
+    const MEMBERSHIP: Membership = Membership {
+        member: DESTINATION,
+        since: RippleTime(1000),
+        fee: Drops(12),
+        active: true,
+    };
+
+    #[wasm_bindgen_test]
+    fn lays_out_records_after_their_version() {
+        mock::reset();
+        assert_eq!(Membership::LEN, ACC_ID_LEN + 4 + 8 + 1);
+        assert!(store_record(b"member", &MEMBERSHIP).is_ok());
+
+        let stored = mock::state(b"member").unwrap();
+        assert_eq!(stored[0], 2);
+        assert_eq!(&stored[1..21], DESTINATION.as_bytes());
+        assert_eq!(&stored[21..25], &1000u32.to_be_bytes());
+        assert_eq!(&stored[25..33], &12u64.to_be_bytes());
+        assert_eq!(stored[33], 1);
+        assert!(matches!(load_record::<Membership>(b"member"), Ok(record) if record == MEMBERSHIP));
+    }
+
+    #[wasm_bindgen_test]
+    fn reads_records_of_other_hooks() {
+        const NAMESPACE: Hash = [5; HASH_LEN];
+        const MEMBERS: ForeignTable<Membership> = ForeignTable::new(ISSUER, NAMESPACE);
+
+        mock::reset();
+        assert!(store_record(b"member", &MEMBERSHIP).is_ok());
+        let mut stored = mock::state(b"member").unwrap();
+        mock::set_foreign_state(ISSUER, NAMESPACE, b"member", &stored);
+        assert!(matches!(MEMBERS.get(b"member"), Ok(record) if record == MEMBERSHIP));
+        assert!(matches!(MEMBERS.get(b"other"), Err(HookError::DoesntExist)));
+
+        // written with another version of the layout
+        stored[0] = 1;
+        mock::set_foreign_state(ISSUER, NAMESPACE, b"member", &stored);
+        assert!(matches!(
+            MEMBERS.get(b"member"),
+            Err(HookError::DoesNotMatch)
+        ));
+        mock::set_foreign_state(ISSUER, NAMESPACE, b"member", &stored[..20]);
+        assert!(matches!(
+            MEMBERS.get(b"member"),
+            Err(HookError::DoesNotMatch)
+        ));
+    }
+}
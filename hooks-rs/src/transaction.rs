@@ -1,7 +1,16 @@
 use core::mem::MaybeUninit;
 
 use crate::api::*;
-use crate::{c, hook_account, ledger_seq, AccountId, AccountType, AmountType, TxnType};
+use crate::flags::AccountSetFlag;
+use crate::host::{ExternHost, HostEnv};
+use crate::{c, ledger_seq, AccountId, AccountType, AmountType, TxnType};
+
+mod batch;
+mod store;
+mod writer;
+
+pub use batch::*;
+pub use writer::*;
 
 /// Builds a transaction to send XRP.
 ///
@@ -12,7 +21,7 @@ use crate::{c, hook_account, ledger_seq, AccountId, AccountType, AmountType, Txn
 /// Equivalent to `PREPARE_PAYMENT_SIMPLE` in `macro.h` in
 /// official hooks API.
 ///
-/// When successfully built, the transaction buffer will be 270 bytes long
+/// When successfully built with both tags, the transaction buffer will be 270 bytes long
 /// that look like:
 ///
 /// ```
@@ -31,29 +40,69 @@ use crate::{c, hook_account, ledger_seq, AccountId, AccountType, AmountType, Txn
 /// ED202E000000013D00000000000000015B316CD7252B2F6A808CFBC98D9DD7C687316E850D7608647173A8793CD9553B2D5CB2D9188C36F2EEE397BCF9DAE609966A2F79C69275F57D7BD22DAB20ED037C765D2702C5E3E248D5DDBD1399D6AF79DB23FF37599BEA01AF2300985DA7BE52C0858A14090A708604BC3BB4459F01E50AC0023FE682D2ADE1 // txn details (138 bytes)
 /// ```
 ///
+/// A tag that is `None` is left out of the transaction, which then starts 5 bytes
+/// later in the buffer and is 5 bytes shorter.
+///
 /// # Example
 ///
 /// ```
-/// let xrp_payment_txn_builder = XrpPaymentBuilder::new(1000, &otxn_account, 0, 0);
+/// let xrp_payment_txn_builder = XrpPaymentBuilder::new(Drops(1000), &otxn_account, None, None);
 /// let mut xrp_payment_txn_buffer = XrpPaymentBuilder::uninit_buffer();
-/// match xrp_payment_txn_builder.build(&mut xrp_payment_txn_buffer) {
-///     Ok(_) => {}
+/// let tx = match xrp_payment_txn_builder.build(&mut xrp_payment_txn_buffer) {
+///     Ok(tx) => tx,
 ///     Err(err) => {
 ///         rollback(b"could not build xrp payment txn", err.into());
 ///     }
 /// };
-/// let txn_hash = match emit(&xrp_payment_txn_buffer) {
+/// let txn_hash = match emit(tx) {
 ///     Ok(hash) => hash,
 ///     Err(err) => {
 ///         rollback(b"could not emit xrp payment txn", err.into());
 ///     }
 /// };
 /// ```
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
 pub struct XrpPaymentBuilder<'a> {
-    drops: u64,
-    to_address: &'a [u8; 20],
-    dest_tag: u32,
-    src_tag: u32,
+    drops: Drops,
+    to_address: &'a AccountId,
+    dest_tag: Option<DestTag>,
+    src_tag: Option<SrcTag>,
+    ledger_window: Option<LedgerWindow>,
+}
+
+/// Byte length of a serialized issued (non-XRP) amount, without its field code
+pub const ISSUED_AMOUNT_LEN: usize = 48;
+
+/// Builds a transaction to send an issued (non-XRP) amount over a trust line.
+///
+/// **Like [XrpPaymentBuilder], this only works with `cbak` function present in
+/// your hook code.**
+///
+/// Equivalent to `PREPARE_PAYMENT_SIMPLE_TRUSTLINE` in `macro.h` in
+/// official hooks API. The transaction is laid out like the one built by
+/// [XrpPaymentBuilder], except that the amount takes 49 bytes instead of 9,
+/// so the transaction buffer will be 310 bytes long, and the tags are left out the same way. `PREPARE_PAYMENT_SIMPLE_TRUSTLINE_SIZE`
+/// in `macro.h` is 309, which is one byte too short for the emit details.
+///
+/// The amount is the 48 bytes long serialized amount including the currency code and
+/// the issuer, such as the `Amount` of an originating payment read with [otxn_field].
+///
+/// # Example
+/// ```
+/// let amount = otxn_field::<ISSUED_AMOUNT_LEN>(FieldId::Amount).unwrap_line_number();
+///
+/// let mut buffer = TrustlinePaymentBuilder::uninit_buffer();
+/// TrustlinePaymentBuilder::new(&amount, &otxn_account, None, None)
+///     .build(&mut buffer)
+///     .unwrap_line_number();
+/// ```
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct TrustlinePaymentBuilder<'a> {
+    amount: &'a [u8; ISSUED_AMOUNT_LEN],
+    to_address: &'a AccountId,
+    dest_tag: Option<DestTag>,
+    src_tag: Option<SrcTag>,
+    ledger_window: Option<LedgerWindow>,
 }
 
 #[repr(u8)]
@@ -65,17 +114,26 @@ enum FieldCode {
     DestinationTag = 0xE,
     FirstLedgerSequence = 0x1A,
     LastLedgerSequence = 0x1B,
+    SetFlag = 0x21,
+    ClearFlag = 0x22,
 }
 
 /// Builds a transaction.
 pub trait TransactionBuilder<const TXN_LEN: usize> {
-    /// Byte length of the transaction.
+    /// Byte length of the buffer, which is the length of the transaction with every
+    /// optional field, such as both tags of a payment.
     const TXN_LEN: usize = TXN_LEN;
     /// Transaction type of the transaction.
     const TXN_TYPE: TxnType;
     /// Builds a specific transaction by directly modifying the uninitialized buffer provided
     /// as an argument.
     ///
+    /// The buffer is the one the transaction ends up in: every field is written into it in
+    /// place, the host writes the emit details straight into it, and the transaction is
+    /// never copied as a whole. `Ok(tx)` is the built transaction, to be passed as is to
+    /// [emit] or [EmitReservation::emit]. It ends with the buffer, and takes all of its
+    /// `TXN_LEN` bytes unless optional fields were left out, which makes it start later.
+    ///
     /// The reason that this function must take a mutable reference to an uninitialized buffer
     /// and cannot initialize its own buffer inside it to return it is because of Rust's
     /// restrictions on returning unsafe pointer to local variables to the outer scope
@@ -113,26 +171,29 @@ pub trait TransactionBuilder<const TXN_LEN: usize> {
     /// Therefore, we have no choice but to use this syntax:
     ///
     /// ```
-    /// let xrp_payment_txn_builder = XrpPaymentBuilder::new(1000, &otxn_account, 0, 0);
+    /// let xrp_payment_txn_builder = XrpPaymentBuilder::new(Drops(1000), &otxn_account, None, None);
     /// let mut buffer = XrpPaymentBuilder::uninit_buffer();
-    /// match xrp_payment_txn_builder.build(&mut buffer) {
-    ///     Ok(ptr) => ptr,
+    /// let tx = match xrp_payment_txn_builder.build(&mut buffer) {
+    ///     Ok(tx) => tx,
     ///     Err(err) => {
     ///         rollback(b"could not build xrp payment txn", err.into());
     ///     }
     /// };
     /// ```
-    fn build(&self, uninitialized_buffer: &mut [MaybeUninit<u8>; TXN_LEN]) -> Result<()>;
+    fn build<'b>(
+        &self,
+        uninitialized_buffer: &'b mut [MaybeUninit<u8>; TXN_LEN],
+    ) -> Result<&'b [u8]>;
 
     /// Utility method for creating an uninitialized buffer for a predefined length.
     /// Use this for creating an uninitialized transaction buffer to pass to `build`.
     ///
     /// # Example
     /// ```
-    /// let xrp_payment_txn_builder = XrpPaymentBuilder::new(1000, &otxn_account, 0, 0);
+    /// let xrp_payment_txn_builder = XrpPaymentBuilder::new(Drops(1000), &otxn_account, None, None);
     /// let mut buffer = XrpPaymentBuilder::uninit_buffer();
-    /// match xrp_payment_txn_builder.build(&mut buffer) {
-    ///     Ok(ptr) => ptr,
+    /// let tx = match xrp_payment_txn_builder.build(&mut buffer) {
+    ///     Ok(tx) => tx,
     ///     Err(err) => {
     ///         rollback(b"could not build xrp payment txn", err.into());
     ///     }
@@ -142,6 +203,147 @@ pub trait TransactionBuilder<const TXN_LEN: usize> {
     fn uninit_buffer() -> [MaybeUninit<u8>; TXN_LEN] {
         MaybeUninit::uninit_array()
     }
+
+    /// Builds the transaction and emits it through `reservation`, returning the
+    /// 32-bytes long txn hash.
+    ///
+    /// # Example
+    /// ```
+    /// let mut reservation = EmitReservation::new(1).unwrap_line_number();
+    ///
+    /// let xrp_payment_txn_builder = XrpPaymentBuilder::new(Drops(1000), &otxn_account, None, None);
+    /// let txn_hash = match xrp_payment_txn_builder.build_and_emit(&mut reservation) {
+    ///     Ok(hash) => hash,
+    ///     Err(err) => {
+    ///         rollback(b"could not emit xrp payment txn", err.into());
+    ///     }
+    /// };
+    /// ```
+    #[inline(always)]
+    fn build_and_emit(&self, reservation: &mut EmitReservation) -> Result<[u8; HASH_LEN]> {
+        if reservation.remaining() == 0 {
+            return Err(HookError::TooManyEmittedTxn);
+        }
+
+        let mut buffer = Self::uninit_buffer();
+        let tx = self.build(&mut buffer)?;
+
+        reservation.emit(tx)
+    }
+}
+
+/// Checks a fully built transaction buffer before it is emitted.
+///
+/// [emit] only reports [HookError::EmissionFailure] when the host rejects a transaction,
+/// and the reason only shows up in the trace log of the node. Validating the buffer
+/// first traces the problem and returns an error for it instead:
+///
+/// - [HookError::ParseError] if the buffer is not a valid serialized transaction
+/// - [HookError::DoesntExist] if `FirstLedgerSequence`, `LastLedgerSequence` or `Fee` is missing
+/// - [HookError::InvalidArgument] if the ledger window does not include the next ledger,
+///   or if the fee is not in XRP or lower than [etxn_fee_base]
+///
+/// This costs extra host calls, so it is mostly meant to be used during development.
+///
+/// # Example
+/// ```
+/// let mut xrp_payment_txn_buffer = XrpPaymentBuilder::uninit_buffer();
+/// let tx = xrp_payment_txn_builder
+///     .build(&mut xrp_payment_txn_buffer)
+///     .unwrap_line_number();
+/// tx.validate().unwrap_line_number();
+/// ```
+pub trait ValidateTransaction {
+    /// Validates the transaction. Must only be called on a fully built transaction.
+    fn validate(&self) -> Result<()>;
+}
+
+/// For buffers whose transaction takes all of them, such as ones built with every optional
+/// field. Otherwise validate the transaction returned by [TransactionBuilder::build].
+impl<const TXN_LEN: usize> ValidateTransaction for [MaybeUninit<u8>; TXN_LEN] {
+    #[inline(always)]
+    fn validate(&self) -> Result<()> {
+        // SAFETY: a built transaction buffer is fully initialized
+        let tx = unsafe { core::slice::from_raw_parts(self.as_ptr() as *const u8, TXN_LEN) };
+
+        tx.validate()
+    }
+}
+
+impl ValidateTransaction for [u8] {
+    #[inline(always)]
+    fn validate(&self) -> Result<()> {
+        if !sto_validate(self) {
+            let _ = trace(
+                b"txn is not a valid serialized object",
+                b"",
+                DataRepr::AsUTF8,
+            );
+            return Err(HookError::ParseError);
+        }
+
+        let ledger_window = LedgerWindow {
+            first: LedgerSequence(u32_field(self, FieldId::FirstLedgerSequence)?),
+            last: LedgerSequence(u32_field(self, FieldId::LastLedgerSequence)?),
+        };
+        let next_ledger_sequence = ledger_seq().next();
+        if ledger_window.first > next_ledger_sequence {
+            let _ = trace_num(
+                b"FirstLedgerSequence is after the next ledger",
+                ledger_window.first.into(),
+            );
+            return Err(HookError::InvalidArgument);
+        }
+        if ledger_window.last < next_ledger_sequence {
+            let _ = trace_num(
+                b"LastLedgerSequence is before the next ledger",
+                ledger_window.last.into(),
+            );
+            return Err(HookError::InvalidArgument);
+        }
+
+        let fee = match sto_subfield(self, FieldId::Fee) {
+            Ok(fee) if fee.len() == 8 => fee,
+            Ok(_) => {
+                let _ = trace(b"Fee is not in XRP", b"", DataRepr::AsUTF8);
+                return Err(HookError::InvalidArgument);
+            }
+            Err(err) => {
+                let _ = trace(b"txn has no Fee", b"", DataRepr::AsUTF8);
+                return Err(err);
+            }
+        };
+        // checked above that the fee is 8 bytes long
+        let fee = u64::from_be_bytes(unsafe { *(fee.as_ptr() as *const [u8; 8]) });
+        // an amount in XRP has the highest bit unset and the positive bit set
+        if fee & 0xC000_0000_0000_0000 != 0x4000_0000_0000_0000 {
+            let _ = trace(b"Fee is not a positive XRP amount", b"", DataRepr::AsUTF8);
+            return Err(HookError::InvalidArgument);
+        }
+        let fee_drops = fee & 0x3FFF_FFFF_FFFF_FFFF;
+        let min_fee_drops = etxn_fee_base(self)?;
+        if fee_drops < min_fee_drops {
+            let _ = trace_num(b"Fee is lower than etxn_fee_base", min_fee_drops as i64);
+            return Err(HookError::InvalidArgument);
+        }
+
+        Ok(())
+    }
+}
+
+#[inline(always)]
+fn u32_field(tx: &[u8], field_id: FieldId) -> Result<u32> {
+    match sto_subfield(tx, field_id) {
+        Ok(field) if field.len() == 4 => Ok(u32::from_be_bytes(unsafe {
+            // checked that the field is 4 bytes long
+            *(field.as_ptr() as *const [u8; 4])
+        })),
+        Ok(_) => Err(HookError::ParseError),
+        Err(err) => {
+            let _ = trace_num(b"txn is missing field", field_id as i64);
+            Err(err)
+        }
+    }
 }
 
 /// A generic buffer for building a transaction.
@@ -155,7 +357,23 @@ pub struct TransactionBuffer<'a, const TXN_LEN: usize> {
 // of declaring it as an associated constant, but specifying
 // constant has the return type in `build` method is unstable
 // in Rust nightly right now. See `generic_const_exprs` feature.
-impl<const TXN_LEN: usize> TransactionBuffer<'_, TXN_LEN> {
+impl<'a, const TXN_LEN: usize> TransactionBuffer<'a, TXN_LEN> {
+    /// Creates a transaction buffer that starts encoding at the beginning of `buf`.
+    ///
+    /// The encoders don't check bounds, so the fields encoded into it must fit
+    /// into `TXN_LEN` bytes.
+    #[inline(always)]
+    pub fn new(buf: &'a mut [MaybeUninit<u8>; TXN_LEN]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    /// The position the next field will be encoded at, which is the number of bytes
+    /// encoded so far
+    #[inline(always)]
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
     /// Encodes a transaction type.
     ///
     /// # Example
@@ -166,21 +384,7 @@ impl<const TXN_LEN: usize> TransactionBuffer<'_, TXN_LEN> {
     /// ```
     #[inline(always)]
     pub fn encode_txn_type(&mut self, tt: TxnType) {
-        unsafe {
-            self.buf
-                .get_unchecked_mut(self.pos)
-                .as_mut_ptr()
-                .write(FieldCode::TransactionType.into());
-            self.buf
-                .get_unchecked_mut(self.pos + 1)
-                .as_mut_ptr()
-                .write(((tt as u16 >> 8) & 0xFF) as u8);
-            self.buf
-                .get_unchecked_mut(self.pos + 2)
-                .as_mut_ptr()
-                .write((tt as u16 & 0xFF) as u8);
-        }
-        self.pos += 3;
+        self.write_u16_field(FieldId::TransactionType, tt as u16);
     }
 
     /// Encodes a serialized field value for the first byte,
@@ -197,29 +401,42 @@ impl<const TXN_LEN: usize> TransactionBuffer<'_, TXN_LEN> {
     /// ```
     #[inline(always)]
     pub fn encode_u32(&mut self, data: u32, field: u8) {
-        unsafe {
-            self.buf
-                .get_unchecked_mut(self.pos)
-                .as_mut_ptr()
-                .write(0x20 + (field & 0x0F));
-            self.buf
-                .get_unchecked_mut(self.pos + 1)
-                .as_mut_ptr()
-                .write(((data >> 24) & 0xFF) as u8);
-            self.buf
-                .get_unchecked_mut(self.pos + 2)
-                .as_mut_ptr()
-                .write(((data >> 16) & 0xFF) as u8);
-            self.buf
-                .get_unchecked_mut(self.pos + 3)
-                .as_mut_ptr()
-                .write(((data >> 8) & 0xFF) as u8);
-            self.buf
-                .get_unchecked_mut(self.pos + 4)
-                .as_mut_ptr()
-                .write((data & 0xFF) as u8);
-        }
-        self.pos += 5;
+        self.write_header(SerializedType::UInt32 as u8, field & 0x0F);
+        self.write_u32(data);
+    }
+
+    /// Encodes the `Flags` of a transaction from one of the flag sets of [flags](crate::flags)
+    /// or raw bits. Nothing is added to them, so include `FULLY_CANONICAL_SIG` like the
+    /// builders of this crate do.
+    ///
+    /// # Example
+    /// ```
+    /// let mut txn_buffer = ...
+    ///
+    /// txn_buffer.encode_flags(TrustSetFlags::FULLY_CANONICAL_SIG | TrustSetFlags::SET_NO_RIPPLE);
+    /// ```
+    #[inline(always)]
+    pub fn encode_flags(&mut self, flags: impl Into<u32>) {
+        self.encode_u32(flags.into(), FieldCode::Flags.into());
+    }
+
+    /// Encodes the `SetFlag` of an `AccountSet` transaction, the setting it turns on.
+    ///
+    /// # Example
+    /// ```
+    /// let mut txn_buffer = ...
+    ///
+    /// txn_buffer.encode_set_flag(AccountSetFlag::RequireDest);
+    /// ```
+    #[inline(always)]
+    pub fn encode_set_flag(&mut self, flag: AccountSetFlag) {
+        self.encode_u32_with_field_id(flag.into(), FieldCode::SetFlag.into());
+    }
+
+    /// Encodes the `ClearFlag` of an `AccountSet` transaction, the setting it turns off.
+    #[inline(always)]
+    pub fn encode_clear_flag(&mut self, flag: AccountSetFlag) {
+        self.encode_u32_with_field_id(flag.into(), FieldCode::ClearFlag.into());
     }
 
     /// Encodes a u32 value with a field id. Note that
@@ -239,37 +456,15 @@ impl<const TXN_LEN: usize> TransactionBuffer<'_, TXN_LEN> {
     /// ```
     #[inline(always)]
     pub fn encode_u32_with_field_id(&mut self, data: u32, field: u8) {
-        unsafe {
-            self.buf
-                .get_unchecked_mut(self.pos)
-                .as_mut_ptr()
-                .write(0x20);
-            self.buf
-                .get_unchecked_mut(self.pos + 1)
-                .as_mut_ptr()
-                .write(field);
-            self.buf
-                .get_unchecked_mut(self.pos + 2)
-                .as_mut_ptr()
-                .write(((data >> 24) & 0xFF) as u8);
-            self.buf
-                .get_unchecked_mut(self.pos + 3)
-                .as_mut_ptr()
-                .write(((data >> 16) & 0xFF) as u8);
-            self.buf
-                .get_unchecked_mut(self.pos + 4)
-                .as_mut_ptr()
-                .write(((data >> 8) & 0xFF) as u8);
-            self.buf
-                .get_unchecked_mut(self.pos + 5)
-                .as_mut_ptr()
-                .write((data & 0xFF) as u8);
-        }
-        self.pos += 6;
+        self.write_header(SerializedType::UInt32 as u8, field);
+        self.write_u32(data);
     }
 
     /// Encodes amount in drops.
     ///
+    /// Amount types with a field code of 16 or more, such as [AmountType::MinimumOffer],
+    /// are not supported.
+    ///
     /// # Example
     /// ```
     /// let mut txn_buffer = ...
@@ -294,45 +489,11 @@ impl<const TXN_LEN: usize> TransactionBuffer<'_, TXN_LEN> {
     #[inline(always)]
     pub fn encode_drops_at(&mut self, pos: usize, drops: u64, amount_type: AmountType) {
         let amount_type: u8 = amount_type.into();
-        unsafe {
-            self.buf
-                .get_unchecked_mut(pos)
-                .as_mut_ptr()
-                .write(0x60 + (amount_type & 0x0F));
-            self.buf
-                .get_unchecked_mut(pos + 1)
-                .as_mut_ptr()
-                .write((0b01000000 + ((drops >> 56) & 0b00111111)) as u8);
-            self.buf
-                .get_unchecked_mut(pos + 2)
-                .as_mut_ptr()
-                .write(((drops >> 48) & 0xFF) as u8);
-            self.buf
-                .get_unchecked_mut(pos + 3)
-                .as_mut_ptr()
-                .write(((drops >> 40) & 0xFF) as u8);
-            self.buf
-                .get_unchecked_mut(pos + 4)
-                .as_mut_ptr()
-                .write(((drops >> 32) & 0xFF) as u8);
-            self.buf
-                .get_unchecked_mut(pos + 5)
-                .as_mut_ptr()
-                .write(((drops >> 24) & 0xFF) as u8);
-            self.buf
-                .get_unchecked_mut(pos + 6)
-                .as_mut_ptr()
-                .write(((drops >> 16) & 0xFF) as u8);
-            self.buf
-                .get_unchecked_mut(pos + 7)
-                .as_mut_ptr()
-                .write(((drops >> 8) & 0xFF) as u8);
-            self.buf
-                .get_unchecked_mut(pos + 8)
-                .as_mut_ptr()
-                .write((drops & 0xFF) as u8);
-        }
-        self.pos += 9;
+        let next_pos = self.pos + 9;
+        self.pos = pos;
+        self.write_header(SerializedType::Amount as u8, amount_type & 0x0F);
+        self.write_drops(Drops(drops));
+        self.pos = next_pos;
     }
 
     /// Encodes an amount in drops at a specific position of the buffer.
@@ -394,6 +555,26 @@ impl<const TXN_LEN: usize> TransactionBuffer<'_, TXN_LEN> {
         }
     }
 
+    /// Encodes an issued (non-XRP) amount, given as the 48 bytes long serialized
+    /// amount without its field code.
+    ///
+    /// # Example
+    /// ```
+    /// let mut txn_buffer = ...
+    ///
+    /// txn_buffer.encode_issued_amount(&amount, AmountType::Amount);
+    /// ```
+    #[inline(always)]
+    pub fn encode_issued_amount(
+        &mut self,
+        amount: &[u8; ISSUED_AMOUNT_LEN],
+        amount_type: AmountType,
+    ) {
+        let amount_type: u8 = amount_type.into();
+        self.write_header(SerializedType::Amount as u8, amount_type & 0x0F);
+        self.write_issued_amount(amount);
+    }
+
     /// Encodes a signing public key as null. For transactions
     /// emitted from hooks, the signing public key is always null.
     ///
@@ -405,27 +586,26 @@ impl<const TXN_LEN: usize> TransactionBuffer<'_, TXN_LEN> {
     /// ```
     #[inline(always)]
     pub fn encode_signing_pubkey_as_null(&mut self) {
-        // leave self.buf[self.pos + 2..self.pos + 35] as 0 because they
-        // are already initialized to 0 and meant to be like that to
-        // represent null
-        unsafe {
-            self.buf
-                .get_unchecked_mut(self.pos)
-                .as_mut_ptr()
-                .write(0x73);
-            self.buf
-                .get_unchecked_mut(self.pos + 1)
-                .as_mut_ptr()
-                .write(0x21);
+        // 33 zero bytes represent null
+        self.write_field_header(FieldId::SigningPubKey);
+        self.write_byte(PUBLIC_KEY_LEN as u8);
+        self.write_zeros::<PUBLIC_KEY_LEN>();
+    }
 
-            // avoid creating loops in the resulting wasm
-            let u64_ptr = self.buf.get_unchecked_mut(self.pos + 2).as_mut_ptr() as *mut u64;
-            u64_ptr.write(0);
-            u64_ptr.offset(1).write(0);
-            u64_ptr.offset(2).write(0);
-            u64_ptr.offset(3).write(0); // total 32 bytes of 0
-        }
-        self.pos += 35;
+    /// Encodes a signing public key, for objects that carry a real key rather than the
+    /// null one of emitted transactions.
+    ///
+    /// # Example
+    /// ```
+    /// let mut txn_buffer = ...
+    ///
+    /// txn_buffer.encode_signing_pubkey(&public_key);
+    /// ```
+    #[inline(always)]
+    pub fn encode_signing_pubkey(&mut self, public_key: &PublicKey) {
+        self.write_field_header(FieldId::SigningPubKey);
+        self.write_byte(PUBLIC_KEY_LEN as u8);
+        self.write_array(&public_key.0);
     }
 
     /// Encodes an account.
@@ -438,45 +618,215 @@ impl<const TXN_LEN: usize> TransactionBuffer<'_, TXN_LEN> {
     /// ```
     #[inline(always)]
     pub fn encode_account(&mut self, account_id: &AccountId, account_type: AccountType) {
+        self.write_header(SerializedType::Account as u8, account_type.into());
+        self.write_account(account_id);
+    }
+
+    // Copies [XRP_PAYMENT_TEMPLATE] after its destination tag, where the fields that are
+    // always there start, to the same place in the buffer
+    #[inline(always)]
+    fn copy_xrp_payment_template(&mut self) {
+        const FROM: usize = XRP_PAYMENT_TAGS_END;
         unsafe {
-            let account_type: u8 = account_type.into();
-            self.buf
-                .get_unchecked_mut(self.pos)
-                .as_mut_ptr()
-                .write(0x80 + account_type);
-            self.buf
-                .get_unchecked_mut(self.pos + 1)
-                .as_mut_ptr()
-                .write(0x14);
+            store::copy_n::<{ XRP_PAYMENT_FIELDS_LEN - FROM }>(
+                self.buf,
+                FROM,
+                &XRP_PAYMENT_TEMPLATE,
+                FROM,
+            )
+        };
+        self.pos = XRP_PAYMENT_FIELDS_LEN;
+    }
 
-            let u64_account_id_ptr = account_id.as_ptr() as *mut u64;
-            let u64_buf_ptr = self.buf.get_unchecked_mut(self.pos + 2).as_ptr() as *mut u64;
+    // Writes the tags of an XRP payment and the fields before them so that they end where
+    // the destination tag of [XRP_PAYMENT_TEMPLATE] does, without moving the position, and
+    // returns where the transaction starts
+    #[inline(always)]
+    fn put_xrp_payment_tags(
+        &mut self,
+        src_tag: Option<SrcTag>,
+        dest_tag: Option<DestTag>,
+    ) -> usize {
+        let mut start = XRP_PAYMENT_TAGS_END;
+        if let Some(DestTag(dest_tag)) = dest_tag {
+            start -= 5;
+            unsafe { store::put(self.buf, start, 0x20 + FieldCode::DestinationTag as u8) };
+            self.put_u32_at(start + 1, dest_tag);
+        }
+        // the sequence, which is always 0
+        start -= 5;
+        unsafe { store::put(self.buf, start, 0x20 + FieldCode::Sequence as u8) };
+        self.put_u32_at(start + 1, 0);
+        if let Some(SrcTag(src_tag)) = src_tag {
+            start -= 5;
+            unsafe { store::put(self.buf, start, 0x20 + FieldCode::SourceTag as u8) };
+            self.put_u32_at(start + 1, src_tag);
+        }
+        // the transaction type and the flags
+        start -= 8;
+        unsafe { store::copy_8(self.buf, start, &XRP_PAYMENT_TEMPLATE, 0) };
+
+        start
+    }
+
+    // Writes the value of a u32 field whose header is already in the buffer, without
+    // moving the position
+    #[inline(always)]
+    fn put_u32_at(&mut self, pos: usize, data: u32) {
+        unsafe { store::copy_4(self.buf, pos, &data.to_be_bytes(), 0) };
+    }
+
+    // Writes the value of an 8 bytes long field whose header is already in the buffer,
+    // without moving the position
+    #[inline(always)]
+    fn put_u64_at(&mut self, pos: usize, data: u64) {
+        unsafe { store::copy_8(self.buf, pos, &data.to_be_bytes(), 0) };
+    }
+
+    // Writes an account ID whose header is already in the buffer, without moving the
+    // position
+    #[inline(always)]
+    fn put_account_id_at(&mut self, pos: usize, account_id: &AccountId) {
+        unsafe { store::copy_n::<ACC_ID_LEN>(self.buf, pos, &account_id.0, 0) };
+    }
+
+    // The bytes from `start` to the end of the buffer, for the host to read the transaction
+    // being built from
+    #[inline(always)]
+    fn uninit_from(&self, start: usize) -> &[MaybeUninit<u8>] {
+        unsafe { core::slice::from_raw_parts(self.buf.as_ptr().add(start), TXN_LEN - start) }
+    }
 
-            // 16 bytes written
-            u64_buf_ptr.write(u64_account_id_ptr.read());
-            u64_buf_ptr
-                .offset(1)
-                .write(u64_account_id_ptr.offset(1).read());
+    // The transaction built from `start` to the end of the buffer, all of which must be
+    // initialized
+    #[inline(always)]
+    fn into_built(self, start: usize) -> &'a [u8] {
+        unsafe {
+            core::slice::from_raw_parts(self.buf.as_ptr().add(start) as *const u8, TXN_LEN - start)
+        }
+    }
 
-            let u32_account_id_ptr = account_id.as_ptr().offset(16) as *mut u32;
-            let u32_buf_ptr = self.buf.get_unchecked_mut(self.pos + 18).as_ptr() as *mut u32;
+    // The `len` bytes from the current position, for the host to write into
+    #[inline(always)]
+    fn next_mut(&mut self, len: usize) -> &mut [MaybeUninit<u8>] {
+        unsafe { store::slice_mut(self.buf, self.pos, len) }
+    }
+
+    // Overwrites the fee reserved at `pos` once the rest of the transaction is built,
+    // without moving the position
+    #[inline(always)]
+    fn encode_fee_at(&mut self, pos: usize, drops: u64) {
+        #[cfg(not(feature = "safe"))]
+        unsafe {
+            Self::encode_drops_at_buf_ptr(self.buf.as_mut_ptr(), pos, drops, AmountType::Fee)
+        };
 
-            // 4 bytes written
-            u32_buf_ptr.write(u32_account_id_ptr.read());
+        #[cfg(feature = "safe")]
+        {
+            let next_pos = self.pos;
+            self.encode_drops_at(pos, drops, AmountType::Fee);
+            self.pos = next_pos;
         }
-        self.pos += 22;
     }
 }
 
 impl<'a> XrpPaymentBuilder<'a> {
-    /// Creates a new builder for XRP payment.
+    /// Creates a new builder for XRP payment, with the tags left out where they are `None`.
     #[inline(always)]
-    pub fn new(drops: u64, to_address: &'a [u8; 20], dest_tag: u32, src_tag: u32) -> Self {
+    pub fn new(
+        drops: Drops,
+        to_address: &'a AccountId,
+        dest_tag: Option<DestTag>,
+        src_tag: Option<SrcTag>,
+    ) -> Self {
         Self {
             drops,
             to_address,
             dest_tag,
             src_tag,
+            ledger_window: None,
+        }
+    }
+
+    /// Sets the ledgers in which the transaction is valid.
+    /// Defaults to [LedgerWindow::current] with [LedgerWindow::DEFAULT_LEDGER_COUNT] ledgers.
+    #[inline(always)]
+    pub fn with_ledger_window(mut self, ledger_window: LedgerWindow) -> Self {
+        self.ledger_window = Some(ledger_window);
+        self
+    }
+}
+
+/// Ledgers in which an emitted transaction can be applied, encoded as its
+/// `FirstLedgerSequence` and `LastLedgerSequence`.
+///
+/// # Chaining emitted transactions
+///
+/// The host only accepts emitted transactions that are valid from the next ledger on,
+/// so a transaction that depends on a previously emitted one, such as
+/// "create trust line, then send tokens", cannot be emitted in the same hook execution
+/// and simply wait for the first one. Instead:
+///
+/// 1. Emit the first transaction from `hook` with [emit_tracked](crate::emit_tracked),
+///    optionally with a short window so it expires quickly if it can't be applied.
+/// 2. `cbak` is called once the first transaction was applied or has expired.
+///    Its `EmitParentTxnID`, available with [otxn_emit_parent_txn_id], is the
+///    transaction that originally triggered the hook, and [otxn_id] is the emitted
+///    transaction itself.
+/// 3. Emit the second transaction from `cbak` with
+///    [emit_after_tracked](crate::emit_after_tracked), which only emits it if the first
+///    one was applied. Its default window starts in the ledger after the first one.
+///
+/// # Example
+/// ```
+/// let xrp_payment_txn_builder = XrpPaymentBuilder::new(Drops(1000), &otxn_account, None, None)
+///     .with_ledger_window(LedgerWindow::current(2));
+/// ```
+#[derive(Clone, Copy)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct LedgerWindow {
+    /// First ledger in which the transaction can be applied
+    pub first: LedgerSequence,
+    /// Last ledger in which the transaction can be applied
+    pub last: LedgerSequence,
+}
+
+impl LedgerWindow {
+    /// Number of ledgers in the window used by builders by default
+    pub const DEFAULT_LEDGER_COUNT: u32 = 5;
+
+    /// A window of `ledger_count` ledgers starting from the next ledger, which is the
+    /// earliest ledger an emitted transaction can be applied in. A `ledger_count` of 0 is
+    /// taken as 1.
+    #[inline(always)]
+    pub fn current(ledger_count: u32) -> Self {
+        Self::after(ledger_seq(), ledger_count)
+    }
+
+    /// A window of `ledger_count` ledgers starting from the ledger after `ledger_sequence`.
+    /// A `ledger_count` of 0 is taken as 1, since a window can't be empty.
+    #[inline(always)]
+    pub fn after(ledger_sequence: LedgerSequence, ledger_count: u32) -> Self {
+        let ledger_count = if ledger_count == 0 { 1 } else { ledger_count };
+
+        Self {
+            first: ledger_sequence.next(),
+            last: ledger_sequence + ledger_count,
+        }
+    }
+
+    /// Whether the transaction can be applied in the ledger `ledger_sequence`
+    #[inline(always)]
+    pub fn contains(&self, ledger_sequence: LedgerSequence) -> bool {
+        self.first <= ledger_sequence && ledger_sequence <= self.last
+    }
+
+    /// Number of ledgers in the window, 0 if `last` is before `first`
+    #[inline(always)]
+    pub fn ledger_count(&self) -> u32 {
+        match self.last.ledgers_since(self.first) {
+            Some(ledgers) => ledgers + 1,
+            None => 0,
         }
     }
 }
@@ -485,20 +835,163 @@ impl TransactionBuilder<270> for XrpPaymentBuilder<'_> {
     const TXN_TYPE: TxnType = TxnType::Payment;
 
     #[inline(always)]
-    fn build(
+    fn build<'b>(
         &self,
-        uninitialized_buffer: &mut [MaybeUninit<u8>; XrpPaymentBuilder::TXN_LEN],
-    ) -> Result<()> {
-        let current_ledger_sequence = ledger_seq() as u32;
-        let hook_account = match hook_account() {
-            Err(e) => return Err(e),
-            Ok(acc) => acc,
+        uninitialized_buffer: &'b mut [MaybeUninit<u8>; XrpPaymentBuilder::TXN_LEN],
+    ) -> Result<&'b [u8]> {
+        self.build_with(&ExternHost, uninitialized_buffer)
+    }
+}
+
+/// Byte length of an XRP payment without its emit details
+const XRP_PAYMENT_FIELDS_LEN: usize = 132;
+
+/// Where the destination tag, the last field of [XRP_PAYMENT_TEMPLATE] that is written
+/// by [TransactionBuffer::put_xrp_payment_tags], ends
+const XRP_PAYMENT_TAGS_END: usize = 23;
+
+/// The bytes of an XRP payment before its emit details, with every value that differs
+/// between payments encoded as 0, so that [XrpPaymentBuilder] copies the constant bytes at
+/// once and only encodes the rest.
+const XRP_PAYMENT_TEMPLATE: [u8; XRP_PAYMENT_FIELDS_LEN] = {
+    let mut template = [0; XRP_PAYMENT_FIELDS_LEN];
+    let txn_type = (TxnType::Payment as u16).to_be_bytes();
+    let flags = c::tfCANONICAL.to_be_bytes();
+
+    template[0] = FieldCode::TransactionType as u8;
+    template[1] = txn_type[0];
+    template[2] = txn_type[1];
+    template[3] = 0x20 + FieldCode::Flags as u8;
+    template[4] = flags[0];
+    template[5] = flags[1];
+    template[6] = flags[2];
+    template[7] = flags[3];
+    template[8] = 0x20 + FieldCode::SourceTag as u8; // source tag at 9
+    template[13] = 0x20 + FieldCode::Sequence as u8; // sequence is always 0
+    template[18] = 0x20 + FieldCode::DestinationTag as u8; // destination tag at 19
+    template[23] = 0x20;
+    template[24] = FieldCode::FirstLedgerSequence as u8; // first ledger sequence at 25
+    template[29] = 0x20;
+    template[30] = FieldCode::LastLedgerSequence as u8; // last ledger sequence at 31
+    template[35] = 0x60 + AmountType::Amount as u8; // amount at 36
+    template[36] = 0x40;
+    template[44] = 0x60 + AmountType::Fee as u8; // fee at 45, encoded last
+    template[45] = 0x40;
+    template[53] = 0x73; // null signing public key
+    template[54] = 0x21;
+    template[88] = 0x80 + AccountType::Account as u8; // source account at 90
+    template[89] = 0x14;
+    template[110] = 0x80 + AccountType::Destination as u8; // destination account at 112
+    template[111] = 0x14;
+    template
+};
+
+impl XrpPaymentBuilder<'_> {
+    /// Builds the transaction like [build](TransactionBuilder::build), but with the
+    /// ledger sequence, hook account, emit details and fee taken from `env`.
+    #[inline(always)]
+    pub fn build_with<'b>(
+        &self,
+        env: &impl HostEnv,
+        uninitialized_buffer: &'b mut [MaybeUninit<u8>; XrpPaymentBuilder::TXN_LEN],
+    ) -> Result<&'b [u8]> {
+        let ledger_window = match self.ledger_window {
+            Some(ledger_window) => ledger_window,
+            None => LedgerWindow::after(env.ledger_seq(), LedgerWindow::DEFAULT_LEDGER_COUNT),
         };
+        let hook_account = env.hook_account()?;
         let mut txn_buffer = TransactionBuffer {
             buf: uninitialized_buffer,
             pos: 0,
         };
 
+        // field headers and null signing public key after the tags
+        txn_buffer.copy_xrp_payment_template(); // pos = 132
+
+        // the tags and the fields before them, moved up by 5 bytes for every tag left out
+        let start = txn_buffer.put_xrp_payment_tags(self.src_tag, self.dest_tag);
+        txn_buffer.put_u32_at(25, ledger_window.first.0);
+        txn_buffer.put_u32_at(31, ledger_window.last.0);
+        txn_buffer.put_u64_at(
+            36,
+            0x4000_0000_0000_0000 | (self.drops.0 & 0x3FFF_FFFF_FFFF_FFFF),
+        );
+        txn_buffer.put_account_id_at(90, &hook_account);
+        txn_buffer.put_account_id_at(112, self.to_address);
+
+        // transaction metadata
+        env.insert_etxn_details(txn_buffer.next_mut(138))?;
+        txn_buffer.pos += 138; // pos = 270
+
+        // encode fee because we have the full transaction now
+        let fee = env.etxn_fee_base(txn_buffer.uninit_from(start))?;
+        txn_buffer.encode_fee_at(44, fee);
+
+        Ok(txn_buffer.into_built(start))
+    }
+}
+
+impl<'a> TrustlinePaymentBuilder<'a> {
+    /// Creates a new builder for a payment of an issued amount.
+    #[inline(always)]
+    pub fn new(
+        amount: &'a [u8; ISSUED_AMOUNT_LEN],
+        to_address: &'a AccountId,
+        dest_tag: Option<DestTag>,
+        src_tag: Option<SrcTag>,
+    ) -> Self {
+        Self {
+            amount,
+            to_address,
+            dest_tag,
+            src_tag,
+            ledger_window: None,
+        }
+    }
+
+    /// Sets the ledgers in which the transaction is valid.
+    /// Defaults to [LedgerWindow::current] with [LedgerWindow::DEFAULT_LEDGER_COUNT] ledgers.
+    #[inline(always)]
+    pub fn with_ledger_window(mut self, ledger_window: LedgerWindow) -> Self {
+        self.ledger_window = Some(ledger_window);
+        self
+    }
+}
+
+impl TransactionBuilder<310> for TrustlinePaymentBuilder<'_> {
+    const TXN_TYPE: TxnType = TxnType::Payment;
+
+    #[inline(always)]
+    fn build<'b>(
+        &self,
+        uninitialized_buffer: &'b mut [MaybeUninit<u8>; TrustlinePaymentBuilder::TXN_LEN],
+    ) -> Result<&'b [u8]> {
+        self.build_with(&ExternHost, uninitialized_buffer)
+    }
+}
+
+impl TrustlinePaymentBuilder<'_> {
+    /// Builds the transaction like [build](TransactionBuilder::build), but with the
+    /// ledger sequence, hook account, emit details and fee taken from `env`.
+    #[inline(always)]
+    pub fn build_with<'b>(
+        &self,
+        env: &impl HostEnv,
+        uninitialized_buffer: &'b mut [MaybeUninit<u8>; TrustlinePaymentBuilder::TXN_LEN],
+    ) -> Result<&'b [u8]> {
+        let ledger_window = match self.ledger_window {
+            Some(ledger_window) => ledger_window,
+            None => LedgerWindow::after(env.ledger_seq(), LedgerWindow::DEFAULT_LEDGER_COUNT),
+        };
+        let hook_account = env.hook_account()?;
+        // the transaction ends with the buffer, so it starts 5 bytes later for every tag
+        // left out
+        let start = 5 * (self.src_tag.is_none() as usize + self.dest_tag.is_none() as usize);
+        let mut txn_buffer = TransactionBuffer {
+            buf: uninitialized_buffer,
+            pos: start,
+        };
+
         // transaction type
         txn_buffer.encode_txn_type(Self::TXN_TYPE); // pos = 3
 
@@ -506,71 +999,113 @@ impl TransactionBuilder<270> for XrpPaymentBuilder<'_> {
         txn_buffer.encode_u32(c::tfCANONICAL, FieldCode::Flags.into()); // pos = 8
 
         // source tag
-        txn_buffer.encode_u32(self.src_tag, FieldCode::SourceTag.into()); // pos = 13
+        if let Some(SrcTag(src_tag)) = self.src_tag {
+            txn_buffer.encode_u32(src_tag, FieldCode::SourceTag.into()); // pos = 13
+        }
 
         // sequence
         txn_buffer.encode_u32(0, FieldCode::Sequence.into()); // pos = 18
 
         // destination tag
-        txn_buffer.encode_u32(self.dest_tag, FieldCode::DestinationTag.into()); // pos = 23
+        if let Some(DestTag(dest_tag)) = self.dest_tag {
+            txn_buffer.encode_u32(dest_tag, FieldCode::DestinationTag.into()); // pos = 23
+        }
 
         // first ledger sequence
-        txn_buffer.encode_u32_with_field_id(
-            current_ledger_sequence + 1,
-            FieldCode::FirstLedgerSequence.into(),
-        ); // pos = 29
+        txn_buffer
+            .encode_u32_with_field_id(ledger_window.first.0, FieldCode::FirstLedgerSequence.into()); // pos = 29
 
         // last ledger sequence
-        txn_buffer.encode_u32_with_field_id(
-            current_ledger_sequence + 5,
-            FieldCode::LastLedgerSequence.into(),
-        ); // pos = 35
+        txn_buffer
+            .encode_u32_with_field_id(ledger_window.last.0, FieldCode::LastLedgerSequence.into()); // pos = 35
 
-        // amount in drops
-        txn_buffer.encode_drops(self.drops, AmountType::Amount); // pos = 44
+        // issued amount
+        txn_buffer.encode_issued_amount(self.amount, AmountType::Amount); // pos = 84
 
         // fee in drops (fee will be calculated at the end, but we need to reserve space for it)
         let fee_pos = txn_buffer.pos;
-        txn_buffer.encode_drops(0, AmountType::Fee); // pos = 53
+        txn_buffer.encode_drops(0, AmountType::Fee); // pos = 93
 
         // signing public key, but it is always null
-        txn_buffer.encode_signing_pubkey_as_null(); // pos = 88
+        txn_buffer.encode_signing_pubkey_as_null(); // pos = 128
 
         // source account
-        txn_buffer.encode_account(&hook_account, AccountType::Account); // pos = 110
+        txn_buffer.encode_account(&hook_account, AccountType::Account); // pos = 150
 
         // destination account
-        txn_buffer.encode_account(self.to_address, AccountType::Destination); // pos = 132
+        txn_buffer.encode_account(self.to_address, AccountType::Destination); // pos = 172
 
-        let buf_mut_ptr = txn_buffer.buf.as_mut_ptr();
         // transaction metadata
-        let insert_etxn_details_from_ptr_result: Result<u64> =
-            insert_etxn_details_from_ptr(unsafe { buf_mut_ptr.add(txn_buffer.pos) as u32 }, 138);
-        match insert_etxn_details_from_ptr_result {
-            Err(e) => return Err(e),
-            Ok(_) => {}
-        }
-        txn_buffer.pos += 138; // pos = 270
+        env.insert_etxn_details(txn_buffer.next_mut(138))?;
+        txn_buffer.pos += 138; // pos = 310
 
         // encode fee because we have the full transaction now
-        let fee = match etxn_fee_base_from_ptr(buf_mut_ptr, XrpPaymentBuilder::TXN_LEN) {
-            Err(e) => return Err(e),
-            Ok(fee) => fee,
-        };
+        let fee = env.etxn_fee_base(txn_buffer.uninit_from(start))?;
+        txn_buffer.encode_fee_at(fee_pos, fee);
 
-        unsafe {
-            TransactionBuffer::<{ XrpPaymentBuilder::TXN_LEN }>::encode_drops_at_buf_ptr(
-                buf_mut_ptr,
-                fee_pos,
-                fee,
-                AmountType::Fee,
-            )
-        };
-
-        Ok(())
+        Ok(txn_buffer.into_built(start))
     }
 }
 
+/// Builds an XRP payment into `buf_out` like `PREPARE_PAYMENT_SIMPLE` in `macro.h`,
+/// using [XrpPaymentBuilder]. Evaluates to the [Result] of building it.
+///
+/// `buf_out` has to be a buffer from [XrpPaymentBuilder::uninit_buffer], which is as
+/// long as `PREPARE_PAYMENT_SIMPLE_SIZE` for hooks with a `cbak` function.
+/// `drops` and both tags are plain `u32` and `u64` values like in `macro.h`, so the tags
+/// are always set and the transaction takes the whole buffer.
+///
+/// # Example
+/// ```
+/// let mut tx = XrpPaymentBuilder::uninit_buffer();
+/// prepare_payment_simple!(tx, 1000, &otxn_account, 0, 0).unwrap_line_number();
+/// let txn_hash = emit(&tx).unwrap_line_number();
+/// ```
+#[macro_export]
+macro_rules! prepare_payment_simple {
+    ($buf_out:expr, $drops:expr, $to_address:expr, $dest_tag:expr, $src_tag:expr) => {
+        $crate::TransactionBuilder::build(
+            &$crate::XrpPaymentBuilder::new(
+                $crate::Drops($drops),
+                $to_address,
+                ::core::option::Option::Some($crate::DestTag($dest_tag)),
+                ::core::option::Option::Some($crate::SrcTag($src_tag)),
+            ),
+            &mut $buf_out,
+        )
+    };
+}
+
+/// Builds a payment of an issued amount into `buf_out` like
+/// `PREPARE_PAYMENT_SIMPLE_TRUSTLINE` in `macro.h`, using [TrustlinePaymentBuilder].
+/// Evaluates to the [Result] of building it.
+///
+/// `buf_out` has to be a buffer from [TrustlinePaymentBuilder::uninit_buffer], which is one
+/// byte longer than `PREPARE_PAYMENT_SIMPLE_TRUSTLINE_SIZE` for hooks with a `cbak` function,
+/// and `tlamt` the 48 bytes long serialized amount. Like with [prepare_payment_simple!],
+/// both tags are always set.
+///
+/// # Example
+/// ```
+/// let mut tx = TrustlinePaymentBuilder::uninit_buffer();
+/// prepare_payment_simple_trustline!(tx, &amount, &otxn_account, 0, 0).unwrap_line_number();
+/// let txn_hash = emit(&tx).unwrap_line_number();
+/// ```
+#[macro_export]
+macro_rules! prepare_payment_simple_trustline {
+    ($buf_out:expr, $tlamt:expr, $to_address:expr, $dest_tag:expr, $src_tag:expr) => {
+        $crate::TransactionBuilder::build(
+            &$crate::TrustlinePaymentBuilder::new(
+                $tlamt,
+                $to_address,
+                ::core::option::Option::Some($crate::DestTag($dest_tag)),
+                ::core::option::Option::Some($crate::SrcTag($src_tag)),
+            ),
+            &mut $buf_out,
+        )
+    };
+}
+
 impl From<FieldCode> for u8 {
     #[inline(always)]
     fn from(field_code: FieldCode) -> Self {
@@ -584,7 +1119,11 @@ mod tests {
 
     use wasm_bindgen_test::wasm_bindgen_test;
 
-    use crate::{AccountType, AmountType, TransactionBuffer, ACC_ID_LEN};
+    use crate::fixtures::{DESTINATION, HOOK_ACCOUNT};
+    use crate::{
+        AccountId, AccountSetFlag, AccountType, AmountType, FieldId, PublicKey, TransactionBuffer,
+        TrustSetFlags, ISSUED_AMOUNT_LEN,
+    };
 
     #[wasm_bindgen_test]
     fn can_encode_transaction_type() {
@@ -612,6 +1151,7 @@ mod tests {
             TxnType::TrustSet,
             TxnType::AccountDelete,
             TxnType::HookSet,
+            TxnType::Invoke,
             TxnType::Amendment,
             TxnType::Fee,
             TxnType::UnlModify,
@@ -685,11 +1225,40 @@ mod tests {
         );
     }
 
+    #[wasm_bindgen_test]
+    fn can_encode_issued_amount() {
+        let mut amount = [0; ISSUED_AMOUNT_LEN];
+        for (i, byte) in amount.iter_mut().enumerate() {
+            *byte = i as u8 + 1;
+        }
+        let mut uninitialized_buffer: [MaybeUninit<u8>; 60] = MaybeUninit::uninit_array();
+        for i in 0..60 {
+            unsafe {
+                uninitialized_buffer
+                    .get_unchecked_mut(i)
+                    .as_mut_ptr()
+                    .write(0);
+            }
+        }
+        let mut txn_buffer = TransactionBuffer {
+            buf: &mut uninitialized_buffer,
+            pos: 3,
+        };
+        txn_buffer.encode_issued_amount(&amount, AmountType::Amount);
+
+        assert_eq!(txn_buffer.pos, 52);
+        let buffer = unsafe { MaybeUninit::array_assume_init(uninitialized_buffer) };
+        assert_eq!(buffer[..3], [0, 0, 0]);
+        assert_eq!(buffer[3], 0x60 + AmountType::Amount as u8);
+        assert_eq!(buffer[4..52], amount);
+        assert_eq!(buffer[52..], [0; 8]);
+    }
+
     #[wasm_bindgen_test]
     fn can_encode_account() {
-        let account: [u8; ACC_ID_LEN] = [
+        let account = AccountId([
             1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20,
-        ];
+        ]);
         let mut uninitialized_buffer: [MaybeUninit<u8>; 270] = MaybeUninit::uninit_array();
         for i in 0..270 {
             unsafe {
@@ -983,6 +1552,88 @@ mod tests {
         )
     }
 
+    #[wasm_bindgen_test]
+    fn writes_every_byte_of_the_null_signing_pubkey() {
+        let mut uninitialized_buffer = [MaybeUninit::new(0xAA); 40];
+        let mut txn_buffer = TransactionBuffer::new(&mut uninitialized_buffer);
+        txn_buffer.encode_signing_pubkey_as_null();
+
+        assert_eq!(txn_buffer.pos(), 35);
+        let buffer = unsafe { MaybeUninit::array_assume_init(uninitialized_buffer) };
+        assert_eq!(buffer[..2], [0x73, 0x21]);
+        assert_eq!(buffer[2..35], [0; 33]);
+        assert_eq!(buffer[35..], [0xAA; 5]);
+    }
+
+    #[wasm_bindgen_test]
+    fn encodes_signing_pubkeys() {
+        let public_key = PublicKey(crate::hex!(
+            "03EE83BB432547885C219634A1BC407A9DB0474145D69737D09CCDC63E1DEE7FE3"
+        ));
+        let mut uninitialized_buffer = [MaybeUninit::new(0xAA); 40];
+        let mut txn_buffer = TransactionBuffer::new(&mut uninitialized_buffer);
+        txn_buffer.pos = 3;
+        txn_buffer.encode_signing_pubkey(&public_key);
+
+        assert_eq!(txn_buffer.pos(), 38);
+        let buffer = unsafe { MaybeUninit::array_assume_init(uninitialized_buffer) };
+        assert_eq!(buffer[..5], [0xAA, 0xAA, 0xAA, 0x73, 0x21]);
+        assert_eq!(buffer[5..38], public_key.0);
+        assert_eq!(buffer[38..], [0xAA; 2]);
+        let decoded = crate::decode::decode(&buffer[3..38]);
+        assert_eq!(
+            decoded.field(FieldId::SigningPubKey),
+            Some(&public_key.0[..])
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn encodes_flags() {
+        let mut uninitialized_buffer = [MaybeUninit::new(0xAA); 5];
+        let mut txn_buffer = TransactionBuffer::new(&mut uninitialized_buffer);
+        txn_buffer.encode_flags(TrustSetFlags::FULLY_CANONICAL_SIG | TrustSetFlags::SET_AUTH);
+
+        assert_eq!(txn_buffer.pos(), 5);
+        let buffer = unsafe { MaybeUninit::array_assume_init(uninitialized_buffer) };
+        assert_eq!(
+            crate::decode::decode(&buffer).u32(FieldId::Flags),
+            Some(0x8001_0000)
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn encodes_account_set_flags() {
+        let mut uninitialized_buffer = [MaybeUninit::new(0xAA); 12];
+        let mut txn_buffer = TransactionBuffer::new(&mut uninitialized_buffer);
+        txn_buffer.encode_set_flag(AccountSetFlag::DepositAuth);
+        txn_buffer.encode_clear_flag(AccountSetFlag::DisallowIncomingRemit);
+
+        assert_eq!(txn_buffer.pos(), 12);
+        let buffer = unsafe { MaybeUninit::array_assume_init(uninitialized_buffer) };
+        let decoded = crate::decode::decode(&buffer);
+        assert_eq!(decoded.u32(FieldId::SetFlag), Some(9));
+        assert_eq!(decoded.u32(FieldId::ClearFlag), Some(16));
+    }
+
+    #[wasm_bindgen_test]
+    fn writes_the_null_signing_pubkey_at_every_alignment() {
+        for pos in 0..8 {
+            let mut uninitialized_buffer = [MaybeUninit::new(0xAA); 48];
+            let mut txn_buffer = TransactionBuffer::new(&mut uninitialized_buffer);
+            txn_buffer.pos = pos;
+            txn_buffer.encode_signing_pubkey_as_null();
+
+            let mut expected = [0xAA; 48];
+            expected[pos] = 0x73;
+            expected[pos + 1] = 0x21;
+            expected[pos + 2..pos + 35].fill(0);
+            assert_eq!(
+                unsafe { MaybeUninit::array_assume_init(uninitialized_buffer) },
+                expected
+            );
+        }
+    }
+
     #[wasm_bindgen_test]
     fn can_encode_signing_pupkey_as_null() {
         let mut uninitialized_buffer: [MaybeUninit<u8>; 270] = MaybeUninit::uninit_array();
@@ -1017,4 +1668,381 @@ mod tests {
             ]
         )
     }
+
+    #[wasm_bindgen_test]
+    fn trustline_payment_buffer_fits_the_emit_details() {
+        use super::*;
+
+        let mut uninitialized_buffer = TrustlinePaymentBuilder::uninit_buffer();
+        let mut txn_buffer = TransactionBuffer {
+            buf: &mut uninitialized_buffer,
+            pos: 0,
+        };
+        txn_buffer.encode_txn_type(TxnType::Payment);
+        txn_buffer.encode_u32(c::tfCANONICAL, FieldCode::Flags.into());
+        txn_buffer.encode_u32(0, FieldCode::SourceTag.into());
+        txn_buffer.encode_u32(0, FieldCode::Sequence.into());
+        txn_buffer.encode_u32(0, FieldCode::DestinationTag.into());
+        txn_buffer.encode_u32_with_field_id(0, FieldCode::FirstLedgerSequence.into());
+        txn_buffer.encode_u32_with_field_id(0, FieldCode::LastLedgerSequence.into());
+        txn_buffer.encode_issued_amount(&[0; ISSUED_AMOUNT_LEN], AmountType::Amount);
+        txn_buffer.encode_drops(0, AmountType::Fee);
+        txn_buffer.encode_signing_pubkey_as_null();
+        txn_buffer.encode_account(&AccountId::ZERO, AccountType::Account);
+        txn_buffer.encode_account(&AccountId::ZERO, AccountType::Destination);
+
+        // the emit details take the rest of the buffer
+        assert_eq!(txn_buffer.pos + 138, TrustlinePaymentBuilder::TXN_LEN);
+    }
+
+    #[wasm_bindgen_test]
+    fn xrp_payment_matches_snapshot() {
+        use super::*;
+
+        crate::mock::reset();
+        crate::mock::set_hook_account(HOOK_ACCOUNT);
+        crate::mock::set_ledger_seq(100);
+        crate::mock::set_otxn_id([3; HASH_LEN]);
+        let _ = EmitReservation::new(1).unwrap_line_number();
+
+        let mut buffer = XrpPaymentBuilder::uninit_buffer();
+        assert!(XrpPaymentBuilder::new(
+            Drops(1000),
+            &DESTINATION,
+            Some(DestTag(7)),
+            Some(SrcTag(8))
+        )
+        .build(&mut buffer)
+        .is_ok());
+
+        crate::snapshot::assert_snapshot(
+            &unsafe { MaybeUninit::array_assume_init(buffer) },
+            "
+            120000
+            2280000000
+            2300000008
+            2400000000
+            2E00000007
+            201A00000065
+            201B00000069
+            6140000000000003E8
+            68400000000000000A
+            7321000000000000000000000000000000000000000000000000000000000000000000
+            81140101010101010101010101010101010101010101
+            83140202020202020202020202020202020202020202
+            ED202E000000013D00000000000000015B03030303030303030303030303030303030303030303030303030303030303035C00000000000000000000000000000000000000000000000000000000000000008A1401010101010101010101010101010101010101015D0000000000000000000000000000000000000000000000000000000000000000E1
+            ",
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn xrp_payment_template_matches_the_encoders() {
+        use super::*;
+
+        let mut buffer = XrpPaymentBuilder::uninit_buffer();
+        let mut txn_buffer = TransactionBuffer::new(&mut buffer);
+        txn_buffer.encode_txn_type(TxnType::Payment);
+        txn_buffer.encode_u32(c::tfCANONICAL, FieldCode::Flags.into());
+        txn_buffer.encode_u32(0, FieldCode::SourceTag.into());
+        txn_buffer.encode_u32(0, FieldCode::Sequence.into());
+        txn_buffer.encode_u32(0, FieldCode::DestinationTag.into());
+        txn_buffer.encode_u32_with_field_id(0, FieldCode::FirstLedgerSequence.into());
+        txn_buffer.encode_u32_with_field_id(0, FieldCode::LastLedgerSequence.into());
+        txn_buffer.encode_drops(0, AmountType::Amount);
+        txn_buffer.encode_drops(0, AmountType::Fee);
+        txn_buffer.encode_signing_pubkey_as_null();
+        txn_buffer.encode_account(&AccountId::ZERO, AccountType::Account);
+        txn_buffer.encode_account(&AccountId::ZERO, AccountType::Destination);
+        assert_eq!(txn_buffer.pos(), XRP_PAYMENT_FIELDS_LEN);
+
+        let encoded = unsafe {
+            core::slice::from_raw_parts(buffer.as_ptr() as *const u8, XRP_PAYMENT_FIELDS_LEN)
+        };
+        assert_eq!(encoded, XRP_PAYMENT_TEMPLATE);
+    }
+
+    #[wasm_bindgen_test]
+    fn trustline_payment_matches_snapshot() {
+        use super::*;
+
+        crate::mock::reset();
+        crate::mock::set_hook_account(HOOK_ACCOUNT);
+        crate::mock::set_ledger_seq(100);
+        crate::mock::set_otxn_id([3; HASH_LEN]);
+        let _ = EmitReservation::new(1).unwrap_line_number();
+
+        let mut amount = [4; ISSUED_AMOUNT_LEN];
+        amount[0] = 0xD4;
+        let mut buffer = TrustlinePaymentBuilder::uninit_buffer();
+        assert!(TrustlinePaymentBuilder::new(
+            &amount,
+            &DESTINATION,
+            Some(DestTag(7)),
+            Some(SrcTag(8))
+        )
+        .build(&mut buffer)
+        .is_ok());
+
+        crate::snapshot::assert_snapshot(
+            &unsafe { MaybeUninit::array_assume_init(buffer) },
+            "
+            120000
+            2280000000
+            2300000008
+            2400000000
+            2E00000007
+            201A00000065
+            201B00000069
+            61D40404040404040404040404040404040404040404040404040404040404040404040404040404040404040404040404
+            68400000000000000A
+            7321000000000000000000000000000000000000000000000000000000000000000000
+            81140101010101010101010101010101010101010101
+            83140202020202020202020202020202020202020202
+            ED202E000000013D00000000000000015B03030303030303030303030303030303030303030303030303030303030303035C00000000000000000000000000000000000000000000000000000000000000008A1401010101010101010101010101010101010101015D0000000000000000000000000000000000000000000000000000000000000000E1
+            ",
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn opens_ledger_windows_after_the_current_ledger() {
+        use super::*;
+
+        let window = LedgerWindow::after(LedgerSequence(100), 5);
+        assert_eq!(window.first, LedgerSequence(101));
+        assert_eq!(window.last, LedgerSequence(105));
+        assert_eq!(window.ledger_count(), 5);
+        assert!(!window.contains(LedgerSequence(100)));
+        assert!(window.contains(LedgerSequence(101)) && window.contains(LedgerSequence(105)));
+        assert!(!window.contains(LedgerSequence(106)));
+
+        crate::mock::reset();
+        crate::mock::set_ledger_seq(200);
+        assert_eq!(LedgerWindow::current(1).first, LedgerSequence(201));
+        assert_eq!(LedgerWindow::current(1).last, LedgerSequence(201));
+        assert_eq!(LedgerWindow::current(0).last, LedgerSequence(201));
+        assert_eq!(
+            LedgerWindow::after(LedgerSequence(100), 0).ledger_count(),
+            1
+        );
+        let empty = LedgerWindow {
+            first: LedgerSequence(10),
+            last: LedgerSequence(9),
+        };
+        assert_eq!(empty.ledger_count(), 0);
+    }
+
+    #[wasm_bindgen_test]
+    fn builds_with_a_hook_ctx() {
+        use super::*;
+
+        crate::mock::reset();
+        crate::mock::set_hook_account(HOOK_ACCOUNT);
+        crate::mock::set_ledger_seq(100);
+        let ctx = crate::HookCtx::load(0).unwrap_line_number();
+        // the context keeps the values it read
+        crate::mock::set_ledger_seq(300);
+        let _ = EmitReservation::new(2).unwrap_line_number();
+
+        for drops in [1000, 2000] {
+            let mut buffer = XrpPaymentBuilder::uninit_buffer();
+            let tx = XrpPaymentBuilder::new(Drops(drops), &DESTINATION, None, None)
+                .build_with(&ctx, &mut buffer)
+                .unwrap_line_number();
+
+            let tx = crate::decode::decode(&tx[..122]);
+            assert_eq!(tx.u32(FieldId::FirstLedgerSequence), Some(101));
+            assert_eq!(tx.drops(FieldId::Amount), Some(drops));
+            assert_eq!(tx.account(FieldId::Account), Some(HOOK_ACCOUNT));
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn builds_with_an_injected_host() {
+        use super::*;
+        use crate::fixtures::ISSUER;
+
+        struct FakeHost;
+
+        impl HostEnv for FakeHost {
+            fn ledger_seq(&self) -> LedgerSequence {
+                LedgerSequence(200)
+            }
+
+            fn hook_account(&self) -> Result<AccountId> {
+                Ok(ISSUER)
+            }
+
+            fn etxn_fee_base(&self, tx_blob: &[MaybeUninit<u8>]) -> Result<u64> {
+                Ok(tx_blob.len() as u64)
+            }
+
+            fn insert_etxn_details(&self, emit_details: &mut [MaybeUninit<u8>]) -> Result<u64> {
+                emit_details.fill(MaybeUninit::new(0xED));
+                Ok(emit_details.len() as u64)
+            }
+        }
+
+        // nothing is reserved on the mock host, so its etxn_details would fail
+        crate::mock::reset();
+
+        let mut buffer = XrpPaymentBuilder::uninit_buffer();
+        assert!(XrpPaymentBuilder::new(
+            Drops(1000),
+            &DESTINATION,
+            Some(DestTag(7)),
+            Some(SrcTag(8))
+        )
+        .build_with(&FakeHost, &mut buffer)
+        .is_ok());
+
+        let buffer = unsafe { MaybeUninit::array_assume_init(buffer) };
+        let tx = crate::decode::decode(&buffer[..132]);
+        assert_eq!(tx.u32(FieldId::FirstLedgerSequence), Some(201));
+        assert_eq!(tx.u32(FieldId::LastLedgerSequence), Some(205));
+        assert_eq!(tx.drops(FieldId::Fee), Some(270));
+        assert_eq!(tx.account(FieldId::Account), Some(ISSUER));
+        assert_eq!(buffer[132..], [0xED; 138]);
+    }
+
+    #[wasm_bindgen_test]
+    fn computes_the_fee_on_the_buffer_in_place() {
+        use core::cell::Cell;
+
+        use super::*;
+
+        // Checks that the fee is asked for the buffer being built, once it is complete
+        // but for its fee, and records where that buffer is
+        struct FeeHost {
+            tx_blob: Cell<*const MaybeUninit<u8>>,
+        }
+
+        impl HostEnv for FeeHost {
+            fn ledger_seq(&self) -> LedgerSequence {
+                LedgerSequence(200)
+            }
+
+            fn hook_account(&self) -> Result<AccountId> {
+                Ok(HOOK_ACCOUNT)
+            }
+
+            fn etxn_fee_base(&self, tx_blob: &[MaybeUninit<u8>]) -> Result<u64> {
+                self.tx_blob.set(tx_blob.as_ptr());
+                let tx = unsafe { &*(tx_blob as *const [MaybeUninit<u8>] as *const [u8]) };
+                let (fields, emit_details) = tx.split_at(tx.len() - 138);
+                assert_eq!(crate::decode::decode(fields).drops(FieldId::Fee), Some(0));
+                assert!(emit_details.iter().all(|&byte| byte == 0xED));
+                Ok(12)
+            }
+
+            fn insert_etxn_details(&self, emit_details: &mut [MaybeUninit<u8>]) -> Result<u64> {
+                emit_details.fill(MaybeUninit::new(0xED));
+                Ok(emit_details.len() as u64)
+            }
+        }
+
+        let host = FeeHost {
+            tx_blob: Cell::new(core::ptr::null()),
+        };
+        let mut buffer = XrpPaymentBuilder::uninit_buffer();
+        let buffer_start = buffer.as_ptr() as *const u8;
+        let tx = XrpPaymentBuilder::new(Drops(1000), &DESTINATION, None, Some(SrcTag(8)))
+            .build_with(&host, &mut buffer)
+            .unwrap_line_number();
+        // the transaction, not the whole buffer, since the destination tag is left out
+        assert_eq!(host.tx_blob.get() as *const u8, tx.as_ptr());
+        assert_eq!(tx.as_ptr(), buffer_start.wrapping_add(5));
+        assert_eq!(
+            crate::decode::decode(&tx[..127]).drops(FieldId::Fee),
+            Some(12)
+        );
+
+        let mut amount = [4; ISSUED_AMOUNT_LEN];
+        amount[0] = 0xD4;
+        let mut buffer = TrustlinePaymentBuilder::uninit_buffer();
+        let tx = TrustlinePaymentBuilder::new(&amount, &DESTINATION, None, None)
+            .build_with(&host, &mut buffer)
+            .unwrap_line_number();
+        assert_eq!(host.tx_blob.get() as *const u8, tx.as_ptr());
+        assert_eq!(
+            crate::decode::decode(&tx[..162]).drops(FieldId::Fee),
+            Some(12)
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn leaves_out_absent_tags() {
+        use super::*;
+        use crate::decode::decode;
+
+        // Asks for a fee as high as the length of the transaction
+        struct FixedHost;
+
+        impl HostEnv for FixedHost {
+            fn ledger_seq(&self) -> LedgerSequence {
+                LedgerSequence(200)
+            }
+
+            fn hook_account(&self) -> Result<AccountId> {
+                Ok(HOOK_ACCOUNT)
+            }
+
+            fn etxn_fee_base(&self, tx_blob: &[MaybeUninit<u8>]) -> Result<u64> {
+                Ok(tx_blob.len() as u64)
+            }
+
+            fn insert_etxn_details(&self, emit_details: &mut [MaybeUninit<u8>]) -> Result<u64> {
+                emit_details.fill(MaybeUninit::new(0xED));
+                Ok(emit_details.len() as u64)
+            }
+        }
+
+        // a zero tag is still a tag
+        let tags = [
+            (Some(DestTag(0)), Some(SrcTag(8))),
+            (Some(DestTag(7)), None),
+            (None, Some(SrcTag(0))),
+            (None, None),
+        ];
+        let mut amount = [4; ISSUED_AMOUNT_LEN];
+        amount[0] = 0xD4;
+        for (dest_tag, src_tag) in tags {
+            let tags_len = 5 * (dest_tag.is_some() as usize + src_tag.is_some() as usize);
+
+            let mut buffer = XrpPaymentBuilder::uninit_buffer();
+            let tx = XrpPaymentBuilder::new(Drops(1000), &DESTINATION, dest_tag, src_tag)
+                .build_with(&FixedHost, &mut buffer)
+                .unwrap_line_number();
+            assert_eq!(tx.len(), 260 + tags_len);
+            assert_eq!(tx[tx.len() - 138..], [0xED; 138]);
+            let fields = decode(&tx[..tx.len() - 138]);
+            assert_eq!(fields.txn_type(), Some(TxnType::Payment as u16));
+            assert_eq!(fields.u32(FieldId::Flags), Some(c::tfCANONICAL));
+            assert_eq!(fields.u32(FieldId::Sequence), Some(0));
+            assert_eq!(
+                fields.u32(FieldId::DestinationTag),
+                dest_tag.map(|tag| tag.0)
+            );
+            assert_eq!(fields.u32(FieldId::SourceTag), src_tag.map(|tag| tag.0));
+            assert_eq!(fields.u32(FieldId::FirstLedgerSequence), Some(201));
+            assert_eq!(fields.u32(FieldId::LastLedgerSequence), Some(205));
+            assert_eq!(fields.drops(FieldId::Amount), Some(1000));
+            assert_eq!(fields.drops(FieldId::Fee), Some(tx.len() as u64));
+            assert_eq!(fields.account(FieldId::Account), Some(HOOK_ACCOUNT));
+            assert_eq!(fields.account(FieldId::Destination), Some(DESTINATION));
+
+            let mut buffer = TrustlinePaymentBuilder::uninit_buffer();
+            let tx = TrustlinePaymentBuilder::new(&amount, &DESTINATION, dest_tag, src_tag)
+                .build_with(&FixedHost, &mut buffer)
+                .unwrap_line_number();
+            assert_eq!(tx.len(), 300 + tags_len);
+            let fields = decode(&tx[..tx.len() - 138]);
+            assert_eq!(
+                fields.u32(FieldId::DestinationTag),
+                dest_tag.map(|tag| tag.0)
+            );
+            assert_eq!(fields.u32(FieldId::SourceTag), src_tag.map(|tag| tag.0));
+            assert_eq!(fields.field(FieldId::Amount), Some(&amount[..]));
+            assert_eq!(fields.drops(FieldId::Fee), Some(tx.len() as u64));
+        }
+    }
 }
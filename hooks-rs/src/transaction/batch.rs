@@ -0,0 +1,375 @@
+//! Emitting several transactions in one call, with what became of each of them.
+//!
+//! [emit_all] and [emit_selected] reserve, build and emit a batch of transactions and
+//! return an [EmittedBatch], which records the hash of every emitted transaction and the
+//! error of every one that failed, in the order of the builders. A failure doesn't stop
+//! the rest of the batch from being emitted.
+//!
+//! Hooks distributing payments can keep [EmittedBatch::failed_mask] in their state and
+//! pass it to [emit_selected] in a later execution, which only emits the payments that
+//! failed before.
+//!
+//! # Example
+//! ```
+//! let pending = match state::<8>(b"pending") {
+//!     Ok(mask) => u64::from_be_bytes(mask),
+//!     Err(HookError::DoesntExist) => u64::MAX,
+//!     Err(err) => return Err(err),
+//! };
+//! let batch = emit_selected(&payouts, pending)?;
+//! state_set(&batch.failed_mask().to_be_bytes(), b"pending")?;
+//! ```
+
+use super::TransactionBuilder;
+use crate::api::*;
+
+/// Maximum number of transactions in a batch for [emit_selected] and the masks of an
+/// [EmittedBatch], one bit each in a [u64]
+pub const EMITTED_BATCH_MASK_MAX_LEN: usize = 64;
+
+/// What became of one of the transactions of an [EmittedBatch]
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub enum Emission {
+    /// Emitted, with the hash of the emitted transaction
+    Emitted([u8; HASH_LEN]),
+    /// Could not be built or emitted
+    Failed(HookError),
+    /// Not selected to be emitted by [emit_selected]
+    Skipped,
+}
+
+/// What became of every transaction of a batch, in the order of the builders
+#[derive(Clone, Copy)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct EmittedBatch<const N: usize> {
+    emissions: [Emission; N],
+}
+
+impl<const N: usize> EmittedBatch<N> {
+    /// What became of each transaction
+    #[inline(always)]
+    pub fn emissions(&self) -> &[Emission; N] {
+        &self.emissions
+    }
+
+    /// The hash of the `i`th transaction, if it was emitted
+    #[inline(always)]
+    pub fn hash(&self, i: usize) -> Option<[u8; HASH_LEN]> {
+        match self.emissions.get(i) {
+            Some(Emission::Emitted(hash)) => Some(*hash),
+            _ => None,
+        }
+    }
+
+    /// Number of emitted transactions
+    #[inline(always)]
+    pub fn emitted_count(&self) -> usize {
+        let mut count = 0;
+        // guarded loop
+        let mut i = 0;
+        while {
+            max_iter(N as u32 + 1);
+            i < N
+        } {
+            if let Emission::Emitted(_) = self.emissions[i] {
+                count += 1;
+            }
+            i += 1;
+        }
+
+        count
+    }
+
+    /// The error of the first transaction that failed
+    #[inline(always)]
+    pub fn first_error(&self) -> Option<HookError> {
+        // guarded loop
+        let mut i = 0;
+        while {
+            max_iter(N as u32 + 1);
+            i < N
+        } {
+            if let Emission::Failed(err) = self.emissions[i] {
+                return Some(err);
+            }
+            i += 1;
+        }
+
+        None
+    }
+
+    /// Whether no transaction failed, although some may have been skipped
+    #[inline(always)]
+    pub fn is_complete(&self) -> bool {
+        self.first_error().is_none()
+    }
+
+    /// The emitted transactions, as bit `i` set for the `i`th transaction.
+    ///
+    /// Fails to compile for batches of more than [EMITTED_BATCH_MASK_MAX_LEN]
+    /// transactions.
+    #[inline(always)]
+    pub fn emitted_mask(&self) -> u64 {
+        const {
+            assert!(
+                N <= EMITTED_BATCH_MASK_MAX_LEN,
+                "batch is too long for a mask"
+            )
+        };
+
+        let mut mask = 0;
+        // guarded loop
+        let mut i = 0;
+        while {
+            max_iter(N as u32 + 1);
+            i < N
+        } {
+            if let Emission::Emitted(_) = self.emissions[i] {
+                mask |= 1 << i;
+            }
+            i += 1;
+        }
+
+        mask
+    }
+
+    /// The transactions that failed, as bit `i` set for the `i`th transaction, to be
+    /// retried with [emit_selected].
+    ///
+    /// Fails to compile for batches of more than [EMITTED_BATCH_MASK_MAX_LEN]
+    /// transactions.
+    #[inline(always)]
+    pub fn failed_mask(&self) -> u64 {
+        const {
+            assert!(
+                N <= EMITTED_BATCH_MASK_MAX_LEN,
+                "batch is too long for a mask"
+            )
+        };
+
+        let mut mask = 0;
+        // guarded loop
+        let mut i = 0;
+        while {
+            max_iter(N as u32 + 1);
+            i < N
+        } {
+            if let Emission::Failed(_) = self.emissions[i] {
+                mask |= 1 << i;
+            }
+            i += 1;
+        }
+
+        mask
+    }
+}
+
+/// Reserves, builds and emits several transactions in one call, returning what became
+/// of each of them in the same order as `builders`.
+///
+/// Fails as a whole only if the transactions could not be reserved. A failure to
+/// build or emit one of the transactions does not stop the rest from being emitted,
+/// so check the returned batch.
+///
+/// Since [etxn_reserve] can only be called once per hook execution, do not
+/// reserve any transactions yourself before calling this function.
+///
+/// # Example
+/// ```
+/// let half = drops / 2;
+/// let builders = [
+///     XrpPaymentBuilder::new(Drops(half), &first_recipient, None, None),
+///     XrpPaymentBuilder::new(Drops(drops - half), &second_recipient, None, None),
+/// ];
+/// let batch = match emit_all(&builders) {
+///     Ok(batch) => batch,
+///     Err(err) => {
+///         rollback(b"could not reserve txns", err.into());
+///     }
+/// };
+/// ```
+#[inline(always)]
+pub fn emit_all<const N: usize, const TXN_LEN: usize, B: TransactionBuilder<TXN_LEN>>(
+    builders: &[B; N],
+) -> Result<EmittedBatch<N>> {
+    emit_batch(builders, N as u32, u64::MAX)
+}
+
+/// Like [emit_all], but only for the builders whose bit is set in `selected`.
+///
+/// Bit `i` selects the `i`th builder. The others are [Skipped](Emission::Skipped), and
+/// nothing is reserved if none is selected.
+///
+/// Fails to compile for more than [EMITTED_BATCH_MASK_MAX_LEN] builders.
+///
+/// # Example
+/// ```
+/// // retry the payouts that failed in the previous execution
+/// let batch = emit_selected(&payouts, previous.failed_mask())?;
+/// ```
+#[inline(always)]
+pub fn emit_selected<const N: usize, const TXN_LEN: usize, B: TransactionBuilder<TXN_LEN>>(
+    builders: &[B; N],
+    selected: u64,
+) -> Result<EmittedBatch<N>> {
+    const {
+        assert!(
+            N <= EMITTED_BATCH_MASK_MAX_LEN,
+            "batch is too long for a mask"
+        )
+    };
+
+    let selected = if N == EMITTED_BATCH_MASK_MAX_LEN {
+        selected
+    } else {
+        selected & ((1 << N) - 1)
+    };
+    emit_batch(builders, selected.count_ones(), selected)
+}
+
+// Emits the `count` builders whose bit is set in `selected`. The builders after the
+// 64th, which only emit_all can have, have no bit and are always emitted.
+#[inline(always)]
+fn emit_batch<const N: usize, const TXN_LEN: usize, B: TransactionBuilder<TXN_LEN>>(
+    builders: &[B; N],
+    count: u32,
+    selected: u64,
+) -> Result<EmittedBatch<N>> {
+    let mut batch = EmittedBatch {
+        emissions: [Emission::Skipped; N],
+    };
+    if count == 0 {
+        return Ok(batch);
+    }
+
+    let mut reservation = EmitReservation::new(count)?;
+    // guarded loop
+    let mut i = 0;
+    while {
+        max_iter(N as u32 + 1);
+        i < N
+    } {
+        if i >= EMITTED_BATCH_MASK_MAX_LEN || selected & (1 << i) != 0 {
+            batch.emissions[i] = match builders[i].build_and_emit(&mut reservation) {
+                Ok(hash) => Emission::Emitted(hash),
+                Err(err) => Emission::Failed(err),
+            };
+        }
+        i += 1;
+    }
+
+    Ok(batch)
+}
+
+#[cfg(test)]
+mod tests {
+    use core::mem::MaybeUninit;
+
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::decode::decode;
+    use crate::fixtures::{DESTINATION, HOOK_ACCOUNT, ISSUER};
+    use crate::mock;
+    use crate::{TxnType, XrpPaymentBuilder};
+
+    // A payment that fails to build if `fails` is set
+    struct Payout<'a> {
+        payment: XrpPaymentBuilder<'a>,
+        fails: bool,
+    }
+
+    impl TransactionBuilder<270> for Payout<'_> {
+        const TXN_TYPE: TxnType = TxnType::Payment;
+
+        fn build<'b>(
+            &self,
+            uninitialized_buffer: &'b mut [MaybeUninit<u8>; 270],
+        ) -> Result<&'b [u8]> {
+            if self.fails {
+                return Err(HookError::InvalidArgument);
+            }
+
+            self.payment.build(uninitialized_buffer)
+        }
+    }
+
+    fn payout(destination: &AccountId, drops: u64, fails: bool) -> Payout<'_> {
+        Payout {
+            payment: XrpPaymentBuilder::new(Drops(drops), destination, None, None),
+            fails,
+        }
+    }
+
+    fn setup() {
+        mock::reset();
+        mock::set_hook_account(HOOK_ACCOUNT);
+        mock::set_ledger_seq(100);
+    }
+
+    #[wasm_bindgen_test]
+    fn records_the_hash_or_error_of_every_transaction() {
+        setup();
+        let payouts = [
+            payout(&DESTINATION, 100, false),
+            payout(&ISSUER, 200, true),
+            payout(&ISSUER, 300, false),
+        ];
+        let batch = emit_all(&payouts).unwrap_line_number();
+
+        assert_eq!(
+            batch.emissions(),
+            &[
+                Emission::Emitted([1; HASH_LEN]),
+                Emission::Failed(HookError::InvalidArgument),
+                Emission::Emitted([2; HASH_LEN]),
+            ]
+        );
+        assert_eq!(batch.hash(2), Some([2; HASH_LEN]));
+        assert_eq!(batch.hash(1), None);
+        assert_eq!(batch.emitted_count(), 2);
+        assert_eq!(batch.first_error(), Some(HookError::InvalidArgument));
+        assert!(!batch.is_complete());
+        assert_eq!(batch.emitted_mask(), 0b101);
+        assert_eq!(batch.failed_mask(), 0b010);
+        assert_eq!(mock::emitted().len(), 2);
+    }
+
+    #[wasm_bindgen_test]
+    fn only_emits_the_selected_transactions() {
+        setup();
+        let payouts = [
+            payout(&DESTINATION, 100, false),
+            payout(&ISSUER, 200, false),
+            payout(&ISSUER, 300, false),
+        ];
+        // bits past the builders are ignored
+        let batch = emit_selected(&payouts, 0b1010).unwrap_line_number();
+
+        assert_eq!(
+            batch.emissions(),
+            &[
+                Emission::Skipped,
+                Emission::Emitted([1; HASH_LEN]),
+                Emission::Skipped,
+            ]
+        );
+        assert!(batch.is_complete());
+        assert_eq!(batch.failed_mask(), 0);
+        let emitted = mock::emitted();
+        assert_eq!(emitted.len(), 1);
+        assert_eq!(decode(&emitted[0]).drops(FieldId::Amount), Some(200));
+    }
+
+    #[wasm_bindgen_test]
+    fn reserves_nothing_without_selected_transactions() {
+        setup();
+        let payouts = [payout(&DESTINATION, 100, false)];
+        let batch = emit_selected(&payouts, 0).unwrap_line_number();
+
+        assert_eq!(batch.emissions(), &[Emission::Skipped]);
+        assert_eq!(batch.emitted_count(), 0);
+        assert!(EmitReservation::new(1).is_ok());
+    }
+}
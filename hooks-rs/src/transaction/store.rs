@@ -0,0 +1,296 @@
+//! Stores into transaction buffers, used by the encoders of
+//! [TransactionBuffer](super::TransactionBuffer).
+//!
+//! By default they write through raw pointers without bounds checks, and copy 8 or 4
+//! bytes at a time with unaligned stores, so that the encoders compile to a few store
+//! instructions instead of loops or `memcpy`. With the `safe` feature they index the
+//! buffer instead, which panics rather than writing out of bounds, so the encoders can
+//! run under Miri. Both are compiled in tests to check that they write the same bytes.
+//!
+//! Longer runs of bytes are copied or zeroed with [copy_n](fast::copy_n) and
+//! [zero_n](fast::zero_n), which unroll into those stores at compile time, as a loop
+//! copying bytes could be turned back into a `memcpy` by the optimizer.
+//!
+//! All of them are `unsafe`, even in the safe implementation, so that the encoders call
+//! them the same way: `pos` and `len` must be within the buffer, and `from` and `len`
+//! within the source.
+
+use core::mem::MaybeUninit;
+
+/// Longest run of bytes [copy_n](fast::copy_n) and [zero_n](fast::zero_n) unroll
+pub(super) const MAX_UNROLLED_LEN: usize = 136;
+
+// Runs `$body` with `$offset` bound to every multiple of 8 up to `$len`, exclusive of the
+// last partial 8 bytes, as a sequence of `if`s on constants instead of a loop
+macro_rules! unroll_8 {
+    ($len:expr, |$offset:ident| $body:expr) => {
+        unroll_8!(@ $len, $offset, $body; 0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16)
+    };
+    (@ $len:expr, $offset:ident, $body:expr; $($i:literal)*) => {
+        $(
+            if ($i + 1) * 8 <= $len {
+                let $offset = $i * 8;
+                $body;
+            }
+        )*
+    };
+}
+
+#[cfg(not(feature = "safe"))]
+pub(super) use fast::*;
+#[cfg(feature = "safe")]
+pub(super) use safe::*;
+
+/// Raw pointer stores without bounds checks
+#[cfg_attr(all(feature = "safe", not(test)), allow(dead_code))]
+pub(super) mod fast {
+    use super::*;
+
+    /// Writes `byte` at `pos`
+    #[inline(always)]
+    pub(crate) unsafe fn put(buf: &mut [MaybeUninit<u8>], pos: usize, byte: u8) {
+        buf.get_unchecked_mut(pos).as_mut_ptr().write(byte);
+    }
+
+    /// Copies the 8 bytes of `src` from `from` to `pos`
+    #[inline(always)]
+    pub(crate) unsafe fn copy_8(buf: &mut [MaybeUninit<u8>], pos: usize, src: &[u8], from: usize) {
+        let u64_buf_ptr = buf.get_unchecked_mut(pos).as_mut_ptr() as *mut u64;
+        u64_buf_ptr.write_unaligned((src.as_ptr().add(from) as *const u64).read_unaligned());
+    }
+
+    /// Copies the 4 bytes of `src` from `from` to `pos`
+    #[inline(always)]
+    pub(crate) unsafe fn copy_4(buf: &mut [MaybeUninit<u8>], pos: usize, src: &[u8], from: usize) {
+        let u32_buf_ptr = buf.get_unchecked_mut(pos).as_mut_ptr() as *mut u32;
+        u32_buf_ptr.write_unaligned((src.as_ptr().add(from) as *const u32).read_unaligned());
+    }
+
+    /// Writes 8 zero bytes at `pos`
+    #[inline(always)]
+    pub(crate) unsafe fn zero_8(buf: &mut [MaybeUninit<u8>], pos: usize) {
+        (buf.get_unchecked_mut(pos).as_mut_ptr() as *mut u64).write_unaligned(0);
+    }
+
+    /// Copies the `N` bytes of `src` from `from` to `pos`, 8 bytes at a time, then 4, then
+    /// byte by byte
+    #[inline(always)]
+    pub(crate) unsafe fn copy_n<const N: usize>(
+        buf: &mut [MaybeUninit<u8>],
+        pos: usize,
+        src: &[u8],
+        from: usize,
+    ) {
+        const { assert!(N <= MAX_UNROLLED_LEN) };
+        unroll_8!(N, |offset| copy_8(buf, pos + offset, src, from + offset));
+
+        let words = N / 8 * 8;
+        if N % 8 >= 4 {
+            copy_4(buf, pos + words, src, from + words);
+        }
+        let bytes = N / 4 * 4;
+        if N % 4 >= 1 {
+            put(buf, pos + bytes, *src.get_unchecked(from + bytes));
+        }
+        if N % 4 >= 2 {
+            put(buf, pos + bytes + 1, *src.get_unchecked(from + bytes + 1));
+        }
+        if N % 4 >= 3 {
+            put(buf, pos + bytes + 2, *src.get_unchecked(from + bytes + 2));
+        }
+    }
+
+    /// Writes `N` zero bytes at `pos`, 8 bytes at a time, then 4, then byte by byte
+    #[inline(always)]
+    pub(crate) unsafe fn zero_n<const N: usize>(buf: &mut [MaybeUninit<u8>], pos: usize) {
+        const { assert!(N <= MAX_UNROLLED_LEN) };
+        unroll_8!(N, |offset| zero_8(buf, pos + offset));
+
+        let words = N / 8 * 8;
+        if N % 8 >= 4 {
+            (buf.get_unchecked_mut(pos + words).as_mut_ptr() as *mut u32).write_unaligned(0);
+        }
+        let bytes = N / 4 * 4;
+        if N % 4 >= 1 {
+            put(buf, pos + bytes, 0);
+        }
+        if N % 4 >= 2 {
+            put(buf, pos + bytes + 1, 0);
+        }
+        if N % 4 >= 3 {
+            put(buf, pos + bytes + 2, 0);
+        }
+    }
+
+    /// The `len` bytes of the buffer from `pos`
+    #[inline(always)]
+    pub(crate) unsafe fn slice_mut(
+        buf: &mut [MaybeUninit<u8>],
+        pos: usize,
+        len: usize,
+    ) -> &mut [MaybeUninit<u8>] {
+        core::slice::from_raw_parts_mut(buf.as_mut_ptr().add(pos), len)
+    }
+}
+
+/// Bounds checked stores without raw pointers
+#[cfg(any(test, feature = "safe"))]
+pub(super) mod safe {
+    use super::*;
+
+    /// Writes `byte` at `pos`
+    #[inline(always)]
+    pub(crate) unsafe fn put(buf: &mut [MaybeUninit<u8>], pos: usize, byte: u8) {
+        buf[pos].write(byte);
+    }
+
+    /// Copies the 8 bytes of `src` from `from` to `pos`
+    #[inline(always)]
+    pub(crate) unsafe fn copy_8(buf: &mut [MaybeUninit<u8>], pos: usize, src: &[u8], from: usize) {
+        copy(buf, pos, &src[from..from + 8]);
+    }
+
+    /// Copies the 4 bytes of `src` from `from` to `pos`
+    #[inline(always)]
+    pub(crate) unsafe fn copy_4(buf: &mut [MaybeUninit<u8>], pos: usize, src: &[u8], from: usize) {
+        copy(buf, pos, &src[from..from + 4]);
+    }
+
+    /// Copies the `N` bytes of `src` from `from` to `pos`
+    #[inline(always)]
+    pub(crate) unsafe fn copy_n<const N: usize>(
+        buf: &mut [MaybeUninit<u8>],
+        pos: usize,
+        src: &[u8],
+        from: usize,
+    ) {
+        copy(buf, pos, &src[from..from + N]);
+    }
+
+    /// Writes `N` zero bytes at `pos`
+    #[inline(always)]
+    pub(crate) unsafe fn zero_n<const N: usize>(buf: &mut [MaybeUninit<u8>], pos: usize) {
+        copy(buf, pos, &[0; N]);
+    }
+
+    /// The `len` bytes of the buffer from `pos`
+    #[inline(always)]
+    pub(crate) unsafe fn slice_mut(
+        buf: &mut [MaybeUninit<u8>],
+        pos: usize,
+        len: usize,
+    ) -> &mut [MaybeUninit<u8>] {
+        &mut buf[pos..pos + len]
+    }
+
+    fn copy(buf: &mut [MaybeUninit<u8>], pos: usize, bytes: &[u8]) {
+        for (slot, byte) in buf[pos..pos + bytes.len()].iter_mut().zip(bytes) {
+            slot.write(*byte);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+
+    const SRC: [u8; 24] = [
+        1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24,
+    ];
+
+    // Runs `store` on a buffer filled with 0xAA with both implementations, and checks that
+    // they wrote the same bytes
+    fn assert_same(store: impl Fn(&mut [MaybeUninit<u8>], bool)) -> [u8; 32] {
+        let mut fast = [MaybeUninit::new(0xAA); 32];
+        let mut safe = [MaybeUninit::new(0xAA); 32];
+        store(&mut fast, true);
+        store(&mut safe, false);
+
+        let fast = unsafe { MaybeUninit::array_assume_init(fast) };
+        let safe = unsafe { MaybeUninit::array_assume_init(safe) };
+        assert_eq!(fast, safe);
+
+        fast
+    }
+
+    // also run natively, so the raw pointer stores can be checked with
+    // `cargo miri test --target x86_64-unknown-linux-gnu transaction::store`
+    #[wasm_bindgen_test(unsupported = test)]
+    fn implementations_write_the_same_bytes() {
+        // at every alignment of the buffer and of the source
+        for pos in 0..8 {
+            for from in 0..8 {
+                let buf = assert_same(|buf, is_fast| unsafe {
+                    if is_fast {
+                        fast::put(buf, pos, 0x73);
+                        fast::copy_8(buf, pos + 1, &SRC, from);
+                        fast::copy_4(buf, pos + 9, &SRC, from + 8);
+                        fast::zero_n::<8>(buf, pos + 13);
+                        fast::slice_mut(buf, pos + 21, 3).fill(MaybeUninit::new(0xED));
+                    } else {
+                        safe::put(buf, pos, 0x73);
+                        safe::copy_8(buf, pos + 1, &SRC, from);
+                        safe::copy_4(buf, pos + 9, &SRC, from + 8);
+                        safe::zero_n::<8>(buf, pos + 13);
+                        safe::slice_mut(buf, pos + 21, 3).fill(MaybeUninit::new(0xED));
+                    }
+                });
+
+                assert_eq!(buf[..pos], [0xAA; 8][..pos]);
+                assert_eq!(buf[pos], 0x73);
+                assert_eq!(buf[pos + 1..pos + 13], SRC[from..from + 12]);
+                assert_eq!(buf[pos + 13..pos + 21], [0; 8]);
+                assert_eq!(buf[pos + 21..pos + 24], [0xED; 3]);
+                assert!(buf[pos + 24..].iter().all(|&byte| byte == 0xAA));
+            }
+        }
+    }
+
+    // Copies and zeroes runs of `N` bytes at an unaligned position with both
+    // implementations, and checks that they wrote exactly those bytes
+    fn assert_unrolled<const N: usize>() {
+        let src: [u8; MAX_UNROLLED_LEN] = core::array::from_fn(|i| i as u8 + 1);
+        for is_fast in [true, false] {
+            let mut buf = [MaybeUninit::new(0xAA); 2 * MAX_UNROLLED_LEN + 2];
+            unsafe {
+                if is_fast {
+                    fast::copy_n::<N>(&mut buf, 1, &src, 3);
+                    fast::zero_n::<N>(&mut buf, N + 2);
+                } else {
+                    safe::copy_n::<N>(&mut buf, 1, &src, 3);
+                    safe::zero_n::<N>(&mut buf, N + 2);
+                }
+            }
+            let buf = unsafe { MaybeUninit::array_assume_init(buf) };
+
+            assert_eq!(buf[0], 0xAA);
+            assert_eq!(buf[1..N + 1], src[3..N + 3]);
+            assert_eq!(buf[N + 1], 0xAA);
+            assert!(buf[N + 2..2 * N + 2].iter().all(|&byte| byte == 0));
+            assert!(buf[2 * N + 2..].iter().all(|&byte| byte == 0xAA));
+        }
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn unrolled_stores_write_runs_of_any_length() {
+        assert_unrolled::<1>();
+        assert_unrolled::<3>();
+        assert_unrolled::<4>();
+        assert_unrolled::<7>();
+        assert_unrolled::<8>();
+        assert_unrolled::<13>();
+        assert_unrolled::<20>();
+        assert_unrolled::<33>();
+        assert_unrolled::<48>();
+        assert_unrolled::<132>();
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    #[should_panic]
+    fn safe_stores_are_bounds_checked() {
+        let mut buf = [MaybeUninit::new(0); 8];
+        unsafe { safe::copy_8(&mut buf, 1, &SRC, 0) };
+    }
+}
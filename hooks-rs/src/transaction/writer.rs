@@ -0,0 +1,393 @@
+//! Field encoders shared by every buffer serialized objects are written into.
+//!
+//! [FieldWriter] only needs a buffer to append bytes, and encodes field headers, length
+//! prefixes and the values of integers, hashes, amounts, accounts and blobs on top of
+//! that, so encoders are written once for every kind of buffer. It is implemented by
+//! [TransactionBuffer], whose `encode_*` methods call into it and which doesn't check
+//! bounds, and by [FixedWriter], which truncates what doesn't fit, for building objects
+//! such as memos or fields for [sto_emplace](crate::sto_emplace) with bounds checks.
+//!
+//! # Example
+//! ```
+//! let mut memo = FixedWriter::<64>::new();
+//! memo.write_field_header(FieldId::Memo);
+//! memo.write_blob_field(FieldId::MemoType, b"payout")?;
+//! memo.write_blob_field(FieldId::MemoData, &payout_count.to_be_bytes())?;
+//! memo.write_object_end();
+//! ```
+
+use super::{store, TransactionBuffer};
+use crate::api::*;
+use crate::reader::VL_MAX_LEN;
+use crate::utils::FixedWriter;
+use crate::ISSUED_AMOUNT_LEN;
+
+const OBJECT_END_MARKER: u8 = 0xE1;
+const ARRAY_END_MARKER: u8 = 0xF1;
+
+/// A buffer fields of serialized objects are appended to
+pub trait FieldWriter {
+    /// Append a byte
+    fn write_byte(&mut self, byte: u8);
+
+    /// Append `bytes`
+    fn write_bytes(&mut self, bytes: &[u8]);
+
+    /// Append the `N` bytes of `bytes`. Meant for the values of fixed length fields, such
+    /// as hashes and accounts, which buffers may copy faster than slices.
+    #[inline(always)]
+    fn write_array<const N: usize>(&mut self, bytes: &[u8; N]) {
+        self.write_bytes(bytes);
+    }
+
+    /// Append `N` zero bytes
+    #[inline(always)]
+    fn write_zeros<const N: usize>(&mut self) {
+        self.write_array(&[0; N]);
+    }
+
+    /// Append the header of `field_id`, 1 to 3 bytes depending on its type and field codes
+    #[inline(always)]
+    fn write_field_header(&mut self, field_id: FieldId) {
+        self.write_header((field_id as u32 >> 16) as u8, field_id.field_code() as u8);
+    }
+
+    /// Append the header of the field with `field_code` within the serialized type
+    /// `type_code`, for field codes that have no [FieldId], like those of [AccountType]
+    #[inline(always)]
+    fn write_header(&mut self, type_code: u8, field_code: u8) {
+        match (type_code < 16, field_code < 16) {
+            (true, true) => self.write_byte((type_code << 4) | field_code),
+            (true, false) => {
+                self.write_byte(type_code << 4);
+                self.write_byte(field_code);
+            }
+            (false, true) => {
+                self.write_byte(field_code);
+                self.write_byte(type_code);
+            }
+            (false, false) => {
+                self.write_byte(0);
+                self.write_byte(type_code);
+                self.write_byte(field_code);
+            }
+        }
+    }
+
+    /// Append the 1 to 3 byte prefix of a variable length value of `len` bytes.
+    ///
+    /// Fails with [HookError::TooBig] without writing if `len` is more than
+    /// [VL_MAX_LEN].
+    #[inline(always)]
+    fn write_vl_len(&mut self, len: usize) -> Result<()> {
+        if len <= 192 {
+            self.write_byte(len as u8);
+        } else if len <= 12480 {
+            let len = len - 193;
+            self.write_byte(193 + (len >> 8) as u8);
+            self.write_byte((len & 0xFF) as u8);
+        } else if len <= VL_MAX_LEN {
+            let len = len - 12481;
+            self.write_byte(241 + (len >> 16) as u8);
+            self.write_byte(((len >> 8) & 0xFF) as u8);
+            self.write_byte((len & 0xFF) as u8);
+        } else {
+            return Err(HookError::TooBig);
+        }
+
+        Ok(())
+    }
+
+    /// Append `bytes` after their length prefix, see [write_vl_len](Self::write_vl_len)
+    #[inline(always)]
+    fn write_vl(&mut self, bytes: &[u8]) -> Result<()> {
+        self.write_vl_len(bytes.len())?;
+        self.write_bytes(bytes);
+
+        Ok(())
+    }
+
+    /// Append a `UInt8` field
+    #[inline(always)]
+    fn write_u8_field(&mut self, field_id: FieldId, value: u8) {
+        self.write_field_header(field_id);
+        self.write_byte(value);
+    }
+
+    /// Append a `UInt16` field
+    #[inline(always)]
+    fn write_u16_field(&mut self, field_id: FieldId, value: u16) {
+        self.write_field_header(field_id);
+        self.write_array(&value.to_be_bytes());
+    }
+
+    /// Append a big endian `u32`, without a field header
+    #[inline(always)]
+    fn write_u32(&mut self, value: u32) {
+        self.write_array(&value.to_be_bytes());
+    }
+
+    /// Append a `UInt32` field
+    #[inline(always)]
+    fn write_u32_field(&mut self, field_id: FieldId, value: u32) {
+        self.write_field_header(field_id);
+        self.write_u32(value);
+    }
+
+    /// Append a `UInt64` field
+    #[inline(always)]
+    fn write_u64_field(&mut self, field_id: FieldId, value: u64) {
+        self.write_field_header(field_id);
+        self.write_array(&value.to_be_bytes());
+    }
+
+    /// Append a `Hash256` field
+    #[inline(always)]
+    fn write_hash256_field(&mut self, field_id: FieldId, hash: &Hash) {
+        self.write_field_header(field_id);
+        self.write_array(hash);
+    }
+
+    /// Append an amount in XRP, without a field header. Only the lower 62 bits of
+    /// `drops` are encoded, which is more than [Drops::MAX].
+    #[inline(always)]
+    fn write_drops(&mut self, drops: Drops) {
+        // positive, and not an issued amount
+        self.write_array(
+            &((drops.0 & 0x3FFF_FFFF_FFFF_FFFF) | 0x4000_0000_0000_0000).to_be_bytes(),
+        );
+    }
+
+    /// Append an `Amount` field in XRP, see [write_drops](Self::write_drops)
+    #[inline(always)]
+    fn write_drops_field(&mut self, field_id: FieldId, drops: Drops) {
+        self.write_field_header(field_id);
+        self.write_drops(drops);
+    }
+
+    /// Append the 48 bytes long serialized issued amount `amount`, without a field header
+    #[inline(always)]
+    fn write_issued_amount(&mut self, amount: &[u8; ISSUED_AMOUNT_LEN]) {
+        self.write_array(amount);
+    }
+
+    /// Append an `Amount` field in an issued currency, given as the 48 bytes long
+    /// serialized amount
+    #[inline(always)]
+    fn write_issued_amount_field(&mut self, field_id: FieldId, amount: &[u8; ISSUED_AMOUNT_LEN]) {
+        self.write_field_header(field_id);
+        self.write_issued_amount(amount);
+    }
+
+    /// Append an account ID after its length prefix, without a field header
+    #[inline(always)]
+    fn write_account(&mut self, account_id: &AccountId) {
+        self.write_byte(ACC_ID_LEN as u8);
+        self.write_array(&account_id.0);
+    }
+
+    /// Append an `AccountID` field, which is variable length
+    #[inline(always)]
+    fn write_account_field(&mut self, field_id: FieldId, account_id: &AccountId) {
+        self.write_field_header(field_id);
+        self.write_account(account_id);
+    }
+
+    /// Append a `Blob` field, failing like [write_vl_len](Self::write_vl_len) if `bytes`
+    /// is too long
+    #[inline(always)]
+    fn write_blob_field(&mut self, field_id: FieldId, bytes: &[u8]) -> Result<()> {
+        self.write_field_header(field_id);
+        self.write_vl(bytes)
+    }
+
+    /// Append the marker ending the fields of an object, which starts with the
+    /// [header](Self::write_field_header) of its field
+    #[inline(always)]
+    fn write_object_end(&mut self) {
+        self.write_byte(OBJECT_END_MARKER);
+    }
+
+    /// Append the marker ending the objects of an array, which starts with the
+    /// [header](Self::write_field_header) of its field
+    #[inline(always)]
+    fn write_array_end(&mut self) {
+        self.write_byte(ARRAY_END_MARKER);
+    }
+}
+
+impl<const TXN_LEN: usize> FieldWriter for TransactionBuffer<'_, TXN_LEN> {
+    #[inline(always)]
+    fn write_byte(&mut self, byte: u8) {
+        unsafe { store::put(self.buf, self.pos, byte) };
+        self.pos += 1;
+    }
+
+    #[inline(always)]
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        // guarded loop
+        let mut i = 0;
+        while {
+            max_iter(TXN_LEN as u32 + 1);
+            i < bytes.len()
+        } {
+            unsafe { store::put(self.buf, self.pos + i, bytes[i]) };
+            i += 1;
+        }
+        self.pos += bytes.len();
+    }
+
+    // unrolled into a few stores, which fails to compile for arrays longer than
+    // MAX_UNROLLED_LEN, far more than any field
+    #[inline(always)]
+    fn write_array<const N: usize>(&mut self, bytes: &[u8; N]) {
+        unsafe { store::copy_n::<N>(self.buf, self.pos, bytes, 0) };
+        self.pos += N;
+    }
+
+    // the buffer may be uninitialized, so the zeros are written as 8 byte stores and the
+    // bytes left over
+    #[inline(always)]
+    fn write_zeros<const N: usize>(&mut self) {
+        unsafe { store::zero_n::<N>(self.buf, self.pos) };
+        self.pos += N;
+    }
+
+    // integers are written byte by byte, which the optimizer turns into fewer
+    // instructions than a copy of their big endian bytes
+    #[inline(always)]
+    fn write_u32(&mut self, value: u32) {
+        unsafe {
+            store::put(self.buf, self.pos, (value >> 24) as u8);
+            store::put(self.buf, self.pos + 1, (value >> 16) as u8);
+            store::put(self.buf, self.pos + 2, (value >> 8) as u8);
+            store::put(self.buf, self.pos + 3, value as u8);
+        }
+        self.pos += 4;
+    }
+
+    #[inline(always)]
+    fn write_drops(&mut self, drops: Drops) {
+        let drops = drops.0;
+        unsafe {
+            // positive, and not an issued amount
+            store::put(self.buf, self.pos, (0x40 | ((drops >> 56) & 0x3F)) as u8);
+            store::put(self.buf, self.pos + 1, (drops >> 48) as u8);
+            store::put(self.buf, self.pos + 2, (drops >> 40) as u8);
+            store::put(self.buf, self.pos + 3, (drops >> 32) as u8);
+            store::put(self.buf, self.pos + 4, (drops >> 24) as u8);
+            store::put(self.buf, self.pos + 5, (drops >> 16) as u8);
+            store::put(self.buf, self.pos + 6, (drops >> 8) as u8);
+            store::put(self.buf, self.pos + 7, drops as u8);
+        }
+        self.pos += 8;
+    }
+
+    #[inline(always)]
+    fn write_issued_amount(&mut self, amount: &[u8; ISSUED_AMOUNT_LEN]) {
+        unsafe {
+            // not copy_n, which lets the optimizer load the whole amount into locals
+            // before storing any of it, 10 instructions more
+            store::copy_8(self.buf, self.pos, amount, 0);
+            store::copy_8(self.buf, self.pos + 8, amount, 8);
+            store::copy_8(self.buf, self.pos + 16, amount, 16);
+            store::copy_8(self.buf, self.pos + 24, amount, 24);
+            store::copy_8(self.buf, self.pos + 32, amount, 32);
+            store::copy_8(self.buf, self.pos + 40, amount, 40); // total 48 bytes
+        }
+        self.pos += ISSUED_AMOUNT_LEN;
+    }
+}
+
+impl<const N: usize> FieldWriter for FixedWriter<N> {
+    #[inline(always)]
+    fn write_byte(&mut self, byte: u8) {
+        FixedWriter::write_bytes(self, &[byte]);
+    }
+
+    #[inline(always)]
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        FixedWriter::write_bytes(self, bytes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::mem::MaybeUninit;
+
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::decode::decode;
+    use crate::fixtures::DESTINATION;
+
+    fn write_memo(writer: &mut impl FieldWriter) {
+        writer.write_field_header(FieldId::Memos);
+        writer.write_field_header(FieldId::Memo);
+        writer
+            .write_blob_field(FieldId::MemoType, b"payout")
+            .unwrap_line_number();
+        writer.write_object_end();
+        writer.write_array_end();
+        writer.write_u8_field(FieldId::TickSize, 5);
+        writer.write_u32_field(FieldId::SourceTag, 7);
+        writer.write_hash256_field(FieldId::InvoiceID, &[3; HASH_LEN]);
+        writer.write_drops_field(FieldId::Amount, Drops(1_000));
+        writer.write_account_field(FieldId::Destination, &DESTINATION);
+    }
+
+    #[wasm_bindgen_test]
+    fn writes_the_same_fields_into_every_buffer() {
+        let mut fixed = FixedWriter::<128>::new();
+        write_memo(&mut fixed);
+        assert!(!fixed.is_truncated());
+
+        let mut uninitialized_buffer: [MaybeUninit<u8>; 128] = MaybeUninit::uninit_array();
+        // avoid undefined behavior when calling array_assume_init
+        uninitialized_buffer.fill(MaybeUninit::new(0));
+        let mut txn_buffer = TransactionBuffer::new(&mut uninitialized_buffer);
+        write_memo(&mut txn_buffer);
+        let len = txn_buffer.pos();
+        let buffer = unsafe { MaybeUninit::array_assume_init(uninitialized_buffer) };
+        assert_eq!(&buffer[..len], fixed.as_bytes());
+
+        let decoded = decode(fixed.as_bytes());
+        assert_eq!(decoded.field(FieldId::TickSize), Some(&[5][..]));
+        assert_eq!(decoded.u32(FieldId::SourceTag), Some(7));
+        assert_eq!(decoded.field(FieldId::InvoiceID), Some(&[3; HASH_LEN][..]));
+        assert_eq!(decoded.drops(FieldId::Amount), Some(1_000));
+        assert_eq!(decoded.account(FieldId::Destination), Some(DESTINATION));
+        let memos = decoded.object(FieldId::Memos).unwrap();
+        let memo = memos.object(FieldId::Memo).unwrap();
+        assert_eq!(memo.field(FieldId::MemoType), Some(&b"payout"[..]));
+    }
+
+    #[wasm_bindgen_test]
+    fn writes_length_prefixes_the_reader_reads_back() {
+        for len in [0, 192, 193, 12480, 12481, VL_MAX_LEN] {
+            let mut prefix = FixedWriter::<3>::new();
+            assert!(prefix.write_vl_len(len).is_ok());
+            let mut reader = crate::SliceReader::new(prefix.as_bytes());
+            assert!(matches!(reader.read_vl_len(), Ok(read) if read == len));
+            assert!(reader.is_empty());
+        }
+
+        let mut prefix = FixedWriter::<3>::new();
+        assert!(matches!(
+            prefix.write_vl_len(VL_MAX_LEN + 1),
+            Err(HookError::TooBig)
+        ));
+        assert!(prefix.is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    fn truncates_fixed_writers() {
+        let mut writer = FixedWriter::<4>::new();
+        writer.write_account_field(FieldId::Destination, &DESTINATION);
+        assert!(writer.is_truncated());
+        assert_eq!(
+            writer.as_bytes(),
+            [0x83, 0x14, DESTINATION.0[0], DESTINATION.0[1]]
+        );
+    }
+}
@@ -1,3 +1,5 @@
+use core::mem::MaybeUninit;
+
 use crate::api::*;
 
 /// Comparable array of variables.
@@ -20,6 +22,7 @@ use crate::api::*;
 ///     rollback(b"arrays are not the same", -1);
 /// }
 /// ```
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
 pub struct ComparableArray<'a, T, const N: usize>
 where
     T: PartialEq,
@@ -94,6 +97,162 @@ where
     }
 }
 
+/// Fixed size stack buffer that text is written into, truncating anything that
+/// does not fit. Used by [trace!](crate::trace!) and for building messages such as
+/// memos without allocating.
+///
+/// The `write_*` methods only use guarded loops, so they can be used anywhere in
+/// a hook. Unless the `small` feature is enabled, `FixedWriter` also implements
+/// [core::fmt::Write] for code that is generic over writers, but `core::fmt` itself
+/// contains unguarded loops, so a hook using `write!` may be rejected when it is installed.
+///
+/// # Example
+/// ```
+/// let mut memo = FixedWriter::<64>::new();
+/// memo.write_bytes(b"payout #");
+/// memo.write_u64(payout_count);
+///
+/// let _ = trace(b"memo", memo.as_bytes(), DataRepr::AsUTF8);
+/// ```
+pub struct FixedWriter<const N: usize> {
+    buf: [MaybeUninit<u8>; N],
+    len: usize,
+    truncated: bool,
+}
+
+impl<const N: usize> FixedWriter<N> {
+    /// Creates an empty writer
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self {
+            buf: MaybeUninit::uninit_array(),
+            len: 0,
+            truncated: false,
+        }
+    }
+
+    /// The bytes written so far
+    #[inline(always)]
+    pub fn as_bytes(&self) -> &[u8] {
+        // SAFETY: the first `len` bytes have been written
+        unsafe { core::slice::from_raw_parts(self.buf.as_ptr() as *const u8, self.len) }
+    }
+
+    /// Number of bytes written so far
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether nothing has been written yet
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Whether anything was cut off because the buffer was full
+    #[inline(always)]
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Writes bytes as they are, truncating them if the buffer is full
+    #[inline(always)]
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        let count = core::cmp::min(bytes.len(), N - self.len);
+        if count < bytes.len() {
+            self.truncated = true;
+        }
+
+        // guarded loop
+        let mut i = 0;
+        while {
+            max_iter(N as u32 + 1);
+            i < count
+        } {
+            self.buf[self.len].write(bytes[i]);
+            self.len += 1;
+            i += 1;
+        }
+    }
+
+    /// Writes bytes as uppercase hex, truncating them if the buffer is full
+    #[inline(always)]
+    pub fn write_hex(&mut self, bytes: &[u8]) {
+        const UPPERCASE_DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+        let count = core::cmp::min(bytes.len(), (N - self.len) / 2);
+        if count < bytes.len() {
+            self.truncated = true;
+        }
+
+        // guarded loop
+        let mut i = 0;
+        while {
+            max_iter(N as u32 / 2 + 1);
+            i < count
+        } {
+            self.buf[self.len].write(UPPERCASE_DIGITS[(bytes[i] >> 4) as usize]);
+            self.buf[self.len + 1].write(UPPERCASE_DIGITS[(bytes[i] & 0x0F) as usize]);
+            self.len += 2;
+            i += 1;
+        }
+    }
+
+    /// Writes an unsigned integer in decimal
+    #[inline(always)]
+    pub fn write_u64(&mut self, number: u64) {
+        // u64::MAX has 20 digits
+        let mut digits = [0u8; 20];
+        let mut digits_len = 0;
+        let mut rest = number;
+
+        // guarded loop
+        while {
+            max_iter(21);
+            digits_len == 0 || rest > 0
+        } {
+            digits[19 - digits_len] = b'0' + (rest % 10) as u8;
+            rest /= 10;
+            digits_len += 1;
+        }
+
+        self.write_bytes(&digits[20 - digits_len..]);
+    }
+
+    /// Writes a signed integer in decimal
+    #[inline(always)]
+    pub fn write_i64(&mut self, number: i64) {
+        if number < 0 {
+            self.write_bytes(b"-");
+        }
+
+        self.write_u64(number.unsigned_abs());
+    }
+}
+
+impl<const N: usize> Default for FixedWriter<N> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Not available with the `small` feature, which leaves out `core::fmt`
+#[cfg(not(feature = "small"))]
+impl<const N: usize> core::fmt::Write for FixedWriter<N> {
+    /// Writes as much of `s` as fits, and returns an error if it had to be truncated
+    #[inline(always)]
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.write_bytes(s.as_bytes());
+
+        if self.truncated {
+            core::fmt::Result::Err(core::fmt::Error)
+        } else {
+            core::fmt::Result::Ok(())
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     // use super::*;
@@ -0,0 +1,277 @@
+//! Vesting schedules kept in hook state and paid out as they vest.
+//!
+//! A [VestingSchedule] releases `rate` drops per second of [ledger_time] to its
+//! beneficiary, starting at `start` but only paid out once the `cliff` has passed, until
+//! `total` drops have been released. The hook stores the schedule under a key of its
+//! choice with [VestingSchedule::store], usually when the hook account sets it up with an
+//! Invoke, and then calls [release_vested] whenever it runs to pay out what vested since
+//! the last payout.
+//!
+//! # Example
+//! ```
+//! const GRANT_KEY: &[u8; 5] = b"grant";
+//!
+//! #[hook]
+//! fn vesting() -> Outcome<'static> {
+//!     let mut reservation = EmitReservation::new(1)?;
+//!     match release_vested(GRANT_KEY, &mut reservation)? {
+//!         Some(_) => Outcome::Accept { code: 0, msg: b"released vested drops" },
+//!         None => Outcome::Accept { code: 0, msg: b"nothing vested yet" },
+//!     }
+//! }
+//! ```
+
+use crate::api::*;
+use crate::transaction::{TransactionBuilder, XrpPaymentBuilder};
+
+/// Length of a [VestingSchedule] in hook state
+pub const VESTING_SCHEDULE_LEN: usize = 52;
+
+/// A linear vesting schedule and how much of it was paid out already
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct VestingSchedule {
+    /// Account the vested drops are paid to
+    pub beneficiary: AccountId,
+    /// When the drops start vesting
+    pub start: RippleTime,
+    /// Seconds after `start` before anything is paid out. What vested in the meantime is
+    /// paid out at once afterwards.
+    pub cliff: u32,
+    /// Drops vesting every second
+    pub rate: Drops,
+    /// Drops vesting in all
+    pub total: Drops,
+    /// Drops paid out so far
+    pub released: Drops,
+}
+
+/// A payout made by [release_vested]
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct VestingRelease {
+    /// Drops paid to the beneficiary
+    pub amount: Drops,
+    /// Hash of the emitted payment
+    pub txn_hash: [u8; HASH_LEN],
+}
+
+// The layout of a schedule in state, all big endian
+#[repr(C)]
+struct StoredSchedule {
+    beneficiary: [u8; ACC_ID_LEN],
+    start: [u8; 4],
+    cliff: [u8; 4],
+    rate: [u8; 8],
+    total: [u8; 8],
+    released: [u8; 8],
+}
+
+impl VestingSchedule {
+    /// A schedule paying `rate` drops per second from `start`, after a cliff of `cliff`
+    /// seconds, until `total` drops are paid out. Nothing is released yet.
+    #[inline(always)]
+    pub const fn new(
+        beneficiary: AccountId,
+        start: RippleTime,
+        cliff: u32,
+        rate: Drops,
+        total: Drops,
+    ) -> Self {
+        Self {
+            beneficiary,
+            start,
+            cliff,
+            rate,
+            total,
+            released: Drops::ZERO,
+        }
+    }
+
+    /// Drops vested at `now`, whether paid out or not
+    #[inline(always)]
+    pub const fn vested(&self, now: RippleTime) -> Drops {
+        if now.0 < self.start.0.saturating_add(self.cliff) {
+            return Drops::ZERO;
+        }
+
+        let vested = self.rate.0.saturating_mul((now.0 - self.start.0) as u64);
+        if vested > self.total.0 {
+            self.total
+        } else {
+            Drops(vested)
+        }
+    }
+
+    /// Drops vested at `now` but not paid out yet
+    #[inline(always)]
+    pub const fn releasable(&self, now: RippleTime) -> Drops {
+        Drops(self.vested(now).0.saturating_sub(self.released.0))
+    }
+
+    /// Whether all drops have been paid out, after which the schedule can be removed
+    #[inline(always)]
+    pub const fn is_complete(&self) -> bool {
+        self.released.0 >= self.total.0
+    }
+
+    /// Read the schedule stored under `key`, [HookError::DoesntExist] if there is none
+    /// and [HookError::DoesNotMatch] if the entry isn't [VESTING_SCHEDULE_LEN] bytes long
+    #[inline(always)]
+    pub fn load(key: &[u8]) -> Result<Self> {
+        let bytes = state_exact::<VESTING_SCHEDULE_LEN>(key)?;
+        let stored =
+            unsafe { core::mem::transmute::<[u8; VESTING_SCHEDULE_LEN], StoredSchedule>(bytes) };
+
+        Ok(Self {
+            beneficiary: AccountId(stored.beneficiary),
+            start: RippleTime::from_be_bytes(stored.start),
+            cliff: u32::from_be_bytes(stored.cliff),
+            rate: Drops(u64::from_be_bytes(stored.rate)),
+            total: Drops(u64::from_be_bytes(stored.total)),
+            released: Drops(u64::from_be_bytes(stored.released)),
+        })
+    }
+
+    /// Write the schedule to hook state under `key`, replacing the one stored there
+    #[inline(always)]
+    pub fn store(&self, key: &[u8]) -> Result<()> {
+        let stored = StoredSchedule {
+            beneficiary: self.beneficiary.0,
+            start: self.start.to_be_bytes(),
+            cliff: self.cliff.to_be_bytes(),
+            rate: self.rate.0.to_be_bytes(),
+            total: self.total.0.to_be_bytes(),
+            released: self.released.0.to_be_bytes(),
+        };
+        let bytes =
+            unsafe { core::mem::transmute::<StoredSchedule, [u8; VESTING_SCHEDULE_LEN]>(stored) };
+
+        match state_set(&bytes, key) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// Pay out what vested since the last payout of the schedule stored under `key`, and
+/// record it in the schedule.
+///
+/// Returns `Ok(None)` without emitting if nothing is releasable yet. The payment is
+/// emitted before the schedule is written back, so if writing it fails the error is
+/// returned and the hook should roll back, which also discards the payment.
+#[inline(always)]
+pub fn release_vested(
+    key: &[u8],
+    reservation: &mut EmitReservation,
+) -> Result<Option<VestingRelease>> {
+    let mut schedule = VestingSchedule::load(key)?;
+    let amount = schedule.releasable(ledger_time());
+    if amount == Drops::ZERO {
+        return Ok(None);
+    }
+
+    let txn_hash = XrpPaymentBuilder::new(amount, &schedule.beneficiary, None, None)
+        .build_and_emit(reservation)?;
+
+    schedule.released = Drops(schedule.released.0 + amount.0);
+    schedule.store(key)?;
+
+    Ok(Some(VestingRelease { amount, txn_hash }))
+}
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::decode::decode;
+    use crate::fixtures::{DESTINATION, HOOK_ACCOUNT};
+    use crate::mock;
+
+    const KEY: &[u8; 5] = b"grant";
+
+    // 10 drops a second from time 1000, paid out from 1100, 5000 drops in all
+    const SCHEDULE: VestingSchedule =
+        VestingSchedule::new(DESTINATION, RippleTime(1000), 100, Drops(10), Drops(5000));
+
+    fn pay_out() -> Outcome<'static> {
+        let mut reservation = EmitReservation::new(1)?;
+        match release_vested(KEY, &mut reservation)? {
+            Some(_) => Outcome::Accept {
+                code: 0,
+                msg: b"released",
+            },
+            None => Outcome::Accept {
+                code: 1,
+                msg: b"nothing vested",
+            },
+        }
+    }
+
+    // the drops paid out by an invocation at `time`
+    fn pay_out_at(time: u32) -> Option<u64> {
+        mock::set_ledger_last_time(time);
+        let invocation = mock::invoke(pay_out);
+        assert!(invocation.accepted);
+
+        match &invocation.emitted[..] {
+            [] => None,
+            [payment] => {
+                let payment = decode(&payment.tx);
+                assert_eq!(payment.account(FieldId::Destination), Some(DESTINATION));
+                payment.drops(FieldId::Amount)
+            }
+            _ => panic!("emitted more than one payment"),
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn vests_linearly_after_the_cliff() {
+        assert_eq!(SCHEDULE.vested(RippleTime(900)), Drops::ZERO);
+        assert_eq!(SCHEDULE.vested(RippleTime(1099)), Drops::ZERO);
+        assert_eq!(SCHEDULE.vested(RippleTime(1100)), Drops(1000));
+        assert_eq!(SCHEDULE.vested(RippleTime(1250)), Drops(2500));
+        assert_eq!(SCHEDULE.vested(RippleTime(u32::MAX)), Drops(5000));
+
+        let released = VestingSchedule {
+            released: Drops(2000),
+            ..SCHEDULE
+        };
+        assert_eq!(released.releasable(RippleTime(1250)), Drops(500));
+        assert_eq!(released.releasable(RippleTime(1150)), Drops::ZERO);
+        assert!(!released.is_complete());
+    }
+
+    #[wasm_bindgen_test]
+    fn pays_out_what_vested_since_the_last_payout() {
+        mock::reset();
+        mock::set_hook_account(HOOK_ACCOUNT);
+        assert!(!mock::invoke(pay_out).accepted);
+
+        assert!(SCHEDULE.store(KEY).is_ok());
+        assert!(matches!(VestingSchedule::load(KEY), Ok(schedule) if schedule == SCHEDULE));
+        assert_eq!(pay_out_at(1050), None);
+        assert_eq!(pay_out_at(1200), Some(2000));
+        assert_eq!(pay_out_at(1200), None);
+        assert_eq!(pay_out_at(1300), Some(1000));
+        assert_eq!(pay_out_at(9000), Some(2000));
+        assert_eq!(pay_out_at(9999), None);
+        assert!(matches!(VestingSchedule::load(KEY), Ok(schedule) if schedule.is_complete()));
+    }
+
+    #[wasm_bindgen_test]
+    fn rejects_schedules_of_the_wrong_length() {
+        mock::reset();
+        mock::set_state(KEY, &[1; VESTING_SCHEDULE_LEN - 1]);
+        assert!(matches!(
+            VestingSchedule::load(KEY),
+            Err(HookError::DoesNotMatch)
+        ));
+        mock::set_state(KEY, &[1; VESTING_SCHEDULE_LEN + 1]);
+        assert!(matches!(
+            VestingSchedule::load(KEY),
+            Err(HookError::TooSmall)
+        ));
+    }
+}
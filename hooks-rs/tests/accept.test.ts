@@ -1,7 +1,7 @@
 // xrpl
 import { Client, Invoke, Transaction, Wallet } from "@transia/xrpl";
 import { ExecutionUtility } from "@transia/hooks-toolkit";
-import { TestUtils } from "./setup";
+import { Faucet, TestUtils } from "./setup";
 
 const HOOK_NAME = "accept";
 
@@ -12,19 +12,14 @@ describe("accept.rs", () => {
 
   beforeAll(async () => {
     const hook = await TestUtils.buildHook(HOOK_NAME);
-    client = new Client("wss://xahau-test.net", {});
-    await client.connect();
-    client.networkID = await client.getNetworkID();
-    // Because Faucet only allows one account to be created every 60 seconds,
-    // we will use the following accounts for testing. Change the secrets when
-    // running out of funds.
-    // rULqKiLvbGda4VKAqsto5hxmmDavJm2oav
-    alice = Wallet.fromSecret(`shW8QFpXBGGh86mjdkpMTKk54kqwh`);
-    // rnvgdKhaMnLtNMZmGAkKq1VNUx25Qg8Vxz
-    bob = Wallet.fromSecret(`snSRRGDAgcUB9F4JWgx2YCt6aS7h3`);
+    client = await TestUtils.connect();
+    // Fresh accounts from the faucet, because the faucet is rate limited
+    // this may take a while.
+    alice = await Faucet.waitAndGetNewWallet();
+    bob = await Faucet.waitAndGetNewWallet();
 
     await TestUtils.setHook(client, alice.seed!, hook);
-  }, 3 * 60_000);
+  }, 5 * 60_000);
 
   afterAll(async () => {
     await client.disconnect();
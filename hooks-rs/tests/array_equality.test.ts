@@ -1,6 +1,6 @@
 // xrpl
 import { Client, Invoke, Transaction, Wallet } from "@transia/xrpl";
-import { TestUtils } from "./setup";
+import { Faucet, TestUtils } from "./setup";
 import { HookExecution } from "@transia/xrpl/dist/npm/models/transactions/metadata";
 
 const HOOK_NAME = "array_equality";
@@ -12,15 +12,13 @@ describe("array_equality.rs", () => {
 
   beforeAll(async () => {
     const hook = await TestUtils.buildHook(HOOK_NAME);
-    client = new Client("wss://xahau-test.net", {});
-    await client.connect();
-    client.networkID = await client.getNetworkID();
-    // r3PpxbrPPE2e5GBXHnWxgX6jcuYdsooxVw
-    alice = Wallet.fromSecret(`snnrWYB1mAcJA4PuuAYAYdP1qdofx`);
-    // rLf4fgWGaexgjYuY1s1iqsJ4mQNyYqh7QF
-    bob = Wallet.fromSecret(`safu4MhrXEsXTXb7sQhbiSDswfY2D`);
+    client = await TestUtils.connect();
+    // Fresh accounts from the faucet, because the faucet is rate limited
+    // this may take a while.
+    alice = await Faucet.waitAndGetNewWallet();
+    bob = await Faucet.waitAndGetNewWallet();
     await TestUtils.setHook(client, alice.seed!, hook);
-  }, 3 * 60_000);
+  }, 5 * 60_000);
 
   afterAll(async () => {
     await client.disconnect();
@@ -0,0 +1,41 @@
+import { stat } from "fs/promises";
+import path from "path";
+import { TestUtils } from "./setup";
+
+// Upper bounds in bytes of examples built with the `small` feature, before they are
+// optimized and cleaned for SetHook. The fee of installing a hook grows with its size,
+// so raise a bound only when the growth is worth paying for.
+const MAX_SMALL_SIZES: [string, number][] = [
+  ["accept", 700],
+  ["xrp_payment_txn", 3400],
+  ["trace_fmt", 1100],
+];
+
+describe("code size", () => {
+  let defaultWasmDir: string;
+  let smallWasmDir: string;
+
+  beforeAll(async () => {
+    defaultWasmDir = await TestUtils.buildExamples();
+    smallWasmDir = await TestUtils.buildExamples(["small"]);
+  }, 5 * 60_000);
+
+  const sizeOf = async (wasmDir: string, hookName: string) =>
+    (await stat(path.resolve(wasmDir, `${hookName}.wasm`))).size;
+
+  it.each(MAX_SMALL_SIZES)(
+    `%s.rs built with the small feature is at most %d bytes`,
+    async (hookName, maxSize) => {
+      expect(await sizeOf(smallWasmDir, hookName)).toBeLessThanOrEqual(
+        maxSize,
+      );
+    },
+  );
+
+  it(`the small feature leaves out formatting`, async () => {
+    const defaultSize = await sizeOf(defaultWasmDir, "trace_fmt");
+    const smallSize = await sizeOf(smallWasmDir, "trace_fmt");
+
+    expect(smallSize).toBeLessThan(defaultSize / 2);
+  });
+});
@@ -0,0 +1,84 @@
+// xrpl
+import { Client, Invoke, Transaction, Wallet } from "@transia/xrpl";
+import { HookExecution } from "@transia/xrpl/dist/npm/models/transactions/metadata";
+import { Faucet, TestUtils } from "./setup";
+
+const HOOK_NAME = "etxn_generation";
+
+describe("etxn_generation.rs", () => {
+  let client: Client;
+  let alice: Wallet;
+  let bob: Wallet;
+
+  beforeAll(async () => {
+    const hook = await TestUtils.buildHook(HOOK_NAME);
+    client = await TestUtils.connect();
+    // Fresh accounts from the faucet, because the faucet is rate limited
+    // this may take a while.
+    alice = await Faucet.waitAndGetNewWallet();
+    bob = await Faucet.waitAndGetNewWallet();
+
+    await TestUtils.setHook(client, alice.seed!, hook);
+  }, 5 * 60_000);
+
+  afterAll(async () => {
+    await client.disconnect();
+  }, 10_000);
+
+  it(
+    "returns the generation of an emitted transaction",
+    async () => {
+      const tx: Invoke & Transaction = {
+        TransactionType: "Invoke",
+        Account: bob.classicAddress,
+        Destination: alice.classicAddress,
+      };
+      // Autofilling fee does not work with hooks yet
+      const { Fee, ...rest } = await client.autofill(tx);
+      const fee = await TestUtils.getTransactionFee(client, rest);
+      const txResponse = await TestUtils.submitAndWaitWithRetries(
+        client,
+        {
+          ...tx,
+          Fee: fee,
+        },
+        {
+          wallet: bob,
+          autofill: true,
+        },
+      );
+      if (!txResponse.result.meta) {
+        throw new Error("No meta in tx response");
+      }
+      if (typeof txResponse.result.meta === "string") {
+        throw new Error("Meta is string, not object");
+      }
+
+      const { meta } = txResponse.result;
+      if (!(meta.HookExecutions && meta.HookExecutions.length > 0)) {
+        throw new Error(`Hook execution data is empty`);
+      }
+
+      if (meta.HookExecutions.length > 1) {
+        throw new Error(`Hook execution happened more than once`);
+      }
+
+      if (txResponse.result.meta.TransactionResult !== "tesSUCCESS") {
+        console.error(JSON.stringify(txResponse, null, 2));
+
+        throw new Error(`Transaction failed`);
+      }
+
+      // safe type: we checked everything
+      const [hookExecution] = meta.HookExecutions as [HookExecution];
+
+      const { HookReturnString, HookReturnCode } = hookExecution.HookExecution;
+
+      // A transaction submitted by a user has generation 0, so anything
+      // emitted in response to it has generation 1
+      expect(BigInt(HookReturnCode)).toEqual(1n);
+      expect(HookReturnString).toBe(``);
+    },
+    3 * 60_000,
+  );
+});
@@ -1,7 +1,7 @@
 // xrpl
 import { Client, Invoke, Transaction, Wallet } from "@transia/xrpl";
 import { ExecutionUtility } from "@transia/hooks-toolkit";
-import { TestUtils } from "./setup";
+import { Faucet, TestUtils } from "./setup";
 
 const HOOK_NAME = "fee_base";
 
@@ -12,19 +12,14 @@ describe("fee_base.rs", () => {
 
   beforeAll(async () => {
     const hook = await TestUtils.buildHook(HOOK_NAME);
-    client = new Client("wss://xahau-test.net", {});
-    await client.connect();
-    client.networkID = await client.getNetworkID();
-    // Because Faucet only allows one account to be created every 60 seconds,
-    // we will use the following accounts for testing. Change the secrets when
-    // running out of funds.
-    // rskv5aj9YUE5zkFkzCMQwRW5HVvbcFme61
-    alice = Wallet.fromSecret(`shoFfDJhGCbfpppg25QCyPh2s9DoX`);
-    // r4y53NaQfLrK9x6oWZ7Lnzct1vejFfP9pn
-    bob = Wallet.fromSecret(`ssoiVBiqwKb9JpdRNP9eGd58WkyK8`);
+    client = await TestUtils.connect();
+    // Fresh accounts from the faucet, because the faucet is rate limited
+    // this may take a while.
+    alice = await Faucet.waitAndGetNewWallet();
+    bob = await Faucet.waitAndGetNewWallet();
 
     await TestUtils.setHook(client, alice.seed!, hook);
-  }, 3 * 60_000);
+  }, 5 * 60_000);
 
   afterAll(async () => {
     await client.disconnect();
@@ -1,6 +1,6 @@
 // xrpl
 import { Client, Invoke, Transaction, Wallet } from "@transia/xrpl";
-import { TestUtils } from "./setup";
+import { Faucet, TestUtils } from "./setup";
 import { HookExecution } from "@transia/xrpl/dist/npm/models/transactions/metadata";
 
 const HOOK_NAME = "float";
@@ -12,19 +12,14 @@ describe("float.rs", () => {
 
   beforeAll(async () => {
     const hook = await TestUtils.buildHook(HOOK_NAME);
-    client = new Client("wss://xahau-test.net", {});
-    await client.connect();
-    client.networkID = await client.getNetworkID();
-    // Because Faucet only allows one account to be created every 60 seconds,
-    // we will use the following accounts for testing. Change the secrets when
-    // running out of funds.
-    // rYfrPiEjJPpGYwBwEeuM3pemPEEJQjdqr
-    alice = Wallet.fromSecret(`ssG4eo68yjdYQo4zmYuVvd4DvAe2o`);
-    // rhvzodKsvRDmcQJiqNXYRD8QVnAjAQ3qu1
-    bob = Wallet.fromSecret(`saNhdnP187eTCsND4egdAw4T2qSKZ`);
+    client = await TestUtils.connect();
+    // Fresh accounts from the faucet, because the faucet is rate limited
+    // this may take a while.
+    alice = await Faucet.waitAndGetNewWallet();
+    bob = await Faucet.waitAndGetNewWallet();
     await new Promise((resolve) => setTimeout(resolve, 5000));
     await TestUtils.setHook(client, alice.seed!, hook);
-  }, 3 * 60_000);
+  }, 5 * 60_000);
 
   afterAll(async () => {
     await client.disconnect();
@@ -6,7 +6,7 @@ import {
   Transaction,
   Wallet,
 } from "@transia/xrpl";
-import { TestUtils } from "./setup";
+import { Faucet, TestUtils } from "./setup";
 import { HookExecution } from "@transia/xrpl/dist/npm/models/transactions/metadata";
 
 const HOOK_NAME = "hook_account";
@@ -18,18 +18,13 @@ describe("hook_account.rs", () => {
 
   beforeAll(async () => {
     const hook = await TestUtils.buildHook(HOOK_NAME);
-    client = new Client("wss://xahau-test.net", {});
-    await client.connect();
-    client.networkID = await client.getNetworkID();
-    // Because Faucet only allows one account to be created every 60 seconds,
-    // we will use the following accounts for testing. Change the secrets when
-    // running out of funds.
-    // rUXE2vDtggMF4e9s9HneDyeqZacUwtWV2M
-    alice = Wallet.fromSecret(`snnKdEThbMhPthyidR7roRnR2fb8b`);
-    // rEnmXfkF2Y9jDwNG6t12tcSf3tf9nsy446
-    bob = Wallet.fromSecret(`snxbZienDLSnCgT8YxhR61AfSmLJd`);
+    client = await TestUtils.connect();
+    // Fresh accounts from the faucet, because the faucet is rate limited
+    // this may take a while.
+    alice = await Faucet.waitAndGetNewWallet();
+    bob = await Faucet.waitAndGetNewWallet();
     await TestUtils.setHook(client, alice.seed!, hook);
-  }, 3 * 60_000);
+  }, 5 * 60_000);
 
   afterAll(async () => {
     await client.disconnect();
@@ -1,6 +1,6 @@
 // xrpl
 import { Client, Invoke, Transaction, Wallet } from "@transia/xrpl";
-import { TestUtils } from "./setup";
+import { Faucet, TestUtils } from "./setup";
 import { HookExecution } from "@transia/xrpl/dist/npm/models/transactions/metadata";
 
 const HOOK_NAME = "hook_hash";
@@ -12,18 +12,13 @@ describe("hook_hash.rs", () => {
 
   beforeAll(async () => {
     const hook = await TestUtils.buildHook(HOOK_NAME);
-    client = new Client("wss://xahau-test.net", {});
-    await client.connect();
-    client.networkID = await client.getNetworkID();
-    // Because Faucet only allows one account to be created every 60 seconds,
-    // we will use the following accounts for testing. Change the secrets when
-    // running out of funds.
-    // rMXRvoMFnuf2xPK5Pio94D5LhttE7X5H6e
-    alice = Wallet.fromSecret(`ssJaynfvJVm7wANHyCFAWYV94SXWP`);
-    // rniKiTrsgGkDFc65pw7zruXJirs4aPweN6
-    bob = Wallet.fromSecret(`ssz1zWkMuEQN9twEvTTnGZW8KtZjw`);
+    client = await TestUtils.connect();
+    // Fresh accounts from the faucet, because the faucet is rate limited
+    // this may take a while.
+    alice = await Faucet.waitAndGetNewWallet();
+    bob = await Faucet.waitAndGetNewWallet();
     await TestUtils.setHook(client, alice.seed!, hook);
-  }, 3 * 60_000);
+  }, 5 * 60_000);
 
   afterAll(async () => {
     await client.disconnect();
@@ -1,6 +1,6 @@
 // xrpl
 import { Client, Invoke, Transaction, Wallet } from "@transia/xrpl";
-import { TestUtils } from "./setup";
+import { Faucet, TestUtils } from "./setup";
 import { HookExecution } from "@transia/xrpl/dist/npm/models/transactions/metadata";
 import {
   iHookParamEntry,
@@ -24,18 +24,13 @@ describe("hook_param.rs", () => {
         new iHookParamValue(HOOK_PARAMETER_VALUE),
       ).toXrpl(),
     ];
-    client = new Client("wss://xahau-test.net", {});
-    await client.connect();
-    client.networkID = await client.getNetworkID();
-    // Because Faucet only allows one account to be created every 60 seconds,
-    // we will use the following accounts for testing. Change the secrets when
-    // running out of funds.
-    // raqx32Kp1x89J3CL52Qz5dHeT5C7bkWawq
-    alice = Wallet.fromSecret(`saBCpHjhanDJ2WmhgL78ccaaXLC1q`);
-    // rfGSEDJTtFE19wLkAYPH7929mybG86kXve
-    bob = Wallet.fromSecret(`ssQoaL9mZVi8LsRc3ntTob14YgQNa`);
+    client = await TestUtils.connect();
+    // Fresh accounts from the faucet, because the faucet is rate limited
+    // this may take a while.
+    alice = await Faucet.waitAndGetNewWallet();
+    bob = await Faucet.waitAndGetNewWallet();
     await TestUtils.setHook(client, alice.seed!, hook);
-  }, 3 * 60_000);
+  }, 5 * 60_000);
 
   afterAll(async () => {
     await client.disconnect();
@@ -0,0 +1,97 @@
+// xrpl
+import { Client, Invoke, Transaction, Wallet } from "@transia/xrpl";
+import { HookExecution } from "@transia/xrpl/dist/npm/models/transactions/metadata";
+import { Faucet, TestUtils } from "./setup";
+import {
+  iHookParamEntry,
+  iHookParamName,
+  iHookParamValue,
+} from "@transia/hooks-toolkit";
+
+const HOOK_NAME = "hook_params";
+
+describe("hook_params.rs", () => {
+  let client: Client;
+  let alice: Wallet;
+  let bob: Wallet;
+
+  beforeAll(async () => {
+    const hook = await TestUtils.buildHook(HOOK_NAME);
+    // `greeting` is not set, so the default is used
+    hook.HookParameters = [
+      new iHookParamEntry(
+        new iHookParamName("min drops"),
+        // 1000 as big endian u64
+        new iHookParamValue("00000000000003E8", true),
+      ).toXrpl(),
+    ];
+    client = await TestUtils.connect();
+    // Fresh accounts from the faucet, because the faucet is rate limited
+    // this may take a while.
+    alice = await Faucet.waitAndGetNewWallet();
+    bob = await Faucet.waitAndGetNewWallet();
+
+    await TestUtils.setHook(client, alice.seed!, hook);
+  }, 5 * 60_000);
+
+  afterAll(async () => {
+    await client.disconnect();
+  }, 10_000);
+
+  it(
+    "loads the hook parameters into a struct",
+    async () => {
+      const tx: Invoke & Transaction = {
+        TransactionType: "Invoke",
+        Account: bob.classicAddress,
+        Destination: alice.classicAddress,
+      };
+      // Autofilling fee does not work with hooks yet
+      const { Fee, ...rest } = await client.autofill(tx);
+      const fee = await TestUtils.getTransactionFee(client, rest);
+      const txResponse = await TestUtils.submitAndWaitWithRetries(
+        client,
+        {
+          ...tx,
+          Fee: fee,
+        },
+        {
+          wallet: bob,
+          autofill: true,
+        },
+      );
+      if (!txResponse.result.meta) {
+        throw new Error("No meta in tx response");
+      }
+      if (typeof txResponse.result.meta === "string") {
+        throw new Error("Meta is string, not object");
+      }
+
+      const { meta } = txResponse.result;
+      if (!(meta.HookExecutions && meta.HookExecutions.length > 0)) {
+        throw new Error(`Hook execution data is empty`);
+      }
+
+      if (meta.HookExecutions.length > 1) {
+        throw new Error(`Hook execution happened more than once`);
+      }
+
+      if (txResponse.result.meta.TransactionResult !== "tesSUCCESS") {
+        console.error(JSON.stringify(txResponse, null, 2));
+
+        throw new Error(`Transaction failed`);
+      }
+
+      // safe type: we checked everything
+      const [hookExecution] = meta.HookExecutions as [HookExecution];
+
+      const { HookReturnString, HookReturnCode } = hookExecution.HookExecution;
+
+      expect(BigInt(HookReturnCode)).toEqual(1000n);
+      expect(Buffer.from(HookReturnString, `hex`).toString()).toBe(
+        `default greeting`,
+      );
+    },
+    3 * 60_000,
+  );
+});
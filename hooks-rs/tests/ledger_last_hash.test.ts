@@ -1,7 +1,7 @@
 // xrpl
 import { Client, Invoke, Transaction, Wallet } from "@transia/xrpl";
 import { HookExecution } from "@transia/xrpl/dist/npm/models/transactions/metadata";
-import { TestUtils } from "./setup";
+import { Faucet, TestUtils } from "./setup";
 
 const HOOK_NAME = "ledger_last_hash";
 
@@ -12,19 +12,14 @@ describe("ledger_last_hash.rs", () => {
 
   beforeAll(async () => {
     const hook = await TestUtils.buildHook(HOOK_NAME);
-    client = new Client("wss://xahau-test.net", {});
-    await client.connect();
-    client.networkID = await client.getNetworkID();
-    // Because Faucet only allows one account to be created every 60 seconds,
-    // we will use the following accounts for testing. Change the secrets when
-    // running out of funds.
-    // rPFvrHbUmfp8UARDwnPgnN1gQx2rc67To4
-    alice = Wallet.fromSecret(`ssLCm1tMprKeUetCXMza28UMduH7w`);
-    // r9UEnJRaB4ec5hcyRSi3PkmCtBontPFgCo
-    bob = Wallet.fromSecret(`shMSguE2R3MrBuXVwtFxkDQLohX9T`);
+    client = await TestUtils.connect();
+    // Fresh accounts from the faucet, because the faucet is rate limited
+    // this may take a while.
+    alice = await Faucet.waitAndGetNewWallet();
+    bob = await Faucet.waitAndGetNewWallet();
 
     await TestUtils.setHook(client, alice.seed!, hook);
-  }, 3 * 60_000);
+  }, 5 * 60_000);
 
   afterAll(async () => {
     await client.disconnect();
@@ -1,7 +1,7 @@
 // xrpl
 import { Client, Invoke, Transaction, Wallet } from "@transia/xrpl";
 import { ExecutionUtility } from "@transia/hooks-toolkit";
-import { TestUtils } from "./setup";
+import { Faucet, TestUtils } from "./setup";
 
 const HOOK_NAME = "ledger_last_time";
 
@@ -12,19 +12,14 @@ describe("ledger_last_time.rs", () => {
 
   beforeAll(async () => {
     const hook = await TestUtils.buildHook(HOOK_NAME);
-    client = new Client("wss://xahau-test.net", {});
-    await client.connect();
-    client.networkID = await client.getNetworkID();
-    // Because Faucet only allows one account to be created every 60 seconds,
-    // we will use the following accounts for testing. Change the secrets when
-    // running out of funds.
-    // rw3nfJSVBWBbJiVYRzq7ExFgU1VgD8tnq2
-    alice = Wallet.fromSecret(`snbePNZWBkkYFiaj3YGaeuK4TeGu6`);
-    // rha3hBEaA8LnTXNPz2UP6GT4qv4DfBCPSj
-    bob = Wallet.fromSecret(`snZd1z4ikJtPsrC7TYyse9vaVZe7r`);
+    client = await TestUtils.connect();
+    // Fresh accounts from the faucet, because the faucet is rate limited
+    // this may take a while.
+    alice = await Faucet.waitAndGetNewWallet();
+    bob = await Faucet.waitAndGetNewWallet();
 
     await TestUtils.setHook(client, alice.seed!, hook);
-  }, 3 * 60_000);
+  }, 5 * 60_000);
 
   afterAll(async () => {
     await client.disconnect();
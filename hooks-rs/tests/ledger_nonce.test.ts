@@ -1,7 +1,7 @@
 // xrpl
 import { Client, Invoke, Transaction, Wallet } from "@transia/xrpl";
 import { HookExecution } from "@transia/xrpl/dist/npm/models/transactions/metadata";
-import { TestUtils } from "./setup";
+import { Faucet, TestUtils } from "./setup";
 
 const HOOK_NAME = "ledger_nonce";
 
@@ -12,19 +12,14 @@ describe("ledger_nonce.rs", () => {
 
   beforeAll(async () => {
     const hook = await TestUtils.buildHook(HOOK_NAME);
-    client = new Client("wss://xahau-test.net", {});
-    await client.connect();
-    client.networkID = await client.getNetworkID();
-    // Because Faucet only allows one account to be created every 60 seconds,
-    // we will use the following accounts for testing. Change the secrets when
-    // running out of funds.
-    // rUXpwfqGpjyQ9VTNgxo5PgBHGMAdWGJSrN
-    alice = Wallet.fromSecret(`snjBctdUQQ2zz5kb3SuZV2jatA9p2`);
-    // rCYE1Z89m8tqL3Gib7Z7gVectWDNPcv2p
-    bob = Wallet.fromSecret(`sng6ogXY21JrPwfKwZUHxDpek2k59`);
+    client = await TestUtils.connect();
+    // Fresh accounts from the faucet, because the faucet is rate limited
+    // this may take a while.
+    alice = await Faucet.waitAndGetNewWallet();
+    bob = await Faucet.waitAndGetNewWallet();
 
     await TestUtils.setHook(client, alice.seed!, hook);
-  }, 3 * 60_000);
+  }, 5 * 60_000);
 
   afterAll(async () => {
     await client.disconnect();
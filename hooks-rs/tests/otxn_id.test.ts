@@ -1,6 +1,6 @@
 // xrpl
 import { Client, Invoke, Transaction, Wallet } from "@transia/xrpl";
-import { TestUtils } from "./setup";
+import { Faucet, TestUtils } from "./setup";
 import { HookExecution } from "@transia/xrpl/dist/npm/models/transactions/metadata";
 
 const HOOK_NAME = "otxn_id";
@@ -12,15 +12,13 @@ describe("otxn_id.rs", () => {
 
   beforeAll(async () => {
     const hook = await TestUtils.buildHook(HOOK_NAME);
-    client = new Client("wss://xahau-test.net", {});
-    await client.connect();
-    client.networkID = await client.getNetworkID();
-    // rfqgDEAw2G7fUcMnwpgsXmYnsySSV3CRtj
-    alice = Wallet.fromSecret(`sntQj32h4YEDAsxzVcQwtEv2gj8Ux`);
-    // rpFQbg22UZMC9AefFMqUZ55dPz7kCAngfR
-    bob = Wallet.fromSecret(`ssdc8xiiCBzvRExAJEh12xj39S6nk`);
+    client = await TestUtils.connect();
+    // Fresh accounts from the faucet, because the faucet is rate limited
+    // this may take a while.
+    alice = await Faucet.waitAndGetNewWallet();
+    bob = await Faucet.waitAndGetNewWallet();
     await TestUtils.setHook(client, alice.seed!, hook);
-  }, 3 * 60_000);
+  }, 5 * 60_000);
 
   afterAll(async () => {
     await client.disconnect();
@@ -1,6 +1,6 @@
 // xrpl
 import { Client, Invoke, Transaction, Wallet } from "@transia/xrpl";
-import { TestUtils } from "./setup";
+import { Faucet, TestUtils } from "./setup";
 import { HookExecution } from "@transia/xrpl/dist/npm/models/transactions/metadata";
 
 const HOOK_NAME = "otxn_type";
@@ -12,15 +12,13 @@ describe("otxn_type.rs", () => {
 
   beforeAll(async () => {
     const hook = await TestUtils.buildHook(HOOK_NAME);
-    client = new Client("wss://xahau-test.net", {});
-    await client.connect();
-    client.networkID = await client.getNetworkID();
-    // r9CavMR8ZzXA3ARmxyA6PymimGE571aGe4
-    alice = Wallet.fromSecret(`shpProjUCuJLev3Uk1xzhzZeLaMBA`);
-    // rn5r2h5JaJQTeK5be2ByhpNXimBzAsFSrX
-    bob = Wallet.fromSecret(`shc8YTU2AwW68nNutd6kzA73cMmPc`);
+    client = await TestUtils.connect();
+    // Fresh accounts from the faucet, because the faucet is rate limited
+    // this may take a while.
+    alice = await Faucet.waitAndGetNewWallet();
+    bob = await Faucet.waitAndGetNewWallet();
     await TestUtils.setHook(client, alice.seed!, hook);
-  }, 3 * 60_000);
+  }, 5 * 60_000);
 
   afterAll(async () => {
     await client.disconnect();
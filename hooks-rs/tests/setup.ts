@@ -2,6 +2,9 @@ import { createHookPayload, iHook } from "@transia/hooks-toolkit";
 import {
   Client,
   encode,
+  Invoke,
+  LedgerEntryRequest,
+  Payment,
   SetHook,
   SetHookFlags,
   Transaction,
@@ -9,6 +12,7 @@ import {
   Wallet,
   XrplError,
 } from "@transia/xrpl";
+import { HookExecution } from "@transia/xrpl/dist/npm/models/transactions/metadata";
 import { getFeeEstimateXrp } from "@transia/xrpl/dist/npm/sugar";
 import { exec as execWithCallback } from "child_process";
 import {
@@ -23,6 +27,25 @@ const exec = util.promisify(execWithCallback);
 const readFile = util.promisify(readFileWithCallback);
 const writeFile = util.promisify(writeFileWithCallback);
 
+/**
+ * The node the tests run against. Defaults to the public testnet.
+ *
+ * Set `XAHAU_NODE_URL` to run against another node, for example a local xahaud
+ * started with `xahaud --standalone`. Set `XAHAU_STANDALONE=1` as well in that case,
+ * so that ledgers are closed by the tests and accounts are funded from the genesis
+ * account instead of the faucet. Standalone nodes need to accept admin commands
+ * from the machine running the tests.
+ */
+export const NETWORK = {
+  url: process.env.XAHAU_NODE_URL ?? `wss://xahau-test.net`,
+  standalone: process.env.XAHAU_STANDALONE === `1`,
+};
+
+// The master account of a fresh ledger, holding all XAH
+const GENESIS_SEED = `snoPBrXtMeMyMHUVTgbuqAfg1SUTb`;
+// Enough to pay for the reserves and fees of any test
+const STANDALONE_FUNDING_DROPS = `10000000000`;
+
 interface FaucetSuccessResponse {
   account: {
     //     "xAddress": "XV5CC9AbwcsBYScgsjxWpe5VMooGZ8n8NMmaNuhUbqHPozq",
@@ -83,9 +106,174 @@ export class Faucet {
 
     throw new Error(`Could not get new account after ${tries} tries`);
   }
+
+  static async waitAndGetNewWallet(): Promise<Wallet> {
+    if (NETWORK.standalone) {
+      // There is no faucet on a standalone node
+      const wallet = Wallet.generate();
+      const client = await TestUtils.connect();
+      try {
+        await TestUtils.fundWallets(client, wallet);
+      } finally {
+        await client.disconnect();
+      }
+      return wallet;
+    }
+
+    const { account } = await Faucet.waitAndGetNewAccount();
+
+    return Wallet.fromSecret(account.secret);
+  }
 }
 
 export class TestUtils {
+  /**
+   * Connect to the node in {@link NETWORK}. On a standalone node, a ledger is closed
+   * every second until the client disconnects, so that transactions get validated.
+   */
+  static async connect(): Promise<Client> {
+    const client = new Client(NETWORK.url, {});
+    await client.connect();
+    client.networkID = await client.getNetworkID();
+
+    if (NETWORK.standalone) {
+      const ledgerCloser = setInterval(() => {
+        client
+          // eslint-disable-next-line @typescript-eslint/no-explicit-any -- admin command missing from the request types
+          .request({ command: `ledger_accept` } as any)
+          .catch(() => {});
+      }, 1000);
+      client.on(`disconnected`, () => clearInterval(ledgerCloser));
+    }
+
+    return client;
+  }
+
+  /**
+   * Fund wallets from the genesis account on a standalone node. Does nothing on other
+   * networks, where accounts come from the faucet.
+   */
+  static async fundWallets(client: Client, ...wallets: Wallet[]) {
+    if (!NETWORK.standalone) {
+      return;
+    }
+
+    const genesis = Wallet.fromSeed(GENESIS_SEED);
+    for (const wallet of wallets) {
+      const tx: Payment = {
+        TransactionType: `Payment`,
+        Account: genesis.classicAddress,
+        Destination: wallet.classicAddress,
+        Amount: STANDALONE_FUNDING_DROPS,
+      };
+      await TestUtils.submitAndWaitWithRetries(client, tx, {
+        wallet: genesis,
+        autofill: true,
+      });
+    }
+  }
+
+  /**
+   * Submit an Invoke transaction from `from` to `to`, which triggers the hook installed
+   * on `to`. Throws if the transaction does not succeed.
+   */
+  static async invoke(
+    client: Client,
+    from: Wallet,
+    to: Wallet,
+    fields: Partial<Invoke> = {},
+  ) {
+    const tx: Invoke & Transaction = {
+      TransactionType: `Invoke`,
+      Account: from.classicAddress,
+      Destination: to.classicAddress,
+      ...fields,
+    };
+    // Autofilling fee does not work with hooks yet
+    const { Fee, ...rest } = await client.autofill(tx);
+    const fee = await TestUtils.getTransactionFee(client, rest);
+    const txResponse = await TestUtils.submitAndWaitWithRetries(
+      client,
+      {
+        ...tx,
+        Fee: fee,
+      },
+      {
+        wallet: from,
+        autofill: true,
+      },
+    );
+    if (!txResponse.result.meta) {
+      throw new Error(`No meta in tx response`);
+    }
+    if (typeof txResponse.result.meta === `string`) {
+      throw new Error(`Meta is string, not object`);
+    }
+    if (txResponse.result.meta.TransactionResult !== `tesSUCCESS`) {
+      console.error(JSON.stringify(txResponse, null, 2));
+
+      throw new Error(`Transaction failed`);
+    }
+
+    return {
+      fee,
+      meta: txResponse.result.meta,
+      hookExecutions: (txResponse.result.meta.HookExecutions ??
+        []) as HookExecution[],
+    };
+  }
+
+  /**
+   * Get the balance of an account in drops, as of the last validated ledger
+   */
+  static async getBalance(client: Client, address: string): Promise<bigint> {
+    const {
+      result: {
+        account_data: { Balance },
+      },
+    } = await client.request({
+      command: `account_info`,
+      account: address,
+      ledger_index: `validated`,
+    });
+
+    return BigInt(Balance);
+  }
+
+  /**
+   * Get the hook state stored by `account` under `key` in `namespace`, both hex,
+   * or `undefined` if there is none
+   */
+  static async getHookState(
+    client: Client,
+    account: string,
+    namespace: string,
+    key: string,
+  ): Promise<string | undefined> {
+    try {
+      const response = await client.request({
+        command: `ledger_entry`,
+        hook_state: {
+          account,
+          key: key.padStart(64, `0`),
+          namespace_id: namespace,
+        },
+        ledger_index: `validated`,
+      } as LedgerEntryRequest);
+
+      return (response.result.node as { HookStateData: string }).HookStateData;
+    } catch (error) {
+      // eslint-disable-next-line @typescript-eslint/consistent-type-assertions,@typescript-eslint/no-unsafe-member-access -- same as in waitForMaybeNonExistentTx
+      const message = ((error as XrplError)?.data as { error: string })
+        ?.error as string;
+      if (message === `entryNotFound`) {
+        return undefined;
+      }
+
+      throw error;
+    }
+  }
+
   private static wasm2wat(
     wabt: Awaited<ReturnType<typeof initWabt>>,
     wasm: Uint8Array,
@@ -95,8 +283,34 @@ export class TestUtils {
     return mo.toText({ foldExprs: false, inlineExport: false });
   }
 
+  /**
+   * Builds the examples in release mode with the given cargo features, returning the
+   * directory of the wasm files. Builds with features go to their own target directory,
+   * so they don't replace the default build.
+   */
+  static async buildExamples(features: string[] = []): Promise<string> {
+    const targetDir = path.resolve(
+      __dirname,
+      `..`,
+      `target`,
+      ...(features.length > 0 ? [`features`, features.join(`-`)] : []),
+    );
+    const featureArgs =
+      features.length > 0 ? ` --features ${features.join(`,`)}` : ``;
+    await exec(
+      `cargo +nightly build --examples --release --target-dir ${targetDir}${featureArgs}`,
+    );
+
+    return path.resolve(
+      targetDir,
+      `wasm32-unknown-unknown`,
+      `release`,
+      `examples`,
+    );
+  }
+
   static async buildHook(hookName: string): Promise<iHook> {
-    await exec("cargo +nightly build --examples --release");
+    const wasmDir = await TestUtils.buildExamples();
     const hook = createHookPayload(
       0,
       // Add hook code after this
@@ -105,14 +319,6 @@ export class TestUtils {
       undefined,
       ["Invoke"],
     );
-    const wasmDir = path.resolve(
-      __dirname,
-      `..`,
-      `target`,
-      `wasm32-unknown-unknown`,
-      `release`,
-      `examples`,
-    );
     const debugDir = path.resolve(__dirname, `..`, `target`);
     const wasmInFile = path.resolve(wasmDir, `${hookName}.wasm`);
     const wasmOutFlattened = path.resolve(
@@ -1,6 +1,6 @@
 // xrpl
 import { Client, Invoke, Transaction, Wallet } from "@transia/xrpl";
-import { TestUtils } from "./setup";
+import { Faucet, TestUtils } from "./setup";
 import { HookExecution } from "@transia/xrpl/dist/npm/models/transactions/metadata";
 import { padHexString, StateUtility } from "@transia/hooks-toolkit";
 
@@ -13,15 +13,13 @@ describe("state.rs", () => {
 
   beforeAll(async () => {
     const hook = await TestUtils.buildHook(HOOK_NAME);
-    client = new Client("wss://xahau-test.net", {});
-    await client.connect();
-    client.networkID = await client.getNetworkID();
-    // ra1ETDhsmHLUucKAAshR5iWkPhj2JkSXWf
-    alice = Wallet.fromSecret(`ssxrDUgoE89Zo57sSjDwHp5Tg5UFa`);
-    // r4i6zUa46fh4WhgkmDA9raTHMy5raCtswW
-    bob = Wallet.fromSecret(`shsWx8eafgdNacB3MbBgehczURBqH`);
+    client = await TestUtils.connect();
+    // Fresh accounts from the faucet, because the faucet is rate limited
+    // this may take a while.
+    alice = await Faucet.waitAndGetNewWallet();
+    bob = await Faucet.waitAndGetNewWallet();
     await TestUtils.setHook(client, alice.seed!, hook);
-  }, 3 * 60_000);
+  }, 5 * 60_000);
 
   afterAll(async () => {
     await client.disconnect();
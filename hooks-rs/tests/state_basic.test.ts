@@ -6,7 +6,7 @@ import {
   Transaction,
   Wallet,
 } from "@transia/xrpl";
-import { TestUtils } from "./setup";
+import { Faucet, TestUtils } from "./setup";
 import { HookExecution } from "@transia/xrpl/dist/npm/models/transactions/metadata";
 import { iHook, padHexString, StateUtility } from "@transia/hooks-toolkit";
 
@@ -20,16 +20,15 @@ describe("state_basic.rs", () => {
 
   beforeAll(async () => {
     hook = await TestUtils.buildHook(HOOK_NAME);
-    client = new Client("wss://xahau-test.net", {});
-    await client.connect();
-    client.networkID = await client.getNetworkID();
-  }, 3 * 60_000);
+    client = await TestUtils.connect();
+    // Fresh accounts from the faucet, because the faucet is rate limited
+    // this may take a while.
+    alice = await Faucet.waitAndGetNewWallet();
+    bob = await Faucet.waitAndGetNewWallet();
+  }, 5 * 60_000);
 
   beforeEach(async () => {
-    // rfv8LQPv4a7DbmvjGBqW2TSEt864wJ5Vgf
-    alice = Wallet.fromSecret(`snoUzNNV2m3K9dW7oJEebmRSkHsHX`);
-    // r4gLsVufLvPsEUGkAPEeateoLLmeT7esPn
-    bob = Wallet.fromSecret(`snbFSGYKuzrTjLtwmQtzd32PtcmJ4`);
+    // Deletes the state of the previous test along with the hook
     await TestUtils.setHook(client, alice.seed!, hook);
   }, 3 * 60_000);
 
@@ -6,7 +6,7 @@ import {
   Transaction,
   Wallet,
 } from "@transia/xrpl";
-import { TestUtils } from "./setup";
+import { Faucet, TestUtils } from "./setup";
 import { HookExecution } from "@transia/xrpl/dist/npm/models/transactions/metadata";
 
 const HOOK_NAME = "util_accid";
@@ -20,15 +20,13 @@ describe.skip("util_accid.rs", () => {
 
   beforeAll(async () => {
     const hook = await TestUtils.buildHook(HOOK_NAME);
-    client = new Client("wss://xahau-test.net", {});
-    await client.connect();
-    client.networkID = await client.getNetworkID();
-    // rUVWhZnU2AYWYXzHpCUcSVtcuuNXkuDD1X
-    alice = Wallet.fromSecret(`snudyVMLzKNzXZ9HbzdiNFzfDKP2F`);
-    // r4tryuYjz7ZxtJFb2ELWGTNnEFsT1ZxaJp
-    bob = Wallet.fromSecret(`snmxzsV6WCiZ1RCg566sKfMkzPcwB`);
+    client = await TestUtils.connect();
+    // Fresh accounts from the faucet, because the faucet is rate limited
+    // this may take a while.
+    alice = await Faucet.waitAndGetNewWallet();
+    bob = await Faucet.waitAndGetNewWallet();
     await TestUtils.setHook(client, alice.seed!, hook);
-  }, 3 * 60_000);
+  }, 5 * 60_000);
 
   afterAll(async () => {
     await client.disconnect();
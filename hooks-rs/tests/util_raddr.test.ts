@@ -1,6 +1,6 @@
 // xrpl
 import { Client, Invoke, Transaction, Wallet } from "@transia/xrpl";
-import { TestUtils } from "./setup";
+import { Faucet, TestUtils } from "./setup";
 import { HookExecution } from "@transia/xrpl/dist/npm/models/transactions/metadata";
 
 const HOOK_NAME = "util_raddr";
@@ -12,16 +12,14 @@ describe("util_raddr.rs", () => {
 
   beforeAll(async () => {
     const hook = await TestUtils.buildHook(HOOK_NAME);
-    client = new Client("wss://xahau-test.net", {});
-    await client.connect();
-    client.networkID = await client.getNetworkID();
+    client = await TestUtils.connect();
 
-    // rHvPxERqTjFTeydCkYVBjEdURf4MeW7rWN
-    alice = Wallet.fromSecret(`shqPWeDjqgSp87wxidV6nYR5qzSNt`);
-    // rh41GcuurC14yBr71Y48nMiriCz7oFsKM9
-    bob = Wallet.fromSecret(`ssXniQYTWZWiNo2kHpetU2GcknBiQ`);
+    // Fresh accounts from the faucet, because the faucet is rate limited
+    // this may take a while.
+    alice = await Faucet.waitAndGetNewWallet();
+    bob = await Faucet.waitAndGetNewWallet();
     await TestUtils.setHook(client, alice.seed!, hook);
-  }, 3 * 60_000);
+  }, 5 * 60_000);
 
   afterAll(async () => {
     await client.disconnect();
@@ -1,7 +1,6 @@
 // xrpl
-import { Client, Invoke, Transaction, Wallet } from "@transia/xrpl";
-import { TestUtils } from "./setup";
-import { HookExecution } from "@transia/xrpl/dist/npm/models/transactions/metadata";
+import { Client, Wallet } from "@transia/xrpl";
+import { Faucet, TestUtils } from "./setup";
 
 const HOOK_NAME = "xrp_payment_txn";
 
@@ -12,15 +11,13 @@ describe("xrp_payment_txn.rs", () => {
 
   beforeAll(async () => {
     const hook = await TestUtils.buildHook(HOOK_NAME);
-    client = new Client("wss://xahau-test.net", {});
-    await client.connect();
-    client.networkID = await client.getNetworkID();
-    // rDvQ6RxKjFCVPXWJ63MnYJwV2zDj5vg5Vj
-    alice = Wallet.fromSecret(`shPxyMAhKBaUnhLNYgj9Xmh7sud3X`);
-    // rEjrZWyogtCxtEhf2CVizXm8DsnYqNP1Nw
-    bob = Wallet.fromSecret(`snbMxLUTVDVkRArAJMxosNhdsJhJn`);
+    client = await TestUtils.connect();
+    // Fresh accounts from the faucet, because the faucet is rate limited
+    // this may take a while.
+    alice = await Faucet.waitAndGetNewWallet();
+    bob = await Faucet.waitAndGetNewWallet();
     await TestUtils.setHook(client, alice.seed!, hook);
-  }, 3 * 60_000);
+  }, 5 * 60_000);
 
   afterAll(async () => {
     await client.disconnect();
@@ -29,59 +26,19 @@ describe("xrp_payment_txn.rs", () => {
   it(
     `alice pays 1000 drops of XRP to bob`,
     async () => {
-      const {
-        result: {
-          account_data: { Balance: bobBalanceBefore },
-        },
-      } = await client.request({
-        command: "account_info",
-        account: bob.classicAddress,
-        ledger_index: "validated",
-      });
-      const {
-        result: {
-          account_data: { Balance: aliceBalanceBefore },
-        },
-      } = await client.request({
-        command: "account_info",
-        account: alice.classicAddress,
-        ledger_index: "validated",
-      });
-      const tx: Invoke & Transaction = {
-        TransactionType: "Invoke",
-        Account: bob.classicAddress,
-        Destination: alice.classicAddress,
-      };
-      // Autofilling fee does not work with hooks yet
-      const { Fee, ...rest } = await client.autofill(tx);
-      const fee = await TestUtils.getTransactionFee(client, rest);
-      const txResponse = await TestUtils.submitAndWaitWithRetries(
+      const bobBalanceBefore = await TestUtils.getBalance(
         client,
-        {
-          ...tx,
-          Fee: fee,
-        },
-        {
-          wallet: bob,
-          autofill: true,
-        },
+        bob.classicAddress,
+      );
+      const aliceBalanceBefore = await TestUtils.getBalance(
+        client,
+        alice.classicAddress,
       );
-      if (!txResponse.result.meta) {
-        throw new Error("No meta in tx response");
-      }
-      if (typeof txResponse.result.meta === "string") {
-        throw new Error("Meta is string, not object");
-      }
-
-      if (txResponse.result.meta.TransactionResult !== "tesSUCCESS") {
-        console.error(JSON.stringify(txResponse, null, 2));
-
-        throw new Error(`Transaction failed`);
-      }
 
-      const [hookExecution] = txResponse.result.meta.HookExecutions as [
-        HookExecution,
-      ];
+      const {
+        fee,
+        hookExecutions: [hookExecution],
+      } = await TestUtils.invoke(client, bob, alice);
 
       const { HookReturnString, HookReturnCode } = hookExecution.HookExecution;
 
@@ -90,40 +47,39 @@ describe("xrp_payment_txn.rs", () => {
       ).toBe(0n);
       expect(HookReturnString).toMatch(/^[A-F0-9]{64}$/);
 
-      await TestUtils.waitForMaybeNonExistentTx(client, HookReturnString);
+      // The emitted payment has to be applied to the ledger, not just emitted
+      expect(
+        await TestUtils.waitForMaybeNonExistentTx(client, HookReturnString),
+      ).toBe(true);
       const emittedTx = await client.request({
         command: "tx",
         transaction: HookReturnString,
       });
+      expect(emittedTx.result.TransactionType).toBe("Payment");
+      expect(emittedTx.result.Account).toBe(alice.classicAddress);
+      expect((emittedTx.result as { Destination?: string }).Destination).toBe(
+        bob.classicAddress,
+      );
+      expect((emittedTx.result as { Amount?: string }).Amount).toBe("1000");
       const emittedTxFee = emittedTx.result.Fee!;
       expect(emittedTxFee).toBeDefined();
 
-      const {
-        result: {
-          account_data: { Balance: bobBalanceAfter },
-        },
-      } = await client.request({
-        command: "account_info",
-        account: bob.classicAddress,
-        ledger_index: "validated",
-      });
-      const {
-        result: {
-          account_data: { Balance: aliceBalanceAfter },
-        },
-      } = await client.request({
-        command: "account_info",
-        account: alice.classicAddress,
-        ledger_index: "validated",
-      });
+      const bobBalanceAfter = await TestUtils.getBalance(
+        client,
+        bob.classicAddress,
+      );
+      const aliceBalanceAfter = await TestUtils.getBalance(
+        client,
+        alice.classicAddress,
+      );
 
-      const dropsSentToBob = 1000;
-      expect(Number(bobBalanceAfter) - dropsSentToBob).toBe(
-        Number(bobBalanceBefore) - Number(fee),
+      const dropsSentToBob = 1000n;
+      expect(bobBalanceAfter).toBe(
+        bobBalanceBefore - BigInt(fee) + dropsSentToBob,
+      );
+      expect(aliceBalanceAfter).toBe(
+        aliceBalanceBefore - dropsSentToBob - BigInt(emittedTxFee),
       );
-      expect(
-        Number(aliceBalanceAfter) + dropsSentToBob + Number(emittedTxFee),
-      ).toBeCloseTo(Number(aliceBalanceBefore));
     },
     3 * 60_000,
   );